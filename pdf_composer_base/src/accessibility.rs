@@ -0,0 +1,174 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId};
+use regex::Regex;
+
+use crate::catalog::set_viewer_preference;
+use crate::utils::encode_pdf_text_string;
+use pdf_composer_definitions::consts::CROSS_MARK;
+
+/// This function marks the document as a tagged PDF in the catalog.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the document has no catalog.
+///
+/// # Remarks
+///
+/// Sets `/MarkInfo << /Marked true >>` and the `/DisplayDocTitle true` viewer preference
+/// (both required by PDF/UA). If Chromium did not produce a `/StructTreeRoot` (older Chromium
+/// versions ignore the tagging flag), a warning is printed, as the document is not really tagged.
+pub fn mark_as_tagged(doc: &mut Document) -> Result<(), lopdf::Error> {
+    let catalog = doc.catalog_mut()?;
+    let mut mark_info = Dictionary::new();
+    mark_info.set("Marked", LopdfObject::Boolean(true));
+    catalog.set("MarkInfo", mark_info);
+    let has_structure_tree = catalog.has(b"StructTreeRoot");
+
+    set_viewer_preference(doc, "DisplayDocTitle", LopdfObject::Boolean(true))?;
+
+    if !has_structure_tree {
+        eprintln!(
+            "{}{}",
+            CROSS_MARK.yellow(),
+            "Chromium did not generate a structure tree; the PDF is not fully tagged.".yellow()
+        );
+    }
+    Ok(())
+}
+
+/// This function extracts the alt text of every `<img>` element in the generated HTML, in document order.
+///
+/// # Arguments
+///
+/// * `html` - A string slice (`&str`) containing the generated HTML.
+///
+/// # Returns
+///
+/// A `Vec<String>` with one entry per image. Images without an `alt` attribute get an empty string.
+pub fn image_alt_texts(html: &str) -> Vec<String> {
+    let image_regex = Regex::new(r"(?i)<img\b[^>]*>").unwrap();
+    let alt_regex = Regex::new(r#"(?i)\balt\s*=\s*"([^"]*)""#).unwrap();
+
+    image_regex
+        .find_iter(html)
+        .map(|image| {
+            alt_regex
+                .captures(image.as_str())
+                .map(|captures| unescape_html_attribute(&captures[1]))
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// This function copies image alt text into the `/Alt` entries of the `/Figure` structure elements.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `alt_texts` - The alt text of each image, in document order (see `image_alt_texts`).
+///
+/// # Remarks
+///
+/// Figures are matched to images by their order in the structure tree. Figures that already have
+/// an `/Alt` entry are left alone. If the number of figures does not match the number of images
+/// (for example, Chromium tagged an SVG as a figure) nothing is changed, rather than risk
+/// describing the wrong figure.
+pub fn fill_figure_alt_text(doc: &mut Document, alt_texts: &[String]) {
+    let structure_root = match doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"StructTreeRoot").ok())
+        .and_then(|root| root.as_reference().ok())
+    {
+        Some(root_id) => root_id,
+        None => return,
+    };
+
+    let mut figure_ids: Vec<ObjectId> = Vec::new();
+    collect_figures(
+        doc,
+        &LopdfObject::Reference(structure_root),
+        &mut figure_ids,
+        0,
+    );
+
+    if figure_ids.len() != alt_texts.len() {
+        if !alt_texts.is_empty() {
+            eprintln!(
+                "{}{}",
+                CROSS_MARK.yellow(),
+                format!(
+                    "Found {} tagged figures for {} images; image alt text not copied.",
+                    figure_ids.len(),
+                    alt_texts.len()
+                )
+                .yellow()
+            );
+        }
+        return;
+    }
+
+    for (figure_id, alt_text) in figure_ids.iter().zip(alt_texts) {
+        if alt_text.is_empty() {
+            continue;
+        }
+        if let Ok(figure) = doc.get_dictionary_mut(*figure_id) {
+            let has_alt = matches!(figure.get(b"Alt"), Ok(LopdfObject::String(bytes, _)) if !bytes.is_empty());
+            if !has_alt {
+                figure.set("Alt", encode_pdf_text_string(alt_text));
+            }
+        }
+    }
+}
+
+/// Recursively walks the structure tree (depth first, so in document order), collecting the
+/// object IDs of `/Figure` structure elements.
+fn collect_figures(doc: &Document, node: &LopdfObject, figure_ids: &mut Vec<ObjectId>, depth: u32) {
+    // Guard against malformed (cyclic) structure trees
+    if depth > 256 {
+        return;
+    }
+
+    match node {
+        LopdfObject::Reference(id) => {
+            if let Ok(LopdfObject::Dictionary(dictionary)) = doc.get_object(*id) {
+                if matches!(dictionary.get(b"S"), Ok(LopdfObject::Name(name)) if name == b"Figure")
+                {
+                    figure_ids.push(*id);
+                }
+                if let Ok(kids) = dictionary.get(b"K") {
+                    collect_figures(doc, kids, figure_ids, depth + 1);
+                }
+            }
+        }
+        LopdfObject::Array(kids) => {
+            for kid in kids {
+                collect_figures(doc, kid, figure_ids, depth + 1);
+            }
+        }
+        LopdfObject::Dictionary(dictionary) => {
+            if let Ok(kids) = dictionary.get(b"K") {
+                collect_figures(doc, kids, figure_ids, depth + 1);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Reverses the escaping the markdown crate applies to attribute values.
+fn unescape_html_attribute(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
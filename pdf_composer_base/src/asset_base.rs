@@ -0,0 +1,161 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+use std::env;
+use std::path::Path;
+
+use crate::html_document::inline_local_images;
+use crate::utils::escape_html;
+use pdf_composer_definitions::asset_base::PathOrUrl;
+
+/// This function resolves the relative links and images of a document against its asset base,
+/// before it is printed (the page Chromium prints is a `data:` URL, so nothing relative to it
+/// resolves).
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+/// * `source_file` - The path of the source document.
+/// * `asset_base` - The asset base (`None` for the directory of the source document).
+///
+/// # Returns
+///
+/// A `String` with the relative links and images resolved.
+///
+/// # Remarks
+///
+/// * With a directory, images are embedded from it (as data URLs) and links point to its files
+///   (`file://` URLs).
+/// * With a URL, images and links are made absolute against it (a `<base>` element would also
+///   turn the `#heading` links into links to the URL).
+///
+/// Links to markdown files are left for `rewrite_document_links`, and links with a scheme
+/// (`https:`, `mailto:` etc), `#fragment` links and protocol-relative (`//host`) URLs are left
+/// alone.
+pub fn resolve_assets(html: &str, source_file: &Path, asset_base: Option<&PathOrUrl>) -> String {
+    match (asset_directory(source_file, asset_base), asset_base) {
+        (Some(directory), _) => resolve_against_directory(html, directory),
+        (None, Some(PathOrUrl::Url(url))) => {
+            let html = replace_relative_urls(html, "img", "src", |src| join_url(url, src));
+            replace_relative_urls(&html, "a", "href", |href| join_url(url, href))
+        }
+        // Only a URL asset base has no directory
+        (None, _) => html.to_string(),
+    }
+}
+
+/// This function returns the directory the relative links and images of a document are resolved
+/// against (see `resolve_assets`).
+///
+/// # Arguments
+///
+/// * `source_file` - The path of the source document.
+/// * `asset_base` - The asset base (`None` for the directory of the source document).
+///
+/// # Returns
+///
+/// * `Some(&Path)` with the asset base directory, or the directory of the source document.
+/// * `None` if the asset base is a URL.
+pub fn asset_directory<'a>(
+    source_file: &'a Path,
+    asset_base: Option<&'a PathOrUrl>,
+) -> Option<&'a Path> {
+    match asset_base {
+        None => Some(source_file.parent().unwrap_or(Path::new(""))),
+        Some(PathOrUrl::Path(directory)) => Some(directory),
+        Some(PathOrUrl::Url(_)) => None,
+    }
+}
+
+/// Returns true if a link path (without `?query` or `#fragment`) is a markdown file, whose links
+/// are rewritten to the generated documents rather than resolved against the asset base.
+pub fn is_markdown_link(path: &str) -> bool {
+    let markdown_regex = Regex::new(r"(?i)\.(?:md|markdown)$").unwrap();
+    markdown_regex.is_match(path)
+}
+
+/// Embeds the images of a document from a directory, and points its links to the files in it.
+fn resolve_against_directory(html: &str, directory: &Path) -> String {
+    let html = inline_local_images(html, directory);
+    replace_relative_urls(&html, "a", "href", |href| file_url(directory, href))
+}
+
+/// Replaces the relative URLs in an attribute of the elements of a tag.
+fn replace_relative_urls(
+    html: &str,
+    tag: &str,
+    attribute: &str,
+    mut resolve: impl FnMut(&str) -> String,
+) -> String {
+    let url_regex =
+        Regex::new(&format!(r#"(<{}\b[^>]*?\s{}=")([^"]*)(")"#, tag, attribute)).unwrap();
+    let scheme_regex = Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:").unwrap();
+
+    url_regex
+        .replace_all(html, |captures: &Captures| {
+            let url = captures[2].replace("&amp;", "&");
+            let path = url.split(['?', '#']).next().unwrap_or_default();
+            if path.is_empty()
+                || url.starts_with("//")
+                || scheme_regex.is_match(&url)
+                || is_markdown_link(path)
+            {
+                return captures[0].to_string();
+            }
+            format!(
+                "{}{}{}",
+                &captures[1],
+                escape_html(&resolve(&url)),
+                &captures[3]
+            )
+        })
+        .to_string()
+}
+
+/// Joins a relative URL to a base URL, which is taken to be a directory. URLs starting with `/`
+/// are relative to the host.
+fn join_url(base: &str, relative: &str) -> String {
+    if let Some(root_relative) = relative.strip_prefix('/') {
+        let origin_end = base
+            .find("://")
+            .and_then(|scheme_end| {
+                base[scheme_end + 3..]
+                    .find('/')
+                    .map(|host_end| scheme_end + 3 + host_end)
+            })
+            .unwrap_or(base.len());
+        return format!("{}/{}", &base[..origin_end], root_relative);
+    }
+    format!(
+        "{}/{}",
+        base.trim_end_matches('/'),
+        relative.trim_start_matches("./")
+    )
+}
+
+/// Returns the `file://` URL of a file relative to a directory (keeping any `?query` or `#fragment`).
+fn file_url(directory: &Path, relative: &str) -> String {
+    let split_at = relative.find(['?', '#']).unwrap_or(relative.len());
+    let (path, suffix) = relative.split_at(split_at);
+
+    let file = directory.join(url_escape::decode(path).as_ref());
+    let file = file.canonicalize().unwrap_or_else(|_| {
+        env::current_dir()
+            .map(|current_directory| current_directory.join(&file))
+            .unwrap_or(file)
+    });
+    let file = file.display().to_string().replace('\\', "/");
+    // Windows paths (`C:/docs`) need a third slash, and extended-length ones lose their prefix
+    let file = match file.strip_prefix("//?/UNC/") {
+        Some(share_path) => format!("//{}", share_path),
+        None => file.trim_start_matches("//?/").to_string(),
+    };
+    let separator = if file.starts_with('/') { "" } else { "/" };
+    format!(
+        "file://{}{}{}",
+        separator,
+        url_escape::encode_path(&file),
+        suffix
+    )
+}
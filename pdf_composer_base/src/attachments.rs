@@ -0,0 +1,320 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId, Stream};
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::utils::{
+    decode_pdf_text_string, encode_pdf_text_string, extract_to_end_string, media_type,
+};
+use pdf_composer_definitions::attachments::FileAttachment;
+use pdf_composer_definitions::pdf_version::PDFVersion;
+
+/// Struct holding a file to embed in a PDF.
+#[derive(Clone, Debug)]
+pub struct EmbeddedFile {
+    /// The file name shown in the attachments panel of PDF readers.
+    pub name: String,
+    /// The content of the file.
+    pub content: Vec<u8>,
+    /// The media type of the file (such as `text/markdown`).
+    pub mime_type: String,
+    /// The description shown next to the file name (`None` for no description).
+    pub description: Option<String>,
+    /// How the file relates to the document (`Source`, `Data` or `Supplement`), for PDF 2.0 and PDF/A-3 readers.
+    pub relationship: &'static str,
+}
+
+/// This function gathers the files of the source document to embed in its PDF: the markdown file
+/// as it was read, and the front matter with the placeholders resolved.
+///
+/// # Arguments
+///
+/// * `source_file` - The path of the source (markdown) document.
+/// * `yaml_btreemap` - The YAML Front Matter of the document, after the variables were expanded.
+/// * `attach_source` - Whether the markdown file is embedded.
+/// * `attach_front_matter` - Whether the front matter is embedded (as `<name>.yaml`).
+///
+/// # Returns
+///
+/// * `Ok(Vec<EmbeddedFile>)` with the files to embed (empty if neither is attached).
+/// * `Err(e)` if the source document could not be read.
+pub fn source_attachments(
+    source_file: &str,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    attach_source: bool,
+    attach_front_matter: bool,
+) -> Result<Vec<EmbeddedFile>, Box<dyn std::error::Error>> {
+    let file_name = extract_to_end_string(source_file).unwrap_or(source_file);
+    let mut attachments: Vec<EmbeddedFile> = Vec::new();
+
+    if attach_source {
+        attachments.push(EmbeddedFile {
+            name: file_name.to_string(),
+            content: fs::read(Path::new(source_file))?,
+            mime_type: "text/markdown".to_string(),
+            description: Some("Source markdown".to_string()),
+            relationship: "Source",
+        });
+    }
+
+    if attach_front_matter {
+        let stem = Path::new(file_name)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        attachments.push(EmbeddedFile {
+            name: format!("{}.yaml", stem),
+            content: serde_yml::to_string(yaml_btreemap)?.into_bytes(),
+            mime_type: "application/yaml".to_string(),
+            description: Some("Resolved front matter".to_string()),
+            relationship: "Data",
+        });
+    }
+
+    Ok(attachments)
+}
+
+/// This function reads a file to embed in a PDF as an attachment.
+///
+/// # Arguments
+///
+/// * `attachment` - The `FileAttachment` with the path and description of the file.
+///
+/// # Returns
+///
+/// * `Ok(EmbeddedFile)` with the file, named after its file name.
+/// * `Err(e)` if the file could not be read.
+pub fn file_attachment(attachment: &FileAttachment) -> io::Result<EmbeddedFile> {
+    let name = attachment
+        .path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    Ok(EmbeddedFile {
+        name,
+        content: fs::read(&attachment.path)?,
+        // The media type without parameters (such as `; charset=utf-8`)
+        mime_type: media_type(&attachment.path)
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        description: attachment.description.clone(),
+        relationship: "Supplement",
+    })
+}
+
+/// This function embeds files in a PDF document, adding them to the `EmbeddedFiles` name tree of
+/// the catalog (where PDF readers list attachments). Files already embedded are kept.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `files` - The `EmbeddedFile`s to embed.
+///
+/// # Returns
+///
+/// * `Ok(())` if the files were embedded.
+/// * `Err(e)` if the document has no catalog.
+///
+/// # Remarks
+///
+/// A file with the same name as one already embedded gets a number after its name
+/// (`notes (2).md`), so no file is lost. The relationship of each file (`AFRelationship`) is only
+/// written to PDF 2.0 documents, the first version with associated files, so set the version first.
+pub fn embed_files(doc: &mut Document, files: &[EmbeddedFile]) -> Result<(), lopdf::Error> {
+    if files.is_empty() {
+        return Ok(());
+    }
+    let writes_associated_files = supports_associated_files(doc);
+
+    let mut embedded = embedded_files(doc);
+    for file in files {
+        let name = unique_name(&embedded, &file.name);
+
+        let mut params = Dictionary::new();
+        params.set("Size", file.content.len() as i64);
+        let mut stream_dictionary = Dictionary::new();
+        stream_dictionary.set("Type", "EmbeddedFile");
+        stream_dictionary.set(
+            "Subtype",
+            LopdfObject::Name(file.mime_type.as_bytes().to_vec()),
+        );
+        stream_dictionary.set("Params", params);
+        let stream_id = doc.add_object(Stream::new(stream_dictionary, file.content.clone()));
+
+        let mut file_streams = Dictionary::new();
+        file_streams.set("F", stream_id);
+        file_streams.set("UF", stream_id);
+        let mut file_specification = Dictionary::new();
+        file_specification.set("Type", "Filespec");
+        file_specification.set("F", encode_pdf_text_string(&name));
+        file_specification.set("UF", encode_pdf_text_string(&name));
+        if let Some(description) = &file.description {
+            file_specification.set("Desc", encode_pdf_text_string(description));
+        }
+        file_specification.set("EF", file_streams);
+        if writes_associated_files {
+            file_specification.set("AFRelationship", file.relationship);
+        }
+        let file_specification_id = doc.add_object(file_specification);
+
+        embedded.insert(name, file_specification_id);
+    }
+
+    set_embedded_files(doc, &embedded)
+}
+
+/// This function returns the files embedded in a PDF document, from the `EmbeddedFiles` name tree.
+///
+/// # Arguments
+///
+/// * `doc` - A reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// A `BTreeMap<String, ObjectId>` of file name → file specification (empty if there are none).
+pub fn embedded_files(doc: &Document) -> BTreeMap<String, ObjectId> {
+    let mut files: BTreeMap<String, ObjectId> = BTreeMap::new();
+    if let Some(tree) = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Names").ok())
+        .and_then(|names| doc.dereference(names).ok())
+        .and_then(|(_id, names)| names.as_dict().ok())
+        .and_then(|names| names.get(b"EmbeddedFiles").ok())
+    {
+        collect_embedded_files(doc, tree, &mut files, 0);
+    }
+    files
+}
+
+/// This function replaces the files embedded in a PDF document, writing the `EmbeddedFiles` name
+/// tree and, for PDF 2.0 documents, the associated files (`AF`) of the catalog.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `files` - The file names and their file specifications.
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the document has no catalog.
+pub fn set_embedded_files(
+    doc: &mut Document,
+    files: &BTreeMap<String, ObjectId>,
+) -> Result<(), lopdf::Error> {
+    let mut names = match doc.catalog()?.get(b"Names") {
+        Ok(names) => doc.dereference(names)?.1.as_dict()?.clone(),
+        Err(_) => Dictionary::new(),
+    };
+
+    if files.is_empty() {
+        names.remove(b"EmbeddedFiles");
+        let names_id = (!names.is_empty()).then(|| doc.add_object(names));
+        let catalog = doc.catalog_mut()?;
+        catalog.remove(b"AF");
+        match names_id {
+            Some(names_id) => catalog.set("Names", names_id),
+            None => {
+                catalog.remove(b"Names");
+            }
+        }
+        return Ok(());
+    }
+
+    // The keys of a name tree are sorted by their bytes
+    let mut entries: Vec<(LopdfObject, ObjectId)> = files
+        .iter()
+        .map(|(name, file_specification_id)| (encode_pdf_text_string(name), *file_specification_id))
+        .collect();
+    entries.sort_by(|(first, _), (second, _)| first.as_str().ok().cmp(&second.as_str().ok()));
+
+    let mut name_tree_entries: Vec<LopdfObject> = Vec::new();
+    for (name, file_specification_id) in &entries {
+        name_tree_entries.push(name.clone());
+        name_tree_entries.push(LopdfObject::Reference(*file_specification_id));
+    }
+    let mut name_tree = Dictionary::new();
+    name_tree.set("Names", name_tree_entries);
+    let name_tree_id = doc.add_object(name_tree);
+    names.set("EmbeddedFiles", name_tree_id);
+
+    let associated_files: Vec<LopdfObject> = entries
+        .iter()
+        .map(|(_name, file_specification_id)| LopdfObject::Reference(*file_specification_id))
+        .collect();
+    let names_id = doc.add_object(names);
+    let writes_associated_files = supports_associated_files(doc);
+    let catalog = doc.catalog_mut()?;
+    catalog.set("Names", names_id);
+    if writes_associated_files {
+        catalog.set("AF", associated_files);
+    } else {
+        catalog.remove(b"AF");
+    }
+    Ok(())
+}
+
+/// Returns whether the version of the document allows associated files (`AF`).
+fn supports_associated_files(doc: &Document) -> bool {
+    doc.version
+        .parse::<PDFVersion>()
+        .is_ok_and(|pdf_version| pdf_version.supports_associated_files())
+}
+
+/// Returns the name, with a number after it (`notes (2).md`) if a file already has it.
+pub fn unique_name(files: &BTreeMap<String, ObjectId>, name: &str) -> String {
+    if !files.contains_key(name) {
+        return name.to_string();
+    }
+    let (stem, extension) = match name.rfind('.') {
+        Some(dot) if dot > 0 => (&name[..dot], &name[dot..]),
+        _ => (name, ""),
+    };
+    (2..)
+        .map(|number| format!("{} ({}){}", stem, number, extension))
+        .find(|numbered_name| !files.contains_key(numbered_name))
+        .unwrap_or_default()
+}
+
+/// Collects every file specification of an `EmbeddedFiles` name tree (names already collected are kept).
+fn collect_embedded_files(
+    doc: &Document,
+    node: &LopdfObject,
+    files: &mut BTreeMap<String, ObjectId>,
+    depth: u8,
+) {
+    if depth > 32 {
+        return;
+    }
+    let Some(node) = doc
+        .dereference(node)
+        .ok()
+        .and_then(|(_id, node)| node.as_dict().ok())
+    else {
+        return;
+    };
+    if let Ok(names) = node.get(b"Names").and_then(LopdfObject::as_array) {
+        for pair in names.chunks_exact(2) {
+            if let (Ok(name), Ok(file_specification_id)) =
+                (pair[0].as_str(), pair[1].as_reference())
+            {
+                files
+                    .entry(decode_pdf_text_string(name))
+                    .or_insert(file_specification_id);
+            }
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(LopdfObject::as_array) {
+        for kid in kids {
+            collect_embedded_files(doc, kid, files, depth + 1);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use barcoders::sym::code128::Code128;
+use barcoders::sym::ean13::EAN13;
+
+use crate::shortcodes::{ShortcodeArguments, ShortcodeOutput};
+use crate::utils::escape_html;
+
+/// The blank space (in bar widths) scanners need either side of a barcode.
+const QUIET_ZONE: usize = 10;
+/// The height of the bars, in bar widths.
+const BAR_HEIGHT: usize = 50;
+/// The height of the human readable text under the bars, in bar widths.
+const TEXT_HEIGHT: usize = 12;
+/// The height of a barcode shortcode when no `height` is given.
+const DEFAULT_BARCODE_HEIGHT: &str = "1.5cm";
+
+/// This function renders the `{{barcode code128 "INV-2024-0031"}}` shortcode as an inline SVG barcode.
+///
+/// # Arguments
+///
+/// * `arguments` - The shortcode arguments: the symbology (`code128` or `ean13`), the data to encode,
+///   an optional `height` (any CSS length, `1.5cm` by default) and `text=false` to leave out the
+///   human readable text under the bars.
+///
+/// # Returns
+///
+/// * `Ok(ShortcodeOutput)` with the SVG.
+/// * `Err(message)` if the symbology is unknown or the data cannot be encoded with it.
+///
+/// # Remarks
+///
+/// Code 128 encodes printable ASCII text. EAN-13 encodes 12 digits (the check digit is added) or
+/// 13 digits (the check digit is checked).
+pub fn barcode_shortcode(arguments: &ShortcodeArguments) -> Result<ShortcodeOutput, String> {
+    let symbology = arguments.required(0, "barcode type (code128 or ean13)")?;
+    let data = arguments.required(1, "barcode data")?;
+    let height = arguments
+        .named
+        .get("height")
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_BARCODE_HEIGHT);
+    let show_text = arguments.named.get("text").map(String::as_str) != Some("false");
+
+    let (bars, text) = match symbology.to_lowercase().as_str() {
+        "code128" => {
+            if !data
+                .chars()
+                .all(|character| (' '..='~').contains(&character))
+            {
+                return Err("Code 128 data must be printable ASCII".to_string());
+            }
+            // `Ɓ` selects Code 128 character set B (printable ASCII)
+            let code = Code128::new(format!("Ɓ{}", data)).map_err(|error| error.to_string())?;
+            (code.encode(), data.to_string())
+        }
+        "ean13" => {
+            let code = EAN13::new(data).map_err(|error| error.to_string())?;
+            (code.encode(), ean13_with_check_digit(data))
+        }
+        _ => return Err(format!("unknown barcode type {}", symbology)),
+    };
+
+    let width = bars.len() + 2 * QUIET_ZONE;
+    let total_height = if show_text {
+        BAR_HEIGHT + TEXT_HEIGHT
+    } else {
+        BAR_HEIGHT
+    };
+
+    // One rectangle per run of dark bars
+    let mut path = String::new();
+    let mut index = 0;
+    while index < bars.len() {
+        if bars[index] == 1 {
+            let run = bars[index..].iter().take_while(|bar| **bar == 1).count();
+            path.push_str(&format!(
+                "M{} 0h{}v{}h-{}z",
+                index + QUIET_ZONE,
+                run,
+                BAR_HEIGHT,
+                run
+            ));
+            index += run;
+        } else {
+            index += 1;
+        }
+    }
+
+    let text_element = if show_text {
+        format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"monospace\" font-size=\"{}\" text-anchor=\"middle\">{}</text>",
+            width / 2,
+            total_height - 1,
+            TEXT_HEIGHT - 2,
+            escape_html(&text)
+        )
+    } else {
+        String::new()
+    };
+
+    Ok(ShortcodeOutput {
+        html: format!(
+            "<svg class=\"barcode\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" \
+             style=\"height: {}\" shape-rendering=\"crispEdges\" role=\"img\">\
+             <title>{}</title><rect width=\"{}\" height=\"{}\" fill=\"#fff\"/>\
+             <path d=\"{}\" fill=\"#000\"/>{}</svg>",
+            width,
+            total_height,
+            escape_html(height),
+            escape_html(&text),
+            width,
+            total_height,
+            path,
+            text_element
+        ),
+        block: false,
+    })
+}
+
+/// Returns the 13 digits of an EAN-13 code, adding the check digit when only 12 are given.
+fn ean13_with_check_digit(data: &str) -> String {
+    let digits: Vec<u32> = data
+        .chars()
+        .filter_map(|digit| digit.to_digit(10))
+        .collect();
+    if digits.len() != 12 {
+        return data.to_string();
+    }
+    let weighted_sum: u32 = digits
+        .iter()
+        .enumerate()
+        .map(|(index, digit)| if index % 2 == 0 { *digit } else { digit * 3 })
+        .sum();
+    format!("{}{}", data, (10 - weighted_sum % 10) % 10)
+}
@@ -0,0 +1,165 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use std::time::{Duration, Instant};
+
+use crate::generate::{
+    build_output, document_build, document_unfilled_placeholders, expand_document_variables,
+    read_source_document, render_document_html, DocumentOverrides,
+};
+use crate::source_ordering::ordered_source_files;
+use crate::utils::{missing_front_matter_keys, remove_unfilled_placeholders};
+use pdf_composer_definitions::bench::{BenchReport, StageTimings};
+use pdf_composer_definitions::front_matter::{MissingKeysPolicy, PlaceholderPolicy};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function renders the source files of a composer a number of times, timing each stage.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the source files and settings to render.
+/// * `iterations` - The number of times to render the source files (at least `1`).
+///
+/// # Returns
+///
+/// A `BenchReport` with the min, median and max time of each stage over the iterations (the
+/// report is printed as well).
+///
+/// # Remarks
+///
+/// The stages are `read` (the source file and its front matter), `render` (markdown to HTML),
+/// `build (<format>)` for each output format (Chromium and the PDF post-processing, for PDFs) and
+/// the `total`. The time of a stage in an iteration is its time summed over all the source files.
+///
+/// The documents are rendered one at a time, in source order, whatever the processing mode, so the
+/// stages do not compete for the CPU and the timings can be compared between runs. Documents are
+/// written to the output directory as usual (each iteration overwrites the last), but they are not
+/// merged in merge mode.
+pub fn run_bench(composer: &PDFComposerStruct, iterations: usize) -> BenchReport {
+    let iterations = iterations.max(1);
+    let source_files = ordered_source_files(&composer.fmy_source_files, &composer.source_ordering);
+    // In merge mode, pages are numbered once all the chapters are merged
+    let page_numbering = match &composer.merge_mode {
+        Some(_) => None,
+        None => composer.page_numbering.clone(),
+    };
+
+    // The time each stage took in each iteration, in the order the stages first ran
+    let mut samples: Vec<(String, Vec<Duration>)> = Vec::new();
+
+    for iteration in 1..=iterations {
+        println!(
+            "{} {}/{}\n",
+            "Benchmark iteration".cyan(),
+            iteration,
+            iterations
+        );
+        let mut stage_times: Vec<(String, Duration)> = Vec::new();
+        let iteration_start = Instant::now();
+
+        for source_file in &source_files {
+            let filename = source_file.display().to_string();
+
+            let start = Instant::now();
+            let source_document =
+                read_source_document(&filename, composer.console_output.is_quiet());
+            add_time(&mut stage_times, "read", start.elapsed());
+            let Ok((yaml_btreemap, markdown_content)) = source_document else {
+                continue;
+            };
+            let (yaml_btreemap, mut markdown_content) =
+                expand_document_variables(composer, source_file, yaml_btreemap, &markdown_content);
+            if composer.missing_keys_policy == MissingKeysPolicy::Skip
+                && !missing_front_matter_keys(&yaml_btreemap, &composer.required_front_matter_keys)
+                    .is_empty()
+            {
+                continue;
+            }
+            let unfilled_placeholders =
+                document_unfilled_placeholders(composer, &yaml_btreemap, &markdown_content);
+            match composer.placeholder_policy {
+                PlaceholderPolicy::Error if !unfilled_placeholders.is_empty() => continue,
+                PlaceholderPolicy::Remove => {
+                    markdown_content =
+                        remove_unfilled_placeholders(&yaml_btreemap, &markdown_content)
+                }
+                _ => {}
+            }
+
+            let start = Instant::now();
+            let html =
+                render_document_html(composer, source_file, &yaml_btreemap, &markdown_content);
+            add_time(&mut stage_times, "render", start.elapsed());
+
+            let document_build = document_build(
+                composer,
+                source_file,
+                &DocumentOverrides::default(),
+                yaml_btreemap,
+                page_numbering.clone(),
+            );
+            for &output_format in &composer.output_formats {
+                let start = Instant::now();
+                // A failed build is timed too (and reported as it happens)
+                let _ = build_output(
+                    composer,
+                    source_file,
+                    output_format,
+                    &html,
+                    &markdown_content,
+                    &document_build,
+                );
+                add_time(
+                    &mut stage_times,
+                    &format!("build ({})", output_format),
+                    start.elapsed(),
+                );
+            }
+        }
+        add_time(&mut stage_times, "total", iteration_start.elapsed());
+
+        for (stage, time) in stage_times {
+            match samples.iter_mut().find(|(name, _)| *name == stage) {
+                Some((_, times)) => times.push(time),
+                None => samples.push((stage, vec![time])),
+            }
+        }
+    }
+
+    let report = BenchReport {
+        iterations,
+        documents: source_files.len(),
+        stages: samples
+            .into_iter()
+            .map(|(stage, times)| stage_timings(stage, times))
+            .collect(),
+    };
+    println!("\n{}\n{}", "Benchmark".cyan(), report);
+    report
+}
+
+/// Adds the time a stage took for one source file to its time in the iteration.
+fn add_time(stage_times: &mut Vec<(String, Duration)>, stage: &str, time: Duration) {
+    match stage_times.iter_mut().find(|(name, _)| name == stage) {
+        Some((_, total)) => *total += time,
+        None => stage_times.push((stage.to_string(), time)),
+    }
+}
+
+/// Returns the min, median and max of the times a stage took.
+fn stage_timings(stage: String, mut times: Vec<Duration>) -> StageTimings {
+    times.sort();
+    let middle = times.len() / 2;
+    let median = if times.len() % 2 == 0 {
+        (times[middle - 1] + times[middle]) / 2
+    } else {
+        times[middle]
+    };
+    StageTimings {
+        stage,
+        min: times[0],
+        median,
+        max: times[times.len() - 1],
+    }
+}
@@ -0,0 +1,220 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use regex::Regex;
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::build_pdf::PDFBuilder;
+use crate::html_document::{
+    document_language, document_text_direction, html_document, inline_local_images, page_css,
+    screen_page_css, stylesheet_css, PageStyle,
+};
+use crate::utils::{escape_html, output_file_path, write_atomically};
+use pdf_composer_definitions::consts::{CHECK_MARK, CROSS_MARK};
+use pdf_composer_definitions::merge::MergeMode;
+use pdf_composer_definitions::output_format::OutputFormat;
+
+/// This function writes a self-contained HTML document from a given HTML string, source file and
+/// YAML data, instead of printing it to PDF.
+///
+/// # Arguments
+///
+/// * `generated_html` - A `String` containing the HTML generated from the markdown.
+/// * `yaml_btreemap` - A `BTreeMap<String, Value>` containing the YAML data.
+/// * `dictionary_entries` - A `BTreeMap<String, String>` of document information entries (entry → YAML key), written as `<meta>` elements.
+/// * `instance_data` - The same settings the PDF would be built with (source_file, output_directory, paper_size etc).
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path of the HTML document, if it was successfully written.
+/// * `Err(e)` if the HTML document could not be written.
+///
+/// # Remarks
+///
+/// The HTML document has the same styles as the PDF (the page size applies when it is printed,
+/// and on screen the body is shown as a sheet of paper). Images with a local `src` are inlined as
+/// data URLs, so the document can be moved or published on its own. Settings that only apply to
+/// PDFs (page numbers, running headers, the QR code footer, tagging) are not used.
+pub fn build_html(
+    generated_html: String,
+    yaml_btreemap: BTreeMap<String, Value>,
+    dictionary_entries: BTreeMap<String, String>,
+    instance_data: PDFBuilder,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let PDFBuilder {
+        source_file,
+        output_file_name: file_name,
+        output_directory,
+        paper_size,
+        orientation,
+        margins,
+        mirrored_margins,
+        font,
+        text_direction,
+        language,
+        text_justification,
+        stylesheet,
+        creator,
+        quiet,
+        ..
+    } = instance_data;
+
+    // Front matter `dir` and `lang` (or `language`) entries override the composer-wide settings
+    let text_direction = document_text_direction(&yaml_btreemap, text_direction);
+    let language = document_language(&yaml_btreemap, language, text_justification.as_ref());
+
+    let title = yaml_btreemap
+        .get("title")
+        .and_then(|value| value.as_str())
+        .unwrap_or(&file_name);
+
+    let mut head = document_meta(&creator, &yaml_btreemap, &dictionary_entries);
+    head.push_str(&page_css(
+        &PageStyle {
+            paper_size,
+            orientation,
+            margins,
+            mirrored_margins,
+            font,
+            text_direction,
+            text_justification: text_justification.as_ref(),
+        },
+        "all",
+    ));
+    head.push_str(&screen_page_css(paper_size, orientation, margins));
+    head.push_str(&stylesheet_css(stylesheet.as_deref()));
+
+    let source_directory = Path::new(&source_file).parent().unwrap_or(Path::new(""));
+    let html_document_string = html_document(
+        &inline_local_images(&generated_html, source_directory),
+        title,
+        language.as_deref(),
+        text_direction,
+        &head,
+    );
+
+    let html_file_path = output_file_path(&output_directory, format!("{}.html", file_name));
+    let html_file_path_as_string = html_file_path.display().to_string();
+    if let Err(error) = write_atomically(&html_file_path, |writer| {
+        writer.write_all(html_document_string.as_bytes())
+    }) {
+        eprintln!(
+            "\n{}{}\n{} {}",
+            CROSS_MARK.on_red(),
+            html_file_path_as_string.on_red(),
+            "Failed to save HTML document.".red(),
+            error
+        );
+        return Err(error.into());
+    }
+
+    if !quiet {
+        println!(
+            "\n{}{} → {}",
+            CHECK_MARK.to_string().green(),
+            source_file.green(),
+            html_file_path_as_string.yellow()
+        );
+    }
+
+    Ok(html_file_path)
+}
+
+/// This function combines the HTML documents generated for each source file (chapter) into one
+/// HTML document, in merge mode.
+///
+/// # Arguments
+///
+/// * `merge_mode` - The merge mode, with the file name of the merged document.
+/// * `output_directory` - The directory the merged document is written to.
+/// * `chapters` - The source file and HTML document of each chapter, in order.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path of the merged HTML document.
+/// * `Err(e)` if a chapter could not be read or the merged document could not be written.
+///
+/// # Remarks
+///
+/// The `<head>` (styles, language etc) of the first chapter is used, with the title replaced by the
+/// `Title` document information override, if there is one. Each chapter body is put in a
+/// `<section class="chapter">`, starting on a new page when printed.
+pub fn merge_html_chapters(
+    merge_mode: &MergeMode,
+    output_directory: &Path,
+    chapters: &[(PathBuf, PathBuf)],
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut opening = String::new();
+    let mut sections = String::new();
+    for (index, (_source_file, chapter_html)) in chapters.iter().enumerate() {
+        let html = fs::read_to_string(chapter_html)?;
+        let (Some(body_start), Some(body_end)) = (html.find("<body>"), html.rfind("</body>"))
+        else {
+            return Err(format!("{} has no <body>", chapter_html.display()).into());
+        };
+        if index == 0 {
+            opening = html[..body_start].to_string();
+        }
+        sections.push_str("<section class=\"chapter\">");
+        sections.push_str(&html[body_start + "<body>".len()..body_end]);
+        sections.push_str("</section>\n");
+    }
+
+    if let Some(title) = merge_mode.options.doc_info_overrides.get("Title") {
+        let title_regex = Regex::new(r"(?s)<title>.*?</title>").unwrap();
+        let title_element = format!("<title>{}</title>", escape_html(title));
+        opening = title_regex
+            .replace(&opening, regex::NoExpand(&title_element))
+            .to_string();
+    }
+    let opening = opening.replacen(
+        "</head>",
+        "<style>\n.chapter + .chapter { break-before: page; }\n</style></head>",
+        1,
+    );
+
+    let merged_file = output_file_path(
+        output_directory,
+        merge_mode.output_file_name(OutputFormat::Html),
+    );
+    let merged_html = format!("{}<body>{}</body></html>", opening, sections);
+    write_atomically(&merged_file, |writer| {
+        writer.write_all(merged_html.as_bytes())
+    })?;
+    Ok(merged_file)
+}
+
+/// Returns the `<meta>` elements of the document: the generator (the `Creator` of the PDF), and
+/// the document information entries with a front matter value (`Subject` as the description).
+fn document_meta(
+    generator: &str,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    dictionary_entries: &BTreeMap<String, String>,
+) -> String {
+    let mut meta = format!(
+        "<meta name=\"generator\" content=\"{}\">",
+        escape_html(generator)
+    );
+    for (entry, yaml_key) in dictionary_entries {
+        let Some(value) = yaml_btreemap.get(yaml_key).and_then(|value| value.as_str()) else {
+            continue;
+        };
+        let name = match entry.to_lowercase().as_str() {
+            // The title is the <title> element
+            "title" => continue,
+            "subject" => "description".to_string(),
+            name => name.to_string(),
+        };
+        meta.push_str(&format!(
+            "<meta name=\"{}\" content=\"{}\">",
+            escape_html(&name),
+            escape_html(value)
+        ));
+    }
+    meta
+}
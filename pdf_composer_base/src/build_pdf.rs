@@ -2,24 +2,60 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 use colored::Colorize;
-use lopdf::{Document, Object as LopdfObject, StringFormat};
+use lopdf::{Document, Object as LopdfObject};
 use serde_yml::Value;
 use std::collections::BTreeMap;
-use std::fs::{create_dir_all, OpenOptions};
-use std::io;
-use std::path::{Path, PathBuf};
-
-use crate::utils::extract_to_end_string;
-use async_std::task;
-use chromiumoxide::{cdp::browser_protocol::page::PrintToPdfParams, Browser, BrowserConfig};
-use pdf_composer_definitions::consts::{CHECK_MARK, CROSS_MARK, PACKAGE_NAME};
-use pdf_composer_definitions::fonts::{FontsStandard, GetCssName};
-use pdf_composer_definitions::page_properties::{
-    PageMargins, PaperOrientation, PaperSize, ToDimensions,
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::accessibility::{fill_figure_alt_text, image_alt_texts, mark_as_tagged};
+use crate::attachments::{embed_files, file_attachment, source_attachments};
+use crate::catalog::{
+    set_catalog_language, set_page_labels, set_pdf_version, set_viewer_preference,
+    set_viewer_preferences,
 };
-use pdf_composer_definitions::pdf_version::PDFVersion;
-
-use futures::StreamExt;
+use crate::chunked_rendering::{split_into_chunks, stitch_chunks};
+use crate::conformance::apply_conformance;
+use crate::destinations::set_open_action;
+use crate::deterministic::{make_deterministic, set_deterministic_id};
+use crate::form_fields::{add_form_fields, add_signature_field};
+use crate::html_document::{
+    document_language, document_text_direction, html_document, page_css, page_dimensions,
+    stylesheet_css, PageStyle,
+};
+use crate::icc_profile::add_output_intent;
+use crate::image_policy::apply_image_policy;
+use crate::locked_file::write_pdf_file;
+use crate::optimize::optimize_document;
+use crate::page_numbering::number_pages;
+use crate::persistent_browser::{browser_for, discard_browser, PersistentBrowser};
+use crate::post_process::post_process;
+use crate::print_command::{print_to_pdf, ExtendedPrintToPdfParams};
+use crate::qr_code::stamp_qr_code_footer;
+use crate::running_headers::stamp_running_headers;
+#[cfg(feature = "timestamp")]
+use crate::timestamp::{timestamp_document, timestamp_pdf_file};
+use crate::utils::{doc_info_value, encode_pdf_text_string, output_file_path};
+use crate::watermark::stamp_watermark;
+use async_std::task;
+use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+use pdf_composer_definitions::attachments::FileAttachment;
+use pdf_composer_definitions::conformance::Conformance;
+use pdf_composer_definitions::consts::{CHECK_MARK, CROSS_MARK};
+use pdf_composer_definitions::fonts::FontsStandard;
+use pdf_composer_definitions::icc_profile::IccProfile;
+use pdf_composer_definitions::image_policy::ImagePolicy;
+use pdf_composer_definitions::locked_file::LockedFilePolicy;
+use pdf_composer_definitions::open_action::OpenAction;
+use pdf_composer_definitions::page_numbering::{PageLabelRange, PageNumbering};
+use pdf_composer_definitions::page_properties::{PageMargins, PaperOrientation, PaperSize};
+use pdf_composer_definitions::pdf_version::{PDFFeature, PDFVersion};
+use pdf_composer_definitions::running_headers::RunningHeaders;
+use pdf_composer_definitions::signature_fields::SignatureField;
+use pdf_composer_definitions::text_direction::TextDirection;
+use pdf_composer_definitions::text_justification::JustifyOptions;
+use pdf_composer_definitions::viewer_preferences::ViewerPreferences;
 
 /// This function generates a PDF document from a given HTML string, source file and YAML data.
 /// It also all updated dictionary entries, PDF version, paper size, paper orientation sets margins and the font before writing PDFs to the output directory.
@@ -33,26 +69,26 @@ use futures::StreamExt;
 ///
 /// # Returns
 ///
-/// * `Ok(())` if the PDF document was successfully generated and saved.
+/// * `Ok((PathBuf, u32))` with the path of the PDF document and its number of pages, if it was successfully generated and saved.
 /// * `Err(e)` if an error occurred during the process, where `e` is a `Box<dyn std::error::Error>` containing the error information.
 ///
 /// # Remarks
 ///
 /// This function performs the following tasks:
 ///
-/// 1. Launches a Headless Chromium browser instance using the `Browser::launch` method.
+/// 1. Launches a Headless Chromium browser instance using the `Browser::launch` method (or uses the warm persistent browser, see `browser_for`).
 /// 2. Constructs the HTML content by combining the generated HTML with a basic HTML structure and encoding it for URL safety.
 /// 3. Creates a new browser page and navigates to the HTML content.
 /// 4. Converts the page content to PDF format using the `page.pdf` method.
 /// 5. Creates a new `Document` object from the PDF data using the `Document::load_mem` method.
-/// 6. Updates the PDF document version based on the provided `pdf_version`.
+/// 6. Updates the PDF document version based on the provided `pdf_version` (`PDFVersion::Auto` picks the lowest version that can express the features used).
 /// 7. Sets the paper size `paper_size`
 /// 8. Sets the paper margins `margins`
 /// 9. Sets the PDF font `font`
 /// 10. Set the orientation for the paper `orientation`
 /// 11. Iterates over the objects in the PDF document and updates the "Creator" and "Producer" metadata entries, if present.
-/// 12. If the "Creator" metadata entry is found, adds or updates the PDF document's metadata properties based on the `dictionary_entries`.
-/// 13. Saves the modified PDF document to the specified output directory with a filename derived from the source file.
+/// 12. If the "Creator" metadata entry is found, adds or updates the PDF document's metadata properties based on the `dictionary_entries` (a name for `Trapped` and dates for `CreationDate` and `ModDate`).
+/// 13. Saves the modified PDF document (once, streamed through a buffered writer) to the specified output directory with a filename derived from the source file.
 /// 14. Displays a success message with the path to the generated PDF file and the updated metadata properties.
 ///
 /// The function handles cases where the PDF file is already open by another process and prints an error message if an error occurs during the process.
@@ -61,98 +97,129 @@ pub fn build_pdf(
     yaml_btreemap: BTreeMap<String, Value>,
     dictionary_entries: BTreeMap<String, String>,
     instance_data: PDFBuilder,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(PathBuf, u32), Box<dyn std::error::Error>> {
     // Destructure instance_data (PDFBuilder struct)
     let PDFBuilder {
         orientation,
         source_file,
+        output_file_name,
         output_directory,
         pdf_version,
         paper_size,
         margins,
+        mirrored_margins,
         font,
+        text_direction,
+        language,
+        text_justification,
+        stylesheet,
+        tagged_pdf,
+        running_headers,
+        page_numbering,
+        page_labels,
+        viewer_preferences,
+        open_action,
+        qr_code_footer,
+        watermark,
+        attach_source,
+        attach_front_matter,
+        attachments,
+        signature_fields,
+        #[cfg(feature = "timestamp")]
+        timestamp_authority,
+        conformance,
+        output_intent,
+        image_policy,
+        deterministic,
+        chunked_rendering,
+        persistent_browser,
+        locked_file_policy,
+        post_process_command,
+        quiet,
+        creator,
+        producer,
     } = instance_data;
 
-    // Set page size for all PDF documents based on orientation.
-    let (page_width, page_height) = match orientation {
-        PaperOrientation::Landscape => (paper_size.to_dimensions().1, paper_size.to_dimensions().0),
-        PaperOrientation::Portrait => paper_size.to_dimensions(),
+    // Front matter `dir` and `lang` (or `language`) entries override the composer-wide settings
+    let text_direction = document_text_direction(&yaml_btreemap, text_direction);
+    let language = document_language(&yaml_btreemap, language, text_justification.as_ref());
+
+    #[cfg(feature = "timestamp")]
+    let timestamped = timestamp_authority.is_some();
+    #[cfg(not(feature = "timestamp"))]
+    let timestamped = false;
+
+    // Pick (or check) the PDF version before Chromium is started
+    let pdf_version = match pdf_version.resolve(&pdf_features(
+        tagged_pdf,
+        language.is_some(),
+        page_numbering.is_some() || page_labels.is_some(),
+        text_direction,
+        conformance,
+        timestamped,
+    )) {
+        Ok(pdf_version) => pdf_version,
+        Err(message) => {
+            eprintln!(
+                "{}{} {}",
+                CROSS_MARK.red(),
+                source_file.red(),
+                message.red()
+            );
+            return Err(message.into());
+        }
     };
 
-    task::block_on(async {
-        // Remove the markdown, md, file extension
-        let filename_path = source_file.trim_end_matches(".md");
-        // Extract only the file name
-        let extracted_filename = extract_to_end_string(filename_path);
-        let extracted_filename_as_string = extracted_filename.unwrap().to_string();
+    // Set page size for all PDF documents based on orientation.
+    let (page_width, page_height) = page_dimensions(paper_size, orientation);
 
+    task::block_on(async {
         let mut string_values_btreemap: BTreeMap<String, String> = BTreeMap::new();
         for (key, value) in yaml_btreemap.clone() {
-            if let Value::String(string_value) = value {
-                string_values_btreemap.insert(key, string_value);
+            match value {
+                Value::String(string_value) => {
+                    string_values_btreemap.insert(key, string_value);
+                }
+                // For entries such as `Trapped: true`
+                Value::Bool(bool_value) => {
+                    string_values_btreemap.insert(key, bool_value.to_string());
+                }
+                _ => {}
             }
         }
-        let (browser, mut handler) = Browser::launch(BrowserConfig::builder().build()?).await?;
-
-        let _handle = async_std::task::spawn(async move {
-            loop {
-                let _event = handler.next().await.unwrap();
-            }
-        });
+        let browser = browser_for(persistent_browser.as_ref()).await?;
 
         // TODO RL Template this? External file?
-        // Set CSS @media print media query and @page property for pages
-        let mut css_page = String::from("<style>\n@media print {\n ");
-        let (css_font_name, css_font_weight, css_font_style) = font.get_css_name();
-        let css_font = format!(
-            "body {{ font-family: {}; font-weight: {}; font-style: {} }}\n\n",
-            css_font_name, css_font_weight, css_font_style
+        let mut css_page = page_css(
+            &PageStyle {
+                paper_size,
+                orientation,
+                margins,
+                mirrored_margins,
+                font,
+                text_direction,
+                text_justification: text_justification.as_ref(),
+            },
+            "print",
         );
-        let css_at_page = format!("@page {{\nsize: {}in {}in;\n}}", page_width, page_height);
-        css_page.push_str(&css_font);
-        css_page.push_str(&css_at_page);
-        css_page.push_str("\n}\n</style>");
+        css_page.push_str(&stylesheet_css(stylesheet.as_deref()));
 
         // Set the title String to either the yaml 'title' entry,
         // or (if there is no 'title' entry), the filename of the source file in question
         let title_string = yaml_btreemap
             .get("title")
             .and_then(|value| value.as_str())
-            .unwrap_or(&extracted_filename_as_string);
-        let mut html_string = String::new();
-        let html_before_string = format!(
-            "<html><head><title>{}</title>{}</head><body>",
-            title_string, css_page
-        );
-        let html_after_string = "</body></html>";
-
-        // Encode the HTML content to URL-safe format
-        // url_escape:: comes from the url_escape crate
-        url_escape::encode_query_to_string(generated_html, &mut html_string);
-
-        let mut pdf_file = extracted_filename_as_string;
+            .unwrap_or(&output_file_name);
+        let mut pdf_file = output_file_name.clone();
         pdf_file.push_str(".pdf");
 
-        let pdf_file_path = Path::new(&output_directory).join(pdf_file);
+        let pdf_file_path = output_file_path(&output_directory, pdf_file);
         let pdf_file_path_as_string = pdf_file_path
             .clone()
             .into_os_string()
             .into_string()
             .unwrap();
 
-        // Navigate the tab to the HTML content.
-        // In this case, the page is a data stream
-        let page = browser
-            .new_page(
-                format!(
-                    "data:text/html;charset=utf-8,{}{}{}",
-                    html_before_string, html_string, html_after_string
-                )
-                .as_str(),
-            )
-            .await?;
-        let _html = page.wait_for_navigation().await?.content().await?;
-
         // Convert the page to PDF format
         let paper_settings = PrintToPdfParams {
             // landscape: todo!(),
@@ -173,20 +240,191 @@ pub fn build_pdf(
             ..Default::default()
         };
 
-        // let pdf = page.pdf(PrintToPdfParams::default()).await?;
-        let pdf = page.pdf(paper_settings).await?;
+        // Very large documents are rendered a few sections at a time, each in its own tab, as
+        // one giant tab can run Chromium out of memory
+        let chunks = match chunked_rendering {
+            Some(sections_per_chunk) => split_into_chunks(&generated_html, sections_per_chunk),
+            None => vec![generated_html.clone()],
+        };
+        let printed: Result<Vec<Document>, Box<dyn std::error::Error>> = async {
+            let mut chunk_docs: Vec<Document> = Vec::with_capacity(chunks.len());
+            for chunk in &chunks {
+                let html_document_string = html_document(
+                    chunk,
+                    title_string,
+                    language.as_deref(),
+                    text_direction,
+                    &css_page,
+                );
 
-        // Create a new PDF document
-        let mut doc: Document = Document::load_mem(&pdf)?;
-        doc.version = pdf_version.to_string();
+                // Encode the whole HTML document to URL-safe format (a `#` in the styles would otherwise
+                // end the data URL). url_escape:: comes from the url_escape crate
+                let mut html_string = String::new();
+                url_escape::encode_query_to_string(&html_document_string, &mut html_string);
+
+                // Navigate the tab to the HTML content.
+                // In this case, the page is a data stream
+                let page = browser
+                    .new_page(format!("data:text/html;charset=utf-8,{}", html_string).as_str())
+                    .await?;
+                let _html = page.wait_for_navigation().await?.content().await?;
+
+                // let pdf = page.pdf(PrintToPdfParams::default()).await?;
+                let pdf = print_to_pdf(
+                    &page,
+                    ExtendedPrintToPdfParams {
+                        params: paper_settings.clone(),
+                        generate_tagged_pdf: tagged_pdf.then_some(true),
+                        generate_document_outline: running_headers.map(|_| true),
+                    },
+                )
+                .await?;
+                if chunks.len() > 1 || persistent_browser.is_some() {
+                    // Free the memory of the tab before the next chunk (or document) is rendered
+                    page.close().await?;
+                }
+                chunk_docs.push(Document::load_mem(&pdf)?);
+            }
+            Ok(chunk_docs)
+        }
+        .await;
+        let mut chunk_docs = match printed {
+            Ok(chunk_docs) => chunk_docs,
+            Err(error) => {
+                // A persistent browser that failed (Chromium crashed, for example) is not used again
+                if let Some(persistent_browser) = &persistent_browser {
+                    discard_browser(persistent_browser, &browser);
+                }
+                return Err(error);
+            }
+        };
 
+        // Create a new PDF document (stitching the chunks together, continuing their pages)
+        let mut doc: Document = if chunk_docs.len() == 1 {
+            chunk_docs.remove(0)
+        } else {
+            stitch_chunks(chunk_docs)?
+        };
+        set_pdf_version(&mut doc, pdf_version);
+
+        // Record the document language in the catalog, where readers look for it
+        if let Some(lang) = &language {
+            set_catalog_language(&mut doc, lang)?;
+        }
+
+        // Right-to-left documents should also page right-to-left in the viewer
+        if text_direction == TextDirection::Rtl {
+            set_viewer_preference(&mut doc, "Direction", LopdfObject::Name(b"R2L".to_vec()))?;
+        }
+        set_viewer_preferences(&mut doc, &viewer_preferences)?;
+        if let Some(open_action) = &open_action {
+            if let Err(error) = set_open_action(&mut doc, open_action) {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!("{} opens as usual: {}.", source_file, error).yellow()
+                );
+            }
+        }
+
+        // Tagged PDFs must say so in the catalog (the structure tree itself comes from Chromium)
+        if tagged_pdf {
+            mark_as_tagged(&mut doc)?;
+            fill_figure_alt_text(&mut doc, &image_alt_texts(&generated_html));
+        }
+
+        // The boxes of the `{{field}}` shortcodes become fillable form fields
+        add_form_fields(&mut doc)?;
+        for signature_field in &signature_fields {
+            if let Err(error) = add_signature_field(&mut doc, signature_field) {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!(
+                        "Signature field {} not added to {}: {}.",
+                        signature_field.name, source_file, error
+                    )
+                    .yellow()
+                );
+            }
+        }
+
+        // Running headers are drawn on the finished pages, using the outline to find the headings
+        if let Some(running_headers) = running_headers {
+            let stamped_pages =
+                stamp_running_headers(&mut doc, running_headers, font, margins, mirrored_margins)?;
+            if stamped_pages == 0 {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    "No headings found in the document outline; running headers not added."
+                        .yellow()
+                );
+            }
+        }
+
+        if let Some(page_numbering) = &page_numbering {
+            number_pages(&mut doc, page_numbering, font, margins, mirrored_margins)?;
+        }
+        if let Some(page_labels) = &page_labels {
+            set_page_labels(&mut doc, page_labels)?;
+        }
+
+        if let Some(qr_code_data) = &qr_code_footer {
+            stamp_qr_code_footer(&mut doc, qr_code_data, margins, mirrored_margins)?;
+        }
+        if let Some(watermark) = &watermark {
+            stamp_watermark(&mut doc, watermark, font)?;
+        }
+
+        if let Some(image_policy) = &image_policy {
+            apply_image_policy(&mut doc, image_policy);
+        }
+
+        let mut embedded_files = match source_attachments(
+            &source_file,
+            &yaml_btreemap,
+            attach_source,
+            attach_front_matter,
+        ) {
+            Ok(embedded_files) => embedded_files,
+            Err(error) => {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!(
+                        "The source of {} could not be attached: {}",
+                        source_file, error
+                    )
+                    .yellow()
+                );
+                Vec::new()
+            }
+        };
+        for attachment in &attachments {
+            match file_attachment(attachment) {
+                Ok(embedded_file) => embedded_files.push(embedded_file),
+                Err(error) => eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!(
+                        "{} could not be attached: {}",
+                        attachment.path.display(),
+                        error
+                    )
+                    .yellow()
+                ),
+            }
+        }
+        embed_files(&mut doc, &embedded_files)?;
+
+        optimize_document(&mut doc);
         doc.compress();
-        create_dir_all(pdf_file_path.parent().unwrap())?;
-        doc.save(pdf_file_path.clone()).unwrap();
+        if deterministic {
+            make_deterministic(&mut doc);
+        }
 
-        #[allow(unused_variables)]
-        let mut object_count: i32 = 0;
-        // Iterate over the objects in the PDF document and count them
+        // Iterate over the objects in the PDF document
         for object_element in &mut doc.objects {
             let (_key, object) = object_element;
             match object {
@@ -202,20 +440,13 @@ pub fn build_pdf(
                         // Check if the key is "Creator"
                         if ascii_key == "Creator" {
                             // Update the value associated with the key
-                            let default_creator = &PACKAGE_NAME.to_string();
-                            let ascii_string = string_values_btreemap
-                                .get("generator")
-                                .unwrap_or(default_creator);
-                            let ascii_bytes: Vec<u8> = ascii_string.as_bytes().to_vec();
-                            *value = lopdf::Object::String(ascii_bytes, StringFormat::Literal);
+                            *value = encode_pdf_text_string(&creator);
                             // Set creator_found to true
                             creator_found = true;
                         }
                         if ascii_key == "Producer" {
                             // Update the value associated with the key
-                            let ascii_string = PACKAGE_NAME;
-                            let ascii_bytes: Vec<u8> = ascii_string.as_bytes().to_vec();
-                            *value = lopdf::Object::String(ascii_bytes, StringFormat::Literal);
+                            *value = encode_pdf_text_string(&producer);
                         }
                     }
                     // If Creator key was found, add/update various PDF properties/metadata
@@ -226,20 +457,25 @@ pub fn build_pdf(
                                 check_entry_exists(entry.1.to_string(), &string_values_btreemap);
 
                             if entry_exists {
-                                let (_key, value) = populate_dictionary(
+                                match populate_dictionary(
+                                    entry.0.to_string(),
                                     entry.1.to_string(),
-                                    string_values_btreemap.clone(),
-                                );
-                                dictionary.set(entry.0.as_bytes().to_vec(), value);
+                                    &string_values_btreemap,
+                                ) {
+                                    Ok((key, value)) => dictionary.set(key, value),
+                                    Err(message) => eprintln!(
+                                        "{}{}",
+                                        CROSS_MARK.yellow(),
+                                        format!(
+                                            "Document information entry {} not set: {}.",
+                                            entry.0, message
+                                        )
+                                        .yellow()
+                                    ),
+                                }
                             }
                         }
                     }
-
-                    object_count += 1;
-                }
-                LopdfObject::Stream(_) => {
-                    // It's a stream object
-                    object_count += 1;
                 }
                 _ => {
                     // It's some other type of object
@@ -247,6 +483,60 @@ pub fn build_pdf(
             }
         }
 
+        // The output intent and standard are applied last, once the document information entries
+        // are in place
+        let fallback_title = pdf_file_path
+            .file_stem()
+            .map(|file_stem| file_stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let colour_managed = match &output_intent {
+            Some(icc_profile) => add_output_intent(&mut doc, icc_profile),
+            None => Ok(()),
+        }
+        .and_then(|()| match conformance {
+            Some(conformance) => apply_conformance(&mut doc, conformance, &fallback_title),
+            None => Ok(()),
+        });
+        if let Err(message) = colour_managed {
+            eprintln!(
+                "{}{} {}",
+                CROSS_MARK.red(),
+                source_file.red(),
+                message.red()
+            );
+            return Err(message.into());
+        }
+
+        // The document ID is a digest of the finished document, metadata and standard included
+        if deterministic {
+            set_deterministic_id(&mut doc)?;
+        }
+
+        // The timestamp covers the finished PDF, so it is saved as it was timestamped (a PDF the
+        // post-process command changes is timestamped after the command instead)
+        #[cfg(feature = "timestamp")]
+        let timestamped_pdf = match &timestamp_authority {
+            Some(tsa_url) if post_process_command.is_none() => {
+                match timestamp_document(&mut doc, tsa_url) {
+                    Ok(pdf) => Some(pdf),
+                    Err(message) => {
+                        eprintln!(
+                            "{}{} {}",
+                            CROSS_MARK.red(),
+                            source_file.red(),
+                            format!("not timestamped: {}", message).red()
+                        );
+                        return Err(message);
+                    }
+                }
+            }
+            _ => None,
+        };
+        #[cfg(not(feature = "timestamp"))]
+        let timestamped_pdf: Option<Vec<u8>> = None;
+        // Counted here, so the report does not load the saved PDF again
+        let page_count = doc.get_pages().len() as u32;
+
         let mut error_message = "\n".to_owned()
             + &CROSS_MARK.on_red().to_string()
             + &pdf_file_path_as_string.on_red().to_string()
@@ -258,11 +548,18 @@ pub fn build_pdf(
                 .as_str(),
         );
 
-        match is_file_open(&pdf_file_path_as_string) {
-            Ok(true) => println!("{} is open by another process.", &pdf_file_path_as_string),
-            Ok(false) => {
-                doc.save(pdf_file_path.clone()).unwrap();
-
+        // Written to a temporary file and renamed into place, so that a crash or a viewer reading
+        // the PDF at the same time never sees a truncated document (a locked PDF follows the
+        // locked file policy)
+        let pdf_file_path = match write_pdf_file(&pdf_file_path, &locked_file_policy, |writer| {
+            match &timestamped_pdf {
+                Some(pdf) => writer.write_all(pdf),
+                None => doc.save_to(writer),
+            }
+        }) {
+            Ok(saved_file_path) if quiet => saved_file_path,
+            Ok(saved_file_path) => {
+                let pdf_file_path_as_string = saved_file_path.display().to_string();
                 println!(
                     "\n{}{} → {}",
                     CHECK_MARK.to_string().green(),
@@ -279,19 +576,96 @@ pub fn build_pdf(
                         println!("* {}: {}", entry.0.cyan(), entry.1.green());
                     }
                 }
+                saved_file_path
+            }
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+                // The PDF is locked by the application it is open in (a viewer on Windows)
+                eprintln!("{} is open by another process.", &pdf_file_path_as_string);
+                return Err(
+                    format!("{} is open by another process", pdf_file_path_as_string).into(),
+                );
+            }
+            Err(error) => {
+                eprintln!("{} {}", error_message, error);
+                return Err(error.into());
+            }
+        };
+
+        // The PDF is kept when the command fails, to look into what the command reported
+        if let Some(command) = &post_process_command {
+            if let Err(message) = post_process(command, &pdf_file_path) {
+                eprintln!(
+                    "{}{} {}",
+                    CROSS_MARK.red(),
+                    source_file.red(),
+                    format!("post-process command failed: {}", message).red()
+                );
+                return Err(message);
             }
-            Err(error) => println!("{} {}", error_message, error),
         }
 
-        Ok(())
+        // Timestamped last, so the timestamp covers the PDF as the command left it
+        #[cfg(feature = "timestamp")]
+        let pdf_file_path = match (&timestamp_authority, &post_process_command) {
+            (Some(tsa_url), Some(_)) => {
+                match timestamp_pdf_file(&pdf_file_path, tsa_url, &locked_file_policy) {
+                    Ok(saved_file_path) => saved_file_path,
+                    Err(message) => {
+                        eprintln!(
+                            "{}{} {}",
+                            CROSS_MARK.red(),
+                            source_file.red(),
+                            format!("not timestamped: {}", message).red()
+                        );
+                        return Err(message);
+                    }
+                }
+            }
+            _ => pdf_file_path,
+        };
+
+        Ok((pdf_file_path, page_count))
     })
 }
 
+/// Returns the features of the PDF that need a minimum PDF version.
+fn pdf_features(
+    tagged_pdf: bool,
+    has_language: bool,
+    has_page_numbers: bool,
+    text_direction: TextDirection,
+    conformance: Option<Conformance>,
+    timestamped: bool,
+) -> Vec<PDFFeature> {
+    let mut features = Vec::new();
+    if tagged_pdf {
+        features.push(PDFFeature::TaggedPdf);
+    }
+    if has_language {
+        features.push(PDFFeature::DocumentLanguage);
+    }
+    if has_page_numbers {
+        features.push(PDFFeature::PageLabels);
+    }
+    if text_direction == TextDirection::Rtl {
+        features.push(PDFFeature::RightToLeftPaging);
+    }
+    if let Some(conformance) = conformance {
+        features.push(conformance.pdf_feature());
+    }
+    if timestamped {
+        features.push(PDFFeature::DocumentTimestamp);
+    }
+    features
+}
+
 /// PDFBuilder Struct for passing data into the build_pdf function
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct PDFBuilder {
     /// `source_file` - A `String` representing the path to the source file (e.g., Markdown file) from which the HTML was generated.
     pub source_file: String,
+    /// `output_file_name` - The file name (without `.pdf`) for the PDF file, named after the source file unless overridden.
+    pub output_file_name: String,
     /// `output_directory` - A `PathBuf` representing the directory where the PDF file should be saved.
     pub output_directory: PathBuf,
     /// `pdf_version` - A `PDFVersion` enum value specifying the version of the PDF document.
@@ -302,102 +676,109 @@ pub struct PDFBuilder {
     pub orientation: PaperOrientation,
     /// `margins` - Page margins.
     pub margins: PageMargins,
+    /// `mirrored_margins` - Whether the margins are top, outer, bottom, inner and alternate on left and right pages.
+    pub mirrored_margins: bool,
     /// `font` - The font to be used for the PDF document.
     pub font: FontsStandard,
+    /// `text_direction` - The direction text is written in (can be overridden by the `dir` front matter entry).
+    pub text_direction: TextDirection,
+    /// `language` - Optional language tag (can be overridden by the `lang` front matter entry).
+    pub language: Option<String>,
+    /// `text_justification` - Optional justification and hyphenation of the body text.
+    pub text_justification: Option<JustifyOptions>,
+    /// `stylesheet` - Optional CSS file whose styles are added after the built-in styles.
+    pub stylesheet: Option<PathBuf>,
+    /// `tagged_pdf` - Whether a tagged (accessible) PDF is generated.
+    pub tagged_pdf: bool,
+    /// `running_headers` - Options for the current section title at the top of each page (`None` for no running headers).
+    pub running_headers: Option<RunningHeaders>,
+    /// `page_numbering` - How the pages are numbered (`None` for no page numbers).
+    pub page_numbering: Option<PageNumbering>,
+    /// `page_labels` - The page labels shown by PDF viewers, replacing those of the page numbering (`None` for the labels of the page numbering).
+    pub page_labels: Option<Vec<PageLabelRange>>,
+    /// `viewer_preferences` - How PDF viewers show the PDF when it is opened (page layout, panel shown etc).
+    pub viewer_preferences: ViewerPreferences,
+    /// `open_action` - Where the PDF opens (`None` for the first page at the viewer's default zoom).
+    pub open_action: Option<OpenAction>,
+    /// `qr_code_footer` - The data (usually a URL) for a QR code in the footer of every page (`None` for no QR code).
+    pub qr_code_footer: Option<String>,
+    /// `watermark` - The text drawn diagonally across every page (`None` for no watermark).
+    pub watermark: Option<String>,
+    /// `attach_source` - Whether the source markdown file is embedded in the PDF.
+    pub attach_source: bool,
+    /// `attach_front_matter` - Whether the resolved front matter is embedded in the PDF.
+    pub attach_front_matter: bool,
+    /// `attachments` - The files embedded in the PDF as attachments (from the composer and the front matter).
+    pub attachments: Vec<FileAttachment>,
+    /// `signature_fields` - The unsigned signature fields added to the PDF.
+    pub signature_fields: Vec<SignatureField>,
+    /// `timestamp_authority` - The URL of the time stamping authority the PDF gets a document timestamp from (`None` for no timestamp).
+    #[cfg(feature = "timestamp")]
+    pub timestamp_authority: Option<String>,
+    /// `conformance` - The standard the PDF must conform to (`None` for no particular standard).
+    pub conformance: Option<Conformance>,
+    /// `output_intent` - The ICC colour profile written into the catalog as an output intent (`None` for no output intent).
+    pub output_intent: Option<IccProfile>,
+    /// `image_policy` - How the raster images are treated, such as downsampling (`None` leaves them as they are).
+    pub image_policy: Option<ImagePolicy>,
+    /// `deterministic` - Whether the dates and document ID are fixed, so identical inputs give identical PDFs.
+    pub deterministic: bool,
+    /// `chunked_rendering` - The number of H1 sections rendered together in one tab (`None` renders the document in one go).
+    pub chunked_rendering: Option<usize>,
+    /// `persistent_browser` - The warm headless Chromium to print with (`None` launches Chromium for this PDF).
+    pub persistent_browser: Option<Arc<PersistentBrowser>>,
+    /// `locked_file_policy` - What happens when the PDF file is locked by another process (a PDF viewer).
+    pub locked_file_policy: LockedFilePolicy,
+    /// `post_process_command` - Optional command run on the saved PDF (such as an optimizer or validator).
+    pub post_process_command: Option<String>,
+    /// `quiet` - Whether the progress messages (the PDF saved and its metadata) are left out.
+    pub quiet: bool,
+    /// `creator` - The `Creator` document information entry (the `generator` front matter value, the composer creator or PDF Composer).
+    pub creator: String,
+    /// `producer` - The `Producer` document information entry (the composer producer or PDF Composer).
+    pub producer: String,
 }
 
 /// This function populates a dictionary (BTreeMap) with a key-value pair.
-/// The key is a byte vector representation of the `yaml_entry` string,
-/// and the value is an `LopdfObject` containing a byte vector representation
-/// of the corresponding string value from the `string_values_btreemap`.
+/// The key is a byte vector representation of the `doc_info_entry` string,
+/// and the value is an `LopdfObject` typed for the entry from the
+/// corresponding string value in the `string_values_btreemap`.
 ///
 /// # Arguments
 ///
-/// * `yaml_entry` - A `String` representing the key for the dictionary entry.
+/// * `doc_info_entry` - A `String` representing the key for the dictionary entry.
+/// * `yaml_entry` - A `String` representing the YAML key the value is read from.
 /// * `string_values_btreemap` - A `BTreeMap<String, String>` containing the
 ///   string values to be used for populating the dictionary.
 ///
 /// # Returns
 ///
-/// A tuple containing:
-///
-/// * A `Vec<u8>` representing the key (byte vector of `yaml_entry`).
-/// * An `LopdfObject` containing a byte vector representation of the
-///   corresponding string value from `string_values_btreemap`, with a
-///   `StringFormat::Literal` format.
+/// * `Ok((Vec<u8>, LopdfObject))` with the key (byte vector of `doc_info_entry`)
+///   and the value, typed as the PDF specification has it for the entry: a name
+///   for `Trapped`, a date for `CreationDate` and `ModDate`, and a text string
+///   (PDFDocEncoding or UTF-16BE) otherwise (see `doc_info_value`).
+/// * `Err(message)` if the value cannot be written as the type of the entry
+///   (such as a `CreationDate` that is not a date).
 ///
 /// # Panics
 ///
 /// This function will panic if the `string_values_btreemap` does not contain
-/// a value for the lowercase version of the `yaml_entry` key.
+/// a value for the `yaml_entry` key.
 fn populate_dictionary(
+    doc_info_entry: String,
     yaml_entry: String,
-    string_values_btreemap: BTreeMap<String, String>,
-) -> (Vec<u8>, LopdfObject) {
-    // Convert the `yaml_entry` string to a byte vector to be used as the key
-    let key = yaml_entry.as_bytes().to_vec();
+    string_values_btreemap: &BTreeMap<String, String>,
+) -> Result<(Vec<u8>, LopdfObject), String> {
+    // Convert the `doc_info_entry` string to a byte vector to be used as the key
+    let key = doc_info_entry.as_bytes().to_vec();
 
-    // Get the value from the `string_values_btreemap` corresponding to the
-    // lowercase version of the `yaml_entry` key
+    // Get the value from the `string_values_btreemap` corresponding to the `yaml_entry` key
     // This will panic if the key is not found in the BTreeMap
-    let value_string = string_values_btreemap
-        .get(&yaml_entry.to_lowercase())
-        .unwrap();
-
-    // Convert the value string to a byte vector
-    let value_as_bytes: Vec<u8> = value_string.as_bytes().to_vec();
-
-    // Return a tuple containing the key (byte vector of `yaml_entry`) and
-    // an `LopdfObject` containing the byte vector representation of the value
-    // string, with a `StringFormat::Literal` format
-    (
-        key,
-        LopdfObject::String(value_as_bytes, StringFormat::Literal),
-    )
-}
+    let value_string = string_values_btreemap.get(&yaml_entry).unwrap();
 
-/// This function checks if a file is open (exclusively locked by another process).
-///
-/// # Arguments
-///
-/// * `file_path` - A string slice (`&str`) representing the path to the file.
-///
-/// # Returns
-///
-/// * `Ok(true)` if the file is exclusively locked by another process.
-/// * `Ok(false)` if the file is not exclusively locked and can be opened for writing.
-/// * `Err(e)` if an error occurs while attempting to open the file for any reason
-///   other than the file being exclusively locked (e.g., file not found, invalid
-///   path, etc.).
-///
-/// # Remarks
-///
-/// This function attempts to open the specified file for writing using
-/// `OpenOptions::new().write(true).open(file_path)`. If the file can be opened
-/// successfully, it means that the file is not exclusively locked by another process,
-/// so the function returns `Ok(false)`.
-///
-/// If the `open` operation fails with an `io::ErrorKind::PermissionDenied` error,
-/// it indicates that the file is exclusively locked by another process, so the
-/// function returns `Ok(true)`.
-///
-/// For any other error kind, the function propagates the error by returning `Err(e)`.
-fn is_file_open(file_path: &str) -> Result<bool, io::Error> {
-    match OpenOptions::new().write(true).open(file_path) {
-        Ok(_) => {
-            // The file was successfully opened, which means it's not exclusively locked by another process
-            Ok(false)
-        }
-        Err(error) => {
-            match error.kind() {
-                io::ErrorKind::PermissionDenied => {
-                    // The file is exclusively locked by another process
-                    Ok(true)
-                }
-                _ => Err(error),
-            }
-        }
-    }
+    // Return a tuple containing the key (byte vector of `doc_info_entry`) and
+    // an `LopdfObject` containing the value typed for the entry
+    doc_info_value(&doc_info_entry, value_string).map(|value| (key, value))
 }
 
 /// This function checks if a given entry (a key's value) exists in a `BTreeMap<String, String>`.
@@ -0,0 +1,276 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use regex::{Captures, Regex};
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::build_pdf::PDFBuilder;
+use crate::callouts::fenced_callouts_to_blockquotes;
+use crate::utils::{
+    merge_markdown_yaml, output_file_path, unescape_placeholders, write_atomically,
+};
+use pdf_composer_definitions::consts::{CHECK_MARK, CROSS_MARK};
+use pdf_composer_definitions::merge::MergeMode;
+use pdf_composer_definitions::output_format::{OutputFormat, TextFormat};
+
+/// This function writes the content of a document as plain text or normalized markdown, instead of
+/// printing it to PDF (for search indexes and other tools that go along with the PDFs).
+///
+/// # Arguments
+///
+/// * `generated_html` - A `String` containing the HTML generated from the markdown (for plain text).
+/// * `markdown_content` - The markdown of the document, without the front matter (for markdown).
+/// * `yaml_btreemap` - A `BTreeMap<String, Value>` containing the YAML data.
+/// * `instance_data` - The same settings the PDF would be built with (source_file, output_directory etc).
+/// * `text_format` - Whether plain text or markdown is written.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path of the text file, if it was successfully written.
+/// * `Err(e)` if the text file could not be written.
+///
+/// # Remarks
+///
+/// Plain text is made from the HTML, so shortcodes, cross-references and wiki links are resolved
+/// (images become their alt text, and charts, QR codes etc are left out). Markdown is the source
+/// markdown with the placeholders filled in, fenced callouts written as `> [!NOTE]` blockquotes,
+/// `\n` line endings, no trailing spaces (hard line breaks are written as `\`) and no runs of
+/// blank lines outside code blocks.
+pub fn build_text(
+    generated_html: &str,
+    markdown_content: &str,
+    yaml_btreemap: BTreeMap<String, Value>,
+    instance_data: PDFBuilder,
+    text_format: TextFormat,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let PDFBuilder {
+        source_file,
+        output_file_name: file_name,
+        output_directory,
+        quiet,
+        ..
+    } = instance_data;
+
+    let text = match text_format {
+        TextFormat::Plain => html_to_text(generated_html),
+        TextFormat::Markdown => {
+            let markdown =
+                unescape_placeholders(&merge_markdown_yaml(yaml_btreemap, markdown_content));
+            normalize_markdown(&fenced_callouts_to_blockquotes(&markdown))
+        }
+    };
+
+    let text_file_path = output_file_path(
+        &output_directory,
+        format!(
+            "{}.{}",
+            file_name,
+            OutputFormat::Text(text_format).extension()
+        ),
+    );
+    let text_file_path_as_string = text_file_path.display().to_string();
+    if let Err(error) =
+        write_atomically(&text_file_path, |writer| writer.write_all(text.as_bytes()))
+    {
+        eprintln!(
+            "\n{}{}\n{} {}",
+            CROSS_MARK.on_red(),
+            text_file_path_as_string.on_red(),
+            "Failed to save text document.".red(),
+            error
+        );
+        return Err(error.into());
+    }
+
+    if !quiet {
+        println!(
+            "\n{}{} → {}",
+            CHECK_MARK.to_string().green(),
+            source_file.green(),
+            text_file_path_as_string.yellow()
+        );
+    }
+
+    Ok(text_file_path)
+}
+
+/// This function joins the text written for each source file (chapter) into one text file, in
+/// merge mode.
+///
+/// # Arguments
+///
+/// * `merge_mode` - The merge mode, with the file name of the merged document.
+/// * `output_directory` - The directory the merged text file is written to.
+/// * `chapters` - The source file and text file of each chapter, in order.
+/// * `text_format` - Whether the chapters are plain text or markdown.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path of the merged text file.
+/// * `Err(e)` if a chapter could not be read or the merged text file could not be written.
+pub fn merge_text_chapters(
+    merge_mode: &MergeMode,
+    output_directory: &Path,
+    chapters: &[(PathBuf, PathBuf)],
+    text_format: TextFormat,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut texts: Vec<String> = Vec::with_capacity(chapters.len());
+    for (_source_file, chapter_text) in chapters {
+        texts.push(fs::read_to_string(chapter_text)?.trim_end().to_string());
+    }
+
+    let merged_file = output_file_path(
+        output_directory,
+        merge_mode.output_file_name(OutputFormat::Text(text_format)),
+    );
+    let merged_text = format!("{}\n", texts.join("\n\n"));
+    write_atomically(&merged_file, |writer| {
+        writer.write_all(merged_text.as_bytes())
+    })?;
+    Ok(merged_file)
+}
+
+/// Converts HTML to plain text: a blank line between blocks, a line per list item (starting with
+/// `- `) and table row (cells separated by tabs), and images as their alt text. Whitespace is
+/// collapsed, except in `<pre>` blocks.
+fn html_to_text(html: &str) -> String {
+    // Drawings and scripts have no text worth keeping
+    let hidden_regex =
+        Regex::new(r"(?s)<svg[\s>].*?</svg>|<script[\s>].*?</script>|<style[\s>].*?</style>")
+            .unwrap();
+    let pre_regex = Regex::new(r"(?is)<pre\b[^>]*>(.*?)</pre>").unwrap();
+    let tag_regex = Regex::new(r"<[^>]+>").unwrap();
+    let numeric_entity_regex = Regex::new(r"&#(x[0-9a-fA-F]+|[0-9]+);").unwrap();
+
+    let html = hidden_regex.replace_all(html, "");
+    let mut text = String::with_capacity(html.len());
+    let mut last_end = 0;
+    for captures in pre_regex.captures_iter(&html) {
+        let pre = captures.get(0).unwrap();
+        text.push_str(&flowing_text(&html[last_end..pre.start()]));
+        // Code blocks keep their line breaks and indentation
+        text.push_str("\n\n");
+        text.push_str(&tag_regex.replace_all(&captures[1], ""));
+        text.push_str("\n\n");
+        last_end = pre.end();
+    }
+    text.push_str(&flowing_text(&html[last_end..]));
+
+    let text = numeric_entity_regex.replace_all(&text, |captures: &Captures| {
+        let code = match captures[1].strip_prefix('x') {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => captures[1].parse().ok(),
+        };
+        code.and_then(char::from_u32)
+            .map_or(captures[0].to_string(), |character| character.to_string())
+    });
+    let text = text
+        .replace("&nbsp;", "\u{a0}")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&");
+
+    normalize_text(&text)
+}
+
+/// Converts HTML outside `<pre>` blocks to text, with the whitespace of the HTML collapsed and
+/// line breaks only where the blocks, list items, table rows and `<br>` elements end.
+fn flowing_text(html: &str) -> String {
+    let whitespace_regex = Regex::new(r"\s+").unwrap();
+    let image_regex = Regex::new(r#"<img\b[^>]*?\salt="([^"]*)"[^>]*>"#).unwrap();
+    let block_end_regex = Regex::new(
+        r"(?i)</(?:p|h[1-6]|blockquote|table|ol|ul|section|div|figure|figcaption|dl|dd|dt)>|<hr\b[^>]*>",
+    )
+    .unwrap();
+    let line_end_regex = Regex::new(r"(?i)</(?:li|tr)>|<br\b[^>]*>").unwrap();
+    let cell_end_regex = Regex::new(r"(?i) ?</t[dh]> ?").unwrap();
+    let list_item_regex = Regex::new(r"(?i)<li\b[^>]*>").unwrap();
+    let tag_regex = Regex::new(r"<[^>]+>").unwrap();
+    let line_space_regex = Regex::new(r"[ \t]*\n[ \t]*").unwrap();
+
+    let text = whitespace_regex.replace_all(html, " ");
+    let text = image_regex.replace_all(&text, "$1");
+    let text = block_end_regex.replace_all(&text, "\n\n");
+    let text = line_end_regex.replace_all(&text, "\n");
+    let text = cell_end_regex.replace_all(&text, "\t");
+    let text = list_item_regex.replace_all(&text, "- ");
+    let text = tag_regex.replace_all(&text, "");
+    line_space_regex
+        .replace_all(text.trim_start(), "\n")
+        .to_string()
+}
+
+/// Normalizes text: `\n` line endings, no trailing spaces, at most one blank line in a row, and a
+/// single newline at the end.
+fn normalize_text(text: &str) -> String {
+    let mut normalized = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !normalized.is_empty() {
+            normalized.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        normalized.push_str(line);
+        blank_lines = 0;
+    }
+    normalized.push('\n');
+    normalized
+}
+
+/// Normalizes markdown like `normalize_text`, leaving fenced code blocks as they are, and writing
+/// hard line breaks (two or more trailing spaces) as a trailing `\`.
+fn normalize_markdown(markdown: &str) -> String {
+    let mut normalized = String::with_capacity(markdown.len());
+    let mut blank_lines = 0;
+    let mut fence: Option<String> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let fence_marker: String = trimmed
+            .chars()
+            .take_while(|character| *character == '`' || *character == '~')
+            .collect();
+
+        if let Some(open_fence) = &fence {
+            // Inside a code block, lines are kept as they are
+            if fence_marker.len() >= open_fence.len()
+                && fence_marker.starts_with(&open_fence[..1])
+                && trimmed[fence_marker.len()..].trim().is_empty()
+            {
+                fence = None;
+            }
+            normalized.push('\n');
+            normalized.push_str(line);
+            continue;
+        }
+
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        // A code block starts with three or more backticks (or tildes)
+        if fence_marker.len() >= 3 && !(fence_marker.contains('`') && fence_marker.contains('~')) {
+            fence = Some(fence_marker);
+        }
+        if !normalized.is_empty() {
+            normalized.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        let hard_break = line.ends_with("  ");
+        normalized.push_str(line.trim_end());
+        if hard_break {
+            normalized.push('\\');
+        }
+        blank_lines = 0;
+    }
+    normalized.push('\n');
+    normalized
+}
@@ -0,0 +1,142 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+
+use crate::utils::escape_html;
+
+/// The styles for the callout boxes, added to the page CSS.
+pub const CALLOUT_CSS: &str = "
+.callout { margin: 1em 0; padding: 0.5em 1em; border: 1px solid var(--callout-color); \
+border-left-width: 4px; border-radius: 4px; break-inside: avoid; }
+.callout > p:last-child { margin-bottom: 0; }
+.callout-title { display: flex; align-items: center; gap: 0.4em; margin-top: 0; font-weight: bold; color: var(--callout-color); }
+.callout-title svg { width: 1.1em; height: 1.1em; flex: none; }
+.callout-note { --callout-color: #0969da; }
+.callout-tip { --callout-color: #1a7f37; }
+.callout-important { --callout-color: #8250df; }
+.callout-warning { --callout-color: #9a6700; }
+.callout-caution { --callout-color: #d1242f; }
+";
+
+/// The callout types: the marker (`[!NOTE]`, `:::note`), the default title and the icon (SVG path data).
+const CALLOUT_TYPES: [(&str, &str, &str); 5] = [
+    (
+        "note",
+        "Note",
+        "M8 0a8 8 0 1 1 0 16A8 8 0 0 1 8 0Zm-.75 7v5h1.5V7ZM8 3.5a1 1 0 1 0 0 2 1 1 0 0 0 0-2Z",
+    ),
+    (
+        "tip",
+        "Tip",
+        "M8 0a5.5 5.5 0 0 1 3.3 9.9c-.5.4-.8.9-.8 1.4V12h-5v-.7c0-.5-.3-1-.8-1.4A5.5 5.5 0 0 1 8 0ZM5.5 13.5h5V15a1 1 0 0 1-1 1h-3a1 1 0 0 1-1-1Z",
+    ),
+    (
+        "important",
+        "Important",
+        "M1.5 0h13A1.5 1.5 0 0 1 16 1.5v10a1.5 1.5 0 0 1-1.5 1.5H7l-4 3v-3H1.5A1.5 1.5 0 0 1 0 11.5v-10A1.5 1.5 0 0 1 1.5 0Zm5.75 2.5v5h1.5v-5Zm.75 6.5a1 1 0 1 0 0 2 1 1 0 0 0 0-2Z",
+    ),
+    (
+        "warning",
+        "Warning",
+        "M8 .5 16 15.5H0Zm-.75 5v5h1.5v-5ZM8 11.5a1 1 0 1 0 0 2 1 1 0 0 0 0-2Z",
+    ),
+    (
+        "caution",
+        "Caution",
+        "M4.7 0h6.6L16 4.7v6.6L11.3 16H4.7L0 11.3V4.7Zm2.55 3.5v5h1.5v-5Zm.75 6.5a1 1 0 1 0 0 2 1 1 0 0 0 0-2Z",
+    ),
+];
+
+/// This function rewrites `:::note` fenced callouts as GitHub style `> [!NOTE]` blockquotes.
+///
+/// # Arguments
+///
+/// * `markdown` - The markdown content of the source document.
+///
+/// # Returns
+///
+/// A `String` with the fenced callouts rewritten (and everything else unchanged).
+///
+/// # Remarks
+///
+/// A fenced callout starts with a `:::type` line (optionally followed by a title, for example
+/// `:::warning Mind the gap`) and ends with a `:::` line. Fenced code blocks are left alone.
+pub fn fenced_callouts_to_blockquotes(markdown: &str) -> String {
+    let opening_regex = Regex::new(r"^:::\s*([A-Za-z]+)\s*(.*?)\s*$").unwrap();
+
+    let mut output = String::with_capacity(markdown.len());
+    let mut code_fence: Option<&str> = None;
+    let mut in_callout = false;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(fence) = code_fence {
+            if trimmed.starts_with(fence) {
+                code_fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            code_fence = Some(&trimmed[..3]);
+        } else if in_callout && line.trim() == ":::" {
+            in_callout = false;
+            output.push('\n');
+            continue;
+        } else if let Some(captures) = opening_regex.captures(line).filter(|_| !in_callout) {
+            let kind = captures[1].to_lowercase();
+            if CALLOUT_TYPES.iter().any(|(marker, _, _)| *marker == kind) {
+                in_callout = true;
+                output.push_str(&format!("> [!{}] {}\n", kind.to_uppercase(), &captures[2]));
+                continue;
+            }
+        }
+
+        if in_callout {
+            output.push_str("> ");
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// This function turns `> [!NOTE]` style blockquotes in the generated HTML into callout boxes.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+///
+/// # Returns
+///
+/// A `String` with the callout blockquotes given a class, a title and an icon.
+///
+/// # Remarks
+///
+/// The callout types are `NOTE`, `TIP`, `IMPORTANT`, `WARNING` and `CAUTION` (in any case).
+/// Text after the marker, on the same line, replaces the default title.
+pub fn style_callouts(html: &str) -> String {
+    let callout_regex = Regex::new(
+        r"(?i)<blockquote>\s*<p>\[!(note|tip|important|warning|caution)\][ \t]*([^\n<]*)(\n|</p>\s*)",
+    )
+    .unwrap();
+
+    callout_regex
+        .replace_all(html, |captures: &Captures| {
+            let kind = captures[1].to_lowercase();
+            let (_, default_title, icon) = CALLOUT_TYPES
+                .iter()
+                .find(|(marker, _, _)| *marker == kind)
+                .unwrap();
+            let title = match captures[2].trim() {
+                "" => escape_html(default_title),
+                title => title.to_string(),
+            };
+            // The rest of the first paragraph (if any) continues in a new paragraph
+            let rest = if &captures[3] == "\n" { "<p>" } else { "" };
+            format!(
+                "<blockquote class=\"callout callout-{}\">\n<p class=\"callout-title\">\
+                 <svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 16 16\" aria-hidden=\"true\"><path fill=\"currentColor\" d=\"{}\"/></svg>\
+                 {}</p>\n{}",
+                kind, icon, title, rest
+            )
+        })
+        .to_string()
+}
@@ -0,0 +1,190 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::xref::XrefType;
+use lopdf::{dictionary, Dictionary, Document, Object as LopdfObject};
+use std::collections::BTreeMap;
+
+use crate::utils::encode_pdf_text_string;
+use pdf_composer_definitions::page_numbering::PageLabelRange;
+use pdf_composer_definitions::pdf_version::PDFVersion;
+use pdf_composer_definitions::viewer_preferences::ViewerPreferences;
+
+/// This function sets the PDF version of the document, and how it is written to match.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `pdf_version` - The `PDFVersion` the document is saved as.
+///
+/// # Remarks
+///
+/// The version is written to the file header. A `/Version` entry in the document catalog would
+/// override the header, so it is removed. Versions before 1.5 have no cross-reference streams, so
+/// those documents are saved with a classic cross-reference table.
+pub fn set_pdf_version(doc: &mut Document, pdf_version: PDFVersion) {
+    doc.version = pdf_version.to_string();
+    doc.reference_table.cross_reference_type = if pdf_version.supports_cross_reference_streams() {
+        XrefType::CrossReferenceStream
+    } else {
+        XrefType::CrossReferenceTable
+    };
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.remove(b"Version");
+    }
+}
+
+/// This function sets the natural language of the document (`/Lang`) in the document catalog.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `language` - A BCP 47 language tag (for example, `en-GB`).
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the document has no catalog.
+///
+/// # Remarks
+///
+/// PDF readers, screen readers and accessibility checkers (PDF/UA) read the language from the
+/// catalog, not from custom document information entries.
+pub fn set_catalog_language(doc: &mut Document, language: &str) -> Result<(), lopdf::Error> {
+    doc.catalog_mut()?
+        .set("Lang", encode_pdf_text_string(language));
+    Ok(())
+}
+
+/// This function sets a single entry in the `/ViewerPreferences` dictionary of the document catalog,
+/// keeping any entries already there.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `key` - The viewer preference to set (for example, `Direction`).
+/// * `value` - The `LopdfObject` value for the viewer preference.
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the document has no catalog.
+pub fn set_viewer_preference(
+    doc: &mut Document,
+    key: &str,
+    value: LopdfObject,
+) -> Result<(), lopdf::Error> {
+    let catalog = doc.catalog_mut()?;
+    let mut viewer_preferences = match catalog.get(b"ViewerPreferences") {
+        Ok(LopdfObject::Dictionary(dictionary)) => dictionary.clone(),
+        _ => Dictionary::new(),
+    };
+    viewer_preferences.set(key, value);
+    catalog.set("ViewerPreferences", viewer_preferences);
+    Ok(())
+}
+
+/// This function writes how PDF viewers show the document when it is opened into the document
+/// catalog: the page layout (`/PageLayout`), the panel shown (`/PageMode`) and the viewer
+/// preferences (`/ViewerPreferences`, such as `Direction`, `FitWindow` and `HideToolbar`).
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `preferences` - The `ViewerPreferences` to write.
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the document has no catalog.
+///
+/// # Remarks
+///
+/// Only the options that are set are written; viewer preferences already in the catalog (such as
+/// `Direction` for right-to-left documents) are kept, unless the `direction` is set.
+pub fn set_viewer_preferences(
+    doc: &mut Document,
+    preferences: &ViewerPreferences,
+) -> Result<(), lopdf::Error> {
+    let flags = [
+        ("FitWindow", preferences.fit),
+        ("CenterWindow", preferences.center_window),
+        ("HideToolbar", preferences.hide_toolbar),
+        ("HideMenubar", preferences.hide_menubar),
+        ("HideWindowUI", preferences.hide_window_ui),
+        ("DisplayDocTitle", preferences.display_doc_title),
+    ];
+    for (key, _) in flags.iter().filter(|(_, value)| *value) {
+        set_viewer_preference(doc, key, LopdfObject::Boolean(true))?;
+    }
+    if let Some(direction) = preferences.direction {
+        set_viewer_preference(
+            doc,
+            "Direction",
+            LopdfObject::Name(direction.pdf_name().as_bytes().to_vec()),
+        )?;
+    }
+
+    let catalog = doc.catalog_mut()?;
+    if let Some(page_layout) = preferences.page_layout {
+        catalog.set("PageLayout", page_layout.pdf_name());
+    }
+    if let Some(page_mode) = preferences.page_mode {
+        catalog.set("PageMode", page_mode.pdf_name());
+    }
+    Ok(())
+}
+
+/// This function sets the page labels (`/PageLabels`) in the document catalog, so PDF viewers show
+/// the page numbers printed on the pages (i, ii, iii, 1, 2, 3), or labels such as `Cover`, rather
+/// than the physical page numbers.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `ranges` - The page label ranges (or page numbering sections, see `PageLabelRange::from`).
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the document has no catalog.
+///
+/// # Remarks
+///
+/// Ranges starting after the last page are left out. If no range starts on the first page,
+/// the pages before the first range are labelled with decimal numbers.
+pub fn set_page_labels(doc: &mut Document, ranges: &[PageLabelRange]) -> Result<(), lopdf::Error> {
+    let page_count = doc.get_pages().len() as u32;
+
+    // Page label ranges are keyed by page index (starting at 0); a later range replaces
+    // an earlier one starting on the same page
+    let mut labels: BTreeMap<u32, Dictionary> = BTreeMap::new();
+    labels.insert(0, dictionary! { "S" => "D" });
+    for range in ranges
+        .iter()
+        .filter(|range| range.start_page >= 1 && range.start_page <= page_count)
+    {
+        // Without a numbering style, the label is the prefix alone
+        let mut label = Dictionary::new();
+        if let Some(style) = range.style {
+            label.set("S", style.pdf_name());
+            if range.first_number != 1 {
+                label.set("St", range.first_number as i64);
+            }
+        }
+        if !range.prefix.is_empty() {
+            label.set("P", encode_pdf_text_string(&range.prefix));
+        }
+        labels.insert(range.start_page - 1, label);
+    }
+
+    let mut nums: Vec<LopdfObject> = Vec::with_capacity(labels.len() * 2);
+    for (page_index, label) in labels {
+        nums.push((page_index as i64).into());
+        nums.push(label.into());
+    }
+
+    doc.catalog_mut()?
+        .set("PageLabels", dictionary! { "Nums" => nums });
+    Ok(())
+}
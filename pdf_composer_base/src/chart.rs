@@ -0,0 +1,290 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use serde_yml::Value;
+use std::f64::consts::PI;
+
+use crate::shortcodes::{ShortcodeArguments, ShortcodeContext, ShortcodeOutput};
+use crate::utils::escape_html;
+
+/// The width of the chart drawing (the SVG is scaled to the `width` argument).
+const CHART_WIDTH: f64 = 600.0;
+/// The height of the chart drawing.
+const CHART_HEIGHT: f64 = 320.0;
+/// The space around the plot area of bar and line charts: left, right, top and bottom.
+const PLOT_MARGINS: [f64; 4] = [56.0, 20.0, 36.0, 44.0];
+/// The width of a chart shortcode when no `width` is given.
+const DEFAULT_CHART_WIDTH: &str = "15cm";
+/// The colours of the bars, lines and pie slices, in order.
+const CHART_COLOURS: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+/// This function renders the `{{chart bar sales}}` shortcode as an SVG chart of front matter data.
+///
+/// # Arguments
+///
+/// * `arguments` - The shortcode arguments: the chart type (`bar`, `line` or `pie`), the front matter
+///   key holding the data, an optional `title` and an optional `width` (any CSS length, `15cm` by default).
+/// * `context` - The document the shortcode is in.
+///
+/// # Returns
+///
+/// * `Ok(ShortcodeOutput)` with the SVG.
+/// * `Err(message)` if the chart type is unknown or the front matter data is missing or not numbers.
+///
+/// # Remarks
+///
+/// The data is either a mapping of labels to numbers (kept in the order written) or a list of numbers
+/// (labelled 1, 2, 3 and so on).
+pub fn chart_shortcode(
+    arguments: &ShortcodeArguments,
+    context: &ShortcodeContext,
+) -> Result<ShortcodeOutput, String> {
+    let chart_type = arguments.required(0, "chart type (bar, line or pie)")?;
+    let key = arguments.required(1, "front matter key of the chart data")?;
+    let width = arguments
+        .named
+        .get("width")
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_CHART_WIDTH);
+    let title = arguments.named.get("title").map(String::as_str);
+
+    let data = context
+        .front_matter
+        .get(key)
+        .ok_or_else(|| format!("no {} in the front matter", key))?;
+    let data = chart_data(data).ok_or_else(|| {
+        format!(
+            "{} must be a mapping of labels to numbers, or a list of numbers",
+            key
+        )
+    })?;
+    if data.is_empty() {
+        return Err(format!("{} has no data", key));
+    }
+
+    let content = match chart_type.to_lowercase().as_str() {
+        "bar" => axis_chart(&data, false),
+        "line" => axis_chart(&data, true),
+        "pie" => pie_chart(&data)?,
+        _ => return Err(format!("unknown chart type {}", chart_type)),
+    };
+
+    let title_element = title.map_or(String::new(), |title| {
+        format!(
+            "<text x=\"{}\" y=\"22\" font-size=\"16\" font-weight=\"bold\" text-anchor=\"middle\">{}</text>",
+            CHART_WIDTH / 2.0,
+            escape_html(title)
+        )
+    });
+
+    Ok(ShortcodeOutput {
+        html: format!(
+            "<svg class=\"chart chart-{kind}\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" \
+             style=\"width: {width}; max-width: 100%\" font-size=\"12\" role=\"img\">\
+             <title>{alt}</title>{title}{content}</svg>",
+            kind = escape_html(&chart_type.to_lowercase()),
+            w = CHART_WIDTH,
+            h = CHART_HEIGHT,
+            width = escape_html(width),
+            alt = escape_html(title.unwrap_or(key)),
+            title = title_element,
+            content = content
+        ),
+        block: true,
+    })
+}
+
+/// Reads the chart labels and values from the front matter data.
+fn chart_data(data: &Value) -> Option<Vec<(String, f64)>> {
+    match data {
+        Value::Mapping(mapping) => mapping
+            .iter()
+            .map(|(label, value)| Some((value_label(label)?, value.as_f64()?)))
+            .collect(),
+        Value::Sequence(values) => values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| Some(((index + 1).to_string(), value.as_f64()?)))
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Returns the label of a mapping key (labels such as years are numbers in YAML).
+fn value_label(label: &Value) -> Option<String> {
+    match label {
+        Value::String(label) => Some(label.to_string()),
+        Value::Number(label) => Some(label.to_string()),
+        Value::Bool(label) => Some(label.to_string()),
+        _ => None,
+    }
+}
+
+/// Draws a bar chart, or a line chart, with a value axis and a label under each bar or point.
+fn axis_chart(data: &[(String, f64)], line: bool) -> String {
+    let [left, right, top, bottom] = PLOT_MARGINS;
+    let plot_width = CHART_WIDTH - left - right;
+    let plot_height = CHART_HEIGHT - top - bottom;
+
+    let (axis_minimum, axis_maximum, step) = axis_range(data);
+    let y = |value: f64| top + plot_height * (axis_maximum - value) / (axis_maximum - axis_minimum);
+
+    let mut svg = String::new();
+    // Grid lines and the value axis labels
+    let mut tick = axis_minimum;
+    while tick <= axis_maximum + step / 2.0 {
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{y:.1}\" x2=\"{}\" y2=\"{y:.1}\" stroke=\"{}\"/>\
+             <text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            left,
+            CHART_WIDTH - right,
+            if tick == 0.0 { "#888" } else { "#ddd" },
+            left - 6.0,
+            y(tick) + 4.0,
+            format_number(tick),
+            y = y(tick)
+        ));
+        tick += step;
+    }
+
+    let slot = plot_width / data.len() as f64;
+    let mut points: Vec<(f64, f64)> = Vec::new();
+    for (index, (label, value)) in data.iter().enumerate() {
+        let centre = left + slot * (index as f64 + 0.5);
+        if line {
+            points.push((centre, y(*value)));
+        } else {
+            let bar_top = y(value.max(0.0));
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                centre - slot * 0.3,
+                bar_top,
+                slot * 0.6,
+                y(value.min(0.0)) - bar_top,
+                CHART_COLOURS[0]
+            ));
+        }
+        // Value above the bar or point (below it for negative values)
+        let value_y = if *value < 0.0 {
+            y(*value) + 14.0
+        } else {
+            y(*value) - 6.0
+        };
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\" text-anchor=\"middle\">{}</text>\
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            centre,
+            value_y,
+            format_number(*value),
+            centre,
+            CHART_HEIGHT - bottom + 18.0,
+            escape_html(label)
+        ));
+    }
+
+    if line {
+        svg.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+            points
+                .iter()
+                .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                .collect::<Vec<String>>()
+                .join(" "),
+            CHART_COLOURS[0]
+        ));
+        for (x, y) in &points {
+            svg.push_str(&format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3.5\" fill=\"{}\"/>",
+                x, y, CHART_COLOURS[0]
+            ));
+        }
+    }
+
+    svg
+}
+
+/// Draws a pie chart with a legend giving each label and its share.
+fn pie_chart(data: &[(String, f64)]) -> Result<String, String> {
+    if data.iter().any(|(_, value)| *value < 0.0) {
+        return Err("pie chart values cannot be negative".to_string());
+    }
+    let total: f64 = data.iter().map(|(_, value)| value).sum();
+    if total <= 0.0 {
+        return Err("pie chart values add up to zero".to_string());
+    }
+
+    let (centre_x, centre_y, radius) = (170.0, 178.0, 124.0);
+    let mut svg = String::new();
+    let mut angle = -PI / 2.0;
+    for (index, (label, value)) in data.iter().enumerate() {
+        let colour = CHART_COLOURS[index % CHART_COLOURS.len()];
+        let sweep = value / total * 2.0 * PI;
+        if sweep >= 2.0 * PI - f64::EPSILON {
+            svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+                centre_x, centre_y, radius, colour
+            ));
+        } else if sweep > 0.0 {
+            svg.push_str(&format!(
+                "<path d=\"M{} {}L{:.2} {:.2}A{r} {r} 0 {} 1 {:.2} {:.2}Z\" fill=\"{}\" stroke=\"#fff\"/>",
+                centre_x,
+                centre_y,
+                centre_x + radius * angle.cos(),
+                centre_y + radius * angle.sin(),
+                u8::from(sweep > PI),
+                centre_x + radius * (angle + sweep).cos(),
+                centre_y + radius * (angle + sweep).sin(),
+                colour,
+                r = radius
+            ));
+        }
+        angle += sweep;
+
+        let legend_y = 60.0 + index as f64 * 22.0;
+        svg.push_str(&format!(
+            "<rect x=\"340\" y=\"{}\" width=\"14\" height=\"14\" fill=\"{}\"/>\
+             <text x=\"362\" y=\"{}\">{} ({}%)</text>",
+            legend_y,
+            colour,
+            legend_y + 11.0,
+            escape_html(label),
+            format_number((value / total * 1000.0).round() / 10.0)
+        ));
+    }
+
+    Ok(svg)
+}
+
+/// Returns the value axis minimum, maximum and step, at round numbers and always including zero.
+fn axis_range(data: &[(String, f64)]) -> (f64, f64, f64) {
+    let minimum = data.iter().map(|(_, value)| *value).fold(0.0, f64::min);
+    let maximum = data.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+    if maximum == minimum {
+        return (0.0, 1.0, 0.25);
+    }
+
+    // Around five grid lines, at 1, 2, 2.5 or 5 times a power of ten
+    let rough_step = (maximum - minimum) / 5.0;
+    let magnitude = 10f64.powf(rough_step.log10().floor());
+    let step = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .iter()
+        .map(|multiple| multiple * magnitude)
+        .find(|step| *step >= rough_step)
+        .unwrap_or(10.0 * magnitude);
+
+    (
+        (minimum / step).floor() * step,
+        (maximum / step).ceil() * step,
+        step,
+    )
+}
+
+/// Formats a number for a label, without floating point noise or a trailing `.0`.
+fn format_number(number: f64) -> String {
+    let rounded = (number * 1_000_000.0).round() / 1_000_000.0;
+    // Avoid showing -0
+    format!("{}", rounded + 0.0)
+}
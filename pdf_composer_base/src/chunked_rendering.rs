@@ -0,0 +1,397 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId};
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::destinations::{named_destinations, set_named_destinations};
+use crate::page_tree::{page_with_inherited_attributes, rebuild_page_tree};
+
+/// This function splits the HTML of a document into chunks of whole sections, each starting at an
+/// H1 heading, to be rendered one at a time.
+///
+/// # Arguments
+///
+/// * `html` - The HTML of the document body.
+/// * `sections_per_chunk` - The number of H1 sections in each chunk (at least 1).
+///
+/// # Returns
+///
+/// The HTML of each chunk, in order. A document with no more sections than `sections_per_chunk`
+/// is a single chunk.
+///
+/// # Remarks
+///
+/// Only H1 headings outside any block (list, table, `div` etc) start a section, so a chunk never
+/// cuts through an element. Anything before the first heading goes with the first section.
+/// Chromium only writes the named destinations that links in the page point to, so the elements
+/// each chunk has that other chunks link to get a hidden link from their own chunk too.
+pub fn split_into_chunks(html: &str, sections_per_chunk: usize) -> Vec<String> {
+    let tag_regex = Regex::new(
+        r"(?i)<(/?)(h1|div|section|article|aside|nav|header|footer|blockquote|details|figure|table|ul|ol|dl)\b[^>]*>",
+    )
+    .unwrap();
+
+    let mut depth: usize = 0;
+    let mut section_starts: Vec<usize> = Vec::new();
+    for captures in tag_regex.captures_iter(html) {
+        let is_closing = !captures[1].is_empty();
+        if captures[2].eq_ignore_ascii_case("h1") {
+            if !is_closing && depth == 0 {
+                section_starts.push(captures.get(0).unwrap().start());
+            }
+        } else if is_closing {
+            depth = depth.saturating_sub(1);
+        } else {
+            depth += 1;
+        }
+    }
+
+    let mut chunk_starts: Vec<usize> = section_starts
+        .into_iter()
+        .skip(sections_per_chunk.max(1))
+        .step_by(sections_per_chunk.max(1))
+        .collect();
+    chunk_starts.insert(0, 0);
+    chunk_starts.push(html.len());
+    let chunks: Vec<&str> = chunk_starts
+        .windows(2)
+        .map(|bounds| &html[bounds[0]..bounds[1]])
+        .collect();
+    if chunks.len() == 1 {
+        return vec![html.to_string()];
+    }
+
+    let id_regex = Regex::new(r#"\sid="([^"]+)""#).unwrap();
+    let link_regex = Regex::new(r##"href="#([^"]+)""##).unwrap();
+    let ids: Vec<BTreeSet<&str>> = chunks
+        .iter()
+        .map(|chunk| {
+            id_regex
+                .captures_iter(chunk)
+                .map(|captures| captures.get(1).unwrap().as_str())
+                .collect()
+        })
+        .collect();
+    let link_targets: Vec<BTreeSet<&str>> = chunks
+        .iter()
+        .map(|chunk| {
+            link_regex
+                .captures_iter(chunk)
+                .map(|captures| captures.get(1).unwrap().as_str())
+                .collect()
+        })
+        .collect();
+
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let linked_from_elsewhere: Vec<&str> = ids[index]
+                .iter()
+                .filter(|id| {
+                    link_targets
+                        .iter()
+                        .enumerate()
+                        .any(|(other, targets)| other != index && targets.contains(*id))
+                })
+                .copied()
+                .collect();
+            if linked_from_elsewhere.is_empty() {
+                return chunk.to_string();
+            }
+            let links: String = linked_from_elsewhere
+                .iter()
+                .map(|id| format!("<a href=\"#{}\"></a>", id))
+                .collect();
+            format!("{}<div style=\"display: none\">{}</div>", chunk, links)
+        })
+        .collect()
+}
+
+/// This function stitches the PDFs rendered for the chunks of a document into one PDF.
+///
+/// # Arguments
+///
+/// * `chunks` - The lopdf `Document` of each chunk, in order (at least one).
+///
+/// # Returns
+///
+/// * `Ok(Document)` with the pages of all the chunks.
+/// * `Err(e)` if there are no chunks or a chunk has no catalog.
+///
+/// # Remarks
+///
+/// The catalog of the first chunk is kept. The outline items, tagged structure and named
+/// destinations of the other chunks are added to those of the first chunk, so the result reads as
+/// if the document had been rendered in one go.
+pub fn stitch_chunks(chunks: Vec<Document>) -> Result<Document, Box<dyn std::error::Error>> {
+    let mut chunks = chunks.into_iter();
+    let mut doc = chunks.next().ok_or("no chunks to stitch")?;
+
+    let mut pages: Vec<(ObjectId, Dictionary)> = Vec::new();
+    for page_id in doc.get_pages().into_values() {
+        pages.push((page_id, page_with_inherited_attributes(&doc, page_id)?));
+    }
+    let mut destinations = named_destinations(&doc);
+
+    for mut chunk in chunks {
+        // Give the chunk objects ids that are not used in the document
+        chunk.renumber_objects_with(doc.max_id + 1);
+        doc.max_id = chunk.max_id;
+
+        // The structure parents of the chunk follow on from those of the document
+        let parent_tree_offset = next_parent_tree_key(&doc);
+        offset_struct_parents(&mut chunk, parent_tree_offset);
+
+        for (name, destination) in named_destinations(&chunk) {
+            destinations.entry(name).or_insert(destination);
+        }
+        for page_id in chunk.get_pages().into_values() {
+            pages.push((page_id, page_with_inherited_attributes(&chunk, page_id)?));
+        }
+
+        let chunk_catalog = chunk.catalog()?.clone();
+        let chunk_catalog_id = chunk.trailer.get(b"Root")?.as_reference()?;
+        for (object_id, object) in chunk.objects {
+            let is_page_tree_node = matches!(object.type_name(), Ok("Pages") | Ok("Page"));
+            if object_id != chunk_catalog_id && !is_page_tree_node {
+                doc.objects.insert(object_id, object);
+            }
+        }
+
+        if let Ok(outlines_id) = chunk_catalog
+            .get(b"Outlines")
+            .and_then(LopdfObject::as_reference)
+        {
+            append_outline(&mut doc, outlines_id)?;
+        }
+        if let Ok(struct_tree_root_id) = chunk_catalog
+            .get(b"StructTreeRoot")
+            .and_then(LopdfObject::as_reference)
+        {
+            append_struct_tree(&mut doc, struct_tree_root_id, parent_tree_offset)?;
+        }
+    }
+
+    rebuild_page_tree(&mut doc, pages)?;
+    set_named_destinations(&mut doc, &destinations)?;
+
+    // Drop the old page tree nodes, catalogs and outline and structure roots of the chunks
+    doc.prune_objects();
+    Ok(doc)
+}
+
+/// Adds the top level items of an outline (of a chunk) after the outline items of the document.
+fn append_outline(doc: &mut Document, outlines_id: ObjectId) -> Result<(), lopdf::Error> {
+    let outlines = doc.get_dictionary(outlines_id)?.clone();
+    let (Ok(first_id), Ok(last_id)) = (
+        outlines.get(b"First").and_then(LopdfObject::as_reference),
+        outlines.get(b"Last").and_then(LopdfObject::as_reference),
+    ) else {
+        return Ok(());
+    };
+
+    let Ok(document_outlines_id) = doc
+        .catalog()?
+        .get(b"Outlines")
+        .and_then(LopdfObject::as_reference)
+    else {
+        doc.catalog_mut()?.set("Outlines", outlines_id);
+        return Ok(());
+    };
+
+    // Guard against cyclic outlines in malformed documents
+    let mut item_id = Some(first_id);
+    let mut seen: BTreeSet<ObjectId> = BTreeSet::new();
+    while let Some(id) = item_id.filter(|id| seen.insert(*id)) {
+        let item = doc.get_dictionary_mut(id)?;
+        item.set("Parent", document_outlines_id);
+        item_id = item.get(b"Next").and_then(LopdfObject::as_reference).ok();
+    }
+
+    let document_outlines = doc.get_dictionary(document_outlines_id)?;
+    let previous_last_id = document_outlines
+        .get(b"Last")
+        .and_then(LopdfObject::as_reference)
+        .ok();
+    let count = document_outlines
+        .get(b"Count")
+        .and_then(LopdfObject::as_i64)
+        .unwrap_or(0)
+        + outlines
+            .get(b"Count")
+            .and_then(LopdfObject::as_i64)
+            .unwrap_or(0);
+
+    match previous_last_id {
+        Some(previous_last_id) => {
+            doc.get_dictionary_mut(previous_last_id)?
+                .set("Next", first_id);
+            doc.get_dictionary_mut(first_id)?
+                .set("Prev", previous_last_id);
+        }
+        None => doc
+            .get_dictionary_mut(document_outlines_id)?
+            .set("First", first_id),
+    }
+    let document_outlines = doc.get_dictionary_mut(document_outlines_id)?;
+    document_outlines.set("Last", last_id);
+    document_outlines.set("Count", count);
+    Ok(())
+}
+
+/// Returns the first free key in the structure parent tree of the document (0 if it has none).
+fn next_parent_tree_key(doc: &Document) -> i64 {
+    let Some(struct_tree_root) = struct_tree_root(doc) else {
+        return 0;
+    };
+    let highest_key = struct_tree_root
+        .get(b"ParentTree")
+        .ok()
+        .and_then(|parent_tree| {
+            number_tree_entries(doc, parent_tree, 0)
+                .last_key_value()
+                .map(|(key, _value)| *key)
+        })
+        .unwrap_or(-1);
+    let next_key = struct_tree_root
+        .get(b"ParentTreeNextKey")
+        .and_then(LopdfObject::as_i64)
+        .unwrap_or(0);
+    next_key.max(highest_key + 1)
+}
+
+/// Moves the `StructParents` of the pages and `StructParent` of the annotations (and other
+/// objects) of a chunk along by `offset`.
+fn offset_struct_parents(chunk: &mut Document, offset: i64) {
+    if offset == 0 {
+        return;
+    }
+    for object in chunk.objects.values_mut() {
+        let dictionary = match object {
+            LopdfObject::Dictionary(dictionary) => dictionary,
+            LopdfObject::Stream(stream) => &mut stream.dict,
+            _ => continue,
+        };
+        for key in [b"StructParents".as_slice(), b"StructParent".as_slice()] {
+            if let Ok(value) = dictionary.get(key).and_then(LopdfObject::as_i64) {
+                dictionary.set(key, value + offset);
+            }
+        }
+    }
+}
+
+/// Adds the structure elements of a structure tree (of a chunk) after those of the document, and
+/// its parent tree entries (already moved along by `parent_tree_offset`) to the document.
+fn append_struct_tree(
+    doc: &mut Document,
+    struct_tree_root_id: ObjectId,
+    parent_tree_offset: i64,
+) -> Result<(), lopdf::Error> {
+    let chunk_root = doc.get_dictionary(struct_tree_root_id)?.clone();
+    let Ok(document_root_id) = doc
+        .catalog()?
+        .get(b"StructTreeRoot")
+        .and_then(LopdfObject::as_reference)
+    else {
+        // Only tagged documents are stitched with a structure tree
+        return Ok(());
+    };
+
+    let chunk_kids = as_array(chunk_root.get(b"K").ok());
+    for kid in &chunk_kids {
+        if let Ok(LopdfObject::Dictionary(element)) = kid
+            .as_reference()
+            .and_then(|kid_id| doc.get_object_mut(kid_id))
+        {
+            element.set("P", document_root_id);
+        }
+    }
+    let mut entries = doc
+        .get_dictionary(document_root_id)?
+        .get(b"ParentTree")
+        .ok()
+        .map(|parent_tree| number_tree_entries(doc, parent_tree, 0))
+        .unwrap_or_default();
+    if let Ok(parent_tree) = chunk_root.get(b"ParentTree") {
+        entries.extend(
+            number_tree_entries(doc, parent_tree, 0)
+                .into_iter()
+                .map(|(key, value)| (key + parent_tree_offset, value)),
+        );
+    }
+    let next_key = entries.last_key_value().map_or(0, |(key, _value)| key + 1);
+    let nums: Vec<LopdfObject> = entries
+        .into_iter()
+        .flat_map(|(key, value)| [LopdfObject::Integer(key), value])
+        .collect();
+    let mut parent_tree = Dictionary::new();
+    parent_tree.set("Nums", nums);
+    let parent_tree_id = doc.add_object(parent_tree);
+
+    let document_root = doc.get_dictionary_mut(document_root_id)?;
+    let mut kids = as_array(document_root.get(b"K").ok());
+    kids.extend(chunk_kids);
+    document_root.set("K", kids);
+    document_root.set("ParentTree", parent_tree_id);
+    document_root.set("ParentTreeNextKey", next_key);
+    if let Ok(LopdfObject::Dictionary(role_map)) = chunk_root.get(b"RoleMap") {
+        if !document_root.has(b"RoleMap") {
+            document_root.set("RoleMap", role_map.clone());
+        }
+    }
+    Ok(())
+}
+
+/// Returns the structure tree root of the document.
+fn struct_tree_root(doc: &Document) -> Option<&Dictionary> {
+    let root_id = doc
+        .catalog()
+        .ok()?
+        .get(b"StructTreeRoot")
+        .and_then(LopdfObject::as_reference)
+        .ok()?;
+    doc.get_dictionary(root_id).ok()
+}
+
+/// Returns the entries of a number tree (such as the structure parent tree), following its `Kids`.
+fn number_tree_entries(
+    doc: &Document,
+    node: &LopdfObject,
+    depth: u8,
+) -> BTreeMap<i64, LopdfObject> {
+    let mut entries = BTreeMap::new();
+    let node = match node {
+        LopdfObject::Reference(node_id) => doc.get_dictionary(*node_id).ok(),
+        LopdfObject::Dictionary(node) => Some(node),
+        _ => None,
+    };
+    // Guard against cyclic number trees in malformed documents
+    let Some(node) = node.filter(|_| depth < 32) else {
+        return entries;
+    };
+    if let Ok(nums) = node.get(b"Nums").and_then(LopdfObject::as_array) {
+        for pair in nums.chunks_exact(2) {
+            if let Ok(key) = pair[0].as_i64() {
+                entries.insert(key, pair[1].clone());
+            }
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(LopdfObject::as_array) {
+        for kid in kids {
+            entries.extend(number_tree_entries(doc, kid, depth + 1));
+        }
+    }
+    entries
+}
+
+/// Returns an object as an array: the items of an array, or the object on its own.
+fn as_array(object: Option<&LopdfObject>) -> Vec<LopdfObject> {
+    match object {
+        Some(LopdfObject::Array(items)) => items.clone(),
+        Some(object) => vec![object.clone()],
+        None => Vec::new(),
+    }
+}
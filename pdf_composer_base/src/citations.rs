@@ -0,0 +1,288 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use hayagriva::archive::{locales, ArchivedStyle};
+use hayagriva::citationberg::json::Item as CslJsonItem;
+use hayagriva::citationberg::{IndependentStyle, Style};
+use hayagriva::{
+    BibliographyDriver, BibliographyRequest, BufWriteFormat, CitationItem, CitationRequest,
+    ElemChildren, Entry, Rendered,
+};
+use regex::Regex;
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::utils::{escape_html, map_outside_code};
+use pdf_composer_definitions::consts::CROSS_MARK;
+
+/// The citation style used when the front matter does not choose one.
+const DEFAULT_CITATION_STYLE: &str = "apa";
+/// The bibliography section title used when the front matter does not set one.
+const DEFAULT_BIBLIOGRAPHY_TITLE: &str = "References";
+
+/// Renders the citations (in order) and the bibliography for one kind of bibliography entry.
+/// (hayagriva's entry trait is not public, so this cannot be a generic function.)
+macro_rules! render_citations {
+    ($entries:expr, $groups:expr, $style:expr, $locales:expr) => {{
+        let mut driver = BibliographyDriver::new();
+        for group in $groups {
+            let items = group
+                .iter()
+                .map(|key| CitationItem::with_entry($entries[key.as_str()]))
+                .collect();
+            driver.citation(CitationRequest::from_items(items, $style, $locales));
+        }
+        driver.finish(BibliographyRequest::new($style, None, $locales))
+    }};
+}
+
+/// This function resolves `[@key]` (and `[@key1; @key2]`) citations in the generated HTML against
+/// the bibliography file named in the YAML Front Matter, and appends the bibliography.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+/// * `yaml_btreemap` - The YAML Front Matter of the document.
+/// * `source_directory` - The directory of the source document (the bibliography path is relative to it).
+///
+/// # Returns
+///
+/// A `String` with the citations rendered and the bibliography appended. The HTML is returned
+/// unchanged if the front matter has no `bibliography` entry or the bibliography cannot be loaded.
+///
+/// # Remarks
+///
+/// The front matter entries used are:
+///
+/// * `bibliography` - Path to a BibTeX/BibLaTeX (`.bib`) or CSL-JSON (`.json`) file.
+/// * `csl` - The citation style: the name of a bundled style (for example, `apa`, `ieee`,
+///   `chicago-author-date`) or the path to a `.csl` file. Defaults to `apa`.
+/// * `reference-section-title` - The title of the bibliography section. Defaults to `References`.
+///
+/// Citations inside code are left alone, and citations of unknown keys are left as written.
+pub fn process_citations(
+    html: &str,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    source_directory: &Path,
+) -> String {
+    let Some(bibliography_path) = yaml_btreemap
+        .get("bibliography")
+        .and_then(|value| value.as_str())
+    else {
+        return html.to_string();
+    };
+
+    let citation_regex = Regex::new(r"\[(@[\w:.\-/]+(?:\s*;\s*@[\w:.\-/]+)*)\]").unwrap();
+
+    // Collect the citation groups in document order
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    map_outside_code(html, |text| {
+        for captures in citation_regex.captures_iter(text) {
+            groups.push(citation_keys(&captures[1]));
+        }
+        text.to_string()
+    });
+    if groups.is_empty() {
+        return html.to_string();
+    }
+
+    let style = match citation_style(yaml_btreemap, source_directory) {
+        Ok(style) => style,
+        Err(message) => {
+            warn(&message);
+            return html.to_string();
+        }
+    };
+    let locales = locales();
+
+    let bibliography_file = source_directory.join(bibliography_path);
+    let bibliography = match fs::read_to_string(&bibliography_file) {
+        Ok(bibliography) => bibliography,
+        Err(error) => {
+            warn(&format!(
+                "Could not read bibliography {}: {}",
+                bibliography_file.display(),
+                error
+            ));
+            return html.to_string();
+        }
+    };
+
+    let is_csl_json = bibliography_file
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    let (known_groups, rendered) = if is_csl_json {
+        let items: Vec<CslJsonItem> = match serde_json::from_str(&bibliography) {
+            Ok(items) => items,
+            Err(error) => {
+                warn(&format!("Invalid CSL-JSON bibliography: {}", error));
+                return html.to_string();
+            }
+        };
+        let entries: BTreeMap<String, &CslJsonItem> = items
+            .iter()
+            .filter_map(|item| item.id().map(|id| (id.to_string(), item)))
+            .collect();
+        let known_groups = known_citation_groups(&groups, |key| entries.contains_key(key));
+        let rendered = render_citations!(entries, known_groups.iter().flatten(), &style, &locales);
+        (known_groups, rendered)
+    } else {
+        let library = match hayagriva::io::from_biblatex_str(&bibliography) {
+            Ok(library) => library,
+            Err(errors) => {
+                warn(&format!(
+                    "Invalid BibTeX bibliography ({} errors)",
+                    errors.len()
+                ));
+                return html.to_string();
+            }
+        };
+        let entries: BTreeMap<String, &Entry> = library
+            .iter()
+            .map(|entry| (entry.key().to_string(), entry))
+            .collect();
+        let known_groups = known_citation_groups(&groups, |key| entries.contains_key(key));
+        let rendered = render_citations!(entries, known_groups.iter().flatten(), &style, &locales);
+        (known_groups, rendered)
+    };
+
+    let title = yaml_btreemap
+        .get("reference-section-title")
+        .and_then(|value| value.as_str())
+        .unwrap_or(DEFAULT_BIBLIOGRAPHY_TITLE);
+
+    // Replace each known citation group with its rendered citation, linked to its bibliography entry
+    let mut rendered_citations = rendered.citations.iter();
+    let mut group_index = 0;
+    let mut cited_html = map_outside_code(html, |text| {
+        citation_regex
+            .replace_all(text, |captures: &regex::Captures| {
+                let known_group = known_groups.get(group_index).cloned().flatten();
+                group_index += 1;
+                // Only known groups were rendered, so only they take the next rendered citation
+                let citation = known_group.as_ref().and_then(|_| rendered_citations.next());
+                match (known_group, citation) {
+                    (Some(group), Some(citation)) => format!(
+                        "<a class=\"citation\" href=\"#ref-{}\">{}</a>",
+                        escape_html(&group[0]),
+                        to_html(&citation.citation)
+                    ),
+                    _ => captures[0].to_string(),
+                }
+            })
+            .to_string()
+    });
+
+    cited_html.push_str(&bibliography_html(&rendered, title));
+    cited_html
+}
+
+/// Returns the bibliography section for the rendered citations (empty if the style has no bibliography).
+fn bibliography_html(rendered: &Rendered, title: &str) -> String {
+    let Some(bibliography) = &rendered.bibliography else {
+        return String::new();
+    };
+
+    let mut html = format!(
+        "<section class=\"bibliography\"><h2>{}</h2>\n",
+        escape_html(title)
+    );
+    for item in &bibliography.items {
+        let first_field = match &item.first_field {
+            Some(first_field) => {
+                let mut buffer = String::new();
+                let _ = first_field.write_buf(&mut buffer, BufWriteFormat::Html);
+                format!("<span class=\"csl-left-margin\">{}</span> ", buffer)
+            }
+            None => String::new(),
+        };
+        html.push_str(&format!(
+            "<div class=\"csl-entry\" id=\"ref-{}\">{}{}</div>\n",
+            escape_html(&item.key),
+            first_field,
+            to_html(&item.content)
+        ));
+    }
+    html.push_str("</section>\n");
+    html
+}
+
+/// Writes rendered citation elements as HTML.
+fn to_html(children: &ElemChildren) -> String {
+    let mut buffer = String::new();
+    let _ = children.write_buf(&mut buffer, BufWriteFormat::Html);
+    buffer
+}
+
+/// Returns the citation style chosen in the front matter (`csl`): a `.csl` file or a bundled style name.
+fn citation_style(
+    yaml_btreemap: &BTreeMap<String, Value>,
+    source_directory: &Path,
+) -> Result<IndependentStyle, String> {
+    let style_name = yaml_btreemap
+        .get("csl")
+        .and_then(|value| value.as_str())
+        .unwrap_or(DEFAULT_CITATION_STYLE);
+
+    let style = if style_name.to_lowercase().ends_with(".csl") {
+        let style_file = source_directory.join(style_name);
+        let xml = fs::read_to_string(&style_file).map_err(|error| {
+            format!(
+                "Could not read citation style {}: {}",
+                style_file.display(),
+                error
+            )
+        })?;
+        Style::from_xml(&xml)
+            .map_err(|error| format!("Invalid citation style {}: {}", style_name, error))?
+    } else {
+        ArchivedStyle::by_name(style_name)
+            .ok_or_else(|| format!("Unknown citation style {}", style_name))?
+            .get()
+    };
+
+    match style {
+        Style::Independent(style) => Ok(style),
+        Style::Dependent(_) => Err(format!(
+            "Citation style {} depends on another style; use the independent (parent) style instead",
+            style_name
+        )),
+    }
+}
+
+/// Prints a citation warning.
+fn warn(message: &str) {
+    eprintln!("{}{}", CROSS_MARK.yellow(), message.yellow());
+}
+
+/// Splits the keys of a citation group (`@key1; @key2`).
+fn citation_keys(group: &str) -> Vec<String> {
+    group
+        .split(';')
+        .map(|key| key.trim().trim_start_matches('@').to_string())
+        .collect()
+}
+
+/// Returns, for each citation group, the group if all its keys are known, or `None` (with a warning).
+fn known_citation_groups(
+    groups: &[Vec<String>],
+    is_known: impl Fn(&str) -> bool,
+) -> Vec<Option<Vec<String>>> {
+    groups
+        .iter()
+        .map(|group| {
+            let unknown: Vec<&String> = group.iter().filter(|key| !is_known(key)).collect();
+            if unknown.is_empty() {
+                Some(group.clone())
+            } else {
+                for key in unknown {
+                    warn(&format!("Citation key @{} is not in the bibliography", key));
+                }
+                None
+            }
+        })
+        .collect()
+}
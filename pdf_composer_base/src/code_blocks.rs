@@ -0,0 +1,177 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+
+use pdf_composer_definitions::code_wrap::CodeWrap;
+
+/// The styles for the lines of code blocks (numbered, highlighted, and wrapped, shrunk or truncated
+/// when they are too long), added to the page CSS.
+pub const CODE_BLOCK_CSS: &str = "
+pre .code-line { display: inline-block; min-width: 100%; box-sizing: border-box; }
+pre.code-wrap { white-space: pre-wrap; overflow-wrap: anywhere; }
+pre.code-wrap.line-numbers .code-line { padding-left: 3.75em; text-indent: -3.75em; }
+pre.code-shrink { container-type: inline-size; }
+pre.code-shrink > code { font-size: min(1em, calc(100cqi / var(--code-columns) / 0.6)); }
+pre.code-truncate { overflow: hidden; }
+pre.code-truncate .code-line { width: 100%; overflow: hidden; text-overflow: ellipsis; vertical-align: top; }
+pre.line-numbers { counter-reset: code-line; }
+pre.line-numbers .code-line::before { counter-increment: code-line; content: counter(code-line); \
+display: inline-block; width: 2.5em; margin-right: 0.75em; padding-right: 0.5em; text-align: right; \
+color: #6e7781; border-right: 1px solid #d0d7de; user-select: none; }
+pre .code-line.highlighted { background: #fff8c5; }
+";
+
+/// Separates the language of a code block from its highlighted lines in the `language-` class the
+/// markdown is converted with (`rust` and `{3-5}` become `language-rust:highlight-3-5`).
+const HIGHLIGHT_MARKER: &str = ":highlight-";
+
+/// This function moves the highlighted lines of fenced code blocks (` ```rust {3-5} `) from the
+/// info string to the language, where they survive the conversion to HTML (see `format_code_blocks`).
+///
+/// # Arguments
+///
+/// * `markdown` - The markdown content of the source document.
+///
+/// # Returns
+///
+/// A `String` with the opening fences rewritten (and everything else unchanged).
+///
+/// # Remarks
+///
+/// The lines are numbered from 1, as ranges (`3-5`) or single lines, separated by commas
+/// (`{1,4-6}`), at the end of the info string. Lines inside code blocks are left alone.
+pub fn mark_code_highlights(markdown: &str) -> String {
+    let opening_regex =
+        Regex::new(r"^(\s*(?:>\s*)*)(`{3,}|~{3,})\s*([^\s`{]*)[^`{]*?\{\s*([\d\s,-]+?)\s*\}\s*$")
+            .unwrap();
+
+    let mut output = String::with_capacity(markdown.len());
+    // The fence character and length of the code block the line is in, if any
+    let mut code_fence: Option<(u8, usize)> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start_matches(|c: char| c.is_whitespace() || c == '>');
+        let fence_length = |fence: u8| trimmed.bytes().take_while(|c| *c == fence).count();
+        if let Some((fence, length)) = code_fence {
+            // A closing fence is at least as long as the opening fence, with nothing after it
+            if fence_length(fence) >= length && fence_length(fence) == trimmed.trim_end().len() {
+                code_fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let fence = trimmed.as_bytes()[0];
+            code_fence = Some((fence, fence_length(fence)));
+            if let Some(captures) = opening_regex.captures(line) {
+                let lines: String = captures[4].split_whitespace().collect();
+                output.push_str(&format!(
+                    "{}{}{}{}{}\n",
+                    &captures[1], &captures[2], &captures[3], HIGHLIGHT_MARKER, lines
+                ));
+                continue;
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// This function lays out the lines of the code blocks in the generated HTML: numbering them,
+/// highlighting the lines marked by `mark_code_highlights`, and wrapping, shrinking or truncating
+/// long lines.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+/// * `line_numbers` - Whether the lines of every code block are numbered (see `set_code_line_numbers`).
+/// * `code_wrap` - What happens to lines that are too long for the page (see `set_code_wrap`).
+///
+/// # Returns
+///
+/// A `String` with the lines of the code blocks wrapped in `code-line` spans, and the code blocks
+/// given the classes of their line numbers and `CodeWrap`.
+///
+/// # Remarks
+///
+/// The numbers are drawn with CSS counters, so they are not part of the text copied from the PDF.
+/// Code blocks shrink to fit their longest line, counting a tab as 8 characters (as browsers
+/// show them), and the line numbers.
+pub fn format_code_blocks(html: &str, line_numbers: bool, code_wrap: CodeWrap) -> String {
+    let code_block_regex =
+        Regex::new(r#"(?s)<pre><code(?: class="language-([^"]*)")?>(.*?)</code></pre>"#).unwrap();
+
+    code_block_regex
+        .replace_all(html, |captures: &Captures| {
+            let language = captures.get(1).map_or("", |language| language.as_str());
+            let (language, highlighted) = match language.split_once(HIGHLIGHT_MARKER) {
+                Some((language, lines)) => (language, highlighted_lines(lines)),
+                None => (language, Vec::new()),
+            };
+
+            let code = captures[2].strip_suffix('\n').unwrap_or(&captures[2]);
+            let lines: Vec<String> = code
+                .split('\n')
+                .enumerate()
+                .map(|(index, line)| {
+                    let is_highlighted = highlighted
+                        .iter()
+                        .any(|(first, last)| (*first..=*last).contains(&(index + 1)));
+                    let class = if is_highlighted {
+                        "code-line highlighted"
+                    } else {
+                        "code-line"
+                    };
+                    format!("<span class=\"{}\">{}</span>", class, line)
+                })
+                .collect();
+            let mut pre_attributes = match code_wrap {
+                CodeWrap::Wrap => String::from(" class=\"code-wrap"),
+                CodeWrap::ShrinkToFit => String::from(" class=\"code-shrink"),
+                CodeWrap::Truncate => String::from(" class=\"code-truncate"),
+            };
+            if line_numbers {
+                pre_attributes.push_str(" line-numbers");
+            }
+            pre_attributes.push('"');
+            if code_wrap == CodeWrap::ShrinkToFit {
+                // The line numbers take up about 6 characters
+                let columns = code.split('\n').map(line_width).max().unwrap_or(0)
+                    + if line_numbers { 6 } else { 0 };
+                pre_attributes.push_str(&format!(" style=\"--code-columns: {}\"", columns.max(1)));
+            }
+            let code_class = if language.is_empty() {
+                String::new()
+            } else {
+                format!(" class=\"language-{}\"", language)
+            };
+            format!(
+                "<pre{}><code{}>{}\n</code></pre>",
+                pre_attributes,
+                code_class,
+                lines.join("\n")
+            )
+        })
+        .to_string()
+}
+
+/// This function parses highlighted lines such as `1,4-6` into ranges of line numbers, leaving out
+/// anything that is not a line number or range.
+fn highlighted_lines(lines: &str) -> Vec<(usize, usize)> {
+    lines
+        .split(',')
+        .filter_map(|range| match range.split_once('-') {
+            Some((first, last)) => Some((first.parse().ok()?, last.parse().ok()?)),
+            None => range.parse().ok().map(|line| (line, line)),
+        })
+        .collect()
+}
+
+/// This function returns the width of a line of escaped HTML code in characters, counting an
+/// entity (such as `&lt;`) as one character and a tab as 8.
+fn line_width(line: &str) -> usize {
+    let entity_regex = Regex::new(r"&#?\w+;").unwrap();
+    entity_regex
+        .replace_all(line, "_")
+        .chars()
+        .map(|character| if character == '\t' { 8 } else { 1 })
+        .sum()
+}
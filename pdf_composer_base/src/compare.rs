@@ -0,0 +1,629 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+
+use crate::inspect::read_pdf_metadata;
+use crate::raster::RasterImage;
+use crate::stamp::page_media_box;
+use crate::utils::write_atomically;
+use pdf_composer_definitions::compare::{
+    CompareOptions, PageComparison, PdfComparison, StructuralDifference,
+};
+
+/// Compares two PDF documents, such as the same source generated by two versions of PDF Composer,
+/// to detect rendering regressions.
+///
+/// # Arguments
+///
+/// * `a` - The path of the first (for example, the expected) PDF document.
+/// * `b` - The path of the second (for example, the newly generated) PDF document.
+/// * `options` - The `CompareOptions`: whether the pages are rendered and compared as images, at
+///   what resolution and tolerance, and where the diff images are written.
+///
+/// # Returns
+///
+/// * `Ok(PdfComparison)` with the structural differences and the raster comparison of each page.
+/// * `Err(e)` if a document could not be read, its pages could not be rendered or a diff image
+///   could not be written.
+///
+/// # Remarks
+///
+/// The structure compared is the PDF version, page count, document information (except the
+/// `ignored_info_entries`), language and named destinations, and for each page its size, text,
+/// fonts, images and annotations.
+///
+/// The pages are rendered with `pdftoppm` from Poppler, which must be installed for the raster
+/// comparison (`CompareOptions::raster`). Only the pages both documents have are compared. The diff
+/// image of a page shows the second document faded, with the pixels that differ in red.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_composer::{compare_pdfs, CompareOptions};
+/// use std::path::{Path, PathBuf};
+///
+/// let comparison = compare_pdfs(
+///     Path::new("expected/sample_file_01.pdf"),
+///     Path::new("pdf_composer_pdfs/sample_file_01.pdf"),
+///     &CompareOptions {
+///         diff_directory: Some(PathBuf::from("pdf_composer_diffs")),
+///         ..CompareOptions::default()
+///     },
+/// )
+/// .unwrap();
+///
+/// for difference in &comparison.structural_differences {
+///     println!("{}", difference);
+/// }
+/// assert!(comparison.is_match());
+/// ```
+pub fn compare_pdfs(
+    a: &Path,
+    b: &Path,
+    options: &CompareOptions,
+) -> Result<PdfComparison, Box<dyn std::error::Error>> {
+    let mut comparison = PdfComparison {
+        structural_differences: structural_differences(a, b, options)?,
+        pages: Vec::new(),
+    };
+
+    if options.raster {
+        let pages_a = render_pages(a, options.dpi)?;
+        let pages_b = render_pages(b, options.dpi)?;
+        let file_stem = b
+            .file_stem()
+            .map(|file_stem| file_stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for (index, (page_a, page_b)) in pages_a.iter().zip(&pages_b).enumerate() {
+            let page_number = index as u32 + 1;
+            let (different_pixels, total_pixels, diff) =
+                compare_rasters(page_a, page_b, options.pixel_tolerance);
+            let differs =
+                different_pixels as f64 > total_pixels as f64 * options.max_different_pixels;
+            let diff_image = match &options.diff_directory {
+                Some(diff_directory) if differs => {
+                    let path =
+                        diff_directory.join(format!("{}_page_{}_diff.png", file_stem, page_number));
+                    write_atomically(&path, |writer| write_png(writer, &diff))?;
+                    Some(path)
+                }
+                _ => None,
+            };
+            comparison.pages.push(PageComparison {
+                page_number,
+                different_pixels,
+                total_pixels,
+                differs,
+                diff_image,
+            });
+        }
+    }
+
+    Ok(comparison)
+}
+
+/// Returns the differences in the structure of two PDF documents, the documents as a whole first,
+/// then page by page.
+fn structural_differences(
+    a: &Path,
+    b: &Path,
+    options: &CompareOptions,
+) -> Result<Vec<StructuralDifference>, Box<dyn std::error::Error>> {
+    let mut differences = Vec::new();
+    let mut compare =
+        |page_number: Option<u32>, subject: &str, value_a: String, value_b: String| {
+            if value_a != value_b {
+                differences.push(StructuralDifference {
+                    page_number,
+                    subject: subject.to_string(),
+                    a: value_a,
+                    b: value_b,
+                });
+            }
+        };
+
+    let metadata_a = read_pdf_metadata(a)
+        .map_err(|error| format!("could not load {}: {}", a.display(), error))?;
+    let metadata_b = read_pdf_metadata(b)
+        .map_err(|error| format!("could not load {}: {}", b.display(), error))?;
+    compare(None, "version", metadata_a.version, metadata_b.version);
+    compare(
+        None,
+        "page count",
+        metadata_a.page_count.to_string(),
+        metadata_b.page_count.to_string(),
+    );
+    let info_keys: BTreeSet<&String> = metadata_a
+        .info
+        .keys()
+        .chain(metadata_b.info.keys())
+        .collect();
+    for key in info_keys {
+        if options.ignored_info_entries.contains(key) {
+            continue;
+        }
+        compare(
+            None,
+            &format!("info {}", key),
+            metadata_a.info.get(key).cloned().unwrap_or_default(),
+            metadata_b.info.get(key).cloned().unwrap_or_default(),
+        );
+    }
+    compare(
+        None,
+        "language",
+        metadata_a.language.unwrap_or_default(),
+        metadata_b.language.unwrap_or_default(),
+    );
+    compare(
+        None,
+        "named destinations",
+        destinations_text(&metadata_a.named_destinations),
+        destinations_text(&metadata_b.named_destinations),
+    );
+
+    let doc_a = Document::load(a)?;
+    let doc_b = Document::load(b)?;
+    for ((page_number, page_a), (_, page_b)) in doc_a.get_pages().into_iter().zip(doc_b.get_pages())
+    {
+        let page = Some(page_number);
+        compare(
+            page,
+            "size",
+            page_size(&doc_a, page_a),
+            page_size(&doc_b, page_b),
+        );
+        let text_a = page_text(&doc_a, page_a);
+        let text_b = page_text(&doc_b, page_b);
+        // The first line that differs, rather than the text of the whole page
+        let first_difference = (0..text_a.len().max(text_b.len()))
+            .find(|&line| text_a.get(line) != text_b.get(line))
+            .map(|line| {
+                (
+                    text_a.get(line).cloned().unwrap_or_default(),
+                    text_b.get(line).cloned().unwrap_or_default(),
+                )
+            });
+        if let Some((line_a, line_b)) = first_difference {
+            compare(page, "text", line_a, line_b);
+        }
+        compare(
+            page,
+            "fonts",
+            page_fonts(&doc_a, page_a),
+            page_fonts(&doc_b, page_b),
+        );
+        compare(
+            page,
+            "images",
+            page_images(&doc_a, page_a),
+            page_images(&doc_b, page_b),
+        );
+        compare(
+            page,
+            "annotations",
+            page_annotations(&doc_a, page_a),
+            page_annotations(&doc_b, page_b),
+        );
+    }
+
+    Ok(differences)
+}
+
+/// Returns the named destinations as text, `name → page` separated by commas.
+fn destinations_text(destinations: &BTreeMap<String, u32>) -> String {
+    destinations
+        .iter()
+        .map(|(name, page_number)| format!("{} → {}", name, page_number))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns the size of a page in points, such as `595 × 842`.
+fn page_size(doc: &Document, page_id: ObjectId) -> String {
+    match page_media_box(doc, page_id) {
+        Some([left, bottom, right, top]) => {
+            format!("{} × {}", (right - left).round(), (top - bottom).round())
+        }
+        None => String::new(),
+    }
+}
+
+/// Returns the lines of text of a page, with the white space in each line normalised.
+///
+/// The text is decoded with the `ToUnicode` maps of the fonts, as Chromium writes it in glyph
+/// codes (`Identity-H`). A line ends where the text moves to another line (a new `y` position).
+fn page_text(doc: &Document, page_id: ObjectId) -> Vec<String> {
+    let Ok(content) = doc
+        .get_page_content(page_id)
+        .and_then(|content| Content::decode(&content))
+    else {
+        return Vec::new();
+    };
+    let fonts = doc.get_page_fonts(page_id);
+    let mut to_unicode_maps: BTreeMap<Vec<u8>, (usize, BTreeMap<u32, String>)> = BTreeMap::new();
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_y: Option<f32> = None;
+    let mut font: Option<Vec<u8>> = None;
+    for operation in &content.operations {
+        let operands = &operation.operands;
+        let new_y = match operation.operator.as_str() {
+            "Tm" => operands.get(5).and_then(|y| y.as_float().ok()),
+            "Td" | "TD" => operands
+                .get(1)
+                .and_then(|y| y.as_float().ok())
+                .filter(|y| *y != 0.0)
+                .map(|y| line_y.unwrap_or_default() + y),
+            "T*" | "'" | "\"" => Some(f32::NAN),
+            _ => None,
+        };
+        if let Some(new_y) = new_y {
+            if line_y != Some(new_y) {
+                lines.push(std::mem::take(&mut line));
+            }
+            line_y = Some(new_y);
+        }
+
+        match operation.operator.as_str() {
+            "Tf" => {
+                font = operands
+                    .first()
+                    .and_then(|name| name.as_name().ok())
+                    .map(<[u8]>::to_vec)
+            }
+            "Tj" | "TJ" | "'" | "\"" => {
+                let Some(font_name) = &font else {
+                    continue;
+                };
+                let (code_length, to_unicode) = to_unicode_maps
+                    .entry(font_name.clone())
+                    .or_insert_with(|| to_unicode_map(doc, fonts.get(font_name).copied()));
+                let strings = operands.iter().flat_map(|operand| match operand {
+                    LopdfObject::Array(items) => items.iter().collect::<Vec<_>>(),
+                    operand => vec![operand],
+                });
+                for string in strings {
+                    if let LopdfObject::String(bytes, _) = string {
+                        for code in bytes.chunks(*code_length) {
+                            let code = code.iter().fold(0, |code, byte| code << 8 | *byte as u32);
+                            match to_unicode.get(&code) {
+                                Some(text) => line.push_str(text),
+                                None if *code_length == 1 => line.push(code as u8 as char),
+                                None => line.push('\u{FFFD}'),
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    lines.push(line);
+
+    lines
+        .iter()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+/// Returns the length of the character codes (`2` for `Type0` fonts, otherwise `1`) and the
+/// `ToUnicode` map (`bfchar` and `bfrange` entries) of a font.
+fn to_unicode_map(doc: &Document, font: Option<&Dictionary>) -> (usize, BTreeMap<u32, String>) {
+    let mut to_unicode = BTreeMap::new();
+    let Some(font) = font else {
+        return (1, to_unicode);
+    };
+    let code_length = match font.get(b"Subtype").and_then(LopdfObject::as_name) {
+        Ok(b"Type0") => 2,
+        _ => 1,
+    };
+    let Some(cmap) = font
+        .get(b"ToUnicode")
+        .ok()
+        .and_then(|cmap| doc.dereference(cmap).ok()?.1.as_stream().ok())
+        .and_then(|stream| stream.decompressed_content().ok())
+    else {
+        return (code_length, to_unicode);
+    };
+
+    // The hexadecimal strings (`<0044>`) of the map, in the sections they are in
+    let cmap = String::from_utf8_lossy(&cmap);
+    let hex = |text: &str| u32::from_str_radix(text.trim_matches(['<', '>']), 16).ok();
+    let unicode = |text: &str| {
+        let units: Vec<u16> = text
+            .trim_matches(['<', '>'])
+            .as_bytes()
+            .chunks(4)
+            .filter_map(|unit| u16::from_str_radix(std::str::from_utf8(unit).ok()?, 16).ok())
+            .collect();
+        String::from_utf16_lossy(&units)
+    };
+    for section in cmap.split("beginbfchar").skip(1) {
+        let entries: Vec<&str> = section
+            .split("endbfchar")
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        for entry in entries.chunks_exact(2) {
+            if let Some(code) = hex(entry[0]) {
+                to_unicode.insert(code, unicode(entry[1]));
+            }
+        }
+    }
+    for section in cmap.split("beginbfrange").skip(1) {
+        let entries: Vec<&str> = section
+            .split("endbfrange")
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        // Ranges mapped to an array of strings (`[<0041> <0042>]`) are rare, and skipped
+        for entry in entries.chunks_exact(3) {
+            let (Some(first), Some(last)) = (hex(entry[0]), hex(entry[1])) else {
+                continue;
+            };
+            let Some(start) = unicode(entry[2]).chars().next() else {
+                continue;
+            };
+            for code in first..=last.min(first + 0xFFFF) {
+                if let Some(character) = char::from_u32(start as u32 + code - first) {
+                    to_unicode.insert(code, character.to_string());
+                }
+            }
+        }
+    }
+    (code_length, to_unicode)
+}
+
+/// Returns the names of the fonts a page uses, without the subset prefixes (`ABCDEF+`), which
+/// change each time a PDF is generated.
+fn page_fonts(doc: &Document, page_id: ObjectId) -> String {
+    let fonts: BTreeSet<String> = doc
+        .get_page_fonts(page_id)
+        .values()
+        .filter_map(|font| {
+            font.get(b"BaseFont")
+                .and_then(LopdfObject::as_name_str)
+                .ok()
+        })
+        .map(|name| match name.split_once('+') {
+            Some((prefix, name)) if prefix.len() == 6 => name.to_string(),
+            _ => name.to_string(),
+        })
+        .collect();
+    fonts.into_iter().collect::<Vec<_>>().join(", ")
+}
+
+/// Returns the sizes (in pixels) of the images drawn on a page, such as `640 × 480`.
+fn page_images(doc: &Document, page_id: ObjectId) -> String {
+    let (resources, resource_ids) = doc.get_page_resources(page_id);
+    let mut images: Vec<String> = resources
+        .into_iter()
+        .chain(
+            resource_ids
+                .into_iter()
+                .filter_map(|id| doc.get_dictionary(id).ok()),
+        )
+        .filter_map(|resources| resources.get(b"XObject").ok())
+        .filter_map(|x_objects| doc.dereference(x_objects).ok()?.1.as_dict().ok())
+        .flat_map(|x_objects| x_objects.iter())
+        .filter_map(|(_, x_object)| doc.dereference(x_object).ok()?.1.as_stream().ok())
+        .filter(|stream| {
+            matches!(
+                stream.dict.get(b"Subtype").and_then(LopdfObject::as_name),
+                Ok(b"Image")
+            )
+        })
+        .map(|stream| {
+            let dimension = |key: &[u8]| {
+                stream
+                    .dict
+                    .get(key)
+                    .and_then(LopdfObject::as_i64)
+                    .unwrap_or_default()
+            };
+            format!("{} × {}", dimension(b"Width"), dimension(b"Height"))
+        })
+        .collect();
+    images.sort();
+    images.join(", ")
+}
+
+/// Returns the number of annotations of each type (such as `Link`) on a page, such as `Link × 3`.
+fn page_annotations(doc: &Document, page_id: ObjectId) -> String {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for annotation in doc.get_page_annotations(page_id) {
+        let subtype = annotation
+            .get(b"Subtype")
+            .and_then(LopdfObject::as_name_str)
+            .unwrap_or("?");
+        *counts.entry(subtype.to_string()).or_default() += 1;
+    }
+    counts
+        .iter()
+        .map(|(subtype, count)| format!("{} × {}", subtype, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Renders the pages of a PDF document to RGB images with `pdftoppm` (Poppler).
+fn render_pages(path: &Path, dpi: u32) -> Result<Vec<RasterImage>, Box<dyn std::error::Error>> {
+    let output = Command::new("pdftoppm")
+        .arg("-r")
+        .arg(dpi.max(1).to_string())
+        .arg(path)
+        .output()
+        .map_err(|error| {
+            format!(
+                "could not render the pages of {} with pdftoppm (Poppler): {}",
+                path.display(),
+                error
+            )
+        })?;
+    if !output.status.success() {
+        return Err(format!(
+            "could not render the pages of {} with pdftoppm (Poppler): {}",
+            path.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into());
+    }
+
+    // Without an output file name, pdftoppm writes the pages to stdout one PPM image after another
+    let mut pages = Vec::new();
+    let mut data = output.stdout.as_slice();
+    while !data.is_empty() {
+        let (page, rest) = parse_ppm(data).ok_or_else(|| {
+            format!(
+                "could not read the pages of {} rendered by pdftoppm",
+                path.display()
+            )
+        })?;
+        pages.push(page);
+        data = rest;
+    }
+    Ok(pages)
+}
+
+/// Parses a binary PPM (`P6`) image with 8 bit samples, returning it and the data after it.
+fn parse_ppm(data: &[u8]) -> Option<(RasterImage, &[u8])> {
+    let mut position = 0;
+    let mut header = [0usize; 3];
+    if !data.starts_with(b"P6") {
+        return None;
+    }
+    position += 2;
+    for value in header.iter_mut() {
+        // White space and comments come between the values of the header
+        loop {
+            match data.get(position)? {
+                byte if byte.is_ascii_whitespace() => position += 1,
+                b'#' => {
+                    while *data.get(position)? != b'\n' {
+                        position += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+        let start = position;
+        while data.get(position)?.is_ascii_digit() {
+            position += 1;
+        }
+        *value = std::str::from_utf8(&data[start..position])
+            .ok()?
+            .parse()
+            .ok()?;
+    }
+    let [width, height, max_value] = header;
+    if max_value != 255 {
+        return None;
+    }
+    // A single white space character ends the header
+    position += 1;
+    let end = position.checked_add(width * height * 3)?;
+    let samples = data.get(position..end)?.to_vec();
+    Some((
+        RasterImage {
+            width,
+            height,
+            components: 3,
+            samples,
+        },
+        &data[end..],
+    ))
+}
+
+/// Compares two RGB images pixel by pixel.
+///
+/// Returns the number of pixels that differ by more than the tolerance, the number of pixels
+/// compared, and the diff image: `b` faded to grey, with the pixels that differ in red. When the
+/// sizes differ, the images are compared over the larger size and the pixels only one image has
+/// count as different.
+fn compare_rasters(a: &RasterImage, b: &RasterImage, tolerance: u8) -> (usize, usize, RasterImage) {
+    let width = a.width.max(b.width);
+    let height = a.height.max(b.height);
+    fn pixel(image: &RasterImage, x: usize, y: usize) -> Option<&[u8]> {
+        let index = (y * image.width + x) * 3;
+        (x < image.width && y < image.height).then(|| &image.samples[index..index + 3])
+    }
+
+    let mut different_pixels = 0;
+    let mut samples = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        for x in 0..width {
+            match (pixel(a, x, y), pixel(b, x, y)) {
+                (Some(pixel_a), Some(pixel_b))
+                    if pixel_a
+                        .iter()
+                        .zip(pixel_b)
+                        .all(|(sample_a, sample_b)| sample_a.abs_diff(*sample_b) <= tolerance) =>
+                {
+                    let grey = (pixel_b[0] as u32 * 299
+                        + pixel_b[1] as u32 * 587
+                        + pixel_b[2] as u32 * 114)
+                        / 1000;
+                    let faded = (255 - (255 - grey) / 4) as u8;
+                    samples.extend([faded, faded, faded]);
+                }
+                _ => {
+                    different_pixels += 1;
+                    samples.extend([255, 0, 0]);
+                }
+            }
+        }
+    }
+
+    (
+        different_pixels,
+        width * height,
+        RasterImage {
+            width,
+            height,
+            components: 3,
+            samples,
+        },
+    )
+}
+
+/// Writes an RGB image as a PNG image.
+fn write_png<W: Write>(writer: &mut W, image: &RasterImage) -> io::Result<()> {
+    let write_chunk = |writer: &mut W, chunk_type: &[u8; 4], data: &[u8]| -> io::Result<()> {
+        let mut crc = Crc::new();
+        crc.update(chunk_type);
+        crc.update(data);
+        writer.write_all(&(data.len() as u32).to_be_bytes())?;
+        writer.write_all(chunk_type)?;
+        writer.write_all(data)?;
+        writer.write_all(&crc.sum().to_be_bytes())
+    };
+
+    writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+    let mut header = Vec::with_capacity(13);
+    header.extend((image.width as u32).to_be_bytes());
+    header.extend((image.height as u32).to_be_bytes());
+    // 8 bit samples, RGB, deflate compression, adaptive filtering, no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+    write_chunk(writer, b"IHDR", &header)?;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in image.samples.chunks(image.width * 3) {
+        // Each row starts with its filter type, none
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+    write_chunk(writer, b"IDAT", &encoder.finish()?)?;
+    write_chunk(writer, b"IEND", &[])
+}
@@ -0,0 +1,86 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use markdown::mdast::Node;
+use markdown::ParseOptions;
+use serde_yml::Value;
+use std::collections::BTreeMap;
+
+use crate::utils::merge_markdown_yaml;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// The reading speed `{{reading_time}}` is worked out with, in words per minute.
+const WORDS_PER_MINUTE: usize = 200;
+
+/// This function fills in the `{{word_count}}` and `{{reading_time}}` placeholders of a document,
+/// as front matter values (so they work wherever placeholders do).
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` (for the QR code footer template).
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) of the document. `word_count` and
+///   `reading_time` are added when the markdown or QR code footer refer to them and the front
+///   matter does not set them itself.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+///
+/// # Remarks
+///
+/// Words are counted in the text of the document after its placeholders are filled in, leaving
+/// out code, HTML and link targets. The reading time is in whole minutes (at 200 words per
+/// minute), and at least one minute for a document with any words.
+pub fn fill_computed_placeholders(
+    composer: &PDFComposerStruct,
+    yaml_btreemap: &mut BTreeMap<String, Value>,
+    markdown_content: &str,
+) {
+    let templates = [
+        markdown_content,
+        composer.qr_code_footer.as_deref().unwrap_or(""),
+        composer.watermark.as_deref().unwrap_or(""),
+    ];
+    let is_used = |key: &str| {
+        let placeholder = format!("{{{{{}}}}}", key);
+        templates
+            .iter()
+            .any(|template| template.contains(&placeholder))
+    };
+    let add_word_count = !yaml_btreemap.contains_key("word_count") && is_used("word_count");
+    let add_reading_time = !yaml_btreemap.contains_key("reading_time") && is_used("reading_time");
+    if !add_word_count && !add_reading_time {
+        return;
+    }
+
+    let words = word_count(&merge_markdown_yaml(
+        yaml_btreemap.clone(),
+        markdown_content,
+    ));
+    if add_word_count {
+        yaml_btreemap.insert("word_count".to_string(), Value::String(words.to_string()));
+    }
+    if add_reading_time {
+        let minutes = words.div_ceil(WORDS_PER_MINUTE);
+        yaml_btreemap.insert(
+            "reading_time".to_string(),
+            Value::String(minutes.to_string()),
+        );
+    }
+}
+
+/// Returns the number of words in the text of a markdown document.
+fn word_count(markdown_content: &str) -> usize {
+    match markdown::to_mdast(markdown_content, &ParseOptions::gfm()) {
+        Ok(tree) => text_word_count(&tree),
+        Err(_) => markdown_content.split_whitespace().count(),
+    }
+}
+
+/// Returns the number of words in the text nodes of a markdown tree.
+fn text_word_count(node: &Node) -> usize {
+    match node {
+        Node::Text(text) => text.value.split_whitespace().count(),
+        Node::Code(_) | Node::InlineCode(_) | Node::Html(_) => 0,
+        _ => node
+            .children()
+            .map_or(0, |children| children.iter().map(text_word_count).sum()),
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::Regex;
+use std::ops::Range;
+
+use crate::utils::ESCAPED_PLACEHOLDER_PATTERN;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// The two ways of writing a conditional block, each closed by its own `else` and end tags.
+#[derive(Clone, Copy, PartialEq)]
+enum ConditionalForm {
+    /// `{{#if name}}`, `{{else}}` and `{{/if}}`
+    Placeholder,
+    /// `<!-- if:name -->`, `<!-- else -->` and `<!-- endif -->`, which markdown editors hide
+    Comment,
+}
+
+/// An open conditional block: its form, whether its condition holds, and whether it is past its
+/// `else` tag.
+struct OpenBlock {
+    form: ConditionalForm,
+    condition: bool,
+    in_else: bool,
+}
+
+/// This function returns the conditions that hold for the documents of a composer: the name of
+/// its profile (see `set_profile`) and its flags (see `add_flag`).
+pub fn active_conditions(composer: &PDFComposerStruct) -> Vec<&str> {
+    composer
+        .profile
+        .iter()
+        .chain(&composer.flags)
+        .map(String::as_str)
+        .collect()
+}
+
+/// This function keeps or removes the conditional blocks of a document, such as internal notes
+/// left out of the PDF for customers.
+///
+/// * `{{#if name}}…{{/if}}` - Kept if `name` is one of the conditions, with an optional
+///   `{{else}}` part kept otherwise.
+/// * `<!-- if:name -->…<!-- endif -->` - The same as HTML comments (with `<!-- else -->`), which
+///   markdown previews do not show.
+///
+/// `!name` turns a condition around (`{{#if !internal}}`), and blocks can be nested.
+///
+/// # Arguments
+///
+/// * `markdown` - The markdown of the document, without the front matter.
+/// * `conditions` - The names that hold (see `active_conditions`).
+///
+/// # Returns
+///
+/// A `String` with the blocks whose condition holds kept, without their tags, and the others
+/// removed.
+///
+/// # Remarks
+///
+/// A tag on a line of its own is removed with its line. Tags in fenced code blocks, and escaped
+/// tags (`\{{#if draft}}`), are left alone. A block that is not closed runs to the end of the
+/// document, and an `else` or end tag without a block is left as it is.
+pub fn apply_conditional_content(markdown: &str, conditions: &[&str]) -> String {
+    if !markdown.contains("{{") && !markdown.contains("<!--") {
+        return markdown.to_string();
+    }
+    let tag_regex = Regex::new(&format!(
+        r"{}|\{{\{{#if\s+(!?[\w-]+)\s*\}}\}}|\{{\{{(else)\}}\}}|\{{\{{(/if)\}}\}}|<!--\s*if:\s*(!?[\w-]+)\s*-->|<!--\s*(else)\s*-->|<!--\s*(endif)\s*-->",
+        ESCAPED_PLACEHOLDER_PATTERN
+    ))
+    .unwrap();
+    let code_blocks = fenced_code_blocks(markdown);
+
+    let mut output = String::with_capacity(markdown.len());
+    let mut open_blocks: Vec<OpenBlock> = Vec::new();
+    let mut last_end = 0;
+    for captures in tag_regex.captures_iter(markdown) {
+        let whole = captures.get(0).unwrap();
+        if code_blocks
+            .iter()
+            .any(|code_block| code_block.contains(&whole.start()))
+        {
+            continue;
+        }
+        // Which group matched: 1 to 3 are placeholders, 4 to 6 comments (none is escaped text)
+        let Some(group) = (1..=6).find(|group| captures.get(*group).is_some()) else {
+            continue;
+        };
+        let form = if group <= 3 {
+            ConditionalForm::Placeholder
+        } else {
+            ConditionalForm::Comment
+        };
+        let is_open_block = open_blocks.last().is_some_and(|block| block.form == form);
+        match group {
+            1 | 4 => {}
+            2 | 5 if is_open_block => {}
+            3 | 6 if is_open_block => {}
+            // An else or end tag without a block is left as it is
+            _ => continue,
+        }
+
+        let tag = whole_line_tag(markdown, whole.range());
+        if is_kept(&open_blocks) {
+            output.push_str(&markdown[last_end..tag.start]);
+        }
+        last_end = tag.end;
+        match group {
+            1 | 4 => {
+                let name = &captures[group];
+                let condition = match name.strip_prefix('!') {
+                    Some(name) => !conditions.contains(&name),
+                    None => conditions.contains(&name),
+                };
+                open_blocks.push(OpenBlock {
+                    form,
+                    condition,
+                    in_else: false,
+                });
+            }
+            2 | 5 => {
+                if let Some(block) = open_blocks.last_mut() {
+                    block.in_else = true;
+                }
+            }
+            _ => {
+                open_blocks.pop();
+            }
+        }
+    }
+    if is_kept(&open_blocks) {
+        output.push_str(&markdown[last_end..]);
+    }
+    output
+}
+
+/// Whether the text at this point is kept: the part of every open block that is kept.
+fn is_kept(open_blocks: &[OpenBlock]) -> bool {
+    open_blocks
+        .iter()
+        .all(|block| block.condition != block.in_else)
+}
+
+/// Returns the range of a tag, widened to its whole line (with the line break) when the tag is the
+/// only thing on the line.
+fn whole_line_tag(markdown: &str, tag: Range<usize>) -> Range<usize> {
+    let line_start = markdown[..tag.start]
+        .rfind('\n')
+        .map_or(0, |index| index + 1);
+    let line_end = markdown[tag.end..]
+        .find('\n')
+        .map_or(markdown.len(), |index| tag.end + index + 1);
+    if markdown[line_start..tag.start].trim().is_empty()
+        && markdown[tag.end..line_end].trim().is_empty()
+    {
+        line_start..line_end
+    } else {
+        tag
+    }
+}
+
+/// Returns the byte ranges of the fenced code blocks of the markdown, from the start of the
+/// opening fence to the end of the closing fence (or of the markdown, if it is not closed).
+fn fenced_code_blocks(markdown: &str) -> Vec<Range<usize>> {
+    let mut code_blocks = Vec::new();
+    let mut code_fence: Option<(&str, usize)> = None;
+    let mut line_start = 0;
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let line_end = line_start + line.len();
+        if let Some((fence, start)) = code_fence {
+            if trimmed.starts_with(fence) {
+                code_blocks.push(start..line_end);
+                code_fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            code_fence = Some((&trimmed[..3], line_start));
+        }
+        line_start = line_end;
+    }
+    if let Some((_, start)) = code_fence {
+        code_blocks.push(start..markdown.len());
+    }
+    code_blocks
+}
@@ -0,0 +1,403 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use schemars::schema_for;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::PDFComposer;
+use pdf_composer_definitions::config::{ConfigDiagnostic, PDFComposerConfig};
+use pdf_composer_definitions::image_policy::ImagePolicy;
+use pdf_composer_definitions::locale::Locale;
+use pdf_composer_definitions::page_properties::{PaperOrientation, PaperSize};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function reads a configuration file.
+///
+/// # Arguments
+///
+/// * `path` - The path of the configuration file (`.toml`, or `.yaml` / `.yml`).
+///
+/// # Returns
+///
+/// * `Ok(PDFComposerConfig)` with the settings of the file.
+/// * `Err(e)` if the file could not be read, or has a setting that is not recognised.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_composer::{read_config, PDFComposer, PDFComposerStruct};
+/// use std::path::Path;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // Read the settings, then leave out the source documents of the file
+/// let mut config = read_config(Path::new("pdf_composer.toml"))?;
+/// config.sources.clear();
+///
+/// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+/// my_pdf_doc.apply_config(&config, Path::new("."))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_config(path: &Path) -> Result<PDFComposerConfig, Box<dyn Error>> {
+    let content = fs::read_to_string(path)?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let config = if is_toml {
+        toml::from_str(&content).map_err(|error| error.message().to_string())?
+    } else {
+        serde_yml::from_str(&content)?
+    };
+    Ok(config)
+}
+
+/// This function sets up a composer with the settings of a configuration file (see `apply_config`),
+/// with the settings of the profile of the composer (see `set_profile`) on top.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` to set up.
+/// * `config` - The settings of the configuration file.
+/// * `config_directory` - The directory of the configuration file, the paths are relative to.
+///
+/// # Returns
+///
+/// * `Ok(())` if the settings were applied.
+/// * `Err(e)` if a setting has a value that is not valid, or the file has no profile of the name
+///   set, in which case nothing is changed.
+pub fn configure(
+    composer: &mut PDFComposerStruct,
+    config: &PDFComposerConfig,
+    config_directory: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let Some(profile) = &composer.profile else {
+        return apply_settings(composer, config, config_directory);
+    };
+    let Some(profile_config) = config.profile.get(profile) else {
+        let profiles: Vec<&str> = config.profile.keys().map(String::as_str).collect();
+        return Err(if profiles.is_empty() {
+            format!(
+                "profile {} not found, the configuration file has no profiles",
+                profile
+            )
+        } else {
+            format!(
+                "profile {} not found (the profiles are {})",
+                profile,
+                profiles.join(", ")
+            )
+        }
+        .into());
+    };
+    let config = merge_config(config.clone(), profile_config.clone(), Path::new(""));
+    apply_settings(composer, &config, config_directory)
+}
+
+/// This function sets up a composer with settings (those of a configuration file, or of the
+/// `_pdf_composer.yaml` files of a directory), leaving out the profiles.
+///
+/// # Returns
+///
+/// * `Ok(())` if the settings were applied.
+/// * `Err(e)` if a setting has a value that is not valid, in which case nothing is changed.
+pub fn apply_settings(
+    composer: &mut PDFComposerStruct,
+    config: &PDFComposerConfig,
+    config_directory: &Path,
+) -> Result<(), Box<dyn Error>> {
+    // Every value is checked before the composer is changed
+    let invalid_values: Vec<String> = invalid_values(config)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    if !invalid_values.is_empty() {
+        return Err(invalid_values.join(", ").into());
+    }
+
+    // The extensions are set first, as the files of source directories are found when they are added
+    if !config.source_extensions.is_empty() {
+        let source_extensions: Vec<&str> = config
+            .source_extensions
+            .iter()
+            .map(String::as_str)
+            .collect();
+        composer.set_source_extensions(&source_extensions);
+    }
+    if !config.sources.is_empty() {
+        composer.add_source_files(
+            config
+                .sources
+                .iter()
+                .map(|source| config_directory.join(source))
+                .collect(),
+        );
+    }
+    if let Some(output_directory) = &config.output_directory {
+        composer.set_output_directory(config_directory.join(output_directory).as_path());
+    }
+    if let Some(paper_size) = config.paper_size.as_deref().map(str::parse) {
+        composer.set_paper_size(paper_size?);
+    }
+    if let Some(orientation) = config.orientation.as_deref().map(str::parse) {
+        composer.set_orientation(orientation?);
+    }
+    if let Some(margins) = &config.margins {
+        composer.set_margins(margins);
+    }
+    if let Some(language) = &config.language {
+        composer.set_language(language);
+    }
+    if let Some(locale) = &config.locale {
+        composer.set_locale(locale)?;
+    }
+    if !config.doc_info.is_empty() {
+        composer.set_doc_info_entries(&config.doc_info);
+    }
+    if !config.defaults.is_empty() {
+        composer.set_front_matter_defaults(&config.defaults);
+    }
+    if let Some(stylesheet) = &config.stylesheet {
+        composer.set_stylesheet(config_directory.join(stylesheet));
+    }
+    for flag in &config.flags {
+        composer.add_flag(flag);
+    }
+    match config.watermark.as_deref().map(str::trim) {
+        Some("") => composer.watermark = None,
+        Some(watermark) => composer.set_watermark(watermark),
+        None => {}
+    }
+    if config.image_max_dpi.is_some() || config.jpeg_quality.is_some() {
+        let image_policy = composer.image_policy.unwrap_or_default();
+        composer.set_image_policy(ImagePolicy {
+            max_dpi: config.image_max_dpi.unwrap_or(image_policy.max_dpi),
+            jpeg_quality: config.jpeg_quality.or(image_policy.jpeg_quality),
+            ..image_policy
+        });
+    }
+    Ok(())
+}
+
+/// This function puts settings (those of a profile, or of a `_pdf_composer.yaml` file) on top of
+/// others.
+///
+/// # Arguments
+///
+/// * `base` - The settings underneath.
+/// * `overlay` - The settings on top.
+/// * `overlay_directory` - The directory the paths of the settings on top are relative to, from
+///   the directory of those underneath.
+///
+/// # Returns
+///
+/// A `PDFComposerConfig` with the `doc_info` and `defaults` entries (those on top winning) and the
+/// `flags` of both, the other settings of `overlay` where it has them and of `base` where it has
+/// not, and the profiles of `base`.
+pub fn merge_config(
+    base: PDFComposerConfig,
+    overlay: PDFComposerConfig,
+    overlay_directory: &Path,
+) -> PDFComposerConfig {
+    let mut doc_info = base.doc_info;
+    doc_info.extend(overlay.doc_info);
+    let mut defaults = base.defaults;
+    defaults.extend(overlay.defaults);
+    let mut flags = base.flags;
+    flags.extend(overlay.flags);
+    let sources = if overlay.sources.is_empty() {
+        base.sources
+    } else {
+        overlay
+            .sources
+            .iter()
+            .map(|source| overlay_directory.join(source))
+            .collect()
+    };
+    PDFComposerConfig {
+        sources,
+        source_extensions: if overlay.source_extensions.is_empty() {
+            base.source_extensions
+        } else {
+            overlay.source_extensions
+        },
+        output_directory: overlay
+            .output_directory
+            .map(|output_directory| overlay_directory.join(output_directory))
+            .or(base.output_directory),
+        paper_size: overlay.paper_size.or(base.paper_size),
+        orientation: overlay.orientation.or(base.orientation),
+        margins: overlay.margins.or(base.margins),
+        language: overlay.language.or(base.language),
+        locale: overlay.locale.or(base.locale),
+        doc_info,
+        defaults,
+        stylesheet: overlay
+            .stylesheet
+            .map(|stylesheet| overlay_directory.join(stylesheet))
+            .or(base.stylesheet),
+        watermark: overlay.watermark.or(base.watermark),
+        image_max_dpi: overlay.image_max_dpi.or(base.image_max_dpi),
+        jpeg_quality: overlay.jpeg_quality.or(base.jpeg_quality),
+        flags,
+        profile: base.profile,
+    }
+}
+
+/// This function validates a configuration file without applying it: the settings are read, their
+/// values checked and the source documents and stylesheet looked for.
+///
+/// # Arguments
+///
+/// * `path` - The path of the configuration file (`.toml`, or `.yaml` / `.yml`).
+///
+/// # Returns
+///
+/// A `Vec<ConfigDiagnostic>` with the problems found (empty if there are none). A file that cannot
+/// be read or parsed gives a single `ConfigDiagnostic::InvalidFile`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_composer::validate_config;
+/// use std::path::Path;
+///
+/// for diagnostic in validate_config(Path::new("pdf_composer.toml")) {
+///     println!("{:?}: {}", diagnostic.severity(), diagnostic);
+/// }
+/// ```
+pub fn validate_config(path: &Path) -> Vec<ConfigDiagnostic> {
+    let config = match read_config(path) {
+        Ok(config) => config,
+        Err(error) => return vec![ConfigDiagnostic::InvalidFile(error.to_string())],
+    };
+    let config_directory = path.parent().unwrap_or(Path::new(""));
+
+    let mut diagnostics = invalid_values(&config);
+    diagnostics.extend(
+        config
+            .sources
+            .iter()
+            .map(|source| config_directory.join(source))
+            .filter(|source| !source.exists())
+            .map(ConfigDiagnostic::SourceNotFound),
+    );
+    let profile_stylesheets = config.profile.values().map(|profile| &profile.stylesheet);
+    for stylesheet in std::iter::once(&config.stylesheet)
+        .chain(profile_stylesheets)
+        .flatten()
+    {
+        let stylesheet = config_directory.join(stylesheet);
+        if !stylesheet.is_file() {
+            diagnostics.push(ConfigDiagnostic::StylesheetNotFound(stylesheet));
+        }
+    }
+    diagnostics
+}
+
+/// This function returns the JSON Schema of the configuration file, derived from
+/// `PDFComposerConfig`, for editors to complete and validate `pdf_composer.toml` (and `.yaml`)
+/// files. It is the schema published as `pdf_composer.schema.json`.
+///
+/// # Examples
+///
+/// ```
+/// use pdf_composer::config_schema;
+///
+/// let schema = config_schema();
+/// assert!(schema.contains("\"paper_size\""));
+/// ```
+pub fn config_schema() -> String {
+    let schema = schema_for!(PDFComposerConfig);
+    serde_json::to_string_pretty(&schema).unwrap_or_default()
+}
+
+/// Returns the settings of a configuration whose values are not valid.
+fn invalid_values(config: &PDFComposerConfig) -> Vec<ConfigDiagnostic> {
+    let invalid_value = |setting: &str, message: String| ConfigDiagnostic::InvalidValue {
+        setting: setting.to_string(),
+        message,
+    };
+    let mut diagnostics = Vec::new();
+    if let Some(Err(message)) = config.paper_size.as_deref().map(str::parse::<PaperSize>) {
+        diagnostics.push(invalid_value("paper_size", message));
+    }
+    if let Some(Err(message)) = config
+        .orientation
+        .as_deref()
+        .map(str::parse::<PaperOrientation>)
+    {
+        diagnostics.push(invalid_value("orientation", message));
+    }
+    if let Some(margins) = &config.margins {
+        let margin_count = margins.split_whitespace().count();
+        if margins
+            .split_whitespace()
+            .any(|margin| margin.parse::<u32>().is_err())
+        {
+            diagnostics.push(invalid_value(
+                "margins",
+                format!("{} are not whole millimetres", margins),
+            ));
+        } else if ![1, 2, 3, 4].contains(&margin_count) {
+            diagnostics.push(invalid_value(
+                "margins",
+                format!("{} are not 1 to 4 values", margins),
+            ));
+        }
+    }
+    if config
+        .language
+        .as_ref()
+        .is_some_and(|language| language.trim().is_empty())
+    {
+        diagnostics.push(invalid_value("language", "is empty".to_string()));
+    }
+    if let Some(Err(message)) = config.locale.as_deref().map(str::parse::<Locale>) {
+        diagnostics.push(invalid_value("locale", message));
+    }
+    if config.image_max_dpi == Some(0) {
+        diagnostics.push(invalid_value("image_max_dpi", "is 0".to_string()));
+    }
+    if let Some(jpeg_quality) = config
+        .jpeg_quality
+        .filter(|quality| !(1..=100).contains(quality))
+    {
+        diagnostics.push(invalid_value(
+            "jpeg_quality",
+            format!("{} is not from 1 to 100", jpeg_quality),
+        ));
+    }
+    for (name, profile) in &config.profile {
+        let setting = |setting: &str| format!("profile.{}.{}", name, setting);
+        if !profile.sources.is_empty() {
+            diagnostics.push(invalid_value(
+                &setting("sources"),
+                "cannot be set in a profile".to_string(),
+            ));
+        }
+        if !profile.source_extensions.is_empty() {
+            diagnostics.push(invalid_value(
+                &setting("source_extensions"),
+                "cannot be set in a profile".to_string(),
+            ));
+        }
+        if !profile.profile.is_empty() {
+            diagnostics.push(invalid_value(
+                &setting("profile"),
+                "profiles cannot have profiles".to_string(),
+            ));
+        }
+        diagnostics.extend(invalid_values(profile).into_iter().map(
+            |diagnostic| match diagnostic {
+                ConfigDiagnostic::InvalidValue {
+                    setting: name,
+                    message,
+                } => invalid_value(&setting(&name), message),
+                diagnostic => diagnostic,
+            },
+        ));
+    }
+    diagnostics
+}
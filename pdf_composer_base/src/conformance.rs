@@ -0,0 +1,517 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{
+    dictionary, Dictionary, Document, Object as LopdfObject, ObjectId, Stream, StringFormat,
+};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::icc_profile::add_output_intent;
+use crate::stamp::page_media_box;
+use crate::utils::{
+    decode_pdf_text_string, encode_pdf_text_string, escape_html, utc_timestamp, write_atomically,
+};
+use pdf_composer_definitions::conformance::Conformance;
+use pdf_composer_definitions::icc_profile::IccProfile;
+use pdf_composer_definitions::pdf_version::PDFVersion;
+
+/// This function makes a document conform to a standard, or says why it cannot.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`, with its document information entries in place.
+/// * `conformance` - The standard the document must conform to.
+/// * `fallback_title` - The title for a document without a `Title` entry (such as the file name).
+///
+/// # Returns
+///
+/// * `Ok(())` if the document now conforms.
+/// * `Err(message)` saying why the document cannot conform (fonts that are not embedded, a PDF version the standard does not allow).
+///
+/// # Remarks
+///
+/// For PDF/X-4, every page gets a `TrimBox` and `BleedBox` (its media box) unless it has them,
+/// transparency groups get a colour space, and the document gets an sRGB output intent (unless it
+/// has a PDF/X output intent), the `GTS_PDFXVersion`, `Trapped`, `Title` and date document
+/// information entries, matching XMP metadata and a document ID.
+pub fn apply_conformance(
+    doc: &mut Document,
+    conformance: Conformance,
+    fallback_title: &str,
+) -> Result<(), String> {
+    if let Ok(pdf_version) = doc.version.parse::<PDFVersion>() {
+        if !conformance.pdf_feature().allows(pdf_version) {
+            return Err(format!(
+                "{} does not allow PDF {} documents",
+                conformance, pdf_version
+            ));
+        }
+    }
+
+    match conformance {
+        Conformance::PdfX4 => {
+            let missing_fonts = fonts_not_embedded(doc);
+            if !missing_fonts.is_empty() {
+                return Err(format!(
+                    "{} needs embedded fonts, but these fonts are not embedded: {} (page numbers and running headers use the standard PDF fonts)",
+                    conformance,
+                    missing_fonts.into_iter().collect::<Vec<_>>().join(", ")
+                ));
+            }
+            set_page_boxes(doc);
+            set_transparency_group_colour_spaces(doc);
+            ensure_output_intent(doc)?;
+            let info_id = set_pdf_x_info(doc, fallback_title);
+            set_xmp_metadata(doc, info_id).map_err(|error| error.to_string())?;
+            Ok(())
+        }
+    }
+}
+
+/// This function makes a saved PDF conform to a standard (after pages were added to it, such as a
+/// cover).
+///
+/// # Arguments
+///
+/// * `path` - The path of the PDF, which is overwritten.
+/// * `conformance` - The standard the PDF must conform to.
+///
+/// # Returns
+///
+/// * `Ok(())` if the PDF was updated.
+/// * `Err(e)` if the PDF could not be read, cannot conform or could not be saved.
+pub fn apply_conformance_to_file(
+    path: &Path,
+    conformance: Conformance,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut doc = Document::load(path)?;
+    let fallback_title = path
+        .file_stem()
+        .map(|file_stem| file_stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    apply_conformance(&mut doc, conformance, &fallback_title)?;
+    write_atomically(path, |writer| doc.save_to(writer))?;
+    Ok(())
+}
+
+/// Returns the names of the fonts without an embedded font program (Type 3 fonts draw their glyphs
+/// themselves, and composite fonts are checked through their descendant fonts).
+fn fonts_not_embedded(doc: &Document) -> BTreeSet<String> {
+    let mut missing_fonts = BTreeSet::new();
+    for object in doc.objects.values() {
+        let Ok(font) = object.as_dict() else {
+            continue;
+        };
+        if !font.type_is(b"Font") {
+            continue;
+        }
+        let subtype = font.get(b"Subtype").and_then(LopdfObject::as_name_str);
+        if matches!(subtype, Ok("Type3") | Ok("Type0")) {
+            continue;
+        }
+        let is_embedded = font
+            .get(b"FontDescriptor")
+            .ok()
+            .and_then(|descriptor| doc.dereference(descriptor).ok())
+            .and_then(|(_, descriptor)| descriptor.as_dict().ok())
+            .is_some_and(|descriptor| {
+                [b"FontFile".as_slice(), b"FontFile2", b"FontFile3"]
+                    .iter()
+                    .any(|key| descriptor.has(key))
+            });
+        if !is_embedded {
+            let name = font
+                .get(b"BaseFont")
+                .and_then(LopdfObject::as_name_str)
+                .unwrap_or("unnamed font");
+            missing_fonts.insert(name.to_string());
+        }
+    }
+    missing_fonts
+}
+
+/// Gives every page a `TrimBox` (unless it has a `TrimBox` or `ArtBox`) and a `BleedBox`, both its
+/// media box: the paper size is the trimmed size, without bleed.
+fn set_page_boxes(doc: &mut Document) {
+    for page_id in doc.get_pages().into_values() {
+        let Some(media_box) = page_media_box(doc, page_id) else {
+            continue;
+        };
+        let media_box: Vec<LopdfObject> = media_box.iter().map(|value| (*value).into()).collect();
+        let Ok(page) = doc.get_dictionary_mut(page_id) else {
+            continue;
+        };
+        if !page.has(b"TrimBox") && !page.has(b"ArtBox") {
+            page.set("TrimBox", media_box.clone());
+        }
+        if !page.has(b"BleedBox") {
+            page.set("BleedBox", media_box);
+        }
+    }
+}
+
+/// Gives the transparency group of every page using transparency a blending colour space
+/// (`DeviceRGB`, the colour space Chromium draws in), adding the group if the page has none.
+fn set_transparency_group_colour_spaces(doc: &mut Document) {
+    for page_id in doc.get_pages().into_values() {
+        let group = doc
+            .get_dictionary(page_id)
+            .ok()
+            .and_then(|page| page.get(b"Group").ok())
+            .cloned();
+        match group {
+            Some(LopdfObject::Reference(group_id)) => {
+                if let Ok(group) = doc.get_dictionary_mut(group_id) {
+                    set_group_colour_space(group);
+                }
+            }
+            Some(LopdfObject::Dictionary(mut group)) => {
+                set_group_colour_space(&mut group);
+                if let Ok(page) = doc.get_dictionary_mut(page_id) {
+                    page.set("Group", group);
+                }
+            }
+            _ if page_uses_transparency(doc, page_id) => {
+                if let Ok(page) = doc.get_dictionary_mut(page_id) {
+                    page.set(
+                        "Group",
+                        dictionary! {
+                            "Type" => "Group",
+                            "S" => "Transparency",
+                            "CS" => "DeviceRGB",
+                        },
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Sets the colour space of a transparency group that has none.
+fn set_group_colour_space(group: &mut Dictionary) {
+    let is_transparency_group = group
+        .get(b"S")
+        .and_then(LopdfObject::as_name_str)
+        .is_ok_and(|subtype| subtype == "Transparency");
+    if is_transparency_group && !group.has(b"CS") {
+        group.set("CS", "DeviceRGB");
+    }
+}
+
+/// Returns whether the resources of a page use transparency: soft masks, constant opacity below 1
+/// or blend modes other than `Normal` in its graphics states, or images with soft masks.
+fn page_uses_transparency(doc: &Document, page_id: ObjectId) -> bool {
+    let (Some(resources), resource_ids) = doc.get_page_resources(page_id) else {
+        return false;
+    };
+    let resources: Vec<&Dictionary> = std::iter::once(resources)
+        .chain(
+            resource_ids
+                .iter()
+                .filter_map(|resource_id| doc.get_dictionary(*resource_id).ok()),
+        )
+        .collect();
+
+    let entries = |resources: &Dictionary, key: &[u8]| -> Vec<Dictionary> {
+        resources
+            .get(key)
+            .ok()
+            .and_then(|entries| doc.dereference(entries).ok())
+            .and_then(|(_, entries)| entries.as_dict().ok())
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|(_, entry)| doc.dereference(entry).ok())
+                    .filter_map(|(_, entry)| match entry {
+                        LopdfObject::Dictionary(dictionary) => Some(dictionary.clone()),
+                        LopdfObject::Stream(stream) => Some(stream.dict.clone()),
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    resources.iter().any(|resources| {
+        let graphics_states = entries(resources, b"ExtGState");
+        let images = entries(resources, b"XObject");
+        graphics_states.iter().any(|state| {
+            let has_soft_mask = state
+                .get(b"SMask")
+                .is_ok_and(|mask| !matches!(mask.as_name_str(), Ok("None")));
+            let is_translucent = [b"CA".as_slice(), b"ca"].iter().any(|key| {
+                state
+                    .get(key)
+                    .and_then(LopdfObject::as_float)
+                    .is_ok_and(|opacity| opacity < 1.0)
+            });
+            let has_blend_mode = state
+                .get(b"BM")
+                .is_ok_and(|blend_mode| !matches!(blend_mode.as_name_str(), Ok("Normal")));
+            has_soft_mask || is_translucent || has_blend_mode
+        }) || images
+            .iter()
+            .any(|image| image.has(b"SMask") || image.has(b"SMaskInData"))
+    })
+}
+
+/// Adds an sRGB output intent for PDF/X to the catalog, unless it has one (such as the output
+/// intent set with `set_output_intent`).
+fn ensure_output_intent(doc: &mut Document) -> Result<(), String> {
+    let has_pdf_x_intent = doc
+        .catalog()
+        .map_err(|error| error.to_string())?
+        .get(b"OutputIntents")
+        .ok()
+        .and_then(|intents| doc.dereference(intents).ok())
+        .and_then(|(_, intents)| intents.as_array().ok())
+        .is_some_and(|intents| {
+            intents.iter().any(|intent| {
+                doc.dereference(intent)
+                    .ok()
+                    .and_then(|(_, intent)| intent.as_dict().ok())
+                    .and_then(|intent| intent.get(b"S").ok())
+                    .and_then(|subtype| subtype.as_name_str().ok())
+                    == Some("GTS_PDFX")
+            })
+        });
+    if has_pdf_x_intent {
+        return Ok(());
+    }
+    add_output_intent(doc, &IccProfile::SRGB)
+}
+
+/// Sets the document information entries PDF/X needs (version, trapping, title and dates), and a
+/// document ID. Returns the `ObjectId` of the document information dictionary.
+fn set_pdf_x_info(doc: &mut Document, fallback_title: &str) -> ObjectId {
+    let info_id = match doc.trailer.get(b"Info").and_then(LopdfObject::as_reference) {
+        Ok(info_id) if doc.get_dictionary(info_id).is_ok() => info_id,
+        _ => {
+            let info_id = doc.add_object(Dictionary::new());
+            doc.trailer.set("Info", info_id);
+            info_id
+        }
+    };
+    let now = pdf_date(&utc_timestamp(SystemTime::now()));
+
+    if let Ok(info) = doc.get_dictionary_mut(info_id) {
+        info.set("GTS_PDFXVersion", LopdfObject::string_literal("PDF/X-4"));
+        // Trapping must be known (True or False); nothing is trapped here
+        if !matches!(
+            info.get(b"Trapped").and_then(LopdfObject::as_name_str),
+            Ok("True")
+        ) {
+            info.set("Trapped", "False");
+        }
+        let has_title = info
+            .get(b"Title")
+            .and_then(LopdfObject::as_str)
+            .is_ok_and(|title| !decode_pdf_text_string(title).trim().is_empty());
+        if !has_title {
+            info.set("Title", encode_pdf_text_string(fallback_title));
+        }
+        for key in ["CreationDate", "ModDate"] {
+            if !info.has(key.as_bytes()) {
+                info.set(key, LopdfObject::string_literal(now.as_str()));
+            }
+        }
+    }
+
+    if !doc.trailer.has(b"ID") {
+        let id = document_id(doc, info_id);
+        doc.trailer.set(
+            "ID",
+            vec![
+                LopdfObject::String(id.clone(), StringFormat::Hexadecimal),
+                LopdfObject::String(id, StringFormat::Hexadecimal),
+            ],
+        );
+    }
+    info_id
+}
+
+/// Returns a document ID (16 bytes, from a SHA-256 digest) of the document information entries and
+/// the number of pages.
+fn document_id(doc: &Document, info_id: ObjectId) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    if let Ok(info) = doc.get_dictionary(info_id) {
+        for (key, value) in info.iter() {
+            // Each entry written with its lengths, so no two sets of entries run together the same
+            hasher.update((key.len() as u64).to_be_bytes());
+            hasher.update(key);
+            let text = value.as_str().unwrap_or_default();
+            hasher.update((text.len() as u64).to_be_bytes());
+            hasher.update(text);
+        }
+    }
+    hasher.update((doc.get_pages().len() as u64).to_be_bytes());
+    hasher.finalize()[..16].to_vec()
+}
+
+/// Sets the XMP metadata of the document, matching its document information entries.
+fn set_xmp_metadata(doc: &mut Document, info_id: ObjectId) -> Result<(), lopdf::Error> {
+    let text = |key: &[u8]| -> Option<String> {
+        doc.get_dictionary(info_id)
+            .ok()?
+            .get(key)
+            .and_then(LopdfObject::as_str)
+            .ok()
+            .map(decode_pdf_text_string)
+            .filter(|text| !text.trim().is_empty())
+    };
+    let now = utc_timestamp(SystemTime::now());
+    let create_date = text(b"CreationDate")
+        .and_then(|date| xmp_date(&date))
+        .unwrap_or(now.clone());
+    let modify_date = text(b"ModDate")
+        .and_then(|date| xmp_date(&date))
+        .unwrap_or(now.clone());
+    let document_uuid = doc
+        .trailer
+        .get(b"ID")
+        .and_then(LopdfObject::as_array)
+        .ok()
+        .and_then(|ids| ids.first())
+        .and_then(|id| id.as_str().ok())
+        .map(uuid)
+        .unwrap_or_default();
+
+    let mut properties = vec![
+        "<dc:format>application/pdf</dc:format>".to_string(),
+        format!(
+            "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:title>",
+            escape_html(&text(b"Title").unwrap_or_default())
+        ),
+    ];
+    if let Some(author) = text(b"Author") {
+        properties.push(format!(
+            "<dc:creator><rdf:Seq><rdf:li>{}</rdf:li></rdf:Seq></dc:creator>",
+            escape_html(&author)
+        ));
+    }
+    if let Some(subject) = text(b"Subject") {
+        properties.push(format!(
+            "<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:description>",
+            escape_html(&subject)
+        ));
+    }
+    if let Some(keywords) = text(b"Keywords") {
+        properties.push(format!(
+            "<pdf:Keywords>{}</pdf:Keywords>",
+            escape_html(&keywords)
+        ));
+    }
+    if let Some(creator) = text(b"Creator") {
+        properties.push(format!(
+            "<xmp:CreatorTool>{}</xmp:CreatorTool>",
+            escape_html(&creator)
+        ));
+    }
+    if let Some(producer) = text(b"Producer") {
+        properties.push(format!(
+            "<pdf:Producer>{}</pdf:Producer>",
+            escape_html(&producer)
+        ));
+    }
+    properties.extend([
+        format!("<xmp:CreateDate>{}</xmp:CreateDate>", create_date),
+        format!("<xmp:ModifyDate>{}</xmp:ModifyDate>", modify_date),
+        format!("<xmp:MetadataDate>{}</xmp:MetadataDate>", modify_date),
+        "<pdf:Trapped>False</pdf:Trapped>".to_string(),
+        format!(
+            "<xmpMM:DocumentID>uuid:{}</xmpMM:DocumentID>",
+            document_uuid
+        ),
+        format!(
+            "<xmpMM:InstanceID>uuid:{}</xmpMM:InstanceID>",
+            document_uuid
+        ),
+        "<xmpMM:RenditionClass>default</xmpMM:RenditionClass>".to_string(),
+        "<xmpMM:VersionID>1</xmpMM:VersionID>".to_string(),
+        "<pdfxid:GTS_PDFXVersion>PDF/X-4</pdfxid:GTS_PDFXVersion>".to_string(),
+    ]);
+
+    let packet = format!(
+        "<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+         <x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+         <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+         <rdf:Description rdf:about=\"\" \
+         xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+         xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\" \
+         xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\" \
+         xmlns:xmpMM=\"http://ns.adobe.com/xap/1.0/mm/\" \
+         xmlns:pdfxid=\"http://www.npes.org/pdfx/ns/id/\">\n\
+         {}\n\
+         </rdf:Description>\n\
+         </rdf:RDF>\n\
+         </x:xmpmeta>\n\
+         <?xpacket end=\"w\"?>",
+        properties.join("\n")
+    );
+
+    // Metadata is left uncompressed, so tools that do not read PDF can find it
+    let mut metadata = Stream::new(
+        dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+        packet.into_bytes(),
+    );
+    metadata.allows_compression = false;
+    let metadata_id = doc.add_object(metadata);
+    doc.catalog_mut()?.set("Metadata", metadata_id);
+    Ok(())
+}
+
+/// Formats an ISO 8601 UTC timestamp (`2024-05-01T09:30:00Z`) as a PDF date (`D:20240501093000Z`).
+pub fn pdf_date(timestamp: &str) -> String {
+    format!("D:{}", timestamp.replace(['-', ':', 'T'], ""))
+}
+
+/// Converts a PDF date (`D:20240501093000+01'00'`) to an XMP date (`2024-05-01T09:30:00+01:00`).
+fn xmp_date(pdf_date: &str) -> Option<String> {
+    let date_regex = Regex::new(
+        r"^D:(\d{4})(\d{2})?(\d{2})?(\d{2})?(\d{2})?(\d{2})?(?:([Zz])|([+-])(\d{2})'?(\d{2})?'?)?",
+    )
+    .unwrap();
+    let captures = date_regex.captures(pdf_date.trim())?;
+    let part = |index: usize, default: &str| {
+        captures
+            .get(index)
+            .map_or(default.to_string(), |part| part.as_str().to_string())
+    };
+    let time_zone = match captures.get(8) {
+        Some(sign) => format!("{}{}:{}", sign.as_str(), part(9, "00"), part(10, "00")),
+        None => "Z".to_string(),
+    };
+    Some(format!(
+        "{}-{}-{}T{}:{}:{}{}",
+        part(1, "0000"),
+        part(2, "01"),
+        part(3, "01"),
+        part(4, "00"),
+        part(5, "00"),
+        part(6, "00"),
+        time_zone
+    ))
+}
+
+/// Formats 16 bytes as a UUID (other lengths are hashed first).
+fn uuid(bytes: &[u8]) -> String {
+    let bytes: Vec<u8> = if bytes.len() == 16 {
+        bytes.to_vec()
+    } else {
+        Sha256::digest(bytes)[..16].to_vec()
+    };
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
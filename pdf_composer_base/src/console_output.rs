@@ -0,0 +1,34 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::Once;
+
+/// Makes sure the colours are chosen once per process.
+static COLOURS_CHOSEN: Once = Once::new();
+
+/// This function chooses (once per process) whether the console output is coloured, from the
+/// environment: never with `NO_COLOR` (or `CLICOLOR=0`), always with `CLICOLOR_FORCE`, and
+/// otherwise only when both stdout and stderr are terminals.
+///
+/// # Remarks
+///
+/// The messages are coloured before it is known which stream they are printed to, so both must be
+/// terminals: a log file or CI capturing either stream then gets no ANSI escapes. An application
+/// can still decide for itself afterwards with `colored::control::set_override`.
+pub fn choose_colours() {
+    COLOURS_CHOSEN.call_once(|| {
+        let colourise =
+            if env::var("CLICOLOR_FORCE").is_ok_and(|value| !value.is_empty() && value != "0") {
+                true
+            } else if env::var("NO_COLOR").is_ok_and(|value| !value.is_empty())
+                || env::var("CLICOLOR").as_deref() == Ok("0")
+            {
+                false
+            } else {
+                io::stdout().is_terminal() && io::stderr().is_terminal()
+            };
+        colored::control::set_override(colourise);
+    });
+}
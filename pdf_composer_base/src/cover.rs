@@ -0,0 +1,92 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId};
+use std::path::Path;
+
+use crate::merge::save_document;
+use crate::page_tree::{page_with_inherited_attributes, rebuild_page_tree};
+
+/// This function puts the pages of a cover PDF before the first page of a generated PDF.
+///
+/// # Arguments
+///
+/// * `path` - The path of the generated PDF, which is updated.
+/// * `cover` - The path of the cover PDF.
+///
+/// # Returns
+///
+/// * `Ok(u32)` with the number of cover pages, if the cover was added.
+/// * `Err(e)` if either PDF could not be read or the updated PDF could not be written.
+///
+/// # Remarks
+///
+/// Unlike merging, the catalog of the generated PDF is kept, so its outline, language, named
+/// destinations and tagged structure still apply. Its page labels are moved along by the number of
+/// cover pages, and the cover pages get no page label.
+pub fn prepend_cover(path: &Path, cover: &Path) -> Result<u32, Box<dyn std::error::Error>> {
+    let mut doc = Document::load(path)?;
+    let mut cover_doc = Document::load(cover)
+        .map_err(|error| format!("could not load cover {}: {}", cover.display(), error))?;
+
+    // Give the cover objects ids that are not used in the generated PDF
+    cover_doc.renumber_objects_with(doc.max_id + 1);
+    doc.max_id = cover_doc.max_id;
+
+    let mut pages: Vec<(ObjectId, Dictionary)> = Vec::new();
+    for page_id in cover_doc.get_pages().into_values() {
+        pages.push((
+            page_id,
+            page_with_inherited_attributes(&cover_doc, page_id)?,
+        ));
+    }
+    let cover_page_count = pages.len() as i64;
+    for page_id in doc.get_pages().into_values() {
+        pages.push((page_id, page_with_inherited_attributes(&doc, page_id)?));
+    }
+
+    let cover_catalog_id = cover_doc.trailer.get(b"Root")?.as_reference()?;
+    for (object_id, object) in cover_doc.objects {
+        let is_page_tree_node = matches!(object.type_name(), Ok("Pages") | Ok("Page"));
+        if object_id != cover_catalog_id && !is_page_tree_node {
+            doc.objects.insert(object_id, object);
+        }
+    }
+
+    rebuild_page_tree(&mut doc, pages)?;
+    shift_page_labels(&mut doc, cover_page_count)?;
+
+    // Drop the old page tree nodes and the cover document information
+    doc.prune_objects();
+    save_document(&mut doc, path)?;
+    Ok(cover_page_count as u32)
+}
+
+/// Moves the page labels of a document along by `offset` pages, leaving the first pages unlabelled.
+fn shift_page_labels(doc: &mut Document, offset: i64) -> Result<(), lopdf::Error> {
+    let Ok(page_labels) = doc.catalog()?.get(b"PageLabels") else {
+        return Ok(());
+    };
+    let Ok(nums) = doc
+        .dereference(page_labels)?
+        .1
+        .as_dict()
+        .and_then(|page_labels| page_labels.get(b"Nums"))
+        .and_then(LopdfObject::as_array)
+    else {
+        return Ok(());
+    };
+
+    let mut shifted: Vec<LopdfObject> = vec![LopdfObject::Integer(0), Dictionary::new().into()];
+    for pair in nums.chunks_exact(2) {
+        if let Ok(page_index) = pair[0].as_i64() {
+            shifted.push(LopdfObject::Integer(page_index + offset));
+            shifted.push(pair[1].clone());
+        }
+    }
+
+    let mut page_labels = Dictionary::new();
+    page_labels.set("Nums", shifted);
+    doc.catalog_mut()?.set("PageLabels", page_labels);
+    Ok(())
+}
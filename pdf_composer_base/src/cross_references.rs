@@ -0,0 +1,224 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use regex::{Captures, Regex};
+use std::collections::BTreeMap;
+
+use crate::utils::{escape_html, map_outside_code};
+use pdf_composer_definitions::consts::CROSS_MARK;
+
+/// The characters allowed in a label, after its `sec:`, `fig:` or `tbl:` prefix
+/// (a trailing `.` or `-` is treated as punctuation, not part of the label).
+const LABEL_PATTERN: &str = r"(?:sec|fig|tbl):[\w:.\-]*\w";
+
+/// This function numbers the labelled headings, figures and tables in the generated HTML and
+/// resolves the references to them.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+///
+/// # Returns
+///
+/// A `String` with the labels turned into element ids and the references turned into internal links.
+///
+/// # Remarks
+///
+/// Labels are written after the element:
+///
+/// * Headings: `## Introduction {#sec:intro}`. Sections are numbered by their position in the
+///   heading structure (for example, `2.1`), starting from the highest heading level used.
+/// * Figures: `![The architecture](architecture.png){#fig:architecture}`, on its own paragraph. The image
+///   is wrapped in a figure with its alt text as the caption (`Figure 3: The architecture`).
+/// * Tables: a `Table: Results {#tbl:results}` paragraph directly before (or after) the table becomes its
+///   caption (`Table 1: Results`).
+///
+/// References are written `@fig:architecture`, `[@fig:architecture]` or `[see @fig:architecture]`
+/// (with `;` between several references), and become links reading `Figure 3`, `Section 2.1` or `Table 1`.
+/// References inside code are left alone, and references to unknown labels are left as written.
+pub fn resolve_cross_references(html: &str) -> String {
+    if !html.contains("{#") {
+        return html.to_string();
+    }
+
+    let mut labels: BTreeMap<String, String> = BTreeMap::new();
+    let html = label_headings(html, &mut labels);
+    let html = label_figures(&html, &mut labels);
+    let html = label_tables(&html, &mut labels);
+
+    let bracketed_regex =
+        Regex::new(&format!(r"\[([^\[\]<>]*@{}[^\[\]<>]*)\]", LABEL_PATTERN)).unwrap();
+    let reference_regex = Regex::new(&format!(r"(^|[^\w@])@({})", LABEL_PATTERN)).unwrap();
+
+    map_outside_code(&html, |text| {
+        // `[see @fig:a; @fig:b]` reads "see Figure 1, Figure 2" (without the brackets)
+        let text = bracketed_regex.replace_all(text, |captures: &Captures| {
+            let references = captures[1]
+                .split(';')
+                .map(|reference| reference.trim())
+                .collect::<Vec<&str>>()
+                .join(", ");
+            replace_references(&reference_regex, &references, &labels)
+        });
+        replace_references(&reference_regex, &text, &labels)
+    })
+}
+
+/// Replaces each `@label` reference in the text with a link to the labelled element.
+fn replace_references(
+    reference_regex: &Regex,
+    text: &str,
+    labels: &BTreeMap<String, String>,
+) -> String {
+    reference_regex
+        .replace_all(text, |captures: &Captures| {
+            let label = &captures[2];
+            match labels.get(label) {
+                Some(name) => format!(
+                    "{}<a class=\"cross-reference\" href=\"#{}\">{}</a>",
+                    &captures[1],
+                    escape_html(label),
+                    name
+                ),
+                None => {
+                    eprintln!(
+                        "{}{}",
+                        CROSS_MARK.yellow(),
+                        format!("Cross-reference @{} has no matching label", label).yellow()
+                    );
+                    captures[0].to_string()
+                }
+            }
+        })
+        .to_string()
+}
+
+/// Numbers every heading and turns `{#sec:...}` labels into heading ids.
+fn label_headings(html: &str, labels: &mut BTreeMap<String, String>) -> String {
+    let heading_regex = Regex::new(r"(?s)<h([1-6])>(.*?)</h[1-6]>").unwrap();
+    let label_regex = Regex::new(r"\s*\{#(sec:[\w:.\-]*\w)\}\s*$").unwrap();
+
+    let top_level = heading_regex
+        .captures_iter(html)
+        .filter_map(|captures| captures[1].parse::<usize>().ok())
+        .min()
+        .unwrap_or(1);
+
+    let mut counters = [0usize; 6];
+    heading_regex
+        .replace_all(html, |captures: &Captures| {
+            let level: usize = captures[1].parse().unwrap_or(1);
+            counters[level - 1] += 1;
+            counters[level..].fill(0);
+
+            let content = &captures[2];
+            match label_regex.captures(content) {
+                Some(label_captures) => {
+                    let label = label_captures[1].to_string();
+                    let number = counters[top_level - 1..level]
+                        .iter()
+                        .map(|counter| counter.to_string())
+                        .collect::<Vec<String>>()
+                        .join(".");
+                    let heading = format!(
+                        "<h{level} id=\"{}\">{}</h{level}>",
+                        escape_html(&label),
+                        &content[..label_captures.get(0).unwrap().start()]
+                    );
+                    labels.insert(label, format!("Section {}", number));
+                    heading
+                }
+                None => captures[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+/// Wraps images labelled `{#fig:...}` in numbered figures, captioned with the image alt text.
+/// (Only an image on its own paragraph becomes a figure.)
+fn label_figures(html: &str, labels: &mut BTreeMap<String, String>) -> String {
+    let figure_regex =
+        Regex::new(r"(<p>)?<img ([^>]*?)\s*/?>\{#(fig:[\w:.\-]*\w)\}(</p>)?").unwrap();
+    let alt_regex = Regex::new(r#"alt="([^"]*)""#).unwrap();
+
+    let mut figure_number = 0;
+    figure_regex
+        .replace_all(html, |captures: &Captures| {
+            figure_number += 1;
+            let label = captures[3].to_string();
+            let name = format!("Figure {}", figure_number);
+            let caption = alt_regex
+                .captures(&captures[2])
+                .map(|alt| format!("{}: {}", name, &alt[1]))
+                .unwrap_or_else(|| name.clone());
+            let id = escape_html(&label);
+            labels.insert(label, name);
+
+            match (captures.get(1), captures.get(4)) {
+                (Some(_), Some(_)) => format!(
+                    "<figure id=\"{}\"><img {} /><figcaption>{}</figcaption></figure>",
+                    id, &captures[2], caption
+                ),
+                // An image within other content cannot become a figure, so it only gets the id
+                (opening, closing) => format!(
+                    "{}<img id=\"{}\" {} />{}",
+                    opening.map_or("", |opening| opening.as_str()),
+                    id,
+                    &captures[2],
+                    closing.map_or("", |closing| closing.as_str())
+                ),
+            }
+        })
+        .to_string()
+}
+
+/// Turns `Table: ... {#tbl:...}` paragraphs into numbered captions of the adjacent tables.
+fn label_tables(html: &str, labels: &mut BTreeMap<String, String>) -> String {
+    let caption_regex = Regex::new(r"<p>Table: (.*?)\s*\{#(tbl:[\w:.\-]*\w)\}</p>").unwrap();
+
+    let mut output = String::with_capacity(html.len());
+    let mut last_end = 0;
+    let mut table_number = 0;
+    for captures in caption_regex.captures_iter(html) {
+        let whole = captures.get(0).unwrap();
+        output.push_str(&html[last_end..whole.start()]);
+        last_end = whole.end();
+
+        table_number += 1;
+        let label = captures[2].to_string();
+        let name = format!("Table {}", table_number);
+        let caption_html = format!(
+            "<table id=\"{}\">\n<caption>{}: {}</caption>",
+            escape_html(&label),
+            name,
+            &captures[1]
+        );
+        labels.insert(label.clone(), name.clone());
+
+        let following = &html[last_end..];
+        if following.trim_start().starts_with("<table>") {
+            // Caption written before the table
+            last_end += following.len() - following.trim_start().len() + "<table>".len();
+            output.push_str(&caption_html);
+        } else if let Some(table_start) = output
+            .trim_end()
+            .ends_with("</table>")
+            .then(|| output.rfind("<table>"))
+            .flatten()
+        {
+            // Caption written after the table
+            output.replace_range(table_start..table_start + "<table>".len(), &caption_html);
+        } else {
+            // No table next to the caption, so it stays a (linkable) paragraph
+            output.push_str(&format!(
+                "<p class=\"table-caption\" id=\"{}\">{}: {}</p>",
+                escape_html(&label),
+                name,
+                &captures[1]
+            ));
+        }
+    }
+    output.push_str(&html[last_end..]);
+    output
+}
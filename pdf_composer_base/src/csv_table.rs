@@ -0,0 +1,115 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use csv::ReaderBuilder;
+
+use crate::shortcodes::{ShortcodeArguments, ShortcodeContext, ShortcodeOutput};
+use crate::utils::escape_html;
+
+/// The styles for the tables read from CSV files, added to the page CSS.
+pub const DATA_TABLE_CSS: &str = "
+.data-table { margin: 1em 0; border-collapse: collapse; font-size: 0.9em; }
+.data-table th, .data-table td { padding: 0.3em 0.7em; border-bottom: 1px solid #d0d7de; }
+.data-table thead th { border-bottom: 2px solid #57606a; }
+.data-table tbody tr:nth-child(even) { background-color: #f6f8fa; }
+.data-table tr { break-inside: avoid; }
+";
+
+/// This function renders the `{{table "data/results.csv"}}` shortcode as a table of the CSV file.
+///
+/// # Arguments
+///
+/// * `arguments` - The shortcode arguments: the path of the CSV file (relative to the source
+///   document), and the optional `columns` (the headers of the columns to show, comma separated),
+///   `align` (`left`, `center` or `right` for each column shown, comma separated) and `delimiter`
+///   (`,` by default).
+/// * `context` - The document the shortcode is in.
+///
+/// # Returns
+///
+/// * `Ok(ShortcodeOutput)` with the table.
+/// * `Err(message)` if the file cannot be read, or a column or alignment is not recognised.
+///
+/// # Remarks
+///
+/// The first row of the CSV file is the table header.
+pub fn csv_table_shortcode(
+    arguments: &ShortcodeArguments,
+    context: &ShortcodeContext,
+) -> Result<ShortcodeOutput, String> {
+    let path = arguments.required(0, "CSV file path")?;
+    let delimiter = match arguments.named.get("delimiter").map(String::as_bytes) {
+        None => b',',
+        Some([delimiter]) => *delimiter,
+        Some(_) => return Err("the delimiter must be a single character".to_string()),
+    };
+
+    let csv_path = context.source_directory.join(path);
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(&csv_path)
+        .map_err(|error| format!("cannot read {}: {}", csv_path.display(), error))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|error| error.to_string())?
+        .iter()
+        .map(str::to_string)
+        .collect();
+
+    // The indexes of the columns to show, in the order given
+    let columns: Vec<usize> = match arguments.named.get("columns") {
+        Some(columns) => columns
+            .split(',')
+            .map(|column| {
+                let column = column.trim();
+                headers
+                    .iter()
+                    .position(|header| header.trim() == column)
+                    .ok_or_else(|| format!("{} has no {} column", path, column))
+            })
+            .collect::<Result<_, String>>()?,
+        None => (0..headers.len()).collect(),
+    };
+
+    let alignments: Vec<&str> = match arguments.named.get("align") {
+        Some(align) => align
+            .split(',')
+            .map(|alignment| match alignment.trim() {
+                alignment @ ("left" | "center" | "right") => Ok(alignment),
+                alignment => Err(format!("unknown alignment {}", alignment)),
+            })
+            .collect::<Result<_, String>>()?,
+        None => Vec::new(),
+    };
+    let align_attribute = |position: usize| {
+        alignments.get(position).map_or(String::new(), |alignment| {
+            format!(" align=\"{}\"", alignment)
+        })
+    };
+
+    let mut html = String::from("<table class=\"data-table\">\n<thead>\n<tr>\n");
+    for (position, column) in columns.iter().enumerate() {
+        html.push_str(&format!(
+            "<th{}>{}</th>\n",
+            align_attribute(position),
+            escape_html(&headers[*column])
+        ));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+    for record in reader.records() {
+        let record = record.map_err(|error| error.to_string())?;
+        html.push_str("<tr>\n");
+        for (position, column) in columns.iter().enumerate() {
+            html.push_str(&format!(
+                "<td{}>{}</td>\n",
+                align_attribute(position),
+                escape_html(record.get(*column).unwrap_or_default())
+            ));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>");
+
+    Ok(ShortcodeOutput { html, block: true })
+}
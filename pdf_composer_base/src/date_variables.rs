@@ -0,0 +1,361 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use crate::deterministic::source_date;
+use crate::utils::{civil_date, ESCAPED_PLACEHOLDER_PATTERN};
+use pdf_composer_definitions::date_options::{DateLocale, DateOptions};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// The format of the `{{today}}` placeholder (without a locale, see `set_locale`).
+pub const TODAY_FORMAT: &str = "%Y-%m-%d";
+/// The format of the `{{now}}` placeholder (without a locale, see `set_locale`).
+pub const NOW_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// This function fills in the date placeholders of a document: `{{build_date "format"}}` in the
+/// markdown, and `{{today}}` and `{{now}}` (as front matter values, so they work wherever
+/// placeholders do).
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the `DateOptions` (and whether output is deterministic).
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) of the document. `today` and `now`
+///   are added when the markdown or QR code footer refer to them and the front matter does not
+///   set them itself.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+///
+/// # Returns
+///
+/// The markdown with the `{{build_date "format"}}` placeholders replaced.
+///
+/// # Remarks
+///
+/// The date is the time the document is generated, or the `SOURCE_DATE_EPOCH` time with
+/// deterministic output (see `set_deterministic`), in the time zone of the `DateOptions`.
+/// `today` and `now` are written in the date formats of the locale, if there is one (see
+/// `set_locale`). They are only added when they are used, so HTML cached for a document that does
+/// not use them stays valid.
+pub fn fill_date_placeholders(
+    composer: &PDFComposerStruct,
+    yaml_btreemap: &mut BTreeMap<String, Value>,
+    markdown_content: &str,
+) -> String {
+    let time = if composer.deterministic {
+        source_date()
+    } else {
+        SystemTime::now()
+    };
+    let date_options = &composer.date_options;
+    let (today_format, now_format) = match &composer.locale {
+        Some(locale) => (locale.date_format, locale.date_time_format),
+        None => (TODAY_FORMAT, NOW_FORMAT),
+    };
+
+    let templates = [
+        markdown_content,
+        composer.qr_code_footer.as_deref().unwrap_or(""),
+        composer.watermark.as_deref().unwrap_or(""),
+    ];
+    for (key, format) in [("today", today_format), ("now", now_format)] {
+        let placeholder = format!("{{{{{}}}}}", key);
+        if !yaml_btreemap.contains_key(key)
+            && templates
+                .iter()
+                .any(|template| template.contains(&placeholder))
+        {
+            yaml_btreemap.insert(
+                key.to_string(),
+                Value::String(format_date(time, date_options, format)),
+            );
+        }
+    }
+
+    if !markdown_content.contains("{{build_date") {
+        return markdown_content.to_string();
+    }
+    let regex = Regex::new(&format!(
+        r#"{}|\{{\{{build_date\s+"([^"]*)"\s*\}}\}}"#,
+        ESCAPED_PLACEHOLDER_PATTERN
+    ))
+    .unwrap();
+    regex
+        .replace_all(markdown_content, |captures: &Captures<'_>| {
+            match captures.get(1) {
+                Some(format) => format_date(time, date_options, format.as_str()),
+                // An escaped placeholder (see `unescape_placeholders`)
+                None => captures[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+/// This function formats a time with `strftime` style conversion specifications.
+///
+/// # Arguments
+///
+/// * `time` - The time to format.
+/// * `date_options` - The time zone (UTC offset) and the language of month and day names.
+/// * `format` - The format, with the conversion specifications `%Y` (year), `%y` (two digit
+///   year), `%m` (month), `%B` and `%b` (full and abbreviated month name), `%d` and `%e` (day of
+///   the month, zero and space padded), `%A` and `%a` (full and abbreviated day name), `%j` (day
+///   of the year), `%H`, `%I`, `%M` and `%S` (hours, 12-hour clock hours, minutes and seconds),
+///   `%p` (AM or PM), `%z` (UTC offset) and `%%`. Other characters are copied as they are.
+///
+/// # Returns
+///
+/// A `String` with the formatted time.
+pub fn format_date(time: SystemTime, date_options: &DateOptions, format: &str) -> String {
+    let utc_seconds = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(error) => -(error.duration().as_secs() as i64),
+    };
+    let seconds = utc_seconds + i64::from(date_options.utc_offset_minutes) * 60;
+    let (days, seconds_of_day) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_date(days);
+    let (hour, minute, second) = (
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60,
+    );
+    // 1970-01-01 was a Thursday (Sunday is 0)
+    let weekday = (days + 4).rem_euclid(7) as usize;
+    let day_of_year = days - days_from_civil(year, 1, 1) + 1;
+    let (month_names, day_names) = locale_names(date_options.locale);
+
+    let mut formatted = String::with_capacity(format.len() + 16);
+    let mut characters = format.chars();
+    while let Some(character) = characters.next() {
+        if character != '%' {
+            formatted.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('Y') => formatted.push_str(&year.to_string()),
+            Some('y') => formatted.push_str(&format!("{:02}", year.rem_euclid(100))),
+            Some('m') => formatted.push_str(&format!("{:02}", month)),
+            Some('B') => formatted.push_str(month_names[month as usize - 1]),
+            Some('b') => formatted.push_str(&abbreviation(month_names[month as usize - 1])),
+            Some('d') => formatted.push_str(&format!("{:02}", day)),
+            Some('e') => formatted.push_str(&format!("{:>2}", day)),
+            Some('A') => formatted.push_str(day_names[weekday]),
+            Some('a') => formatted.push_str(&abbreviation(day_names[weekday])),
+            Some('j') => formatted.push_str(&format!("{:03}", day_of_year)),
+            Some('H') => formatted.push_str(&format!("{:02}", hour)),
+            Some('I') => formatted.push_str(&format!("{:02}", (hour + 11) % 12 + 1)),
+            Some('M') => formatted.push_str(&format!("{:02}", minute)),
+            Some('S') => formatted.push_str(&format!("{:02}", second)),
+            Some('p') => formatted.push_str(if hour < 12 { "AM" } else { "PM" }),
+            Some('z') => {
+                let offset = date_options.utc_offset_minutes;
+                formatted.push_str(&format!(
+                    "{}{:02}{:02}",
+                    if offset < 0 { '-' } else { '+' },
+                    offset.abs() / 60,
+                    offset.abs() % 60
+                ));
+            }
+            Some('%') => formatted.push('%'),
+            Some(other) => {
+                formatted.push('%');
+                formatted.push(other);
+            }
+            None => formatted.push('%'),
+        }
+    }
+    formatted
+}
+
+/// Returns the number of days from 1970-01-01 to a date (the inverse of `civil_date`).
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the first three letters of a month or day name.
+fn abbreviation(name: &str) -> String {
+    name.chars().take(3).collect()
+}
+
+/// Returns the month names (January first) and day names (Sunday first) of a locale.
+fn locale_names(locale: DateLocale) -> ([&'static str; 12], [&'static str; 7]) {
+    match locale {
+        DateLocale::En => (
+            [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ],
+            [
+                "Sunday",
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+            ],
+        ),
+        DateLocale::De => (
+            [
+                "Januar",
+                "Februar",
+                "März",
+                "April",
+                "Mai",
+                "Juni",
+                "Juli",
+                "August",
+                "September",
+                "Oktober",
+                "November",
+                "Dezember",
+            ],
+            [
+                "Sonntag",
+                "Montag",
+                "Dienstag",
+                "Mittwoch",
+                "Donnerstag",
+                "Freitag",
+                "Samstag",
+            ],
+        ),
+        DateLocale::Es => (
+            [
+                "enero",
+                "febrero",
+                "marzo",
+                "abril",
+                "mayo",
+                "junio",
+                "julio",
+                "agosto",
+                "septiembre",
+                "octubre",
+                "noviembre",
+                "diciembre",
+            ],
+            [
+                "domingo",
+                "lunes",
+                "martes",
+                "miércoles",
+                "jueves",
+                "viernes",
+                "sábado",
+            ],
+        ),
+        DateLocale::Fr => (
+            [
+                "janvier",
+                "février",
+                "mars",
+                "avril",
+                "mai",
+                "juin",
+                "juillet",
+                "août",
+                "septembre",
+                "octobre",
+                "novembre",
+                "décembre",
+            ],
+            [
+                "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+            ],
+        ),
+        DateLocale::It => (
+            [
+                "gennaio",
+                "febbraio",
+                "marzo",
+                "aprile",
+                "maggio",
+                "giugno",
+                "luglio",
+                "agosto",
+                "settembre",
+                "ottobre",
+                "novembre",
+                "dicembre",
+            ],
+            [
+                "domenica",
+                "lunedì",
+                "martedì",
+                "mercoledì",
+                "giovedì",
+                "venerdì",
+                "sabato",
+            ],
+        ),
+        DateLocale::Nl => (
+            [
+                "januari",
+                "februari",
+                "maart",
+                "april",
+                "mei",
+                "juni",
+                "juli",
+                "augustus",
+                "september",
+                "oktober",
+                "november",
+                "december",
+            ],
+            [
+                "zondag",
+                "maandag",
+                "dinsdag",
+                "woensdag",
+                "donderdag",
+                "vrijdag",
+                "zaterdag",
+            ],
+        ),
+        DateLocale::Pt => (
+            [
+                "janeiro",
+                "fevereiro",
+                "março",
+                "abril",
+                "maio",
+                "junho",
+                "julho",
+                "agosto",
+                "setembro",
+                "outubro",
+                "novembro",
+                "dezembro",
+            ],
+            [
+                "domingo",
+                "segunda-feira",
+                "terça-feira",
+                "quarta-feira",
+                "quinta-feira",
+                "sexta-feira",
+                "sábado",
+            ],
+        ),
+    }
+}
@@ -0,0 +1,321 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::merge::save_document;
+use crate::stamp::page_media_box;
+use pdf_composer_definitions::destinations::NamedDestination;
+use pdf_composer_definitions::open_action::{OpenAction, Zoom};
+
+/// Adds named destinations to an existing PDF document, keeping the destinations already there
+/// (a destination with the same name is replaced).
+///
+/// Links from other documents (`document.pdf#name`) and bookmarks can then point to these places.
+///
+/// # Arguments
+///
+/// * `path` - The path to the PDF document to update.
+/// * `destinations` - The `NamedDestination`s to add.
+///
+/// # Returns
+///
+/// * `Ok(())` if the document was updated.
+/// * `Err(e)` if the file could not be read or written, or a destination is on a page the document does not have.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_composer::{add_named_destinations, NamedDestination};
+///
+/// add_named_destinations(
+///     "pdf_composer_pdfs/guide.pdf",
+///     &[
+///         NamedDestination::new("cover", 1),
+///         NamedDestination::new("appendix", 12),
+///     ],
+/// )
+/// .unwrap();
+/// ```
+pub fn add_named_destinations<P: AsRef<Path>>(
+    path: P,
+    destinations: &[NamedDestination],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let mut doc = Document::load(path)?;
+    let page_ids = doc.get_pages();
+
+    let mut named = named_destinations(&doc);
+    for destination in destinations {
+        let page_id = *page_ids.get(&destination.page_number).ok_or_else(|| {
+            format!(
+                "destination {} is on page {}, but {} has {} pages",
+                destination.name,
+                destination.page_number,
+                path.display(),
+                page_ids.len()
+            )
+        })?;
+        let top = match destination.top {
+            Some(top) => LopdfObject::Real(top),
+            None => LopdfObject::Null,
+        };
+        named.insert(
+            destination.name.as_bytes().to_vec(),
+            LopdfObject::Array(vec![
+                LopdfObject::Reference(page_id),
+                LopdfObject::Name(b"XYZ".to_vec()),
+                LopdfObject::Null,
+                top,
+                LopdfObject::Null,
+            ]),
+        );
+    }
+
+    set_named_destinations(&mut doc, &named)?;
+    // Drop the destination objects that were replaced
+    doc.prune_objects();
+    save_document(&mut doc, path)
+}
+
+/// This function sets where a document opens (`/OpenAction` in the catalog): a page or named
+/// destination, at a zoom.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `open_action` - The `OpenAction` with the page or destination name and the zoom.
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the page or destination is not in the document, or the document has no catalog.
+///
+/// # Remarks
+///
+/// A page opens at its top; a named destination opens at the place it stands for (with `Zoom::Fit`
+/// and `Zoom::FitHeight` showing the whole page or page height it is on).
+pub fn set_open_action(
+    doc: &mut Document,
+    open_action: &OpenAction,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (page_id, top, zoom) = match open_action {
+        OpenAction::Page(page_number, zoom) => {
+            let page_id = *doc.get_pages().get(page_number).ok_or_else(|| {
+                format!(
+                    "the document opens at page {}, but has {} pages",
+                    page_number,
+                    doc.get_pages().len()
+                )
+            })?;
+            let top = page_media_box(doc, page_id).map(|[_left, _bottom, _right, top]| top);
+            (page_id, top, zoom)
+        }
+        OpenAction::Destination(name, zoom) => {
+            let destination = named_destinations(doc)
+                .remove(name.as_bytes())
+                .ok_or_else(|| format!("the document has no destination named {}", name))?;
+            let explicit = destination.as_array()?;
+            let page_id = explicit
+                .first()
+                .ok_or("the destination has no page")?
+                .as_reference()?;
+            // `[page /XYZ left top zoom]` and `[page /FitH top]` destinations have a top
+            let top = match explicit.get(1).and_then(|kind| kind.as_name_str().ok()) {
+                Some("XYZ") => explicit.get(3),
+                Some("FitH") | Some("FitBH") => explicit.get(2),
+                _ => None,
+            }
+            .and_then(|top| top.as_float().ok());
+            (page_id, top, zoom)
+        }
+    };
+
+    let top = top.map_or(LopdfObject::Null, LopdfObject::Real);
+    let mut destination = vec![LopdfObject::Reference(page_id)];
+    match zoom {
+        Zoom::Fit => destination.push(LopdfObject::Name(b"Fit".to_vec())),
+        Zoom::FitWidth => destination.extend([LopdfObject::Name(b"FitH".to_vec()), top]),
+        Zoom::FitHeight => {
+            destination.extend([LopdfObject::Name(b"FitV".to_vec()), LopdfObject::Null])
+        }
+        Zoom::Percent(percent) => destination.extend([
+            LopdfObject::Name(b"XYZ".to_vec()),
+            LopdfObject::Null,
+            top,
+            LopdfObject::Real(*percent as f32 / 100.0),
+        ]),
+        Zoom::Inherit => destination.extend([
+            LopdfObject::Name(b"XYZ".to_vec()),
+            LopdfObject::Null,
+            top,
+            LopdfObject::Null,
+        ]),
+    }
+
+    doc.catalog_mut()?.set("OpenAction", destination);
+    Ok(())
+}
+
+/// This function returns the named destinations of a document, from both the catalog `/Dests`
+/// dictionary and the `/Names` `/Dests` name tree.
+///
+/// # Arguments
+///
+/// * `doc` - A reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// A `BTreeMap` of destination name to explicit destination (`[page /XYZ left top zoom]` etc).
+pub fn named_destinations(doc: &Document) -> BTreeMap<Vec<u8>, LopdfObject> {
+    let mut destinations: BTreeMap<Vec<u8>, LopdfObject> = BTreeMap::new();
+    let Ok(catalog) = doc.catalog() else {
+        return destinations;
+    };
+
+    if let Some(dests) = catalog
+        .get(b"Dests")
+        .ok()
+        .and_then(|dests| doc.dereference(dests).ok())
+        .and_then(|(_id, dests)| dests.as_dict().ok())
+    {
+        for (name, destination) in dests.iter() {
+            if let Some(explicit) = explicit_destination(doc, destination) {
+                destinations.insert(name.clone(), explicit);
+            }
+        }
+    }
+
+    if let Some(tree) = catalog
+        .get(b"Names")
+        .ok()
+        .and_then(|names| doc.dereference(names).ok())
+        .and_then(|(_id, names)| names.as_dict().ok())
+        .and_then(|names| names.get(b"Dests").ok())
+    {
+        collect_name_tree(doc, tree, &mut destinations, 0);
+    }
+
+    destinations
+}
+
+/// This function replaces the named destinations of a document.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `destinations` - The destination names and explicit destinations.
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated.
+/// * `Err(e)` if the document has no catalog.
+///
+/// # Remarks
+///
+/// Links can refer to a destination by name (looked up in `/Dests`) or by string (looked up in
+/// the `/Names` `/Dests` tree), so the destinations are written to both.
+pub fn set_named_destinations(
+    doc: &mut Document,
+    destinations: &BTreeMap<Vec<u8>, LopdfObject>,
+) -> Result<(), lopdf::Error> {
+    let mut names = match doc.catalog()?.get(b"Names") {
+        Ok(names) => doc.dereference(names)?.1.as_dict()?.clone(),
+        Err(_) => Dictionary::new(),
+    };
+
+    if destinations.is_empty() {
+        names.remove(b"Dests");
+        let names_id = (!names.is_empty()).then(|| doc.add_object(names));
+        let catalog = doc.catalog_mut()?;
+        catalog.remove(b"Dests");
+        match names_id {
+            Some(names_id) => catalog.set("Names", names_id),
+            None => {
+                catalog.remove(b"Names");
+            }
+        }
+        return Ok(());
+    }
+
+    let mut dests = Dictionary::new();
+    let mut name_tree_entries: Vec<LopdfObject> = Vec::new();
+    for (name, destination) in destinations {
+        let destination_id = doc.add_object(destination.clone());
+        dests.set(name.clone(), destination_id);
+        // The keys of a name tree are sorted, which the `BTreeMap` order gives
+        name_tree_entries.push(LopdfObject::string_literal(name.clone()));
+        name_tree_entries.push(LopdfObject::Reference(destination_id));
+    }
+
+    let mut name_tree = Dictionary::new();
+    name_tree.set("Names", name_tree_entries);
+    let name_tree_id = doc.add_object(name_tree);
+    names.set("Dests", name_tree_id);
+
+    let dests_id = doc.add_object(dests);
+    let names_id = doc.add_object(names);
+    let catalog = doc.catalog_mut()?;
+    catalog.set("Dests", dests_id);
+    catalog.set("Names", names_id);
+    Ok(())
+}
+
+/// This function keeps only the named destinations pointing at the given pages (for when pages are dropped).
+pub fn retain_destinations_to_pages(
+    destinations: &mut BTreeMap<Vec<u8>, LopdfObject>,
+    page_ids: &[ObjectId],
+) {
+    destinations.retain(|_name, destination| {
+        destination
+            .as_array()
+            .ok()
+            .and_then(|explicit| explicit.first())
+            .and_then(|page| page.as_reference().ok())
+            .is_some_and(|page_id| page_ids.contains(&page_id))
+    });
+}
+
+/// Returns the explicit destination array of a destination (which may be wrapped in a `<< /D [...] >>` dictionary).
+fn explicit_destination(doc: &Document, destination: &LopdfObject) -> Option<LopdfObject> {
+    match doc.dereference(destination).ok()?.1 {
+        LopdfObject::Array(explicit) => Some(LopdfObject::Array(explicit.clone())),
+        LopdfObject::Dictionary(wrapped) => explicit_destination(doc, wrapped.get(b"D").ok()?),
+        _ => None,
+    }
+}
+
+/// Collects every entry of a PDF name tree (names already collected are kept).
+fn collect_name_tree(
+    doc: &Document,
+    node: &LopdfObject,
+    destinations: &mut BTreeMap<Vec<u8>, LopdfObject>,
+    depth: u8,
+) {
+    if depth > 32 {
+        return;
+    }
+    let Some(node) = doc
+        .dereference(node)
+        .ok()
+        .and_then(|(_id, node)| node.as_dict().ok())
+    else {
+        return;
+    };
+    if let Ok(names) = node.get(b"Names").and_then(LopdfObject::as_array) {
+        for pair in names.chunks_exact(2) {
+            if let (Ok(name), Some(explicit)) =
+                (pair[0].as_str(), explicit_destination(doc, &pair[1]))
+            {
+                destinations.entry(name.to_vec()).or_insert(explicit);
+            }
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(LopdfObject::as_array) {
+        for kid in kids {
+            collect_name_tree(doc, kid, destinations, depth + 1);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Dictionary, Document, Object as LopdfObject, StringFormat};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::time::{Duration, SystemTime};
+
+use crate::conformance::pdf_date;
+use crate::utils::utc_timestamp;
+
+/// The environment variable reproducible builds set to the time a build is made, in seconds since
+/// 1970-01-01 00:00:00 UTC.
+const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// This function makes the dates of a document the same each time, so identical inputs produce
+/// identical PDFs.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+///
+/// # Remarks
+///
+/// The `CreationDate` and `ModDate` document information entries are set to the time in the
+/// `SOURCE_DATE_EPOCH` environment variable (1970-01-01 00:00:00 UTC if it is not set). The
+/// objects themselves are already written in a stable order. The document ID (random in the PDFs
+/// Chromium prints) is removed, and set with `set_deterministic_id` once the document is finished.
+pub fn make_deterministic(doc: &mut Document) {
+    doc.trailer.remove(b"ID");
+
+    let date = LopdfObject::string_literal(pdf_date(&utc_timestamp(source_date())));
+    let info_id = match doc.trailer.get(b"Info").and_then(LopdfObject::as_reference) {
+        Ok(info_id) if doc.get_dictionary(info_id).is_ok() => info_id,
+        _ => {
+            let info_id = doc.add_object(Dictionary::new());
+            doc.trailer.set("Info", info_id);
+            info_id
+        }
+    };
+    if let Ok(info) = doc.get_dictionary_mut(info_id) {
+        info.set("CreationDate", date.clone());
+        info.set("ModDate", date);
+    }
+}
+
+/// This function sets the document ID in the trailer to a digest of the document, so identical
+/// documents get the same ID and documents that differ in anything, their document information
+/// entries included, get different ones.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// * `Ok(())` if the ID was set.
+/// * `Err(e)` if the document could not be serialized.
+///
+/// # Remarks
+///
+/// Call it as the last change before the document is saved. The digest is the first 16 bytes of
+/// the SHA-256 digest of the document saved without an ID, which (unlike the hashers of the
+/// standard library) stays the same from one Rust release to the next. It is both parts of the ID,
+/// unless the document already has a permanent ID (the first part, which the XMP metadata of a
+/// standard such as PDF/X-4 refers to); then only the changing second part is the digest.
+pub fn set_deterministic_id(doc: &mut Document) -> Result<(), lopdf::Error> {
+    let permanent_id = doc
+        .trailer
+        .get(b"ID")
+        .and_then(LopdfObject::as_array)
+        .ok()
+        .and_then(|ids| ids.first())
+        .and_then(|id| id.as_str().ok())
+        .map(<[u8]>::to_vec);
+    doc.trailer.remove(b"ID");
+
+    let mut serialized = Vec::new();
+    doc.save_to(&mut serialized)?;
+    let digest = Sha256::digest(&serialized)[..16].to_vec();
+    doc.trailer.set(
+        "ID",
+        vec![
+            LopdfObject::String(
+                permanent_id.unwrap_or_else(|| digest.clone()),
+                StringFormat::Hexadecimal,
+            ),
+            LopdfObject::String(digest, StringFormat::Hexadecimal),
+        ],
+    );
+    Ok(())
+}
+
+/// Returns the time in the `SOURCE_DATE_EPOCH` environment variable, or 1970-01-01 00:00:00 UTC.
+pub fn source_date() -> SystemTime {
+    let seconds = env::var(SOURCE_DATE_EPOCH)
+        .ok()
+        .and_then(|seconds| seconds.trim().parse::<u64>().ok())
+        .unwrap_or(0);
+    SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// Returns a one-page document as Chromium prints it, with a random document ID and the time
+    /// it was printed.
+    fn printed_document(random_id: &[u8], printed_at: &str) -> Document {
+        let mut doc = Document::with_version("1.4");
+        let pages_id = doc.new_object_id();
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        doc.objects.insert(
+            pages_id,
+            LopdfObject::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => 1,
+                "Kids" => vec![page_id.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        let info_id = doc.add_object(dictionary! {
+            "Producer" => LopdfObject::string_literal("Skia/PDF"),
+            "CreationDate" => LopdfObject::string_literal(printed_at),
+            "ModDate" => LopdfObject::string_literal(printed_at),
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc.trailer.set("Info", info_id);
+        doc.trailer.set(
+            "ID",
+            vec![
+                LopdfObject::String(random_id.to_vec(), StringFormat::Hexadecimal),
+                LopdfObject::String(random_id.to_vec(), StringFormat::Hexadecimal),
+            ],
+        );
+        doc
+    }
+
+    /// Returns the saved bytes of a printed document made deterministic.
+    fn deterministic_bytes(random_id: &[u8], printed_at: &str) -> Vec<u8> {
+        let mut doc = printed_document(random_id, printed_at);
+        make_deterministic(&mut doc);
+        set_deterministic_id(&mut doc).unwrap();
+        let mut saved = Vec::new();
+        doc.save_to(&mut saved).unwrap();
+        saved
+    }
+
+    #[test]
+    fn gives_the_same_bytes_from_one_run_to_the_next() {
+        // 2023-11-14 22:13:20 UTC
+        env::set_var(SOURCE_DATE_EPOCH, "1700000000");
+
+        let first = deterministic_bytes(&[0x1A; 16], "D:20240501093000+01'00'");
+        let second = deterministic_bytes(&[0x2B; 16], "D:20240502101500+01'00'");
+        assert_eq!(first, second);
+
+        let doc = Document::load_mem(&first).unwrap();
+        let info_id = doc.trailer.get(b"Info").unwrap().as_reference().unwrap();
+        let info = doc.get_dictionary(info_id).unwrap();
+        for entry in [b"CreationDate".as_slice(), b"ModDate"] {
+            let date = info.get(entry).unwrap().as_str().unwrap();
+            assert!(date.starts_with(b"D:20231114221320"));
+        }
+
+        let ids = doc.trailer.get(b"ID").unwrap().as_array().unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(ids[0].as_str().unwrap().len(), 16);
+        assert_eq!(ids[0], ids[1]);
+        assert_ne!(ids[0].as_str().unwrap(), [0x1A; 16]);
+    }
+
+    #[test]
+    fn gives_different_documents_different_ids() {
+        let mut first = printed_document(&[0x1A; 16], "D:20240501093000+01'00'");
+        let mut second = printed_document(&[0x1A; 16], "D:20240501093000+01'00'");
+        second.trailer.remove(b"Info");
+        set_deterministic_id(&mut first).unwrap();
+        set_deterministic_id(&mut second).unwrap();
+
+        assert_ne!(
+            first.trailer.get(b"ID").ok(),
+            second.trailer.get(b"ID").ok()
+        );
+    }
+
+    #[test]
+    fn keeps_a_permanent_id() {
+        let mut doc = printed_document(&[0x1A; 16], "D:20240501093000+01'00'");
+        set_deterministic_id(&mut doc).unwrap();
+
+        let ids = doc.trailer.get(b"ID").unwrap().as_array().unwrap();
+        assert_eq!(ids[0].as_str().unwrap(), [0x1A; 16]);
+        assert_ne!(ids[1].as_str().unwrap(), [0x1A; 16]);
+    }
+}
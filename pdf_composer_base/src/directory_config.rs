@@ -0,0 +1,110 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::borrow::Cow;
+use std::mem;
+use std::path::Path;
+
+use crate::config::{apply_settings, merge_config, read_config};
+use crate::generate::DocumentOverrides;
+use pdf_composer_definitions::config::{PDFComposerConfig, DIRECTORY_CONFIG_FILE_NAME};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function reads the `_pdf_composer.yaml` files in the directories of a document, and merges
+/// them, from the outermost directory in, so the settings of nearer directories win.
+///
+/// # Arguments
+///
+/// * `document` - The path of the source document.
+///
+/// # Returns
+///
+/// * `Ok(Some(PDFComposerConfig))` with the merged settings, the paths relative to the current directory.
+/// * `Ok(None)` if none of the directories has a `_pdf_composer.yaml` file.
+/// * `Err(message)` if a file could not be read or parsed, or sets `sources`, `source_extensions`,
+///   `output_directory` or `profile`.
+///
+/// # Remarks
+///
+/// The directories are those of the document path, as it is given: `docs/guides/intro.md` is under
+/// `docs/guides`, `docs` and the current directory. The `doc_info` and `defaults` entries of the
+/// files are merged entry by entry, their `flags` are all added, and every other setting is taken
+/// from the nearest file that has it.
+pub fn directory_config(document: &Path) -> Result<Option<PDFComposerConfig>, String> {
+    let mut config_files: Vec<(&Path, _)> = document
+        .ancestors()
+        .skip(1)
+        .map(|directory| (directory, directory.join(DIRECTORY_CONFIG_FILE_NAME)))
+        .filter(|(_, config_file)| config_file.is_file())
+        .collect();
+    if config_files.is_empty() {
+        return Ok(None);
+    }
+    config_files.reverse();
+
+    let mut merged = PDFComposerConfig::default();
+    for (directory, config_file) in config_files {
+        let config = read_config(&config_file)
+            .map_err(|error| format!("{}: {}", config_file.display(), error))?;
+        if !config.sources.is_empty()
+            || !config.source_extensions.is_empty()
+            || config.output_directory.is_some()
+            || !config.profile.is_empty()
+        {
+            return Err(format!(
+                "{}: sources, source_extensions, output_directory and profile can only be set in the configuration file",
+                config_file.display()
+            ));
+        }
+        merged = merge_config(merged, config, directory);
+    }
+    Ok(Some(merged))
+}
+
+/// This function returns the composer settings of a document, with the settings of the
+/// `_pdf_composer.yaml` files in its directories (see `directory_config`) on top of the composer
+/// settings.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings of every document.
+/// * `document` - The path of the source document.
+///
+/// # Returns
+///
+/// * `Ok((composer, overrides))` - The composer to generate the document with (the composer itself
+///   if no directory has settings) and the document information entries of the directories, on
+///   top of the composer entries.
+/// * `Err(message)` if a file could not be read, or has a value that is not valid.
+///
+/// # Remarks
+///
+/// The front matter defaults of the directories are added to those of the composer, and the other
+/// settings replace the composer settings.
+pub fn apply_directory_config<'a>(
+    composer: &'a PDFComposerStruct,
+    document: &Path,
+) -> Result<(Cow<'a, PDFComposerStruct>, DocumentOverrides), String> {
+    let Some(mut config) = directory_config(document)? else {
+        return Ok((Cow::Borrowed(composer), DocumentOverrides::default()));
+    };
+
+    let doc_info_entries = mem::take(&mut config.doc_info);
+    let defaults = mem::take(&mut config.defaults);
+    let mut directory_composer = composer.clone();
+    apply_settings(&mut directory_composer, &config, Path::new("")).map_err(|error| {
+        format!(
+            "invalid {} settings for {}: {}",
+            DIRECTORY_CONFIG_FILE_NAME,
+            document.display(),
+            error
+        )
+    })?;
+    directory_composer.front_matter_defaults.extend(defaults);
+
+    let overrides = DocumentOverrides {
+        doc_info_entries,
+        ..Default::default()
+    };
+    Ok((Cow::Owned(directory_composer), overrides))
+}
@@ -0,0 +1,125 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fs::{self, create_dir_all};
+use std::path::Path;
+
+use chromiumoxide::detection::{default_executable, DetectionOptions};
+use pdf_composer_definitions::doctor::{CheckStatus, DoctorCheck, DoctorReport};
+use pdf_composer_definitions::fonts::GetCssName;
+use pdf_composer_definitions::output_format::OutputFormat;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function checks the environment PDF Composer will run in, before any documents are generated.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` whose configuration is checked.
+///
+/// # Returns
+///
+/// A `DoctorReport` with one `DoctorCheck` per item checked.
+///
+/// # Remarks
+///
+/// The following checks are performed:
+///
+/// 1. A Chromium (or Chrome) executable can be found. The `CHROME` environment variable is honoured.
+///    Chromium is not needed (so not looked for) when none of the output formats is PDF.
+/// 2. The output directory can be created and written to.
+/// 3. The configured font resolves to a CSS font family.
+/// 4. The source files exist.
+pub fn run_doctor(composer: &PDFComposerStruct) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    report
+        .checks
+        .push(match default_executable(DetectionOptions::default()) {
+            // HTML documents, e-books and text are written without Chromium
+            _ if !composer.output_formats.contains(&OutputFormat::Pdf) => DoctorCheck {
+                name: "Chromium".to_string(),
+                status: CheckStatus::Pass,
+                detail: format!(
+                    "not needed for {} output",
+                    composer
+                        .output_formats
+                        .iter()
+                        .map(|output_format| output_format.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+            Ok(path) => DoctorCheck {
+                name: "Chromium".to_string(),
+                status: CheckStatus::Pass,
+                detail: path.display().to_string(),
+            },
+            Err(error) => DoctorCheck {
+                name: "Chromium".to_string(),
+                status: CheckStatus::Fail,
+                detail: error,
+            },
+        });
+
+    report
+        .checks
+        .push(check_output_directory(&composer.output_directory));
+
+    let (css_font_name, _weight, _style) = composer.font.get_css_name();
+    report.checks.push(DoctorCheck {
+        name: "Font".to_string(),
+        status: CheckStatus::Pass,
+        detail: format!("{:?} ({})", composer.font, css_font_name),
+    });
+
+    let missing_sources: Vec<String> = composer
+        .fmy_source_files
+        .iter()
+        .filter(|path| !path.is_file())
+        .map(|path| path.display().to_string())
+        .collect();
+    report.checks.push(if composer.fmy_source_files.is_empty() {
+        DoctorCheck {
+            name: "Source files".to_string(),
+            status: CheckStatus::Warn,
+            detail: "no source files set".to_string(),
+        }
+    } else if missing_sources.is_empty() {
+        DoctorCheck {
+            name: "Source files".to_string(),
+            status: CheckStatus::Pass,
+            detail: format!("{} found", composer.fmy_source_files.len()),
+        }
+    } else {
+        DoctorCheck {
+            name: "Source files".to_string(),
+            status: CheckStatus::Fail,
+            detail: format!("not found: {}", missing_sources.join(", ")),
+        }
+    });
+
+    report
+}
+
+/// Checks the output directory can be created and a file written into it.
+fn check_output_directory(output_directory: &Path) -> DoctorCheck {
+    let name = "Output directory".to_string();
+    let probe_file = output_directory.join(".pdf_composer_doctor");
+
+    let result = create_dir_all(output_directory)
+        .and_then(|_| fs::write(&probe_file, b"pdf_composer"))
+        .and_then(|_| fs::remove_file(&probe_file));
+
+    match result {
+        Ok(_) => DoctorCheck {
+            name,
+            status: CheckStatus::Pass,
+            detail: format!("{} is writable", output_directory.display()),
+        },
+        Err(error) => DoctorCheck {
+            name,
+            status: CheckStatus::Fail,
+            detail: format!("{} is not writable: {}", output_directory.display(), error),
+        },
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::{escape_html, output_name};
+use crate::wiki_links::resolve_wiki_links;
+use pdf_composer_definitions::output_format::OutputFormat;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function rewrites links to other source documents (`[spec](./spec.md)`) to point to the PDFs
+/// (or HTML documents) generated from them, and resolves `[[wiki links]]`.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown of the source document.
+/// * `source_file` - The path of the source document (links are relative to its directory).
+/// * `composer` - The `PDFComposerStruct` with the source files being generated (and the merge mode).
+/// * `output_format` - The format of the document being generated (links point to documents of the same format).
+///
+/// # Returns
+///
+/// A `String` with the links to other source documents rewritten.
+///
+/// # Remarks
+///
+/// All the PDFs are saved to the output directory, so a link becomes the PDF file name
+/// (`spec.pdf`, or `spec.html` for HTML output, keeping any `#fragment`). In merge mode every
+/// source document is part of the merged PDF, so links point to the merged PDF instead (or to the
+/// chapter, inside a merged e-book). Links to markdown files that are not
+/// being generated are left alone. Wiki links are resolved with the composer's wiki link resolver
+/// (see `resolve_wiki_links`), and then rewritten like any other link.
+pub fn rewrite_document_links(
+    html: &str,
+    source_file: &Path,
+    composer: &PDFComposerStruct,
+    output_format: OutputFormat,
+) -> String {
+    let html = resolve_wiki_links(
+        html,
+        composer.wiki_link_resolver.as_ref(),
+        output_format.extension(),
+    );
+    if composer.source_extensions.is_empty() {
+        return html;
+    }
+    // Links to files with a source extension (`.md`, `.markdown` etc), whatever its case
+    let extensions: Vec<String> = composer
+        .source_extensions
+        .iter()
+        .map(|extension| regex::escape(extension))
+        .collect();
+    let link_regex = Regex::new(&format!(
+        r##"<a ((?:class="[^"]*" )?)href="([^"#?:]+(?i:{}))((?:[#?][^"]*)?)""##,
+        extensions.join("|")
+    ))
+    .unwrap();
+    let source_directory = source_file.parent().unwrap_or(Path::new(""));
+
+    link_regex
+        .replace_all(&html, |captures: &Captures| {
+            let linked_path = url_escape::decode(&captures[2]).replace("&amp;", "&");
+            let linked_file = comparable_path(&source_directory.join(linked_path));
+
+            let is_source_file = composer
+                .fmy_source_files
+                .iter()
+                .any(|source_file| comparable_path(source_file) == linked_file);
+            if !is_source_file {
+                return captures[0].to_string();
+            }
+
+            let pdf_file = match (&composer.merge_mode, output_format) {
+                // Inside a merged e-book, each source document is a chapter file
+                #[cfg(feature = "epub")]
+                (Some(_), OutputFormat::Epub) => {
+                    let chapter_number = composer
+                        .fmy_source_files
+                        .iter()
+                        .position(|source_file| comparable_path(source_file) == linked_file)
+                        .unwrap_or_default()
+                        + 1;
+                    format!("chapter-{}.xhtml", chapter_number)
+                }
+                (Some(merge_mode), output_format) => merge_mode.output_file_name(output_format),
+                (None, output_format) => generated_file_name(&linked_file, composer, output_format),
+            };
+            format!(
+                "<a {}href=\"{}{}\"",
+                &captures[1],
+                escape_html(&url_escape::encode_path(&pdf_file)),
+                &captures[3]
+            )
+        })
+        .to_string()
+}
+
+/// Returns the file name of the PDF (or HTML document) generated from a source file (the same
+/// naming as `document_build`).
+fn generated_file_name(
+    source_file: &Path,
+    composer: &PDFComposerStruct,
+    output_format: OutputFormat,
+) -> String {
+    format!(
+        "{}.{}",
+        output_name(
+            source_file,
+            &composer.source_extensions,
+            composer.output_namer.as_ref(),
+        ),
+        output_format.extension()
+    )
+}
+
+/// Returns the path in a form that can be compared with other paths to the same file.
+fn comparable_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.components().collect())
+}
@@ -0,0 +1,619 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use regex::Regex;
+use serde_yml::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use zip::result::ZipResult;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::build_pdf::PDFBuilder;
+use crate::callouts::CALLOUT_CSS;
+use crate::code_blocks::CODE_BLOCK_CSS;
+use crate::csv_table::DATA_TABLE_CSS;
+use crate::form_fields::FORM_FIELD_CSS;
+use crate::glossary::GLOSSARY_CSS;
+use crate::html_document::{document_language, document_text_direction, replace_local_images};
+use crate::layout_shortcodes::LAYOUT_SHORTCODE_CSS;
+use crate::utils::{escape_html, media_type, output_file_path, utc_timestamp, write_atomically};
+use pdf_composer_definitions::consts::{CHECK_MARK, CROSS_MARK, PACKAGE_NAME};
+use pdf_composer_definitions::fonts::{FontsStandard, GetCssName};
+use pdf_composer_definitions::merge::{MergeMetadataSource, MergeMode};
+use pdf_composer_definitions::output_format::OutputFormat;
+use pdf_composer_definitions::text_direction::{TextDirection, RTL_FALLBACK_FONTS};
+use pdf_composer_definitions::text_justification::JustifyOptions;
+
+/// The document information entries (in lower case) that have a Dublin Core element in the EPUB
+/// metadata, with the name of the element.
+const DUBLIN_CORE_ELEMENTS: [(&str, &str); 9] = [
+    ("title", "title"),
+    ("author", "creator"),
+    ("subject", "description"),
+    ("keywords", "subject"),
+    ("publisher", "publisher"),
+    ("rights", "rights"),
+    ("date", "date"),
+    ("contributor", "contributor"),
+    ("identifier", "identifier"),
+];
+
+/// Struct holding one chapter (source document) of an e-book.
+struct EpubChapter {
+    /// The chapter title.
+    title: String,
+    /// The XHTML of the chapter body, with images at `images/<name>`.
+    body: String,
+    /// The name and content of each image of the chapter.
+    images: Vec<(String, Vec<u8>)>,
+}
+
+/// Struct holding the content of an e-book.
+struct EpubBook {
+    /// Dublin Core element → value (`title`, `creator` etc).
+    metadata: BTreeMap<String, String>,
+    /// The language tag of the book.
+    language: String,
+    /// The direction text is written in (and pages are turned).
+    text_direction: TextDirection,
+    /// The stylesheet of every chapter.
+    css: String,
+    /// The chapters, in reading order.
+    chapters: Vec<EpubChapter>,
+    /// The application the e-book was generated with (the `Creator` of the PDF).
+    generator: String,
+}
+
+/// This function writes an EPUB 3 e-book from a given HTML string, source file and YAML data,
+/// instead of printing it to PDF.
+///
+/// # Arguments
+///
+/// * `generated_html` - A `String` containing the HTML generated from the markdown.
+/// * `yaml_btreemap` - A `BTreeMap<String, Value>` containing the YAML data.
+/// * `dictionary_entries` - A `BTreeMap<String, String>` of document information entries (entry → YAML key), written as the e-book metadata.
+/// * `instance_data` - The same settings the PDF would be built with (source_file, output_directory, font etc).
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path of the e-book, if it was successfully written.
+/// * `Err(e)` if the e-book could not be written.
+///
+/// # Remarks
+///
+/// The document information entries with a Dublin Core element become the e-book metadata
+/// (`Author` as the creator, `Subject` as the description, `Keywords` as the subject etc). Local
+/// images are packaged in the e-book. The e-book is reflowable, so the paper size, margins and
+/// settings that only apply to PDFs (page numbers, running headers etc) are not used.
+pub fn build_epub(
+    generated_html: String,
+    yaml_btreemap: BTreeMap<String, Value>,
+    dictionary_entries: BTreeMap<String, String>,
+    instance_data: PDFBuilder,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let PDFBuilder {
+        source_file,
+        output_file_name: file_name,
+        output_directory,
+        font,
+        text_direction,
+        language,
+        text_justification,
+        creator,
+        quiet,
+        ..
+    } = instance_data;
+
+    // Front matter `dir` and `lang` (or `language`) entries override the composer-wide settings
+    let text_direction = document_text_direction(&yaml_btreemap, text_direction);
+    let language = document_language(&yaml_btreemap, language, text_justification.as_ref());
+
+    let title = yaml_btreemap
+        .get("title")
+        .and_then(|value| value.as_str())
+        .unwrap_or(&file_name)
+        .to_string();
+
+    let mut metadata: BTreeMap<String, String> = BTreeMap::new();
+    for (entry, yaml_key) in &dictionary_entries {
+        if let (Some(element), Some(value)) = (
+            dublin_core_element(entry),
+            yaml_btreemap.get(yaml_key).and_then(|value| value.as_str()),
+        ) {
+            metadata.insert(element.to_string(), value.to_string());
+        }
+    }
+    metadata.entry("title".to_string()).or_insert(title.clone());
+
+    // Local images are packaged in the e-book
+    let source_directory = Path::new(&source_file).parent().unwrap_or(Path::new(""));
+    let mut images: Vec<(String, Vec<u8>)> = Vec::new();
+    let body = replace_local_images(&generated_html, source_directory, |image_file, content| {
+        let extension = image_file
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let name = format!("image-{}.{}", images.len() + 1, extension);
+        images.push((name.clone(), content));
+        format!("images/{}", name)
+    });
+
+    let book = EpubBook {
+        metadata,
+        language: language.unwrap_or("und".to_string()),
+        text_direction,
+        css: epub_css(font, text_direction, text_justification.as_ref()),
+        chapters: vec![EpubChapter {
+            title,
+            body: xhtml_body(&body),
+            images,
+        }],
+        generator: creator,
+    };
+
+    let epub_file_path = output_file_path(&output_directory, format!("{}.epub", file_name));
+    let epub_file_path_as_string = epub_file_path.display().to_string();
+    if let Err(error) = write_epub(&epub_file_path, &book) {
+        eprintln!(
+            "\n{}{}\n{} {}",
+            CROSS_MARK.on_red(),
+            epub_file_path_as_string.on_red(),
+            "Failed to save EPUB document.".red(),
+            error
+        );
+        return Err(error);
+    }
+
+    if !quiet {
+        println!(
+            "\n{}{} → {}",
+            CHECK_MARK.to_string().green(),
+            source_file.green(),
+            epub_file_path_as_string.yellow()
+        );
+    }
+
+    Ok(epub_file_path)
+}
+
+/// This function combines the e-books generated for each source file (chapter) into one e-book,
+/// in merge mode.
+///
+/// # Arguments
+///
+/// * `merge_mode` - The merge mode, with the file name of the merged e-book and the merge options.
+/// * `output_directory` - The directory the merged e-book is written to.
+/// * `chapters` - The source file and e-book of each chapter, in order.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path of the merged e-book.
+/// * `Err(e)` if a chapter could not be read or the merged e-book could not be written.
+///
+/// # Remarks
+///
+/// The chapters make up the spine in the order the source files were added. The metadata comes
+/// from the first (or last) chapter, as the merge options `metadata_source` says, with the
+/// `doc_info_overrides` on top. The language, text direction and styles of the first chapter are used.
+pub fn merge_epub_chapters(
+    merge_mode: &MergeMode,
+    output_directory: &Path,
+    chapters: &[(PathBuf, PathBuf)],
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut books: Vec<EpubBook> = Vec::with_capacity(chapters.len());
+    for (_source_file, chapter_epub) in chapters {
+        books.push(read_epub(chapter_epub)?);
+    }
+
+    let mut metadata = match merge_mode.options.metadata_source {
+        MergeMetadataSource::First => books.first().map(|book| book.metadata.clone()),
+        MergeMetadataSource::Last => books.last().map(|book| book.metadata.clone()),
+        MergeMetadataSource::Empty => None,
+    }
+    .unwrap_or_default();
+    // Each chapter has its own identifier, the merged e-book gets a new one
+    metadata.remove("identifier");
+    for (entry, value) in &merge_mode.options.doc_info_overrides {
+        if let Some(element) = dublin_core_element(entry) {
+            metadata.insert(element.to_string(), value.to_string());
+        }
+    }
+
+    let merged_file = output_file_path(
+        output_directory,
+        merge_mode.output_file_name(OutputFormat::Epub),
+    );
+    if !metadata.contains_key("title") {
+        let stem = merged_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        metadata.insert("title".to_string(), stem);
+    }
+
+    let mut books = books.into_iter();
+    let Some(first_book) = books.next() else {
+        return Err("there are no chapters to merge".into());
+    };
+    let mut merged = EpubBook {
+        metadata,
+        ..first_book
+    };
+    for book in books {
+        merged.chapters.extend(book.chapters);
+    }
+
+    write_epub(&merged_file, &merged)?;
+    Ok(merged_file)
+}
+
+/// Returns the Dublin Core element of a document information entry (`None` if it has none).
+fn dublin_core_element(entry: &str) -> Option<&'static str> {
+    let entry = entry.to_lowercase();
+    DUBLIN_CORE_ELEMENTS
+        .iter()
+        .find(|(name, _)| *name == entry)
+        .map(|(_, element)| *element)
+}
+
+/// Returns the stylesheet of the e-book chapters: the font, the justification and the styles of
+/// the generated elements (callouts, tables etc).
+fn epub_css(
+    font: FontsStandard,
+    text_direction: TextDirection,
+    text_justification: Option<&JustifyOptions>,
+) -> String {
+    let (mut css_font_name, css_font_weight, css_font_style) = font.get_css_name();
+    if text_direction == TextDirection::Rtl {
+        css_font_name = format!("{}, {}", css_font_name, RTL_FALLBACK_FONTS);
+    }
+    let mut css = format!(
+        "body {{ font-family: {}; font-weight: {}; font-style: {} }}\n",
+        css_font_name, css_font_weight, css_font_style
+    );
+    if let Some(JustifyOptions { hyphenate, .. }) = text_justification {
+        let hyphens = if *hyphenate { "auto" } else { "manual" };
+        css.push_str(&format!(
+            "body {{ text-align: justify; hyphens: {}; -webkit-hyphens: {}; -epub-hyphens: {} }}\n",
+            hyphens, hyphens, hyphens
+        ));
+    }
+    css.push_str("svg { max-width: 100%; height: auto; }\n");
+    css.push_str(CALLOUT_CSS);
+    css.push_str(CODE_BLOCK_CSS);
+    css.push_str(GLOSSARY_CSS);
+    css.push_str(DATA_TABLE_CSS);
+    css.push_str(LAYOUT_SHORTCODE_CSS);
+    css.push_str(FORM_FIELD_CSS);
+    css
+}
+
+/// Replaces the HTML named entities that are not defined in XHTML (e-book readers are XML parsers).
+fn xhtml_body(html: &str) -> String {
+    html.replace("&nbsp;", "&#160;")
+        .replace("&copy;", "&#169;")
+        .replace("&mdash;", "&#8212;")
+        .replace("&ndash;", "&#8211;")
+        .replace("&hellip;", "&#8230;")
+}
+
+/// Writes an e-book to an EPUB file.
+fn write_epub(path: &Path, book: &EpubBook) -> Result<(), Box<dyn std::error::Error>> {
+    write_atomically(path, |writer| {
+        write_epub_archive(writer, book).map_err(io::Error::other)
+    })?;
+    Ok(())
+}
+
+/// Writes the ZIP archive of an e-book.
+fn write_epub_archive<W: Write + Seek>(target: W, book: &EpubBook) -> ZipResult<()> {
+    let mut zip = ZipWriter::new(target);
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    // The mimetype comes first, uncompressed, so readers can recognise the file
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(b"application/epub+zip")?;
+
+    zip.start_file("META-INF/container.xml", deflated)?;
+    zip.write_all(
+        b"<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+          <container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">\n\
+          <rootfiles>\n\
+          <rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/>\n\
+          </rootfiles>\n\
+          </container>\n",
+    )?;
+
+    let language = escape_html(&book.language);
+    let title = escape_html(book.metadata.get("title").map_or("", String::as_str));
+    let mut manifest_items = String::from(
+        "<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n\
+         <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    );
+    let mut spine_items = String::new();
+    let mut toc_items = String::new();
+    let heading_regex = Regex::new(r#"(?s)<h2 id="([^"]+)"[^>]*>(.*?)</h2>"#).unwrap();
+    let tag_regex = Regex::new(r"<[^>]+>").unwrap();
+
+    for (index, chapter) in book.chapters.iter().enumerate() {
+        let number = index + 1;
+        let chapter_file = format!("chapter-{}.xhtml", number);
+
+        // Give the images of each chapter their own names
+        let mut body = chapter.body.clone();
+        for (name, content) in &chapter.images {
+            let image_name = format!("{}-{}", number, name);
+            body = body.replace(
+                &format!("\"images/{}\"", name),
+                &format!("\"images/{}\"", image_name),
+            );
+            zip.start_file(format!("OEBPS/images/{}", image_name), deflated)?;
+            zip.write_all(content)?;
+            manifest_items.push_str(&format!(
+                "<item id=\"image-{}\" href=\"images/{}\" media-type=\"{}\"/>\n",
+                escape_html(&image_name.replace('.', "-")),
+                escape_html(&image_name),
+                media_type(Path::new(&image_name))
+            ));
+        }
+
+        zip.start_file(format!("OEBPS/{}", chapter_file), deflated)?;
+        zip.write_all(
+            format!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n\
+                 <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+                 lang=\"{}\" xml:lang=\"{}\" dir=\"{}\">\n\
+                 <head><meta charset=\"utf-8\"/><title>{}</title>\
+                 <link rel=\"stylesheet\" type=\"text/css\" href=\"style.css\"/></head>\n\
+                 <body>{}</body>\n</html>\n",
+                language,
+                language,
+                book.text_direction,
+                escape_html(&chapter.title),
+                body
+            )
+            .as_bytes(),
+        )?;
+
+        let properties = if body.contains("<svg") {
+            " properties=\"svg\""
+        } else {
+            ""
+        };
+        manifest_items.push_str(&format!(
+            "<item id=\"chapter-{}\" href=\"{}\" media-type=\"application/xhtml+xml\"{}/>\n",
+            number, chapter_file, properties
+        ));
+        spine_items.push_str(&format!("<itemref idref=\"chapter-{}\"/>\n", number));
+
+        // The table of contents lists the chapters and their sections
+        let sections: String = heading_regex
+            .captures_iter(&body)
+            .map(|captures| {
+                format!(
+                    "<li><a href=\"{}#{}\">{}</a></li>",
+                    chapter_file,
+                    &captures[1],
+                    tag_regex.replace_all(&captures[2], "").trim()
+                )
+            })
+            .collect();
+        toc_items.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>{}</li>\n",
+            chapter_file,
+            escape_html(&chapter.title),
+            if sections.is_empty() {
+                String::new()
+            } else {
+                format!("<ol>{}</ol>", sections)
+            }
+        ));
+    }
+
+    zip.start_file("OEBPS/nav.xhtml", deflated)?;
+    zip.write_all(
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<!DOCTYPE html>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" \
+             lang=\"{}\" xml:lang=\"{}\" dir=\"{}\">\n\
+             <head><meta charset=\"utf-8\"/><title>{}</title></head>\n\
+             <body><nav epub:type=\"toc\" id=\"toc\"><h1>{}</h1>\n<ol>\n{}</ol></nav></body>\n</html>\n",
+            language, language, book.text_direction, title, title, toc_items
+        )
+        .as_bytes(),
+    )?;
+
+    zip.start_file("OEBPS/style.css", deflated)?;
+    zip.write_all(book.css.as_bytes())?;
+
+    let identifier = match book.metadata.get("identifier") {
+        Some(identifier) => identifier.to_string(),
+        None => content_uuid(book),
+    };
+    let mut metadata_elements = format!(
+        "<dc:identifier id=\"book-id\">{}</dc:identifier>\n\
+         <dc:title>{}</dc:title>\n\
+         <dc:language>{}</dc:language>\n",
+        escape_html(&identifier),
+        title,
+        language
+    );
+    for (element, value) in &book.metadata {
+        if element != "identifier" && element != "title" {
+            metadata_elements.push_str(&format!(
+                "<dc:{}>{}</dc:{}>\n",
+                element,
+                escape_html(value),
+                element
+            ));
+        }
+    }
+
+    zip.start_file("OEBPS/content.opf", deflated)?;
+    zip.write_all(
+        format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+             <package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"book-id\" \
+             xml:lang=\"{}\" dir=\"{}\">\n\
+             <metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">\n{}\
+             <meta property=\"dcterms:modified\">{}</meta>\n\
+             <meta name=\"generator\" content=\"{}\"/>\n\
+             </metadata>\n<manifest>\n{}</manifest>\n\
+             <spine page-progression-direction=\"{}\">\n{}</spine>\n</package>\n",
+            language,
+            book.text_direction,
+            metadata_elements,
+            utc_timestamp(SystemTime::now()),
+            escape_html(&book.generator),
+            manifest_items,
+            book.text_direction,
+            spine_items
+        )
+        .as_bytes(),
+    )?;
+
+    zip.finish()?;
+    Ok(())
+}
+
+/// Reads back an e-book written by `write_epub` (for merging).
+fn read_epub(path: &Path) -> Result<EpubBook, Box<dyn std::error::Error>> {
+    let mut zip = ZipArchive::new(File::open(path)?)?;
+    let mut read_text = |name: &str| -> Result<String, Box<dyn std::error::Error>> {
+        let mut text = String::new();
+        zip.by_name(name)?.read_to_string(&mut text)?;
+        Ok(text)
+    };
+
+    let package = read_text("OEBPS/content.opf")?;
+    let css = read_text("OEBPS/style.css")?;
+
+    let element_regex = Regex::new(r"<dc:([a-z]+)(?: [^>]*)?>([^<]*)</dc:[a-z]+>").unwrap();
+    let mut metadata: BTreeMap<String, String> = BTreeMap::new();
+    let mut language = "und".to_string();
+    for captures in element_regex.captures_iter(&package) {
+        let value = unescape_xml(&captures[2]);
+        match &captures[1] {
+            "language" => language = value,
+            element => {
+                metadata.insert(element.to_string(), value);
+            }
+        }
+    }
+    let generator_regex = Regex::new(r#"<meta name="generator" content="([^"]*)"/>"#).unwrap();
+    let generator = generator_regex
+        .captures(&package)
+        .map_or(PACKAGE_NAME.to_string(), |captures| {
+            unescape_xml(&captures[1])
+        });
+    let text_direction = if package.contains("page-progression-direction=\"rtl\"") {
+        TextDirection::Rtl
+    } else {
+        TextDirection::Ltr
+    };
+
+    let itemref_regex = Regex::new(r#"<itemref idref="chapter-(\d+)"/>"#).unwrap();
+    let title_regex = Regex::new(r"(?s)<title>(.*?)</title>").unwrap();
+    let mut chapters: Vec<EpubChapter> = Vec::new();
+    for captures in itemref_regex.captures_iter(&package) {
+        let number = &captures[1];
+        let chapter = read_text(&format!("OEBPS/chapter-{}.xhtml", number))?;
+        let (Some(body_start), Some(body_end)) = (chapter.find("<body>"), chapter.rfind("</body>"))
+        else {
+            return Err(format!("chapter {} of {} has no <body>", number, path.display()).into());
+        };
+        let title = title_regex
+            .captures(&chapter)
+            .map(|captures| unescape_xml(&captures[1]))
+            .unwrap_or_default();
+        chapters.push(EpubChapter {
+            title,
+            body: chapter[body_start + "<body>".len()..body_end].to_string(),
+            images: Vec::new(),
+        });
+    }
+
+    // Images are named after the chapter they are in (`1-image-1.png`)
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+        let Some(name) = file
+            .name()
+            .strip_prefix("OEBPS/images/")
+            .map(str::to_string)
+        else {
+            continue;
+        };
+        let chapter_index = name
+            .split('-')
+            .next()
+            .and_then(|number| number.parse::<usize>().ok())
+            .unwrap_or(1);
+        let mut content = Vec::new();
+        file.read_to_end(&mut content)?;
+        if let Some(chapter) = chapters.get_mut(chapter_index.saturating_sub(1)) {
+            chapter.images.push((name, content));
+        }
+    }
+
+    Ok(EpubBook {
+        metadata,
+        language,
+        text_direction,
+        css,
+        chapters,
+        generator,
+    })
+}
+
+/// Reverses `escape_html`.
+fn unescape_xml(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Returns a `urn:uuid:` identifier made from the content of the e-book, so the same sources
+/// give the same identifier.
+fn content_uuid(book: &EpubBook) -> String {
+    let mut hashes = [0u64; 2];
+    for (seed, hash) in hashes.iter_mut().enumerate() {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        book.metadata.hash(&mut hasher);
+        for chapter in &book.chapters {
+            chapter.title.hash(&mut hasher);
+            chapter.body.hash(&mut hasher);
+        }
+        *hash = hasher.finish();
+    }
+    // Version 4 layout (random-looking bits, with the version and variant set)
+    let bytes: Vec<u8> = hashes
+        .iter()
+        .flat_map(|hash| hash.to_be_bytes())
+        .enumerate()
+        .map(|(index, byte)| match index {
+            6 => (byte & 0x0f) | 0x40,
+            8 => (byte & 0x3f) | 0x80,
+            _ => byte,
+        })
+        .collect();
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
@@ -0,0 +1,104 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Dictionary, Document, ObjectId};
+use std::collections::BTreeSet;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::destinations::{
+    named_destinations, retain_destinations_to_pages, set_named_destinations,
+};
+use crate::page_tree::{
+    page_with_inherited_attributes, rebuild_page_tree, PAGE_DEPENDENT_CATALOG_ENTRIES,
+};
+use crate::utils::write_atomically;
+
+/// Extracts selected pages from a PDF document into a new PDF document.
+///
+/// # Arguments
+///
+/// * `input` - The path of the PDF document to extract pages from.
+/// * `ranges` - The pages to extract, as inclusive ranges of page numbers starting at `1`
+///   (for example, `1..=3` and `7..=7`). Pages are written in the order given; a page selected
+///   more than once is only written the first time.
+/// * `output` - The path to write the extracted PDF document to. Missing parent directories are created.
+///
+/// # Returns
+///
+/// * `Ok(())` if the extracted document was written.
+/// * `Err(e)` if no pages were selected, a page number is out of range, or the input could not be
+///   read or the output could not be written.
+///
+/// # Remarks
+///
+/// The document information, language and viewer preferences of the input are kept. The outline,
+/// page labels and tagged structure are dropped as they describe the pages of the full document.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_composer::extract_pages;
+/// use std::path::Path;
+///
+/// extract_pages(
+///     Path::new("pdf_composer_pdfs/annual_report.pdf"),
+///     &[1..=2, 10..=12],
+///     Path::new("pdf_composer_pdfs/annual_report_excerpt.pdf"),
+/// )
+/// .unwrap();
+/// ```
+pub fn extract_pages(
+    input: &Path,
+    ranges: &[RangeInclusive<u32>],
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut doc = Document::load(input)
+        .map_err(|error| format!("could not load {}: {}", input.display(), error))?;
+    let page_ids = doc.get_pages();
+
+    let mut seen_pages: BTreeSet<u32> = BTreeSet::new();
+    let mut selected_pages: Vec<(ObjectId, Dictionary)> = Vec::new();
+    for range in ranges {
+        for page_number in range.clone() {
+            let page_id = *page_ids.get(&page_number).ok_or_else(|| {
+                format!(
+                    "page {} is out of range ({} has {} pages)",
+                    page_number,
+                    input.display(),
+                    page_ids.len()
+                )
+            })?;
+            if seen_pages.insert(page_number) {
+                selected_pages.push((page_id, page_with_inherited_attributes(&doc, page_id)?));
+            }
+        }
+    }
+
+    if selected_pages.is_empty() {
+        return Err("no pages selected to extract".into());
+    }
+
+    // Keep the named destinations (link targets) on the pages extracted
+    let mut destinations = named_destinations(&doc);
+    let selected_page_ids: Vec<ObjectId> = selected_pages.iter().map(|(id, _)| *id).collect();
+    retain_destinations_to_pages(&mut destinations, &selected_page_ids);
+
+    doc.objects
+        .retain(|_, object| !matches!(object.type_name(), Ok("Pages") | Ok("Page")));
+
+    let catalog = doc.catalog_mut()?;
+    for key in PAGE_DEPENDENT_CATALOG_ENTRIES {
+        catalog.remove(key);
+    }
+
+    rebuild_page_tree(&mut doc, selected_pages)?;
+    set_named_destinations(&mut doc, &destinations)?;
+
+    // Drop the content, fonts and images only used by the pages left out
+    doc.prune_objects();
+
+    write_atomically(output, |writer| doc.save_to(writer))?;
+
+    Ok(())
+}
@@ -0,0 +1,809 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object as LopdfObject, ObjectId, Stream};
+use std::collections::BTreeMap;
+
+use crate::shortcodes::{ShortcodeArguments, ShortcodeOutput};
+use crate::stamp::add_standard_font;
+use crate::utils::{decode_pdf_text_string, encode_pdf_text_string, encode_win_ansi, escape_html};
+use pdf_composer_definitions::signature_fields::SignatureField;
+
+/// The start of the link URLs the `{{field}}` shortcode renders to. Chromium turns each link into a
+/// link annotation at the rendered position of the field, which `add_form_fields` replaces with
+/// the field.
+const FORM_FIELD_URL_PREFIX: &str = "pdf-composer-field:";
+
+/// The resource name of the font of the text fields (Helvetica).
+const TEXT_FIELD_FONT_RESOURCE: &str = "Helv";
+
+/// The largest font size of the text in a text field, in points.
+const MAX_TEXT_FIELD_FONT_SIZE: f32 = 12.0;
+
+/// The `Ff` flag of a field that must have a value before the form is submitted.
+const REQUIRED_FLAG: i64 = 1 << 1;
+/// The `Ff` flag of a text field that takes several lines of text.
+const MULTILINE_FLAG: i64 = 1 << 12;
+
+/// The styles for the form fields, added to the page CSS. The fields are drawn as boxes on the
+/// page, so they are still there when the PDF is printed.
+pub const FORM_FIELD_CSS: &str = "
+.form-field { display: inline-block; box-sizing: border-box; vertical-align: bottom; border-bottom: 1px solid #57606a; text-decoration: none; }
+.form-field-checkbox { border: 1px solid #57606a; vertical-align: text-bottom; }
+.form-field-signature { border-bottom-color: #000; }
+";
+
+/// A widget annotation of a form field, and its rectangle on the page.
+type Widget = (ObjectId, [f32; 4]);
+
+/// The kinds of form field the `{{field}}` shortcode creates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum FormFieldKind {
+    Text,
+    Checkbox,
+    Signature,
+}
+
+impl FormFieldKind {
+    /// Returns the kind written in the shortcode (`text`, `checkbox` or `signature`).
+    fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "text" => Some(Self::Text),
+            "checkbox" => Some(Self::Checkbox),
+            "signature" => Some(Self::Signature),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the kind, as written in the shortcode.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Checkbox => "checkbox",
+            Self::Signature => "signature",
+        }
+    }
+
+    /// Returns the width and height of the field when the shortcode gives none (CSS lengths).
+    fn default_size(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Text => ("12em", "1.4em"),
+            Self::Checkbox => ("1em", "1em"),
+            Self::Signature => ("16em", "3em"),
+        }
+    }
+
+    /// Returns the PDF field type (`FT`) of the kind.
+    fn field_type(self) -> &'static str {
+        match self {
+            Self::Text => "Tx",
+            Self::Checkbox => "Btn",
+            Self::Signature => "Sig",
+        }
+    }
+}
+
+/// A form field, as carried by the URL of its link from the HTML to the PDF.
+#[derive(Clone, Debug, PartialEq)]
+struct FormField {
+    /// The kind of field.
+    kind: FormFieldKind,
+    /// The name of the field (fields with the same name share their value).
+    name: String,
+    /// The value the field starts with (`None` for an empty or unchecked field).
+    value: Option<String>,
+    /// The text PDF readers show when the pointer is over the field (`None` for none).
+    tooltip: Option<String>,
+    /// Whether the field must be filled in.
+    required: bool,
+    /// Whether a text field takes several lines of text.
+    multiline: bool,
+}
+
+impl FormField {
+    /// Returns the link URL of the field.
+    fn url(&self) -> String {
+        let mut url = format!(
+            "{}{}/{}",
+            FORM_FIELD_URL_PREFIX,
+            self.kind.name(),
+            url_escape::encode_component(&self.name)
+        );
+        let mut parameters: Vec<String> = Vec::new();
+        if let Some(value) = &self.value {
+            parameters.push(format!("value={}", url_escape::encode_component(value)));
+        }
+        if let Some(tooltip) = &self.tooltip {
+            parameters.push(format!("tooltip={}", url_escape::encode_component(tooltip)));
+        }
+        if self.required {
+            parameters.push("required".to_string());
+        }
+        if self.multiline {
+            parameters.push("multiline".to_string());
+        }
+        if !parameters.is_empty() {
+            url.push('?');
+            url.push_str(&parameters.join("&"));
+        }
+        url
+    }
+
+    /// Returns the field of a link URL, or `None` if the URL is not the URL of a field.
+    fn from_url(url: &str) -> Option<Self> {
+        let url = url.strip_prefix(FORM_FIELD_URL_PREFIX)?;
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let (kind, name) = path.split_once('/')?;
+        let mut field = FormField {
+            kind: FormFieldKind::parse(kind)?,
+            name: url_escape::decode(name).to_string(),
+            value: None,
+            tooltip: None,
+            required: false,
+            multiline: false,
+        };
+        for parameter in query.split('&').filter(|parameter| !parameter.is_empty()) {
+            let (key, value) = parameter.split_once('=').unwrap_or((parameter, ""));
+            let value = url_escape::decode(value).to_string();
+            match key {
+                "value" => field.value = Some(value),
+                "tooltip" => field.tooltip = Some(value),
+                "required" => field.required = true,
+                "multiline" => field.multiline = true,
+                _ => {}
+            }
+        }
+        (!field.name.is_empty()).then_some(field)
+    }
+
+    /// Returns the field flags (`Ff`).
+    fn flags(&self) -> i64 {
+        let mut flags = 0;
+        if self.required {
+            flags |= REQUIRED_FLAG;
+        }
+        if self.multiline && self.kind == FormFieldKind::Text {
+            flags |= MULTILINE_FLAG;
+        }
+        flags
+    }
+
+    /// Returns whether a checkbox starts checked.
+    fn checked(&self) -> bool {
+        matches!(
+            self.value.as_deref(),
+            Some("true" | "yes" | "on" | "checked")
+        )
+    }
+}
+
+/// This function renders the `{{field text "customer_name"}}` shortcode as a box that becomes a
+/// fillable form field in the PDF.
+///
+/// # Arguments
+///
+/// * `arguments` - The shortcode arguments: the kind of field (`text`, `checkbox` or
+///   `signature`), its name, and the optional `width` and `height` (any CSS length), `value` (the
+///   text a text field starts with, or `true` for a checked checkbox), `tooltip`, `required=true`
+///   and, for text fields, `multiline=true`.
+///
+/// # Returns
+///
+/// * `Ok(ShortcodeOutput)` with the box, a link that `add_form_fields` replaces with the field.
+/// * `Err(message)` if the kind or name is missing or not valid.
+///
+/// # Remarks
+///
+/// Fields with the same name are one field shown in several places (a name repeated on every
+/// page, for example), so they share their value. In HTML and EPUB output the field is an empty box.
+pub fn form_field_shortcode(arguments: &ShortcodeArguments) -> Result<ShortcodeOutput, String> {
+    let kind_argument = arguments.required(0, "field kind (text, checkbox or signature)")?;
+    let kind = FormFieldKind::parse(kind_argument).ok_or_else(|| {
+        format!(
+            "field kind {} is not text, checkbox or signature",
+            kind_argument
+        )
+    })?;
+    let name = arguments.required(1, "field name")?;
+    // Periods separate the parts of the full name of a PDF field
+    if name.contains('.') {
+        return Err(format!("field name {} has a period in it", name));
+    }
+
+    let flag = |key: &str| {
+        arguments
+            .named
+            .get(key)
+            .is_some_and(|value| value == "true")
+    };
+    let field = FormField {
+        kind,
+        name: name.to_string(),
+        value: arguments.named.get("value").cloned(),
+        tooltip: arguments.named.get("tooltip").cloned(),
+        required: flag("required"),
+        multiline: flag("multiline"),
+    };
+
+    let (default_width, default_height) = kind.default_size();
+    let width = arguments
+        .named
+        .get("width")
+        .map(String::as_str)
+        .unwrap_or(default_width);
+    let height = arguments
+        .named
+        .get("height")
+        .map(String::as_str)
+        .unwrap_or(default_height);
+    let title = field
+        .tooltip
+        .as_deref()
+        .map(|tooltip| format!(" title=\"{}\"", escape_html(tooltip)))
+        .unwrap_or_default();
+
+    Ok(ShortcodeOutput {
+        html: format!(
+            "<a class=\"form-field form-field-{}\" href=\"{}\" style=\"width: {}; height: {}\"{}></a>",
+            kind.name(),
+            escape_html(&field.url()),
+            escape_html(width),
+            escape_html(height),
+            title
+        ),
+        block: false,
+    })
+}
+
+/// This function replaces the links rendered by the `{{field}}` shortcode with fillable form
+/// fields (AcroForm widgets) at the same place on the page, and lists the fields in the
+/// interactive form of the catalog.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// * `Ok(usize)` with the number of fields added (`0` if the document has no `{{field}}` links).
+/// * `Err(e)` if the document has no catalog.
+///
+/// # Remarks
+///
+/// Every field has an appearance of its own (the text it starts with, a check mark), so PDF
+/// readers and PDF/A validators do not have to make them up. Fields with the same name become the
+/// widgets of one field. The fields the interactive form already has are kept.
+pub fn add_form_fields(doc: &mut Document) -> Result<usize, lopdf::Error> {
+    // The widgets (and their rectangles) of each field, in the order the fields first appear
+    let mut fields: Vec<(FormField, Vec<Widget>)> = Vec::new();
+
+    for page_id in doc.get_pages().into_values() {
+        let Some(mut annotations) = page_annotations(doc, page_id) else {
+            continue;
+        };
+        let mut replaced = false;
+        for annotation in annotations.iter_mut() {
+            let annotation_id = annotation.as_reference().ok();
+            let Some((field, rect)) = doc
+                .dereference(annotation)
+                .ok()
+                .and_then(|(_id, annotation)| annotation.as_dict().ok())
+                .and_then(|annotation| field_link(doc, annotation))
+            else {
+                continue;
+            };
+
+            let widget = widget_annotation(rect, page_id);
+            let widget_id = match annotation_id {
+                Some(annotation_id) => {
+                    doc.objects
+                        .insert(annotation_id, LopdfObject::Dictionary(widget));
+                    annotation_id
+                }
+                None => doc.add_object(widget),
+            };
+            *annotation = LopdfObject::Reference(widget_id);
+            replaced = true;
+
+            match fields
+                .iter_mut()
+                .find(|(existing, _widgets)| existing.name == field.name)
+            {
+                Some((_existing, widgets)) => widgets.push((widget_id, rect)),
+                None => fields.push((field, vec![(widget_id, rect)])),
+            }
+        }
+        if replaced {
+            doc.get_dictionary_mut(page_id)?
+                .set("Annots", LopdfObject::Array(annotations));
+        }
+    }
+
+    if fields.is_empty() {
+        return Ok(0);
+    }
+
+    let font_id = add_standard_font(doc, "Helvetica");
+    let mut field_ids: Vec<LopdfObject> = Vec::new();
+    for (field, widgets) in &fields {
+        // Every widget shows the value of the field (the first of the fields with its name)
+        for (widget_id, rect) in widgets {
+            let appearance = widget_appearance(doc, field, *rect, font_id);
+            let widget = doc.get_dictionary_mut(*widget_id)?;
+            for (key, value) in appearance.iter() {
+                widget.set(key.clone(), value.clone());
+            }
+        }
+        let field_id = match widgets.as_slice() {
+            [(widget_id, _rect)] => {
+                let widget = doc.get_dictionary_mut(*widget_id)?;
+                for (key, value) in field_dictionary(field).iter() {
+                    widget.set(key.clone(), value.clone());
+                }
+                *widget_id
+            }
+            _ => {
+                let mut parent = field_dictionary(field);
+                parent.set(
+                    "Kids",
+                    widgets
+                        .iter()
+                        .map(|(widget_id, _rect)| LopdfObject::Reference(*widget_id))
+                        .collect::<Vec<LopdfObject>>(),
+                );
+                let parent_id = doc.add_object(parent);
+                for (widget_id, _rect) in widgets {
+                    doc.get_dictionary_mut(*widget_id)?.set("Parent", parent_id);
+                }
+                parent_id
+            }
+        };
+        field_ids.push(LopdfObject::Reference(field_id));
+    }
+
+    let has_signature_field = fields
+        .iter()
+        .any(|(field, _widgets)| field.kind == FormFieldKind::Signature);
+    set_interactive_form(doc, field_ids, has_signature_field, Some(font_id))?;
+    Ok(fields.len())
+}
+
+/// This function rebuilds the interactive form (`AcroForm`) of the catalog from the form fields
+/// of the pages, such as after merging documents, whose fields are then all fillable.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// * `Ok(())` if the catalog was updated (the interactive form is removed if there are no fields).
+/// * `Err(e)` if the document has no catalog.
+pub fn rebuild_interactive_form(doc: &mut Document) -> Result<(), lopdf::Error> {
+    let mut field_ids: Vec<ObjectId> = Vec::new();
+    let mut has_signature_field = false;
+    for page_id in doc.get_pages().into_values() {
+        for annotation in page_annotations(doc, page_id).unwrap_or_default() {
+            let Ok(mut field_id) = annotation.as_reference() else {
+                continue;
+            };
+            let is_widget = doc.get_dictionary(field_id).is_ok_and(is_widget);
+            if !is_widget {
+                continue;
+            }
+            // The field is the top of the chain of parents
+            for _ in 0..32 {
+                match doc
+                    .get_dictionary(field_id)
+                    .and_then(|field| field.get(b"Parent"))
+                    .and_then(LopdfObject::as_reference)
+                {
+                    Ok(parent_id) => field_id = parent_id,
+                    Err(_) => break,
+                }
+            }
+            if let Ok(field) = doc.get_dictionary(field_id) {
+                has_signature_field |= field
+                    .get(b"FT")
+                    .and_then(LopdfObject::as_name)
+                    .is_ok_and(|field_type| field_type == b"Sig");
+            }
+            if !field_ids.contains(&field_id) {
+                field_ids.push(field_id);
+            }
+        }
+    }
+
+    doc.catalog_mut()?.remove(b"AcroForm");
+    if field_ids.is_empty() {
+        return Ok(());
+    }
+    let field_ids = field_ids.into_iter().map(LopdfObject::Reference).collect();
+    set_interactive_form(doc, field_ids, has_signature_field, None)
+}
+
+/// This function adds an unsigned signature field to a PDF document, for a signing service to
+/// sign in.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `signature_field` - The `SignatureField` with the name, page and rectangle of the field.
+///
+/// # Returns
+///
+/// * `Ok(())` if the field was added.
+/// * `Err(e)` if the name is empty, has a period in it or is the name of a field the document
+///   already has, the rectangle is empty, or the page is not in the document.
+pub fn add_signature_field(
+    doc: &mut Document,
+    signature_field: &SignatureField,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let SignatureField {
+        name,
+        page_number,
+        rect,
+    } = signature_field;
+    if name.is_empty() || name.contains('.') {
+        return Err(format!("the name {:?} is empty or has a period in it", name).into());
+    }
+    if form_field_names(doc).contains(name) {
+        return Err(format!("the document already has a field named {}", name).into());
+    }
+    let [x1, y1, x2, y2] = *rect;
+    let rect = [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)];
+    if rect[0] == rect[2] || rect[1] == rect[3] {
+        return Err("the rectangle of the field is empty".into());
+    }
+    let page_id = *doc
+        .get_pages()
+        .get(page_number)
+        .ok_or_else(|| format!("the document has no page {}", page_number))?;
+
+    let field = FormField {
+        kind: FormFieldKind::Signature,
+        name: name.clone(),
+        value: None,
+        tooltip: None,
+        required: false,
+        multiline: false,
+    };
+    let mut widget = widget_annotation(rect, page_id);
+    let appearance_id = doc.add_object(appearance_stream(
+        rect[2] - rect[0],
+        rect[3] - rect[1],
+        Vec::new(),
+        None,
+    ));
+    widget.set("AP", dictionary! { "N" => appearance_id });
+    for (key, value) in field_dictionary(&field).iter() {
+        widget.set(key.clone(), value.clone());
+    }
+    let widget_id = doc.add_object(widget);
+
+    let mut annotations = page_annotations(doc, page_id).unwrap_or_default();
+    annotations.push(LopdfObject::Reference(widget_id));
+    doc.get_dictionary_mut(page_id)?
+        .set("Annots", LopdfObject::Array(annotations));
+    set_interactive_form(doc, vec![LopdfObject::Reference(widget_id)], true, None)?;
+    Ok(())
+}
+
+/// Returns the names of the fields of the interactive form of a document.
+pub fn form_field_names(doc: &Document) -> Vec<String> {
+    let Some(fields) = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"AcroForm").ok())
+        .and_then(|interactive_form| doc.dereference(interactive_form).ok())
+        .and_then(|(_id, interactive_form)| interactive_form.as_dict().ok())
+        .and_then(|interactive_form| interactive_form.get(b"Fields").ok())
+        .and_then(|fields| doc.dereference(fields).ok())
+        .and_then(|(_id, fields)| fields.as_array().ok())
+    else {
+        return Vec::new();
+    };
+    fields
+        .iter()
+        .filter_map(|field| doc.dereference(field).ok())
+        .filter_map(|(_id, field)| field.as_dict().ok())
+        .filter_map(|field| field.get(b"T").and_then(LopdfObject::as_str).ok())
+        .map(decode_pdf_text_string)
+        .collect()
+}
+
+/// Returns whether an annotation is a form field widget.
+fn is_widget(annotation: &Dictionary) -> bool {
+    annotation
+        .get(b"Subtype")
+        .and_then(LopdfObject::as_name)
+        .is_ok_and(|subtype| subtype == b"Widget")
+}
+
+/// Returns the annotations of a page (`None` if it has none).
+pub fn page_annotations(doc: &Document, page_id: ObjectId) -> Option<Vec<LopdfObject>> {
+    let page = doc.get_dictionary(page_id).ok()?;
+    let annotations = doc.dereference(page.get(b"Annots").ok()?).ok()?.1;
+    annotations.as_array().ok().cloned()
+}
+
+/// Returns the field and rectangle of a `{{field}}` link annotation, or `None` for any other annotation.
+fn field_link(doc: &Document, annotation: &Dictionary) -> Option<(FormField, [f32; 4])> {
+    if annotation
+        .get(b"Subtype")
+        .and_then(LopdfObject::as_name)
+        .ok()?
+        != b"Link"
+    {
+        return None;
+    }
+    let action = doc
+        .dereference(annotation.get(b"A").ok()?)
+        .ok()?
+        .1
+        .as_dict()
+        .ok()?;
+    let url = action.get(b"URI").and_then(LopdfObject::as_str).ok()?;
+    let field = FormField::from_url(&String::from_utf8_lossy(url))?;
+
+    let values: Vec<f32> = doc
+        .dereference(annotation.get(b"Rect").ok()?)
+        .ok()?
+        .1
+        .as_array()
+        .ok()?
+        .iter()
+        .filter_map(|value| value.as_float().ok())
+        .collect();
+    let [x1, y1, x2, y2]: [f32; 4] = values.try_into().ok()?;
+    Some((field, [x1.min(x2), y1.min(y2), x1.max(x2), y1.max(y2)]))
+}
+
+/// Returns the entries of a field: its type, name, value, tooltip, flags and default appearance.
+fn field_dictionary(field: &FormField) -> Dictionary {
+    let mut dictionary = Dictionary::new();
+    dictionary.set("FT", field.kind.field_type());
+    dictionary.set("T", encode_pdf_text_string(&field.name));
+    if let Some(tooltip) = &field.tooltip {
+        dictionary.set("TU", encode_pdf_text_string(tooltip));
+    }
+    let flags = field.flags();
+    if flags != 0 {
+        dictionary.set("Ff", flags);
+    }
+    match field.kind {
+        FormFieldKind::Text => {
+            // Font size 0 fits the text to the field
+            dictionary.set(
+                "DA",
+                LopdfObject::string_literal(format!("/{} 0 Tf 0 g", TEXT_FIELD_FONT_RESOURCE)),
+            );
+            if let Some(value) = &field.value {
+                dictionary.set("V", encode_pdf_text_string(value));
+            }
+        }
+        FormFieldKind::Checkbox => {
+            let state = if field.checked() { "Yes" } else { "Off" };
+            dictionary.set("V", state);
+        }
+        FormFieldKind::Signature => {}
+    }
+    dictionary
+}
+
+/// Returns a widget annotation over the rectangle of a link (its appearance is added by `widget_appearance`).
+fn widget_annotation(rect: [f32; 4], page_id: ObjectId) -> Dictionary {
+    dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "Rect" => rect.iter().map(|value| LopdfObject::Real(*value)).collect::<Vec<LopdfObject>>(),
+        "P" => page_id,
+        // Printed
+        "F" => 4,
+    }
+}
+
+/// Returns the appearance entries of a widget of a field, the size of its rectangle.
+fn widget_appearance(
+    doc: &mut Document,
+    field: &FormField,
+    rect: [f32; 4],
+    font_id: ObjectId,
+) -> Dictionary {
+    let width = rect[2] - rect[0];
+    let height = rect[3] - rect[1];
+
+    let mut widget = Dictionary::new();
+    match field.kind {
+        FormFieldKind::Text => {
+            let appearance = text_appearance(
+                field.value.as_deref(),
+                field.multiline,
+                width,
+                height,
+                font_id,
+            );
+            let appearance_id = doc.add_object(appearance);
+            widget.set("AP", dictionary! { "N" => appearance_id });
+        }
+        FormFieldKind::Checkbox => {
+            let checked_id = doc.add_object(appearance_stream(
+                width,
+                height,
+                check_mark(width, height),
+                None,
+            ));
+            let unchecked_id = doc.add_object(appearance_stream(width, height, Vec::new(), None));
+            let state = if field.checked() { "Yes" } else { "Off" };
+            widget.set("AS", state);
+            widget.set(
+                "AP",
+                dictionary! { "N" => dictionary! { "Yes" => checked_id, "Off" => unchecked_id } },
+            );
+            // ZapfDingbats `4` is the check mark PDF readers draw when the box is clicked
+            widget.set(
+                "MK",
+                dictionary! { "CA" => LopdfObject::string_literal("4") },
+            );
+        }
+        FormFieldKind::Signature => {
+            let appearance_id = doc.add_object(appearance_stream(width, height, Vec::new(), None));
+            widget.set("AP", dictionary! { "N" => appearance_id });
+        }
+    }
+    widget
+}
+
+/// Returns the appearance of a text field: the text it starts with, in Helvetica, or nothing.
+fn text_appearance(
+    value: Option<&str>,
+    multiline: bool,
+    width: f32,
+    height: f32,
+    font_id: ObjectId,
+) -> Stream {
+    let Some(value) = value.filter(|value| !value.is_empty()) else {
+        return appearance_stream(width, height, Vec::new(), None);
+    };
+    let font_size = MAX_TEXT_FIELD_FONT_SIZE.min(height * 0.7).max(1.0);
+    // Multiline text starts at the top of the field, single lines are centred in it
+    let baseline = if multiline {
+        height - font_size - 2.0
+    } else {
+        (height - font_size * 0.7) / 2.0
+    };
+    let operations = vec![
+        Operation::new("BMC", vec!["Tx".into()]),
+        Operation::new("q", vec![]),
+        Operation::new("BT", vec![]),
+        Operation::new("g", vec![0.into()]),
+        Operation::new(
+            "Tf",
+            vec![
+                LopdfObject::Name(TEXT_FIELD_FONT_RESOURCE.as_bytes().to_vec()),
+                font_size.into(),
+            ],
+        ),
+        Operation::new("Td", vec![2.into(), baseline.max(0.0).into()]),
+        Operation::new(
+            "Tj",
+            vec![LopdfObject::string_literal(encode_win_ansi(value))],
+        ),
+        Operation::new("ET", vec![]),
+        Operation::new("Q", vec![]),
+        Operation::new("EMC", vec![]),
+    ];
+    let resources = dictionary! {
+        "Font" => dictionary! { TEXT_FIELD_FONT_RESOURCE => font_id },
+    };
+    appearance_stream(width, height, operations, Some(resources))
+}
+
+/// Returns the drawing operations of a check mark filling a box of the given size.
+fn check_mark(width: f32, height: f32) -> Vec<Operation> {
+    let size = width.min(height);
+    let (x, y) = ((width - size) / 2.0, (height - size) / 2.0);
+    vec![
+        Operation::new("q", vec![]),
+        Operation::new("G", vec![0.into()]),
+        Operation::new("w", vec![(size * 0.12).into()]),
+        Operation::new("J", vec![1.into()]),
+        Operation::new("j", vec![1.into()]),
+        Operation::new("m", vec![(x + size * 0.2).into(), (y + size * 0.5).into()]),
+        Operation::new(
+            "l",
+            vec![(x + size * 0.42).into(), (y + size * 0.25).into()],
+        ),
+        Operation::new("l", vec![(x + size * 0.8).into(), (y + size * 0.75).into()]),
+        Operation::new("S", vec![]),
+        Operation::new("Q", vec![]),
+    ]
+}
+
+/// Returns an appearance stream (a form XObject) of the given size.
+fn appearance_stream(
+    width: f32,
+    height: f32,
+    operations: Vec<Operation>,
+    resources: Option<Dictionary>,
+) -> Stream {
+    let mut dictionary = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Form",
+        "BBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+    };
+    if let Some(resources) = resources {
+        dictionary.set("Resources", resources);
+    }
+    let content = Content { operations }.encode().unwrap_or_default();
+    Stream::new(dictionary, content)
+}
+
+/// Sets the interactive form of the catalog: the fields, the default font of the text fields
+/// (`font_id`, or Helvetica if `None`, unless the form already has one) and whether the document
+/// has signature fields. The fields the form already has are kept.
+pub fn set_interactive_form(
+    doc: &mut Document,
+    field_ids: Vec<LopdfObject>,
+    has_signature_field: bool,
+    font_id: Option<ObjectId>,
+) -> Result<(), lopdf::Error> {
+    let mut interactive_form = match doc.catalog()?.get(b"AcroForm") {
+        Ok(interactive_form) => doc.dereference(interactive_form)?.1.as_dict()?.clone(),
+        Err(_) => Dictionary::new(),
+    };
+    let mut fields: Vec<LopdfObject> = interactive_form
+        .get(b"Fields")
+        .and_then(|fields| doc.dereference(fields))
+        .and_then(|(_id, fields)| fields.as_array())
+        .cloned()
+        .unwrap_or_default();
+    for field_id in field_ids {
+        if !fields.contains(&field_id) {
+            fields.push(field_id);
+        }
+    }
+    interactive_form.set("Fields", fields);
+
+    let mut fonts: BTreeMap<Vec<u8>, LopdfObject> = BTreeMap::new();
+    if let Ok(resources) = interactive_form
+        .get(b"DR")
+        .and_then(|resources| doc.dereference(resources))
+        .and_then(|(_id, resources)| resources.as_dict())
+    {
+        if let Ok(font_dictionary) = resources
+            .get(b"Font")
+            .and_then(|fonts| doc.dereference(fonts))
+            .and_then(|(_id, fonts)| fonts.as_dict())
+        {
+            for (name, font) in font_dictionary.iter() {
+                fonts.insert(name.clone(), font.clone());
+            }
+        }
+    }
+    if !fonts.contains_key(TEXT_FIELD_FONT_RESOURCE.as_bytes()) {
+        let font_id = font_id.unwrap_or_else(|| add_standard_font(doc, "Helvetica"));
+        fonts.insert(
+            TEXT_FIELD_FONT_RESOURCE.as_bytes().to_vec(),
+            LopdfObject::Reference(font_id),
+        );
+    }
+    let mut font_dictionary = Dictionary::new();
+    for (name, font) in fonts {
+        font_dictionary.set(name, font);
+    }
+    interactive_form.set("DR", dictionary! { "Font" => font_dictionary });
+    interactive_form.set(
+        "DA",
+        LopdfObject::string_literal(format!("/{} 0 Tf 0 g", TEXT_FIELD_FONT_RESOURCE)),
+    );
+    if has_signature_field {
+        // SignaturesExist
+        interactive_form.set("SigFlags", 1);
+    }
+
+    let interactive_form_id = doc.add_object(interactive_form);
+    doc.catalog_mut()?.set("AcroForm", interactive_form_id);
+    Ok(())
+}
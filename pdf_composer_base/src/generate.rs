@@ -0,0 +1,1028 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::asset_base::{asset_directory, resolve_assets};
+use crate::build_html::build_html;
+use crate::build_pdf::{build_pdf, PDFBuilder};
+use crate::build_text::build_text;
+#[cfg(feature = "citations")]
+use crate::citations;
+use crate::computed_variables::fill_computed_placeholders;
+use crate::conditional_content::{active_conditions, apply_conditional_content};
+use crate::date_variables::fill_date_placeholders;
+use crate::document_links::rewrite_document_links;
+#[cfg(feature = "epub")]
+use crate::epub::build_epub;
+#[cfg(feature = "git")]
+use crate::git_variables::fill_git_placeholders;
+use crate::html_cache::cached_html;
+use crate::locale_placeholders::fill_locale_placeholders;
+use crate::persistent_browser::shared_browser;
+use crate::progress::write_progress;
+use crate::render::render_markdown;
+use crate::utils::{
+    confined_path, expand_env_vars, merge_markdown_yaml, missing_front_matter_keys, output_name,
+    parse_front_matter, remove_unfilled_placeholders, unescape_placeholders, unfilled_placeholders,
+    yaml_mapping_to_btreemap, FrontMatterError,
+};
+use pdf_composer_definitions::attachments::FileAttachment;
+use pdf_composer_definitions::consts::{CROSS_MARK, PACKAGE_NAME};
+use pdf_composer_definitions::front_matter::{MissingKeysPolicy, PlaceholderPolicy};
+use pdf_composer_definitions::generation_report::{
+    DocumentStatus, GeneratedDocument, GenerationReport,
+};
+use pdf_composer_definitions::output_format::OutputFormat;
+use pdf_composer_definitions::page_numbering::PageNumbering;
+use pdf_composer_definitions::page_properties::{PaperOrientation, PaperSize};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+use pdf_composer_definitions::progress::ProgressEvent;
+
+/// Struct holding the settings of a single document that differ from the composer settings.
+#[derive(Default)]
+pub struct DocumentOverrides {
+    /// The file name (without `.pdf`) of the PDF (`None` to name it after the source document).
+    pub output_file_name: Option<String>,
+    /// The paper size (`None` for the composer paper size).
+    pub paper_size: Option<PaperSize>,
+    /// The paper orientation (`None` for the composer orientation).
+    pub orientation: Option<PaperOrientation>,
+    /// Document information entries (PDF entry → front matter key), on top of the composer ones.
+    pub doc_info_entries: BTreeMap<String, String>,
+}
+
+/// Struct holding why a document was not generated (in one output format).
+#[derive(Clone, Debug)]
+pub struct OutputError {
+    /// Whether the document was skipped or failed.
+    pub status: DocumentStatus,
+    /// Why the document was not generated (the `error` of the report).
+    pub message: String,
+}
+
+impl OutputError {
+    /// Creates the error of a document that failed.
+    pub fn failed(message: impl Into<String>) -> Self {
+        OutputError {
+            status: DocumentStatus::Failed,
+            message: message.into(),
+        }
+    }
+
+    /// Creates the error of a document that was skipped.
+    pub fn skipped(message: impl Into<String>) -> Self {
+        OutputError {
+            status: DocumentStatus::Skipped,
+            message: message.into(),
+        }
+    }
+}
+
+/// Struct holding a document generated in one output format.
+#[derive(Clone, Debug)]
+pub struct OutputFile {
+    /// The path of the generated document.
+    pub path: PathBuf,
+    /// The number of pages of a generated PDF (`None` for the other output formats).
+    pub page_count: Option<u32>,
+}
+
+/// The result of generating a document in one output format: the generated document, or why it
+/// was not generated.
+pub type OutputResult = Result<OutputFile, OutputError>;
+
+/// Implement `From<PathBuf>` for OutputFile: a generated document without a page count (HTML
+/// documents, e-books and text files).
+impl From<PathBuf> for OutputFile {
+    fn from(path: PathBuf) -> Self {
+        OutputFile {
+            path,
+            page_count: None,
+        }
+    }
+}
+
+/// Struct holding what a document is built with, the same for every output format.
+pub struct DocumentBuild {
+    /// The YAML Front Matter (or other data) filling the placeholders.
+    pub yaml_btreemap: BTreeMap<String, Value>,
+    /// Document information entries (PDF entry → front matter key).
+    pub dictionary_entries: BTreeMap<String, String>,
+    /// The settings the document is built with.
+    pub instance_data: PDFBuilder,
+}
+
+/// The error of a source document without valid YAML Front Matter.
+const INVALID_FRONT_MATTER_ERROR: &str = "no valid YAML Front Matter";
+
+/// This function reads a source document, splitting it into its YAML Front Matter and markdown.
+///
+/// # Arguments
+///
+/// * `filename` - The path of the source document.
+/// * `quiet` - Whether the progress message is left out (see `set_quiet`).
+///
+/// # Returns
+///
+/// * `Ok((BTreeMap<String, Value>, String))` with the front matter and the markdown.
+/// * `Err(message)` if the document cannot be read (or is not UTF-8 text), or does not start with
+///   valid YAML Front Matter (the message is printed too).
+pub fn read_source_document(
+    filename: &str,
+    quiet: bool,
+) -> Result<(BTreeMap<String, Value>, String), String> {
+    match fs::read_to_string(filename) {
+        Ok(source) => parse_source_document(filename, &source, quiet),
+        Err(error) => {
+            eprintln!("File {} could not be read: {}", filename.red(), error);
+            Err(format!("could not be read: {}", error))
+        }
+    }
+}
+
+/// This function splits the text of a source document into its YAML Front Matter and markdown
+/// (see `parse_front_matter`).
+///
+/// # Arguments
+///
+/// * `filename` - The name of the source document (for the messages).
+/// * `source` - The text of the source document.
+/// * `quiet` - Whether the progress message is left out (see `set_quiet`).
+///
+/// # Returns
+///
+/// * `Ok((BTreeMap<String, Value>, String))` with the front matter and the markdown.
+/// * `Err(message)` if the document does not start with valid YAML Front Matter, or its front
+///   matter is not closed with a `---` line (the message is printed too).
+pub fn parse_source_document(
+    filename: &str,
+    source: &str,
+    quiet: bool,
+) -> Result<(BTreeMap<String, Value>, String), String> {
+    let (yaml_content, markdown_content) = match parse_front_matter(source) {
+        Ok(sections) => sections,
+        Err(FrontMatterError::Missing) => {
+            eprintln!("File {} is not a valid yaml file", filename.red());
+            return Err(INVALID_FRONT_MATTER_ERROR.to_string());
+        }
+        Err(error) => {
+            eprintln!("File {}: {}", filename.red(), error);
+            return Err(error.to_string());
+        }
+    };
+
+    // Parse YAML content.
+    let yaml: Value = serde_yml::from_str(&yaml_content).unwrap_or(Value::Null);
+    // Check if YAML is valid.
+    if yaml == Value::Null {
+        eprintln!("File {} is not a valid yaml file", filename.red());
+        return Err(INVALID_FRONT_MATTER_ERROR.to_string());
+    }
+    if !quiet {
+        println!("{}. {}", filename.cyan(), "Processing...".green());
+    }
+
+    // Convert YAML Front Matter to a BTreeMap.
+    let Some(yaml_btreemap) = yaml_mapping_to_btreemap(&yaml) else {
+        eprintln!("File {} is not a valid yaml file", filename.red());
+        return Err(INVALID_FRONT_MATTER_ERROR.to_string());
+    };
+    Ok((yaml_btreemap, markdown_content))
+}
+
+/// This function fills in the variables of a document before it is rendered: the conditional
+/// blocks of the profile and flags (see `apply_conditional_content`), the allowed `${NAME}`
+/// environment variable references (see `set_env_var_allow_list`), in its front matter
+/// string values and its markdown, the date placeholders (see `fill_date_placeholders`), the
+/// Git placeholders (see `fill_git_placeholders`, `git` feature), the `{{date key}}` and
+/// `{{number key}}` placeholders (see `fill_locale_placeholders`) and the computed placeholders
+/// (see `fill_computed_placeholders`).
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the profile and flags, the environment variables that may be expanded and the date options.
+/// * `document` - The path of the source document (for the Git placeholders).
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) of the document.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+///
+/// # Returns
+///
+/// The front matter and markdown with the variables filled in.
+pub fn expand_document_variables(
+    composer: &PDFComposerStruct,
+    #[cfg_attr(not(feature = "git"), allow(unused_variables))] document: &Path,
+    yaml_btreemap: BTreeMap<String, Value>,
+    markdown_content: &str,
+) -> (BTreeMap<String, Value>, String) {
+    let allow_list = &composer.env_var_allow_list;
+    let mut yaml_btreemap: BTreeMap<String, Value> = if allow_list.is_empty() {
+        yaml_btreemap
+    } else {
+        yaml_btreemap
+            .into_iter()
+            .map(|(key, value)| (key, expand_yaml_env_vars(value, allow_list)))
+            .collect()
+    };
+    let markdown_content =
+        apply_conditional_content(markdown_content, &active_conditions(composer));
+    let markdown_content = expand_env_vars(&markdown_content, allow_list);
+    #[cfg(feature = "git")]
+    fill_git_placeholders(composer, document, &mut yaml_btreemap, &markdown_content);
+    let markdown_content = fill_date_placeholders(composer, &mut yaml_btreemap, &markdown_content);
+    let markdown_content = fill_locale_placeholders(composer, &yaml_btreemap, &markdown_content);
+    fill_computed_placeholders(composer, &mut yaml_btreemap, &markdown_content);
+    (yaml_btreemap, markdown_content)
+}
+
+/// This function fills in the front matter keys a document does not set with the front matter
+/// defaults of the composer (see `set_front_matter_defaults`).
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the front matter defaults.
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) of the document.
+pub fn apply_front_matter_defaults(
+    composer: &PDFComposerStruct,
+    yaml_btreemap: &mut BTreeMap<String, Value>,
+) {
+    for (key, value) in &composer.front_matter_defaults {
+        yaml_btreemap
+            .entry(key.clone())
+            .or_insert_with(|| Value::String(value.clone()));
+    }
+}
+
+/// Expands the environment variable references of the strings in a YAML value (and the values nested in it).
+fn expand_yaml_env_vars(value: Value, allow_list: &[String]) -> Value {
+    match value {
+        Value::String(string_value) => Value::String(expand_env_vars(&string_value, allow_list)),
+        Value::Sequence(sequence) => Value::Sequence(
+            sequence
+                .into_iter()
+                .map(|value| expand_yaml_env_vars(value, allow_list))
+                .collect(),
+        ),
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .into_iter()
+                .map(|(key, value)| (key, expand_yaml_env_vars(value, allow_list)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// This function finds the placeholders of a document that have no front matter value, in its
+/// markdown and in the QR code footer (see `unfilled_placeholders`).
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the placeholder policy and the QR code footer.
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) filling the placeholders.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+///
+/// # Returns
+///
+/// The keys of the placeholders without a value, or none with `PlaceholderPolicy::LeaveAsIs`
+/// (as they are not looked for).
+pub fn document_unfilled_placeholders(
+    composer: &PDFComposerStruct,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    markdown_content: &str,
+) -> Vec<String> {
+    if composer.placeholder_policy == PlaceholderPolicy::LeaveAsIs {
+        return Vec::new();
+    }
+    let mut placeholders = unfilled_placeholders(yaml_btreemap, markdown_content);
+    for stamped_text in [&composer.qr_code_footer, &composer.watermark]
+        .into_iter()
+        .flatten()
+    {
+        for key in unfilled_placeholders(yaml_btreemap, stamped_text) {
+            if !placeholders.contains(&key) {
+                placeholders.push(key);
+            }
+        }
+    }
+    placeholders
+}
+
+/// This function converts the markdown of a document to HTML: placeholders are filled in, the
+/// markdown is rendered and citations resolved. Links to other documents depend on the output
+/// format, so they are rewritten afterwards (see `rewrite_document_links`). With an HTML cache
+/// set, the HTML of unchanged documents is read from the cache instead (see `cached_html`).
+/// The HTML transform (see `set_html_transform`), if any, is run last, on cached HTML too.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings to render the document with.
+/// * `document` - The path of the source document (links and paths are relative to its directory).
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) filling the placeholders.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+///
+/// # Returns
+///
+/// A `String` with the HTML of the document body.
+pub fn render_document_html(
+    composer: &PDFComposerStruct,
+    document: &Path,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    markdown_content: &str,
+) -> String {
+    let html = cached_html(composer, document, yaml_btreemap, markdown_content, || {
+        // Insert YAML Front Matter into markdown.
+        let merged_markdown_yaml = merge_markdown_yaml(yaml_btreemap.clone(), markdown_content);
+
+        // Paths in the document are relative to its directory.
+        let source_directory = document.parent().unwrap_or(Path::new(""));
+
+        // Convert Markdown content to HTML.
+        let html: String = render_markdown(
+            &merged_markdown_yaml,
+            yaml_btreemap,
+            source_directory,
+            composer,
+        );
+        // Resolve citations against the front matter bibliography.
+        #[cfg(feature = "citations")]
+        let html = citations::process_citations(&html, yaml_btreemap, source_directory);
+        html
+    });
+
+    match &composer.html_transform {
+        Some(html_transform) => html_transform(html),
+        None => html,
+    }
+}
+
+/// This function generates the PDF (or HTML document, e-book or text file) for one document, from its front matter and markdown,
+/// in each of the composer's output formats.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings to generate the PDF with.
+/// * `document` - The path of the source document (links and paths are relative to its directory).
+/// * `overrides` - The settings of this document that differ from the composer settings.
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) filling the placeholders.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+/// * `page_numbering` - How the pages are numbered (`None` for no page numbers).
+///
+/// # Returns
+///
+/// A `Vec<OutputResult>` with, for each output format (in order):
+///
+/// * `Ok(OutputFile)` with the generated PDF (or HTML document, e-book or text file).
+/// * `Err(OutputError)` if the document was skipped (missing required front matter keys), had
+///   placeholders without a value (`PlaceholderPolicy::Error`) or could not be built.
+///
+/// # Remarks
+///
+/// The markdown is rendered to HTML once, and that HTML is used for every output format (only the
+/// links to other documents are rewritten for each format).
+pub fn generate_document(
+    composer: &PDFComposerStruct,
+    document: &Path,
+    overrides: &DocumentOverrides,
+    mut yaml_btreemap: BTreeMap<String, Value>,
+    markdown_content: &str,
+    page_numbering: Option<PageNumbering>,
+) -> Vec<OutputResult> {
+    let filename = document.display().to_string();
+
+    apply_front_matter_defaults(composer, &mut yaml_btreemap);
+
+    // Fill in the allowed environment variables (build metadata such as `${CI_COMMIT_SHA}`) and dates.
+    let (yaml_btreemap, mut markdown_content) =
+        expand_document_variables(composer, document, yaml_btreemap, markdown_content);
+
+    // Check the required front matter keys are present.
+    let missing_keys =
+        missing_front_matter_keys(&yaml_btreemap, &composer.required_front_matter_keys);
+    if !missing_keys.is_empty() {
+        let name = overrides.output_file_name.as_deref().unwrap_or(&filename);
+        eprintln!(
+            "{}{} is missing required front matter keys [{}]",
+            CROSS_MARK.red(),
+            name.red(),
+            missing_keys.join(", ").yellow()
+        );
+        if composer.missing_keys_policy == MissingKeysPolicy::Skip {
+            eprintln!("File {} skipped.", name.red());
+            let error = OutputError::skipped(format!(
+                "skipped, missing required front matter keys [{}]",
+                missing_keys.join(", ")
+            ));
+            return vec![Err(error); composer.output_formats.len()];
+        }
+    }
+
+    // Apply the placeholder policy to placeholders without a front matter value.
+    let unfilled_placeholders =
+        document_unfilled_placeholders(composer, &yaml_btreemap, &markdown_content);
+    if !unfilled_placeholders.is_empty() {
+        let name = overrides.output_file_name.as_deref().unwrap_or(&filename);
+        match composer.placeholder_policy {
+            PlaceholderPolicy::LeaveAsIs => {}
+            PlaceholderPolicy::Remove => {
+                markdown_content = remove_unfilled_placeholders(&yaml_btreemap, &markdown_content)
+            }
+            PlaceholderPolicy::Warn => eprintln!(
+                "{}{}",
+                CROSS_MARK.yellow(),
+                format!(
+                    "{} has placeholders without a front matter value [{}]",
+                    name,
+                    unfilled_placeholders.join(", ")
+                )
+                .yellow()
+            ),
+            PlaceholderPolicy::Error => {
+                eprintln!(
+                    "{}{} has placeholders without a front matter value [{}]",
+                    CROSS_MARK.red(),
+                    name.red(),
+                    unfilled_placeholders.join(", ").yellow()
+                );
+                eprintln!("File {} not generated.", name.red());
+                let error = OutputError::failed(format!(
+                    "placeholders without a front matter value [{}]",
+                    unfilled_placeholders.join(", ")
+                ));
+                return vec![Err(error); composer.output_formats.len()];
+            }
+        }
+    }
+    let markdown_content = markdown_content.as_str();
+
+    let html = render_document_html(composer, document, &yaml_btreemap, markdown_content);
+    let document_build =
+        document_build(composer, document, overrides, yaml_btreemap, page_numbering);
+
+    // Build the PDF (or HTML, EPUB or text) document for each output format.
+    composer
+        .output_formats
+        .iter()
+        .map(|&output_format| {
+            build_output(
+                composer,
+                document,
+                output_format,
+                &html,
+                markdown_content,
+                &document_build,
+            )
+            .map_err(OutputError::failed)
+        })
+        .collect()
+}
+
+/// This function gathers what a document is built with, for every output format.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings to generate the document with.
+/// * `document` - The path of the source document.
+/// * `overrides` - The settings of this document that differ from the composer settings.
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) filling the placeholders.
+/// * `page_numbering` - How the pages are numbered (`None` for no page numbers).
+///
+/// # Returns
+///
+/// The `DocumentBuild` to pass to `build_output`.
+pub fn document_build(
+    composer: &PDFComposerStruct,
+    document: &Path,
+    overrides: &DocumentOverrides,
+    yaml_btreemap: BTreeMap<String, Value>,
+    page_numbering: Option<PageNumbering>,
+) -> DocumentBuild {
+    let instance_data = PDFBuilder {
+        source_file: document.display().to_string(),
+        output_file_name: overrides.output_file_name.clone().unwrap_or_else(|| {
+            output_name(
+                document,
+                &composer.source_extensions,
+                composer.output_namer.as_ref(),
+            )
+        }),
+        output_directory: document_output_directory(
+            document,
+            &yaml_btreemap,
+            &composer.output_directory,
+        ),
+        pdf_version: composer.pdf_version,
+        paper_size: overrides.paper_size.unwrap_or(composer.paper_size),
+        orientation: overrides.orientation.unwrap_or(composer.orientation),
+        margins: composer.margins,
+        mirrored_margins: composer.mirrored_margins,
+        font: composer.font,
+        text_direction: composer.text_direction,
+        language: composer.language.clone(),
+        text_justification: composer.text_justification.clone(),
+        stylesheet: composer.stylesheet.clone(),
+        tagged_pdf: composer.tagged_pdf,
+        running_headers: composer.running_headers,
+        page_numbering,
+        // In merge mode, the page labels are set on the merged PDF
+        page_labels: match composer.merge_mode {
+            Some(_) => None,
+            None => composer.page_labels.clone(),
+        },
+        viewer_preferences: composer.viewer_preferences,
+        // In merge mode, the merged PDF opens where the open action says
+        open_action: match composer.merge_mode {
+            Some(_) => None,
+            None => composer.open_action.clone(),
+        },
+        qr_code_footer: composer.qr_code_footer.as_ref().map(|data| {
+            let data = if composer.placeholder_policy == PlaceholderPolicy::Remove {
+                remove_unfilled_placeholders(&yaml_btreemap, data)
+            } else {
+                data.clone()
+            };
+            unescape_placeholders(&merge_markdown_yaml(yaml_btreemap.clone(), &data))
+        }),
+        watermark: composer.watermark.as_ref().map(|text| {
+            let text = if composer.placeholder_policy == PlaceholderPolicy::Remove {
+                remove_unfilled_placeholders(&yaml_btreemap, text)
+            } else {
+                text.clone()
+            };
+            unescape_placeholders(&merge_markdown_yaml(yaml_btreemap.clone(), &text))
+        }),
+        attach_source: composer.attach_source,
+        attach_front_matter: composer.attach_front_matter,
+        attachments: document_attachments(composer, document, &yaml_btreemap),
+        // In merge mode, the signature fields are added to the merged PDF
+        signature_fields: match composer.merge_mode {
+            Some(_) => Vec::new(),
+            None => composer.signature_fields.clone(),
+        },
+        // In merge mode, the merged PDF is timestamped
+        #[cfg(feature = "timestamp")]
+        timestamp_authority: match composer.merge_mode {
+            Some(_) => None,
+            None => composer.timestamp_authority.clone(),
+        },
+        conformance: composer.conformance,
+        output_intent: composer.output_intent.clone(),
+        image_policy: composer.image_policy,
+        deterministic: composer.deterministic,
+        chunked_rendering: composer.chunked_rendering,
+        persistent_browser: shared_browser(composer.persistent_browser.as_ref()),
+        locked_file_policy: composer.locked_file_policy.clone(),
+        quiet: composer.console_output.is_quiet(),
+        // In merge mode, the command is run on the merged PDF
+        post_process_command: match composer.merge_mode {
+            Some(_) => None,
+            None => composer.post_process_command.clone(),
+        },
+        creator: document_creator(composer, &yaml_btreemap),
+        producer: composer
+            .producer
+            .as_ref()
+            .map_or(PACKAGE_NAME.to_string(), |producer| {
+                producer.apply(PACKAGE_NAME)
+            }),
+    };
+
+    let mut dictionary_entries = composer.pdf_document_entries.clone().unwrap_or_default();
+    dictionary_entries.extend(overrides.doc_info_entries.clone());
+
+    DocumentBuild {
+        yaml_btreemap,
+        dictionary_entries,
+        instance_data,
+    }
+}
+
+/// This function returns the files embedded in the PDF of a document: those of the composer,
+/// then those in the `attachments` front matter entry.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the attachments of every PDF.
+/// * `document` - The path of the source document.
+/// * `yaml_btreemap` - The YAML Front Matter of the document.
+///
+/// # Returns
+///
+/// A `Vec<FileAttachment>` of the files to embed.
+///
+/// # Remarks
+///
+/// The `attachments` entry is a path, or a list of paths or of `path` and `description` pairs.
+/// The paths are relative to the directory of the source document, and may not lead out of it
+/// (or out of the asset base directory): absolute paths and paths leading elsewhere are left out
+/// with a warning, so a document cannot embed any file readable by the user building it.
+///
+/// ```yaml
+/// attachments:
+///   - data/results.csv
+///   - path: signatures/approval.p7s
+///     description: Approval signature
+/// ```
+pub fn document_attachments(
+    composer: &PDFComposerStruct,
+    document: &Path,
+    yaml_btreemap: &BTreeMap<String, Value>,
+) -> Vec<FileAttachment> {
+    let mut attachments = composer.attachments.clone();
+    let entries = match yaml_btreemap.get("attachments") {
+        Some(Value::Sequence(entries)) => entries.clone(),
+        Some(entry) => vec![entry.clone()],
+        None => Vec::new(),
+    };
+    let source_directory = document.parent().unwrap_or(Path::new(""));
+    let mut roots = vec![source_directory];
+    roots.extend(asset_directory(document, composer.asset_base.as_ref()));
+    for entry in &entries {
+        let (path, description) = match entry {
+            Value::String(path) => (Some(path.as_str()), None),
+            Value::Mapping(_) => (
+                entry.get("path").and_then(Value::as_str),
+                entry.get("description").and_then(Value::as_str),
+            ),
+            _ => (None, None),
+        };
+        let Some(path) = path.map(str::trim).filter(|path| !path.is_empty()) else {
+            eprintln!(
+                "{}{}",
+                CROSS_MARK.yellow(),
+                format!(
+                    "An attachments entry of {} has no path; it is left out.",
+                    document.display()
+                )
+                .yellow()
+            );
+            continue;
+        };
+        let path = match confined_path(source_directory, path, &roots) {
+            Ok(path) => path,
+            Err(error) => {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!(
+                        "An attachments entry of {} is refused ({}); it is left out.",
+                        document.display(),
+                        error
+                    )
+                    .yellow()
+                );
+                continue;
+            }
+        };
+        attachments.push(FileAttachment {
+            path,
+            description: description
+                .map(str::trim)
+                .filter(|description| !description.is_empty())
+                .map(str::to_string),
+        });
+    }
+    attachments
+}
+
+/// Returns the `Creator` of a document: its `generator` front matter value, or the composer
+/// creator (replacing or after PDF Composer).
+fn document_creator(
+    composer: &PDFComposerStruct,
+    yaml_btreemap: &BTreeMap<String, Value>,
+) -> String {
+    if let Some(generator) = yaml_btreemap
+        .get("generator")
+        .and_then(|value| value.as_str())
+    {
+        return generator.to_string();
+    }
+    composer
+        .creator
+        .as_ref()
+        .map_or(PACKAGE_NAME.to_string(), |creator| {
+            creator.apply(PACKAGE_NAME)
+        })
+}
+
+/// This function returns the output directory of a document: the front matter `output_dir` (or
+/// `pdf.output`) entry, if any, otherwise the composer-wide output directory.
+///
+/// # Arguments
+///
+/// * `document` - The path of the source document.
+/// * `yaml_btreemap` - The YAML Front Matter of the document.
+/// * `output_directory` - The composer-wide output directory.
+///
+/// # Returns
+///
+/// A `PathBuf` with the output directory. The front matter entry is relative to the
+/// composer-wide output directory (`output_dir: invoices` saves to `<output directory>/invoices`).
+///
+/// # Remarks
+///
+/// The front matter entry may not lead out of the composer-wide output directory: an absolute
+/// path, or a path whose `..` components lead elsewhere, is ignored with a warning, so a document
+/// cannot write (or overwrite) files anywhere the user building it can.
+pub fn document_output_directory(
+    document: &Path,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    output_directory: &Path,
+) -> PathBuf {
+    let front_matter_directory = yaml_btreemap
+        .get("output_dir")
+        .or_else(|| yaml_btreemap.get("pdf").and_then(|pdf| pdf.get("output")))
+        .and_then(|value| value.as_str())
+        .filter(|directory| !directory.trim().is_empty());
+    match front_matter_directory {
+        Some(directory) => confined_path(output_directory, directory, &[output_directory])
+            .unwrap_or_else(|error| {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!(
+                        "The output directory of {} is refused ({}); it is saved to {}.",
+                        document.display(),
+                        error,
+                        output_directory.display()
+                    )
+                    .yellow()
+                );
+                output_directory.to_path_buf()
+            }),
+        None => output_directory.to_path_buf(),
+    }
+}
+
+/// This function builds the document of one output format from the HTML of a source document.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings to generate the document with.
+/// * `document` - The path of the source document.
+/// * `output_format` - The format of the document to build.
+/// * `html` - The HTML of the document body (see `render_document_html`).
+/// * `markdown_content` - The markdown of the document, without the front matter (for text output).
+/// * `document_build` - What the document is built with (see `document_build`).
+///
+/// # Returns
+///
+/// * `Ok(OutputFile)` with the generated PDF (or HTML document, e-book or text file).
+/// * `Err(message)` if the document could not be built.
+pub fn build_output(
+    composer: &PDFComposerStruct,
+    document: &Path,
+    output_format: OutputFormat,
+    html: &str,
+    markdown_content: &str,
+    document_build: &DocumentBuild,
+) -> Result<OutputFile, String> {
+    // The page Chromium prints has no location, so relative links and images are resolved first.
+    let html = match output_format {
+        OutputFormat::Pdf => resolve_assets(html, document, composer.asset_base.as_ref()),
+        _ => html.to_string(),
+    };
+    // Point links to other source documents at their documents of the same format.
+    let html = rewrite_document_links(&html, document, composer, output_format);
+    let yaml_btreemap = document_build.yaml_btreemap.clone();
+    let dictionary_entries = document_build.dictionary_entries.clone();
+    let instance_data = document_build.instance_data.clone();
+    let built = match output_format {
+        OutputFormat::Pdf => build_pdf(html, yaml_btreemap, dictionary_entries, instance_data).map(
+            |(path, page_count)| OutputFile {
+                path,
+                page_count: Some(page_count),
+            },
+        ),
+        OutputFormat::Html => {
+            build_html(html, yaml_btreemap, dictionary_entries, instance_data).map(OutputFile::from)
+        }
+        #[cfg(feature = "epub")]
+        OutputFormat::Epub => {
+            build_epub(html, yaml_btreemap, dictionary_entries, instance_data).map(OutputFile::from)
+        }
+        OutputFormat::Text(text_format) => build_text(
+            &html,
+            markdown_content,
+            yaml_btreemap,
+            instance_data,
+            text_format,
+        )
+        .map(OutputFile::from),
+    };
+    built.map_err(|error| format!("{} not built: {}", output_format, error))
+}
+
+/// This function gives the status of a source document from the documents generated for each
+/// output format: failed if any output format failed, skipped if any other was skipped, and
+/// generated otherwise.
+///
+/// # Arguments
+///
+/// * `output_files` - The documents generated for each output format (see `generate_document`).
+///
+/// # Returns
+///
+/// The `DocumentStatus`, with the message of the first error of that status (`None` if generated).
+pub fn document_status(output_files: &[OutputResult]) -> (DocumentStatus, Option<String>) {
+    let errors: Vec<&OutputError> = output_files
+        .iter()
+        .filter_map(|output_file| output_file.as_ref().err())
+        .collect();
+    let error = errors
+        .iter()
+        .find(|error| error.status == DocumentStatus::Failed)
+        .or(errors.first());
+    match error {
+        Some(error) => (error.status, Some(error.message.clone())),
+        None => (DocumentStatus::Generated, None),
+    }
+}
+
+/// This function puts the documents generated from a source document in a `GeneratedDocument`,
+/// the first output format as its `output_file` and the others as its `additional_output_files`,
+/// with the number of pages of the generated PDF (if any).
+///
+/// # Arguments
+///
+/// * `source_file` - The path of the source document.
+/// * `output_files` - The documents generated for each output format (see `generate_document`), or a single error if the source document could not be read.
+///
+/// # Returns
+///
+/// The `GeneratedDocument` for the report, with its status and error (see `document_status`).
+pub fn generated_document(
+    source_file: PathBuf,
+    output_files: Vec<OutputResult>,
+) -> GeneratedDocument {
+    let (status, error) = document_status(&output_files);
+    let page_count = output_files
+        .iter()
+        .flatten()
+        .find_map(|output_file| output_file.page_count);
+    let mut output_files = output_files
+        .into_iter()
+        .map(|output_file| output_file.map(|output_file| output_file.path));
+    GeneratedDocument {
+        source_file,
+        output_file: output_files.next().and_then(Result::ok),
+        additional_output_files: output_files.flatten().collect(),
+        page_count,
+        status,
+        error,
+    }
+}
+
+/// This function prints the summary of a run (on stderr) once every document has been attempted:
+/// the number of documents generated, skipped and failed, and why each document that was not
+/// generated was skipped or failed. Nothing is printed if every document was generated.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` of the run (for the progress writer).
+/// * `report` - The `GenerationReport` of the run.
+///
+/// # Remarks
+///
+/// The `summary` progress event (see `set_progress_writer`) is written whatever the outcome.
+pub fn report_summary(composer: &PDFComposerStruct, report: &GenerationReport) {
+    let failed_count = report.failed_count();
+    let skipped_count = report.skipped_count();
+    write_progress(
+        composer,
+        ProgressEvent::Summary {
+            generated: report.succeeded_count(),
+            skipped: skipped_count,
+            failed: failed_count,
+        },
+    );
+    if failed_count == 0 && skipped_count == 0 {
+        return;
+    }
+    let summary = format!(
+        "{} generated, {} skipped, {} failed, of {} documents:",
+        report.succeeded_count(),
+        skipped_count,
+        failed_count,
+        report.documents.len()
+    );
+    if failed_count > 0 {
+        eprintln!("\n{}{}", CROSS_MARK.red(), summary.red());
+    } else {
+        eprintln!("\n{}{}", CROSS_MARK.yellow(), summary.yellow());
+    }
+    for document in &report.documents {
+        let Some(error) = &document.error else {
+            continue;
+        };
+        let source_file = document.source_file.display().to_string();
+        match document.status {
+            DocumentStatus::Failed => eprintln!("  {}: {}", source_file.red(), error),
+            _ => eprintln!("  {}: {}", source_file.yellow(), error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::normalize_path;
+    use crate::PDFComposer;
+
+    /// Returns the YAML Front Matter with the given `attachments` entry.
+    fn front_matter_attachments(paths: &[&str]) -> BTreeMap<String, Value> {
+        let entries = paths
+            .iter()
+            .map(|path| Value::String(path.to_string()))
+            .collect();
+        BTreeMap::from([("attachments".to_string(), Value::Sequence(entries))])
+    }
+
+    #[test]
+    fn attaches_files_of_the_source_directory() {
+        let composer: PDFComposerStruct = PDFComposer::new();
+        let yaml_btreemap = front_matter_attachments(&["data/results.csv", "notes.txt"]);
+
+        let attachments =
+            document_attachments(&composer, Path::new("docs/report.md"), &yaml_btreemap);
+
+        let paths: Vec<PathBuf> = attachments.into_iter().map(|file| file.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                normalize_path(Path::new("docs/data/results.csv")),
+                normalize_path(Path::new("docs/notes.txt"))
+            ]
+        );
+    }
+
+    #[test]
+    fn leaves_out_attachments_outside_the_source_directory() {
+        let composer: PDFComposerStruct = PDFComposer::new();
+        let yaml_btreemap = front_matter_attachments(&[
+            "/etc/passwd",
+            "../secret.txt",
+            "data/../../secret.txt",
+            "../assets/logo.png",
+        ]);
+
+        let attachments =
+            document_attachments(&composer, Path::new("docs/report.md"), &yaml_btreemap);
+
+        assert!(attachments.is_empty());
+    }
+
+    #[test]
+    fn saves_to_front_matter_output_directories_inside_the_output_directory() {
+        let output_directory = Path::new("pdfs");
+        let yaml_btreemap = BTreeMap::from([(
+            "output_dir".to_string(),
+            Value::String("invoices/2024".to_string()),
+        )]);
+
+        assert_eq!(
+            document_output_directory(Path::new("invoice.md"), &yaml_btreemap, output_directory),
+            normalize_path(Path::new("pdfs/invoices/2024"))
+        );
+    }
+
+    #[test]
+    fn ignores_front_matter_output_directories_outside_the_output_directory() {
+        let output_directory = Path::new("pdfs");
+        for directory in ["/tmp", "..", "invoices/../../src"] {
+            let yaml_btreemap = BTreeMap::from([(
+                "output_dir".to_string(),
+                Value::String(directory.to_string()),
+            )]);
+
+            assert_eq!(
+                document_output_directory(
+                    Path::new("invoice.md"),
+                    &yaml_btreemap,
+                    output_directory
+                ),
+                output_directory
+            );
+        }
+    }
+
+    #[test]
+    fn attaches_files_of_the_asset_base_directory() {
+        let mut composer: PDFComposerStruct = PDFComposer::new();
+        composer.set_asset_base("assets");
+        let yaml_btreemap = front_matter_attachments(&["../assets/logo.png", "../other/logo.png"]);
+
+        let attachments =
+            document_attachments(&composer, Path::new("docs/report.md"), &yaml_btreemap);
+
+        let paths: Vec<PathBuf> = attachments.into_iter().map(|file| file.path).collect();
+        assert_eq!(
+            paths,
+            vec![normalize_path(Path::new("docs/../assets/logo.png"))]
+        );
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::generate::{document_status, OutputError, OutputResult};
+use crate::progress::write_progress;
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::generation_report::DocumentStatus;
+use pdf_composer_definitions::generation_threads::{GenerationThreads, ProcessingMode};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+use pdf_composer_definitions::progress::ProgressEvent;
+
+/// This function maps each item (usually a document to generate) in the processing mode of the
+/// composer: in parallel on its generation threads, or one after another.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` (for the processing mode and generation threads).
+/// * `items` - The items to map, in order.
+/// * `operation` - The operation run for each item.
+///
+/// # Returns
+///
+/// The result of the operation for each item, in the order of `items` either way.
+pub fn map_documents<I, R, F>(composer: &PDFComposerStruct, items: Vec<I>, operation: F) -> Vec<R>
+where
+    I: Send,
+    R: Send,
+    F: Fn(I) -> R + Send + Sync,
+{
+    match composer.processing_mode {
+        ProcessingMode::Parallel => run_on_generation_threads(&composer.generation_threads, || {
+            items.into_par_iter().map(operation).collect()
+        }),
+        // No rayon at all: one document at a time, on the calling thread
+        ProcessingMode::Sequential => items.into_iter().map(operation).collect(),
+    }
+}
+
+/// The error of the documents that are not generated because an earlier one failed (`set_fail_fast`).
+pub const FAIL_FAST_ERROR: &str = "not generated, as an earlier document failed (fail fast)";
+
+/// Struct tracking whether a document of a batch has failed, so that with fail fast
+/// (`set_fail_fast`) the documents not started yet are not generated.
+pub struct FailFast<'a> {
+    /// The composer generating the batch (for the output formats and the progress writer).
+    composer: &'a PDFComposerStruct,
+    /// Whether a document has failed.
+    failed: AtomicBool,
+}
+
+impl<'a> FailFast<'a> {
+    /// Creates the tracker of a batch, with the fail fast setting of the composer.
+    pub fn new(composer: &'a PDFComposerStruct) -> Self {
+        FailFast {
+            composer,
+            failed: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns `true` if a document of the batch has failed.
+    pub fn has_failed(&self) -> bool {
+        self.failed.load(Ordering::SeqCst)
+    }
+
+    /// This function generates one document of the batch, giving the result for each output
+    /// format (or a single error if the document could not be generated at all).
+    ///
+    /// # Arguments
+    ///
+    /// * `source_file` - The path of the source document (for the progress events).
+    /// * `operation` - The generation of the document.
+    ///
+    /// # Remarks
+    ///
+    /// A panic in the operation (such as a crashed browser) is caught and becomes the error of
+    /// the document, so the other documents of the batch are still generated. With fail fast, a
+    /// document is not generated once another has failed. The progress events of the document
+    /// (see `set_progress_writer`) are written as it starts and ends.
+    pub fn generate<F>(&self, source_file: &Path, operation: F) -> Vec<OutputResult>
+    where
+        F: FnOnce() -> Vec<OutputResult>,
+    {
+        let output_format_count = self.composer.output_formats.len().max(1);
+        let file = source_file.display().to_string();
+        if self.composer.fail_fast && self.has_failed() {
+            write_progress(
+                self.composer,
+                ProgressEvent::Skip {
+                    file,
+                    message: FAIL_FAST_ERROR.to_string(),
+                },
+            );
+            return vec![Err(OutputError::skipped(FAIL_FAST_ERROR)); output_format_count];
+        }
+
+        write_progress(self.composer, ProgressEvent::Start { file: file.clone() });
+        let started = Instant::now();
+        let results = panic::catch_unwind(AssertUnwindSafe(operation)).unwrap_or_else(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown error".to_string());
+            let error = OutputError::failed(format!("generation panicked: {}", message));
+            vec![Err(error); output_format_count]
+        });
+
+        let (status, error) = document_status(&results);
+        if self.composer.console_output.is_verbose() {
+            let outcome = match status {
+                DocumentStatus::Generated => "generated",
+                DocumentStatus::Skipped => "skipped",
+                DocumentStatus::Failed => "failed",
+            };
+            println!(
+                "{} {} in {:.2}s",
+                file.cyan(),
+                outcome,
+                started.elapsed().as_secs_f64()
+            );
+        }
+        // A skipped document (missing required front matter keys) is not a failure
+        if results.is_empty() || status == DocumentStatus::Failed {
+            self.failed.store(true, Ordering::SeqCst);
+        }
+        let event = match (status, error) {
+            (DocumentStatus::Failed, Some(message)) => ProgressEvent::Error { file, message },
+            (DocumentStatus::Skipped, Some(message)) => ProgressEvent::Skip { file, message },
+            _ => ProgressEvent::Finish {
+                file,
+                outputs: results
+                    .iter()
+                    .flatten()
+                    .map(|output_file| output_file.path.display().to_string())
+                    .collect(),
+            },
+        };
+        write_progress(self.composer, event);
+        results
+    }
+}
+
+/// This function runs an operation (and any parallel iterators in it) on the threads chosen for
+/// generating documents.
+///
+/// # Arguments
+///
+/// * `generation_threads` - The `GenerationThreads` to run on.
+/// * `operation` - The operation to run.
+///
+/// # Returns
+///
+/// The result of the operation.
+///
+/// # Remarks
+///
+/// A thread pool of its own (`GenerationThreads::Count`) is built for each call and dropped
+/// afterwards. If it cannot be built, the operation runs on the global thread pool instead.
+fn run_on_generation_threads<R, F>(generation_threads: &GenerationThreads, operation: F) -> R
+where
+    R: Send,
+    F: FnOnce() -> R + Send,
+{
+    match generation_threads {
+        GenerationThreads::Global => operation(),
+        GenerationThreads::Count(threads) => {
+            match ThreadPoolBuilder::new().num_threads(*threads).build() {
+                Ok(thread_pool) => thread_pool.install(operation),
+                Err(error) => {
+                    eprintln!(
+                        "{}{}",
+                        CROSS_MARK.yellow(),
+                        format!(
+                            "Could not build a thread pool of {} threads ({}); using the global thread pool.",
+                            threads, error
+                        )
+                        .yellow()
+                    );
+                    operation()
+                }
+            }
+        }
+        GenerationThreads::Pool(thread_pool) => thread_pool.install(operation),
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// The placeholders filled in from the Git repository of a document.
+const GIT_VARIABLES: [&str; 4] = ["git_sha", "git_short_sha", "git_tag", "git_last_modified"];
+
+/// This function fills in the `{{git_sha}}`, `{{git_short_sha}}`, `{{git_tag}}` and
+/// `{{git_last_modified}}` placeholders of a document from the Git repository it is in, as front
+/// matter values (so they work wherever placeholders do, document information entries included).
+///
+/// * `git_sha` and `git_short_sha` - The full and abbreviated hash of the checked out commit (`HEAD`).
+/// * `git_tag` - The most recent tag reachable from the checked out commit.
+/// * `git_last_modified` - The date (`YYYY-MM-DD`) of the last commit that changed the source
+///   file, which `{{date git_last_modified}}` writes in the format of the locale.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` (for the QR code footer and watermark templates, and the
+///   document information entries).
+/// * `document` - The path of the source document.
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) of the document. The values are added
+///   when the markdown, the QR code footer, the watermark or a document information entry refers to
+///   them and the front matter does not set them itself.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+///
+/// # Remarks
+///
+/// The `git` command is run in the directory of the document. A value that Git cannot give, such
+/// as outside a repository, for a repository without tags or for a file that is not committed, is
+/// left out (and its placeholder is treated as any other without a value).
+pub fn fill_git_placeholders(
+    composer: &PDFComposerStruct,
+    document: &Path,
+    yaml_btreemap: &mut BTreeMap<String, Value>,
+    markdown_content: &str,
+) {
+    let templates = [
+        markdown_content,
+        composer.qr_code_footer.as_deref().unwrap_or(""),
+        composer.watermark.as_deref().unwrap_or(""),
+    ];
+    let is_used = |key: &str| {
+        let placeholder = format!("{{{{{}}}}}", key);
+        let date_placeholder = format!("{{{{date {}", key);
+        templates
+            .iter()
+            .any(|template| template.contains(&placeholder) || template.contains(&date_placeholder))
+            || composer
+                .pdf_document_entries
+                .as_ref()
+                .is_some_and(|entries| entries.values().any(|yaml_entry| yaml_entry == key))
+    };
+
+    let directory = match document.parent() {
+        Some(directory) if !directory.as_os_str().is_empty() => directory,
+        _ => Path::new("."),
+    };
+    let file_name = document
+        .file_name()
+        .and_then(|file_name| file_name.to_str())
+        .filter(|_| document.is_file());
+    for key in GIT_VARIABLES {
+        if yaml_btreemap.contains_key(key) || !is_used(key) {
+            continue;
+        }
+        let value = match key {
+            "git_sha" => git_output(directory, &["rev-parse", "HEAD"]),
+            "git_short_sha" => git_output(directory, &["rev-parse", "--short", "HEAD"]),
+            "git_tag" => git_output(directory, &["describe", "--tags", "--abbrev=0"]),
+            _ => file_name.and_then(|file_name| {
+                git_output(directory, &["log", "-1", "--format=%cs", "--", file_name])
+            }),
+        };
+        if let Some(value) = value {
+            yaml_btreemap.insert(key.to_string(), Value::String(value));
+        }
+    }
+}
+
+/// Runs a `git` command in a directory, returning what it printed (trimmed), or `None` if it failed
+/// or printed nothing.
+fn git_output(directory: &Path, arguments: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(directory)
+        .args(arguments)
+        .output()
+        .ok()?;
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
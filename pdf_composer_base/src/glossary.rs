@@ -0,0 +1,163 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+use serde_yml::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::utils::{escape_html, map_outside_code};
+
+/// The styles for the glossary section and the linked terms, added to the page CSS.
+pub const GLOSSARY_CSS: &str = "
+a.glossary-term { color: inherit; text-decoration: underline dotted; }
+.glossary dt { font-weight: bold; break-after: avoid; }
+.glossary dd { margin: 0 0 0.6em 1.5em; }
+";
+
+/// The glossary section title used when the front matter does not set one.
+const DEFAULT_GLOSSARY_TITLE: &str = "Glossary";
+
+/// This function links the first occurrence of each glossary term in the generated HTML to its
+/// definition, and appends the glossary section, from the `glossary` entry of the YAML Front Matter.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+/// * `yaml_btreemap` - The YAML Front Matter of the document.
+///
+/// # Returns
+///
+/// A `String` with the terms linked and the glossary appended. The HTML is returned unchanged if
+/// the front matter has no `glossary` entry.
+///
+/// # Remarks
+///
+/// The front matter entries used are:
+///
+/// * `glossary` - The terms, each mapped to its definition.
+/// * `glossary-title` - The title of the glossary section. Defaults to `Glossary`.
+/// * `glossary-links` - `false` to leave the terms in the text unlinked.
+/// * `glossary-section` - `false` to leave out the glossary section (the terms are then not linked
+///   either, having nothing to link to).
+///
+/// Terms are matched as whole words, in any case, and the longest term wins where terms overlap.
+/// Terms in code, headings and links are left alone. The glossary lists the terms alphabetically.
+pub fn process_glossary(html: &str, yaml_btreemap: &BTreeMap<String, Value>) -> String {
+    let Some(Value::Mapping(glossary)) = yaml_btreemap.get("glossary") else {
+        return html.to_string();
+    };
+    let mut terms: Vec<(String, String)> = glossary
+        .iter()
+        .filter_map(|(term, definition)| {
+            let term = term.as_str()?.trim();
+            let definition = match definition {
+                Value::String(definition) => definition.trim().to_string(),
+                Value::Number(number) => number.to_string(),
+                _ => return None,
+            };
+            (!term.is_empty()).then(|| (term.to_string(), definition))
+        })
+        .collect();
+    let is_off = |key: &str| yaml_btreemap.get(key).and_then(Value::as_bool) == Some(false);
+    if terms.is_empty() || is_off("glossary-section") {
+        return html.to_string();
+    }
+    terms.sort_by_key(|(term, _)| term.to_lowercase());
+
+    let html = if is_off("glossary-links") {
+        html.to_string()
+    } else {
+        link_terms(html, &terms)
+    };
+
+    let title = yaml_btreemap
+        .get("glossary-title")
+        .and_then(|value| value.as_str())
+        .unwrap_or(DEFAULT_GLOSSARY_TITLE);
+    let mut section = format!(
+        "<section class=\"glossary\"><h2>{}</h2>\n<dl>\n",
+        escape_html(title)
+    );
+    for (term, definition) in &terms {
+        section.push_str(&format!(
+            "<dt id=\"{}\">{}</dt>\n<dd>{}</dd>\n",
+            term_id(term),
+            escape_html(term),
+            escape_html(definition)
+        ));
+    }
+    section.push_str("</dl>\n</section>\n");
+    html + &section
+}
+
+/// Links the first occurrence of each term (outside code, headings and links) to its definition.
+fn link_terms(html: &str, terms: &[(String, String)]) -> String {
+    // Longest first, so `style sheet` is matched before `style`
+    let mut alternatives: Vec<String> = terms
+        .iter()
+        .map(|(term, _)| regex::escape(&escape_html(term)))
+        .collect();
+    alternatives.sort_by_key(|alternative| std::cmp::Reverse(alternative.len()));
+    let term_regex = Regex::new(&format!(r"(?i)\b(?:{})\b", alternatives.join("|"))).unwrap();
+    let tag_regex = Regex::new(r"(?s)<(/?)(a|h[1-6])\b[^>]*>|<[^>]*>").unwrap();
+    let definitions: BTreeMap<String, (&String, &String)> = terms
+        .iter()
+        .map(|(term, definition)| (escape_html(term).to_lowercase(), (term, definition)))
+        .collect();
+
+    let mut linked: BTreeSet<String> = BTreeSet::new();
+    // How many links and headings the text is inside
+    let mut skipped_depth = 0usize;
+    map_outside_code(html, |chunk| {
+        let mut output = String::with_capacity(chunk.len());
+        let mut last_end = 0;
+        let mut link_text = |text: &str, skipped: bool| -> String {
+            if skipped || linked.len() == definitions.len() {
+                return text.to_string();
+            }
+            term_regex
+                .replace_all(text, |captures: &Captures| {
+                    let key = captures[0].to_lowercase();
+                    match definitions.get(&key) {
+                        Some((term, definition)) if linked.insert(key) => format!(
+                            "<a class=\"glossary-term\" href=\"#{}\" title=\"{}\">{}</a>",
+                            term_id(term),
+                            escape_html(definition),
+                            &captures[0]
+                        ),
+                        _ => captures[0].to_string(),
+                    }
+                })
+                .to_string()
+        };
+        for tag in tag_regex.captures_iter(chunk) {
+            let whole = tag.get(0).unwrap();
+            output.push_str(&link_text(
+                &chunk[last_end..whole.start()],
+                skipped_depth > 0,
+            ));
+            output.push_str(whole.as_str());
+            if tag.get(2).is_some() {
+                if tag[1].is_empty() {
+                    skipped_depth += 1;
+                } else {
+                    skipped_depth = skipped_depth.saturating_sub(1);
+                }
+            }
+            last_end = whole.end();
+        }
+        output.push_str(&link_text(&chunk[last_end..], skipped_depth > 0));
+        output
+    })
+}
+
+/// Returns the id of the definition of a term in the glossary, such as `glossary-style-sheet`.
+fn term_id(term: &str) -> String {
+    let slug: String = term
+        .to_lowercase()
+        .chars()
+        .filter(|character| character.is_alphanumeric() || matches!(character, ' ' | '-' | '_'))
+        .map(|character| if character == ' ' { '-' } else { character })
+        .collect();
+    format!("glossary-{}", slug)
+}
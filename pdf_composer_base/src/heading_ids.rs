@@ -0,0 +1,66 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+use std::collections::BTreeSet;
+
+/// This function gives every heading without an id one made from its text, so `#heading` links work.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+///
+/// # Returns
+///
+/// A `String` with an `id` on every heading.
+///
+/// # Remarks
+///
+/// Ids are made the way GitHub makes them: the heading text in lower case, with spaces replaced
+/// by `-` and other punctuation removed (`## Getting Started!` becomes `getting-started`).
+/// Repeated ids get `-1`, `-2` etc added.
+pub fn add_heading_ids(html: &str) -> String {
+    let heading_regex = Regex::new(r"(?s)<h([1-6])>(.*?)</h([1-6])>").unwrap();
+    let existing_id_regex = Regex::new(r#"<h[1-6] id="([^"]*)""#).unwrap();
+    let tag_regex = Regex::new(r"<[^>]*>|&[#\w]+;").unwrap();
+
+    let mut used_ids: BTreeSet<String> = existing_id_regex
+        .captures_iter(html)
+        .map(|captures| captures[1].to_string())
+        .collect();
+
+    heading_regex
+        .replace_all(html, |captures: &Captures| {
+            let text = tag_regex.replace_all(&captures[2], "");
+            let slug: String = text
+                .trim()
+                .to_lowercase()
+                .chars()
+                .filter(|character| {
+                    character.is_alphanumeric() || matches!(character, ' ' | '-' | '_')
+                })
+                .map(|character| if character == ' ' { '-' } else { character })
+                .collect();
+
+            // Headings with no letters or digits (such as `## ***`) still need an id
+            let slug = if slug.is_empty() {
+                "section".to_string()
+            } else {
+                slug
+            };
+
+            let mut id = slug.clone();
+            let mut repeat = 0;
+            while used_ids.contains(&id) {
+                repeat += 1;
+                id = format!("{}-{}", slug, repeat);
+            }
+            used_ids.insert(id.clone());
+
+            format!(
+                "<h{} id=\"{}\">{}</h{}>",
+                &captures[1], id, &captures[2], &captures[3]
+            )
+        })
+        .to_string()
+}
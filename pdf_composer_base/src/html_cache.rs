@@ -0,0 +1,130 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use serde_yml::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::utils::{latest_modification, source_extension, write_atomically};
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function returns the HTML of a document from the HTML cache, rendering (and caching) it
+/// when it is not there.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the HTML cache directory (`None` renders every time).
+/// * `document` - The path of the source document.
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) filling the placeholders.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+/// * `render` - Renders the HTML of the document.
+///
+/// # Returns
+///
+/// A `String` with the HTML of the document body.
+///
+/// # Remarks
+///
+/// The HTML is cached under a hash of everything it is rendered from: the markdown and front
+/// matter, the path of the document, the settings used for rendering (endnotes, code line numbers and wrapping, citations, the
+/// names of the registered shortcodes), the
+/// PDF Composer version and the latest modification time of the files near the document (images,
+/// CSV files, bibliographies), leaving out other markdown files and the cache and output
+/// directories. Any change gives a
+/// new key, so stale HTML is never used; old entries are simply left behind.
+///
+/// Errors writing to the cache are printed and otherwise ignored (the HTML is rendered as usual).
+pub fn cached_html<F>(
+    composer: &PDFComposerStruct,
+    document: &Path,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    markdown_content: &str,
+    render: F,
+) -> String
+where
+    F: FnOnce() -> String,
+{
+    let Some(cache_directory) = &composer.html_cache else {
+        return render();
+    };
+
+    let key = cache_key(
+        composer,
+        cache_directory,
+        document,
+        yaml_btreemap,
+        markdown_content,
+    );
+    let cache_file = cache_directory.join(format!("{:016x}.html", key));
+    if let Ok(html) = fs::read_to_string(&cache_file) {
+        if composer.console_output.is_verbose() {
+            println!(
+                "HTML of {} read from the cache.",
+                document.display().to_string().cyan()
+            );
+        }
+        return html;
+    }
+
+    let html = render();
+    if let Err(error) = write_cache_file(&cache_file, &html) {
+        eprintln!(
+            "{}Could not write {} to the HTML cache: {}",
+            CROSS_MARK.yellow(),
+            document.display().to_string().yellow(),
+            error
+        );
+    }
+    html
+}
+
+/// Returns the key the HTML of a document is cached under.
+fn cache_key(
+    composer: &PDFComposerStruct,
+    cache_directory: &Path,
+    document: &Path,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    markdown_content: &str,
+) -> u64 {
+    let source_directory = document.parent().unwrap_or(Path::new(""));
+    let generated_directories: Vec<PathBuf> = [cache_directory, &composer.output_directory]
+        .iter()
+        .filter_map(|directory| directory.canonicalize().ok())
+        .collect();
+    // Other source documents do not change the HTML of this one (links to them are rewritten
+    // after the HTML is cached), so editing one chapter leaves the others cached
+    let latest = latest_modification(document, source_directory, |path| {
+        generated_directories.contains(&path.to_path_buf())
+            || path.file_name().is_some_and(|file_name| {
+                source_extension(&file_name.to_string_lossy(), &composer.source_extensions)
+                    .is_some()
+            })
+    });
+
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    document.hash(&mut hasher);
+    format!("{:?}", yaml_btreemap).hash(&mut hasher);
+    markdown_content.hash(&mut hasher);
+    composer.endnotes_title.hash(&mut hasher);
+    composer.code_line_numbers.hash(&mut hasher);
+    composer.code_wrap.hash(&mut hasher);
+    for shortcode_name in composer.shortcodes.keys() {
+        shortcode_name.hash(&mut hasher);
+    }
+    cfg!(feature = "citations").hash(&mut hasher);
+    latest.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Writes an entry to the HTML cache, through a temporary file so that a half-written entry is
+/// never read.
+fn write_cache_file(cache_file: &Path, html: &str) -> std::io::Result<()> {
+    write_atomically(cache_file, |writer| writer.write_all(html.as_bytes()))
+}
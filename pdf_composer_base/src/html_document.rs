@@ -0,0 +1,302 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use base64::Engine;
+use colored::Colorize;
+use regex::{Captures, Regex};
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::callouts::CALLOUT_CSS;
+use crate::code_blocks::CODE_BLOCK_CSS;
+use crate::csv_table::DATA_TABLE_CSS;
+use crate::form_fields::FORM_FIELD_CSS;
+use crate::glossary::GLOSSARY_CSS;
+use crate::layout_shortcodes::LAYOUT_SHORTCODE_CSS;
+use crate::utils::{escape_html, media_type};
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::fonts::{FontsStandard, GetCssName};
+use pdf_composer_definitions::page_properties::{
+    PageMargins, PaperOrientation, PaperSize, ToDimensions,
+};
+use pdf_composer_definitions::text_direction::{TextDirection, RTL_FALLBACK_FONTS};
+use pdf_composer_definitions::text_justification::JustifyOptions;
+
+/// Struct holding the page settings the document styles are made from.
+pub struct PageStyle<'a> {
+    /// The paper size.
+    pub paper_size: PaperSize,
+    /// The paper orientation.
+    pub orientation: PaperOrientation,
+    /// The page margins (in inches).
+    pub margins: PageMargins,
+    /// Whether the margins are top, outer, bottom, inner and alternate on left and right pages.
+    pub mirrored_margins: bool,
+    /// The body font.
+    pub font: FontsStandard,
+    /// The direction text is written in.
+    pub text_direction: TextDirection,
+    /// Optional justification and hyphenation of the body text.
+    pub text_justification: Option<&'a JustifyOptions>,
+}
+
+/// This function returns the text direction of a document: the front matter `dir` entry, if any,
+/// otherwise the composer-wide setting.
+pub fn document_text_direction(
+    yaml_btreemap: &BTreeMap<String, Value>,
+    text_direction: TextDirection,
+) -> TextDirection {
+    match yaml_btreemap.get("dir").and_then(|value| value.as_str()) {
+        Some(dir) if dir.eq_ignore_ascii_case("rtl") => TextDirection::Rtl,
+        Some(dir) if dir.eq_ignore_ascii_case("ltr") => TextDirection::Ltr,
+        _ => text_direction,
+    }
+}
+
+/// This function returns the language of a document: the front matter `lang` (or `language`) entry,
+/// if any, otherwise the composer-wide language, otherwise the hyphenation language.
+pub fn document_language(
+    yaml_btreemap: &BTreeMap<String, Value>,
+    language: Option<String>,
+    text_justification: Option<&JustifyOptions>,
+) -> Option<String> {
+    yaml_btreemap
+        .get("lang")
+        .or(yaml_btreemap.get("language"))
+        .and_then(|value| value.as_str())
+        .filter(|lang| !lang.trim().is_empty())
+        .map(|lang| lang.to_string())
+        .or(language)
+        .or(text_justification.and_then(|options| options.lang.clone()))
+}
+
+/// Returns the width and height (in inches) of the pages.
+pub fn page_dimensions(paper_size: PaperSize, orientation: PaperOrientation) -> (f64, f64) {
+    match orientation {
+        PaperOrientation::Landscape => (paper_size.to_dimensions().1, paper_size.to_dimensions().0),
+        PaperOrientation::Portrait => paper_size.to_dimensions(),
+    }
+}
+
+/// This function returns the `<style>` element with the page size, font and the styles of the
+/// generated elements (callouts, tables etc).
+///
+/// # Arguments
+///
+/// * `style` - The page settings.
+/// * `media` - The media the styles apply to: `print` when printing to PDF, `all` when the HTML is
+///   viewed in a browser.
+///
+/// # Returns
+///
+/// A `String` with the `<style>` element.
+pub fn page_css(style: &PageStyle, media: &str) -> String {
+    let (page_width, page_height) = page_dimensions(style.paper_size, style.orientation);
+    let margins = style.margins;
+
+    // Set CSS @media query and @page property for pages
+    let mut css_page = format!("<style>\n@media {} {{\n ", media);
+    let (mut css_font_name, css_font_weight, css_font_style) = style.font.get_css_name();
+    if style.text_direction == TextDirection::Rtl {
+        css_font_name = format!("{}, {}", css_font_name, RTL_FALLBACK_FONTS);
+    }
+    let css_font = format!(
+        "body {{ font-family: {}; font-weight: {}; font-style: {} }}\n\n",
+        css_font_name, css_font_weight, css_font_style
+    );
+    let css_at_page = format!("@page {{\nsize: {}in {}in;\n}}", page_width, page_height);
+    css_page.push_str(&css_font);
+    if let Some(JustifyOptions { hyphenate, .. }) = style.text_justification {
+        let hyphens = if *hyphenate { "auto" } else { "manual" };
+        css_page.push_str(&format!(
+            "body {{ text-align: justify; hyphens: {}; -webkit-hyphens: {}; overflow-wrap: break-word }}\n\n",
+            hyphens, hyphens
+        ));
+    }
+    css_page.push_str(&css_at_page);
+    css_page.push_str(CALLOUT_CSS);
+    css_page.push_str(CODE_BLOCK_CSS);
+    css_page.push_str(GLOSSARY_CSS);
+    css_page.push_str(DATA_TABLE_CSS);
+    css_page.push_str(LAYOUT_SHORTCODE_CSS);
+    css_page.push_str(FORM_FIELD_CSS);
+    if style.mirrored_margins {
+        // margins are [top, outer, bottom, inner]: the inner margin (gutter) is on the binding side
+        css_page.push_str(&format!(
+            "\n@page {{ margin-top: {}in; margin-bottom: {}in; }}\n\
+             @page :left {{ margin-left: {}in; margin-right: {}in; }}\n\
+             @page :right {{ margin-left: {}in; margin-right: {}in; }}",
+            margins[0], margins[2], margins[1], margins[3], margins[3], margins[1]
+        ));
+    }
+    css_page.push_str("\n}\n</style>");
+    css_page
+}
+
+/// This function returns the `<style>` element with the styles of a stylesheet (see
+/// `set_stylesheet`), to go after the built-in styles so it can override them.
+///
+/// # Arguments
+///
+/// * `stylesheet` - The path of the CSS file, if any.
+///
+/// # Returns
+///
+/// A `String` with the `<style>` element, empty without a stylesheet, or if the stylesheet cannot
+/// be read (with a warning).
+pub fn stylesheet_css(stylesheet: Option<&Path>) -> String {
+    let Some(stylesheet) = stylesheet else {
+        return String::new();
+    };
+    match fs::read_to_string(stylesheet) {
+        // `</style` would end the element early
+        Ok(css) => format!("<style>\n{}\n</style>", css.replace("</style", "<\\/style")),
+        Err(error) => {
+            eprintln!(
+                "{}{}",
+                CROSS_MARK.yellow(),
+                format!(
+                    "Stylesheet {} not read, left out: {}",
+                    stylesheet.display(),
+                    error
+                )
+                .yellow()
+            );
+            String::new()
+        }
+    }
+}
+
+/// This function returns the `<style>` element showing the body as a sheet of paper on screen,
+/// with the page margins, so HTML viewed in a browser looks like the printed page.
+///
+/// # Arguments
+///
+/// * `paper_size` - The paper size.
+/// * `orientation` - The paper orientation.
+/// * `margins` - The page margins (in inches).
+///
+/// # Returns
+///
+/// A `String` with the `<style>` element.
+pub fn screen_page_css(
+    paper_size: PaperSize,
+    orientation: PaperOrientation,
+    margins: PageMargins,
+) -> String {
+    let (page_width, page_height) = page_dimensions(paper_size, orientation);
+    format!(
+        "<style>\n@media screen {{\n\
+         html {{ background: #e5e5e5; }}\n\
+         body {{ box-sizing: border-box; width: {}in; min-height: {}in; margin: 1em auto; \
+         padding: {}in {}in {}in {}in; background: #fff; box-shadow: 0 0 0.5em #999; }}\n}}\n</style>",
+        page_width, page_height, margins[0], margins[1], margins[2], margins[3]
+    )
+}
+
+/// This function wraps the HTML generated from the markdown in a complete HTML document.
+///
+/// # Arguments
+///
+/// * `generated_html` - The HTML generated from the markdown.
+/// * `title` - The document title.
+/// * `language` - The document language tag, if known.
+/// * `text_direction` - The direction text is written in.
+/// * `head` - The `<style>` (and any other) elements for the `<head>`.
+///
+/// # Returns
+///
+/// A `String` with the HTML document.
+pub fn html_document(
+    generated_html: &str,
+    title: &str,
+    language: Option<&str>,
+    text_direction: TextDirection,
+    head: &str,
+) -> String {
+    let lang_attribute = match language {
+        Some(lang) => format!(" lang=\"{}\"", escape_html(lang)),
+        None => String::new(),
+    };
+    format!(
+        "<html{} dir=\"{}\"><head><meta charset=\"utf-8\"><title>{}</title>{}</head><body>{}</body></html>",
+        lang_attribute,
+        text_direction,
+        escape_html(title),
+        head,
+        generated_html
+    )
+}
+
+/// This function replaces the `src` of the images that are local files (relative to the source
+/// document), for documents that have to carry their images with them.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+/// * `source_directory` - The directory of the source document.
+/// * `replace` - A closure called with the path and content of each local image, returning the new `src`.
+///
+/// # Returns
+///
+/// A `String` with the `src` of the local images replaced. Images with a URL (`https:`, `data:` etc)
+/// are left alone, and images that cannot be read are left as they are, with a warning.
+pub fn replace_local_images(
+    html: &str,
+    source_directory: &Path,
+    mut replace: impl FnMut(&Path, Vec<u8>) -> String,
+) -> String {
+    let image_regex = Regex::new(r#"(<img\b[^>]*?\ssrc=")([^"]*)(")"#).unwrap();
+    let scheme_regex = Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:").unwrap();
+
+    image_regex
+        .replace_all(html, |captures: &Captures| {
+            let src = captures[2].replace("&amp;", "&");
+            if src.is_empty() || src.starts_with("//") || scheme_regex.is_match(&src) {
+                return captures[0].to_string();
+            }
+            let path = src.split(['?', '#']).next().unwrap_or_default();
+            let image_file = source_directory.join(url_escape::decode(path).as_ref());
+            match fs::read(&image_file) {
+                Ok(content) => format!(
+                    "{}{}{}",
+                    &captures[1],
+                    escape_html(&replace(&image_file, content)),
+                    &captures[3]
+                ),
+                Err(_) => {
+                    eprintln!(
+                        "{}{}",
+                        CROSS_MARK.yellow(),
+                        format!("Image {} not found, left as it is.", image_file.display())
+                            .yellow()
+                    );
+                    captures[0].to_string()
+                }
+            }
+        })
+        .to_string()
+}
+
+/// This function replaces the `src` of the images that are local files (relative to a directory)
+/// with data URLs, so the document carries its images with it.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+/// * `directory` - The directory the images are relative to.
+///
+/// # Returns
+///
+/// A `String` with the local images inlined (see `replace_local_images`).
+pub fn inline_local_images(html: &str, directory: &Path) -> String {
+    replace_local_images(html, directory, |image_file, content| {
+        format!(
+            "data:{};base64,{}",
+            media_type(image_file),
+            base64::engine::general_purpose::STANDARD.encode(content)
+        )
+    })
+}
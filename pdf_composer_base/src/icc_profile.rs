@@ -0,0 +1,237 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{dictionary, Document, Object as LopdfObject, Stream};
+use std::fs;
+
+use pdf_composer_definitions::icc_profile::IccProfile;
+
+/// The description of the sRGB profile, also used as the output condition identifier.
+pub const SRGB_PROFILE_NAME: &str = "sRGB IEC61966-2.1";
+
+/// The number of entries in the tone reproduction curve of the sRGB profile.
+const TRC_ENTRIES: usize = 1024;
+
+/// This function builds an ICC (version 2) colour profile for the sRGB colour space.
+///
+/// # Returns
+///
+/// A `Vec<u8>` with the ICC profile.
+///
+/// # Remarks
+///
+/// The profile is built rather than shipped, so there is no profile file to license. It has the
+/// sRGB primaries (adapted to the D50 illuminant of the profile connection space, as ICC profiles
+/// need), the D50 white point, and the sRGB tone reproduction curve as a table of 1024 entries.
+pub fn srgb_icc_profile() -> Vec<u8> {
+    let tone_curve = curve_tag();
+    let tags: Vec<([u8; 4], Vec<u8>)> = vec![
+        (*b"desc", description_tag(SRGB_PROFILE_NAME)),
+        (*b"cprt", text_tag("No copyright, use freely")),
+        (*b"wtpt", xyz_tag([0.9642, 1.0, 0.8249])),
+        (*b"rXYZ", xyz_tag([0.4361, 0.2225, 0.0139])),
+        (*b"gXYZ", xyz_tag([0.3851, 0.7169, 0.0971])),
+        (*b"bXYZ", xyz_tag([0.1431, 0.0606, 0.7141])),
+        (*b"rTRC", tone_curve.clone()),
+        (*b"gTRC", tone_curve.clone()),
+        (*b"bTRC", tone_curve),
+    ];
+
+    // The tag data starts after the header (128 bytes) and the tag table
+    let mut tag_table: Vec<u8> = (tags.len() as u32).to_be_bytes().to_vec();
+    let mut tag_data: Vec<u8> = Vec::new();
+    let data_start = 128 + 4 + 12 * tags.len();
+    for (signature, data) in &tags {
+        tag_table.extend_from_slice(signature);
+        tag_table.extend_from_slice(&((data_start + tag_data.len()) as u32).to_be_bytes());
+        tag_table.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        tag_data.extend_from_slice(data);
+        // Every tag starts on a four byte boundary
+        while tag_data.len() % 4 != 0 {
+            tag_data.push(0);
+        }
+    }
+
+    let size = 128 + tag_table.len() + tag_data.len();
+    let mut profile: Vec<u8> = Vec::with_capacity(size);
+    profile.extend_from_slice(&(size as u32).to_be_bytes());
+    profile.extend_from_slice(&[0; 4]); // Preferred CMM
+    profile.extend_from_slice(&[0x02, 0x10, 0x00, 0x00]); // Version 2.1
+    profile.extend_from_slice(b"mntr"); // Display device profile
+    profile.extend_from_slice(b"RGB ");
+    profile.extend_from_slice(b"XYZ ");
+    for date_part in [2024u16, 1, 1, 0, 0, 0] {
+        profile.extend_from_slice(&date_part.to_be_bytes());
+    }
+    profile.extend_from_slice(b"acsp");
+    profile.extend_from_slice(&[0; 24]); // Platform, flags, manufacturer, model, attributes
+    profile.extend_from_slice(&[0; 4]); // Perceptual rendering intent
+    for value in [0.9642, 1.0, 0.8249] {
+        profile.extend_from_slice(&s15_fixed16(value));
+    }
+    profile.extend_from_slice(&[0; 48]); // Creator, profile ID and reserved bytes
+    profile.extend_from_slice(&tag_table);
+    profile.extend_from_slice(&tag_data);
+    profile
+}
+
+/// This function writes an output intent (for PDF/X) with an ICC colour profile into the document
+/// catalog, replacing the output intents it had.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `icc_profile` - The colour profile the document is meant to be printed (or shown) with.
+///
+/// # Returns
+///
+/// * `Ok(())` if the output intent was written.
+/// * `Err(message)` if the profile file could not be read or is not an RGB, CMYK or grey ICC profile.
+///
+/// # Remarks
+///
+/// The output condition identifier is the description of the profile (or the file name of a
+/// profile without one).
+pub fn add_output_intent(doc: &mut Document, icc_profile: &IccProfile) -> Result<(), String> {
+    let (profile_data, profile_name, profile_source) = match icc_profile {
+        IccProfile::SRGB => (
+            srgb_icc_profile(),
+            SRGB_PROFILE_NAME.to_string(),
+            SRGB_PROFILE_NAME.to_string(),
+        ),
+        IccProfile::FromFile(path) => {
+            let profile_data = fs::read(path).map_err(|error| {
+                format!("could not read ICC profile {}: {}", path.display(), error)
+            })?;
+            let profile_name = profile_description(&profile_data).unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|file_stem| file_stem.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+            (profile_data, profile_name, path.display().to_string())
+        }
+    };
+
+    // The profile signature ("acsp") and colour space are in the 128 byte header
+    if profile_data.get(36..40) != Some(b"acsp".as_slice()) {
+        return Err(format!("{} is not an ICC profile", profile_source));
+    }
+    let (components, alternate) = match profile_data.get(16..20) {
+        Some(b"RGB ") => (3, "DeviceRGB"),
+        Some(b"CMYK") => (4, "DeviceCMYK"),
+        Some(b"GRAY") => (1, "DeviceGray"),
+        _ => {
+            return Err(format!(
+                "{} is not an RGB, CMYK or grey ICC profile",
+                profile_source
+            ))
+        }
+    };
+
+    let mut profile = Stream::new(
+        dictionary! { "N" => components, "Alternate" => alternate },
+        profile_data,
+    );
+    let _ = profile.compress();
+    let profile_id = doc.add_object(profile);
+    let intent_id = doc.add_object(dictionary! {
+        "Type" => "OutputIntent",
+        "S" => "GTS_PDFX",
+        "OutputConditionIdentifier" => LopdfObject::string_literal(profile_name.as_str()),
+        "RegistryName" => LopdfObject::string_literal("http://www.color.org"),
+        "Info" => LopdfObject::string_literal(profile_name.as_str()),
+        "DestOutputProfile" => profile_id,
+    });
+    doc.catalog_mut()
+        .map_err(|error| error.to_string())?
+        .set("OutputIntents", vec![LopdfObject::Reference(intent_id)]);
+    Ok(())
+}
+
+/// Returns the description (`desc` tag) of an ICC profile, from a `textDescriptionType` (version
+/// 2) or `multiLocalizedUnicodeType` (version 4) tag.
+fn profile_description(profile_data: &[u8]) -> Option<String> {
+    let read_u32 = |offset: usize| -> Option<usize> {
+        let bytes = profile_data.get(offset..offset + 4)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+    };
+
+    let tag_count = read_u32(128)?;
+    let (tag_offset, tag_size) = (0..tag_count).find_map(|index| {
+        let entry = 132 + 12 * index;
+        (profile_data.get(entry..entry + 4)? == b"desc")
+            .then(|| Some((read_u32(entry + 4)?, read_u32(entry + 8)?)))
+            .flatten()
+    })?;
+    let tag = profile_data.get(tag_offset..tag_offset.checked_add(tag_size)?)?;
+
+    let description = match tag.get(0..4)? {
+        b"desc" => {
+            let length = u32::from_be_bytes(tag.get(8..12)?.try_into().ok()?) as usize;
+            String::from_utf8_lossy(tag.get(12..12 + length)?).to_string()
+        }
+        b"mluc" => {
+            // The first record (language) is used
+            let length = u32::from_be_bytes(tag.get(20..24)?.try_into().ok()?) as usize;
+            let offset = u32::from_be_bytes(tag.get(24..28)?.try_into().ok()?) as usize;
+            let utf16: Vec<u16> = tag
+                .get(offset..offset + length)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&utf16)
+        }
+        _ => return None,
+    };
+    let description = description.trim_end_matches('\0').trim().to_string();
+    (!description.is_empty()).then_some(description)
+}
+
+/// Returns a `textDescriptionType` tag (ASCII only).
+fn description_tag(description: &str) -> Vec<u8> {
+    let mut tag = b"desc\0\0\0\0".to_vec();
+    tag.extend_from_slice(&(description.len() as u32 + 1).to_be_bytes());
+    tag.extend_from_slice(description.as_bytes());
+    tag.push(0);
+    // No Unicode or ScriptCode description
+    tag.extend_from_slice(&[0; 4 + 4 + 2 + 1 + 67]);
+    tag
+}
+
+/// Returns a `textType` tag.
+fn text_tag(text: &str) -> Vec<u8> {
+    let mut tag = b"text\0\0\0\0".to_vec();
+    tag.extend_from_slice(text.as_bytes());
+    tag.push(0);
+    tag
+}
+
+/// Returns an `XYZType` tag with one XYZ value.
+fn xyz_tag(xyz: [f64; 3]) -> Vec<u8> {
+    let mut tag = b"XYZ \0\0\0\0".to_vec();
+    for value in xyz {
+        tag.extend_from_slice(&s15_fixed16(value));
+    }
+    tag
+}
+
+/// Returns a `curveType` tag with the sRGB transfer function.
+fn curve_tag() -> Vec<u8> {
+    let mut tag = b"curv\0\0\0\0".to_vec();
+    tag.extend_from_slice(&(TRC_ENTRIES as u32).to_be_bytes());
+    for index in 0..TRC_ENTRIES {
+        let encoded = index as f64 / (TRC_ENTRIES - 1) as f64;
+        let linear = if encoded <= 0.04045 {
+            encoded / 12.92
+        } else {
+            ((encoded + 0.055) / 1.055).powf(2.4)
+        };
+        tag.extend_from_slice(&((linear * 65535.0).round() as u16).to_be_bytes());
+    }
+    tag
+}
+
+/// Encodes a number as an ICC `s15Fixed16Number`.
+fn s15_fixed16(value: f64) -> [u8; 4] {
+    ((value * 65536.0).round() as i32).to_be_bytes()
+}
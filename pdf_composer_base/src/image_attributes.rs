@@ -0,0 +1,66 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use regex::{Captures, Regex};
+
+use pdf_composer_definitions::consts::CROSS_MARK;
+
+/// This function applies `{width=60%}` style attribute blocks written after images to the images.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+///
+/// # Returns
+///
+/// A `String` with the image sizes turned into inline CSS.
+///
+/// # Remarks
+///
+/// An attribute block holds space separated `width=...` and `height=...` entries, for example
+/// `![Diagram](diagram.png){width=60% height=4cm}`. Sizes can be given in `%`, `px`, `cm`, `mm`, `in`,
+/// `pt`, `em` or `rem` (a number without a unit is in pixels), optionally in quotes. Setting only one of
+/// the two keeps the aspect ratio of the image.
+///
+/// A `#label` in the block (`{#fig:diagram width=60%}`) is kept for the cross-references.
+/// Any other entries are ignored, with a warning.
+pub fn apply_image_attributes(html: &str) -> String {
+    let image_regex = Regex::new(r"<img ([^>]*?)\s*/?>\{([^{}<>]*)\}").unwrap();
+    let size_regex = Regex::new(
+        r"^(width|height)=(?:&quot;)?(\d+(?:\.\d+)?)(%|px|cm|mm|in|pt|em|rem)?(?:&quot;)?$",
+    )
+    .unwrap();
+
+    image_regex
+        .replace_all(html, |captures: &Captures| {
+            let mut styles: Vec<String> = Vec::new();
+            let mut label = String::new();
+            for entry in captures[2].split_whitespace() {
+                if let Some(size) = size_regex.captures(entry) {
+                    let unit = size.get(3).map_or("px", |unit| unit.as_str());
+                    styles.push(format!("{}: {}{}", &size[1], &size[2], unit));
+                } else if entry.starts_with('#') && label.is_empty() {
+                    label = format!("{{{}}}", entry);
+                } else {
+                    eprintln!(
+                        "{}{}",
+                        CROSS_MARK.yellow(),
+                        format!("Image attribute {} is not supported", entry).yellow()
+                    );
+                }
+            }
+
+            if styles.is_empty() {
+                format!("<img {} />{}", &captures[1], label)
+            } else {
+                format!(
+                    "<img {} style=\"{}\" />{}",
+                    &captures[1],
+                    styles.join("; "),
+                    label
+                )
+            }
+        })
+        .to_string()
+}
@@ -0,0 +1,397 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId, Stream};
+use std::collections::BTreeMap;
+
+use crate::jpeg::{decode_jpeg, encode_jpeg};
+use crate::raster::RasterImage;
+use pdf_composer_definitions::image_policy::ImagePolicy;
+
+/// The JPEG quality downsampled JPEG images are written with.
+const DOWNSAMPLED_JPEG_QUALITY: u8 = 85;
+
+/// The size (in bytes) from which images are recompressed at the JPEG quality of the policy, as
+/// recompressing small images saves little.
+const RECOMPRESS_MIN_BYTES: usize = 32 * 1024;
+
+/// How deep form XObjects (drawings inside drawings) are followed to find the images they show.
+const MAX_FORM_DEPTH: usize = 8;
+
+/// A transformation matrix `[a b c d e f]`, as in the `cm` operator.
+type Matrix = [f32; 6];
+
+/// The identity matrix.
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// This function applies an image policy to the raster images of a document: images shown at a
+/// higher resolution than `max_dpi` are resampled down to it, and large images are recompressed
+/// at `jpeg_quality`.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `image_policy` - The `ImagePolicy` to apply.
+///
+/// # Returns
+///
+/// The number of images that were resampled or recompressed.
+///
+/// # Remarks
+///
+/// The resolution of an image is worked out from the largest size it is shown at on any page
+/// (from the page content), so an image shown twice keeps enough pixels for its larger use.
+/// Uncompressed and `FlateDecode` images with 8 bit samples are resampled and compressed again
+/// (or converted to JPEG with `convert_lossless`, except soft masks), and grey and colour
+/// `DCTDecode` (JPEG) images are resampled and written as (baseline) JPEG again. Other images (such
+/// as CMYK JPEG images, image masks and images with a palette) are left as they are. An image that is only
+/// recompressed is kept as it was unless it gets smaller.
+pub fn apply_image_policy(doc: &mut Document, image_policy: &ImagePolicy) -> usize {
+    let downsamples = image_policy.downsample && image_policy.max_dpi > 0;
+    if !downsamples && image_policy.jpeg_quality.is_none() {
+        return 0;
+    }
+
+    let mut sizes = image_display_sizes(doc);
+    // Soft masks are shown at the size of their image
+    let soft_masks: BTreeMap<ObjectId, (f32, f32)> = sizes
+        .iter()
+        .filter_map(|(image_id, size)| {
+            let image = doc.get_object(*image_id).ok()?.as_stream().ok()?;
+            let mask_id = image.dict.get(b"SMask").ok()?.as_reference().ok()?;
+            Some((mask_id, *size))
+        })
+        .collect();
+    for (mask_id, size) in &soft_masks {
+        record_size(&mut sizes, *mask_id, *size);
+    }
+
+    let mut processed = 0;
+    for (image_id, (shown_width, shown_height)) in sizes {
+        // Size in inches times the resolution, rounded up so an image is never made too small
+        let target_size = downsamples.then(|| {
+            let max_dpi = image_policy.max_dpi as f32;
+            (
+                (shown_width / 72.0 * max_dpi).ceil() as usize,
+                (shown_height / 72.0 * max_dpi).ceil() as usize,
+            )
+        });
+        let convert_lossless = image_policy.convert_lossless && !soft_masks.contains_key(&image_id);
+        let Ok(LopdfObject::Stream(image)) = doc.get_object(image_id) else {
+            continue;
+        };
+        if let Some(new_image) = process_image(
+            doc,
+            image,
+            target_size,
+            image_policy.jpeg_quality,
+            convert_lossless,
+        ) {
+            if let Ok(LopdfObject::Stream(image)) = doc.get_object_mut(image_id) {
+                *image = new_image;
+                processed += 1;
+            }
+        }
+    }
+    processed
+}
+
+/// Returns the largest size (width and height in points) each image XObject is shown at, on any
+/// page.
+fn image_display_sizes(doc: &Document) -> BTreeMap<ObjectId, (f32, f32)> {
+    let mut sizes: BTreeMap<ObjectId, (f32, f32)> = BTreeMap::new();
+    for page_id in doc.get_pages().into_values() {
+        let Ok(content) = doc.get_page_content(page_id) else {
+            continue;
+        };
+        let (resources, resource_ids) = doc.get_page_resources(page_id);
+        let mut xobjects: BTreeMap<Vec<u8>, ObjectId> = BTreeMap::new();
+        // Resources set on the page win over inherited ones
+        for resource_id in resource_ids.iter().rev() {
+            if let Ok(resources) = doc.get_dictionary(*resource_id) {
+                xobjects.extend(xobject_ids(doc, resources));
+            }
+        }
+        if let Some(resources) = resources {
+            xobjects.extend(xobject_ids(doc, resources));
+        }
+        record_image_sizes(doc, &content, &xobjects, IDENTITY, 0, &mut sizes);
+    }
+
+    sizes
+}
+
+/// Returns the XObject names and ids of a resource dictionary.
+fn xobject_ids(doc: &Document, resources: &Dictionary) -> BTreeMap<Vec<u8>, ObjectId> {
+    resources
+        .get(b"XObject")
+        .ok()
+        .and_then(|xobjects| doc.dereference(xobjects).ok())
+        .and_then(|(_, xobjects)| xobjects.as_dict().ok())
+        .map(|xobjects| {
+            xobjects
+                .iter()
+                .filter_map(|(name, xobject)| Some((name.clone(), xobject.as_reference().ok()?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Follows the graphics state of a content stream (`q`, `Q` and `cm`), recording the size each
+/// image is drawn at (`Do`), and following form XObjects.
+fn record_image_sizes(
+    doc: &Document,
+    content: &[u8],
+    xobjects: &BTreeMap<Vec<u8>, ObjectId>,
+    matrix: Matrix,
+    depth: usize,
+    sizes: &mut BTreeMap<ObjectId, (f32, f32)>,
+) {
+    let Ok(content) = Content::decode(content) else {
+        return;
+    };
+    let mut current = matrix;
+    let mut saved: Vec<Matrix> = Vec::new();
+    for operation in content.operations {
+        match operation.operator.as_str() {
+            "q" => saved.push(current),
+            "Q" => current = saved.pop().unwrap_or(matrix),
+            "cm" => {
+                let values: Vec<f32> = operation
+                    .operands
+                    .iter()
+                    .filter_map(|operand| operand.as_float().ok())
+                    .collect();
+                if let Ok(operand_matrix) = Matrix::try_from(values.as_slice()) {
+                    current = multiply(&operand_matrix, &current);
+                }
+            }
+            "Do" => {
+                let Some(xobject_id) = operation
+                    .operands
+                    .first()
+                    .and_then(|name| name.as_name().ok())
+                    .and_then(|name| xobjects.get(name))
+                else {
+                    continue;
+                };
+                let Ok(xobject) = doc.get_object(*xobject_id).and_then(LopdfObject::as_stream)
+                else {
+                    continue;
+                };
+                match xobject
+                    .dict
+                    .get(b"Subtype")
+                    .and_then(LopdfObject::as_name_str)
+                {
+                    Ok("Image") => {
+                        // The image fills the unit square, so the matrix gives its size
+                        let width = current[0].hypot(current[1]);
+                        let height = current[2].hypot(current[3]);
+                        record_size(sizes, *xobject_id, (width, height));
+                    }
+                    Ok("Form") if depth < MAX_FORM_DEPTH => {
+                        let form_matrix = xobject
+                            .dict
+                            .get(b"Matrix")
+                            .and_then(LopdfObject::as_array)
+                            .ok()
+                            .map(|values| {
+                                values
+                                    .iter()
+                                    .filter_map(|value| value.as_float().ok())
+                                    .collect::<Vec<f32>>()
+                            })
+                            .and_then(|values| Matrix::try_from(values.as_slice()).ok())
+                            .unwrap_or(IDENTITY);
+                        let form_xobjects = xobject
+                            .dict
+                            .get(b"Resources")
+                            .ok()
+                            .and_then(|resources| doc.dereference(resources).ok())
+                            .and_then(|(_, resources)| resources.as_dict().ok())
+                            .map(|resources| xobject_ids(doc, resources))
+                            .unwrap_or_else(|| xobjects.clone());
+                        let form_content = if xobject.dict.has(b"Filter") {
+                            xobject.decompressed_content().unwrap_or_default()
+                        } else {
+                            xobject.content.clone()
+                        };
+                        record_image_sizes(
+                            doc,
+                            &form_content,
+                            &form_xobjects,
+                            multiply(&form_matrix, &current),
+                            depth + 1,
+                            sizes,
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Records the size an image is shown at, keeping the largest width and height.
+fn record_size(
+    sizes: &mut BTreeMap<ObjectId, (f32, f32)>,
+    image_id: ObjectId,
+    (width, height): (f32, f32),
+) {
+    let size = sizes.entry(image_id).or_insert((0.0, 0.0));
+    size.0 = size.0.max(width);
+    size.1 = size.1.max(height);
+}
+
+/// Multiplies two transformation matrices (`first` applied before `second`).
+fn multiply(first: &Matrix, second: &Matrix) -> Matrix {
+    [
+        first[0] * second[0] + first[1] * second[2],
+        first[0] * second[1] + first[1] * second[3],
+        first[2] * second[0] + first[3] * second[2],
+        first[2] * second[1] + first[3] * second[3],
+        first[4] * second[0] + first[5] * second[2] + second[4],
+        first[4] * second[1] + first[5] * second[3] + second[5],
+    ]
+}
+
+/// Returns the image resampled to (about) a target size and/or recompressed as JPEG, or `None` if
+/// there is nothing to do or it cannot be done.
+fn process_image(
+    doc: &Document,
+    image: &Stream,
+    target_size: Option<(usize, usize)>,
+    jpeg_quality: Option<u8>,
+    convert_lossless: bool,
+) -> Option<Stream> {
+    let dict = &image.dict;
+    let width = dict.get(b"Width").and_then(LopdfObject::as_i64).ok()? as usize;
+    let height = dict.get(b"Height").and_then(LopdfObject::as_i64).ok()? as usize;
+    let is_mask = dict
+        .get(b"ImageMask")
+        .and_then(LopdfObject::as_bool)
+        .unwrap_or(false);
+    let bits_per_component = dict.get(b"BitsPerComponent").and_then(LopdfObject::as_i64);
+    if is_mask || bits_per_component.ok()? != 8 || dict.has(b"Decode") {
+        return None;
+    }
+
+    let filters = if dict.has(b"Filter") {
+        image.filters().ok()?
+    } else {
+        Vec::new()
+    };
+    let is_jpeg = match filters.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] | ["FlateDecode"] | ["LZWDecode"] => false,
+        ["DCTDecode"] => true,
+        _ => return None,
+    };
+
+    // The same scale both ways, so the larger of the two targets is met
+    let new_size = target_size
+        .filter(|(target_width, target_height)| {
+            width > *target_width
+                && height > *target_height
+                && *target_width > 0
+                && *target_height > 0
+        })
+        .map(|(target_width, target_height)| {
+            let scale =
+                (target_width as f32 / width as f32).max(target_height as f32 / height as f32);
+            (
+                ((width as f32 * scale).round() as usize).clamp(1, width),
+                ((height as f32 * scale).round() as usize).clamp(1, height),
+            )
+        });
+    let recompress_quality = jpeg_quality
+        .filter(|_| image.content.len() >= RECOMPRESS_MIN_BYTES && (is_jpeg || convert_lossless));
+    if new_size.is_none() && recompress_quality.is_none() {
+        return None;
+    }
+
+    let raster = if is_jpeg {
+        decode_jpeg(&image.content).ok()?
+    } else {
+        let components = colour_components(doc, dict)?;
+        let samples = if filters.is_empty() {
+            image.content.clone()
+        } else {
+            // lopdf only decompresses streams that are not images
+            let mut unfiltered = image.clone();
+            unfiltered.dict.remove(b"Subtype");
+            unfiltered.decompressed_content().ok()?
+        };
+        if samples.len() < width * height * components {
+            return None;
+        }
+        RasterImage {
+            width,
+            height,
+            components,
+            samples: samples[..width * height * components].to_vec(),
+        }
+    };
+    if raster.width != width || raster.height != height {
+        return None;
+    }
+    let raster = match new_size {
+        Some((new_width, new_height)) => raster.resize(new_width, new_height),
+        None => raster,
+    };
+
+    let mut new_image = image.clone();
+    new_image.dict.set("Width", raster.width as i64);
+    new_image.dict.set("Height", raster.height as i64);
+    // JPEG images stay JPEG, and CMYK images are not written as JPEG
+    if is_jpeg || (recompress_quality.is_some() && raster.components != 4) {
+        let quality = jpeg_quality.unwrap_or(DOWNSAMPLED_JPEG_QUALITY);
+        let jpeg = encode_jpeg(&raster, quality).ok()?;
+        // Written as YCbCr (or grey), whatever colour transform the original had
+        new_image.dict.set("Filter", "DCTDecode");
+        new_image.dict.remove(b"DecodeParms");
+        new_image.set_content(jpeg);
+    } else {
+        new_image.set_plain_content(raster.samples);
+        new_image.compress().ok()?;
+    }
+
+    if new_size.is_none() && new_image.content.len() >= image.content.len() {
+        return None;
+    }
+    Some(new_image)
+}
+
+/// Returns the number of colour components of an image from its colour space (`None` for colour
+/// spaces whose samples are not colour values, such as palettes).
+fn colour_components(doc: &Document, dict: &Dictionary) -> Option<usize> {
+    let (_, colour_space) = doc.dereference(dict.get(b"ColorSpace").ok()?).ok()?;
+    if let Ok(name) = colour_space.as_name_str() {
+        return match name {
+            "DeviceGray" | "CalGray" => Some(1),
+            "DeviceRGB" | "CalRGB" | "Lab" => Some(3),
+            "DeviceCMYK" => Some(4),
+            _ => None,
+        };
+    }
+    let colour_space = colour_space.as_array().ok()?;
+    match colour_space.first()?.as_name_str().ok()? {
+        "CalGray" => Some(1),
+        "CalRGB" | "Lab" => Some(3),
+        "ICCBased" => {
+            let (_, profile) = doc.dereference(colour_space.get(1)?).ok()?;
+            let components = profile
+                .as_stream()
+                .ok()?
+                .dict
+                .get(b"N")
+                .ok()?
+                .as_i64()
+                .ok()?;
+            Some(components as usize)
+        }
+        _ => None,
+    }
+}
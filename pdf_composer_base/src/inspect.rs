@@ -0,0 +1,94 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Document, Object as LopdfObject, ObjectId};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::destinations::named_destinations;
+use crate::utils::decode_pdf_text_string;
+use pdf_composer_definitions::document_metadata::DocumentMetadata;
+
+/// Reads the metadata of an existing PDF document: its version, page count, document information
+/// dictionary, language and named destinations.
+///
+/// This makes it possible to check what PDF Composer wrote without a second PDF library.
+///
+/// # Arguments
+///
+/// * `path` - The path to the PDF document to read.
+///
+/// # Returns
+///
+/// * `Ok(DocumentMetadata)` with the metadata of the document.
+/// * `Err(e)` if the file could not be read or is not a valid PDF document.
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_composer::read_pdf_metadata;
+///
+/// let metadata = read_pdf_metadata("pdf_composer_pdfs/sample_file_01.pdf").unwrap();
+///
+/// println!("PDF {} with {} pages", metadata.version, metadata.page_count);
+/// assert_eq!(metadata.info.get("Producer").map(String::as_str), Some("PDF Composer"));
+/// ```
+pub fn read_pdf_metadata<P: AsRef<Path>>(
+    path: P,
+) -> Result<DocumentMetadata, Box<dyn std::error::Error>> {
+    let doc = Document::load(path)?;
+
+    let mut info: BTreeMap<String, String> = BTreeMap::new();
+    if let Ok(info_object) = doc.trailer.get(b"Info") {
+        if let Ok((_id, LopdfObject::Dictionary(dictionary))) = doc.dereference(info_object) {
+            for (key, value) in dictionary.iter() {
+                if let Some(text) = object_to_text(&doc, value) {
+                    info.insert(String::from_utf8_lossy(key).to_string(), text);
+                }
+            }
+        }
+    }
+
+    let language = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Lang").ok())
+        .and_then(|lang| object_to_text(&doc, lang));
+
+    let page_numbers: BTreeMap<ObjectId, u32> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(page_number, page_id)| (page_id, page_number))
+        .collect();
+    let named_destinations = named_destinations(&doc)
+        .into_iter()
+        .filter_map(|(name, destination)| {
+            let page_id = destination.as_array().ok()?.first()?.as_reference().ok()?;
+            Some((
+                String::from_utf8_lossy(&name).to_string(),
+                *page_numbers.get(&page_id)?,
+            ))
+        })
+        .collect();
+
+    Ok(DocumentMetadata {
+        version: doc.version.to_string(),
+        page_count: page_numbers.len(),
+        info,
+        language,
+        named_destinations,
+    })
+}
+
+/// Converts a document information value to text. Returns `None` for values that have no
+/// sensible text form (dictionaries, streams etc).
+fn object_to_text(doc: &Document, object: &LopdfObject) -> Option<String> {
+    match doc.dereference(object).ok()?.1 {
+        LopdfObject::String(bytes, _) => Some(decode_pdf_text_string(bytes)),
+        LopdfObject::Name(name) => Some(String::from_utf8_lossy(name).to_string()),
+        LopdfObject::Boolean(value) => Some(value.to_string()),
+        LopdfObject::Integer(value) => Some(value.to_string()),
+        LopdfObject::Real(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
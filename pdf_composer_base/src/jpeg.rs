@@ -0,0 +1,198 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use jpeg_decoder::{Decoder, PixelFormat};
+use jpeg_encoder::{ColorType, Encoder, SamplingFactor};
+
+use crate::raster::RasterImage;
+
+/// The most memory (in bytes) a JPEG image may decode to, so a small file declaring a huge image
+/// cannot exhaust the memory (256 MB, a 9000 x 9000 RGB photo).
+const MAX_DECODED_BYTES: usize = 256 * 1024 * 1024;
+
+/// This function decodes a JPEG image (baseline or progressive), such as the content of a
+/// `DCTDecode` image stream, with the `jpeg-decoder` crate.
+///
+/// # Arguments
+///
+/// * `data` - The JPEG data.
+///
+/// # Returns
+///
+/// * `Ok(RasterImage)` with grey (1 component) or RGB (3 components) pixels.
+/// * `Err(message)` if the data is not a valid JPEG, is larger than 256 MB decoded, or is not an 8
+///   bit grey or colour image (CMYK and 16 bit JPEG images are not decoded).
+pub fn decode_jpeg(data: &[u8]) -> Result<RasterImage, String> {
+    let mut decoder = Decoder::new(data);
+    decoder.read_info().map_err(|error| error.to_string())?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| "the JPEG image has no frame header".to_string())?;
+    let components = match info.pixel_format {
+        PixelFormat::L8 => 1,
+        PixelFormat::RGB24 => 3,
+        pixel_format => {
+            return Err(format!(
+                "{:?} JPEG images are not decoded, only grey and RGB ones",
+                pixel_format
+            ))
+        }
+    };
+    let (width, height) = (usize::from(info.width), usize::from(info.height));
+    // Checked before decoding, as `jpeg-decoder` only checks its own limit once the whole image
+    // has been decoded
+    if width * height * components > MAX_DECODED_BYTES {
+        return Err(format!(
+            "the {} x {} JPEG image is larger than the {} MB it may decode to",
+            width,
+            height,
+            MAX_DECODED_BYTES / (1024 * 1024)
+        ));
+    }
+    let samples = decoder.decode().map_err(|error| error.to_string())?;
+    if samples.len() != width * height * components {
+        return Err("the JPEG image has fewer samples than its size".to_string());
+    }
+
+    Ok(RasterImage {
+        width,
+        height,
+        components,
+        samples,
+    })
+}
+
+/// This function encodes an image as a baseline JPEG with the `jpeg-encoder` crate, at a quality
+/// on the same scale as most image editors use.
+///
+/// # Arguments
+///
+/// * `image` - A grey (1 component) or RGB (3 components) image.
+/// * `quality` - The quality from 1 (smallest) to 100 (best).
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` with the JPEG data. Colour images are written as YCbCr with the colour halved in
+///   both directions (4:2:0), which suits photos.
+/// * `Err(message)` if the image does not have 1 or 3 components, its samples do not match its
+///   size, or it is wider or taller than the 65535 pixels a JPEG can be.
+pub fn encode_jpeg(image: &RasterImage, quality: u8) -> Result<Vec<u8>, String> {
+    let colour_type = match image.components {
+        1 => ColorType::Luma,
+        3 => ColorType::Rgb,
+        components => {
+            return Err(format!(
+                "images with {} components are not encoded as JPEG",
+                components
+            ))
+        }
+    };
+    if image.samples.len() != image.width * image.height * image.components {
+        return Err("the image samples do not match its size".to_string());
+    }
+    let width = u16::try_from(image.width).map_err(|_| "the image is too wide for a JPEG")?;
+    let height = u16::try_from(image.height).map_err(|_| "the image is too tall for a JPEG")?;
+
+    let mut jpeg = Vec::new();
+    let mut encoder = Encoder::new(&mut jpeg, quality.clamp(1, 100));
+    encoder.set_sampling_factor(SamplingFactor::R_4_2_0);
+    encoder
+        .encode(&image.samples, width, height, colour_type)
+        .map_err(|error| error.to_string())?;
+    Ok(jpeg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a gradient image, which survives JPEG compression with little change.
+    fn gradient(width: usize, height: usize, components: usize) -> RasterImage {
+        let samples = (0..width * height * components)
+            .map(|index| {
+                let pixel = index / components;
+                ((pixel % width + pixel / width) * 255 / (width + height - 2)) as u8
+            })
+            .collect();
+        RasterImage {
+            width,
+            height,
+            components,
+            samples,
+        }
+    }
+
+    /// Returns the mean absolute difference of the samples of two images of the same size.
+    fn mean_difference(first: &RasterImage, second: &RasterImage) -> f64 {
+        let total: u64 = first
+            .samples
+            .iter()
+            .zip(&second.samples)
+            .map(|(first, second)| u64::from(first.abs_diff(*second)))
+            .sum();
+        total as f64 / first.samples.len() as f64
+    }
+
+    /// Returns the position of a marker (`0xFF` followed by `marker`) in JPEG data.
+    fn find_marker(jpeg: &[u8], marker: u8) -> Option<usize> {
+        jpeg.windows(2).position(|window| window == [0xFF, marker])
+    }
+
+    #[test]
+    fn round_trips_grey_and_rgb_images() {
+        for components in [1, 3] {
+            let image = gradient(48, 32, components);
+            let jpeg = encode_jpeg(&image, 90).unwrap();
+            assert!(jpeg.starts_with(&[0xFF, 0xD8]));
+
+            let decoded = decode_jpeg(&jpeg).unwrap();
+            assert_eq!(
+                (decoded.width, decoded.height, decoded.components),
+                (48, 32, components)
+            );
+            assert!(mean_difference(&image, &decoded) < 4.0);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_jpeg_data() {
+        let jpeg = encode_jpeg(&gradient(48, 32, 3), 90).unwrap();
+        assert!(decode_jpeg(&jpeg[..jpeg.len() / 2]).is_err());
+        assert!(decode_jpeg(&jpeg[..20]).is_err());
+        assert!(decode_jpeg(&jpeg[..2]).is_err());
+        assert!(decode_jpeg(&[]).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_jpeg_data() {
+        assert!(decode_jpeg(b"%PDF-1.7 not a JPEG").is_err());
+
+        // A frame header declaring a 65535 x 65535 image, far more than the decoded size allowed
+        let mut jpeg = encode_jpeg(&gradient(16, 16, 1), 90).unwrap();
+        let frame_header = find_marker(&jpeg, 0xC0).unwrap();
+        jpeg[frame_header + 5..frame_header + 9].copy_from_slice(&[0xFF; 4]);
+        assert!(decode_jpeg(&jpeg).is_err());
+
+        // A frame header with no lines
+        jpeg[frame_header + 5..frame_header + 7].copy_from_slice(&[0x00; 2]);
+        assert!(decode_jpeg(&jpeg).is_err());
+    }
+
+    #[test]
+    fn rejects_images_it_cannot_encode() {
+        let mut image = gradient(8, 8, 3);
+        image.samples.pop();
+        assert!(encode_jpeg(&image, 90).is_err());
+
+        let cmyk = gradient(8, 8, 4);
+        assert!(encode_jpeg(&cmyk, 90).is_err());
+
+        let too_wide = RasterImage {
+            width: 70_000,
+            height: 1,
+            components: 1,
+            samples: vec![0; 70_000],
+        };
+        assert!(encode_jpeg(&too_wide, 90).is_err());
+    }
+}
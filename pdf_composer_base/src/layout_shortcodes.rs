@@ -0,0 +1,95 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use crate::shortcodes::{ShortcodeArguments, ShortcodeOutput};
+
+/// The number of columns of a `{{< columns >}}` shortcode when no count is given.
+const DEFAULT_COLUMN_COUNT: u32 = 2;
+/// The most columns a `{{< columns >}}` shortcode can have.
+const MAX_COLUMN_COUNT: u32 = 6;
+
+/// The styles for the layout shortcodes (page breaks and columns), added to the page CSS.
+pub const LAYOUT_SHORTCODE_CSS: &str = "
+.page-break { break-after: page; }
+.columns { column-gap: 2em; margin: 1em 0; }
+.columns > :first-child { margin-top: 0; }
+.column-break { break-after: column; }
+";
+
+/// This function renders the `{{< pagebreak >}}` shortcode, starting a new page.
+///
+/// # Returns
+///
+/// The `ShortcodeOutput` with an empty block that the page breaks after.
+pub fn page_break_shortcode() -> ShortcodeOutput {
+    ShortcodeOutput {
+        html: "<div class=\"page-break\"></div>".to_string(),
+        block: true,
+    }
+}
+
+/// This function renders the `{{< columnbreak >}}` shortcode, starting a new column in a
+/// `{{< columns >}}` block.
+///
+/// # Returns
+///
+/// The `ShortcodeOutput` with an empty block that the column breaks after.
+pub fn column_break_shortcode() -> ShortcodeOutput {
+    ShortcodeOutput {
+        html: "<div class=\"column-break\"></div>".to_string(),
+        block: true,
+    }
+}
+
+/// This function renders the opening and closing tags of the `{{< columns 3 >}}...{{< /columns >}}`
+/// shortcode, which sets the markdown between them in columns.
+///
+/// # Arguments
+///
+/// * `arguments` - The shortcode arguments: the optional number of columns (`2` by default, up to
+///   `6`), given as the first argument or as `count`.
+///
+/// # Returns
+///
+/// * `Ok((ShortcodeOutput, ShortcodeOutput))` with the opening and closing of the columns block.
+/// * `Err(message)` if the number of columns is not a number from 1 to 6.
+///
+/// # Remarks
+///
+/// The text flows from one column to the next, balanced across them; `{{< columnbreak >}}` starts
+/// the next column early.
+pub fn columns_shortcode(
+    arguments: &ShortcodeArguments,
+) -> Result<(ShortcodeOutput, ShortcodeOutput), String> {
+    let column_count = match arguments
+        .positional
+        .first()
+        .or_else(|| arguments.named.get("count"))
+    {
+        Some(count) => count
+            .parse::<u32>()
+            .ok()
+            .filter(|count| (1..=MAX_COLUMN_COUNT).contains(count))
+            .ok_or_else(|| {
+                format!(
+                    "column count {} is not a number from 1 to {}",
+                    count, MAX_COLUMN_COUNT
+                )
+            })?,
+        None => DEFAULT_COLUMN_COUNT,
+    };
+
+    Ok((
+        ShortcodeOutput {
+            html: format!(
+                "<div class=\"columns\" style=\"column-count: {}\">",
+                column_count
+            ),
+            block: true,
+        },
+        ShortcodeOutput {
+            html: "</div>".to_string(),
+            block: true,
+        },
+    ))
+}
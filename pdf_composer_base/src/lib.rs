@@ -11,32 +11,315 @@
 //! * Setting page margins
 //! * Setting page metadata (PDF fields)
 //! * Setting output directory
+//! * Source directories, with configurable source file extensions (`set_source_extensions`)
+//! * Naming the generated documents with a function of the source file path (`set_output_namer`)
+//! * Validating source documents before generation
+//! * Checking the environment (doctor) before generation
+//! * Reading back the metadata of generated PDFs
+//! * Merging PDFs into a single document
+//! * Extracting selected pages from PDFs
+//! * Comparing two PDFs, structure and rendered pages, for regression testing (`compare_pdfs`)
+//! * Running headers with the current section title
+//! * Page numbering (including Roman numerals for front matter pages)
+//! * Page labels shown by PDF viewers, such as Cover, i–iv, 1–120 (`set_page_labels`)
+//! * Endnotes (footnotes collected at the end of each document)
+//! * Citations and a bibliography in a chosen CSL style (`citations` feature)
+//! * Git revision placeholders such as `{{git_sha}}` and `{{git_last_modified}}` (`git` feature)
+//! * Cross-references to headings, figures and tables
+//! * Image sizing with `{width=60%}` attribute blocks
+//! * Callout (admonition) blocks
+//! * Front matter defaults (`set_front_matter_defaults`), and `_pdf_composer.yaml` settings for the documents of a directory
+//! * Glossaries defined in the front matter, linking the first occurrence of each term to its definition
+//! * Line numbers and highlighted lines in code blocks (`set_code_line_numbers`)
+//! * Long lines in code blocks wrapped, shrunk to fit or truncated with a marker (`set_code_wrap`)
+//! * Wiki links (`[[Other Page]]`) with a configurable resolver
+//! * Links between source documents rewritten to the generated PDFs
+//! * Working internal (`#heading`) links and named destinations
+//! * QR codes (`{{qr "https://example.com"}}` shortcode and page footer)
+//! * Watermarks such as `DRAFT` across every page (`set_watermark`)
+//! * Code 128 and EAN-13 barcodes (`{{barcode code128 "INV-2024-0031"}}` shortcode)
+//! * Bar, line and pie charts of front matter data (`{{chart bar sales}}` shortcode)
+//! * Tables read from CSV files (`{{table "data/results.csv"}}` shortcode)
+//! * Fillable form fields: text, checkboxes and signature placeholders (`{{field text "customer_name"}}` shortcode)
+//! * Mail merge: one PDF per data record from a single template
+//! * Manifest (YAML or TOML) driven batch builds with per-document settings and covers
+//! * Conditional content kept or removed by the profile and flags (`{{#if internal}}`, `add_flag`)
+//! * Configuration files (`pdf_composer.toml`, `load_config`) with profiles such as draft and final (`set_profile`), a JSON Schema for editors (`config_schema`) and validation (`validate_config`), and stylesheets (`set_stylesheet`)
+//! * Documents given as text, such as piped to standard input (`generate_from_markdown`)
+//! * Quiet, normal or verbose console output, for PDFs written to stdout or for timings (`set_console_output`, `set_quiet`)
+//! * No colour escapes in the console output when it is not a terminal, or with `NO_COLOR`
+//! * Preview server with live reload (`preview` feature)
+//! * Self-contained HTML output (`OutputFormat::Html`), without Chromium
+//! * EPUB 3 output (`OutputFormat::Epub`, `epub` feature)
+//! * Plain text and normalized markdown output (`OutputFormat::Text`)
+//! * Several output formats from one rendering of each document (`add_output`)
+//! * PDF/X-4 conformance for print production (`set_conformance`)
+//! * Output intents with ICC colour profiles (`set_output_intent`)
+//! * Downsampling of embedded images above a target DPI (`set_image_policy`)
+//! * JPEG recompression of large embedded images at a given quality (`ImagePolicy::jpeg_quality`)
+//! * Smaller merged PDFs: unused objects dropped and identical streams shared
+//! * Deterministic output, identical inputs giving identical PDFs (`set_deterministic`)
+//! * Chunked rendering of very large documents, section by section (`set_chunked_rendering`)
+//! * Generation on a thread pool of its own or the application's (`set_thread_count`, `set_thread_pool`)
+//! * Strictly sequential processing, one document at a time (`set_processing_mode`)
+//! * Failed documents not stopping the others, with a summary of the failures at the end, or failing fast (`set_fail_fast`)
+//! * NDJSON progress events (start, finish, skip or error of each file) for wrapper tools and CI (`set_progress_writer`)
+//! * Deterministic source ordering: as added, alphabetical or by a front matter key (`set_source_ordering`)
+//! * Cache of the rendered HTML between runs, so only changed documents are rendered again (`set_html_cache`)
+//! * A warm headless Chromium kept alive between `generate_pdfs` calls, with idle shutdown (`with_persistent_browser`)
+//! * Relative links and images resolved against an asset base directory or URL (`set_asset_base`)
+//! * Per-document output directories from the front matter (`output_dir`)
+//! * Waiting for, or saving next to, PDFs locked by a PDF viewer (`set_locked_file_policy`)
+//! * A post-process command, such as an optimizer or validator, run on every generated PDF (`set_post_process_command`)
+//! * Publishing the generated files with an uploader of your own, or to Amazon S3 (`set_uploader`, `s3` feature)
+//! * Opt-in expansion of allowed `${ENV_VAR}` references in front matter and markdown (`set_env_var_allow_list`)
+//! * Date placeholders, `{{today}}`, `{{now}}` and `{{build_date "%e %B %Y"}}`, with time zone and language (`set_date_options`)
+//! * Locale-aware dates and numbers, `{{date key}}` and `{{number key 2}}`, such as `01.05.2024` and `1.234,50` for `de-DE` (`set_locale`)
+//! * Computed placeholders, `{{word_count}}` and `{{reading_time}}`, and the page count in footers (`{pages}`) and the generation report
+//! * Escaped placeholders, `\{{name}}` or `{{"{{"}}name}}`, written as they are
+//! * Removing, reporting or failing on placeholders without a front matter value (`set_placeholder_policy`)
+//! * Hugo-style `{{< name >}}` shortcodes: page breaks, columns and shortcodes of your own (`register_shortcode`)
+//! * A hook rewriting the HTML of each document before it is printed (`set_html_transform`)
+//! * Branded `Creator` and `Producer` document information, replacing or after PDF Composer (`set_creator`, `set_producer`)
+//! * The source markdown (and resolved front matter) embedded in each PDF, for provenance (`set_attach_source`)
+//! * File attachments (data CSVs, signatures, terms) per composer or per document (`attach_file`, `attachments` front matter)
+//! * Unsigned signature fields for signing services to sign in (`add_signature_field`)
+//! * Trusted RFC 3161 document timestamps from a time stamping authority (`timestamp` feature)
+//! * Viewer preferences: page layout, reading direction, the panel shown and window options (`set_viewer_preferences`)
+//! * Opening at a page or destination, at a zoom such as the page width (`set_open_action`)
+//! * Read access to the settings, and a summary of them (`paper_size`, `source_files`, `summary` etc)
+//! * Benchmark mode with min/median/max timings of each stage of generation (`bench`)
 
 use colored::Colorize;
-use rayon::prelude::*;
-use regex::Regex;
-use serde_yml::Value;
+use rayon::ThreadPool;
 use std::collections::BTreeMap;
 use std::fs;
-use std::option::Option;
-use std::path::{PathBuf, MAIN_SEPARATOR_STR};
-use std::process;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use pdf_composer_definitions::{
-    consts::{CROSS_MARK, DEFAULT_MARGIN, DEFAULT_OUTPUT_DIRECTORY, MM_TO_INCH},
+    asset_base::PathOrUrl,
+    attachments::FileAttachment,
+    bench::BenchReport,
+    code_wrap::CodeWrap,
+    config::PDFComposerConfig,
+    configuration_summary::ConfigurationSummary,
+    conformance::Conformance,
+    console_output::ConsoleOutput,
+    consts::{
+        CHECK_MARK, CROSS_MARK, DEFAULT_MARGIN, DEFAULT_OUTPUT_DIRECTORY,
+        DEFAULT_SOURCE_EXTENSIONS, MM_TO_INCH,
+    },
+    date_options::DateOptions,
+    diagnostics::SourceDiagnostics,
+    doctor::DoctorReport,
     fonts::FontsStandard,
+    front_matter::{MissingKeysPolicy, PlaceholderPolicy},
+    generation_report::GenerationReport,
+    generation_threads::{GenerationThreads, ProcessingMode},
+    icc_profile::IccProfile,
+    image_policy::ImagePolicy,
+    locale::Locale,
+    locked_file::LockedFilePolicy,
+    merge::MergeMode,
+    open_action::OpenAction,
     output_directory::OutputDirectory,
-    page_properties::{PaperOrientation, PaperSize},
+    output_format::OutputFormat,
+    page_numbering::{PageLabelRange, PageNumbering},
+    page_properties::{PageMargins, PaperOrientation, PaperSize},
     pdf_composer::PDFComposerStruct,
-    pdf_doc_entry::PDFDocInfoEntry,
+    pdf_doc_entry::{DocInfoBranding, PDFDocInfoEntry},
     pdf_version::PDFVersion,
+    persistent_browser::DEFAULT_BROWSER_IDLE_TIMEOUT,
+    running_headers::RunningHeaders,
+    shortcodes::ShortcodeInvocation,
+    signature_fields::SignatureField,
+    source_ordering::SourceOrdering,
+    text_direction::TextDirection,
+    text_justification::JustifyOptions,
+    upload::Uploader,
+    viewer_preferences::ViewerPreferences,
 };
+/// The `accessibility` module contains the tagged PDF helpers.
+mod accessibility;
+/// The `asset_base` module contains the resolution of relative links and images before printing.
+mod asset_base;
+/// The `attachments` module contains the files embedded in PDFs (such as the source markdown).
+mod attachments;
+/// The `barcode` module contains the barcode shortcode.
+mod barcode;
+/// The `bench` module contains the timing of each stage of generation run by `bench`.
+mod bench;
+use bench::run_bench;
+/// The `build_html` module contains the functions for writing self-contained HTML documents.
+mod build_html;
+use build_html::merge_html_chapters;
 /// The `build_pdf` module contains the core functions for generating PDF files.
 mod build_pdf;
-use build_pdf::{build_pdf, PDFBuilder};
+/// The `build_text` module contains the functions for writing plain text and markdown documents.
+mod build_text;
+use build_text::merge_text_chapters;
+/// The `callouts` module contains the callout (admonition) blocks and their styles.
+mod callouts;
+/// The `catalog` module contains helpers for editing the PDF document catalog.
+mod catalog;
+/// The `chart` module contains the chart shortcode.
+mod chart;
+/// The `chunked_rendering` module contains the splitting of large documents into chunks and the stitching of their PDFs.
+mod chunked_rendering;
+/// The `citations` module contains the citation and bibliography rendering (`citations` feature).
+#[cfg(feature = "citations")]
+mod citations;
+/// The `code_blocks` module contains the line numbers and highlighted lines of code blocks.
+mod code_blocks;
+/// The `compare` module contains the comparison of two PDF documents, structure and rendered pages.
+mod compare;
+pub use compare::compare_pdfs;
+/// The `computed_variables` module contains the computed placeholders (`{{word_count}}` and `{{reading_time}}`).
+mod computed_variables;
+/// The `conditional_content` module contains the `{{#if name}}` blocks kept or removed by the profile and flags.
+mod conditional_content;
+/// The `config` module contains the reading of configuration files (`pdf_composer.toml`).
+mod config;
+use config::configure;
+pub use config::{config_schema, read_config, validate_config};
+/// The `conformance` module contains the changes that make PDF documents conform to a standard (PDF/X-4).
+mod conformance;
+/// The `console_output` module contains the choice of coloured console output (`NO_COLOR`, terminals).
+mod console_output;
+use console_output::choose_colours;
+/// The `cover` module contains the cover pages put before generated documents.
+mod cover;
+/// The `cross_references` module contains the numbering and linking of labelled headings, figures and tables.
+mod cross_references;
+/// The `csv_table` module contains the CSV table shortcode and its styles.
+mod csv_table;
+/// The `destinations` module contains the named destination (link target) helpers.
+mod destinations;
+pub use destinations::add_named_destinations;
+/// The `date_variables` module contains the date placeholders (`{{today}}`, `{{now}}` and `{{build_date "format"}}`).
+mod date_variables;
+/// The `deterministic` module contains the fixing of dates and document IDs for reproducible PDFs.
+mod deterministic;
+/// The `directory_config` module contains the `_pdf_composer.yaml` settings of source directories.
+mod directory_config;
+use directory_config::apply_directory_config;
+/// The `doctor` module contains the environment preflight checks.
+mod doctor;
+use doctor::run_doctor;
+/// The `document_links` module contains the rewriting of links between source documents.
+mod document_links;
+/// The `epub` module contains the functions for writing EPUB e-books.
+#[cfg(feature = "epub")]
+mod epub;
+/// The `extract` module contains the function for extracting pages from PDF documents.
+mod extract;
+pub use extract::extract_pages;
+/// The `form_fields` module contains the fillable form fields of the `{{field}}` shortcode.
+mod form_fields;
+/// The `generate` module contains the generation of a single document from its front matter and markdown.
+mod generate;
+use generate::{
+    generate_document, generated_document, parse_source_document, read_source_document,
+    report_summary, DocumentOverrides, OutputError, OutputResult,
+};
+/// The `generation_threads` module contains the running of parallel generation on the chosen threads.
+mod generation_threads;
+use generation_threads::{map_documents, FailFast};
+/// The `git_variables` module contains the Git placeholders (`{{git_sha}}`, `{{git_tag}}` and `{{git_last_modified}}`, `git` feature).
+#[cfg(feature = "git")]
+mod git_variables;
+/// The `glossary` module contains the glossary section and the linking of glossary terms.
+mod glossary;
+/// The `heading_ids` module contains the ids given to headings for `#heading` links.
+mod heading_ids;
+/// The `html_cache` module contains the cache of the rendered HTML of each document.
+mod html_cache;
+/// The `html_document` module contains the complete HTML document (page styles etc) the PDF is printed from.
+mod html_document;
+/// The `icc_profile` module contains the ICC colour profiles and the output intents they are written into.
+mod icc_profile;
+/// The `image_attributes` module contains the image sizing attribute blocks.
+mod image_attributes;
+/// The `image_policy` module contains the downsampling of the raster images embedded in PDF documents.
+mod image_policy;
+/// The `inspect` module contains functions for reading back existing PDF documents.
+mod inspect;
+/// The `jpeg` module contains the JPEG decoding and encoding (`jpeg-decoder` and `jpeg-encoder`) used to resample images.
+mod jpeg;
+/// The `layout_shortcodes` module contains the page break and column shortcodes.
+mod layout_shortcodes;
+/// The `locale_placeholders` module contains the `{{date key}}` and `{{number key}}` placeholders, written in the conventions of the locale.
+mod locale_placeholders;
+/// The `locked_file` module contains the saving of PDFs that are locked by another process (retry or alternate name).
+mod locked_file;
+/// The `mail_merge` module contains the generation of one PDF per data record from a template.
+mod mail_merge;
+/// The `manifest` module contains the generation of the documents listed in a manifest file.
+mod manifest;
+/// The `merge` module contains the functions for merging PDF documents.
+mod merge;
+use merge::merge_chapters;
+pub use merge::merge_pdfs;
+/// The `optimize` module contains the removal of unused objects and merging of identical streams.
+mod optimize;
+/// The `page_numbering` module contains the page labels and page number footer stamping.
+mod page_numbering;
+/// The `page_tree` module contains helpers for rebuilding the page tree of a PDF document.
+mod page_tree;
+/// The `persistent_browser` module contains the warm headless Chromium shared between PDFs, and its idle shutdown.
+mod persistent_browser;
+use persistent_browser::PersistentBrowser;
+/// The `post_process` module contains the post-process command run on every generated PDF.
+mod post_process;
+/// The `preview` module contains the preview server, which reloads the generated HTML on changes.
+#[cfg(feature = "preview")]
+mod preview;
+/// The `print_command` module contains the extended Chromium print to PDF command.
+mod print_command;
+pub use inspect::read_pdf_metadata;
+/// The `progress` module contains the writing of the progress events of a generation run (NDJSON).
+mod progress;
+/// The `qr_code` module contains the QR code shortcode and page footer.
+mod qr_code;
+/// The `raster` module contains the raster image type and its resampling.
+mod raster;
+/// The `render` module contains the markdown to HTML conversion.
+mod render;
+/// The `running_headers` module contains the running header (current section title) stamping.
+mod running_headers;
+/// The `s3` module contains the uploader publishing the generated files to Amazon S3.
+#[cfg(feature = "s3")]
+mod s3;
+#[cfg(feature = "s3")]
+pub use s3::S3Uploader;
+/// The `shortcodes` module contains the `{{name ...}}` shortcodes (QR codes, barcodes, charts, CSV tables etc).
+mod shortcodes;
+/// The `source_ordering` module contains the sorting of the source files by `SourceOrdering`.
+mod source_ordering;
+use source_ordering::ordered_source_files;
+/// The `stamp` module contains helpers for drawing extra content on existing pages.
+mod stamp;
+/// The `timestamp` module contains the trusted (RFC 3161) timestamping of PDFs.
+#[cfg(feature = "timestamp")]
+mod timestamp;
+/// The `upload` module contains the publishing of the generated files with the composer's uploader.
+mod upload;
+use upload::upload_generated_files;
 /// 'utils' module for helper functions
 mod utils;
-use utils::{merge_markdown_yaml, read_lines, yaml_mapping_to_btreemap};
+use utils::{
+    doc_info_entry_name, doc_info_entry_near_miss, find_source_files, normalize_path, output_name,
+};
+/// The `validate` module contains the checks run by `validate_sources`.
+mod validate;
+use validate::validate_source;
+/// The `watermark` module contains the watermark drawn across every page (such as `DRAFT`).
+mod watermark;
+/// The `wiki_links` module contains the `[[wiki link]]` resolution.
+mod wiki_links;
 
 /// The PDF Composer trait with all the publically exposed methods
 pub trait PDFComposer {
@@ -44,10 +327,16 @@ pub trait PDFComposer {
     fn new() -> Self;
     /// Same as 'new'
     fn default() -> Self;
+    /// Create a new PDF Composer instance that keeps a warm headless Chromium alive between `generate_pdfs` calls
+    fn with_persistent_browser() -> Self;
     /// Set the version of the PDF as per the PDFVersion enum
     fn set_pdf_version(&mut self, pdf_version: PDFVersion);
     /// Set the directory into which generated PDFs will be saved
     fn set_output_directory<T: OutputDirectory>(&mut self, output_directory: T);
+    /// Set the format of the generated documents (PDF, or self-contained HTML) from the OutputFormat enum
+    fn set_output_format(&mut self, output_format: OutputFormat);
+    /// Add another format to generate the documents in, from the same rendering
+    fn add_output(&mut self, output_format: OutputFormat);
     /// Set the paper size from the PaperSize enum
     fn set_paper_size(&mut self, paper_size: PaperSize);
     /// Set the paper orientation from the PaperOrientation enum
@@ -56,12 +345,206 @@ pub trait PDFComposer {
     fn set_font(&mut self, font: FontsStandard);
     /// Set the margins to put around the paper
     fn set_margins(&mut self, margins: &str);
+    /// Set margins that alternate on odd and even pages for double-sided binding
+    fn set_margins_mirrored(&mut self, inner: u32, outer: u32, top: u32, bottom: u32);
     /// Set where the source files are to be found
     fn add_source_files(&mut self, paths: Vec<PathBuf>);
+    /// Set the extensions of the source files found in source directories (such as `.md` and `.markdown`)
+    fn set_source_extensions(&mut self, extensions: &[&str]);
+    /// Set the function naming the generated documents after their source files
+    fn set_output_namer<F>(&mut self, namer: F)
+    where
+        F: Fn(&Path) -> String + Send + Sync + 'static;
     /// Set the PDF document meta-data fields (such as language, keywords etc)
     fn set_doc_info_entry(&mut self, entry: PDFDocInfoEntry);
-    /// Generate the PDF document
-    fn generate_pdfs(&self);
+    /// Set (replacing) all the document information entries from (document information entry, YAML entry) pairs
+    fn set_doc_info_entries<I, K, V>(&mut self, entries: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>;
+    /// Set the Creator written into the document information, instead of or after PDF Composer
+    fn set_creator(&mut self, creator: DocInfoBranding);
+    /// Set the Producer written into the document information, instead of or after PDF Composer
+    fn set_producer(&mut self, producer: DocInfoBranding);
+    /// Set the YAML Front Matter keys every source document must have
+    fn require_front_matter_keys(&mut self, keys: &[&str]);
+    /// Set (replacing) the front matter values documents get when they do not set them, from (key, value) pairs
+    fn set_front_matter_defaults<I, K, V>(&mut self, defaults: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>;
+    /// Set what happens to documents missing a required front matter key
+    fn set_missing_keys_policy(&mut self, policy: MissingKeysPolicy);
+    /// Set what happens to placeholders that have no front matter value
+    fn set_placeholder_policy(&mut self, policy: PlaceholderPolicy);
+    /// Set the direction text is written in from the TextDirection enum
+    fn set_text_direction(&mut self, text_direction: TextDirection);
+    /// Set the language of the documents (a BCP 47 tag such as `en-GB`)
+    fn set_language(&mut self, language: &str);
+    /// Set the body text to be justified (and optionally hyphenated)
+    fn set_text_justification(&mut self, options: JustifyOptions);
+    /// Set whether tagged (accessible) PDFs are generated
+    fn set_tagged_pdf(&mut self, tagged: bool);
+    /// Set the current section title to be shown at the top of each page
+    fn set_running_headers(&mut self, options: RunningHeaders);
+    /// Set how the pages are numbered (for example, i, ii, iii for front matter pages then 1, 2, 3)
+    fn set_page_numbering(&mut self, numbering: PageNumbering);
+    /// Set the page labels PDF viewers show (such as Cover, i to iv, then 1 to 120)
+    fn set_page_labels<I: IntoIterator<Item = PageLabelRange>>(&mut self, ranges: I);
+    /// Set single-PDF (merge) mode, where the source files become chapters of one merged PDF
+    fn set_merge_mode(&mut self, merge_mode: MergeMode);
+    /// Set footnotes to be collected into a notes section (with the given title) at the end of each document
+    fn set_endnotes(&mut self, title: &str);
+    /// Set whether the lines of fenced code blocks are numbered
+    fn set_code_line_numbers(&mut self, line_numbers: bool);
+    /// Set what happens to long lines of code blocks from the CodeWrap enum (wrap, shrink to fit or truncate)
+    fn set_code_wrap(&mut self, code_wrap: CodeWrap);
+    /// Set the function resolving `[[wiki link]]` page names to paths or URLs
+    fn set_wiki_link_resolver<F>(&mut self, resolver: F)
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static;
+    /// Register a shortcode, rendered to HTML by a function for `{{< name >}}` in the markdown
+    fn register_shortcode<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(&ShortcodeInvocation) -> Result<String, String> + Send + Sync + 'static;
+    /// Set a function rewriting the HTML of each document after the markdown is converted
+    fn set_html_transform<F>(&mut self, transform: F)
+    where
+        F: Fn(String) -> String + Send + Sync + 'static;
+    /// Set how PDF viewers show the documents when they are opened (page layout, panel shown etc)
+    fn set_viewer_preferences(&mut self, preferences: ViewerPreferences);
+    /// Set where the documents open (a page or named destination, at a zoom)
+    fn set_open_action(&mut self, open_action: OpenAction);
+    /// Set a QR code (of a URL or other text, with front matter placeholders) in the footer of every page
+    fn set_qr_code_footer(&mut self, data: &str);
+    /// Set a watermark (such as DRAFT, with front matter placeholders) drawn diagonally across every page
+    fn set_watermark(&mut self, text: &str);
+    /// Set whether the source markdown file is embedded in each PDF
+    fn set_attach_source(&mut self, attach: bool);
+    /// Set whether the resolved front matter is embedded in each PDF
+    fn set_attach_front_matter(&mut self, attach: bool);
+    /// Set a file (such as a data CSV) to embed in every PDF as an attachment
+    fn attach_file<P: AsRef<Path>>(&mut self, path: P, description: &str);
+    /// Add an unsigned signature field (a name, page and rectangle) to every PDF
+    fn add_signature_field(&mut self, name: &str, page_number: u32, rect: [f32; 4]);
+    /// Set the time stamping authority (TSA) URL the PDFs get an RFC 3161 document timestamp from
+    #[cfg(feature = "timestamp")]
+    fn set_timestamp_authority(&mut self, url: &str);
+    /// Make the PDFs conform to a standard (such as PDF/X-4) from the Conformance enum
+    fn set_conformance(&mut self, conformance: Conformance);
+    /// Set the ICC colour profile (output intent) the PDFs are meant to be printed with
+    fn set_output_intent(&mut self, icc_profile: IccProfile);
+    /// Set how the raster images embedded in the PDFs are treated (downsampling, JPEG recompression) from the ImagePolicy struct
+    fn set_image_policy(&mut self, image_policy: ImagePolicy);
+    /// Set whether identical inputs produce identical PDFs (byte for byte)
+    fn set_deterministic(&mut self, deterministic: bool);
+    /// Set very large documents to be rendered a number of H1 sections at a time
+    fn set_chunked_rendering(&mut self, sections_per_chunk: usize);
+    /// Set the number of threads the documents are generated on, in a thread pool of their own
+    fn set_thread_count(&mut self, threads: usize);
+    /// Set the rayon thread pool the documents are generated on
+    fn set_thread_pool(&mut self, thread_pool: Arc<ThreadPool>);
+    /// Set whether the documents are generated in parallel or one after another from the ProcessingMode enum
+    fn set_processing_mode(&mut self, processing_mode: ProcessingMode);
+    /// Set whether generation stops once a document fails, instead of generating the others
+    fn set_fail_fast(&mut self, fail_fast: bool);
+    /// Set the order the source files are generated (and merged) in from the SourceOrdering enum
+    fn set_source_ordering(&mut self, source_ordering: SourceOrdering);
+    /// Set the directory the rendered HTML of each document is cached in between runs
+    fn set_html_cache<T: OutputDirectory>(&mut self, cache_directory: T);
+    /// Set how long the persistent browser is kept alive without being used (turning the persistent browser on)
+    fn set_browser_idle_timeout(&mut self, idle_timeout: Duration);
+    /// Set the root (a directory or URL) relative links and images are resolved against when printing
+    fn set_asset_base<T: Into<PathOrUrl>>(&mut self, asset_base: T);
+    /// Set a CSS file whose styles are added after the built-in styles
+    fn set_stylesheet<P: AsRef<Path>>(&mut self, stylesheet: P);
+    /// Set what happens when a PDF cannot be saved because the file is locked from the LockedFilePolicy enum
+    fn set_locked_file_policy(&mut self, locked_file_policy: LockedFilePolicy);
+    /// Set a command (such as an optimizer or validator) run on every generated PDF, with `{output}`, `{name}` and `{dir}` placeholders
+    fn set_post_process_command(&mut self, command: &str);
+    /// Set the uploader every generated file is published with, such as `S3Uploader` (`s3` feature)
+    fn set_uploader<U: Uploader + 'static>(&mut self, uploader: U);
+    /// Set the environment variables `${NAME}` references in the front matter and markdown expand to
+    fn set_env_var_allow_list(&mut self, names: &[&str]);
+    /// Set the time zone and language of the date placeholders from the DateOptions struct
+    fn set_date_options(&mut self, date_options: DateOptions);
+    /// Set the locale (such as `de-DE`) the dates and numbers of the placeholders are written in
+    fn set_locale(&mut self, locale: &str) -> Result<(), String>;
+    /// Set whether the progress messages are left out, leaving stdout to the application (such as a PDF written to it)
+    fn set_quiet(&mut self, quiet: bool);
+    /// Set how much is printed to the console from the ConsoleOutput enum (quiet, normal or verbose)
+    fn set_console_output(&mut self, console_output: ConsoleOutput);
+    /// Set the writer a progress event of each file is written to, as NDJSON (one JSON object per line)
+    fn set_progress_writer<W: Write + Send + 'static>(&mut self, writer: W);
+    /// Set the profile of the configuration file (such as `draft` or `final`) applied on top of its other settings
+    fn set_profile(&mut self, profile: &str);
+    /// Add a flag (such as `internal`) that `{{#if name}}` conditional blocks of the documents are kept for
+    fn add_flag(&mut self, flag: &str);
+    /// Set up the composer from a configuration file (`pdf_composer.toml`, or `.yaml`)
+    fn load_config<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Box<dyn std::error::Error>>;
+    /// Set up the composer from the settings of a configuration file, with paths relative to a directory
+    fn apply_config(
+        &mut self,
+        config: &PDFComposerConfig,
+        config_directory: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+    /// Get the source files, in the order they were added
+    fn source_files(&self) -> &[PathBuf];
+    /// Get the directory the documents are saved to
+    fn output_directory(&self) -> &Path;
+    /// Get the formats of the generated documents
+    fn output_formats(&self) -> &[OutputFormat];
+    /// Get the file name (without its extension) of the documents generated from a source file
+    fn output_name(&self, source_file: &Path) -> String;
+    /// Get the PDF version
+    fn pdf_version(&self) -> PDFVersion;
+    /// Get the paper size
+    fn paper_size(&self) -> PaperSize;
+    /// Get the paper orientation
+    fn orientation(&self) -> PaperOrientation;
+    /// Get the page margins (top, right, bottom, left) in inches
+    fn margins(&self) -> PageMargins;
+    /// Get the standard font
+    fn font(&self) -> FontsStandard;
+    /// Get the language tag, if set
+    fn language(&self) -> Option<&str>;
+    /// Get the document information entries, mapped to the YAML Front Matter keys they are filled from
+    fn doc_info_entries(&self) -> Option<&BTreeMap<String, String>>;
+    /// Get the merge mode, if set
+    fn merge_mode(&self) -> Option<&MergeMode>;
+    /// Get a summary of all the main settings, for displaying or validating them
+    fn summary(&self) -> ConfigurationSummary;
+    /// Generate the PDF document, returning a report of what was generated
+    fn generate_pdfs(&self) -> GenerationReport;
+    /// Generate one PDF for each data record, from a single markdown template
+    fn generate_from_records<P, I>(
+        &self,
+        template: P,
+        records: I,
+        naming: &str,
+    ) -> GenerationReport
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = serde_json::Value>;
+    /// Generate the documents listed in a manifest file (YAML or TOML), each with its own settings
+    fn generate_from_manifest<P: AsRef<Path>>(&self, manifest: P) -> GenerationReport;
+    /// Generate the documents of a markdown source (with front matter) given as text, such as read from stdin
+    fn generate_from_markdown(&self, source: &str, name: &str) -> GenerationReport;
+    /// Check the source files for problems without generating any PDFs
+    fn validate_sources(&self) -> Vec<SourceDiagnostics>;
+    /// Check the environment (Chromium, output directory etc) before generating any PDFs
+    fn doctor(&self) -> DoctorReport;
+    /// Render the source files a number of times, reporting the min, median and max time of each stage
+    fn bench(&self, iterations: usize) -> BenchReport;
+    /// Serve the HTML of a source document over HTTP, reloading it whenever the sources change
+    #[cfg(feature = "preview")]
+    fn preview<P: AsRef<Path>>(
+        &self,
+        source: P,
+        address: &str,
+    ) -> Result<(), Box<dyn std::error::Error>>;
 }
 
 impl PDFComposer for PDFComposerStruct {
@@ -70,36 +553,130 @@ impl PDFComposer for PDFComposerStruct {
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
     ///
     /// // Create a new PDFComposer instance with default values
-    /// let my_pdf_doc = PDFComposer::new();
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     /// ```
     fn new() -> Self {
+        // No colour escapes in logs and CI (once per process)
+        choose_colours();
+
         // Create and return a new instance of PDFComposer.
         // Setting default values, where applicable.
         Self {
             fmy_source_files: Vec::new(),
+            source_extensions: DEFAULT_SOURCE_EXTENSIONS
+                .iter()
+                .map(|extension| extension.to_string())
+                .collect(),
+            output_namer: None,
             output_directory: DEFAULT_OUTPUT_DIRECTORY.into(),
+            output_formats: vec![OutputFormat::Pdf],
             pdf_version: PDFVersion::V1_7,
             pdf_document_entries: None,
+            creator: None,
+            producer: None,
             paper_size: PaperSize::A4,
             orientation: PaperOrientation::Portrait,
             margins: [DEFAULT_MARGIN / MM_TO_INCH; 4],
+            mirrored_margins: false,
             font: FontsStandard::Helvetica,
+            required_front_matter_keys: Vec::new(),
+            front_matter_defaults: BTreeMap::new(),
+            missing_keys_policy: MissingKeysPolicy::Report,
+            placeholder_policy: PlaceholderPolicy::LeaveAsIs,
+            text_direction: TextDirection::Ltr,
+            language: None,
+            text_justification: None,
+            tagged_pdf: false,
+            running_headers: None,
+            page_numbering: None,
+            page_labels: None,
+            merge_mode: None,
+            endnotes_title: None,
+            code_line_numbers: false,
+            code_wrap: CodeWrap::Wrap,
+            wiki_link_resolver: None,
+            shortcodes: BTreeMap::new(),
+            html_transform: None,
+            viewer_preferences: ViewerPreferences::default(),
+            open_action: None,
+            qr_code_footer: None,
+            watermark: None,
+            attach_source: false,
+            attach_front_matter: false,
+            attachments: Vec::new(),
+            signature_fields: Vec::new(),
+            #[cfg(feature = "timestamp")]
+            timestamp_authority: None,
+            conformance: None,
+            output_intent: None,
+            image_policy: None,
+            deterministic: false,
+            chunked_rendering: None,
+            generation_threads: GenerationThreads::Global,
+            processing_mode: ProcessingMode::Parallel,
+            fail_fast: false,
+            source_ordering: SourceOrdering::AsAdded,
+            html_cache: None,
+            persistent_browser: None,
+            asset_base: None,
+            stylesheet: None,
+            locked_file_policy: LockedFilePolicy::Fail,
+            post_process_command: None,
+            uploader: None,
+            env_var_allow_list: Vec::new(),
+            date_options: DateOptions::default(),
+            locale: None,
+            console_output: ConsoleOutput::Normal,
+            progress_writer: None,
+            profile: None,
+            flags: Vec::new(),
         }
     }
 
+    /// Creates a new instance of PDFComposer with default values that prints its PDFs with a
+    /// warm headless Chromium, kept alive across `generate_pdfs` calls, instead of launching
+    /// Chromium for every PDF. This suits services that render single documents on demand, where
+    /// starting Chromium is most of the time a PDF takes.
+    ///
+    /// Chromium is launched when the first PDF is generated. It is shut down once it has not been
+    /// used for the idle timeout (five minutes, see `set_browser_idle_timeout`), and launched
+    /// again when next needed, or when the composer is dropped. Documents generated in parallel
+    /// share the browser, each in a tab of its own.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a PDFComposer instance that keeps Chromium running between calls
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::with_persistent_browser();
+    /// ```
+    fn with_persistent_browser() -> Self {
+        let mut composer = Self::new();
+        composer.persistent_browser = Some(Arc::new(PersistentBrowser::new(
+            DEFAULT_BROWSER_IDLE_TIMEOUT,
+        )));
+        composer
+    }
+
     /// Sets the PDF version for the PDFComposer instance.
     /// Sets the PDF version for the PDF document.
     ///
+    /// Versions `1.4` through `2.0` are supported. Documents of versions before `1.5` are saved
+    /// with a classic cross-reference table. `PDFVersion::Auto` picks the lowest version that can
+    /// express the features each document uses; with an explicit version, a document using a
+    /// feature the version cannot express is not generated.
+    ///
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::{PDFComposer, PDFVersion};
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PDFVersion};
     ///
     /// // Create a new PDF document
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Set the PDF version to 2.0
     /// my_pdf_doc.set_pdf_version(PDFVersion::V1_7);
@@ -113,16 +690,81 @@ impl PDFComposer for PDFComposerStruct {
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
     ///
     /// // Create a new PDF generator instance
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Set the output directory to "output/pdf"
     /// my_pdf_doc.set_output_directory("output/pdf");
     /// ```
     fn set_output_directory<T: OutputDirectory>(&mut self, output_directory: T) {
-        self.output_directory = output_directory.convert();
+        self.output_directory = normalize_path(&output_directory.convert());
+    }
+
+    /// Sets the format of the generated documents (replacing any formats added with `add_output`).
+    /// Defaults to `OutputFormat::Pdf`.
+    ///
+    /// With `OutputFormat::Html`, each source document is written to the output directory as a
+    /// self-contained HTML document instead (the page styles are included, and local images are
+    /// inlined as data URLs), without starting Chromium. In merge mode the documents are combined
+    /// into one HTML document. Page numbers, running headers, the QR code footer and PDF document
+    /// information entries only apply to PDFs; the document information entries become `<meta>`
+    /// elements instead.
+    ///
+    /// With `OutputFormat::Epub` (`epub` feature), each source document is written as an EPUB 3
+    /// e-book, with the document information entries as its metadata. In merge mode the documents
+    /// become the chapters of one e-book.
+    ///
+    /// With `OutputFormat::Text`, the content of each source document (with the placeholders filled
+    /// in) is written as plain text (`TextFormat::Plain`, `.txt`) or normalized markdown
+    /// (`TextFormat::Markdown`, `.md`), for search indexes and other tools that go along with the
+    /// PDFs. In merge mode the documents are joined into one text file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{OutputFormat, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Write HTML documents instead of PDFs
+    /// my_pdf_doc.set_output_format(OutputFormat::Html);
+    /// ```
+    fn set_output_format(&mut self, output_format: OutputFormat) {
+        self.output_formats = vec![output_format];
+    }
+
+    /// Adds another format to generate the documents in (the documents are generated as PDFs by
+    /// default, or in the format set with `set_output_format`). Adding a format that is already
+    /// there does nothing.
+    ///
+    /// Each source document is read and rendered to HTML once, and that rendering is used for all
+    /// the formats, rather than running the whole generation again for each format. Only the links
+    /// to other documents differ, so each format links to the documents of the same format. In
+    /// merge mode each format gets its own merged document.
+    ///
+    /// In the `GenerationReport`, `output_file` is the document of the first format, and
+    /// `additional_output_files` the documents of the other formats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{OutputFormat, PDFComposer, PDFComposerStruct, TextFormat};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Write an HTML document and a plain text file next to each PDF
+    /// my_pdf_doc.add_output(OutputFormat::Pdf);
+    /// my_pdf_doc.add_output(OutputFormat::Html);
+    /// my_pdf_doc.add_output(OutputFormat::Text(TextFormat::Plain));
+    /// ```
+    fn add_output(&mut self, output_format: OutputFormat) {
+        if !self.output_formats.contains(&output_format) {
+            self.output_formats.push(output_format);
+        }
     }
 
     /// Sets the paper size for the PDF documents.
@@ -130,10 +772,10 @@ impl PDFComposer for PDFComposerStruct {
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PaperSize};
     ///
     /// // Create a new PDF generator instance
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Set the paper size to A5
     /// my_pdf_doc.set_paper_size(PaperSize::A5);
@@ -147,10 +789,10 @@ impl PDFComposer for PDFComposerStruct {
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PaperOrientation};
     ///
     /// // Create a new PDF generator instance
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Set the orientation to Landscape
     /// my_pdf_doc.set_orientation(PaperOrientation::Landscape);
@@ -164,10 +806,10 @@ impl PDFComposer for PDFComposerStruct {
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{FontsStandard, PDFComposer, PDFComposerStruct};
     ///
     /// // Create a new PDF generator instance
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Set the font to Times Roman
     /// my_pdf_doc.set_font(FontsStandard::TimesRoman);
@@ -181,10 +823,10 @@ impl PDFComposer for PDFComposerStruct {
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
     ///
     /// // Create a new PDF generator instance
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Set the page margins to 20mm
     /// my_pdf_doc.set_margins("20");
@@ -256,19 +898,51 @@ impl PDFComposer for PDFComposerStruct {
             }
         };
 
+        self.mirrored_margins = false;
         // println!("{:#?}", self.margins);
     }
 
+    /// Sets mirrored page margins (in mm) for double-sided, bound documents.
+    ///
+    /// The `inner` margin (the gutter) is on the binding side of each page: on the left of right-hand
+    /// (odd) pages and on the right of left-hand (even) pages. The `outer` margin is on the other side.
+    /// Calling `set_margins` afterwards switches back to the same margins on every page.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // 25mm gutter, 15mm outer margin, 20mm top and bottom margins
+    /// my_pdf_doc.set_margins_mirrored(25, 15, 20, 20);
+    /// ```
+    fn set_margins_mirrored(&mut self, inner: u32, outer: u32, top: u32, bottom: u32) {
+        self.margins = [
+            f64::from(top) / MM_TO_INCH,
+            f64::from(outer) / MM_TO_INCH,
+            f64::from(bottom) / MM_TO_INCH,
+            f64::from(inner) / MM_TO_INCH,
+        ];
+        self.mirrored_margins = true;
+    }
+
     /// Adds source files to the PDFComposer instance for processing.
     ///
+    /// A directory adds the source files in it and the directories below it, those with one of the
+    /// source extensions (`.md` and `.markdown` unless set with `set_source_extensions`), sorted
+    /// by path. Hidden files and directories (`.git` etc) are skipped.
+    ///
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
     /// use std::path::PathBuf;
     ///
     /// // Create a new PDF generator instance
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Define paths to source files
     /// let source_files = vec![
@@ -280,38 +954,110 @@ impl PDFComposer for PDFComposerStruct {
     /// my_pdf_doc.add_source_files(source_files);
     /// ```
     fn add_source_files(&mut self, paths: Vec<PathBuf>) {
-        let regex = Regex::new(r"(?m)\\").unwrap();
-
-        // Normalize the paths to be OS compliant
+        // Normalize the paths to be OS compliant (keeping Windows UNC share and `\\?\` prefixes)
         let normalized_paths: Vec<PathBuf> = paths
             .iter()
-            .map(|p| {
-                // Normalize the paths to be OS compliant
-                let is_windows = cfg!(target_os = "windows");
-                // Convert the path separator based on the platform
-                let os_compliant_path = if is_windows {
-                    p.display().to_string().replace('/', MAIN_SEPARATOR_STR)
+            .map(|p| normalize_path(p))
+            .flat_map(|path| {
+                if path.is_dir() {
+                    find_source_files(&path, &self.source_extensions)
                 } else {
-                    regex
-                        .replace_all(&p.as_path().display().to_string(), MAIN_SEPARATOR_STR)
-                        .to_string()
-                };
-                PathBuf::from(os_compliant_path)
+                    vec![path]
+                }
             })
             .collect();
 
         self.fmy_source_files.extend(normalized_paths);
     }
 
+    /// Sets the extensions of the source files found in the directories added with
+    /// `add_source_files`, and of the links between documents that are rewritten to the generated
+    /// documents. The documents are named after the source files (see `output_name`), so
+    /// `report.markdown` becomes `report.pdf`.
+    ///
+    /// The extensions are `.md` and `.markdown` unless set. An extension without a leading dot is
+    /// given one, and they are compared without regard to case. Set them before adding directories,
+    /// as the files of a directory are found when it is added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Every .md, .markdown and .mdx file in docs (and the directories below it)
+    /// my_pdf_doc.set_source_extensions(&[".md", ".markdown", ".mdx"]);
+    /// my_pdf_doc.add_source_files(vec![PathBuf::from("docs")]);
+    /// ```
+    fn set_source_extensions(&mut self, extensions: &[&str]) {
+        self.source_extensions = extensions
+            .iter()
+            .map(|extension| extension.trim())
+            .filter(|extension| !extension.is_empty() && *extension != ".")
+            .map(|extension| {
+                if extension.starts_with('.') {
+                    extension.to_string()
+                } else {
+                    format!(".{}", extension)
+                }
+            })
+            .collect();
+    }
+
+    /// Sets a function that names the documents generated from each source file, instead of the
+    /// source file name without its extension.
+    ///
+    /// The function is given the path of the source file (as it was added) and returns the file
+    /// name to use, without the extension of the output format (`.pdf`, `.html` etc). An empty name
+    /// falls back to the source file name. Documents given a name of their own (by a manifest, or
+    /// the naming of `generate_from_records`) keep it. Links between documents are rewritten with
+    /// the same names.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::path::Path;
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Prefix the documents with the name of the directory they are in
+    /// my_pdf_doc.set_output_namer(|source_file| {
+    ///     let directory = source_file
+    ///         .parent()
+    ///         .and_then(Path::file_name)
+    ///         .map(|directory| directory.to_string_lossy().to_string())
+    ///         .unwrap_or_default();
+    ///     let stem = source_file.file_stem().unwrap_or_default().to_string_lossy();
+    ///     format!("{}-{}", directory, stem)
+    /// });
+    ///
+    /// assert_eq!(my_pdf_doc.output_name(Path::new("guides/setup.md")), "guides-setup");
+    /// ```
+    fn set_output_namer<F>(&mut self, namer: F)
+    where
+        F: Fn(&Path) -> String + Send + Sync + 'static,
+    {
+        self.output_namer = Some(Arc::new(namer));
+    }
+
     /// Sets a document information entry for the PDFComposer instance.
     ///
+    /// The reserved entry names (`Title`, `Author`, `Subject`, `Keywords`, `Creator`, `Producer`,
+    /// `CreationDate`, `ModDate` and `Trapped`) are written as the PDF specification has them,
+    /// whatever their case, so `creationdate` sets `CreationDate` rather than a custom entry. A
+    /// custom entry name close to a reserved one (such as `Creation_Date` or `Autor`) is kept as
+    /// it is, with a warning.
+    ///
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::{PDFComposer, PDFDocInfoEntry};
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PDFDocInfoEntry};
     ///
     /// // Create a new PDFComposer instance
-    /// let mut my_pdf_doc = PDFComposer::new();
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
     /// // Define a document information entry
     /// let doc_info_entry = PDFDocInfoEntry {
@@ -323,16 +1069,8 @@ impl PDFComposer for PDFComposerStruct {
     /// my_pdf_doc.set_doc_info_entry(doc_info_entry);
     /// ```
     fn set_doc_info_entry(&mut self, entry: PDFDocInfoEntry) {
-        // Reserved metadata entries in the document information dictionary
-        // These are case sensitive and must be capitalised.
-        // All others will be as entered by the user.
-        let local_doc_info_entry: String = match entry.doc_info_entry.to_lowercase().as_str() {
-            "title" => "Title".to_string(),
-            "author" => "Author".to_string(),
-            "subject" => "Subject".to_string(),
-            "keywords" => "Keywords".to_string(),
-            _ => entry.doc_info_entry.to_string(),
-        };
+        warn_doc_info_entry_near_miss(entry.doc_info_entry);
+        let local_doc_info_entry = doc_info_entry_name(entry.doc_info_entry);
         let local_yaml_entry = entry.yaml_entry;
 
         // Match and handle the Option variant to insert the entry into the PDF document entries.
@@ -350,133 +1088,2228 @@ impl PDFComposer for PDFComposerStruct {
         }
     }
 
-    /// Generates PDF documents based on the configured settings and source files.
+    /// Sets all the document information entries at once, replacing any set before, from
+    /// (document information entry, YAML entry) pairs. This suits mappings read from a
+    /// configuration file: the whole mapping is swapped in one call, and an empty mapping clears
+    /// the entries. Later pairs for the same document information entry win.
     ///
     /// # Examples
     ///
     /// ```
-    /// use pdf_composer::PDFComposer;
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::collections::BTreeMap;
     ///
-    /// // Create a PDF generator instance
-    /// let my_pdf_doc = PDFComposer::new();
+    /// // Create a new PDFComposer instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
     ///
-    /// // Generate PDFs based on the configuration and source files
-    /// my_pdf_doc.generate_pdfs();
+    /// // A mapping read from a configuration file
+    /// let mut mapping: BTreeMap<String, String> = BTreeMap::new();
+    /// mapping.insert("Author".to_string(), "author".to_string());
+    /// mapping.insert("Title".to_string(), "title".to_string());
+    ///
+    /// // Replace the document information entries with the mapping
+    /// my_pdf_doc.set_doc_info_entries(mapping);
+    ///
+    /// // Pairs of string slices work too
+    /// my_pdf_doc.set_doc_info_entries([("Subject", "description"), ("Keywords", "tags")]);
     /// ```
-    fn generate_pdfs(&self) {
-        // Handle case where no source files are set.
-        let error_message = "".to_owned()
-            + &CROSS_MARK.on_red().to_string()
-            + &"No source files set.".on_red().to_string()
-            + " Exiting\n";
-        if self.fmy_source_files.is_empty() {
-            eprintln!("{}", error_message);
-            process::exit(0);
-        }
+    fn set_doc_info_entries<I, K, V>(&mut self, entries: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let new_map: BTreeMap<String, String> = entries
+            .into_iter()
+            .map(|(doc_info_entry, yaml_entry)| {
+                warn_doc_info_entry_near_miss(doc_info_entry.as_ref());
+                (
+                    doc_info_entry_name(doc_info_entry.as_ref()),
+                    yaml_entry.as_ref().to_owned(),
+                )
+            })
+            .collect();
+        self.pdf_document_entries = if new_map.is_empty() {
+            None
+        } else {
+            Some(new_map)
+        };
+    }
 
-        println!("{} {:#?}", "Files:".cyan(), &self.fmy_source_files);
-        println!(
-            "Files to process: {}\n",
-            &self.fmy_source_files.len().to_string().cyan()
-        );
+    /// Sets the `Creator` entry of the document information, the application the document was
+    /// created with, so products built on PDF Composer can carry their own name.
+    /// `DocInfoBranding::Replace` writes the name instead of `PDF Composer` and
+    /// `DocInfoBranding::Append` writes it after (`PDF Composer; Acme Reports`). The name is also
+    /// the generator of HTML and EPUB documents. A `generator` front matter value still overrides
+    /// it for that document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{DocInfoBranding, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // The documents are created with Acme Reports
+    /// my_pdf_doc.set_creator(DocInfoBranding::Replace("Acme Reports".to_string()));
+    /// ```
+    fn set_creator(&mut self, creator: DocInfoBranding) {
+        self.creator = Some(creator);
+    }
 
-        // Process each source file in parallel.
-        self.fmy_source_files.par_iter().for_each(|document| {
-            // Initialize variables for processing YAML and Markdown content.
-            let mut rayon_yaml_delimiter_count = 0;
-            let mut rayon_yaml_content: String = String::default();
-            let mut rayon_markdown_content: String = String::default();
-            let mut yaml_section_complete: bool = false;
-
-            // Extract filename from PathBuf.
-            let filename = <std::path::PathBuf as Clone>::clone(document)
-                .into_os_string()
-                .into_string()
-                .unwrap();
-
-            // Attempt to read metadata of the file.
-            match fs::metadata(filename.clone()) {
-                Ok(_) => 'file_found: {
-                    // File exists, proceed with reading.
-                    println!("File {} exists. {}", filename.cyan(), "Reading...".green());
-                    if let Ok(lines) = read_lines(&filename) {
-                        // Iterate through lines and process YAML and Markdown content.
-                        for line in lines.map_while(Result::ok) {
-                            // Check YAML delimiters and extract content.
-                            if line.trim() == "---" && rayon_yaml_delimiter_count < 2 {
-                                rayon_yaml_delimiter_count += 1;
-                            }
+    /// Sets the `Producer` entry of the document information, the application that wrote the
+    /// PDF. `DocInfoBranding::Replace` writes the name instead of `PDF Composer` and
+    /// `DocInfoBranding::Append` writes it after (`PDF Composer; Acme Reports 2.1`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{DocInfoBranding, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Keep PDF Composer in the Producer, followed by the product name
+    /// my_pdf_doc.set_producer(DocInfoBranding::Append("Acme Reports 2.1".to_string()));
+    /// ```
+    fn set_producer(&mut self, producer: DocInfoBranding) {
+        self.producer = Some(producer);
+    }
 
-                            if line.trim() != "---" && rayon_yaml_delimiter_count < 2 {
-                                rayon_yaml_content.push_str(&format!("{}{}", &line, "\n"));
-                            }
+    /// Sets the YAML Front Matter keys every source document must have.
+    /// Documents missing any of them are reported, and skipped if the policy is `MissingKeysPolicy::Skip`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Every document must have a title and an author
+    /// my_pdf_doc.require_front_matter_keys(&["title", "author"]);
+    /// ```
+    fn require_front_matter_keys(&mut self, keys: &[&str]) {
+        self.required_front_matter_keys = keys.iter().map(|key| key.to_string()).collect();
+    }
 
-                            // Check if YAML section is complete.
-                            if rayon_yaml_delimiter_count == 2 && !yaml_section_complete {
-                                yaml_section_complete = true;
-                                continue;
-                            }
+    /// Sets the front matter values documents get when they do not set them, replacing any set
+    /// before, such as a company name shared by every document. The defaults fill placeholders and
+    /// document information entries as if they were in the front matter, and count as present for
+    /// the required front matter keys. An empty mapping clears them.
+    ///
+    /// Defaults can also be set in the `defaults` table of a configuration file, and for the
+    /// documents of a directory in its `_pdf_composer.yaml` file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Documents without a `company` or `status` get these
+    /// my_pdf_doc.set_front_matter_defaults([("company", "Orange Kiwi"), ("status", "Draft")]);
+    /// ```
+    fn set_front_matter_defaults<I, K, V>(&mut self, defaults: I)
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        self.front_matter_defaults = defaults
+            .into_iter()
+            .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+            .collect();
+    }
 
-                            // Extract Markdown content after YAML section.
-                            if rayon_yaml_delimiter_count == 2 && yaml_section_complete {
-                                rayon_markdown_content.push_str(&format!("{}{}", &line, "\n"));
-                            }
-                        }
-                    }
+    /// Sets what happens to documents that are missing a required front matter key.
+    /// Defaults to `MissingKeysPolicy::Report`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{MissingKeysPolicy, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Do not generate PDFs for documents without a title
+    /// my_pdf_doc.require_front_matter_keys(&["title"]);
+    /// my_pdf_doc.set_missing_keys_policy(MissingKeysPolicy::Skip);
+    /// ```
+    fn set_missing_keys_policy(&mut self, policy: MissingKeysPolicy) {
+        self.missing_keys_policy = policy;
+    }
 
-                    // Parse YAML content.
-                    let yaml: Value = serde_yml::from_str(&rayon_yaml_content).unwrap();
-                    // Check if YAML is valid.
-                    // If file exists, but is not a suitable yaml markdown file, early exit break
-                    if rayon_yaml_delimiter_count == 0 || yaml == Value::Null {
-                        println!("File {} is not a valid yaml file", filename.red());
-                        break 'file_found;
-                    } else {
-                        println!("{}. {}", filename.cyan(), "Processing...".green());
-                    }
+    /// Sets what happens to `{{key}}` placeholders (in the markdown or the QR code footer) that have
+    /// no front matter value. Defaults to `PlaceholderPolicy::LeaveAsIs`.
+    ///
+    /// With `PlaceholderPolicy::Remove` they are removed, with `PlaceholderPolicy::Warn` they are
+    /// reported and left as they are, and with `PlaceholderPolicy::Error` they are reported and the
+    /// document is not generated. Escaped placeholders (`\{{key}}`) are never affected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PlaceholderPolicy};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Do not generate an invoice with an unfilled {{customer_name}}
+    /// my_pdf_doc.set_placeholder_policy(PlaceholderPolicy::Error);
+    /// ```
+    fn set_placeholder_policy(&mut self, policy: PlaceholderPolicy) {
+        self.placeholder_policy = policy;
+    }
 
-                    // Convert YAML Front Matter to a BTreeMap.
-                    let yaml_btreemap: BTreeMap<String, Value> =
-                        yaml_mapping_to_btreemap(&yaml).unwrap();
-
-                    // Insert YAML Front Matter into markdown.
-                    let merged_markdown_yaml =
-                        merge_markdown_yaml(yaml_btreemap.clone(), &rayon_markdown_content);
-
-                    // Convert Markdown content to HTML.
-                    // markdown:: comes from the markdown crate
-                    let html: String = markdown::to_html(&merged_markdown_yaml.to_owned());
-
-                    let instance_data = PDFBuilder {
-                        source_file: filename.to_string(),
-                        output_directory: self.output_directory.to_path_buf(),
-                        pdf_version: self.pdf_version,
-                        paper_size: self.paper_size,
-                        orientation: self.orientation,
-                        margins: self.margins,
-                        font: self.font,
-                    };
-
-                    let dictionary_entries = match &self.pdf_document_entries {
-                        None => BTreeMap::new(),
-                        _ => <Option<BTreeMap<String, String>> as Clone>::clone(
-                            &self.pdf_document_entries,
-                        )
-                        .unwrap(),
-                    };
+    /// Sets the direction text is written in. Defaults to `TextDirection::Ltr`.
+    /// A document can override this with a `dir: rtl` (or `dir: ltr`) front matter entry.
+    ///
+    /// Right-to-left documents get `dir="rtl"` on the generated HTML, fallback fonts with Arabic
+    /// and Hebrew glyphs, and a right-to-left reading direction in the PDF viewer preferences.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, TextDirection};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Lay documents out right-to-left
+    /// my_pdf_doc.set_text_direction(TextDirection::Rtl);
+    /// ```
+    fn set_text_direction(&mut self, text_direction: TextDirection) {
+        self.text_direction = text_direction;
+    }
 
-                    // Build the PDF document.
-                    let _ = build_pdf(html, yaml_btreemap, dictionary_entries, instance_data);
-                }
-                Err(_) => {
-                    // File not found, print error message.
-                    println!("File {} not found.", filename.red());
-                }
-            }
-        });
+    /// Sets the language of the documents, used for the `lang` attribute of the generated HTML
+    /// and the `/Lang` entry of the PDF document catalog.
+    /// A document can override this with a `lang` (or `language`) front matter entry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Documents are in Arabic
+    /// my_pdf_doc.set_language("ar");
+    /// ```
+    fn set_language(&mut self, language: &str) {
+        let trimmed_language = language.trim();
+        self.language = if trimmed_language.is_empty() {
+            None
+        } else {
+            Some(trimmed_language.to_string())
+        };
     }
 
-    fn default() -> Self {
-        Self::new()
+    /// Sets the body text to be justified, optionally hyphenating words at line breaks.
+    ///
+    /// Hyphenation is done by Chromium and needs to know the language of the text. The language is
+    /// taken from the `lang` front matter entry, then `set_language`, then `JustifyOptions::lang`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{JustifyOptions, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Justify and hyphenate British English text
+    /// my_pdf_doc.set_text_justification(JustifyOptions {
+    ///     hyphenate: true,
+    ///     lang: Some("en-GB".to_string()),
+    /// });
+    /// ```
+    fn set_text_justification(&mut self, options: JustifyOptions) {
+        self.text_justification = Some(options);
+    }
+
+    /// Sets whether tagged (accessible) PDFs are generated. Defaults to `false`.
+    ///
+    /// Tagged PDFs carry a structure tree (headings, paragraphs, lists, figures etc) that screen
+    /// readers use, and are a prerequisite for PDF/UA. The document catalog is marked with
+    /// `/MarkInfo << /Marked true >>` and viewers are asked to display the document title.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Generate accessible PDFs
+    /// my_pdf_doc.set_tagged_pdf(true);
+    /// ```
+    fn set_tagged_pdf(&mut self, tagged: bool) {
+        self.tagged_pdf = tagged;
+    }
+
+    /// Sets running headers: the current H1 (or H2) heading shown at the top of each page, like
+    /// the running heads of a book.
+    ///
+    /// The pages each heading starts on are read from the document outline Chromium generates,
+    /// and the headers are then drawn in the top margin of each page in the document font.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, RunningHeaderLevel, RunningHeaders};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Show the current chapter (H1) title, except on the first page of each chapter
+    /// my_pdf_doc.set_running_headers(RunningHeaders {
+    ///     level: RunningHeaderLevel::H1,
+    ///     skip_chapter_pages: true,
+    /// });
+    /// ```
+    fn set_running_headers(&mut self, options: RunningHeaders) {
+        self.running_headers = Some(options);
+    }
+
+    /// Sets how the pages are numbered.
+    ///
+    /// The page numbers are written as PDF page labels (so viewers show `ii` rather than `2`) and,
+    /// unless the footer template is `None`, drawn centred at the bottom of each page.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{
+    ///     PageNumberSection, PageNumberStyle, PageNumbering, PDFComposer, PDFComposerStruct,
+    /// };
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Number the cover, contents and preface (pages 1 to 3) i, ii, iii and restart at 1 on page 4
+    /// my_pdf_doc.set_page_numbering(PageNumbering::sections([
+    ///     PageNumberSection::new(1, PageNumberStyle::LowerRoman),
+    ///     PageNumberSection::new(4, PageNumberStyle::Decimal),
+    /// ]));
+    /// ```
+    fn set_page_numbering(&mut self, numbering: PageNumbering) {
+        self.page_numbering = Some(numbering);
+    }
+
+    /// Sets the page labels PDF viewers show in place of the physical page numbers, such as
+    /// `Cover`, then `i` to `iv` for the front matter and `1` to `120` for the body. A range
+    /// starts on a physical page and is numbered in a style (`PageLabelRange::numbered`), or is
+    /// labelled with text alone (`PageLabelRange::text`).
+    ///
+    /// The page labels replace those written by the page numbering, which still draws the page
+    /// numbers at the bottom of the pages. In merge mode the labels are set on the merged PDF (the
+    /// physical pages are those of the merged PDF), and replace the restarted page numbers of
+    /// `restart_page_numbers` too.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PageLabelRange, PageNumberStyle, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Cover, then i to iv for the contents and preface, then 1 onwards from page 6
+    /// my_pdf_doc.set_page_labels([
+    ///     PageLabelRange::text(1, "Cover"),
+    ///     PageLabelRange::numbered(2, PageNumberStyle::LowerRoman),
+    ///     PageLabelRange::numbered(6, PageNumberStyle::Decimal),
+    /// ]);
+    /// ```
+    fn set_page_labels<I: IntoIterator<Item = PageLabelRange>>(&mut self, ranges: I) {
+        let mut ranges: Vec<PageLabelRange> = ranges.into_iter().collect();
+        ranges.sort_by_key(|range| range.start_page);
+        self.page_labels = Some(ranges);
+    }
+
+    /// Sets single-PDF (merge) mode: each source file is generated as before, then the PDFs are
+    /// merged, in the order the source files were added, into one PDF in the output directory.
+    ///
+    /// Page numbering is applied to the merged PDF. With `restart_page_numbers` the page numbers
+    /// restart at 1 at the start of each chapter; the pages each chapter starts on (and the page
+    /// numbers shown there) are recorded in the `GenerationReport` returned by `generate_pdfs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{MergeMode, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Merge all the chapters into book.pdf, numbering the pages of each chapter from 1
+    /// let mut merge_mode = MergeMode::new("book.pdf");
+    /// merge_mode.restart_page_numbers = true;
+    /// my_pdf_doc.set_merge_mode(merge_mode);
+    /// ```
+    fn set_merge_mode(&mut self, merge_mode: MergeMode) {
+        self.merge_mode = Some(merge_mode);
+    }
+
+    /// Sets endnotes mode: markdown footnotes (`[^1]` references with `[^1]: ...` definitions) are
+    /// collected into a notes section at the end of the document, under the given title, with a
+    /// link from each note back to where it is referenced.
+    ///
+    /// In merge mode each source file is a chapter, so the notes are placed at the end of each chapter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Collect the footnotes at the end of each document
+    /// my_pdf_doc.set_endnotes("Notes");
+    /// ```
+    fn set_endnotes(&mut self, title: &str) {
+        self.endnotes_title = Some(title.trim().to_string());
+    }
+
+    /// Sets whether the lines of fenced code blocks are numbered, as in technical specifications
+    /// and code reviews. The numbers are drawn in a gutter next to the code, and are not part of
+    /// the text copied from the PDF.
+    ///
+    /// Lines can be highlighted, with or without line numbers, by putting them in braces at the
+    /// end of the info string of the code block: ` ```rust {3-5} ` highlights lines 3 to 5, and
+    /// ` ```rust {1,4-6} ` line 1 and lines 4 to 6.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Number the lines of the code blocks
+    /// my_pdf_doc.set_code_line_numbers(true);
+    /// ```
+    fn set_code_line_numbers(&mut self, line_numbers: bool) {
+        self.code_line_numbers = line_numbers;
+    }
+
+    /// Sets what happens to lines of code blocks that are too long for the page, which would
+    /// otherwise be cut off at the edge of the page without a trace:
+    ///
+    /// * `CodeWrap::Wrap` (the default) - Long lines carry on onto the next line.
+    /// * `CodeWrap::ShrinkToFit` - The text of each code block with long lines is made small
+    ///   enough for its longest line to fit, keeping one line of code to a line.
+    /// * `CodeWrap::Truncate` - Long lines are cut off at the edge of the page, ending with a `…`
+    ///   marker so it is clear something is missing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{CodeWrap, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Keep each line of code on one line, shrinking code blocks with long lines
+    /// my_pdf_doc.set_code_wrap(CodeWrap::ShrinkToFit);
+    /// ```
+    fn set_code_wrap(&mut self, code_wrap: CodeWrap) {
+        self.code_wrap = code_wrap;
+    }
+
+    /// Sets the function that resolves the page names of `[[wiki links]]` (as used by Obsidian and
+    /// other wikis) to the paths or URLs they link to.
+    ///
+    /// `[[Other Page]]`, `[[Other Page|link text]]` and `[[Other Page#Section]]` links are supported.
+    /// The resolver is given the page name (`Other Page`); any `#Section` is added to what it returns.
+    /// Without a resolver, links point to the PDF generated for the page (`Other%20Page.pdf`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Link wiki pages to the published site
+    /// my_pdf_doc.set_wiki_link_resolver(|page| {
+    ///     format!("https://wiki.example.com/{}", page.to_lowercase().replace(' ', "-"))
+    /// });
+    /// ```
+    fn set_wiki_link_resolver<F>(&mut self, resolver: F)
+    where
+        F: Fn(&str) -> String + Send + Sync + 'static,
+    {
+        self.wiki_link_resolver = Some(Arc::new(resolver));
+    }
+
+    /// Registers a shortcode: `{{< name arguments >}}` in the markdown is replaced with the HTML
+    /// the handler returns. Registering a name again replaces its handler.
+    ///
+    /// The handler is given the `ShortcodeInvocation`: the positional and `key=value` arguments
+    /// (front matter placeholders in them are already filled in) and, for a shortcode written with a
+    /// closing tag (`{{< name >}}...{{< /name >}}`), the markdown between the tags. When the handler
+    /// returns an error, the shortcode is left as written, with a warning. Built-in shortcodes
+    /// (`pagebreak`, `columns`, `columnbreak`, `barcode`, `chart`, `qr` and `table`) cannot be
+    /// replaced.
+    ///
+    /// The HTML cache (see `set_html_cache`) only knows the names of the registered shortcodes,
+    /// so clear it after changing what a handler returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // {{< badge "Draft" colour=red >}}
+    /// my_pdf_doc.register_shortcode("badge", |shortcode| {
+    ///     let text = shortcode.positional.first().ok_or("missing badge text")?;
+    ///     let colour = shortcode.named.get("colour").map_or("grey", String::as_str);
+    ///     Ok(format!("<span class=\"badge\" style=\"background: {}\">{}</span>", colour, text))
+    /// });
+    /// ```
+    fn register_shortcode<F>(&mut self, name: &str, handler: F)
+    where
+        F: Fn(&ShortcodeInvocation) -> Result<String, String> + Send + Sync + 'static,
+    {
+        self.shortcodes.insert(name.to_string(), Arc::new(handler));
+    }
+
+    /// Sets a function that rewrites the HTML body of each document after the markdown is
+    /// converted to HTML (and shortcodes and citations are rendered), before it is printed or
+    /// saved. Use it for structural edits markdown cannot express, such as wrapping tables in
+    /// containers or adding classes.
+    ///
+    /// The function is given the HTML of the body (without `<html>`, `<head>` or page styles) and
+    /// returns the HTML to use instead. It is run for every output format, including HTML cached
+    /// with `set_html_cache` (the cache holds the HTML from before the transform), and before
+    /// links between documents are rewritten for the output format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Wrap each table in a container, so wide tables can be styled
+    /// my_pdf_doc.set_html_transform(|html| {
+    ///     html.replace("<table>", "<div class=\"table-wrapper\"><table>")
+    ///         .replace("</table>", "</table></div>")
+    /// });
+    /// ```
+    fn set_html_transform<F>(&mut self, transform: F)
+    where
+        F: Fn(String) -> String + Send + Sync + 'static,
+    {
+        self.html_transform = Some(Arc::new(transform));
+    }
+
+    /// Sets how PDF viewers show the documents when they are opened: the page layout (such as two
+    /// pages side by side for a booklet), the panel shown next to the pages (such as the
+    /// bookmarks), the reading and binding direction (right-to-left for Arabic and Hebrew booklets,
+    /// following the text direction when left unset) and the window options (fitting the window to
+    /// the page, hiding the toolbar, showing the title rather than the file name). Options left at
+    /// their defaults are left to the viewer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PageLayout, PageMode, PDFComposer, PDFComposerStruct, ViewerPreferences};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Open booklets two pages at a time, with the bookmarks panel visible
+    /// my_pdf_doc.set_viewer_preferences(ViewerPreferences {
+    ///     page_layout: Some(PageLayout::TwoPageRight),
+    ///     page_mode: Some(PageMode::UseOutlines),
+    ///     display_doc_title: true,
+    ///     ..Default::default()
+    /// });
+    /// ```
+    fn set_viewer_preferences(&mut self, preferences: ViewerPreferences) {
+        self.viewer_preferences = preferences;
+    }
+
+    /// Sets where the documents open, instead of the first page at whatever zoom the viewer
+    /// defaults to: a page (`OpenAction::Page`, starting at `1`) or a named destination such as a
+    /// heading id (`OpenAction::Destination`), at a `Zoom` (the whole page, the width or height
+    /// of the page, a percentage, or the viewer's current zoom). In merge mode the page is a page
+    /// of the merged PDF. A page or destination a document does not have is reported, and the
+    /// document opens as usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{OpenAction, PDFComposer, PDFComposerStruct, Zoom};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Open at the first content page (after the cover), fitting its width to the window
+    /// my_pdf_doc.set_open_action(OpenAction::Page(2, Zoom::FitWidth));
+    ///
+    /// // Or at the `# Contents {#contents}` heading, at actual size
+    /// my_pdf_doc.set_open_action(OpenAction::Destination(
+    ///     "contents".to_string(),
+    ///     Zoom::Percent(100),
+    /// ));
+    /// ```
+    fn set_open_action(&mut self, open_action: OpenAction) {
+        self.open_action = Some(open_action);
+    }
+
+    /// Sets a QR code in the bottom margin of every page, typically linking back to the online
+    /// version of a printed handout or ticket.
+    ///
+    /// `{{placeholder}}` references in `data` are replaced with the YAML Front Matter values of each
+    /// document, so every PDF gets its own QR code. The QR code sits at the outer edge of the text,
+    /// filling most of the bottom margin (up to one inch square), so give the pages a bottom margin
+    /// of at least half an inch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Link every page to the online version of the document
+    /// my_pdf_doc.set_qr_code_footer("https://example.com/{{permalink}}");
+    /// ```
+    fn set_qr_code_footer(&mut self, data: &str) {
+        self.qr_code_footer = Some(data.trim().to_string());
+    }
+
+    /// Sets a watermark, such as `DRAFT` or `CONFIDENTIAL`, drawn in large see-through grey letters
+    /// diagonally across every page, on top of the content.
+    ///
+    /// `{{placeholder}}` references in `text` are replaced with the YAML Front Matter values of each
+    /// document, so a review copy can carry its reviewer or version. The watermark is drawn in the
+    /// standard 14 font matching the document font, so characters outside Windows-1252 are shown as
+    /// `?`. In merge mode, each chapter is watermarked before the chapters are merged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Mark every page of the review copies
+    /// my_pdf_doc.set_watermark("DRAFT {{version}}");
+    /// ```
+    fn set_watermark(&mut self, text: &str) {
+        self.watermark = Some(text.trim().to_string());
+    }
+
+    /// Sets whether the source markdown file (front matter and all, as it was read) is embedded in
+    /// each PDF as an attachment, so the document carries its own provenance. PDF readers list it
+    /// in their attachments panel. Merged PDFs carry the source of every chapter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Every PDF carries the markdown it was generated from
+    /// my_pdf_doc.set_attach_source(true);
+    /// ```
+    fn set_attach_source(&mut self, attach: bool) {
+        self.attach_source = attach;
+    }
+
+    /// Sets whether the front matter of each document is embedded in its PDF as an attachment
+    /// (`<name>.yaml`), with the values it was generated with: environment variables expanded and
+    /// the date and computed placeholders (such as `word_count`) filled in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Every PDF carries the markdown and the front matter values it was generated with
+    /// my_pdf_doc.set_attach_source(true);
+    /// my_pdf_doc.set_attach_front_matter(true);
+    /// ```
+    fn set_attach_front_matter(&mut self, attach: bool) {
+        self.attach_front_matter = attach;
+    }
+
+    /// Adds a file to embed in every PDF as an attachment, such as the data CSV behind the charts,
+    /// a signature or the terms and conditions. The file name is the name of the attachment, and
+    /// the description (if not empty) is shown next to it in PDF readers. The file is read when
+    /// each PDF is generated; a file that cannot be read is reported and left out.
+    ///
+    /// Documents can add attachments of their own with the `attachments` front matter entry, a
+    /// list of paths (relative to the source document) or of `path` and `description` pairs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Every PDF carries the terms and conditions
+    /// my_pdf_doc.attach_file("legal/terms.pdf", "Terms and conditions");
+    /// ```
+    fn attach_file<P: AsRef<Path>>(&mut self, path: P, description: &str) {
+        let description = description.trim();
+        self.attachments.push(FileAttachment {
+            path: normalize_path(path.as_ref()),
+            description: (!description.is_empty()).then(|| description.to_string()),
+        });
+    }
+
+    /// Adds an unsigned signature field to every PDF, so a signing service (or a signer of your
+    /// own) can sign the generated documents where they are meant to be signed, without them being
+    /// authored again. The field is on page `page_number` (starting at `1`), in the rectangle
+    /// `[left, bottom, right, top]`, in points (72 to the inch) from the bottom left corner of the
+    /// page. Signing services refer to the field by its name, which must not have a period in it.
+    ///
+    /// In merge mode, the field is added to the merged PDF. A field on a page the PDF does not
+    /// have, or with the name of a field the PDF already has, is reported and left out.
+    /// `{{field signature "name"}}` places a signature field in the text instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // A 3 by 1 inch field, one inch from the left and bottom edges of page 2
+    /// my_pdf_doc.add_signature_field("customer_signature", 2, [72.0, 72.0, 288.0, 144.0]);
+    /// ```
+    fn add_signature_field(&mut self, name: &str, page_number: u32, rect: [f32; 4]) {
+        self.signature_fields.push(SignatureField {
+            name: name.to_string(),
+            page_number,
+            rect,
+        });
+    }
+
+    /// Sets the time stamping authority (TSA) the PDFs get a trusted timestamp from, so they have
+    /// a generation time anyone can verify. Once a PDF is complete, a hash of it is sent to the TSA
+    /// (RFC 3161) and the timestamp token it signs is embedded in the PDF as a document timestamp
+    /// (`ETSI.RFC3161`), which PDF readers show in their signatures panel. Signatures added later by
+    /// signing services (see `add_signature_field`) keep the timestamp valid.
+    ///
+    /// Document timestamps came with PDF 2.0 (`PDFFeature::DocumentTimestamp`), so with
+    /// `PDFVersion::Auto` the timestamped PDFs are PDF 2.0, and an older explicit version is
+    /// reported as an error.
+    ///
+    /// In merge mode, the merged PDF is timestamped. With a post-process command, the PDF is
+    /// timestamped after the command has run (see `set_post_process_command`). A PDF that cannot
+    /// be timestamped (the TSA cannot be reached, or refuses the request) is reported as an error,
+    /// and is not saved unless the post-process command already had it. As every timestamp is
+    /// different, timestamped PDFs are never identical (see `set_deterministic`).
+    ///
+    /// Needs the `timestamp` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Timestamp every PDF with a public time stamping authority
+    /// my_pdf_doc.set_timestamp_authority("https://freetsa.org/tsr");
+    /// ```
+    #[cfg(feature = "timestamp")]
+    fn set_timestamp_authority(&mut self, url: &str) {
+        self.timestamp_authority = Some(url.to_string());
+    }
+
+    /// Makes the PDFs conform to a standard, so they pass the checks of the people who receive them.
+    ///
+    /// With `Conformance::PdfX4` (PDF/X-4, for print production), every page gets a `TrimBox` and
+    /// `BleedBox` (the paper size), transparency groups get a colour space, and the PDF gets an
+    /// output intent (sRGB, unless `set_output_intent` sets another profile), XMP metadata, the
+    /// `GTS_PDFXVersion` and `Trapped` document information entries, and a document ID. The fonts
+    /// must be embedded: Chromium embeds the fonts of the text, but page numbers and running
+    /// headers are drawn with the standard PDF fonts, so a PDF using them is not generated (an
+    /// error says why). PDF/X-4 is based on PDF 1.6, so set the PDF version to `PDFVersion::V1_6`
+    /// or `PDFVersion::Auto`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{Conformance, PDFComposer, PDFComposerStruct, PDFVersion};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // PDF/X-4 for the print shop
+    /// my_pdf_doc.set_pdf_version(PDFVersion::Auto);
+    /// my_pdf_doc.set_conformance(Conformance::PdfX4);
+    /// ```
+    fn set_conformance(&mut self, conformance: Conformance) {
+        self.conformance = Some(conformance);
+    }
+
+    /// Sets the ICC colour profile the PDFs are meant to be printed (or shown) with. It is written
+    /// into the document catalog as an output intent, which colour-managed printing, PDF/X and PDF/A
+    /// need.
+    ///
+    /// `IccProfile::SRGB` is the colour space Chromium draws in. `IccProfile::FromFile(path)` reads an
+    /// RGB, CMYK or grey ICC profile, such as the CMYK profile of the printing press from the print
+    /// shop; its description becomes the output condition identifier. A profile that cannot be read
+    /// is reported as an error and the PDF is not generated. With `Conformance::PdfX4` and no output
+    /// intent set, the sRGB profile is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{IccProfile, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // The CMYK profile of the printing press
+    /// my_pdf_doc.set_output_intent(IccProfile::FromFile("profiles/PSOcoated_v3.icc".into()));
+    /// ```
+    fn set_output_intent(&mut self, icc_profile: IccProfile) {
+        self.output_intent = Some(icc_profile);
+    }
+
+    /// Sets how the raster images embedded in the PDFs are treated once Chromium has printed them.
+    ///
+    /// With `downsample`, images shown at a higher resolution than `max_dpi` (worked out from the
+    /// largest size each image is shown at on the page) are resampled down to `max_dpi`, which
+    /// shrinks PDFs built from phone photos a lot. JPEG images are written as JPEG again, and other
+    /// images are compressed without loss. CMYK JPEG images, image masks and images with a palette
+    /// are left as they are. `ImagePolicy::default()` downsamples to 300 DPI, the usual
+    /// resolution for print; 150 DPI is plenty for reading on screen.
+    ///
+    /// With `jpeg_quality`, JPEG images larger than 32 KB are recompressed at that quality (kept
+    /// only if they get smaller), and with `convert_lossless` as well, losslessly compressed
+    /// images (such as PNG images) are converted to JPEG too, which suits photos but not
+    /// screenshots or diagrams.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{ImagePolicy, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Small PDFs for reading on screen
+    /// my_pdf_doc.set_image_policy(ImagePolicy {
+    ///     max_dpi: 150,
+    ///     jpeg_quality: Some(75),
+    ///     ..ImagePolicy::default()
+    /// });
+    /// ```
+    fn set_image_policy(&mut self, image_policy: ImagePolicy) {
+        self.image_policy = Some(image_policy);
+    }
+
+    /// Sets whether identical inputs produce identical PDFs, byte for byte. Defaults to `false`.
+    ///
+    /// Chromium writes the time a PDF is printed and a random document ID into every PDF, so two
+    /// runs over the same sources never give the same bytes. When deterministic, the
+    /// `CreationDate` and `ModDate` entries are set to the time in the `SOURCE_DATE_EPOCH`
+    /// environment variable (1970-01-01 00:00:00 UTC if it is not set), and the document ID is a
+    /// SHA-256 digest of the finished PDF (its metadata included), so binary diffs between runs
+    /// only show real changes, whichever Rust release the PDFs were generated with.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Reproducible PDFs, for example to keep them under version control
+    /// my_pdf_doc.set_deterministic(true);
+    /// ```
+    fn set_deterministic(&mut self, deterministic: bool) {
+        self.deterministic = deterministic;
+    }
+
+    /// Sets very large documents to be rendered `sections_per_chunk` H1 sections at a time, each
+    /// chunk in its own Chromium tab, with the pages stitched together afterwards.
+    ///
+    /// A document of thousands of pages rendered in one tab can run Chromium out of memory. With
+    /// chunked rendering, a document is split before its H1 headings (only those outside lists,
+    /// tables and other blocks) into chunks of whole sections, and each chunk starts on a new
+    /// page. The outline, tagged structure and named destinations of the chunks are stitched
+    /// together, and page numbers and running headers are added to the stitched PDF, so page
+    /// numbering carries on through the whole document. Documents with no more H1 sections than
+    /// `sections_per_chunk` are rendered in one go.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Render a 1,500 page manual ten chapters at a time
+    /// my_pdf_doc.set_chunked_rendering(10);
+    /// ```
+    fn set_chunked_rendering(&mut self, sections_per_chunk: usize) {
+        self.chunked_rendering = Some(sections_per_chunk.max(1));
+    }
+
+    /// Sets the number of threads the documents are generated on, in a thread pool of their own
+    /// rather than the global rayon thread pool. `0` uses one thread per CPU.
+    ///
+    /// The thread pool is built for each call to `generate_pdfs()` (and the other generation
+    /// methods) and dropped afterwards, so the crate does not compete with an application that
+    /// keeps the global thread pool busy. Each thread renders with its own Chromium instance, so
+    /// fewer threads also means less memory.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Generate two documents at a time
+    /// my_pdf_doc.set_thread_count(2);
+    /// ```
+    fn set_thread_count(&mut self, threads: usize) {
+        self.generation_threads = GenerationThreads::Count(threads);
+    }
+
+    /// Sets the rayon thread pool the documents are generated on, rather than the global rayon
+    /// thread pool.
+    ///
+    /// Useful in servers that already run a thread pool for background work: the documents are
+    /// generated on its threads, alongside its other work.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use rayon::ThreadPoolBuilder;
+    /// use std::sync::Arc;
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // The thread pool the application uses for background work
+    /// let background_pool = Arc::new(ThreadPoolBuilder::new().num_threads(4).build().unwrap());
+    /// my_pdf_doc.set_thread_pool(Arc::clone(&background_pool));
+    /// ```
+    fn set_thread_pool(&mut self, thread_pool: Arc<ThreadPool>) {
+        self.generation_threads = GenerationThreads::Pool(thread_pool);
+    }
+
+    /// Sets whether the documents are generated in parallel (`ProcessingMode::Parallel`, the
+    /// default) or one after another (`ProcessingMode::Sequential`).
+    ///
+    /// Sequential processing generates one document at a time, in source order (see
+    /// `set_source_ordering`), on the calling thread without rayon at all. Only one Chromium instance runs at a
+    /// time, which suits machines with little memory, and the console output of each document
+    /// comes out in order rather than interleaved. The thread settings (`set_thread_count`,
+    /// `set_thread_pool`) only apply to parallel processing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, ProcessingMode};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // One document at a time, with the console output in order
+    /// my_pdf_doc.set_processing_mode(ProcessingMode::Sequential);
+    /// ```
+    fn set_processing_mode(&mut self, processing_mode: ProcessingMode) {
+        self.processing_mode = processing_mode;
+    }
+
+    /// Sets whether generation stops once a document fails.
+    ///
+    /// By default, a document that fails (a missing file, invalid front matter, a browser crash)
+    /// does not stop the others: every document is attempted, the failures are summarised at the
+    /// end of the run (on stderr), and each failed document has its `error` in the
+    /// `GenerationReport`. With fail fast, the documents not started yet once one fails are not
+    /// generated (their `error` says so), and in merge mode nothing is merged, which suits CI
+    /// builds that should stop at the first problem. Documents being generated in parallel at the
+    /// time are finished.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Stop at the first document that fails
+    /// my_pdf_doc.set_fail_fast(true);
+    /// ```
+    fn set_fail_fast(&mut self, fail_fast: bool) {
+        self.fail_fast = fail_fast;
+    }
+
+    /// Sets the order the source files are generated in (`SourceOrdering::AsAdded`, the default).
+    ///
+    /// In merge mode this is the order of the chapters, and so the page order, of the merged PDF.
+    /// It is also the order of the "Files:" list, of the results and of the `validate_sources`
+    /// reports, so batch logs come out the same on every run.
+    ///
+    /// * `SourceOrdering::Alphabetical` sorts by file path, ignoring case.
+    /// * `SourceOrdering::ByFrontMatterKey` sorts by a YAML Front Matter value, such as
+    ///   `weight: 10`. Numbers come first, lowest first, then text alphabetically. Documents
+    ///   without the key come last.
+    ///
+    /// The sort is stable: documents that compare equal stay in the order they were added.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, SourceOrdering};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Chapters in the order of their `weight` front matter value
+    /// my_pdf_doc.set_source_ordering(SourceOrdering::ByFrontMatterKey("weight".to_string()));
+    /// ```
+    fn set_source_ordering(&mut self, source_ordering: SourceOrdering) {
+        self.source_ordering = source_ordering;
+    }
+
+    /// Sets the directory the rendered HTML of each document is cached in, so repeated runs (and
+    /// the preview server) only convert the markdown of documents that changed to HTML. Chromium
+    /// still prints every PDF.
+    ///
+    /// The HTML is cached under a hash of the markdown, the front matter, the rendering settings,
+    /// the PDF Composer version and the modification times of the files near the document
+    /// (images, CSV files, bibliographies). Any change gives a new entry, so stale HTML is never
+    /// used. Old entries are left behind; delete the directory to clear the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Keep the rendered HTML between runs
+    /// my_pdf_doc.set_html_cache("target/pdf_composer_cache");
+    /// ```
+    fn set_html_cache<T: OutputDirectory>(&mut self, cache_directory: T) {
+        self.html_cache = Some(cache_directory.convert());
+    }
+
+    /// Sets how long the persistent browser (see `with_persistent_browser`) is kept alive without
+    /// being used before it is shut down, turning the persistent browser on if it is not.
+    /// Chromium is launched again when the next PDF is generated. A running browser is shut down
+    /// when the idle timeout is changed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::time::Duration;
+    ///
+    /// // Create a PDFComposer instance that keeps Chromium running between calls
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::with_persistent_browser();
+    ///
+    /// // Shut Chromium down after a minute without requests
+    /// my_pdf_doc.set_browser_idle_timeout(Duration::from_secs(60));
+    /// ```
+    fn set_browser_idle_timeout(&mut self, idle_timeout: Duration) {
+        self.persistent_browser = Some(Arc::new(PersistentBrowser::new(idle_timeout)));
+    }
+
+    /// Sets the root relative links and images are resolved against when the PDFs are printed,
+    /// instead of the directory of each source file.
+    ///
+    /// * A directory (`&Path`, `PathBuf`, or a string without a scheme): images are embedded
+    ///   from it and links point to its files.
+    /// * A URL (a string with a scheme, such as `https://cdn.example.com/docs/`): images are
+    ///   loaded from it and links point to it. Paths starting with `/` are relative to its host.
+    ///
+    /// Links to other source documents and `#heading` links are not affected. HTML documents and
+    /// e-books keep embedding the images next to each source file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Images and links are relative to the published site
+    /// my_pdf_doc.set_asset_base("https://docs.example.com/guide/");
+    /// ```
+    fn set_asset_base<T: Into<PathOrUrl>>(&mut self, asset_base: T) {
+        self.asset_base = Some(asset_base.into());
+    }
+
+    /// Sets a CSS file whose styles are added after the built-in styles (page size, font,
+    /// callouts, tables etc) of every PDF, HTML document and preview, so they can override them.
+    /// The file is read each time a document is generated, and a file that cannot be read is
+    /// left out with a warning. Relative `url()` references in it are not resolved, so fonts and
+    /// images are best given as `data:` or `https:` URLs. E-books keep their own styles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // House styles for headings, tables and links
+    /// my_pdf_doc.set_stylesheet("styles/print.css");
+    /// ```
+    fn set_stylesheet<P: AsRef<Path>>(&mut self, stylesheet: P) {
+        self.stylesheet = Some(normalize_path(stylesheet.as_ref()));
+    }
+
+    /// Sets what happens when a PDF cannot be saved because the file is locked by another process,
+    /// which is common on Windows while the previous version is open in a PDF viewer.
+    ///
+    /// * `LockedFilePolicy::Fail` (the default) reports that the file is open by another process.
+    /// * `LockedFilePolicy::Retry` tries again a number of times, waiting in between, giving time
+    ///   to close the viewer.
+    /// * `LockedFilePolicy::AlternateName` saves the PDF as `report (1).pdf` (or the next free
+    ///   name) instead, and the generation report has the path it was saved to.
+    ///
+    /// The policy applies to the PDF of each source file and to the merged PDF of merge mode.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{LockedFilePolicy, PDFComposer, PDFComposerStruct};
+    /// use std::time::Duration;
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Try saving for up to 30 seconds while the PDF is open in a viewer
+    /// my_pdf_doc.set_locked_file_policy(LockedFilePolicy::retry(10, Duration::from_secs(3)));
+    /// ```
+    fn set_locked_file_policy(&mut self, locked_file_policy: LockedFilePolicy) {
+        self.locked_file_policy = locked_file_policy;
+    }
+
+    /// Sets a command run on every generated PDF once it is saved, so external optimizers and
+    /// validators (such as `qpdf` or `veraPDF`) slot into the pipeline. The placeholders in the
+    /// command are replaced for each PDF: `{output}` with its path, `{name}` with its file name
+    /// without `.pdf`, and `{dir}` with the directory it is in.
+    ///
+    /// The command is run directly, not by a shell: it is split into arguments at whitespace
+    /// (quote an argument with spaces in `"` or `'`), and a path with spaces stays one argument.
+    /// A command that cannot be run, or exits with a failure status, is reported as an error (with
+    /// what the command wrote to its standard error) and the document counts as not generated, but
+    /// the PDF is kept to look into.
+    ///
+    /// In merge mode, the command is run on the merged PDF. With a time stamping authority set
+    /// (see `set_timestamp_authority`), the PDF is timestamped after the command, so the timestamp
+    /// covers the PDF as the command left it. The timestamped PDF is saved again as a whole, which
+    /// keeps its content but not the layout of the file (such as the linearization of
+    /// `qpdf --linearize`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Linearize every PDF (for fast web view) in place with qpdf
+    /// my_pdf_doc.set_post_process_command("qpdf --linearize --replace-input {output}");
+    /// ```
+    fn set_post_process_command(&mut self, command: &str) {
+        self.post_process_command = Some(command.to_string());
+    }
+
+    /// Sets the uploader every generated file is published with once generation is done, so services
+    /// get the URLs of their documents in the `GenerationReport` (`uploads`) instead of walking the
+    /// output directory. An uploader is anything implementing the `Uploader` trait, including a
+    /// function or closure taking the path of the file and its `UploadMetadata` (the source
+    /// document, media type and, for PDFs, document information entries) and returning the URL.
+    /// With the `s3` feature, `S3Uploader` uploads to an Amazon S3 (or S3 compatible) bucket.
+    ///
+    /// Every output format is uploaded; in merge mode, only the merged files. The files are
+    /// uploaded after all of them are generated, and those that fail to upload are reported (and
+    /// listed in `failed_uploads`) without stopping the others.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, UploadMetadata, Url};
+    /// use std::error::Error;
+    /// use std::path::Path;
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Publish the PDFs with your own storage
+    /// my_pdf_doc.set_uploader(
+    ///     |path: &Path, _metadata: &UploadMetadata| -> Result<Url, Box<dyn Error + Send + Sync>> {
+    ///         let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    ///         Ok(Url::parse("https://docs.example.com/pdfs/")?.join(&file_name)?)
+    ///     },
+    /// );
+    /// ```
+    fn set_uploader<U: Uploader + 'static>(&mut self, uploader: U) {
+        self.uploader = Some(Arc::new(uploader));
+    }
+
+    /// Sets the environment variables that `${NAME}` references expand to, in the front matter
+    /// string values and the markdown of each document (mail merge templates included). Build
+    /// metadata such as `${CI_COMMIT_SHA}` can then flow into the document and its document
+    /// information entries.
+    ///
+    /// Expansion is off until this is called. Only the variables listed are expanded, so secrets
+    /// in the environment never reach a document; references to other variables, or to variables
+    /// that are not set, are left as written. An empty list turns expansion off again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // `version: "${CI_COMMIT_SHA}"` in the front matter becomes the commit being built
+    /// my_pdf_doc.set_env_var_allow_list(&["CI_COMMIT_SHA", "CI_PIPELINE_ID"]);
+    /// ```
+    fn set_env_var_allow_list(&mut self, names: &[&str]) {
+        self.env_var_allow_list = names.iter().map(|name| name.to_string()).collect();
+    }
+
+    /// Sets the time zone (as an offset from UTC) and the language of month and day names of the
+    /// date placeholders:
+    ///
+    /// * `{{today}}` - The date the document is generated, such as `2024-05-01`.
+    /// * `{{now}}` - The date and time the document is generated, such as `2024-05-01 09:30`.
+    /// * `{{build_date "format"}}` - The date and time in a `strftime` style format, such as
+    ///   `{{build_date "%e %B %Y"}}` for `1 May 2024` (`%Y`, `%y`, `%m`, `%B`, `%b`, `%d`, `%e`,
+    ///   `%A`, `%a`, `%j`, `%H`, `%I`, `%M`, `%S`, `%p`, `%z` and `%%`).
+    ///
+    /// Dates are in UTC and English by default. With deterministic output (see
+    /// `set_deterministic`) they are the `SOURCE_DATE_EPOCH` time, so the documents stay
+    /// reproducible. A `today` or `now` front matter entry takes precedence over the placeholder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{DateLocale, DateOptions, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Dates in Central European Time, with French month and day names
+    /// my_pdf_doc.set_date_options(DateOptions {
+    ///     utc_offset_minutes: 60,
+    ///     locale: DateLocale::Fr,
+    /// });
+    /// ```
+    fn set_date_options(&mut self, date_options: DateOptions) {
+        self.date_options = date_options;
+    }
+
+    /// Sets the locale (a language tag, such as `de-DE`, `fr`, or `en-GB`) the dates and numbers
+    /// of the placeholders are written in:
+    ///
+    /// * `{{today}}` and `{{now}}` - In the date (and time) format of the locale, such as
+    ///   `01.05.2024` and `01.05.2024 09:30` for `de-DE`, rather than `2024-05-01`.
+    /// * `{{date key}}` - A front matter date (`2024-05-01`, optionally with a time) in the date
+    ///   format of the locale, or `{{date key "%e %B %Y"}}` in a format of its own (see
+    ///   `set_date_options` for the conversion specifications).
+    /// * `{{number key}}` - A front matter number with the decimal and group separators of the
+    ///   locale, such as `1.234,5` for `de-DE`, or `{{number key 2}}` with two decimal places,
+    ///   `1.234,50`.
+    ///
+    /// The month and day names (`%B`, `%b`, `%A` and `%a`) are written in the language of the
+    /// locale too, as `set_date_options` would. English, German, Spanish, French, Italian, Dutch and
+    /// Portuguese are supported; `en` alone is American English (`05/01/2024`).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` if the locale is set.
+    /// * `Err(message)` if the language of the locale is not supported (the locale is unchanged).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Dates such as 01.05.2024 and numbers such as 1.234,50
+    /// my_pdf_doc.set_locale("de-DE").unwrap();
+    /// assert!(my_pdf_doc.set_locale("xx").is_err());
+    /// ```
+    fn set_locale(&mut self, locale: &str) -> Result<(), String> {
+        let locale: Locale = locale.parse()?;
+        self.date_options.locale = locale.date_locale;
+        self.locale = Some(locale);
+        Ok(())
+    }
+
+    /// Sets whether the progress messages (the files processed, the documents generated and the
+    /// metadata of each PDF) are left out. They are printed to stdout, so a quiet composer leaves
+    /// stdout to the application, for example to stream a PDF to a pipe. Warnings and errors are
+    /// still printed, to stderr.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Print nothing but warnings and errors
+    /// my_pdf_doc.set_quiet(true);
+    /// ```
+    fn set_quiet(&mut self, quiet: bool) {
+        self.console_output = match quiet {
+            true => ConsoleOutput::Quiet,
+            false => ConsoleOutput::Normal,
+        };
+    }
+
+    /// Sets how much is printed to the console while generating:
+    ///
+    /// * `ConsoleOutput::Quiet` - Only warnings and errors, to stderr (the same as `set_quiet(true)`).
+    /// * `ConsoleOutput::Normal` - The progress messages too: the files processed, the documents
+    ///   generated and the metadata of each PDF, to stdout (the default).
+    /// * `ConsoleOutput::Verbose` - The settings of the composer (see `summary`), the time each
+    ///   document took to generate and the HTML read from the cache (see `set_html_cache`) too.
+    ///
+    /// Whichever level, the messages are only coloured when both stdout and stderr are terminals
+    /// and the `NO_COLOR` environment variable is not set (`CLICOLOR_FORCE=1` colours them
+    /// anyway), so logs written to files and CI have no ANSI escapes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{ConsoleOutput, PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Print the settings and the time each document took as well
+    /// my_pdf_doc.set_console_output(ConsoleOutput::Verbose);
+    /// ```
+    fn set_console_output(&mut self, console_output: ConsoleOutput) {
+        self.console_output = console_output;
+    }
+
+    /// Sets the writer the progress events of every generation are written to, as NDJSON (one
+    /// JSON object per line, flushed as it is written), so wrapper tools and CI can follow the
+    /// progress without parsing the coloured messages.
+    ///
+    /// Each event has its kind in `event`:
+    ///
+    /// * `start` - A source file started generating, with its path in `file`.
+    /// * `finish` - A source file was generated, with the generated documents in `outputs`.
+    /// * `skip` / `error` - A source file was skipped or failed, with the reason in `message`.
+    /// * `summary` - Every source file has been attempted, with the `generated`, `skipped` and
+    ///   `failed` counts.
+    ///
+    /// In parallel generation the events of different files are interleaved, but each event is a
+    /// whole line. The progress messages are still printed to stdout unless the composer is quiet
+    /// (see `set_quiet`), so set both when writing the events to stdout.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::io;
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Progress events on stdout, instead of the progress messages
+    /// my_pdf_doc.set_quiet(true);
+    /// my_pdf_doc.set_progress_writer(io::stdout());
+    /// ```
+    fn set_progress_writer<W: Write + Send + 'static>(&mut self, writer: W) {
+        self.progress_writer = Some(Arc::new(Mutex::new(writer)));
+    }
+
+    /// Sets the profile of the configuration file applied by `load_config` and `apply_config`, so
+    /// one source tree gives both review copies and the release.
+    ///
+    /// A profile is a named set of settings in the `profile` table of the configuration file
+    /// (`[profile.draft]` in `pdf_composer.toml`), applied on top of the other settings of the
+    /// file: its `doc_info` and `defaults` entries are added to those of the file, and its other
+    /// settings replace them. Set the profile before loading the configuration file, which fails
+    /// if it has no profile of that name.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // The settings of the file, with those of its [profile.draft] table on top
+    /// my_pdf_doc.set_profile("draft");
+    /// my_pdf_doc.load_config("pdf_composer.toml")?;
+    /// my_pdf_doc.generate_pdfs();
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn set_profile(&mut self, profile: &str) {
+        self.profile = Some(profile.trim().to_string());
+    }
+
+    /// Adds a flag, such as `internal`, that the conditional blocks of the documents are kept for,
+    /// so one source gives both the internal and the customer PDF.
+    ///
+    /// `{{#if internal}}…{{/if}}` (or `<!-- if:internal -->…<!-- endif -->`, which markdown
+    /// previews do not show) is kept when `internal` is a flag or the profile (see `set_profile`),
+    /// and removed otherwise. `{{else}}` (`<!-- else -->`) starts a part kept the other way round,
+    /// `!internal` turns the condition around, and blocks can be nested. Tags in fenced code blocks
+    /// are left alone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Keep the {{#if internal}} notes in the PDFs
+    /// my_pdf_doc.add_flag("internal");
+    /// ```
+    fn add_flag(&mut self, flag: &str) {
+        let flag = flag.trim().to_string();
+        if !flag.is_empty() && !self.flags.contains(&flag) {
+            self.flags.push(flag);
+        }
+    }
+
+    /// Sets up the composer from a configuration file (`pdf_composer.toml`, or a `.yaml` / `.yml`
+    /// file with the same settings), such as the one `pdf-composer init` writes:
+    ///
+    /// * `sources` - The source documents, added to the source files.
+    /// * `output_directory` - The directory the generated files are written to.
+    /// * `paper_size` and `orientation` - A paper size name (`A4`, `Letter` etc) and `portrait` or `landscape`.
+    /// * `margins` - The page margins in millimetres, as `set_margins` takes them.
+    /// * `language` - The language of the documents.
+    /// * `locale` - The locale the dates and numbers of the placeholders are written in (see `set_locale`).
+    /// * `doc_info` - Document information entries mapped to front matter keys, replacing the entries.
+    /// * `defaults` - Front matter values the documents get when they do not set them (see `set_front_matter_defaults`).
+    /// * `stylesheet` - A CSS file added after the built-in styles (see `set_stylesheet`).
+    /// * `watermark` - Text drawn diagonally across every page (see `set_watermark`), or `""` for none.
+    /// * `image_max_dpi` and `jpeg_quality` - The resolution the images are downsampled to and the
+    ///   quality JPEG images are recompressed at (see `set_image_policy`).
+    /// * `flags` - Flags the conditional blocks of the documents are kept for, added to the flags
+    ///   (see `add_flag`).
+    /// * `profile` - Named sets of the settings above (other than `sources`), applied on top of the
+    ///   others when selected with `set_profile`.
+    ///
+    /// Paths are relative to the configuration file, and settings that are not in the file are
+    /// left as they are. `_pdf_composer.yaml` files in the directories of the source documents
+    /// (with the same settings, other than `sources`, `output_directory` and `profile`) apply on
+    /// top of these to the documents beneath them, the nearest directory winning. Nothing is
+    /// changed if the file cannot be read, has a setting that is not recognised, has a value that
+    /// is not valid, or has no profile of the name set.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // The settings and source documents of the project
+    /// my_pdf_doc.load_config("pdf_composer.toml")?;
+    /// my_pdf_doc.generate_pdfs();
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn load_config<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let config = read_config(path.as_ref())?;
+        self.apply_config(&config, path.as_ref().parent().unwrap_or(Path::new("")))
+    }
+
+    /// Sets up the composer from the settings of a configuration file that has already been read
+    /// (see `read_config`), with its paths relative to `config_directory`. This is `load_config`
+    /// for settings that are changed before they are applied, such as a command line replacing
+    /// the source documents of the file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerConfig, PDFComposerStruct, PaperSize};
+    /// use std::path::Path;
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// let config = PDFComposerConfig {
+    ///     paper_size: Some("A5".to_string()),
+    ///     stylesheet: Some("styles/print.css".into()),
+    ///     ..Default::default()
+    /// };
+    /// my_pdf_doc.apply_config(&config, Path::new("project")).unwrap();
+    /// assert!(matches!(my_pdf_doc.paper_size(), PaperSize::A5));
+    /// ```
+    fn apply_config(
+        &mut self,
+        config: &PDFComposerConfig,
+        config_directory: &Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        configure(self, config, config_directory)
+    }
+
+    /// Returns the source files, in the order they were added (with their paths normalized).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::path::PathBuf;
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.add_source_files(vec![PathBuf::from("source/file1.md")]);
+    ///
+    /// assert_eq!(my_pdf_doc.source_files().len(), 1);
+    /// ```
+    fn source_files(&self) -> &[PathBuf] {
+        &self.fmy_source_files
+    }
+
+    /// Returns the directory the documents are saved to.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::path::Path;
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_output_directory("output");
+    ///
+    /// assert_eq!(my_pdf_doc.output_directory(), Path::new("output"));
+    /// ```
+    fn output_directory(&self) -> &Path {
+        &self.output_directory
+    }
+
+    /// Returns the formats of the generated documents, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{OutputFormat, PDFComposer, PDFComposerStruct};
+    ///
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// assert_eq!(my_pdf_doc.output_formats(), &[OutputFormat::Pdf]);
+    /// ```
+    fn output_formats(&self) -> &[OutputFormat] {
+        &self.output_formats
+    }
+
+    /// Returns the file name (without the extension of the output format) of the documents
+    /// generated from a source file: the name given by the function set with `set_output_namer`,
+    /// or else the source file name without its source extension (see `set_source_extensions`), or
+    /// without its last extension (`Path::file_stem`) when it has none of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::path::Path;
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// assert_eq!(my_pdf_doc.output_name(Path::new("docs/report.markdown")), "report");
+    /// assert_eq!(my_pdf_doc.output_name(Path::new("notes.txt")), "notes");
+    /// assert_eq!(my_pdf_doc.output_name(Path::new("guide.en.md")), "guide.en");
+    ///
+    /// my_pdf_doc.set_source_extensions(&[".md", ".en.md"]);
+    /// assert_eq!(my_pdf_doc.output_name(Path::new("guide.en.md")), "guide");
+    /// ```
+    fn output_name(&self, source_file: &Path) -> String {
+        output_name(
+            source_file,
+            &self.source_extensions,
+            self.output_namer.as_ref(),
+        )
+    }
+
+    /// Returns the PDF version (`PDFVersion::Auto` picks the lowest version the features need
+    /// when each PDF is generated).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PDFVersion};
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_pdf_version(PDFVersion::V2_0);
+    ///
+    /// assert_eq!(my_pdf_doc.pdf_version(), PDFVersion::V2_0);
+    /// ```
+    fn pdf_version(&self) -> PDFVersion {
+        self.pdf_version
+    }
+
+    /// Returns the paper size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PaperSize};
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_paper_size(PaperSize::A5);
+    ///
+    /// assert!(matches!(my_pdf_doc.paper_size(), PaperSize::A5));
+    /// ```
+    fn paper_size(&self) -> PaperSize {
+        self.paper_size
+    }
+
+    /// Returns the paper orientation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct, PaperOrientation};
+    ///
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// assert!(matches!(my_pdf_doc.orientation(), PaperOrientation::Portrait));
+    /// ```
+    fn orientation(&self) -> PaperOrientation {
+        self.orientation
+    }
+
+    /// Returns the page margins (top, right, bottom, left) in inches. With mirrored margins they
+    /// are top, outer, bottom, inner.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_margins("20");
+    ///
+    /// // 20 mm, in inches
+    /// let top_margin = my_pdf_doc.margins()[0];
+    /// assert_eq!((top_margin * 25.4).round(), 20.0);
+    /// ```
+    fn margins(&self) -> PageMargins {
+        self.margins
+    }
+
+    /// Returns the standard font.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{FontsStandard, PDFComposer, PDFComposerStruct};
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_font(FontsStandard::Courier);
+    ///
+    /// assert!(matches!(my_pdf_doc.font(), FontsStandard::Courier));
+    /// ```
+    fn font(&self) -> FontsStandard {
+        self.font
+    }
+
+    /// Returns the language tag, or `None` if it is not set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_language("en-GB");
+    ///
+    /// assert_eq!(my_pdf_doc.language(), Some("en-GB"));
+    /// ```
+    fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Returns the document information entries, mapped to the YAML Front Matter keys they are
+    /// filled from, or `None` if none are set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_doc_info_entries([("author", "author")]);
+    ///
+    /// let entries = my_pdf_doc.doc_info_entries().unwrap();
+    /// assert_eq!(entries.get("Author").map(String::as_str), Some("author"));
+    /// ```
+    fn doc_info_entries(&self) -> Option<&BTreeMap<String, String>> {
+        self.pdf_document_entries.as_ref()
+    }
+
+    /// Returns the merge mode, or `None` when one PDF is generated for each source file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{MergeMode, PDFComposer, PDFComposerStruct};
+    ///
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_merge_mode(MergeMode::new("book.pdf"));
+    ///
+    /// assert_eq!(my_pdf_doc.merge_mode().unwrap().file_name, "book.pdf");
+    /// ```
+    fn merge_mode(&self) -> Option<&MergeMode> {
+        self.merge_mode.as_ref()
+    }
+
+    /// Returns a `ConfigurationSummary` of the main settings. It displays as one setting per
+    /// line, so applications (and wrappers of PDF Composer) can show what is about to be
+    /// generated, or check the settings against their own rules.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a new PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.set_output_directory("output");
+    ///
+    /// // Show the settings
+    /// let summary = my_pdf_doc.summary();
+    /// println!("{}", summary);
+    /// assert!(summary.source_files.is_empty());
+    /// ```
+    fn summary(&self) -> ConfigurationSummary {
+        ConfigurationSummary {
+            source_files: self.fmy_source_files.clone(),
+            output_directory: self.output_directory.clone(),
+            output_formats: self.output_formats.clone(),
+            pdf_version: self.pdf_version,
+            paper_size: self.paper_size,
+            orientation: self.orientation,
+            margins: self.margins,
+            mirrored_margins: self.mirrored_margins,
+            font: self.font,
+            text_direction: self.text_direction,
+            language: self.language.clone(),
+            doc_info_entries: self.pdf_document_entries.clone().unwrap_or_default(),
+            required_front_matter_keys: self.required_front_matter_keys.clone(),
+            merged_file_name: self
+                .merge_mode
+                .as_ref()
+                .map(|merge_mode| merge_mode.file_name.clone()),
+            tagged_pdf: self.tagged_pdf,
+            conformance: self.conformance,
+            deterministic: self.deterministic,
+            html_cache: self.html_cache.clone(),
+            persistent_browser: self.persistent_browser.is_some(),
+        }
+    }
+
+    /// Generates PDF documents based on the configured settings and source files.
+    ///
+    /// Returns a `GenerationReport` with the PDF generated for each source file (and, in merge mode,
+    /// the merged PDF and the pages each chapter starts on).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a PDF generator instance
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Generate PDFs based on the configuration and source files
+    /// let report = my_pdf_doc.generate_pdfs();
+    /// println!("{} PDFs generated", report.generated_count());
+    /// ```
+    fn generate_pdfs(&self) -> GenerationReport {
+        // Handle case where no source files are set.
+        if self.fmy_source_files.is_empty() {
+            eprintln!(
+                "{}{}\n",
+                CROSS_MARK.on_red(),
+                "No source files set.".on_red()
+            );
+            return GenerationReport::default();
+        }
+
+        let source_files = ordered_source_files(&self.fmy_source_files, &self.source_ordering);
+        if self.console_output.is_verbose() {
+            println!("{}\n{}", "Settings:".cyan(), self.summary());
+        }
+        if !self.console_output.is_quiet() {
+            println!("{} {:#?}", "Files:".cyan(), &source_files);
+            println!(
+                "Files to process: {}\n",
+                &source_files.len().to_string().cyan()
+            );
+        }
+
+        // In merge mode, pages are numbered once all the chapters are merged
+        let chapter_page_numbering = match &self.merge_mode {
+            Some(_) => None,
+            None => self.page_numbering.clone(),
+        };
+
+        // Process each source file (in parallel, unless processing sequentially), collecting the
+        // results in source file order. A document that fails does not stop the others (unless
+        // failing fast), and the failures are summarised at the end.
+        let fail_fast = FailFast::new(self);
+        let outputs: Vec<(PathBuf, Vec<OutputResult>)> =
+            map_documents(self, source_files.iter().collect(), |document| {
+                // Extract filename from PathBuf.
+                let filename = document.display().to_string();
+
+                let output_files = fail_fast.generate(document, || {
+                    // Attempt to read metadata of the file.
+                    match fs::metadata(document) {
+                        Ok(_) => {
+                            // File exists, proceed with reading.
+                            if !self.console_output.is_quiet() {
+                                println!(
+                                    "File {} exists. {}",
+                                    filename.cyan(),
+                                    "Reading...".green()
+                                );
+                            }
+                            // If file exists, but is not a suitable yaml markdown file, no PDF is generated
+                            let source_document =
+                                read_source_document(&filename, self.console_output.is_quiet());
+                            // The `_pdf_composer.yaml` settings of the directories of the document
+                            let directory_settings = apply_directory_config(self, document);
+                            match (source_document, directory_settings) {
+                                // Render the markdown and build the document in each output format.
+                                (
+                                    Ok((yaml_btreemap, markdown_content)),
+                                    Ok((composer, overrides)),
+                                ) => generate_document(
+                                    &composer,
+                                    document,
+                                    &overrides,
+                                    yaml_btreemap,
+                                    &markdown_content,
+                                    chapter_page_numbering.clone(),
+                                ),
+                                (Err(error), _) => vec![Err(OutputError::failed(error))],
+                                (_, Err(error)) => {
+                                    eprintln!("{}{}", CROSS_MARK.red(), error.red());
+                                    vec![Err(OutputError::failed(error))]
+                                }
+                            }
+                        }
+                        Err(_) => {
+                            // File not found, print error message.
+                            eprintln!("File {} not found.", filename.red());
+                            vec![Err(OutputError::failed("file not found"))]
+                        }
+                    }
+                });
+
+                (document.to_path_buf(), output_files)
+            });
+
+        let mut report = GenerationReport {
+            documents: outputs
+                .iter()
+                .map(|(source_file, output_files)| {
+                    generated_document(source_file.clone(), output_files.clone())
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        // Merge mode: combine the generated chapters into a single PDF (one for each output format),
+        // unless failing fast and a chapter failed
+        if self.merge_mode.is_some() && self.fail_fast && fail_fast.has_failed() {
+            eprintln!(
+                "{}{}",
+                CROSS_MARK.red(),
+                "A document failed, so nothing was merged (fail fast).".red()
+            );
+        } else if let Some(merge_mode) = &self.merge_mode {
+            for (index, &output_format) in self.output_formats.iter().enumerate() {
+                let chapters: Vec<(PathBuf, PathBuf)> = outputs
+                    .iter()
+                    .filter_map(|(source_file, output_files)| {
+                        output_files
+                            .get(index)
+                            .cloned()
+                            .and_then(Result::ok)
+                            .map(|output_file| (source_file.clone(), output_file.path))
+                    })
+                    .collect();
+
+                if chapters.is_empty() {
+                    eprintln!(
+                        "{}{}",
+                        CROSS_MARK.red(),
+                        format!(
+                            "No {} documents were generated, so there is nothing to merge.",
+                            output_format
+                        )
+                        .red()
+                    );
+                    continue;
+                }
+
+                let merged = match output_format {
+                    OutputFormat::Pdf => merge_chapters(self, merge_mode, &chapters),
+                    OutputFormat::Html => {
+                        merge_html_chapters(merge_mode, &self.output_directory, &chapters)
+                            .map(|merged_file| (merged_file, Vec::new()))
+                    }
+                    #[cfg(feature = "epub")]
+                    OutputFormat::Epub => {
+                        epub::merge_epub_chapters(merge_mode, &self.output_directory, &chapters)
+                            .map(|merged_file| (merged_file, Vec::new()))
+                    }
+                    OutputFormat::Text(text_format) => merge_text_chapters(
+                        merge_mode,
+                        &self.output_directory,
+                        &chapters,
+                        text_format,
+                    )
+                    .map(|merged_file| (merged_file, Vec::new())),
+                };
+                match merged {
+                    Ok((merged_file, merged_chapters)) => {
+                        if !self.console_output.is_quiet() {
+                            println!(
+                                "\n{}{} chapters → {}",
+                                CHECK_MARK.to_string().green(),
+                                chapters.len().to_string().green(),
+                                merged_file.display().to_string().yellow()
+                            );
+                        }
+                        if !merge_mode.keep_chapter_pdfs {
+                            for (_source_file, chapter_pdf) in &chapters {
+                                let _ = fs::remove_file(chapter_pdf);
+                            }
+                        }
+                        if index == 0 {
+                            report.merged_file = Some(merged_file);
+                            report.chapters = merged_chapters;
+                        } else {
+                            report.additional_merged_files.push(merged_file);
+                        }
+                    }
+                    Err(error) => eprintln!(
+                        "{}{} {}",
+                        CROSS_MARK.red(),
+                        format!("Failed to merge the generated {} documents.", output_format).red(),
+                        error
+                    ),
+                }
+            }
+        }
+
+        upload_generated_files(self, &mut report);
+        report_summary(self, &report);
+
+        report
+    }
+
+    /// Generates one PDF for each data record (mail merge), from a single markdown template.
+    ///
+    /// Each record is a JSON object whose entries fill the `{{placeholders}}` of the template, on top of
+    /// the template's own front matter. `naming` is the file name of each PDF, with placeholders,
+    /// for example `invoice-{{number}}`.
+    ///
+    /// Returns a `GenerationReport` with the PDF generated for each record, in order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use serde_json::json;
+    ///
+    /// // Create a PDF generator instance
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// let records = vec![
+    ///     json!({ "number": "2024-0031", "customer": "Acme Ltd", "total": 120.5 }),
+    ///     json!({ "number": "2024-0032", "customer": "Globex", "total": 99 }),
+    /// ];
+    ///
+    /// // invoice-2024-0031.pdf, invoice-2024-0032.pdf
+    /// let report = my_pdf_doc.generate_from_records("templates/invoice.md", records, "invoice-{{number}}");
+    /// println!("{} invoices generated", report.generated_count());
+    /// ```
+    fn generate_from_records<P, I>(&self, template: P, records: I, naming: &str) -> GenerationReport
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = serde_json::Value>,
+    {
+        mail_merge::generate_from_records(
+            self,
+            template.as_ref(),
+            records.into_iter().collect(),
+            naming,
+        )
+    }
+
+    /// Generates the documents listed in a manifest file (`.yaml`, `.yml` or `.toml`), each with its
+    /// own settings, replacing a loop over `PDFComposerStruct`s in your own code.
+    ///
+    /// Every document uses the composer settings, except those its manifest entry sets: the file name
+    /// of the PDF (`output`), `paper_size`, `orientation`, document information entries (`doc_info`)
+    /// and a `cover` PDF put before the first page. Paths are relative to the manifest.
+    ///
+    /// Returns a `GenerationReport` with the PDF generated for each document, in manifest order.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a PDF generator instance
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // documents:
+    /// //   - docs/handbook.md
+    /// //   - source: docs/quick-start.md
+    /// //     output: quick-start-a5
+    /// //     paper_size: A5
+    /// //     cover: covers/quick-start.pdf
+    /// let report = my_pdf_doc.generate_from_manifest("manifest.yaml");
+    /// println!("{} PDFs generated", report.generated_count());
+    /// ```
+    fn generate_from_manifest<P: AsRef<Path>>(&self, manifest: P) -> GenerationReport {
+        manifest::generate_from_manifest(self, manifest.as_ref())
+    }
+
+    /// Generates the documents of a markdown source given as text, with its YAML Front Matter,
+    /// such as a document piped to a program on standard input. The text goes through the same
+    /// pipeline as a source file: the source is handled as if it were the file `name.md` in the
+    /// current directory, so its relative links and images are resolved against the current
+    /// directory, and the generated documents are named `name` (`name.pdf` etc).
+    ///
+    /// Returns a `GenerationReport` with the document generated from the source. Nothing is merged
+    /// in merge mode, and `set_attach_source` has no source file to attach.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::io::{self, Read};
+    ///
+    /// // Create a PDF generator instance
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // pdf_composer_pdfs/notes.pdf from the markdown on standard input
+    /// let mut source = String::new();
+    /// io::stdin().read_to_string(&mut source).unwrap();
+    /// let report = my_pdf_doc.generate_from_markdown(&source, "notes");
+    /// println!("{} PDFs generated", report.generated_count());
+    /// ```
+    fn generate_from_markdown(&self, source: &str, name: &str) -> GenerationReport {
+        let document = PathBuf::from(format!("{}.md", name));
+        let filename = document.display().to_string();
+
+        let output_files = FailFast::new(self).generate(&document, || {
+            parse_source_document(&filename, source, self.console_output.is_quiet())
+                .map(|(yaml_btreemap, markdown_content)| {
+                    generate_document(
+                        self,
+                        &document,
+                        &DocumentOverrides {
+                            output_file_name: Some(name.to_string()),
+                            ..DocumentOverrides::default()
+                        },
+                        yaml_btreemap,
+                        &markdown_content,
+                        self.page_numbering.clone(),
+                    )
+                })
+                .unwrap_or_else(|error| vec![Err(OutputError::failed(error))])
+        });
+
+        let mut report = GenerationReport {
+            documents: vec![generated_document(document, output_files)],
+            ..Default::default()
+        };
+        upload_generated_files(self, &mut report);
+        report_summary(self, &report);
+
+        report
+    }
+
+    /// Validates the source files without generating any PDFs, returning the diagnostics found for each file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::path::PathBuf;
+    ///
+    /// // Create a PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.add_source_files(vec![PathBuf::from("source/file1.md")]);
+    ///
+    /// // Report any problems found
+    /// for report in my_pdf_doc.validate_sources() {
+    ///     for diagnostic in &report.diagnostics {
+    ///         println!("{}: {}", report.source_file.display(), diagnostic);
+    ///     }
+    /// }
+    /// ```
+    fn validate_sources(&self) -> Vec<SourceDiagnostics> {
+        let source_files = ordered_source_files(&self.fmy_source_files, &self.source_ordering);
+        map_documents(self, source_files.iter().collect(), |document| {
+            validate_source(document, self)
+        })
+    }
+
+    /// Checks the environment before any PDFs are generated: that Chromium can be found,
+    /// the output directory is writable, the font resolves and the source files exist.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// // Create a PDF generator instance
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    ///
+    /// // Catch misconfiguration before starting a long batch
+    /// let report = my_pdf_doc.doctor();
+    /// for check in &report.checks {
+    ///     println!("[{}] {}: {}", check.status, check.name, check.detail);
+    /// }
+    /// ```
+    fn doctor(&self) -> DoctorReport {
+        run_doctor(self)
+    }
+
+    /// Renders the source files `iterations` times, timing each stage of generation, and reports
+    /// (and prints) the min, median and max time of each stage, so performance regressions
+    /// between releases can be measured.
+    ///
+    /// The stages are `read` (the source file and its front matter), `render` (markdown to HTML),
+    /// `build (<format>)` for each output format (Chromium and the PDF post-processing, for PDFs)
+    /// and the `total`, each summed over all the source files of an iteration.
+    ///
+    /// The documents are rendered one at a time, in source order, whatever the processing mode,
+    /// so the stages do not compete for the CPU. They are written to the output directory as
+    /// usual (each iteration overwrites the last), but not merged in merge mode.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    /// use std::path::PathBuf;
+    ///
+    /// // Create a PDF generator instance
+    /// let mut my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// my_pdf_doc.add_source_files(vec![PathBuf::from("source/file1.md")]);
+    ///
+    /// // Render the corpus five times and compare the stages with the last release
+    /// let report = my_pdf_doc.bench(5);
+    /// for stage in &report.stages {
+    ///     println!("{}: {:?} median", stage.stage, stage.median);
+    /// }
+    /// ```
+    fn bench(&self, iterations: usize) -> BenchReport {
+        run_bench(self, iterations)
+    }
+
+    /// Serves the HTML generated for a source document at `address`, with the same styles as the
+    /// PDF, and reloads the page in the browser whenever the document (or a file next to it, like an
+    /// image or a CSV file) changes. Nothing is printed to PDF, so CSS and templates can be tried out
+    /// quickly. Runs until the process is stopped.
+    ///
+    /// Requires the `preview` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use pdf_composer::{PDFComposer, PDFComposerStruct};
+    ///
+    /// let my_pdf_doc: PDFComposerStruct = PDFComposer::new();
+    /// // Open http://127.0.0.1:4000/ in a browser
+    /// my_pdf_doc.preview("docs/guide.md", "127.0.0.1:4000").unwrap();
+    /// ```
+    #[cfg(feature = "preview")]
+    fn preview<P: AsRef<Path>>(
+        &self,
+        source: P,
+        address: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        preview::run_preview(self, source.as_ref(), address)
+    }
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Warns when a custom document information entry name is close to a reserved one (see
+/// `doc_info_entry_near_miss`), as it is probably a mistake.
+fn warn_doc_info_entry_near_miss(doc_info_entry: &str) {
+    if let Some(reserved_entry) = doc_info_entry_near_miss(doc_info_entry) {
+        eprintln!(
+            "{}{}",
+            CROSS_MARK.yellow(),
+            format!(
+                "Document information entry {} is set as a custom entry; did you mean {}?",
+                doc_info_entry, reserved_entry
+            )
+            .yellow()
+        );
     }
 }
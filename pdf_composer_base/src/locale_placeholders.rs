@@ -0,0 +1,187 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use crate::date_variables::{days_from_civil, format_date, NOW_FORMAT, TODAY_FORMAT};
+use crate::utils::ESCAPED_PLACEHOLDER_PATTERN;
+use pdf_composer_definitions::date_options::DateOptions;
+use pdf_composer_definitions::locale::Locale;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function fills in the `{{date key}}` and `{{number key}}` placeholders of a document,
+/// writing front matter values in the conventions of the locale (see `set_locale`).
+///
+/// * `{{date key}}` - A front matter date (`2024-05-01`, optionally with a time, `2024-05-01
+///   09:30`) in the date format of the locale, or `{{date key "format"}}` in a `strftime` style
+///   format (see `format_date`), with the month and day names of the locale.
+/// * `{{number key}}` - A front matter number with the decimal and group separators of the
+///   locale, or `{{number key 2}}` rounded to a number of decimal places.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the `Locale` (and the `DateOptions`).
+/// * `yaml_btreemap` - The YAML Front Matter (or other data) of the document.
+/// * `markdown_content` - The markdown of the document, without the front matter.
+///
+/// # Returns
+///
+/// The markdown with the placeholders replaced.
+///
+/// # Remarks
+///
+/// Without a locale, dates are written as ISO 8601 dates (`2024-05-01`) and numbers with a `.` and
+/// no group separator. A placeholder whose value is missing, or is not a date or number, is left
+/// as it is written.
+pub fn fill_locale_placeholders(
+    composer: &PDFComposerStruct,
+    yaml_btreemap: &BTreeMap<String, Value>,
+    markdown_content: &str,
+) -> String {
+    if !markdown_content.contains("{{date") && !markdown_content.contains("{{number") {
+        return markdown_content.to_string();
+    }
+    let locale = composer.locale.as_ref();
+    let regex = Regex::new(&format!(
+        r#"{}|\{{\{{(date|number)\s+(\w+)(?:\s+"([^"]*)"|\s+(\d+))?\s*\}}\}}"#,
+        ESCAPED_PLACEHOLDER_PATTERN
+    ))
+    .unwrap();
+    regex
+        .replace_all(markdown_content, |captures: &Captures<'_>| {
+            let value = captures
+                .get(2)
+                .and_then(|key| yaml_btreemap.get(key.as_str()));
+            let formatted = match (captures.get(1).map(|helper| helper.as_str()), value) {
+                (Some("date"), Some(Value::String(date))) if captures.get(4).is_none() => {
+                    format_front_matter_date(
+                        date,
+                        composer.date_options,
+                        locale,
+                        captures.get(3).map(|format| format.as_str()),
+                    )
+                }
+                (Some("number"), Some(value)) if captures.get(3).is_none() => {
+                    let decimal_places = captures
+                        .get(4)
+                        .and_then(|decimal_places| decimal_places.as_str().parse().ok());
+                    format_number(value, locale, decimal_places)
+                }
+                _ => None,
+            };
+            // A missing value, or an escaped placeholder (see `unescape_placeholders`), is left as it is
+            formatted.unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+}
+
+/// This function writes a front matter date (`YYYY-MM-DD`, optionally followed by ` HH:MM` or
+/// `THH:MM:SS`) in a format, or the date (or date and time) format of the locale.
+///
+/// # Returns
+///
+/// The formatted date, or `None` if the value is not a date.
+fn format_front_matter_date(
+    date: &str,
+    date_options: DateOptions,
+    locale: Option<&Locale>,
+    format: Option<&str>,
+) -> Option<String> {
+    let regex =
+        Regex::new(r"^(\d{4})-(\d{2})-(\d{2})(?:[T ](\d{2}):(\d{2})(?::(\d{2}))?)?$").unwrap();
+    let captures = regex.captures(date.trim())?;
+    let number = |index: usize| -> i64 {
+        captures
+            .get(index)
+            .map_or(0, |number| number.as_str().parse().unwrap_or(0))
+    };
+    let (month, day, hour, minute, second) =
+        (number(2), number(3), number(4), number(5), number(6));
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+    let seconds =
+        days_from_civil(number(1), month, day) * 86_400 + hour * 3_600 + minute * 60 + second;
+    let time = match u64::try_from(seconds) {
+        Ok(seconds) => SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+        Err(_) => SystemTime::UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs()),
+    };
+    let has_time = captures.get(4).is_some();
+    let format = format.unwrap_or(match (locale, has_time) {
+        (Some(locale), false) => locale.date_format,
+        (Some(locale), true) => locale.date_time_format,
+        (None, false) => TODAY_FORMAT,
+        (None, true) => NOW_FORMAT,
+    });
+    // The date is written as it is, in no particular time zone
+    let date_options = DateOptions {
+        utc_offset_minutes: 0,
+        ..date_options
+    };
+    Some(format_date(time, &date_options, format))
+}
+
+/// This function writes a front matter number (or numeric string) with the decimal and group
+/// separators of the locale, optionally rounded to a number of decimal places.
+///
+/// # Returns
+///
+/// The formatted number, or `None` if the value is not a number.
+fn format_number(
+    value: &Value,
+    locale: Option<&Locale>,
+    decimal_places: Option<usize>,
+) -> Option<String> {
+    let written = match value {
+        Value::Number(number) => number.to_string(),
+        Value::String(string_value) => string_value.trim().to_string(),
+        _ => return None,
+    };
+    let number: f64 = written
+        .parse()
+        .ok()
+        .filter(|number: &f64| number.is_finite())?;
+    let written = match decimal_places {
+        // Halves are rounded away from zero (0.125 to 0.13), as on invoices
+        Some(decimal_places) => {
+            let factor = 10_f64.powi(decimal_places.min(15) as i32);
+            format!("{:.*}", decimal_places, (number * factor).round() / factor)
+        }
+        // As written, unless in exponent notation
+        None if written.contains(['e', 'E']) => number.to_string(),
+        None => written.trim_start_matches('+').to_string(),
+    };
+
+    let (sign, digits) = match written.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", written.as_str()),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let (decimal_separator, group_separator) = match locale {
+        Some(locale) => (locale.decimal_separator, Some(locale.group_separator)),
+        None => ('.', None),
+    };
+
+    let mut formatted = String::from(sign);
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            if let Some(group_separator) = group_separator {
+                formatted.push(group_separator);
+            }
+        }
+        formatted.push(digit);
+    }
+    if !fraction.is_empty() {
+        formatted.push(decimal_separator);
+        formatted.push_str(fraction);
+    }
+    Some(formatted)
+}
@@ -0,0 +1,138 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use crate::utils::write_atomically_with;
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::locked_file::LockedFilePolicy;
+
+/// How many alternate names (`report (1).pdf` to `report (99).pdf`) are tried with `LockedFilePolicy::AlternateName`.
+const MAX_ALTERNATE_NAMES: u32 = 99;
+
+/// This function writes a PDF atomically (see `write_atomically`), following a `LockedFilePolicy`
+/// when the file is locked by another process.
+///
+/// # Arguments
+///
+/// * `path` - The path of the PDF to write.
+/// * `locked_file_policy` - What to do when `path` is locked.
+/// * `write` - Writes the PDF to the (buffered) temporary file.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path the PDF was saved to (an alternate name with
+///   `LockedFilePolicy::AlternateName`).
+/// * `Err(e)` if the PDF could not be written, or `path` stayed locked. A locked file gives an
+///   error of kind `io::ErrorKind::PermissionDenied`.
+///
+/// # Remarks
+///
+/// The PDF is only written once: retries and alternate names only repeat the rename of the
+/// temporary file. A file open in a PDF viewer is only locked on Windows; elsewhere the PDF is
+/// saved to `path` even while a viewer has it open.
+pub fn write_pdf_file<F>(
+    path: &Path,
+    locked_file_policy: &LockedFilePolicy,
+    write: F,
+) -> io::Result<PathBuf>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    write_atomically_with(path, write, |temporary_file| {
+        rename_into_place(temporary_file, path, locked_file_policy)
+    })
+}
+
+/// Renames the temporary file to `path`, following the `LockedFilePolicy` when `path` is locked.
+fn rename_into_place(
+    temporary_file: &Path,
+    path: &Path,
+    locked_file_policy: &LockedFilePolicy,
+) -> io::Result<PathBuf> {
+    let error = match fs::rename(temporary_file, path) {
+        Ok(()) => return Ok(path.to_path_buf()),
+        Err(error) if is_locked(&error) => error,
+        Err(error) => return Err(error),
+    };
+
+    match locked_file_policy {
+        LockedFilePolicy::Fail => Err(error),
+        LockedFilePolicy::Retry { attempts, delay } => {
+            let mut last_error = error;
+            for attempt in 1..=*attempts {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!(
+                        "{} is open by another process; retrying in {:?} ({} of {}).",
+                        path.display(),
+                        delay,
+                        attempt,
+                        attempts
+                    )
+                    .yellow()
+                );
+                thread::sleep(*delay);
+                match fs::rename(temporary_file, path) {
+                    Ok(()) => return Ok(path.to_path_buf()),
+                    Err(error) if is_locked(&error) => last_error = error,
+                    Err(error) => return Err(error),
+                }
+            }
+            Err(last_error)
+        }
+        LockedFilePolicy::AlternateName => {
+            for number in 1..=MAX_ALTERNATE_NAMES {
+                let alternate_path = alternate_file_path(path, number);
+                if alternate_path.exists() && !is_writable(&alternate_path) {
+                    continue;
+                }
+                match fs::rename(temporary_file, &alternate_path) {
+                    Ok(()) => {
+                        eprintln!(
+                            "{}{}",
+                            CROSS_MARK.yellow(),
+                            format!(
+                                "{} is open by another process; saved as {} instead.",
+                                path.display(),
+                                alternate_path.display()
+                            )
+                            .yellow()
+                        );
+                        return Ok(alternate_path);
+                    }
+                    Err(error) if is_locked(&error) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Returns whether an error is the one a file locked by another process gives.
+fn is_locked(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::PermissionDenied
+}
+
+/// Returns whether an existing file can be opened for writing (it is not locked).
+fn is_writable(path: &Path) -> bool {
+    fs::OpenOptions::new().write(true).open(path).is_ok()
+}
+
+/// Returns `path` with ` (number)` added to its file stem, such as `report (1).pdf`.
+fn alternate_file_path(path: &Path, number: u32) -> PathBuf {
+    let mut file_name = OsString::from(path.file_stem().unwrap_or_default());
+    file_name.push(format!(" ({})", number));
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    path.with_file_name(file_name)
+}
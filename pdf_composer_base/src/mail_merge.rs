@@ -0,0 +1,204 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use serde_yml::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+use crate::generate::{
+    generate_document, generated_document, report_summary, DocumentOverrides, OutputError,
+};
+use crate::generation_threads::{map_documents, FailFast};
+use crate::upload::upload_generated_files;
+use crate::utils::{
+    merge_markdown_yaml, parse_front_matter, yaml_mapping_to_btreemap, FrontMatterError,
+};
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::generation_report::{GeneratedDocument, GenerationReport};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function generates one PDF for each data record, from a single markdown template.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings to generate the PDFs with.
+/// * `template` - The path of the markdown template. Its front matter (if any) gives the default values.
+/// * `records` - The data records, each a JSON object whose entries fill the template placeholders.
+/// * `naming` - The file name (without `.pdf`) of each PDF, with placeholders (`invoice-{{number}}`).
+///
+/// # Returns
+///
+/// A `GenerationReport` with a `GeneratedDocument` (for the template) for each record, in order.
+///
+/// # Remarks
+///
+/// Numbers and booleans in the records are turned into text, so they fill placeholders like strings do.
+/// Records that are not objects are skipped. When the name of a record still has placeholders
+/// (a missing entry), the record is named after the template and its position instead, and names
+/// used more than once get a `-2`, `-3` etc suffix, so no PDF is overwritten.
+pub fn generate_from_records(
+    composer: &PDFComposerStruct,
+    template: &Path,
+    records: Vec<serde_json::Value>,
+    naming: &str,
+) -> GenerationReport {
+    let Ok(template_content) = fs::read_to_string(template) else {
+        eprintln!(
+            "{}Template {} not found.",
+            CROSS_MARK.red(),
+            template.display().to_string().red()
+        );
+        return GenerationReport::default();
+    };
+
+    // The template front matter holds the defaults for every record
+    let (defaults, markdown_content) = match parse_front_matter(&template_content) {
+        Ok((yaml_content, markdown_content)) => match serde_yml::from_str::<Value>(&yaml_content) {
+            Ok(Value::Null) => (BTreeMap::new(), markdown_content),
+            Ok(yaml) => match yaml_mapping_to_btreemap(&yaml) {
+                Some(defaults) => (defaults, markdown_content),
+                None => {
+                    eprintln!(
+                        "{}Template {} front matter is not a mapping.",
+                        CROSS_MARK.red(),
+                        template.display().to_string().red()
+                    );
+                    return GenerationReport::default();
+                }
+            },
+            Err(error) => {
+                eprintln!(
+                    "{}Template {} front matter is not valid YAML: {}",
+                    CROSS_MARK.red(),
+                    template.display().to_string().red(),
+                    error
+                );
+                return GenerationReport::default();
+            }
+        },
+        Err(FrontMatterError::Missing) => (BTreeMap::new(), template_content),
+        Err(error) => {
+            eprintln!(
+                "{}Template {}: {}",
+                CROSS_MARK.red(),
+                template.display().to_string().red(),
+                error
+            );
+            return GenerationReport::default();
+        }
+    };
+
+    let template_stem = template
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    if !composer.console_output.is_quiet() {
+        println!("{} {}", "Template:".cyan(), template.display());
+        println!("Records to process: {}\n", records.len().to_string().cyan());
+    }
+
+    // Name every record before generating, so names used more than once can be told apart
+    let mut used_names: BTreeSet<String> = BTreeSet::new();
+    let records: Vec<Option<(String, BTreeMap<String, Value>)>> = records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| {
+            let Some(record_data) = record_front_matter(record) else {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!("Record {} is not an object, skipped.", index + 1).yellow()
+                );
+                return None;
+            };
+            let mut data = defaults.clone();
+            data.extend(record_data);
+
+            let mut name = file_name(&merge_markdown_yaml(data.clone(), naming));
+            if name.is_empty() || name.contains("{{") {
+                let fallback = format!("{}-{}", template_stem, index + 1);
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!(
+                        "Record {} has no value for a placeholder in {}, named {} instead.",
+                        index + 1,
+                        naming,
+                        fallback
+                    )
+                    .yellow()
+                );
+                name = fallback;
+            }
+            let mut unique_name = name.clone();
+            let mut suffix = 2;
+            while !used_names.insert(unique_name.to_lowercase()) {
+                unique_name = format!("{}-{}", name, suffix);
+                suffix += 1;
+            }
+            Some((unique_name, data))
+        })
+        .collect();
+
+    let fail_fast = FailFast::new(composer);
+    let documents: Vec<GeneratedDocument> = map_documents(composer, records, |record| {
+        let output_files = fail_fast.generate(template, || match record {
+            Some((name, data)) => generate_document(
+                composer,
+                template,
+                &DocumentOverrides {
+                    output_file_name: Some(name),
+                    ..Default::default()
+                },
+                data,
+                &markdown_content,
+                composer.page_numbering.clone(),
+            ),
+            None => vec![Err(OutputError::failed("the record is not an object"))],
+        });
+        generated_document(template.to_path_buf(), output_files)
+    });
+
+    let mut report = GenerationReport {
+        documents,
+        ..Default::default()
+    };
+    upload_generated_files(composer, &mut report);
+    report_summary(composer, &report);
+
+    report
+}
+
+/// Converts a JSON record to front matter, with numbers and booleans as text (`None` if it is not an object).
+fn record_front_matter(record: &serde_json::Value) -> Option<BTreeMap<String, Value>> {
+    let serde_json::Value::Object(entries) = record else {
+        return None;
+    };
+    let mut front_matter: BTreeMap<String, Value> = BTreeMap::new();
+    for (key, value) in entries {
+        let value = match value {
+            serde_json::Value::Number(number) => Value::String(number.to_string()),
+            serde_json::Value::Bool(boolean) => Value::String(boolean.to_string()),
+            value => serde_yml::to_value(value).ok()?,
+        };
+        front_matter.insert(key.to_string(), value);
+    }
+    Some(front_matter)
+}
+
+/// Replaces the characters that cannot be used in file names.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|character| match character {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            character if character.is_control() => '-',
+            character => character,
+        })
+        .collect::<String>()
+        .trim()
+        .trim_matches('.')
+        .to_string()
+}
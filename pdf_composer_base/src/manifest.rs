@@ -0,0 +1,283 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::conformance::apply_conformance_to_file;
+use crate::cover::prepend_cover;
+use crate::generate::{
+    generate_document, generated_document, read_source_document, report_summary, DocumentOverrides,
+    OutputError, OutputFile,
+};
+use crate::generation_threads::{map_documents, FailFast};
+use crate::upload::upload_generated_files;
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::generation_report::{GeneratedDocument, GenerationReport};
+use pdf_composer_definitions::output_format::OutputFormat;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// The settings a manifest document entry can have.
+const MANIFEST_DOCUMENT_KEYS: [&str; 6] = [
+    "source",
+    "output",
+    "paper_size",
+    "orientation",
+    "doc_info",
+    "cover",
+];
+
+/// Struct holding one document of a manifest, with its own settings.
+struct ManifestDocument {
+    /// The path of the source document.
+    source: PathBuf,
+    /// The settings that differ from the composer settings.
+    overrides: DocumentOverrides,
+    /// The path of a PDF whose pages go before the document.
+    cover: Option<PathBuf>,
+}
+
+/// This function generates the documents listed in a manifest file, each with its own settings.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings shared by every document.
+/// * `manifest` - The path of the manifest file (`.yaml`, `.yml` or `.toml`).
+///
+/// # Returns
+///
+/// A `GenerationReport` with a `GeneratedDocument` for each document of the manifest, in order
+/// (empty if the manifest could not be read).
+///
+/// # Remarks
+///
+/// Paths in the manifest are relative to the manifest file. A document entry is either the path of
+/// the source document, or a table with the `source` path and any of `output` (the file name of the
+/// PDF), `paper_size`, `orientation`, `doc_info` (PDF entry → front matter key, on top of the
+/// composer entries) and `cover` (a PDF put before the first page). Entries with settings that are
+/// not recognised are reported and skipped.
+pub fn generate_from_manifest(composer: &PDFComposerStruct, manifest: &Path) -> GenerationReport {
+    let manifest_directory = manifest.parent().unwrap_or(Path::new(""));
+    let entries = match read_manifest(manifest) {
+        Ok(entries) => entries,
+        Err(message) => {
+            eprintln!(
+                "{}Manifest {} not read: {}",
+                CROSS_MARK.red(),
+                manifest.display().to_string().red(),
+                message
+            );
+            return GenerationReport::default();
+        }
+    };
+
+    if !composer.console_output.is_quiet() {
+        println!("{} {}", "Manifest:".cyan(), manifest.display());
+        println!(
+            "Documents to process: {}\n",
+            entries.len().to_string().cyan()
+        );
+    }
+
+    let fail_fast = FailFast::new(composer);
+    let documents: Vec<GeneratedDocument> = map_documents(
+        composer,
+        entries.iter().enumerate().collect(),
+        |(index, entry)| {
+            let source_file = entry_source(entry, manifest_directory);
+            let output_files = fail_fast.generate(&source_file, || {
+                let document = match manifest_document(entry, manifest_directory) {
+                    Ok(document) => document,
+                    Err(message) => {
+                        eprintln!(
+                            "{}{}",
+                            CROSS_MARK.red(),
+                            format!("Manifest document {} skipped: {}", index + 1, message).red()
+                        );
+                        return vec![Err(OutputError::skipped(format!(
+                            "manifest entry skipped: {}",
+                            message
+                        )))];
+                    }
+                };
+
+                let filename = document.source.display().to_string();
+                let output_files = if document.source.is_file() {
+                    if !composer.console_output.is_quiet() {
+                        println!("File {} exists. {}", filename.cyan(), "Reading...".green());
+                    }
+                    match read_source_document(&filename, composer.console_output.is_quiet()) {
+                        Ok((yaml_btreemap, markdown_content)) => generate_document(
+                            composer,
+                            &document.source,
+                            &document.overrides,
+                            yaml_btreemap,
+                            &markdown_content,
+                            composer.page_numbering.clone(),
+                        ),
+                        Err(error) => vec![Err(OutputError::failed(error))],
+                    }
+                } else {
+                    eprintln!("File {} not found.", filename.red());
+                    vec![Err(OutputError::failed("file not found"))]
+                };
+
+                // The cover goes on once the document is finished (numbered, stamped etc), and only on PDFs
+                output_files
+                    .into_iter()
+                    .zip(&composer.output_formats)
+                    .map(|(output_file, output_format)| {
+                        match (output_file, &document.cover, output_format) {
+                            (Ok(output_file), Some(cover), OutputFormat::Pdf) => {
+                                // The cover pages need the boxes etc of the standard too
+                                let covered = prepend_cover(&output_file.path, cover).and_then(
+                                    |cover_page_count| match composer.conformance {
+                                        Some(conformance) => apply_conformance_to_file(
+                                            &output_file.path,
+                                            conformance,
+                                        )
+                                        .map(|()| cover_page_count),
+                                        None => Ok(cover_page_count),
+                                    },
+                                );
+                                match covered {
+                                    Ok(cover_page_count) => Ok(OutputFile {
+                                        page_count: output_file
+                                            .page_count
+                                            .map(|page_count| page_count + cover_page_count),
+                                        ..output_file
+                                    }),
+                                    Err(error) => {
+                                        eprintln!(
+                                            "{}{} {}",
+                                            CROSS_MARK.red(),
+                                            format!(
+                                                "Failed to add the cover to {}.",
+                                                output_file.path.display()
+                                            )
+                                            .red(),
+                                            error
+                                        );
+                                        Err(OutputError::failed(format!(
+                                            "cover not added: {}",
+                                            error
+                                        )))
+                                    }
+                                }
+                            }
+                            (output_file, _, _) => output_file,
+                        }
+                    })
+                    .collect()
+            });
+
+            generated_document(source_file, output_files)
+        },
+    );
+
+    let mut report = GenerationReport {
+        documents,
+        ..Default::default()
+    };
+    upload_generated_files(composer, &mut report);
+    report_summary(composer, &report);
+
+    report
+}
+
+/// Reads the document entries of a YAML or TOML manifest.
+fn read_manifest(manifest: &Path) -> Result<Vec<Value>, String> {
+    let content = fs::read_to_string(manifest).map_err(|error| error.to_string())?;
+    let is_toml = manifest
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let manifest_value: Value = if is_toml {
+        let toml_value: toml::Value =
+            toml::from_str(&content).map_err(|error| error.to_string())?;
+        serde_yml::to_value(toml_value).map_err(|error| error.to_string())?
+    } else {
+        serde_yml::from_str(&content).map_err(|error| error.to_string())?
+    };
+
+    match manifest_value.get("documents") {
+        Some(Value::Sequence(entries)) => Ok(entries.to_vec()),
+        _ => Err("there is no list of documents".to_string()),
+    }
+}
+
+/// Reads the source document and settings of a manifest document entry.
+fn manifest_document(entry: &Value, manifest_directory: &Path) -> Result<ManifestDocument, String> {
+    if let Value::String(source) = entry {
+        return Ok(ManifestDocument {
+            source: manifest_directory.join(source),
+            overrides: DocumentOverrides::default(),
+            cover: None,
+        });
+    }
+    let Value::Mapping(settings) = entry else {
+        return Err("a document must be a path or a table of settings".to_string());
+    };
+
+    for key in settings.keys() {
+        let key = key.as_str().unwrap_or_default();
+        if !MANIFEST_DOCUMENT_KEYS.contains(&key) {
+            return Err(format!("unknown setting {}", key));
+        }
+    }
+    let text = |key: &str| -> Result<Option<&str>, String> {
+        match settings.get(key) {
+            None => Ok(None),
+            Some(Value::String(value)) => Ok(Some(value)),
+            Some(_) => Err(format!("{} must be text", key)),
+        }
+    };
+
+    let source = text("source")?.ok_or_else(|| "no source".to_string())?;
+    let mut doc_info_entries: BTreeMap<String, String> = BTreeMap::new();
+    if let Some(doc_info) = settings.get("doc_info") {
+        let Value::Mapping(doc_info) = doc_info else {
+            return Err(
+                "doc_info must be a table of PDF entries and front matter keys".to_string(),
+            );
+        };
+        for (entry, yaml_key) in doc_info {
+            match (entry.as_str(), yaml_key.as_str()) {
+                (Some(entry), Some(yaml_key)) => {
+                    doc_info_entries.insert(entry.to_string(), yaml_key.to_string());
+                }
+                _ => return Err("doc_info entries and front matter keys must be text".to_string()),
+            }
+        }
+    }
+
+    Ok(ManifestDocument {
+        source: manifest_directory.join(source),
+        overrides: DocumentOverrides {
+            output_file_name: text("output")?
+                .map(|output| output.trim_end_matches(".pdf").to_string()),
+            paper_size: text("paper_size")?
+                .map(|paper_size| paper_size.parse())
+                .transpose()?,
+            orientation: text("orientation")?
+                .map(|orientation| orientation.parse())
+                .transpose()?,
+            doc_info_entries,
+        },
+        cover: text("cover")?.map(|cover| manifest_directory.join(cover)),
+    })
+}
+
+/// Returns the source document of a manifest entry, as far as it can be read (for the report).
+fn entry_source(entry: &Value, manifest_directory: &Path) -> PathBuf {
+    let source = match entry {
+        Value::String(source) => source.as_str(),
+        entry => entry
+            .get("source")
+            .and_then(Value::as_str)
+            .unwrap_or_default(),
+    };
+    manifest_directory.join(source)
+}
@@ -0,0 +1,438 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use lopdf::{Bookmark, Dictionary, Document, Object as LopdfObject, ObjectId};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::attachments::{embedded_files, set_embedded_files, unique_name};
+use crate::catalog::{
+    set_page_labels, set_pdf_version, set_viewer_preference, set_viewer_preferences,
+};
+use crate::conformance::apply_conformance;
+use crate::destinations::{named_destinations, set_named_destinations, set_open_action};
+use crate::deterministic::{make_deterministic, set_deterministic_id};
+use crate::form_fields::{add_signature_field, rebuild_interactive_form};
+use crate::locked_file::write_pdf_file;
+use crate::optimize::optimize_document;
+use crate::page_numbering::number_pages;
+use crate::page_tree::{
+    page_with_inherited_attributes, rebuild_page_tree, PAGE_DEPENDENT_CATALOG_ENTRIES,
+};
+use crate::post_process::post_process;
+#[cfg(feature = "timestamp")]
+use crate::timestamp::{timestamp_document, timestamp_pdf_file};
+use crate::utils::{decode_pdf_text_string, doc_info_value, output_file_path, write_atomically};
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::generation_report::MergedChapter;
+use pdf_composer_definitions::merge::{MergeMetadataSource, MergeMode, MergeOptions};
+use pdf_composer_definitions::page_numbering::{PageLabelRange, PageNumberSection, PageNumbering};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+#[cfg(feature = "timestamp")]
+use pdf_composer_definitions::pdf_version::PDFFeature;
+use pdf_composer_definitions::pdf_version::PDFVersion;
+use pdf_composer_definitions::text_direction::TextDirection;
+
+/// Merges several PDF documents into a single PDF document, keeping the page order of `inputs`.
+///
+/// # Arguments
+///
+/// * `inputs` - The paths of the PDF documents to merge, in order.
+/// * `output` - The path to write the merged PDF document to. Missing parent directories are created.
+/// * `options` - The `MergeOptions` controlling metadata, bookmarks and the PDF version.
+///
+/// # Returns
+///
+/// * `Ok(())` if the merged document was written.
+/// * `Err(e)` if `inputs` is empty, or an input could not be read or the output could not be written.
+///
+/// # Remarks
+///
+/// The page tree is rebuilt with every page directly under a single `Pages` node, and inherited page
+/// attributes (resources, media box etc) are copied onto each page so nothing is lost.
+///
+/// The catalog of the first input is kept (so its language and viewer preferences carry over),
+/// but its outline, page labels and tagged structure are dropped as they would only describe the
+/// first input's pages. The named destinations (link targets) of all the inputs are kept; if two
+/// inputs use the same name, the first one wins. The attachments (embedded files) of all the
+/// inputs are kept too, numbered if two have the same name (`notes (2).md`).
+///
+/// # Examples
+///
+/// ```no_run
+/// use pdf_composer::{merge_pdfs, MergeOptions};
+/// use std::path::{Path, PathBuf};
+///
+/// let chapters = vec![
+///     PathBuf::from("pdf_composer_pdfs/chapter_01.pdf"),
+///     PathBuf::from("pdf_composer_pdfs/chapter_02.pdf"),
+/// ];
+///
+/// let mut options = MergeOptions {
+///     bookmarks: true,
+///     ..Default::default()
+/// };
+/// options
+///     .doc_info_overrides
+///     .insert("Title".to_string(), "The complete guide".to_string());
+///
+/// merge_pdfs(&chapters, Path::new("pdf_composer_pdfs/guide.pdf"), options).unwrap();
+/// ```
+pub fn merge_pdfs(
+    inputs: &[PathBuf],
+    output: &Path,
+    options: MergeOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (mut merged, _page_counts) = merge_documents(inputs, &options)?;
+    save_document(&mut merged, output)
+}
+
+/// This function merges several PDF documents into a single lopdf `Document` (see `merge_pdfs`).
+///
+/// # Arguments
+///
+/// * `inputs` - The paths of the PDF documents to merge, in order.
+/// * `options` - The `MergeOptions` controlling metadata, bookmarks and the PDF version.
+///
+/// # Returns
+///
+/// * `Ok((Document, Vec<u32>))` with the merged document and the number of pages taken from each input.
+/// * `Err(e)` if `inputs` is empty, an input could not be read or a `doc_info_overrides` value is
+///   not of the type of its entry (such as a `CreationDate` that is not a date).
+pub fn merge_documents(
+    inputs: &[PathBuf],
+    options: &MergeOptions,
+) -> Result<(Document, Vec<u32>), Box<dyn std::error::Error>> {
+    if inputs.is_empty() {
+        return Err("no PDF documents to merge".into());
+    }
+
+    let mut merged = Document::new();
+    let mut next_id: u32 = 1;
+    let mut highest_version = String::default();
+    let mut merged_pages: Vec<(ObjectId, Dictionary)> = Vec::new();
+    let mut merged_catalog: Option<Dictionary> = None;
+    let mut info_ids: Vec<Option<ObjectId>> = Vec::new();
+    let mut bookmarks: Vec<(String, ObjectId)> = Vec::new();
+    let mut page_counts: Vec<u32> = Vec::new();
+    let mut destinations: BTreeMap<Vec<u8>, LopdfObject> = BTreeMap::new();
+    let mut attachments: BTreeMap<String, ObjectId> = BTreeMap::new();
+
+    for input in inputs {
+        let mut doc = Document::load(input)
+            .map_err(|error| format!("could not load {}: {}", input.display(), error))?;
+
+        // Give every object a unique id across all the input documents
+        doc.renumber_objects_with(next_id);
+        next_id = doc.max_id + 1;
+
+        if doc.version > highest_version {
+            highest_version = doc.version.clone();
+        }
+
+        // The first input with a destination name keeps it
+        for (name, destination) in named_destinations(&doc) {
+            destinations.entry(name).or_insert(destination);
+        }
+
+        // Every input keeps its attachments (a name already taken gets a number)
+        for (name, file_specification_id) in embedded_files(&doc) {
+            attachments.insert(unique_name(&attachments, &name), file_specification_id);
+        }
+
+        let catalog_id = doc.trailer.get(b"Root")?.as_reference()?;
+        if merged_catalog.is_none() {
+            merged_catalog = Some(doc.catalog()?.clone());
+        }
+
+        let info_id = doc
+            .trailer
+            .get(b"Info")
+            .and_then(LopdfObject::as_reference)
+            .ok();
+        info_ids.push(info_id);
+
+        let mut pages: Vec<(ObjectId, Dictionary)> = Vec::new();
+        for page_id in doc.get_pages().into_values() {
+            pages.push((page_id, page_with_inherited_attributes(&doc, page_id)?));
+        }
+
+        if options.bookmarks {
+            if let Some((first_page_id, _)) = pages.first() {
+                bookmarks.push((document_title(&doc, info_id, input), *first_page_id));
+            }
+        }
+
+        for (object_id, object) in doc.objects {
+            let is_page_tree_node = matches!(object.type_name(), Ok("Pages") | Ok("Page"));
+            if object_id != catalog_id && !is_page_tree_node {
+                merged.objects.insert(object_id, object);
+            }
+        }
+        page_counts.push(pages.len() as u32);
+        merged_pages.extend(pages);
+    }
+
+    merged.max_id = next_id - 1;
+
+    let mut catalog = merged_catalog.unwrap_or_default();
+    for key in PAGE_DEPENDENT_CATALOG_ENTRIES {
+        catalog.remove(key);
+    }
+    let catalog_id = merged.add_object(catalog);
+    merged.trailer.set("Root", catalog_id);
+
+    // The chosen version, or the highest version of the inputs (inputs older than 1.4 are saved as
+    // 1.4, the oldest version written). The inputs already have the versions their features need.
+    let pdf_version = match options.pdf_version {
+        Some(pdf_version) if pdf_version != PDFVersion::Auto => pdf_version,
+        _ => highest_version.parse().unwrap_or(PDFVersion::V1_4),
+    };
+    set_pdf_version(&mut merged, pdf_version);
+
+    rebuild_page_tree(&mut merged, merged_pages)?;
+    set_named_destinations(&mut merged, &destinations)?;
+    set_embedded_files(&mut merged, &attachments)?;
+    // Only the form fields of the first input would be in its catalog
+    rebuild_interactive_form(&mut merged)?;
+
+    let selected_info_id = match options.metadata_source {
+        MergeMetadataSource::First => info_ids.first().copied().flatten(),
+        MergeMetadataSource::Last => info_ids.last().copied().flatten(),
+        MergeMetadataSource::Empty => None,
+    };
+    let mut info = selected_info_id
+        .and_then(|info_id| merged.get_dictionary(info_id).ok())
+        .cloned()
+        .unwrap_or_default();
+    for (key, value) in &options.doc_info_overrides {
+        let value = doc_info_value(key, value)
+            .map_err(|error| format!("document information entry {}: {}", key, error))?;
+        info.set(key.as_str(), value);
+    }
+    if !info.is_empty() {
+        let info_id = merged.add_object(info);
+        merged.trailer.set("Info", info_id);
+    }
+
+    for (title, page_id) in bookmarks {
+        merged.add_bookmark(Bookmark::new(title, [0.0, 0.0, 0.0], 0, page_id), None);
+    }
+    if let Some(outline_id) = merged.build_outline() {
+        merged.catalog_mut()?.set("Outlines", outline_id);
+    }
+
+    // Drop the old page tree nodes, catalogs and document information nobody refers to any more,
+    // and share the resources the inputs have in common
+    optimize_document(&mut merged);
+
+    Ok((merged, page_counts))
+}
+
+/// This function saves a document, creating any missing parent directories of `output` first.
+pub fn save_document(doc: &mut Document, output: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    write_atomically(output, |writer| doc.save_to(writer))?;
+    Ok(())
+}
+
+/// Returns the `Title` document information entry of `doc`, falling back to the file name of `path`.
+fn document_title(doc: &Document, info_id: Option<ObjectId>, path: &Path) -> String {
+    info_id
+        .and_then(|info_id| doc.get_dictionary(info_id).ok())
+        .and_then(|info| info.get(b"Title").ok())
+        .and_then(|title| title.as_str().ok())
+        .map(decode_pdf_text_string)
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+/// This function merges the chapter PDFs generated in merge mode into a single PDF, numbering its pages.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` (for the output directory, page numbering, font and margins).
+/// * `merge_mode` - The `MergeMode` options.
+/// * `chapters` - The source file and generated PDF of each chapter, in order.
+///
+/// # Returns
+///
+/// * `Ok((PathBuf, Vec<MergedChapter>))` with the path of the merged PDF and the page mapping of each chapter.
+/// * `Err(e)` if the chapters could not be merged or the merged PDF could not be saved.
+///
+/// # Remarks
+///
+/// Page numbers are applied to the merged PDF (the chapter PDFs are generated without them), so they
+/// either run on through the whole document or, with `restart_page_numbers`, restart at 1 in each
+/// chapter in the style of the last page numbering section.
+pub fn merge_chapters(
+    composer: &PDFComposerStruct,
+    merge_mode: &MergeMode,
+    chapters: &[(PathBuf, PathBuf)],
+) -> Result<(PathBuf, Vec<MergedChapter>), Box<dyn std::error::Error>> {
+    let chapter_pdfs: Vec<PathBuf> = chapters.iter().map(|(_source, pdf)| pdf.clone()).collect();
+    let (mut merged, page_counts) = merge_documents(&chapter_pdfs, &merge_mode.options)?;
+    // The chapters are not timestamped, so the merged PDF is the first to need PDF 2.0 for it
+    #[cfg(feature = "timestamp")]
+    if composer.timestamp_authority.is_some() {
+        let pdf_version = merge_mode
+            .options
+            .pdf_version
+            .unwrap_or(PDFVersion::Auto)
+            .resolve(&[PDFFeature::DocumentTimestamp])
+            .map_err(|error| format!("{} not timestamped: {}", merge_mode.file_name, error))?;
+        set_pdf_version(&mut merged, pdf_version);
+    }
+
+    let mut first_pages: Vec<u32> = Vec::with_capacity(page_counts.len());
+    let mut next_page = 1;
+    for page_count in &page_counts {
+        first_pages.push(next_page);
+        next_page += page_count;
+    }
+
+    let page_numbering = if merge_mode.restart_page_numbers {
+        let style = composer
+            .page_numbering
+            .as_ref()
+            .and_then(|numbering| numbering.sections.last())
+            .map(|section| section.style)
+            .unwrap_or_default();
+        let mut numbering = PageNumbering::sections(
+            first_pages
+                .iter()
+                .map(|first_page| PageNumberSection::new(*first_page, style)),
+        );
+        // Without page numbering set, only the page labels restart
+        numbering.footer_template = composer
+            .page_numbering
+            .as_ref()
+            .and_then(|numbering| numbering.footer_template.clone());
+        Some(numbering)
+    } else {
+        composer.page_numbering.clone()
+    };
+
+    if let Some(page_numbering) = &page_numbering {
+        number_pages(
+            &mut merged,
+            page_numbering,
+            composer.font,
+            composer.margins,
+            composer.mirrored_margins,
+        )?;
+    }
+    if let Some(page_labels) = &composer.page_labels {
+        set_page_labels(&mut merged, page_labels)?;
+    }
+    // A merged right-to-left booklet pages right-to-left, as its documents do
+    if composer.text_direction == TextDirection::Rtl {
+        set_viewer_preference(&mut merged, "Direction", LopdfObject::Name(b"R2L".to_vec()))?;
+    }
+    set_viewer_preferences(&mut merged, &composer.viewer_preferences)?;
+
+    let merged_file = output_file_path(&composer.output_directory, &merge_mode.file_name);
+    for signature_field in &composer.signature_fields {
+        if let Err(error) = add_signature_field(&mut merged, signature_field) {
+            eprintln!(
+                "{}{}",
+                CROSS_MARK.yellow(),
+                format!(
+                    "Signature field {} not added to {}: {}.",
+                    signature_field.name,
+                    merged_file.display(),
+                    error
+                )
+                .yellow()
+            );
+        }
+    }
+    if let Some(open_action) = &composer.open_action {
+        if let Err(error) = set_open_action(&mut merged, open_action) {
+            eprintln!(
+                "{}{}",
+                CROSS_MARK.yellow(),
+                format!("{} opens as usual: {}.", merged_file.display(), error).yellow()
+            );
+        }
+    }
+    if composer.deterministic {
+        make_deterministic(&mut merged);
+    }
+    if let Some(conformance) = composer.conformance {
+        let fallback_title = merged_file
+            .file_stem()
+            .map(|file_stem| file_stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        apply_conformance(&mut merged, conformance, &fallback_title)?;
+    }
+    // The document ID is a digest of the finished document, metadata and standard included
+    if composer.deterministic {
+        set_deterministic_id(&mut merged)?;
+    }
+    #[cfg(feature = "timestamp")]
+    let timestamped_pdf = match &composer.timestamp_authority {
+        // A PDF the post-process command changes is timestamped after the command
+        Some(tsa_url) if composer.post_process_command.is_none() => Some(
+            timestamp_document(&mut merged, tsa_url)
+                .map_err(|error| format!("{} not timestamped: {}", merged_file.display(), error))?,
+        ),
+        _ => None,
+    };
+    #[cfg(not(feature = "timestamp"))]
+    let timestamped_pdf: Option<Vec<u8>> = None;
+    let merged_file =
+        write_pdf_file(
+            &merged_file,
+            &composer.locked_file_policy,
+            |writer| match &timestamped_pdf {
+                Some(pdf) => writer.write_all(pdf),
+                None => merged.save_to(writer),
+            },
+        )?;
+    if let Some(command) = &composer.post_process_command {
+        post_process(command, &merged_file).map_err(|error| {
+            format!(
+                "post-process command failed on {}: {}",
+                merged_file.display(),
+                error
+            )
+        })?;
+    }
+    // Timestamped last, so the timestamp covers the PDF as the command left it
+    #[cfg(feature = "timestamp")]
+    let merged_file = match (
+        &composer.timestamp_authority,
+        &composer.post_process_command,
+    ) {
+        (Some(tsa_url), Some(_)) => {
+            timestamp_pdf_file(&merged_file, tsa_url, &composer.locked_file_policy)
+                .map_err(|error| format!("{} not timestamped: {}", merged_file.display(), error))?
+        }
+        _ => merged_file,
+    };
+
+    let merged_chapters = chapters
+        .iter()
+        .zip(first_pages.iter().zip(page_counts.iter()))
+        .map(
+            |((source_file, _pdf), (first_page, page_count))| MergedChapter {
+                source_file: source_file.clone(),
+                first_page: *first_page,
+                page_count: *page_count,
+                first_page_label: match (&composer.page_labels, &page_numbering) {
+                    (Some(page_labels), _) => PageLabelRange::label(page_labels, *first_page),
+                    (None, Some(numbering)) => numbering.label(*first_page),
+                    (None, None) => first_page.to_string(),
+                },
+            },
+        )
+        .collect();
+
+    Ok((merged_file, merged_chapters))
+}
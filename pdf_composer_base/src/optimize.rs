@@ -0,0 +1,182 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Document, Object as LopdfObject, ObjectId, Stream};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::{Hash, Hasher};
+
+/// This function shrinks a document before it is saved: objects nothing refers to are dropped,
+/// identical streams are merged into one object and the objects are numbered again from 1.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// The number of objects removed.
+///
+/// # Remarks
+///
+/// Merged chapters each carry their own copy of the images, fonts and other resources they share
+/// (a logo on every chapter, the same font file), which is where most of the size of a merged PDF
+/// goes. Streams are only merged when both their dictionary and their (compressed) content are the
+/// same, so nothing a viewer shows changes.
+pub fn optimize_document(doc: &mut Document) -> usize {
+    let object_count = doc.objects.len();
+
+    doc.prune_objects();
+    // Fonts refer to their font files and images to their soft masks, so merging some streams can
+    // make the streams referring to them identical in turn
+    while deduplicate_streams(doc) > 0 {}
+    doc.prune_objects();
+    doc.renumber_objects();
+
+    object_count.saturating_sub(doc.objects.len())
+}
+
+/// Points every reference to a stream at the first identical stream and removes the others,
+/// returning the number of streams removed.
+fn deduplicate_streams(doc: &mut Document) -> usize {
+    let mut candidates: HashMap<u64, Vec<ObjectId>> = HashMap::new();
+    let mut replacements: BTreeMap<ObjectId, ObjectId> = BTreeMap::new();
+
+    for (object_id, object) in &doc.objects {
+        let LopdfObject::Stream(stream) = object else {
+            continue;
+        };
+        let ids = candidates.entry(stream_hash(stream)).or_default();
+        let first_id = ids.iter().copied().find(|first_id| {
+            matches!(
+                doc.objects.get(first_id),
+                Some(LopdfObject::Stream(first)) if same_stream(first, stream)
+            )
+        });
+        match first_id {
+            Some(first_id) => {
+                replacements.insert(*object_id, first_id);
+            }
+            None => ids.push(*object_id),
+        }
+    }
+
+    if !replacements.is_empty() {
+        doc.traverse_objects(|object| {
+            if let LopdfObject::Reference(object_id) = object {
+                if let Some(first_id) = replacements.get(object_id) {
+                    *object_id = *first_id;
+                }
+            }
+        });
+        for object_id in replacements.keys() {
+            doc.objects.remove(object_id);
+        }
+    }
+    replacements.len()
+}
+
+/// Returns a hash of the dictionary and content of a stream.
+fn stream_hash(stream: &Stream) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}", stream.dict).hash(&mut hasher);
+    stream.content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Returns true if two streams have the same dictionary (entries in the same order) and content.
+fn same_stream(first: &Stream, second: &Stream) -> bool {
+    first.dict == second.dict && first.content == second.content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Dictionary};
+
+    /// Returns a document of `page_count` pages, each with its own copy of the same image, and
+    /// an image and a font nothing refers to.
+    fn document_with_copies(page_count: usize) -> Document {
+        let mut doc = Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let mut kids = Vec::new();
+        for _ in 0..page_count {
+            let image_id = doc.add_object(Stream::new(
+                dictionary! { "Type" => "XObject", "Subtype" => "Image" },
+                vec![1, 2, 3, 4],
+            ));
+            let content_id = doc.add_object(Stream::new(
+                Dictionary::new(),
+                b"q 10 0 0 10 0 0 cm /Im0 Do Q".to_vec(),
+            ));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "MediaBox" => vec![0.into(), 0.into(), 100.into(), 100.into()],
+                "Contents" => content_id,
+                "Resources" => dictionary! {
+                    "XObject" => dictionary! { "Im0" => image_id },
+                },
+            });
+            kids.push(LopdfObject::Reference(page_id));
+        }
+        doc.objects.insert(
+            pages_id,
+            LopdfObject::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => page_count as i64,
+                "Kids" => kids,
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+
+        doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image" },
+            vec![5, 6, 7, 8],
+        ));
+        doc.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1" });
+        doc
+    }
+
+    #[test]
+    fn keeps_the_pages() {
+        let mut doc = document_with_copies(3);
+        optimize_document(&mut doc);
+
+        assert_eq!(doc.get_pages().len(), 3);
+        let mut saved = Vec::new();
+        doc.save_to(&mut saved).unwrap();
+        assert_eq!(Document::load_mem(&saved).unwrap().get_pages().len(), 3);
+    }
+
+    #[test]
+    fn drops_unreachable_objects() {
+        let mut doc = document_with_copies(3);
+        let object_count = doc.objects.len();
+
+        let removed = optimize_document(&mut doc);
+
+        // The image and font nothing refers to, and two of the three copies of the image and of
+        // the content stream
+        assert_eq!(removed, 6);
+        assert_eq!(doc.objects.len(), object_count - 6);
+        assert!(!doc.objects.values().any(|object| {
+            matches!(object, LopdfObject::Stream(stream) if stream.content == [5, 6, 7, 8])
+        }));
+        assert!(!doc
+            .objects
+            .values()
+            .any(|object| object.type_name().ok() == Some("Font")));
+    }
+
+    #[test]
+    fn numbers_the_objects_from_one() {
+        let mut doc = document_with_copies(2);
+        optimize_document(&mut doc);
+
+        let ids: Vec<u32> = doc.objects.keys().map(|(id, _generation)| *id).collect();
+        assert_eq!(ids, (1..=doc.objects.len() as u32).collect::<Vec<u32>>());
+        assert_eq!(doc.max_id as usize, doc.objects.len());
+    }
+}
@@ -0,0 +1,91 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::content::Content;
+use lopdf::Document;
+
+use crate::catalog::set_page_labels;
+use crate::stamp::{
+    add_page_font, add_standard_font, append_page_content, approximate_text_width,
+    horizontal_margins, page_media_box, stamp_font_name, text_operations, POINTS_PER_INCH,
+    STAMP_FONT_RESOURCE,
+};
+use pdf_composer_definitions::fonts::FontsStandard;
+use pdf_composer_definitions::page_numbering::{PageLabelRange, PageNumbering};
+use pdf_composer_definitions::page_properties::PageMargins;
+
+/// Font size (in points) of the page number footer text.
+const PAGE_NUMBER_FONT_SIZE: f32 = 9.0;
+
+/// This function numbers the pages of a generated PDF: it sets the page labels shown by PDF viewers
+/// and (if there is a footer template) draws the page number at the bottom of each page.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `numbering` - The `PageNumbering` sections and footer template.
+/// * `font` - The font of the document (Symbol and Zapf Dingbats fall back to Helvetica).
+/// * `margins` - The page margins, used to position the footer in the bottom margin.
+/// * `mirrored_margins` - Whether `margins` are top, outer, bottom, inner and alternate on left and right pages.
+///
+/// # Returns
+///
+/// * `Ok(())` if the pages were numbered.
+/// * `Err(e)` if the catalog or a page could not be updated.
+///
+/// # Remarks
+///
+/// The footer is centred (approximately, as the standard fonts are not measured exactly) between
+/// the left and right margins.
+pub fn number_pages(
+    doc: &mut Document,
+    numbering: &PageNumbering,
+    font: FontsStandard,
+    margins: PageMargins,
+    mirrored_margins: bool,
+) -> Result<(), lopdf::Error> {
+    let label_ranges: Vec<PageLabelRange> = numbering
+        .sections
+        .iter()
+        .map(PageLabelRange::from)
+        .collect();
+    set_page_labels(doc, &label_ranges)?;
+
+    let Some(footer_template) = &numbering.footer_template else {
+        return Ok(());
+    };
+
+    let base_font = stamp_font_name(font);
+    let font_id = add_standard_font(doc, base_font);
+
+    let pages = doc.get_pages();
+    let page_count = pages.len().to_string();
+    for (page_number, page_id) in pages {
+        let Some([left, bottom, right, _top]) = page_media_box(doc, page_id) else {
+            continue;
+        };
+        let footer_text = footer_template
+            .replace("{page}", &numbering.label(page_number))
+            .replace("{pages}", &page_count);
+        if footer_text.trim().is_empty() {
+            continue;
+        }
+
+        let (left_margin, right_margin) =
+            horizontal_margins(margins, mirrored_margins, page_number);
+        let content_centre = left + left_margin + (right - left - left_margin - right_margin) / 2.0;
+        let x = content_centre
+            - approximate_text_width(&footer_text, base_font, PAGE_NUMBER_FONT_SIZE) / 2.0;
+        // Vertically centre the text in the bottom margin
+        let y = bottom + (margins[2] * POINTS_PER_INCH) as f32 / 2.0 - PAGE_NUMBER_FONT_SIZE / 3.0;
+
+        let content = Content {
+            operations: text_operations(&footer_text, PAGE_NUMBER_FONT_SIZE, x, y),
+        };
+
+        add_page_font(doc, page_id, STAMP_FONT_RESOURCE, font_id)?;
+        append_page_content(doc, page_id, content.encode()?)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId};
+
+/// Page attributes that a page can inherit from its ancestors in the page tree.
+const INHERITABLE_PAGE_ATTRIBUTES: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Catalog entries that describe the original pages of a document, so no longer apply once the
+/// page tree has been rebuilt with different pages.
+pub const PAGE_DEPENDENT_CATALOG_ENTRIES: [&[u8]; 4] =
+    [b"Outlines", b"PageLabels", b"StructTreeRoot", b"MarkInfo"];
+
+/// This function returns a copy of a page dictionary with every inherited attribute copied in
+/// from its ancestors in the page tree.
+///
+/// # Arguments
+///
+/// * `doc` - A reference to the lopdf `Document` containing the page.
+/// * `page_id` - The `ObjectId` of the page.
+///
+/// # Returns
+///
+/// * `Ok(Dictionary)` with the page dictionary, safe to move under a different page tree.
+/// * `Err(e)` if the page is not a dictionary.
+///
+/// # Remarks
+///
+/// `Resources`, `MediaBox`, `CropBox` and `Rotate` can be set on a `Pages` node instead of on
+/// each page. Without copying them, a page moved into a new page tree (when merging or extracting
+/// pages) would lose them.
+pub fn page_with_inherited_attributes(
+    doc: &Document,
+    page_id: ObjectId,
+) -> Result<Dictionary, lopdf::Error> {
+    let mut page = doc.get_dictionary(page_id)?.clone();
+
+    let mut parent = page.get(b"Parent").and_then(LopdfObject::as_reference).ok();
+    // Guard against cyclic page trees in malformed documents
+    let mut depth = 0;
+    while let Some(parent_id) = parent {
+        let Ok(ancestor) = doc.get_dictionary(parent_id) else {
+            break;
+        };
+        for key in INHERITABLE_PAGE_ATTRIBUTES {
+            if !page.has(key) {
+                if let Ok(value) = ancestor.get(key) {
+                    page.set(key, value.clone());
+                }
+            }
+        }
+        parent = ancestor
+            .get(b"Parent")
+            .and_then(LopdfObject::as_reference)
+            .ok();
+        depth += 1;
+        if depth > 64 {
+            break;
+        }
+    }
+
+    Ok(page)
+}
+
+/// This function replaces the page tree of a document with a single `Pages` node holding the given pages.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`. Its trailer must point at a catalog.
+/// * `pages` - The pages, in order, each with its (already flattened) page dictionary.
+///
+/// # Returns
+///
+/// * `Ok(ObjectId)` with the `ObjectId` of the new `Pages` node.
+/// * `Err(e)` if the document has no catalog.
+pub fn rebuild_page_tree(
+    doc: &mut Document,
+    pages: Vec<(ObjectId, Dictionary)>,
+) -> Result<ObjectId, lopdf::Error> {
+    let pages_id = doc.new_object_id();
+    let mut kids: Vec<LopdfObject> = Vec::with_capacity(pages.len());
+
+    for (page_id, mut page) in pages {
+        page.set("Parent", pages_id);
+        doc.objects.insert(page_id, LopdfObject::Dictionary(page));
+        kids.push(LopdfObject::Reference(page_id));
+    }
+
+    let mut pages_dictionary = Dictionary::new();
+    pages_dictionary.set("Type", LopdfObject::Name(b"Pages".to_vec()));
+    pages_dictionary.set("Count", kids.len() as i64);
+    pages_dictionary.set("Kids", kids);
+    doc.objects
+        .insert(pages_id, LopdfObject::Dictionary(pages_dictionary));
+
+    doc.catalog_mut()?.set("Pages", pages_id);
+
+    Ok(pages_id)
+}
@@ -0,0 +1,196 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use async_std::task;
+use chromiumoxide::{Browser, BrowserConfig};
+use futures::StreamExt;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use pdf_composer_definitions::persistent_browser::PersistentBrowserHandle;
+
+/// How often an idle persistent browser is checked on.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Struct holding a warm headless Chromium, shared by the `generate_pdfs` calls of one composer
+/// (which holds it as a `PersistentBrowserHandle`) instead of launching Chromium for every PDF.
+pub struct PersistentBrowser {
+    /// How long the browser is kept alive without being used before it is shut down (it is
+    /// launched again when next needed).
+    pub idle_timeout: Duration,
+    /// The running browser, or `None` before the first PDF and after an idle shutdown.
+    running: Mutex<Option<RunningBrowser>>,
+}
+
+/// Struct representing a running persistent browser.
+struct RunningBrowser {
+    /// The browser (each PDF being printed holds a reference, so the browser is only shut down
+    /// when no PDF is being printed).
+    browser: Arc<Browser>,
+    /// When the browser was last seen in use.
+    last_used: Instant,
+}
+
+impl PersistentBrowser {
+    /// Creates a persistent browser, launched when the first PDF is generated, that shuts down
+    /// after `idle_timeout` without use.
+    pub fn new(idle_timeout: Duration) -> Self {
+        PersistentBrowser {
+            idle_timeout,
+            running: Mutex::new(None),
+        }
+    }
+}
+
+impl fmt::Debug for PersistentBrowser {
+    /// Implements the Debug trait for PersistentBrowser, showing the idle timeout and whether the
+    /// browser is running.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistentBrowser")
+            .field("idle_timeout", &self.idle_timeout)
+            .field("running", &lock_running(self).is_some())
+            .finish()
+    }
+}
+
+/// This function returns the persistent browser a composer holds.
+///
+/// # Arguments
+///
+/// * `handle` - The `persistent_browser` of the composer.
+///
+/// # Returns
+///
+/// The `PersistentBrowser` behind the handle, or `None` if the composer has none.
+pub fn shared_browser(handle: Option<&PersistentBrowserHandle>) -> Option<Arc<PersistentBrowser>> {
+    handle.and_then(|handle| handle.clone().downcast::<PersistentBrowser>().ok())
+}
+
+/// This function returns the headless Chromium browser to print a PDF with.
+///
+/// # Arguments
+///
+/// * `persistent_browser` - The persistent browser of the composer (`None` to launch a browser just for this PDF).
+///
+/// # Returns
+///
+/// * `Ok(Arc<Browser>)` with the browser. A browser launched just for one PDF is shut down when it is dropped.
+/// * `Err(e)` if Chromium could not be launched.
+///
+/// # Remarks
+///
+/// A persistent browser is launched the first time it is needed (or again after an idle
+/// shutdown) and then kept running, with a background thread shutting it down once it has not
+/// been used for the idle timeout. Documents generated in parallel share it, each in a tab of its
+/// own.
+pub async fn browser_for(
+    persistent_browser: Option<&Arc<PersistentBrowser>>,
+) -> Result<Arc<Browser>, Box<dyn Error>> {
+    let Some(persistent_browser) = persistent_browser else {
+        return Ok(Arc::new(launch_browser().await?));
+    };
+
+    if let Some(running) = lock_running(persistent_browser).as_mut() {
+        running.last_used = Instant::now();
+        return Ok(running.browser.clone());
+    }
+
+    // The lock is not held while Chromium starts, so another thread may launch one too
+    let browser = Arc::new(launch_browser().await?);
+    let mut running = lock_running(persistent_browser);
+    if let Some(running) = running.as_mut() {
+        running.last_used = Instant::now();
+        return Ok(running.browser.clone());
+    }
+    *running = Some(RunningBrowser {
+        browser: browser.clone(),
+        last_used: Instant::now(),
+    });
+    drop(running);
+
+    spawn_idle_shutdown(persistent_browser);
+    Ok(browser)
+}
+
+/// This function stops a persistent browser from being used again, after it failed to print a
+/// PDF (Chromium crashed, for example), so the next PDF launches a new one.
+///
+/// # Arguments
+///
+/// * `persistent_browser` - The persistent browser of the composer.
+/// * `browser` - The browser that failed.
+pub fn discard_browser(persistent_browser: &PersistentBrowser, browser: &Arc<Browser>) {
+    let mut running = lock_running(persistent_browser);
+    if running
+        .as_ref()
+        .is_some_and(|running| Arc::ptr_eq(&running.browser, browser))
+    {
+        *running = None;
+    }
+}
+
+/// Launches a headless Chromium, driving its connection on a background task.
+async fn launch_browser() -> Result<Browser, Box<dyn Error>> {
+    let (browser, mut handler) = Browser::launch(BrowserConfig::builder().build()?).await?;
+
+    task::spawn(async move {
+        // The handler ends once the browser is shut down
+        while handler.next().await.is_some() {}
+    });
+    Ok(browser)
+}
+
+/// Starts the background thread that shuts a persistent browser down once it has been idle for
+/// its idle timeout. The thread ends when the browser is shut down or the composer is dropped.
+fn spawn_idle_shutdown(persistent_browser: &Arc<PersistentBrowser>) {
+    let persistent_browser = Arc::downgrade(persistent_browser);
+
+    thread::spawn(move || loop {
+        let Some(persistent_browser) = persistent_browser.upgrade() else {
+            return;
+        };
+        let mut running = lock_running(&persistent_browser);
+        let Some(running_browser) = running.as_mut() else {
+            return;
+        };
+        // PDFs being printed hold a reference to the browser
+        if Arc::strong_count(&running_browser.browser) > 1 {
+            running_browser.last_used = Instant::now();
+        }
+        let idle = running_browser.last_used.elapsed();
+        if idle >= persistent_browser.idle_timeout {
+            if let Some(running_browser) = running.take() {
+                drop(running);
+                shut_down(running_browser.browser);
+            }
+            return;
+        }
+        let wait = (persistent_browser.idle_timeout - idle).min(IDLE_CHECK_INTERVAL);
+        drop(running);
+        drop(persistent_browser);
+        thread::sleep(wait);
+    });
+}
+
+/// Closes a browser, waiting for Chromium to exit (a browser still in use is killed when its last
+/// reference is dropped instead).
+fn shut_down(browser: Arc<Browser>) {
+    if let Ok(mut browser) = Arc::try_unwrap(browser) {
+        task::block_on(async {
+            let _ = browser.close().await;
+            let _ = browser.wait().await;
+        });
+    }
+}
+
+/// Locks the running browser of a persistent browser (a thread panicking while holding the lock
+/// leaves the browser as it was).
+fn lock_running(persistent_browser: &PersistentBrowser) -> MutexGuard<'_, Option<RunningBrowser>> {
+    persistent_browser
+        .running
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
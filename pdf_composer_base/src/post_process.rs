@@ -0,0 +1,106 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::error::Error;
+use std::path::Path;
+use std::process::Command;
+
+/// This function runs the post-process command (such as an optimizer or validator) on a generated PDF.
+///
+/// # Arguments
+///
+/// * `command` - The command line, with the `{output}`, `{name}` and `{dir}` placeholders.
+/// * `output_file` - The path of the generated PDF.
+///
+/// # Returns
+///
+/// * `Ok(())` if the command ran and exited successfully.
+/// * `Err(e)` if the command could not be run, or exited with a failure status (with what it
+///   wrote to its standard error).
+///
+/// # Remarks
+///
+/// The command is not run by a shell: it is split into arguments at whitespace (an argument with
+/// spaces is quoted with `"` or `'`), and the placeholders are replaced in each argument, so a path
+/// with spaces stays one argument. `{output}` is the path of the PDF, `{name}` its file name without
+/// `.pdf`, and `{dir}` the directory it is in.
+pub fn post_process(command: &str, output_file: &Path) -> Result<(), Box<dyn Error>> {
+    let name = output_file
+        .file_stem()
+        .map(|file_stem| file_stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dir = output_file
+        .parent()
+        .map(|parent| parent.display().to_string())
+        .unwrap_or_default();
+    let output = output_file.display().to_string();
+
+    let arguments: Vec<String> = command_arguments(command)?
+        .iter()
+        .map(|argument| {
+            argument
+                .replace("{output}", &output)
+                .replace("{name}", &name)
+                .replace("{dir}", &dir)
+        })
+        .collect();
+    let (program, arguments) = arguments
+        .split_first()
+        .ok_or("the post-process command is empty")?;
+
+    let result = Command::new(program)
+        .args(arguments)
+        .output()
+        .map_err(|error| format!("`{}` could not be run: {}", program, error))?;
+    if result.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    let status = match result.status.code() {
+        Some(code) => format!("exited with status {}", code),
+        None => "was terminated".to_string(),
+    };
+    match stderr.trim() {
+        "" => Err(format!("`{}` {}", program, status).into()),
+        stderr => Err(format!("`{}` {}: {}", program, status, stderr).into()),
+    }
+}
+
+/// Splits a command line into its arguments, at whitespace outside quotes.
+fn command_arguments(command: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut arguments = Vec::new();
+    let mut argument = String::new();
+    let mut in_argument = false;
+    let mut quote: Option<char> = None;
+    for character in command.chars() {
+        match quote {
+            Some(open_quote) if character == open_quote => quote = None,
+            Some(_) => argument.push(character),
+            None if character == '"' || character == '\'' => {
+                quote = Some(character);
+                in_argument = true;
+            }
+            None if character.is_whitespace() => {
+                if in_argument {
+                    arguments.push(std::mem::take(&mut argument));
+                    in_argument = false;
+                }
+            }
+            None => {
+                argument.push(character);
+                in_argument = true;
+            }
+        }
+    }
+    if let Some(open_quote) = quote {
+        return Err(format!(
+            "the post-process command has an unclosed {} quote",
+            open_quote
+        )
+        .into());
+    }
+    if in_argument {
+        arguments.push(argument);
+    }
+    Ok(arguments)
+}
@@ -0,0 +1,218 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::asset_base::resolve_assets;
+use crate::document_links::rewrite_document_links;
+use crate::generate::{expand_document_variables, read_source_document, render_document_html};
+use crate::html_document::{
+    document_language, document_text_direction, html_document, page_css, screen_page_css,
+    stylesheet_css, PageStyle,
+};
+use crate::utils::{escape_html, latest_modification, media_type};
+use pdf_composer_definitions::asset_base::PathOrUrl;
+use pdf_composer_definitions::consts::CHECK_MARK;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// The path the preview page polls to find out whether the sources have changed.
+const VERSION_PATH: &str = "/__preview/version";
+
+/// The script that reloads the preview page when the sources change.
+const RELOAD_SCRIPT: &str = "<script>
+(function () {
+  var version = null;
+  setInterval(function () {
+    fetch('/__preview/version')
+      .then(function (response) { return response.text(); })
+      .then(function (latest) {
+        if (version !== null && latest !== version) { location.reload(); }
+        version = latest;
+      })
+      .catch(function () {});
+  }, 1000);
+})();
+</script>";
+
+/// This function serves the HTML generated for a source document over HTTP, reloading the page in
+/// the browser whenever the source document (or a file next to it) changes.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the settings to render the document with.
+/// * `source` - The path of the source document.
+/// * `address` - The address to listen on (for example, `127.0.0.1:4000`).
+///
+/// # Returns
+///
+/// * `Err(e)` if the server could not listen on `address`. Otherwise the server runs until the
+///   process is stopped.
+///
+/// # Remarks
+///
+/// The document is rendered again on every page load, with the same pipeline as the PDF, and
+/// the page styles apply on screen as well as in print. Other paths are served from the directory
+/// of the source document (or the asset base directory), so relative images and stylesheets work.
+/// With an asset base URL, relative links and images point to the URL instead.
+pub fn run_preview(
+    composer: &PDFComposerStruct,
+    source: &Path,
+    address: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let server = Server::http(address).map_err(|error| error.to_string())?;
+    let source_directory = source.parent().unwrap_or(Path::new("")).to_path_buf();
+    // Relative images and stylesheets are served from the asset base directory, if there is one
+    let asset_directory = match &composer.asset_base {
+        Some(PathOrUrl::Path(directory)) => directory.clone(),
+        _ => source_directory.clone(),
+    };
+
+    println!(
+        "{}Previewing {} at {} (press Ctrl+C to stop)",
+        CHECK_MARK.to_string().green(),
+        source.display().to_string().cyan(),
+        format!("http://{}/", server.server_addr()).yellow()
+    );
+
+    for request in server.incoming_requests() {
+        let path = request.url().split(['?', '#']).next().unwrap_or("/");
+        let path = url_escape::decode(path).to_string();
+
+        let response = match path.as_str() {
+            "/" => html_response(preview_page(composer, source)),
+            VERSION_PATH => Response::from_string(sources_version(source, &source_directory)),
+            _ => match static_file(&asset_directory, &path) {
+                Some((content, content_type)) => {
+                    Response::from_data(content).with_header(content_type_header(content_type))
+                }
+                None => Response::from_string("Not found").with_status_code(404),
+            },
+        };
+        respond(request, response);
+    }
+
+    Ok(())
+}
+
+/// Renders the preview page of the source document (or a page saying why it cannot be rendered).
+fn preview_page(composer: &PDFComposerStruct, source: &Path) -> String {
+    let filename = source.display().to_string();
+    let Ok((yaml_btreemap, markdown_content)) =
+        read_source_document(&filename, composer.console_output.is_quiet())
+    else {
+        return format!(
+            "<html><head><meta charset=\"utf-8\"><title>Preview</title></head><body>\
+             <p>{} does not start with valid YAML Front Matter.</p>{}</body></html>",
+            escape_html(&filename),
+            RELOAD_SCRIPT
+        );
+    };
+
+    let (yaml_btreemap, markdown_content) =
+        expand_document_variables(composer, source, yaml_btreemap, &markdown_content);
+    let generated_html = render_document_html(composer, source, &yaml_btreemap, &markdown_content);
+    // Links to other documents point where they do in the documents of the first output format
+    let generated_html = match &composer.asset_base {
+        Some(asset_base @ PathOrUrl::Url(_)) => {
+            resolve_assets(&generated_html, source, Some(asset_base))
+        }
+        _ => generated_html,
+    };
+    let generated_html = rewrite_document_links(
+        &generated_html,
+        source,
+        composer,
+        composer.output_formats.first().copied().unwrap_or_default(),
+    );
+
+    let text_direction = document_text_direction(&yaml_btreemap, composer.text_direction);
+    let language = document_language(
+        &yaml_btreemap,
+        composer.language.clone(),
+        composer.text_justification.as_ref(),
+    );
+    let title = yaml_btreemap
+        .get("title")
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            source
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+
+    let mut css = page_css(
+        &PageStyle {
+            paper_size: composer.paper_size,
+            orientation: composer.orientation,
+            margins: composer.margins,
+            mirrored_margins: composer.mirrored_margins,
+            font: composer.font,
+            text_direction,
+            text_justification: composer.text_justification.as_ref(),
+        },
+        "all",
+    );
+    // On screen, show the body as a sheet of paper with the page margins
+    css.push_str(&screen_page_css(
+        composer.paper_size,
+        composer.orientation,
+        composer.margins,
+    ));
+    css.push_str(&stylesheet_css(composer.stylesheet.as_deref()));
+    css.push_str(RELOAD_SCRIPT);
+
+    html_document(
+        &generated_html,
+        &title,
+        language.as_deref(),
+        text_direction,
+        &css,
+    )
+}
+
+/// Returns a value that changes whenever the source document, or a file near it, is modified.
+fn sources_version(source: &Path, source_directory: &Path) -> String {
+    latest_modification(source, source_directory, |_| false)
+        .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map_or("missing".to_string(), |duration| {
+            duration.as_millis().to_string()
+        })
+}
+
+/// Reads a file under the source directory, with its content type (`None` if there is no such file,
+/// or the path leaves the source directory).
+fn static_file(source_directory: &Path, path: &str) -> Option<(Vec<u8>, &'static str)> {
+    let relative_path = PathBuf::from(path.trim_start_matches('/'));
+    if relative_path
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let file = source_directory.join(&relative_path);
+    let content = fs::read(&file).ok()?;
+    Some((content, media_type(&file)))
+}
+
+/// Returns an HTML response.
+fn html_response(html: String) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_string(html).with_header(content_type_header("text/html; charset=utf-8"))
+}
+
+/// Returns a `Content-Type` header.
+fn content_type_header(content_type: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes())
+        .expect("content types are valid header values")
+}
+
+/// Sends a response (a browser that went away is not an error worth reporting).
+fn respond(request: Request, response: Response<std::io::Cursor<Vec<u8>>>) {
+    let _ = request.respond(response);
+}
@@ -0,0 +1,60 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use base64::Engine;
+use chromiumoxide::cdp::browser_protocol::page::{PrintToPdfParams, PrintToPdfReturns};
+use chromiumoxide::types::{Command, Method, MethodId};
+use chromiumoxide::Page;
+use serde::Serialize;
+
+/// `Page.printToPDF` command with the options the chromiumoxide bindings do not (yet) expose.
+///
+/// The standard `PrintToPdfParams` are flattened into the command, so Chromium sees a single
+/// `Page.printToPDF` call with the extra fields alongside the usual ones.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtendedPrintToPdfParams {
+    /// The standard print parameters (paper size, margins etc)
+    #[serde(flatten)]
+    pub params: PrintToPdfParams,
+    /// Ask Chromium to generate a tagged (accessible) PDF with a structure tree.
+    /// Left to Chromium's default when `None`.
+    #[serde(rename = "generateTaggedPDF", skip_serializing_if = "Option::is_none")]
+    pub generate_tagged_pdf: Option<bool>,
+    /// Ask Chromium to generate a document outline (bookmarks) from the headings.
+    /// Left to Chromium's default when `None`.
+    #[serde(
+        rename = "generateDocumentOutline",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub generate_document_outline: Option<bool>,
+}
+
+impl Method for ExtendedPrintToPdfParams {
+    fn identifier(&self) -> MethodId {
+        PrintToPdfParams::IDENTIFIER.into()
+    }
+}
+
+impl Command for ExtendedPrintToPdfParams {
+    type Response = PrintToPdfReturns;
+}
+
+/// This function prints a page to PDF with the extended print parameters.
+///
+/// # Arguments
+///
+/// * `page` - A reference to the chromiumoxide `Page` to print.
+/// * `params` - The `ExtendedPrintToPdfParams` to print with.
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` containing the bytes of the generated PDF.
+/// * `Err(e)` if Chromium failed to print the page or returned invalid data.
+pub async fn print_to_pdf(
+    page: &Page,
+    params: ExtendedPrintToPdfParams,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let response = page.execute(params).await?;
+    let data: &str = response.data.as_ref();
+    Ok(base64::engine::general_purpose::STANDARD.decode(data)?)
+}
@@ -0,0 +1,41 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+use pdf_composer_definitions::progress::ProgressEvent;
+
+/// This function writes a progress event to the progress writer of the composer (if any), as a
+/// line of JSON, flushing it so the event can be followed as it happens.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` (for the progress writer, see `set_progress_writer`).
+/// * `event` - The progress event to write.
+///
+/// # Remarks
+///
+/// The line is written while holding the lock of the writer, so the events of documents generated
+/// in parallel are never mixed up in a line. A failed write is reported, but does not stop the
+/// generation.
+pub fn write_progress(composer: &PDFComposerStruct, event: ProgressEvent) {
+    let Some(progress_writer) = &composer.progress_writer else {
+        return;
+    };
+    let line = match serde_json::to_string(&event) {
+        Ok(line) => line,
+        Err(error) => {
+            eprintln!("{}Progress event not written: {}", CROSS_MARK.red(), error);
+            return;
+        }
+    };
+    // A writer left poisoned by a panic while writing is still usable for whole lines
+    let mut writer = progress_writer
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Err(error) = writeln!(writer, "{}", line).and_then(|()| writer.flush()) {
+        eprintln!("{}Progress event not written: {}", CROSS_MARK.red(), error);
+    }
+}
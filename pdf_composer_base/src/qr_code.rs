@@ -0,0 +1,141 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::content::{Content, Operation};
+use lopdf::Document;
+use qrcode::{Color, QrCode};
+
+use crate::shortcodes::{ShortcodeArguments, ShortcodeOutput};
+use crate::stamp::{append_page_content, horizontal_margins, page_media_box, POINTS_PER_INCH};
+use crate::utils::escape_html;
+use pdf_composer_definitions::page_properties::PageMargins;
+
+/// The blank border (in modules) scanners need around a QR code.
+const QUIET_ZONE: usize = 4;
+/// The size of a QR code shortcode when no `size` is given.
+const DEFAULT_QR_CODE_SIZE: &str = "3cm";
+/// The largest QR code stamped in the page footer, in points (one inch).
+const MAX_FOOTER_QR_CODE_SIZE: f32 = 72.0;
+
+/// Returns the width (in modules, without the quiet zone) and the dark modules of the QR code for `data`.
+fn qr_code_modules(data: &str) -> Result<(usize, Vec<bool>), String> {
+    let code = QrCode::new(data.as_bytes()).map_err(|error| error.to_string())?;
+    let modules = code
+        .to_colors()
+        .into_iter()
+        .map(|color| color == Color::Dark)
+        .collect();
+    Ok((code.width(), modules))
+}
+
+/// This function renders the `{{qr "data" size=3cm}}` shortcode as an inline SVG QR code.
+///
+/// # Arguments
+///
+/// * `arguments` - The shortcode arguments: the data to encode (usually a URL) and an optional
+///   `size` (any CSS length, `3cm` by default).
+///
+/// # Returns
+///
+/// * `Ok(ShortcodeOutput)` with the SVG.
+/// * `Err(message)` if there is no data or it is too long for a QR code.
+pub fn qr_code_shortcode(arguments: &ShortcodeArguments) -> Result<ShortcodeOutput, String> {
+    let data = arguments.required(0, "QR code data")?;
+    let size = arguments
+        .named
+        .get("size")
+        .map(String::as_str)
+        .unwrap_or(DEFAULT_QR_CODE_SIZE);
+
+    let (width, modules) = qr_code_modules(data)?;
+    let full_width = width + 2 * QUIET_ZONE;
+
+    let mut path = String::new();
+    for (index, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+        let x = index % width + QUIET_ZONE;
+        let y = index / width + QUIET_ZONE;
+        path.push_str(&format!("M{} {}h1v1h-1z", x, y));
+    }
+
+    Ok(ShortcodeOutput {
+        html: format!(
+            "<svg class=\"qr-code\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {w}\" \
+             style=\"width: {size}; height: {size}\" shape-rendering=\"crispEdges\" role=\"img\">\
+             <title>{title}</title><rect width=\"{w}\" height=\"{w}\" fill=\"#fff\"/>\
+             <path d=\"{path}\" fill=\"#000\"/></svg>",
+            w = full_width,
+            size = escape_html(size),
+            title = escape_html(data),
+            path = path
+        ),
+        block: false,
+    })
+}
+
+/// This function stamps a QR code in the bottom margin of every page, at the outer edge of the text.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `data` - The data to encode (usually the URL of the online version of the document).
+/// * `margins` - The page margins (in inches), used to place the QR code.
+/// * `mirrored_margins` - Whether the left and right margins swap on even pages.
+///
+/// # Returns
+///
+/// * `Ok(())` if every page was stamped.
+/// * `Err(e)` if the data is too long for a QR code or a page could not be updated.
+///
+/// # Remarks
+///
+/// The QR code fills most of the bottom margin, up to one inch square.
+pub fn stamp_qr_code_footer(
+    doc: &mut Document,
+    data: &str,
+    margins: PageMargins,
+    mirrored_margins: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (width, modules) = qr_code_modules(data)?;
+    let full_width = (width + 2 * QUIET_ZONE) as f32;
+
+    let bottom_margin = (margins[2] * POINTS_PER_INCH) as f32;
+    let size = (bottom_margin * 0.8).min(MAX_FOOTER_QR_CODE_SIZE);
+    let module_size = size / full_width;
+
+    for (page_number, page_id) in doc.get_pages() {
+        let Some([_left, bottom, right, _top]) = page_media_box(doc, page_id) else {
+            continue;
+        };
+        let (_left_margin, right_margin) =
+            horizontal_margins(margins, mirrored_margins, page_number);
+        let x = right - right_margin - size;
+        let y = bottom + (bottom_margin - size) / 2.0;
+
+        // White background (the quiet zone), then one filled square per dark module
+        let mut operations = vec![
+            Operation::new("g", vec![1.into()]),
+            Operation::new("re", vec![x.into(), y.into(), size.into(), size.into()]),
+            Operation::new("f", vec![]),
+            Operation::new("g", vec![0.into()]),
+        ];
+        for (index, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+            let column = (index % width + QUIET_ZONE) as f32;
+            let row = (index / width + QUIET_ZONE) as f32;
+            operations.push(Operation::new(
+                "re",
+                vec![
+                    (x + column * module_size).into(),
+                    (y + size - (row + 1.0) * module_size).into(),
+                    module_size.into(),
+                    module_size.into(),
+                ],
+            ));
+        }
+        operations.push(Operation::new("f", vec![]));
+
+        let content = Content { operations };
+        append_page_content(doc, page_id, content.encode()?)?;
+    }
+
+    Ok(())
+}
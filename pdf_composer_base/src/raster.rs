@@ -0,0 +1,79 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// A raster image with 8 bit samples, the components of each pixel interleaved (such as RGB), row
+/// by row from the top.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RasterImage {
+    /// The width in pixels.
+    pub width: usize,
+    /// The height in pixels.
+    pub height: usize,
+    /// The number of components (samples) per pixel: 1 for grey, 3 for RGB, 4 for CMYK.
+    pub components: usize,
+    /// The samples, `width * height * components` of them.
+    pub samples: Vec<u8>,
+}
+
+impl RasterImage {
+    /// This function resizes the image by averaging the source pixels each new pixel covers (a box
+    /// filter), which suits downsampling photos.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The new width in pixels (at least 1).
+    /// * `height` - The new height in pixels (at least 1).
+    ///
+    /// # Returns
+    ///
+    /// The resized `RasterImage`.
+    pub fn resize(&self, width: usize, height: usize) -> RasterImage {
+        let width = width.max(1);
+        let height = height.max(1);
+        let components = self.components;
+        let mut samples = Vec::with_capacity(width * height * components);
+
+        // The source columns each new column covers, worked out once for all rows
+        let column_spans: Vec<(usize, usize)> = (0..width)
+            .map(|x| source_span(x, width, self.width))
+            .collect();
+        let mut sums = vec![0u32; components];
+        for y in 0..height {
+            let (top, bottom) = source_span(y, height, self.height);
+            for &(left, right) in &column_spans {
+                sums.iter_mut().for_each(|sum| *sum = 0);
+                for source_y in top..bottom {
+                    let row = source_y * self.width;
+                    for source_x in left..right {
+                        let pixel = (row + source_x) * components;
+                        for (sum, sample) in sums
+                            .iter_mut()
+                            .zip(&self.samples[pixel..pixel + components])
+                        {
+                            *sum += *sample as u32;
+                        }
+                    }
+                }
+                let count = ((bottom - top) * (right - left)) as u32;
+                samples.extend(sums.iter().map(|sum| ((sum + count / 2) / count) as u8));
+            }
+        }
+
+        RasterImage {
+            width,
+            height,
+            components,
+            samples,
+        }
+    }
+}
+
+/// Returns the range of source pixels (along one side) a new pixel covers, at least one pixel.
+fn source_span(index: usize, new_size: usize, source_size: usize) -> (usize, usize) {
+    let start = index * source_size / new_size;
+    let end = ((index + 1) * source_size).div_ceil(new_size);
+    (
+        start.min(source_size - 1),
+        end.clamp(start + 1, source_size),
+    )
+}
@@ -0,0 +1,95 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use markdown::{CompileOptions, Constructs, Options, ParseOptions};
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::callouts::{fenced_callouts_to_blockquotes, style_callouts};
+use crate::code_blocks::{format_code_blocks, mark_code_highlights};
+use crate::cross_references::resolve_cross_references;
+use crate::glossary::process_glossary;
+use crate::heading_ids::add_heading_ids;
+use crate::image_attributes::apply_image_attributes;
+use crate::shortcodes::{expand_shortcodes, insert_shortcode_output, ShortcodeContext};
+use crate::utils::unescape_placeholders;
+
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function converts the markdown of a source document to HTML, using the composer settings.
+///
+/// # Arguments
+///
+/// * `markdown` - The markdown content (with the YAML Front Matter placeholders already replaced).
+/// * `front_matter` - The YAML Front Matter of the document (for shortcodes such as charts).
+/// * `source_directory` - The directory of the source document (for shortcodes reading files).
+/// * `composer` - The `PDFComposerStruct` whose settings (endnotes etc) control the conversion.
+///
+/// # Returns
+///
+/// A `String` containing the HTML.
+///
+/// # Remarks
+///
+/// GitHub flavoured markdown tables and callouts (`> [!NOTE]` blockquotes or `:::note` fenced blocks)
+/// are supported, images can be sized with attribute blocks
+/// (see `apply_image_attributes`), and labelled headings, figures and tables can be
+/// cross-referenced (see `resolve_cross_references`). Lines of fenced code blocks can be
+/// highlighted (` ```rust {3-5} `), numbered, and long lines wrapped (see `format_code_blocks`). Glossary terms defined in the front
+/// matter are linked to a glossary section at the end (see `process_glossary`). Every heading gets an id, so `#heading` links
+/// work (see `add_heading_ids`). `[[wiki links]]` are left for `rewrite_document_links`, as their
+/// targets depend on the output format.
+/// `{{name ...}}` shortcodes are rendered (see `expand_shortcodes`), and then escaped placeholders
+/// turned into literal text (see `unescape_placeholders`).
+///
+/// With endnotes turned on, markdown footnotes (`[^1]` references and `[^1]: ...` definitions) are
+/// collected into a notes section, with the given title, at the end of the document. Each note
+/// links back to where it is referenced.
+pub fn render_markdown(
+    markdown: &str,
+    front_matter: &BTreeMap<String, Value>,
+    source_directory: &Path,
+    composer: &PDFComposerStruct,
+) -> String {
+    let endnotes = composer.endnotes_title.is_some();
+    let options = Options {
+        parse: ParseOptions {
+            constructs: Constructs {
+                gfm_table: true,
+                gfm_footnote_definition: endnotes,
+                gfm_label_start_footnote: endnotes,
+                ..Constructs::default()
+            },
+            ..ParseOptions::default()
+        },
+        compile: CompileOptions {
+            gfm_footnote_label: composer.endnotes_title.clone(),
+            gfm_footnote_label_tag_name: Some("h2".to_string()),
+            gfm_footnote_label_attributes: Some("class=\"endnotes-title\"".to_string()),
+            gfm_footnote_back_label: Some("Back to the text".to_string()),
+            ..CompileOptions::default()
+        },
+    };
+
+    // Table and footnote syntax never fails to parse in plain markdown (no MDX), so this falls back
+    // to the default conversion only in theory
+    let (markdown, shortcode_outputs) = expand_shortcodes(
+        markdown,
+        &ShortcodeContext {
+            front_matter,
+            source_directory,
+            registered: &composer.shortcodes,
+        },
+    );
+    let markdown = unescape_placeholders(&fenced_callouts_to_blockquotes(&mark_code_highlights(
+        &markdown,
+    )));
+    let html = markdown::to_html_with_options(&markdown, &options)
+        .unwrap_or_else(|_| markdown::to_html(&markdown));
+
+    let html = resolve_cross_references(&apply_image_attributes(&style_callouts(&html)));
+    let html = format_code_blocks(&html, composer.code_line_numbers, composer.code_wrap);
+    let html = add_heading_ids(&process_glossary(&html, front_matter));
+    insert_shortcode_output(&html, &shortcode_outputs)
+}
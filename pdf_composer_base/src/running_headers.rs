@@ -0,0 +1,272 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object as LopdfObject, ObjectId};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::stamp::{
+    add_page_font, add_standard_font, append_page_content, horizontal_margins, page_media_box,
+    stamp_font_name, text_operations, POINTS_PER_INCH, STAMP_FONT_RESOURCE,
+};
+use crate::utils::decode_pdf_text_string;
+use pdf_composer_definitions::fonts::FontsStandard;
+use pdf_composer_definitions::page_properties::PageMargins;
+use pdf_composer_definitions::running_headers::{RunningHeaderLevel, RunningHeaders};
+
+/// Font size (in points) of the running header text.
+const RUNNING_HEADER_FONT_SIZE: f32 = 9.0;
+
+/// A heading from the document outline and the page it starts on.
+#[derive(Debug)]
+pub struct OutlineHeading {
+    /// Depth of the heading in the outline (`1` for top level headings)
+    pub level: usize,
+    /// The heading text
+    pub title: String,
+    /// The page the heading starts on (starting at `1`)
+    pub page_number: u32,
+}
+
+/// This function reads the headings of a document, and the pages they start on, from its outline (bookmarks).
+///
+/// # Arguments
+///
+/// * `doc` - A reference to the lopdf `Document`.
+///
+/// # Returns
+///
+/// A `Vec<OutlineHeading>` in document order. Empty if the document has no outline.
+pub fn outline_headings(doc: &Document) -> Vec<OutlineHeading> {
+    let page_numbers: BTreeMap<ObjectId, u32> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(page_number, page_id)| (page_id, page_number))
+        .collect();
+
+    let mut headings: Vec<OutlineHeading> = Vec::new();
+    let Some(first_item) = doc
+        .catalog()
+        .ok()
+        .and_then(|catalog| catalog.get(b"Outlines").ok())
+        .and_then(|outlines| doc.dereference(outlines).ok())
+        .and_then(|(_id, outlines)| outlines.as_dict().ok())
+        .and_then(|outlines| outlines.get(b"First").ok())
+        .and_then(|first| first.as_reference().ok())
+    else {
+        return headings;
+    };
+
+    // Depth first walk of the outline: (item, level). Guard against cycles in malformed outlines.
+    let mut visited: BTreeSet<ObjectId> = BTreeSet::new();
+    let mut stack: Vec<(ObjectId, usize)> = vec![(first_item, 1)];
+    while let Some((item_id, level)) = stack.pop() {
+        if !visited.insert(item_id) {
+            continue;
+        }
+        let Ok(item) = doc.get_dictionary(item_id) else {
+            continue;
+        };
+
+        let title = item
+            .get(b"Title")
+            .and_then(LopdfObject::as_str)
+            .map(decode_pdf_text_string)
+            .unwrap_or_default();
+        if let Some(page_number) =
+            destination_page(doc, item).and_then(|page_id| page_numbers.get(&page_id))
+        {
+            headings.push(OutlineHeading {
+                level,
+                title: title.trim().to_string(),
+                page_number: *page_number,
+            });
+        }
+
+        // Push the next sibling first so the children are visited before it
+        if let Ok(next) = item.get(b"Next").and_then(LopdfObject::as_reference) {
+            stack.push((next, level));
+        }
+        if let Ok(child) = item.get(b"First").and_then(LopdfObject::as_reference) {
+            stack.push((child, level + 1));
+        }
+    }
+
+    headings
+}
+
+/// Returns the page an outline item points at, following `/Dest`, `/A` (GoTo actions) and named destinations.
+fn destination_page(doc: &Document, item: &Dictionary) -> Option<ObjectId> {
+    let destination = item.get(b"Dest").ok().or_else(|| {
+        item.get(b"A")
+            .ok()
+            .and_then(|action| doc.dereference(action).ok())
+            .and_then(|(_id, action)| action.as_dict().ok())
+            .and_then(|action| action.get(b"D").ok())
+    })?;
+    resolve_destination(doc, destination, 0)
+}
+
+/// Resolves an explicit (`[page /XYZ ...]`) or named destination to the page it points at.
+fn resolve_destination(doc: &Document, destination: &LopdfObject, depth: u8) -> Option<ObjectId> {
+    if depth > 8 {
+        return None;
+    }
+    match doc.dereference(destination).ok()?.1 {
+        LopdfObject::Array(explicit) => explicit.first()?.as_reference().ok(),
+        LopdfObject::Dictionary(named) => {
+            resolve_destination(doc, named.get(b"D").ok()?, depth + 1)
+        }
+        LopdfObject::Name(name) => {
+            let dests = doc.catalog().ok()?.get(b"Dests").ok()?;
+            let dests = doc.dereference(dests).ok()?.1.as_dict().ok()?;
+            resolve_destination(doc, dests.get(name).ok()?, depth + 1)
+        }
+        LopdfObject::String(name, _) => {
+            let names = doc.catalog().ok()?.get(b"Names").ok()?;
+            let names = doc.dereference(names).ok()?.1.as_dict().ok()?;
+            let tree = doc.dereference(names.get(b"Dests").ok()?).ok()?.1;
+            let target = lookup_name_tree(doc, tree, name, 0)?;
+            resolve_destination(doc, target, depth + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Looks up `key` in a PDF name tree.
+fn lookup_name_tree<'a>(
+    doc: &'a Document,
+    node: &'a LopdfObject,
+    key: &[u8],
+    depth: u8,
+) -> Option<&'a LopdfObject> {
+    if depth > 32 {
+        return None;
+    }
+    let node = doc.dereference(node).ok()?.1.as_dict().ok()?;
+    if let Ok(names) = node.get(b"Names").and_then(LopdfObject::as_array) {
+        for pair in names.chunks_exact(2) {
+            if pair[0].as_str().ok() == Some(key) {
+                return Some(&pair[1]);
+            }
+        }
+    }
+    if let Ok(kids) = node.get(b"Kids").and_then(LopdfObject::as_array) {
+        for kid in kids {
+            if let Some(found) = lookup_name_tree(doc, kid, key, depth + 1) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+/// This function works out the running header text for each page from the headings of the document.
+///
+/// # Arguments
+///
+/// * `headings` - The headings and the pages they start on, in document order.
+/// * `page_count` - The number of pages in the document.
+/// * `options` - The `RunningHeaders` options.
+///
+/// # Returns
+///
+/// A `BTreeMap` of page number (starting at `1`) to header text. Pages without a header are left out.
+///
+/// # Remarks
+///
+/// A page shows the first heading that starts on it. Pages without a heading of their own show
+/// the last heading started on an earlier page.
+pub fn running_header_texts(
+    headings: &[OutlineHeading],
+    page_count: u32,
+    options: RunningHeaders,
+) -> BTreeMap<u32, String> {
+    let max_level = match options.level {
+        RunningHeaderLevel::H1 => 1,
+        RunningHeaderLevel::H2 => 2,
+    };
+    let headings: Vec<&OutlineHeading> = headings
+        .iter()
+        .filter(|heading| heading.level <= max_level && !heading.title.is_empty())
+        .collect();
+
+    let mut header_texts: BTreeMap<u32, String> = BTreeMap::new();
+    let mut current_heading: Option<&str> = None;
+    for page_number in 1..=page_count {
+        let starting_here: Vec<&&OutlineHeading> = headings
+            .iter()
+            .filter(|heading| heading.page_number == page_number)
+            .collect();
+        let chapter_page = starting_here.iter().any(|heading| heading.level == 1);
+
+        let header_text = starting_here
+            .first()
+            .map(|heading| heading.title.as_str())
+            .or(current_heading);
+        if let Some(last) = starting_here.last() {
+            current_heading = Some(last.title.as_str());
+        }
+
+        if let Some(text) = header_text {
+            if !(options.skip_chapter_pages && chapter_page) {
+                header_texts.insert(page_number, text.to_string());
+            }
+        }
+    }
+
+    header_texts
+}
+
+/// This function stamps the current section title at the top of each page of a generated PDF.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`. Its outline (bookmarks) supplies the headings.
+/// * `options` - The `RunningHeaders` options.
+/// * `font` - The font of the document (Symbol and Zapf Dingbats fall back to Helvetica).
+/// * `margins` - The page margins, used to position the header in the top margin.
+/// * `mirrored_margins` - Whether `margins` are top, outer, bottom, inner and alternate on left and right pages.
+///
+/// # Returns
+///
+/// * `Ok(usize)` with the number of pages a header was added to (`0` if the document has no outline).
+/// * `Err(e)` if a page could not be updated.
+pub fn stamp_running_headers(
+    doc: &mut Document,
+    options: RunningHeaders,
+    font: FontsStandard,
+    margins: PageMargins,
+    mirrored_margins: bool,
+) -> Result<usize, lopdf::Error> {
+    let headings = outline_headings(doc);
+    let pages = doc.get_pages();
+    let header_texts = running_header_texts(&headings, pages.len() as u32, options);
+    if header_texts.is_empty() {
+        return Ok(0);
+    }
+
+    let font_id = add_standard_font(doc, stamp_font_name(font));
+
+    for (page_number, text) in &header_texts {
+        let page_id = pages[page_number];
+        let Some([left, _bottom, _right, top]) = page_media_box(doc, page_id) else {
+            continue;
+        };
+        let (left_margin, _right_margin) =
+            horizontal_margins(margins, mirrored_margins, *page_number);
+
+        // Vertically centre the text in the top margin
+        let x = left + left_margin;
+        let y = top - (margins[0] * POINTS_PER_INCH) as f32 / 2.0 - RUNNING_HEADER_FONT_SIZE / 3.0;
+
+        let content = Content {
+            operations: text_operations(text, RUNNING_HEADER_FONT_SIZE, x, y),
+        };
+
+        add_page_font(doc, page_id, STAMP_FONT_RESOURCE, font_id)?;
+        append_page_content(doc, page_id, content.encode()?)?;
+    }
+
+    Ok(header_texts.len())
+}
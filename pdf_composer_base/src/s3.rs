@@ -0,0 +1,263 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::utils::utc_timestamp;
+use pdf_composer_definitions::upload::{UploadMetadata, Uploader, Url};
+
+/// How long an upload may take (connecting, sending the file and reading the reply).
+const S3_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The largest error reply read from S3 (the XML error document is a few hundred bytes).
+const MAX_S3_ERROR_SIZE: u64 = 64 * 1024;
+
+/// The document information entries sent as S3 user metadata (`x-amz-meta-title` etc).
+const METADATA_ENTRIES: [&str; 3] = ["Title", "Author", "Subject"];
+
+/// Struct representing an uploader publishing the generated files to an Amazon S3 bucket (or a
+/// bucket of an S3 compatible service, such as MinIO), with requests signed with AWS Signature
+/// Version 4
+#[derive(Clone)]
+pub struct S3Uploader {
+    /// The name of the bucket
+    pub bucket: String,
+    /// The region of the bucket (for example, `eu-west-2`)
+    pub region: String,
+    /// The start of the object key of every file (for example, `reports/2024/`), before the file name
+    pub key_prefix: String,
+    /// The endpoint of an S3 compatible service (for example, `http://localhost:9000`), addressed with path-style URLs (`None` for Amazon S3)
+    pub endpoint: Option<String>,
+    /// The access key ID of the credentials
+    pub access_key_id: String,
+    /// The secret access key of the credentials
+    pub secret_access_key: String,
+    /// The session token of temporary credentials (`None` for long-term credentials)
+    pub session_token: Option<String>,
+}
+
+impl S3Uploader {
+    /// Creates an uploader for a bucket, with the credentials of the `AWS_ACCESS_KEY_ID`,
+    /// `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN` environment variables
+    pub fn new(bucket: &str, region: &str) -> Self {
+        S3Uploader {
+            bucket: bucket.to_string(),
+            region: region.to_string(),
+            key_prefix: String::new(),
+            endpoint: None,
+            access_key_id: env::var("AWS_ACCESS_KEY_ID").unwrap_or_default(),
+            secret_access_key: env::var("AWS_SECRET_ACCESS_KEY").unwrap_or_default(),
+            session_token: env::var("AWS_SESSION_TOKEN").ok(),
+        }
+    }
+
+    /// Returns the URL of an object, with its key encoded as the signature expects it.
+    fn object_url(&self, key: &str) -> Result<Url, Box<dyn Error + Send + Sync>> {
+        let key = uri_encode(key, false);
+        let url = match &self.endpoint {
+            Some(endpoint) => format!(
+                "{}/{}/{}",
+                endpoint.trim_end_matches('/'),
+                uri_encode(&self.bucket, true),
+                key
+            ),
+            None => format!(
+                "https://{}.s3.{}.amazonaws.com/{}",
+                self.bucket, self.region, key
+            ),
+        };
+        Ok(Url::parse(&url)?)
+    }
+
+    /// Returns the `Authorization` header of a request, signed with AWS Signature Version 4.
+    ///
+    /// `headers` are the headers to sign (with lowercase names), which include `host` and
+    /// `x-amz-date`; `amz_date` is the time of the request (`20240501T093000Z`).
+    fn authorization(
+        &self,
+        method: &str,
+        canonical_uri: &str,
+        headers: &BTreeMap<String, String>,
+        payload_hash: &str,
+        amz_date: &str,
+    ) -> String {
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let signed_headers = headers
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "{}\n{}\n\n{}\n{}\n{}",
+            method, canonical_uri, canonical_headers, signed_headers, payload_hash
+        );
+
+        let date = &amz_date[..8];
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = [self.region.as_bytes(), b"s3", b"aws4_request"]
+            .iter()
+            .fold(
+                hmac_sha256(
+                    format!("AWS4{}", self.secret_access_key).as_bytes(),
+                    date.as_bytes(),
+                ),
+                |key, data| hmac_sha256(&key, data),
+            );
+        let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+}
+
+/// Implement Debug for S3Uploader, leaving out the secret access key and session token
+impl fmt::Debug for S3Uploader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Uploader")
+            .field("bucket", &self.bucket)
+            .field("region", &self.region)
+            .field("key_prefix", &self.key_prefix)
+            .field("endpoint", &self.endpoint)
+            .field("access_key_id", &self.access_key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Implement Uploader for S3Uploader: each file is put in the bucket under the key prefix and its
+/// file name, with its media type, and the title, author and subject of a PDF as user metadata
+impl Uploader for S3Uploader {
+    fn upload(
+        &self,
+        path: &Path,
+        metadata: &UploadMetadata,
+    ) -> Result<Url, Box<dyn Error + Send + Sync>> {
+        if self.access_key_id.is_empty() || self.secret_access_key.is_empty() {
+            return Err(
+                "no AWS credentials (set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY)".into(),
+            );
+        }
+        let file_name = path
+            .file_name()
+            .ok_or("the file has no name")?
+            .to_string_lossy();
+        let url = self.object_url(&format!("{}{}", self.key_prefix, file_name))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            (None, _) => return Err(format!("{} has no host", url).into()),
+        };
+
+        let body = fs::read(path)?;
+        let payload_hash = hex(&Sha256::digest(&body));
+        let amz_date = utc_timestamp(SystemTime::now()).replace(['-', ':'], "");
+
+        let mut headers: BTreeMap<String, String> = BTreeMap::new();
+        headers.insert("content-type".to_string(), metadata.content_type.clone());
+        headers.insert("host".to_string(), host);
+        headers.insert("x-amz-content-sha256".to_string(), payload_hash.clone());
+        headers.insert("x-amz-date".to_string(), amz_date.clone());
+        if let Some(session_token) = &self.session_token {
+            headers.insert("x-amz-security-token".to_string(), session_token.clone());
+        }
+        // User metadata is sent as HTTP headers, so the values are percent-encoded
+        for entry in METADATA_ENTRIES {
+            if let Some(value) = metadata.info.get(entry) {
+                headers.insert(
+                    format!("x-amz-meta-{}", entry.to_lowercase()),
+                    url_escape::encode_component(value).to_string(),
+                );
+            }
+        }
+        let authorization =
+            self.authorization("PUT", url.path(), &headers, &payload_hash, &amz_date);
+
+        let agent = ureq::AgentBuilder::new().timeout(S3_TIMEOUT).build();
+        let request = headers
+            .iter()
+            .filter(|(name, _)| name.as_str() != "host")
+            .fold(agent.put(url.as_str()), |request, (name, value)| {
+                request.set(name, value)
+            })
+            .set("Authorization", &authorization);
+        match request.send_bytes(&body) {
+            Ok(_) => Ok(url),
+            Err(ureq::Error::Status(status, response)) => {
+                let mut reply = String::new();
+                let _ = response
+                    .into_reader()
+                    .take(MAX_S3_ERROR_SIZE)
+                    .read_to_string(&mut reply);
+                Err(format!("S3 replied {}: {}", status, s3_error_message(&reply)).into())
+            }
+            Err(error) => Err(format!("{}: {}", url, error).into()),
+        }
+    }
+}
+
+/// Returns the message of an S3 XML error document (or its code, or the document itself).
+fn s3_error_message(reply: &str) -> String {
+    let element = |name: &str| {
+        let start = reply.find(&format!("<{}>", name))? + name.len() + 2;
+        let end = reply[start..].find(&format!("</{}>", name))? + start;
+        Some(reply[start..end].to_string())
+    };
+    element("Message")
+        .or_else(|| element("Code"))
+        .unwrap_or_else(|| reply.trim().to_string())
+}
+
+/// Percent-encodes text as AWS Signature Version 4 expects it: everything but letters, digits and
+/// `-._~` (and `/`, unless `encode_slash`).
+fn uri_encode(text: &str, encode_slash: bool) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            b'/' if !encode_slash => "/".to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Returns the HMAC-SHA256 (RFC 2104) of a message.
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block_key = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block_key.map(|byte| byte ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block_key.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Returns bytes as lowercase hexadecimal.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
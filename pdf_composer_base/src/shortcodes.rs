@@ -0,0 +1,492 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use regex::{Captures, Regex};
+use serde_yml::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::barcode::barcode_shortcode;
+use crate::chart::chart_shortcode;
+use crate::csv_table::csv_table_shortcode;
+use crate::form_fields::form_field_shortcode;
+use crate::layout_shortcodes::{column_break_shortcode, columns_shortcode, page_break_shortcode};
+use crate::qr_code::qr_code_shortcode;
+use crate::utils::ESCAPED_PLACEHOLDER_PATTERN;
+use pdf_composer_definitions::consts::CROSS_MARK;
+use pdf_composer_definitions::shortcodes::{ShortcodeHandler, ShortcodeInvocation};
+
+/// Marks the start of a shortcode's place in the markdown. Private use characters pass through
+/// the markdown conversion untouched, so the shortcode output can be put back in the HTML.
+const SHORTCODE_START: char = '\u{E000}';
+/// Marks the end of a shortcode's place in the markdown.
+const SHORTCODE_END: char = '\u{E001}';
+
+/// The names of the built-in shortcodes.
+const SHORTCODE_NAMES: [&str; 5] = ["barcode", "chart", "field", "qr", "table"];
+
+/// The names of the built-in layout shortcodes, only written `{{< name >}}`.
+const LAYOUT_SHORTCODE_NAMES: [&str; 3] = ["columnbreak", "columns", "pagebreak"];
+
+/// The regular expression of the arguments of a shortcode.
+const SHORTCODE_ARGUMENTS_PATTERN: &str = r#"(?:\s+(?:[\w-]+=)?(?:"[^"\n]*"|[^\s"<>{}]+))*"#;
+
+/// Struct holding the arguments of a shortcode: `{{name "positional" key=value key="quoted value"}}`.
+pub struct ShortcodeArguments {
+    /// The arguments without a name, in order.
+    pub positional: Vec<String>,
+    /// The `key=value` arguments.
+    pub named: BTreeMap<String, String>,
+}
+
+impl ShortcodeArguments {
+    /// Returns the positional argument at `index`, or an error naming the argument that is missing.
+    pub fn required(&self, index: usize, description: &str) -> Result<&str, String> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing {}", description))
+    }
+}
+
+/// Struct holding what shortcodes can use from the document they are in.
+pub struct ShortcodeContext<'a> {
+    /// The YAML Front Matter of the document.
+    pub front_matter: &'a BTreeMap<String, Value>,
+    /// The directory of the source document (paths in shortcodes are relative to it).
+    pub source_directory: &'a Path,
+    /// The shortcodes registered with `register_shortcode`, by name.
+    pub registered: &'a BTreeMap<String, ShortcodeHandler>,
+}
+
+/// Struct holding the HTML a shortcode renders to.
+pub struct ShortcodeOutput {
+    /// The HTML.
+    pub html: String,
+    /// `true` if the HTML is a block (such as a table) that replaces the paragraph the shortcode is on.
+    pub block: bool,
+}
+
+/// This function replaces the shortcodes in the markdown with markers, and renders each shortcode.
+///
+/// # Arguments
+///
+/// * `markdown` - The markdown content (with the YAML Front Matter placeholders already replaced).
+/// * `context` - The document the markdown comes from.
+///
+/// # Returns
+///
+/// A tuple containing the markdown with markers in place of the shortcodes, and the rendered
+/// output of each shortcode (to put back with `insert_shortcode_output` once converted to HTML).
+///
+/// # Remarks
+///
+/// Shortcodes are written `{{name arguments}}`, or `{{< name arguments >}}` (see
+/// `expand_tag_shortcodes`). Shortcodes inside fenced code blocks are left alone, as are escaped
+/// shortcodes (`\{{name arguments}}`, see `unescape_placeholders`) and shortcodes that fail to
+/// render (with a warning).
+pub fn expand_shortcodes(
+    markdown: &str,
+    context: &ShortcodeContext,
+) -> (String, Vec<ShortcodeOutput>) {
+    let shortcode_regex = Regex::new(&format!(
+        r#"{}|\{{\{{\s*({})((?:\s+(?:[\w-]+=)?(?:"[^"\n]*"|[^\s"{{}}]+))*)\s*\}}\}}"#,
+        ESCAPED_PLACEHOLDER_PATTERN,
+        SHORTCODE_NAMES.join("|")
+    ))
+    .unwrap();
+
+    let mut outputs: Vec<ShortcodeOutput> = Vec::new();
+    let markdown = expand_tag_shortcodes(markdown, context, &mut outputs);
+    let mut expanded = String::with_capacity(markdown.len());
+    let mut code_fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(fence) = code_fence {
+            if trimmed.starts_with(fence) {
+                code_fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            code_fence = Some(&trimmed[..3]);
+        }
+
+        if code_fence.is_some() || !line.contains("{{") {
+            expanded.push_str(line);
+        } else {
+            let replaced = shortcode_regex.replace_all(line, |captures: &Captures| {
+                if captures.get(1).is_none() {
+                    return captures[0].to_string();
+                }
+                let arguments = parse_arguments(&captures[2]);
+                match render_shortcode(&captures[1], &arguments, context) {
+                    Ok(output) => {
+                        outputs.push(output);
+                        shortcode_marker(outputs.len() - 1)
+                    }
+                    Err(message) => {
+                        eprintln!(
+                            "{}{}",
+                            CROSS_MARK.yellow(),
+                            format!("Shortcode {} not rendered: {}", &captures[0], message)
+                                .yellow()
+                        );
+                        captures[0].to_string()
+                    }
+                }
+            });
+            expanded.push_str(&replaced);
+        }
+        expanded.push('\n');
+    }
+
+    (expanded, outputs)
+}
+
+/// A `{{< name arguments >}}` or `{{< /name >}}` tag found in the markdown.
+struct ShortcodeTag<'a> {
+    /// Where the tag starts in the markdown.
+    start: usize,
+    /// Where the tag ends in the markdown.
+    end: usize,
+    /// `true` for a closing tag (`{{< /name >}}`).
+    closing: bool,
+    /// The name of the shortcode.
+    name: &'a str,
+    /// The arguments, as written.
+    arguments: &'a str,
+}
+
+/// This function replaces the `{{< name arguments >}}` shortcodes in the markdown with markers,
+/// and renders each shortcode.
+///
+/// # Arguments
+///
+/// * `markdown` - The markdown content (with the YAML Front Matter placeholders already replaced).
+/// * `context` - The document the markdown comes from.
+/// * `outputs` - The rendered output of each shortcode, which the output of these shortcodes is added to.
+///
+/// # Returns
+///
+/// A `String` with the markdown, with markers in place of the shortcodes.
+///
+/// # Remarks
+///
+/// Besides the `{{name arguments}}` shortcodes, these are the layout shortcodes `{{< pagebreak >}}`,
+/// `{{< columns >}}...{{< /columns >}}` and `{{< columnbreak >}}`, and the shortcodes registered
+/// with `register_shortcode`. A registered shortcode with a closing tag is given the markdown
+/// between its tags; the markdown between `{{< columns >}}` tags is rendered in place. Block
+/// shortcodes are set apart from the text around them by blank lines, so they are never in a
+/// paragraph. Shortcodes inside fenced code blocks are left alone.
+fn expand_tag_shortcodes(
+    markdown: &str,
+    context: &ShortcodeContext,
+    outputs: &mut Vec<ShortcodeOutput>,
+) -> String {
+    if !markdown.contains("{{<") {
+        return markdown.to_string();
+    }
+
+    let mut expanded = String::with_capacity(markdown.len());
+    let mut text = String::new();
+    let mut code_fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(fence) = code_fence {
+            if trimmed.starts_with(fence) {
+                code_fence = None;
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            code_fence = Some(&trimmed[..3]);
+        }
+
+        if code_fence.is_some() {
+            expanded.push_str(&expand_tags(&text, context, outputs));
+            text.clear();
+            expanded.push_str(line);
+            expanded.push('\n');
+        } else {
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    expanded.push_str(&expand_tags(&text, context, outputs));
+    expanded
+}
+
+/// Replaces the `{{< name >}}` shortcodes of text outside code blocks with markers.
+fn expand_tags(
+    text: &str,
+    context: &ShortcodeContext,
+    outputs: &mut Vec<ShortcodeOutput>,
+) -> String {
+    if !text.contains("{{<") {
+        return text.to_string();
+    }
+
+    let tag_regex = Regex::new(&format!(
+        r"{}|\{{\{{<\s*(/)?([\w-]+)({})\s*>\}}\}}",
+        ESCAPED_PLACEHOLDER_PATTERN, SHORTCODE_ARGUMENTS_PATTERN
+    ))
+    .unwrap();
+    let tags: Vec<ShortcodeTag> = tag_regex
+        .captures_iter(text)
+        .filter_map(|captures| {
+            let name = captures.get(2)?;
+            let tag = captures.get(0)?;
+            Some(ShortcodeTag {
+                start: tag.start(),
+                end: tag.end(),
+                closing: captures.get(1).is_some(),
+                name: name.as_str(),
+                arguments: captures.get(3).map_or("", |arguments| arguments.as_str()),
+            })
+        })
+        .collect();
+
+    expand_tag_range(text, 0, text.len(), &tags, context, outputs)
+}
+
+/// Replaces the shortcode `tags` between `start` and `end` of the text with markers, rendering
+/// the markdown between the tags of `{{< columns >}}` in place.
+fn expand_tag_range(
+    text: &str,
+    start: usize,
+    end: usize,
+    tags: &[ShortcodeTag],
+    context: &ShortcodeContext,
+    outputs: &mut Vec<ShortcodeOutput>,
+) -> String {
+    let mut expanded = String::with_capacity(end - start);
+    let mut position = start;
+    let mut index = 0;
+    while let Some(tag) = tags.get(index) {
+        expanded.push_str(&text[position..tag.start]);
+        let written = &text[tag.start..tag.end];
+        position = tag.end;
+        index += 1;
+        if tag.closing {
+            // A closing tag without an opening tag is left as it is
+            expanded.push_str(written);
+            continue;
+        }
+
+        let arguments = parse_arguments(tag.arguments);
+        let starts_line = text[..tag.start].is_empty() || text[..tag.start].ends_with('\n');
+        let is_built_in =
+            SHORTCODE_NAMES.contains(&tag.name) || LAYOUT_SHORTCODE_NAMES.contains(&tag.name);
+        let takes_content =
+            tag.name == "columns" || (!is_built_in && context.registered.contains_key(tag.name));
+        let closing_index = if takes_content {
+            closing_tag_index(&tags[index..], tag.name).map(|closing_index| index + closing_index)
+        } else {
+            None
+        };
+
+        let rendered = match closing_index {
+            Some(closing_index) => {
+                let closing = &tags[closing_index];
+                let inner_tags = &tags[index..closing_index];
+                let original = &text[tag.start..closing.end];
+                position = closing.end;
+                index = closing_index + 1;
+                if tag.name == "columns" {
+                    columns_shortcode(&arguments).map(|(opening, closing_output)| {
+                        let mut rendered = String::new();
+                        rendered.push_str(&push_output(outputs, opening));
+                        rendered.push_str(&expand_tag_range(
+                            text,
+                            tag.end,
+                            closing.start,
+                            inner_tags,
+                            context,
+                            outputs,
+                        ));
+                        rendered.push_str(&push_output(outputs, closing_output));
+                        rendered
+                    })
+                } else {
+                    let inner = &text[tag.end..closing.start];
+                    registered_shortcode(tag.name, &arguments, Some(inner), starts_line, context)
+                        .map(|output| push_output(outputs, output))
+                }
+                .map_err(|message| (message, original))
+            }
+            None => render_tag_shortcode(tag.name, &arguments, starts_line, context)
+                .map(|output| push_output(outputs, output))
+                .map_err(|message| (message, written)),
+        };
+
+        match rendered {
+            Ok(rendered) => expanded.push_str(&rendered),
+            Err((message, original)) => {
+                eprintln!(
+                    "{}{}",
+                    CROSS_MARK.yellow(),
+                    format!("Shortcode {} not rendered: {}", written, message).yellow()
+                );
+                expanded.push_str(original);
+            }
+        }
+    }
+    expanded.push_str(&text[position..end]);
+    expanded
+}
+
+/// Returns the index of the tag closing a shortcode (skipping nested shortcodes of the same name).
+fn closing_tag_index(tags: &[ShortcodeTag], name: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (index, tag) in tags.iter().enumerate() {
+        if tag.name != name {
+            continue;
+        }
+        if !tag.closing {
+            depth += 1;
+        } else if depth == 0 {
+            return Some(index);
+        } else {
+            depth -= 1;
+        }
+    }
+    None
+}
+
+/// Renders a single `{{< name arguments >}}` shortcode (without a closing tag).
+fn render_tag_shortcode(
+    name: &str,
+    arguments: &ShortcodeArguments,
+    starts_line: bool,
+    context: &ShortcodeContext,
+) -> Result<ShortcodeOutput, String> {
+    match name {
+        "pagebreak" => Ok(page_break_shortcode()),
+        "columnbreak" => Ok(column_break_shortcode()),
+        "columns" => Err("missing {{< /columns >}}".to_string()),
+        _ if SHORTCODE_NAMES.contains(&name) => render_shortcode(name, arguments, context),
+        _ => registered_shortcode(name, arguments, None, starts_line, context),
+    }
+}
+
+/// Renders a shortcode registered with `register_shortcode`. Its HTML is a block when the
+/// shortcode starts a line.
+fn registered_shortcode(
+    name: &str,
+    arguments: &ShortcodeArguments,
+    inner: Option<&str>,
+    block: bool,
+    context: &ShortcodeContext,
+) -> Result<ShortcodeOutput, String> {
+    let handler = context
+        .registered
+        .get(name)
+        .ok_or_else(|| format!("unknown shortcode {}", name))?;
+    let html = handler(&ShortcodeInvocation {
+        name: name.to_string(),
+        positional: arguments.positional.clone(),
+        named: arguments.named.clone(),
+        inner: inner.map(str::to_string),
+    })?;
+    Ok(ShortcodeOutput { html, block })
+}
+
+/// Adds a rendered shortcode to the outputs, returning the marker to put in its place (on its own,
+/// between blank lines, for a block).
+fn push_output(outputs: &mut Vec<ShortcodeOutput>, output: ShortcodeOutput) -> String {
+    let block = output.block;
+    outputs.push(output);
+    let marker = shortcode_marker(outputs.len() - 1);
+    if block {
+        format!("\n\n{}\n\n", marker)
+    } else {
+        marker
+    }
+}
+
+/// Returns the marker of the shortcode output at `index`.
+fn shortcode_marker(index: usize) -> String {
+    format!("{}{}{}", SHORTCODE_START, index, SHORTCODE_END)
+}
+
+/// This function puts the rendered shortcodes back in the HTML, in place of their markers.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown returned by `expand_shortcodes`.
+/// * `outputs` - The rendered shortcodes returned by `expand_shortcodes`.
+///
+/// # Returns
+///
+/// A `String` with the markers replaced by the shortcode output.
+pub fn insert_shortcode_output(html: &str, outputs: &[ShortcodeOutput]) -> String {
+    if outputs.is_empty() {
+        return html.to_string();
+    }
+
+    let marker_regex = Regex::new(&format!(
+        r"(<p>)?{}(\d+){}(</p>)?",
+        SHORTCODE_START, SHORTCODE_END
+    ))
+    .unwrap();
+
+    marker_regex
+        .replace_all(html, |captures: &Captures| {
+            let Some(output) = captures[2]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| outputs.get(index))
+            else {
+                return String::new();
+            };
+            match (captures.get(1), captures.get(3)) {
+                // A block shortcode on its own paragraph takes the place of the paragraph
+                (Some(_), Some(_)) if output.block => output.html.to_string(),
+                (opening, closing) => format!(
+                    "{}{}{}",
+                    opening.map_or("", |opening| opening.as_str()),
+                    output.html,
+                    closing.map_or("", |closing| closing.as_str())
+                ),
+            }
+        })
+        .to_string()
+}
+
+/// Renders a single shortcode.
+fn render_shortcode(
+    name: &str,
+    arguments: &ShortcodeArguments,
+    context: &ShortcodeContext,
+) -> Result<ShortcodeOutput, String> {
+    match name {
+        "barcode" => barcode_shortcode(arguments),
+        "chart" => chart_shortcode(arguments, context),
+        "field" => form_field_shortcode(arguments),
+        "qr" => qr_code_shortcode(arguments),
+        "table" => csv_table_shortcode(arguments, context),
+        _ => Err(format!("unknown shortcode {}", name)),
+    }
+}
+
+/// Splits the arguments of a shortcode into positional and `key=value` arguments (removing any quotes).
+fn parse_arguments(arguments: &str) -> ShortcodeArguments {
+    let argument_regex = Regex::new(r#"(?:([\w-]+)=)?(?:"([^"\n]*)"|([^\s"]+))"#).unwrap();
+
+    let mut parsed = ShortcodeArguments {
+        positional: Vec::new(),
+        named: BTreeMap::new(),
+    };
+    for captures in argument_regex.captures_iter(arguments) {
+        let value = captures
+            .get(2)
+            .or(captures.get(3))
+            .map_or("", |value| value.as_str())
+            .to_string();
+        match captures.get(1) {
+            Some(key) => {
+                parsed.named.insert(key.as_str().to_string(), value);
+            }
+            None => parsed.positional.push(value),
+        }
+    }
+    parsed
+}
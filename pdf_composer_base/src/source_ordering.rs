@@ -0,0 +1,84 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use serde_yml::Value;
+use std::cmp::Ordering;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::utils::parse_front_matter;
+use pdf_composer_definitions::source_ordering::SourceOrdering;
+
+/// The value a document is ordered by with `SourceOrdering::ByFrontMatterKey`.
+enum SortKey {
+    Number(f64),
+    Text(String),
+}
+
+/// This function returns the source files in the order they are to be generated in.
+///
+/// # Arguments
+///
+/// * `source_files` - The source files, in the order they were added.
+/// * `source_ordering` - The `SourceOrdering` to put them in.
+///
+/// # Returns
+///
+/// The source files, in order. The sort is stable, so files that compare equal stay in the order
+/// they were added.
+///
+/// # Remarks
+///
+/// With `SourceOrdering::ByFrontMatterKey`, the front matter of each source file is read (without
+/// any messages; problems with a file are reported when it is generated). Files that cannot be
+/// read or have no such key come last.
+pub fn ordered_source_files(
+    source_files: &[PathBuf],
+    source_ordering: &SourceOrdering,
+) -> Vec<PathBuf> {
+    let mut ordered = source_files.to_vec();
+    match source_ordering {
+        SourceOrdering::AsAdded => {}
+        SourceOrdering::Alphabetical => {
+            ordered.sort_by_cached_key(|source_file| source_file.to_string_lossy().to_lowercase())
+        }
+        SourceOrdering::ByFrontMatterKey(key) => {
+            let mut keyed: Vec<(Option<SortKey>, PathBuf)> = ordered
+                .into_iter()
+                .map(|source_file| (front_matter_sort_key(&source_file, key), source_file))
+                .collect();
+            keyed.sort_by(|(first, _), (second, _)| compare_sort_keys(first, second));
+            ordered = keyed
+                .into_iter()
+                .map(|(_sort_key, source_file)| source_file)
+                .collect();
+        }
+    }
+    ordered
+}
+
+/// Returns the value of a front matter key of a source file to order it by.
+fn front_matter_sort_key(source_file: &Path, key: &str) -> Option<SortKey> {
+    let source = fs::read_to_string(source_file).ok()?;
+    let (yaml_content, _markdown_content) = parse_front_matter(&source).ok()?;
+    let yaml: Value = serde_yml::from_str(&yaml_content).ok()?;
+    match yaml.get(key)? {
+        Value::Number(number) => number.as_f64().map(SortKey::Number),
+        Value::String(text) => Some(SortKey::Text(text.to_lowercase())),
+        Value::Bool(boolean) => Some(SortKey::Text(boolean.to_string())),
+        _ => None,
+    }
+}
+
+/// Compares two sort keys: numbers before text, and documents without a key last.
+fn compare_sort_keys(first: &Option<SortKey>, second: &Option<SortKey>) -> Ordering {
+    match (first, second) {
+        (Some(SortKey::Number(first)), Some(SortKey::Number(second))) => first.total_cmp(second),
+        (Some(SortKey::Text(first)), Some(SortKey::Text(second))) => first.cmp(second),
+        (Some(SortKey::Number(_)), Some(SortKey::Text(_))) => Ordering::Less,
+        (Some(SortKey::Text(_)), Some(SortKey::Number(_))) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
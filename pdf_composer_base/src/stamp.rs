@@ -0,0 +1,235 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::content::Operation;
+use lopdf::{dictionary, Dictionary, Document, Object as LopdfObject, ObjectId, Stream};
+
+use crate::page_tree::page_with_inherited_attributes;
+use crate::utils::encode_win_ansi;
+use pdf_composer_definitions::fonts::{FontsStandard, GetPdfName};
+use pdf_composer_definitions::page_properties::PageMargins;
+
+/// Resource name the stamped text font is added to each page under.
+pub const STAMP_FONT_RESOURCE: &str = "PDFComposerF1";
+
+/// Points per inch (margins are kept in inches, PDF coordinates are in points).
+pub const POINTS_PER_INCH: f64 = 72.0;
+
+/// This function returns the standard 14 font used to stamp text in the document font.
+///
+/// # Arguments
+///
+/// * `font` - The font of the document.
+///
+/// # Returns
+///
+/// The PDF name of the font. Symbol and Zapf Dingbats have no letters or digits, so fall back to Helvetica.
+pub fn stamp_font_name(font: FontsStandard) -> &'static str {
+    match font {
+        FontsStandard::Symbol | FontsStandard::ZapfDingbats => "Helvetica",
+        _ => font.get_pdf_name(),
+    }
+}
+
+/// This function returns the approximate width of text drawn in a standard 14 font.
+///
+/// # Arguments
+///
+/// * `text` - The text to measure.
+/// * `base_font` - The PDF name of the font.
+/// * `font_size` - The font size in points.
+///
+/// # Returns
+///
+/// The width in points. Exact for Courier (every glyph is 600 units wide), an average glyph width otherwise.
+pub fn approximate_text_width(text: &str, base_font: &str, font_size: f32) -> f32 {
+    let glyph_width = if base_font.starts_with("Courier") {
+        0.6
+    } else {
+        0.5
+    };
+    text.chars().count() as f32 * glyph_width * font_size
+}
+
+/// This function returns the media box (`[left, bottom, right, top]` in points) of a page.
+///
+/// # Arguments
+///
+/// * `doc` - A reference to the lopdf `Document`.
+/// * `page_id` - The `ObjectId` of the page.
+///
+/// # Returns
+///
+/// `Some([left, bottom, right, top])`, or `None` if the page has no valid (or inherited) media box.
+pub fn page_media_box(doc: &Document, page_id: ObjectId) -> Option<[f32; 4]> {
+    let page = page_with_inherited_attributes(doc, page_id).ok()?;
+    let media_box = doc.dereference(page.get(b"MediaBox").ok()?).ok()?.1;
+    let values: Vec<f32> = media_box
+        .as_array()
+        .ok()?
+        .iter()
+        .filter_map(|value| value.as_float().ok())
+        .collect();
+    values.try_into().ok()
+}
+
+/// This function returns the left and right page margins of a page, in points.
+///
+/// # Arguments
+///
+/// * `margins` - The page margins in inches: top, right, bottom, left (or top, outer, bottom, inner when mirrored).
+/// * `mirrored_margins` - Whether the margins alternate on left and right pages.
+/// * `page_number` - The page number (starting at `1`). Odd pages are right-hand pages.
+///
+/// # Returns
+///
+/// A tuple of the left and right margins in points.
+pub fn horizontal_margins(
+    margins: PageMargins,
+    mirrored_margins: bool,
+    page_number: u32,
+) -> (f32, f32) {
+    let (left, right) = if mirrored_margins && page_number % 2 == 0 {
+        (margins[1], margins[3])
+    } else {
+        (margins[3], margins[1])
+    };
+    (
+        (left * POINTS_PER_INCH) as f32,
+        (right * POINTS_PER_INCH) as f32,
+    )
+}
+
+/// This function returns the content stream operators to draw a line of grey text.
+///
+/// # Arguments
+///
+/// * `text` - The text to draw. Characters the standard fonts cannot draw are shown as `?`.
+/// * `font_size` - The font size in points.
+/// * `x` - The horizontal position of the start of the text, in points.
+/// * `y` - The vertical position of the text baseline, in points.
+///
+/// # Returns
+///
+/// A `Vec<Operation>` using the font added to the page as `STAMP_FONT_RESOURCE`.
+pub fn text_operations(text: &str, font_size: f32, x: f32, y: f32) -> Vec<Operation> {
+    vec![
+        Operation::new("g", vec![0.3.into()]),
+        Operation::new("BT", vec![]),
+        Operation::new(
+            "Tf",
+            vec![
+                LopdfObject::Name(STAMP_FONT_RESOURCE.as_bytes().to_vec()),
+                font_size.into(),
+            ],
+        ),
+        Operation::new("Td", vec![x.into(), y.into()]),
+        Operation::new(
+            "Tj",
+            vec![LopdfObject::string_literal(encode_win_ansi(text))],
+        ),
+        Operation::new("ET", vec![]),
+    ]
+}
+
+/// This function adds a standard 14 font (with WinAnsiEncoding) to the document.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `base_font` - The PDF name of the font (for example, `Helvetica`).
+///
+/// # Returns
+///
+/// The `ObjectId` of the new font dictionary, to add to pages with `add_page_font`.
+pub fn add_standard_font(doc: &mut Document, base_font: &str) -> ObjectId {
+    doc.add_object(dictionary! {
+        "Type" => "Font",
+        "Subtype" => "Type1",
+        "BaseFont" => base_font,
+        "Encoding" => "WinAnsiEncoding",
+    })
+}
+
+/// This function makes a font available to the content of a page under the given resource name.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `page_id` - The `ObjectId` of the page.
+/// * `resource_name` - The name content streams use for the font (for example, `STAMP_FONT_RESOURCE`).
+/// * `font_id` - The `ObjectId` of the font dictionary.
+///
+/// # Returns
+///
+/// * `Ok(())` if the page resources were updated.
+/// * `Err(e)` if the page (or its resources) could not be read.
+///
+/// # Remarks
+///
+/// The page gets its own copy of its (possibly shared or inherited) resource dictionary,
+/// so other pages are not affected.
+pub fn add_page_font(
+    doc: &mut Document,
+    page_id: ObjectId,
+    resource_name: &str,
+    font_id: ObjectId,
+) -> Result<(), lopdf::Error> {
+    let page = page_with_inherited_attributes(doc, page_id)?;
+    let mut resources = match page.get(b"Resources") {
+        Ok(resources) => doc.dereference(resources)?.1.as_dict()?.clone(),
+        Err(_) => Dictionary::new(),
+    };
+    let mut fonts = match resources.get(b"Font") {
+        Ok(fonts) => doc.dereference(fonts)?.1.as_dict()?.clone(),
+        Err(_) => Dictionary::new(),
+    };
+    fonts.set(resource_name, font_id);
+    resources.set("Font", fonts);
+
+    doc.get_dictionary_mut(page_id)?.set("Resources", resources);
+    Ok(())
+}
+
+/// This function draws extra content on top of a page.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `page_id` - The `ObjectId` of the page.
+/// * `content` - The content stream operators to draw (for example, from `lopdf::content::Content::encode`).
+///
+/// # Returns
+///
+/// * `Ok(())` if the content was added.
+/// * `Err(e)` if the page could not be read.
+///
+/// # Remarks
+///
+/// The existing page content is wrapped in `q` / `Q` so any graphics state it leaves behind
+/// (transformations, colours etc) does not affect the added content.
+pub fn append_page_content(
+    doc: &mut Document,
+    page_id: ObjectId,
+    content: Vec<u8>,
+) -> Result<(), lopdf::Error> {
+    let mut contents: Vec<LopdfObject> = match doc.get_dictionary(page_id)?.get(b"Contents") {
+        Ok(LopdfObject::Array(contents)) => contents.clone(),
+        Ok(LopdfObject::Reference(contents_id)) => match doc.get_object(*contents_id)? {
+            LopdfObject::Array(contents) => contents.clone(),
+            _ => vec![LopdfObject::Reference(*contents_id)],
+        },
+        _ => Vec::new(),
+    };
+
+    let save_state_id = doc.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+    // Start on a new line in case the existing content does not end with whitespace
+    let mut stamp = b"\nQ\n".to_vec();
+    stamp.extend(content);
+    let stamp_id = doc.add_object(Stream::new(Dictionary::new(), stamp));
+
+    contents.insert(0, LopdfObject::Reference(save_state_id));
+    contents.push(LopdfObject::Reference(stamp_id));
+    doc.get_dictionary_mut(page_id)?.set("Contents", contents);
+    Ok(())
+}
@@ -0,0 +1,420 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{dictionary, Document, Object as LopdfObject, ObjectId, StringFormat};
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::form_fields::{form_field_names, page_annotations, set_interactive_form};
+use crate::locked_file::write_pdf_file;
+use pdf_composer_definitions::locked_file::LockedFilePolicy;
+
+/// The room kept in the PDF for the timestamp token, in bytes (tokens with the certificates of
+/// the time stamping authority are usually 4 to 8 KB).
+const TIMESTAMP_TOKEN_SIZE: usize = 16384;
+
+/// Stands in for the byte offsets of the `ByteRange` until the PDF is saved. It is as long as the
+/// longest offset, so the offsets always fit in its place.
+const BYTE_RANGE_PLACEHOLDER: i64 = 9_999_999_999;
+
+/// How long the time stamping authority has to answer.
+const TSA_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The largest reply read from the time stamping authority, in bytes.
+const MAX_TSA_REPLY_SIZE: u64 = 1024 * 1024;
+
+/// The DER object identifier of SHA-256 (2.16.840.1.101.3.4.2.1).
+const SHA256_OID: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+/// `SigFlags`: the document has signatures, and is only to be saved incrementally (appended to),
+/// so the timestamp stays valid.
+const SIGNATURES_EXIST_APPEND_ONLY: i64 = 3;
+
+/// This function adds a trusted timestamp to a PDF document: a hash of the document is sent to a
+/// time stamping authority (RFC 3161), and the timestamp token it returns is embedded as a
+/// document timestamp (`/DocTimeStamp`, `ETSI.RFC3161`).
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document` (complete, as it is to be saved).
+/// * `tsa_url` - The URL of the time stamping authority.
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` with the timestamped PDF, to be written as it is (saving `doc` again would
+///   invalidate the timestamp).
+/// * `Err(e)` if the document has no pages, the time stamping authority could not be reached or
+///   refused the request, or its token is larger than the room kept for it.
+///
+/// # Remarks
+///
+/// The timestamp covers the whole file except its own token, so it has to be the last change made
+/// to the document. Signatures added later are appended to the file (`SigFlags` asks PDF writers
+/// to), which keeps the timestamp valid.
+pub fn timestamp_document(
+    doc: &mut Document,
+    tsa_url: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let (mut pdf, contents) = save_with_timestamp_placeholder(doc)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&pdf[..contents.start]);
+    hasher.update(&pdf[contents.end..]);
+    let token = request_timestamp_token(tsa_url, &hasher.finalize())?;
+    if token.len() > TIMESTAMP_TOKEN_SIZE {
+        return Err(format!(
+            "the timestamp token ({} bytes) is larger than the {} bytes kept for it",
+            token.len(),
+            TIMESTAMP_TOKEN_SIZE
+        )
+        .into());
+    }
+
+    let token_hex: String = token.iter().map(|byte| format!("{:02X}", byte)).collect();
+    pdf[contents.start + 1..contents.start + 1 + token_hex.len()]
+        .copy_from_slice(token_hex.as_bytes());
+    Ok(pdf)
+}
+
+/// Adds the document timestamp to a document with room for its token, and saves it with the
+/// `ByteRange` of the timestamp filled in: everything but the `Contents` placeholder (its angle
+/// brackets included), whose range in the saved PDF is returned with it.
+fn save_with_timestamp_placeholder(
+    doc: &mut Document,
+) -> Result<(Vec<u8>, Range<usize>), Box<dyn std::error::Error>> {
+    let signature_id = doc.add_object(dictionary! {
+        "Type" => "DocTimeStamp",
+        "Filter" => "Adobe.PPKLite",
+        "SubFilter" => "ETSI.RFC3161",
+        "ByteRange" => vec![
+            0.into(),
+            BYTE_RANGE_PLACEHOLDER.into(),
+            BYTE_RANGE_PLACEHOLDER.into(),
+            BYTE_RANGE_PLACEHOLDER.into(),
+        ],
+        "Contents" => LopdfObject::String(vec![0; TIMESTAMP_TOKEN_SIZE], StringFormat::Hexadecimal),
+    });
+    add_timestamp_field(doc, signature_id)?;
+
+    let mut pdf: Vec<u8> = Vec::new();
+    doc.save_to(&mut pdf)?;
+
+    // The token goes between the angle brackets of the `Contents` placeholder; everything else is hashed
+    let placeholder = format!("<{}>", "0".repeat(TIMESTAMP_TOKEN_SIZE * 2));
+    let contents_start = find_bytes(&pdf, placeholder.as_bytes())
+        .ok_or("the room for the timestamp token was not found in the PDF")?;
+    let contents_end = contents_start + placeholder.len();
+
+    let byte_range_placeholder = format!(
+        "{} {} {}",
+        BYTE_RANGE_PLACEHOLDER, BYTE_RANGE_PLACEHOLDER, BYTE_RANGE_PLACEHOLDER
+    );
+    let byte_range_start = find_bytes(&pdf, byte_range_placeholder.as_bytes())
+        .ok_or("the byte range of the timestamp was not found in the PDF")?;
+    let byte_range = format!(
+        "{:<width$}",
+        format!(
+            "{} {} {}",
+            contents_start,
+            contents_end,
+            pdf.len() - contents_end
+        ),
+        width = byte_range_placeholder.len()
+    );
+    pdf[byte_range_start..byte_range_start + byte_range.len()]
+        .copy_from_slice(byte_range.as_bytes());
+    Ok((pdf, contents_start..contents_end))
+}
+
+/// This function adds a trusted timestamp to a saved PDF file, such as a PDF a post-process command
+/// has changed, and saves it again in its place (see `timestamp_document`).
+///
+/// # Arguments
+///
+/// * `pdf_file` - The path of the PDF file.
+/// * `tsa_url` - The URL of the time stamping authority.
+/// * `locked_file_policy` - What to do when the PDF is locked by the application it is open in.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path the timestamped PDF was saved to.
+/// * `Err(e)` if the PDF could not be read, timestamped or saved.
+///
+/// # Remarks
+///
+/// The PDF is saved again as a whole, so the layout of the file itself (such as the linearization
+/// of `qpdf --linearize`) is not kept; its content is.
+pub fn timestamp_pdf_file(
+    pdf_file: &Path,
+    tsa_url: &str,
+    locked_file_policy: &LockedFilePolicy,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let mut doc = Document::load(pdf_file)?;
+    let pdf = timestamp_document(&mut doc, tsa_url)?;
+    Ok(write_pdf_file(pdf_file, locked_file_policy, |writer| {
+        writer.write_all(&pdf)
+    })?)
+}
+
+/// Adds the (invisible) signature field of the document timestamp to the first page and the
+/// interactive form.
+fn add_timestamp_field(
+    doc: &mut Document,
+    signature_id: ObjectId,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let page_id = *doc.get_pages().get(&1).ok_or("the document has no pages")?;
+    let field_names = form_field_names(doc);
+    let name = (1..)
+        .map(|number| match number {
+            1 => "Timestamp".to_string(),
+            _ => format!("Timestamp{}", number),
+        })
+        .find(|name| !field_names.contains(name))
+        .unwrap_or_default();
+
+    let widget_id = doc.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "FT" => "Sig",
+        "T" => LopdfObject::string_literal(name),
+        "V" => signature_id,
+        "Rect" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+        "P" => page_id,
+        // Printed and locked
+        "F" => 132,
+    });
+    let mut annotations = page_annotations(doc, page_id).unwrap_or_default();
+    annotations.push(LopdfObject::Reference(widget_id));
+    doc.get_dictionary_mut(page_id)?
+        .set("Annots", LopdfObject::Array(annotations));
+
+    set_interactive_form(doc, vec![LopdfObject::Reference(widget_id)], true, None)?;
+    let interactive_form_id = doc.catalog()?.get(b"AcroForm")?.as_reference()?;
+    doc.get_dictionary_mut(interactive_form_id)?
+        .set("SigFlags", SIGNATURES_EXIST_APPEND_ONLY);
+    Ok(())
+}
+
+/// Sends a timestamp request for a SHA-256 hash to a time stamping authority, returning the
+/// timestamp token (a DER encoded CMS `SignedData`).
+fn request_timestamp_token(
+    tsa_url: &str,
+    hash: &[u8],
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let agent = ureq::AgentBuilder::new().timeout(TSA_TIMEOUT).build();
+    let response = agent
+        .post(tsa_url)
+        .set("Content-Type", "application/timestamp-query")
+        .send_bytes(&timestamp_request(hash, &nonce()))
+        .map_err(|error| format!("the time stamping authority {}: {}", tsa_url, error))?;
+    let mut reply: Vec<u8> = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_TSA_REPLY_SIZE)
+        .read_to_end(&mut reply)?;
+    timestamp_token(&reply)
+        .map_err(|message| format!("the time stamping authority {}: {}", tsa_url, message).into())
+}
+
+/// Returns a DER encoded `TimeStampReq` for a SHA-256 hash, asking for the certificate of the
+/// time stamping authority in the token (so the timestamp can be verified on its own).
+fn timestamp_request(hash: &[u8], nonce: &[u8]) -> Vec<u8> {
+    let algorithm = der(0x30, &[der(0x06, &SHA256_OID), der(0x05, &[])].concat());
+    let message_imprint = der(0x30, &[algorithm, der(0x04, hash)].concat());
+    der(
+        0x30,
+        &[
+            // version 1
+            der(0x02, &[1]),
+            message_imprint,
+            der(0x02, nonce),
+            // certReq
+            der(0x01, &[0xFF]),
+        ]
+        .concat(),
+    )
+}
+
+/// Returns the timestamp token of a DER encoded `TimeStampResp`, or why there is none.
+fn timestamp_token(reply: &[u8]) -> Result<Vec<u8>, String> {
+    let (tag, response, _) = der_element(reply).ok_or("the reply is not a timestamp response")?;
+    if tag != 0x30 {
+        return Err("the reply is not a timestamp response".to_string());
+    }
+    let (_, status_info, status_info_length) =
+        der_element(response).ok_or("the timestamp response has no status")?;
+    let status = match der_element(status_info) {
+        Some((0x02, status, _)) => status
+            .iter()
+            .fold(0u64, |status, byte| (status << 8) | u64::from(*byte)),
+        _ => return Err("the timestamp response has no status".to_string()),
+    };
+    // 0 is granted, 1 granted with modifications
+    if status > 1 {
+        return Err(format!("the request was refused (status {})", status));
+    }
+    match der_element(&response[status_info_length..]) {
+        Some((0x30, _, token_length)) => {
+            Ok(response[status_info_length..status_info_length + token_length].to_vec())
+        }
+        _ => Err("the timestamp response has no token".to_string()),
+    }
+}
+
+/// Returns the DER encoding of an element: its tag, length and content.
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    if content.len() < 0x80 {
+        element.push(content.len() as u8);
+    } else {
+        let length = content.len().to_be_bytes();
+        let first = length
+            .iter()
+            .position(|byte| *byte != 0)
+            .unwrap_or(length.len() - 1);
+        element.push(0x80 | (length.len() - first) as u8);
+        element.extend_from_slice(&length[first..]);
+    }
+    element.extend_from_slice(content);
+    element
+}
+
+/// Returns the tag, content and encoded length of the first DER element of `bytes`.
+fn der_element(bytes: &[u8]) -> Option<(u8, &[u8], usize)> {
+    let tag = *bytes.first()?;
+    let first_length_byte = *bytes.get(1)?;
+    let (content_length, header_length) = if first_length_byte < 0x80 {
+        (usize::from(first_length_byte), 2)
+    } else {
+        let length_bytes = usize::from(first_length_byte & 0x7F);
+        if length_bytes == 0 || length_bytes > 4 {
+            return None;
+        }
+        let length = bytes
+            .get(2..2 + length_bytes)?
+            .iter()
+            .fold(0usize, |length, byte| (length << 8) | usize::from(*byte));
+        (length, 2 + length_bytes)
+    };
+    let content = bytes.get(header_length..header_length + content_length)?;
+    Some((tag, content, header_length + content_length))
+}
+
+/// Returns a random positive 8 byte nonce, which the time stamping authority puts in its token so
+/// a replayed reply is told apart.
+fn nonce() -> [u8; 8] {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(elapsed) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    let mut nonce = hasher.finish().to_be_bytes();
+    // A positive INTEGER, with no leading zero byte
+    nonce[0] = (nonce[0] & 0x7F) | 0x01;
+    nonce
+}
+
+/// Returns where `needle` first appears in `haystack`.
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a one-page document.
+    fn one_page_document() -> Document {
+        let mut doc = Document::with_version("2.0");
+        let pages_id = doc.new_object_id();
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+        });
+        doc.objects.insert(
+            pages_id,
+            LopdfObject::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Count" => 1,
+                "Kids" => vec![page_id.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    /// Returns the `ByteRange` of the document timestamp of a saved PDF.
+    fn byte_range(pdf: &[u8]) -> Vec<usize> {
+        let doc = Document::load_mem(pdf).unwrap();
+        let signature = doc
+            .objects
+            .values()
+            .filter_map(|object| object.as_dict().ok())
+            .find(|dict| dict.type_is(b"DocTimeStamp"))
+            .unwrap();
+        signature
+            .get(b"ByteRange")
+            .and_then(LopdfObject::as_array)
+            .unwrap()
+            .iter()
+            .map(|offset| offset.as_i64().unwrap() as usize)
+            .collect()
+    }
+
+    #[test]
+    fn byte_range_covers_everything_but_the_contents() {
+        let mut doc = one_page_document();
+        let (pdf, contents) = save_with_timestamp_placeholder(&mut doc).unwrap();
+
+        assert_eq!(
+            byte_range(&pdf),
+            [0, contents.start, contents.end, pdf.len() - contents.end]
+        );
+        assert_eq!(pdf[contents.start], b'<');
+        assert_eq!(pdf[contents.end - 1], b'>');
+        assert_eq!(contents.len(), TIMESTAMP_TOKEN_SIZE * 2 + 2);
+        assert!(pdf[contents.start + 1..contents.end - 1]
+            .iter()
+            .all(|digit| *digit == b'0'));
+    }
+
+    #[test]
+    fn adds_the_timestamp_field() {
+        let mut doc = one_page_document();
+        let (pdf, _contents) = save_with_timestamp_placeholder(&mut doc).unwrap();
+
+        let doc = Document::load_mem(&pdf).unwrap();
+        assert_eq!(form_field_names(&doc), ["Timestamp"]);
+        let interactive_form_id = doc
+            .catalog()
+            .unwrap()
+            .get(b"AcroForm")
+            .and_then(LopdfObject::as_reference)
+            .unwrap();
+        let interactive_form = doc.get_dictionary(interactive_form_id).unwrap();
+        assert_eq!(
+            interactive_form
+                .get(b"SigFlags")
+                .and_then(LopdfObject::as_i64)
+                .ok(),
+            Some(SIGNATURES_EXIST_APPEND_ONLY)
+        );
+    }
+
+    #[test]
+    fn needs_a_page() {
+        let mut doc = Document::with_version("2.0");
+        let catalog_id = doc.add_object(dictionary! { "Type" => "Catalog" });
+        doc.trailer.set("Root", catalog_id);
+
+        assert!(save_with_timestamp_placeholder(&mut doc).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use colored::Colorize;
+use std::path::PathBuf;
+
+use crate::generation_threads::map_documents;
+use crate::inspect::read_pdf_metadata;
+use crate::utils::media_type;
+use pdf_composer_definitions::consts::{CHECK_MARK, CROSS_MARK};
+use pdf_composer_definitions::generation_report::{GenerationReport, UploadedFile};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+use pdf_composer_definitions::upload::UploadMetadata;
+
+/// This function publishes the generated files of a report with the composer's uploader (see
+/// `set_uploader`), adding the URL of each file to the report.
+///
+/// # Arguments
+///
+/// * `composer` - The `PDFComposerStruct` with the uploader.
+/// * `report` - The `GenerationReport` of the generated files, which gets the `uploads` and `failed_uploads`.
+///
+/// # Remarks
+///
+/// In merge mode, only the merged files are published (the chapters are intermediate files). The
+/// files are uploaded on the generation threads, like the documents are generated, and a file that
+/// cannot be uploaded is reported without stopping the others.
+pub fn upload_generated_files(composer: &PDFComposerStruct, report: &mut GenerationReport) {
+    let Some(uploader) = &composer.uploader else {
+        return;
+    };
+
+    let files: Vec<(PathBuf, Option<PathBuf>)> = match composer.merge_mode {
+        Some(_) => report
+            .merged_file
+            .iter()
+            .chain(&report.additional_merged_files)
+            .map(|merged_file| (merged_file.clone(), None))
+            .collect(),
+        None => report
+            .documents
+            .iter()
+            .flat_map(|document| {
+                document
+                    .output_file
+                    .iter()
+                    .chain(&document.additional_output_files)
+                    .map(|output_file| (output_file.clone(), Some(document.source_file.clone())))
+            })
+            .collect(),
+    };
+
+    let uploads = map_documents(composer, files, |(file, source_file)| {
+        let is_pdf = file
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+        let metadata = UploadMetadata {
+            source_file,
+            content_type: media_type(&file).to_string(),
+            info: if is_pdf {
+                read_pdf_metadata(&file)
+                    .map(|metadata| metadata.info)
+                    .unwrap_or_default()
+            } else {
+                Default::default()
+            },
+        };
+        let url = uploader.upload(&file, &metadata);
+        match &url {
+            Ok(_) if composer.console_output.is_quiet() => {}
+            Ok(url) => println!(
+                "{}{} → {}",
+                CHECK_MARK.to_string().green(),
+                file.display().to_string().green(),
+                url.to_string().yellow()
+            ),
+            Err(error) => eprintln!(
+                "{}{} {}",
+                CROSS_MARK.red(),
+                file.display().to_string().red(),
+                format!("not uploaded: {}", error).red()
+            ),
+        }
+        (file, url.ok())
+    });
+
+    for (file, url) in uploads {
+        match url {
+            Some(url) => report.uploads.push(UploadedFile { file, url }),
+            None => report.failed_uploads.push(file),
+        }
+    }
+}
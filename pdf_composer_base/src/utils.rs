@@ -8,12 +8,122 @@ mod yaml_mapping_to_btreemap;
 /// Re-exports the `yaml_mapping_to_btreemap` function for public use.
 pub use yaml_mapping_to_btreemap::yaml_mapping_to_btreemap;
 
-/// The `read_lines` module contains a function to read lines from a file.
-mod read_lines;
-/// Re-exports the `read_lines` function for public use.
-pub use read_lines::read_lines;
-
 /// The `merge_markdown_yaml` module contains a function to merge YAML content into Markdown content.
 mod merge_markdown_yaml;
 /// Re-exports the `merge_markdown_yaml` function for public use.
 pub use merge_markdown_yaml::merge_markdown_yaml;
+
+/// The `unfilled_placeholders` module contains a function to find the placeholders that have no YAML Front Matter value.
+mod unfilled_placeholders;
+/// Re-exports the `unfilled_placeholders` function for public use.
+pub use unfilled_placeholders::unfilled_placeholders;
+
+/// The `remove_unfilled_placeholders` module contains a function to remove the placeholders that have no YAML Front Matter value.
+mod remove_unfilled_placeholders;
+/// Re-exports the `remove_unfilled_placeholders` function for public use.
+pub use remove_unfilled_placeholders::remove_unfilled_placeholders;
+
+/// The `unescape_placeholders` module contains a function to turn escaped placeholders into literal text.
+mod unescape_placeholders;
+/// Re-exports the `unescape_placeholders` function (and the escaped placeholder pattern) for public use.
+pub use unescape_placeholders::{unescape_placeholders, ESCAPED_PLACEHOLDER_PATTERN};
+
+/// The `parse_front_matter` module contains a function to split a source document into its YAML and Markdown sections.
+mod parse_front_matter;
+/// Re-exports the `parse_front_matter` function (and its error) for public use.
+pub use parse_front_matter::{parse_front_matter, FrontMatterError};
+
+/// The `source_extension` module contains functions to find the extensions of source files, and the source files of a directory.
+mod source_extension;
+/// Re-exports the `source_extension` and `find_source_files` functions for public use.
+pub use source_extension::{find_source_files, source_extension};
+
+/// The `output_name` module contains a function to name the documents generated from a source file.
+mod output_name;
+/// Re-exports the `output_name` function for public use.
+pub use output_name::output_name;
+
+/// The `missing_front_matter_keys` module contains a function to find required keys missing from the YAML Front Matter.
+mod missing_front_matter_keys;
+/// Re-exports the `missing_front_matter_keys` function for public use.
+pub use missing_front_matter_keys::missing_front_matter_keys;
+
+/// The `encode_pdf_text_string` module contains a function to encode text for the PDF document information dictionary.
+mod encode_pdf_text_string;
+/// Re-exports the `encode_pdf_text_string` function for public use.
+pub use encode_pdf_text_string::encode_pdf_text_string;
+
+/// The `escape_html` module contains a function to escape text for use in HTML.
+mod escape_html;
+/// Re-exports the `escape_html` function for public use.
+pub use escape_html::escape_html;
+
+/// The `decode_pdf_text_string` module contains a function to decode PDF text strings.
+mod decode_pdf_text_string;
+/// Re-exports the `decode_pdf_text_string` function for public use.
+pub use decode_pdf_text_string::decode_pdf_text_string;
+
+/// The `encode_win_ansi` module contains a function to encode text for the standard 14 fonts.
+mod encode_win_ansi;
+/// Re-exports the `encode_win_ansi` function for public use.
+pub use encode_win_ansi::encode_win_ansi;
+
+/// The `map_outside_code` module contains a function to rewrite HTML while leaving code elements alone.
+mod map_outside_code;
+/// Re-exports the `map_outside_code` function for public use.
+pub use map_outside_code::map_outside_code;
+
+/// The `media_type` module contains a function to find the media (MIME) type of a file.
+mod media_type;
+/// Re-exports the `media_type` function for public use.
+pub use media_type::media_type;
+
+/// The `utc_timestamp` module contains functions to format a time as an ISO 8601 UTC timestamp and to find the date of a day.
+mod utc_timestamp;
+/// Re-exports the `utc_timestamp` and `civil_date` functions for public use.
+pub use utc_timestamp::{civil_date, utc_timestamp};
+
+/// The `latest_modification` module contains a function to find when a source document, or a file near it, last changed.
+mod latest_modification;
+/// Re-exports the `latest_modification` function for public use.
+pub use latest_modification::latest_modification;
+
+/// The `normalize_path` module contains functions to normalize paths (including Windows long and UNC paths) to the separators of the OS.
+mod normalize_path;
+/// Re-exports the `normalize_path` and `output_file_path` functions for public use.
+pub use normalize_path::{normalize_path, output_file_path};
+
+/// The `write_atomically` module contains a function to write a file through a temporary file and a rename.
+mod write_atomically;
+/// Re-exports the `write_atomically` and `write_atomically_with` functions for public use.
+pub use write_atomically::{write_atomically, write_atomically_with};
+
+/// The `doc_info_entry_name` module contains a function to write the reserved document information entry names as the PDF specification has them.
+mod doc_info_entry_name;
+/// Re-exports the `doc_info_entry_name` function for public use.
+pub use doc_info_entry_name::doc_info_entry_name;
+
+/// The `doc_info_entry_near_miss` module contains a function to find the reserved document information entry a custom entry name is close to.
+mod doc_info_entry_near_miss;
+/// Re-exports the `doc_info_entry_near_miss` function for public use.
+pub use doc_info_entry_near_miss::doc_info_entry_near_miss;
+
+/// The `pdf_date_string` module contains a function to convert front matter dates to PDF dates.
+mod pdf_date_string;
+/// Re-exports the `pdf_date_string` function for public use.
+pub use pdf_date_string::pdf_date_string;
+
+/// The `doc_info_value` module contains a function to type front matter values as the PDF specification has them for each document information entry.
+mod doc_info_value;
+/// Re-exports the `doc_info_value` function for public use.
+pub use doc_info_value::doc_info_value;
+
+/// The `expand_env_vars` module contains a function to expand allowed `${NAME}` environment variable references.
+mod expand_env_vars;
+/// Re-exports the `expand_env_vars` function for public use.
+pub use expand_env_vars::expand_env_vars;
+
+/// The `confined_path` module contains a function to join a path written in a document without leading out of its directories.
+mod confined_path;
+/// Re-exports the `confined_path` function for public use.
+pub use confined_path::confined_path;
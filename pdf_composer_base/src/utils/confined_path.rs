@@ -0,0 +1,132 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+use crate::utils::normalize_path;
+
+/// This function joins a path written in a document (a front matter entry, for example) to the
+/// directory it is relative to, checking that it does not lead out of the directories it may
+/// point into.
+///
+/// # Arguments
+///
+/// * `directory` - The directory the path is relative to.
+/// * `path` - The path written in the document.
+/// * `roots` - The directories the path may lead into.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` - The joined path, normalized (see `normalize_path`).
+/// * `Err(String)` - Why the path is refused: it is absolute (`/etc/passwd`, `C:\data`), or it
+///   leads out of every root.
+///
+/// # Remarks
+///
+/// The `.` and `..` components are resolved as written (`data/../../secret` leads out of the
+/// directory). A path to an existing file is also resolved through its symbolic links, so a link
+/// inside a root to a file elsewhere is refused as well.
+pub fn confined_path(directory: &Path, path: &str, roots: &[&Path]) -> Result<PathBuf, String> {
+    // Documents written on Windows separate the components with backslashes
+    let path = path.trim().replace('\\', "/");
+    let relative = Path::new(&path);
+    if relative.has_root()
+        || relative
+            .components()
+            .any(|component| matches!(component, Component::Prefix(_)))
+    {
+        return Err(format!("{} is not a relative path", path));
+    }
+
+    let joined = directory.join(relative);
+    let inside = roots
+        .iter()
+        .any(|root| resolve_dots(&joined).starts_with(resolve_dots(root)));
+    let inside_after_links = match joined.canonicalize() {
+        Ok(file) => roots
+            .iter()
+            .any(|root| root.canonicalize().is_ok_and(|root| file.starts_with(root))),
+        Err(_) => true,
+    };
+    if inside && inside_after_links {
+        Ok(normalize_path(&joined))
+    } else {
+        Err(format!("{} leads out of {}", path, directory.display()))
+    }
+}
+
+/// Returns a path made absolute (against the current directory), with its `.` and `..`
+/// components resolved as written.
+fn resolve_dots(path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        env::current_dir().unwrap_or_default().join(path)
+    };
+    let mut resolved = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            _ => resolved.push(component),
+        }
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::process;
+
+    #[test]
+    fn joins_paths_inside_the_directory() {
+        let directory = Path::new("docs");
+        assert_eq!(
+            confined_path(directory, "data/results.csv", &[directory]),
+            Ok(normalize_path(Path::new("docs/data/results.csv")))
+        );
+        assert!(confined_path(directory, "data/../notes.txt", &[directory]).is_ok());
+        assert!(confined_path(directory, "./notes.txt", &[directory]).is_ok());
+    }
+
+    #[test]
+    fn refuses_absolute_paths() {
+        let directory = Path::new("docs");
+        assert!(confined_path(directory, "/etc/passwd", &[directory]).is_err());
+        assert!(confined_path(directory, "\\etc\\passwd", &[directory]).is_err());
+    }
+
+    #[test]
+    fn refuses_paths_leading_out_of_every_root() {
+        let directory = Path::new("docs");
+        let assets = Path::new("assets");
+        assert!(confined_path(directory, "../secret.txt", &[directory]).is_err());
+        assert!(confined_path(directory, "data/../../secret.txt", &[directory]).is_err());
+        assert!(confined_path(directory, "..\\secret.txt", &[directory]).is_err());
+        assert!(confined_path(directory, "../assets/logo.png", &[directory, assets]).is_ok());
+        assert!(confined_path(directory, "../other/logo.png", &[directory, assets]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuses_links_leading_out_of_every_root() {
+        let base = env::temp_dir().join(format!("pdf-composer-confined-path-{}", process::id()));
+        let directory = base.join("docs");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(base.join("secret.txt"), "secret").unwrap();
+        fs::write(directory.join("notes.txt"), "notes").unwrap();
+        let _ = std::os::unix::fs::symlink(base.join("secret.txt"), directory.join("link.txt"));
+
+        let notes = confined_path(&directory, "notes.txt", &[&directory]);
+        let link = confined_path(&directory, "link.txt", &[&directory]);
+        let _ = fs::remove_dir_all(&base);
+
+        assert!(notes.is_ok());
+        assert!(link.is_err());
+    }
+}
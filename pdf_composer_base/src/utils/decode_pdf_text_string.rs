@@ -0,0 +1,56 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// This function decodes the bytes of a PDF text string into a Rust `String`.
+///
+/// # Arguments
+///
+/// * `bytes` - A byte slice (`&[u8]`) containing the raw bytes of the PDF string object.
+///
+/// # Returns
+///
+/// A `String` containing the decoded text.
+///
+/// # Remarks
+///
+/// Text strings starting with the `FE FF` byte order mark are decoded as UTF-16BE (invalid code
+/// units are replaced with `U+FFFD`). Anything else is treated as PDFDocEncoding, which matches
+/// Latin-1 for the characters PDF Composer writes (see `encode_pdf_text_string`).
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(decode_pdf_text_string(&[0xFE, 0xFF, 0x03, 0xA9]), "Ω");
+/// assert_eq!(decode_pdf_text_string(b"Caf\xE9"), "Café");
+/// ```
+pub fn decode_pdf_text_string(bytes: &[u8]) -> String {
+    match bytes {
+        [0xFE, 0xFF, rest @ ..] => {
+            let code_units: Vec<u16> = rest
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&code_units)
+        }
+        _ => bytes.iter().map(|&byte| byte as char).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_pdf_doc_encoding() {
+        assert_eq!(decode_pdf_text_string(b"Caf\xE9 Z\xFCrich"), "Café Zürich");
+    }
+
+    #[test]
+    fn decodes_utf_16be_with_a_byte_order_mark() {
+        assert_eq!(
+            decode_pdf_text_string(&[0xFE, 0xFF, 0x65, 0xE5, 0x67, 0x2C]),
+            "日本"
+        );
+        assert_eq!(decode_pdf_text_string(&[0xFE, 0xFF]), "");
+    }
+}
@@ -0,0 +1,54 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer_definitions::consts::RESERVED_DOC_INFO_ENTRIES;
+
+/// This function returns the name a document information entry is written to the PDF with.
+///
+/// # Arguments
+///
+/// * `doc_info_entry` - A string slice (`&str`) with the name of the entry, as entered by the user.
+///
+/// # Returns
+///
+/// A `String` with the reserved entry names (`Title`, `Author`, `Subject`, `Keywords`, `Creator`,
+/// `Producer`, `CreationDate`, `ModDate` and `Trapped`) written as the PDF specification has
+/// them, whatever their case, and any other name as it was entered.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(doc_info_entry_name("author"), "Author");
+/// assert_eq!(doc_info_entry_name("creationdate"), "CreationDate");
+/// assert_eq!(doc_info_entry_name("Department"), "Department");
+/// ```
+pub fn doc_info_entry_name(doc_info_entry: &str) -> String {
+    // Reserved metadata entries in the document information dictionary
+    // These are case sensitive and must be written as in the PDF specification.
+    // All others will be as entered by the user.
+    RESERVED_DOC_INFO_ENTRIES
+        .iter()
+        .find(|reserved_entry| reserved_entry.eq_ignore_ascii_case(doc_info_entry))
+        .map_or_else(
+            || doc_info_entry.to_string(),
+            |reserved_entry| reserved_entry.to_string(),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_reserved_entries_as_in_the_pdf_specification() {
+        assert_eq!(doc_info_entry_name("title"), "Title");
+        assert_eq!(doc_info_entry_name("CREATIONDATE"), "CreationDate");
+        assert_eq!(doc_info_entry_name("modDate"), "ModDate");
+    }
+
+    #[test]
+    fn keeps_other_entries_as_entered() {
+        assert_eq!(doc_info_entry_name("department"), "department");
+        assert_eq!(doc_info_entry_name("Review Date"), "Review Date");
+    }
+}
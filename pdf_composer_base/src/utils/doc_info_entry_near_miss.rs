@@ -0,0 +1,100 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer_definitions::consts::RESERVED_DOC_INFO_ENTRIES;
+
+/// This function finds the reserved document information entry a custom entry name was probably
+/// meant to be, such as `Creation_Date` for `CreationDate` or `Autor` for `Author`.
+///
+/// # Arguments
+///
+/// * `doc_info_entry` - A string slice (`&str`) with the name of the entry, as entered by the user.
+///
+/// # Returns
+///
+/// * `Some(&str)` with the reserved entry name, if `doc_info_entry` differs from it only in
+///   separators (spaces, `_` and `-`) or by a typing mistake or two.
+/// * `None` if `doc_info_entry` is a reserved entry name (in any case) or not close to one.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(doc_info_entry_near_miss("mod-date"), Some("ModDate"));
+/// assert_eq!(doc_info_entry_near_miss("Titel"), Some("Title"));
+/// assert_eq!(doc_info_entry_near_miss("title"), None);
+/// assert_eq!(doc_info_entry_near_miss("Department"), None);
+/// ```
+pub fn doc_info_entry_near_miss(doc_info_entry: &str) -> Option<&'static str> {
+    if RESERVED_DOC_INFO_ENTRIES
+        .iter()
+        .any(|reserved_entry| reserved_entry.eq_ignore_ascii_case(doc_info_entry))
+    {
+        return None;
+    }
+
+    let name: Vec<char> = doc_info_entry
+        .chars()
+        .filter(|character| !matches!(character, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    RESERVED_DOC_INFO_ENTRIES
+        .iter()
+        .map(|reserved_entry| {
+            let reserved: Vec<char> = reserved_entry.to_lowercase().chars().collect();
+            (reserved_entry, edit_distance(&name, &reserved))
+        })
+        // One mistake is allowed in short names, two in names of eight letters or more
+        .filter(|(reserved_entry, distance)| {
+            *distance <= if reserved_entry.len() < 8 { 1 } else { 2 }
+        })
+        .min_by_key(|(_reserved_entry, distance)| *distance)
+        .map(|(reserved_entry, _distance)| *reserved_entry)
+}
+
+/// Returns the number of insertions, deletions, substitutions and transpositions of adjacent
+/// characters needed to turn one name into the other.
+fn edit_distance(from: &[char], to: &[char]) -> usize {
+    let mut distances = vec![vec![0; to.len() + 1]; from.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=from.len() {
+        for j in 1..=to.len() {
+            let substitution = usize::from(from[i - 1] != to[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + substitution);
+            if i > 1 && j > 1 && from[i - 1] == to[j - 2] && from[i - 2] == to[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[from.len()][to.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_reserved_entry_of_a_misspelt_name() {
+        assert_eq!(doc_info_entry_near_miss("Autor"), Some("Author"));
+        assert_eq!(doc_info_entry_near_miss("Titel"), Some("Title"));
+        assert_eq!(
+            doc_info_entry_near_miss("creation_date"),
+            Some("CreationDate")
+        );
+        assert_eq!(doc_info_entry_near_miss("Keywrds"), Some("Keywords"));
+    }
+
+    #[test]
+    fn leaves_reserved_and_unrelated_names_alone() {
+        assert_eq!(doc_info_entry_near_miss("author"), None);
+        assert_eq!(doc_info_entry_near_miss("Department"), None);
+        assert_eq!(doc_info_entry_near_miss("Rating"), None);
+    }
+}
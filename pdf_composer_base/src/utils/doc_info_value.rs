@@ -0,0 +1,108 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::Object as LopdfObject;
+
+use super::{encode_pdf_text_string, pdf_date_string};
+
+/// This function converts a YAML Front Matter value to the PDF object a document information
+/// entry is written with, typed as the PDF specification has it for the entry.
+///
+/// # Arguments
+///
+/// * `doc_info_entry` - A string slice (`&str`) with the name of the entry (as written to the PDF, see `doc_info_entry_name`).
+/// * `value` - A string slice (`&str`) with the value from the YAML Front Matter.
+///
+/// # Returns
+///
+/// * `Ok(LopdfObject)` with:
+///   * a name (`/True`, `/False` or `/Unknown`) for `Trapped`, from `true`, `false` or `unknown`
+///     (or `yes` and `no`) in any case.
+///   * a date string (`D:20240501093000+01'00'`) for `CreationDate` and `ModDate`, from an ISO
+///     8601 date and time or a PDF date (see `pdf_date_string`).
+///   * a text string for any other entry (see `encode_pdf_text_string`).
+/// * `Err(message)` if the value cannot be written as the type of the entry.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(
+///     doc_info_value("Trapped", "true"),
+///     Ok(LopdfObject::Name(b"True".to_vec()))
+/// );
+/// assert_eq!(
+///     doc_info_value("CreationDate", "2024-05-01"),
+///     Ok(LopdfObject::string_literal("D:20240501"))
+/// );
+/// assert!(doc_info_value("ModDate", "yesterday").is_err());
+/// ```
+pub fn doc_info_value(doc_info_entry: &str, value: &str) -> Result<LopdfObject, String> {
+    match doc_info_entry {
+        "Trapped" => {
+            let trapped = match value.trim().to_lowercase().as_str() {
+                "true" | "yes" => "True",
+                "false" | "no" => "False",
+                "unknown" => "Unknown",
+                _ => {
+                    return Err(format!(
+                        "\"{}\" is not True, False or Unknown",
+                        value.trim()
+                    ))
+                }
+            };
+            Ok(LopdfObject::Name(trapped.as_bytes().to_vec()))
+        }
+        "CreationDate" | "ModDate" => pdf_date_string(value)
+            .map(LopdfObject::string_literal)
+            .ok_or_else(|| {
+                format!(
+                    "\"{}\" is not a date (such as 2024-05-01 or 2024-05-01T09:30:00Z)",
+                    value.trim()
+                )
+            }),
+        _ => Ok(encode_pdf_text_string(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_trapped_as_a_name() {
+        assert_eq!(
+            doc_info_value("Trapped", "true"),
+            Ok(LopdfObject::Name(b"True".to_vec()))
+        );
+        assert_eq!(
+            doc_info_value("Trapped", " No "),
+            Ok(LopdfObject::Name(b"False".to_vec()))
+        );
+        assert_eq!(
+            doc_info_value("Trapped", "UNKNOWN"),
+            Ok(LopdfObject::Name(b"Unknown".to_vec()))
+        );
+        assert!(doc_info_value("Trapped", "maybe").is_err());
+    }
+
+    #[test]
+    fn writes_dates_as_pdf_dates() {
+        assert_eq!(
+            doc_info_value("CreationDate", "2024-05-01"),
+            Ok(LopdfObject::string_literal("D:20240501"))
+        );
+        assert!(doc_info_value("ModDate", "yesterday").is_err());
+    }
+
+    #[test]
+    fn writes_other_entries_as_text_strings() {
+        assert_eq!(
+            doc_info_value("Subject", "2024-05-01"),
+            Ok(encode_pdf_text_string("2024-05-01"))
+        );
+        assert_eq!(
+            doc_info_value("Title", "true"),
+            Ok(encode_pdf_text_string("true"))
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::{Object as LopdfObject, StringFormat};
+
+/// This function encodes a string as a PDF text string, ready to be written into the document information dictionary.
+///
+/// # Arguments
+///
+/// * `text` - A string slice (`&str`) containing the text to encode.
+///
+/// # Returns
+///
+/// An `LopdfObject::String` containing either:
+///
+/// * The PDFDocEncoding bytes of `text` (as a literal string), if every character can be represented in PDFDocEncoding.
+/// * The UTF-16BE bytes of `text`, prefixed with the `FE FF` byte order mark (as a hexadecimal string), otherwise.
+///
+/// # Remarks
+///
+/// The PDF specification (ISO 32000, 7.9.2.2 Text String Type) only allows text strings to be
+/// PDFDocEncoded or UTF-16BE with a byte order mark. Writing raw UTF-8 bytes makes readers
+/// interpret them as PDFDocEncoding, which turns anything beyond ASCII into mojibake.
+///
+/// PDFDocEncoding matches Latin-1 for printable ASCII and for `0xA1..=0xFF` (except `0xAD`),
+/// so only those characters (plus tab, line feed and carriage return) are written as single bytes.
+///
+/// # Examples
+///
+/// ```ignore
+/// use lopdf::{Object as LopdfObject, StringFormat};
+///
+/// // Latin-1 text stays single byte
+/// assert_eq!(
+///     encode_pdf_text_string("Café"),
+///     LopdfObject::String(vec![b'C', b'a', b'f', 0xE9], StringFormat::Literal)
+/// );
+///
+/// // Anything else is UTF-16BE with a byte order mark
+/// assert_eq!(
+///     encode_pdf_text_string("Ωμέγα")
+///         .as_str()
+///         .unwrap()[..4],
+///     [0xFE, 0xFF, 0x03, 0xA9]
+/// );
+/// ```
+pub fn encode_pdf_text_string(text: &str) -> LopdfObject {
+    let is_pdf_doc_encodable = text.chars().all(|character| {
+        matches!(character, '\t' | '\n' | '\r' | ' '..='~')
+            || (('\u{A1}'..='\u{FF}').contains(&character) && character != '\u{AD}')
+    });
+
+    if is_pdf_doc_encodable {
+        // Every character is a single byte with the same value as its code point
+        let bytes: Vec<u8> = text.chars().map(|character| character as u8).collect();
+        LopdfObject::String(bytes, StringFormat::Literal)
+    } else {
+        let mut bytes: Vec<u8> = vec![0xFE, 0xFF];
+        for code_unit in text.encode_utf16() {
+            bytes.extend_from_slice(&code_unit.to_be_bytes());
+        }
+        LopdfObject::String(bytes, StringFormat::Hexadecimal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_latin_text_as_pdf_doc_encoding() {
+        assert_eq!(
+            encode_pdf_text_string("Café Zürich"),
+            LopdfObject::String(b"Caf\xE9 Z\xFCrich".to_vec(), StringFormat::Literal)
+        );
+    }
+
+    #[test]
+    fn encodes_other_text_as_utf_16be() {
+        assert_eq!(
+            encode_pdf_text_string("日本"),
+            LopdfObject::String(
+                vec![0xFE, 0xFF, 0x65, 0xE5, 0x67, 0x2C],
+                StringFormat::Hexadecimal
+            )
+        );
+        // The soft hyphen has no PDFDocEncoding byte of its own
+        assert!(matches!(
+            encode_pdf_text_string("co\u{AD}operate"),
+            LopdfObject::String(_, StringFormat::Hexadecimal)
+        ));
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// This function encodes text as WinAnsiEncoding bytes, for drawing with the standard 14 fonts.
+///
+/// # Arguments
+///
+/// * `text` - A string slice (`&str`) containing the text to encode.
+///
+/// # Returns
+///
+/// A `Vec<u8>` containing the encoded bytes.
+///
+/// # Remarks
+///
+/// Latin-1 characters and the common Windows-1252 punctuation (curly quotes, dashes, bullet,
+/// ellipsis, euro sign) are encoded. Anything else cannot be drawn with the standard fonts and
+/// is replaced with `?`.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(encode_win_ansi("Café – 5€"), b"Caf\xE9 \x96 5\x80".to_vec());
+/// assert_eq!(encode_win_ansi("日本"), b"??".to_vec());
+/// ```
+pub fn encode_win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|character| match character {
+            '€' => 0x80,
+            '‚' => 0x82,
+            '„' => 0x84,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '™' => 0x99,
+            '\u{20}'..='\u{7E}' | '\u{A0}'..='\u{FF}' => character as u8,
+            _ => b'?',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_latin_1_and_windows_1252_punctuation() {
+        assert_eq!(encode_win_ansi("Café – 5€"), b"Caf\xE9 \x96 5\x80".to_vec());
+        assert_eq!(
+            encode_win_ansi("“Page” • 1…"),
+            b"\x93Page\x94 \x95 1\x85".to_vec()
+        );
+    }
+
+    #[test]
+    fn replaces_other_characters() {
+        assert_eq!(encode_win_ansi("日本"), b"??".to_vec());
+        assert_eq!(encode_win_ansi("a\tb"), b"a?b".to_vec());
+    }
+}
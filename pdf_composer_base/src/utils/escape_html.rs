@@ -0,0 +1,52 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// This function escapes the characters that have special meaning in HTML text and attribute values.
+///
+/// # Arguments
+///
+/// * `text` - A string slice (`&str`) containing the text to escape.
+///
+/// # Returns
+///
+/// A `String` with `&`, `<`, `>`, `"` and `'` replaced by their HTML entities.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(escape_html("Fish & \"Chips\""), "Fish &amp; &quot;Chips&quot;");
+/// ```
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(
+            escape_html("Fish & \"Chips\""),
+            "Fish &amp; &quot;Chips&quot;"
+        );
+        assert_eq!(escape_html("<a href='x'>"), "&lt;a href=&#39;x&#39;&gt;");
+    }
+
+    #[test]
+    fn keeps_other_text() {
+        assert_eq!(escape_html("مرحبا 日本"), "مرحبا 日本");
+        assert_eq!(escape_html(""), "");
+    }
+}
@@ -0,0 +1,81 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+use std::env;
+
+/// This function expands the `${NAME}` environment variable references of a text.
+///
+/// # Arguments
+///
+/// * `text` - A string slice (`&str`) containing the text to expand.
+/// * `allow_list` - The names of the environment variables that may be expanded.
+///
+/// # Returns
+///
+/// A `String` with each `${NAME}` replaced by the value of the environment variable `NAME`, if
+/// `NAME` is in `allow_list` and set. Other references are left as they are, so variables that
+/// are not allowed (secrets, say) never reach the document.
+///
+/// # Examples
+///
+/// ```ignore
+/// env::set_var("CI_COMMIT_SHA", "3f2c1a9");
+/// let allow_list = vec!["CI_COMMIT_SHA".to_string()];
+/// assert_eq!(expand_env_vars("Build ${CI_COMMIT_SHA}", &allow_list), "Build 3f2c1a9");
+/// assert_eq!(expand_env_vars("${HOME}", &allow_list), "${HOME}");
+/// ```
+pub fn expand_env_vars(text: &str, allow_list: &[String]) -> String {
+    if allow_list.is_empty() || !text.contains("${") {
+        return text.to_string();
+    }
+
+    let regex = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+    regex
+        .replace_all(text, |captures: &Captures<'_>| {
+            let name = &captures[1];
+            if allow_list.iter().any(|allowed| allowed == name) {
+                if let Ok(value) = env::var(name) {
+                    return value;
+                }
+            }
+            captures[0].to_string()
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_allowed_variables() {
+        env::set_var("PDF_COMPOSER_TEST_COMMIT", "3f2c1a9");
+        let allow_list = vec!["PDF_COMPOSER_TEST_COMMIT".to_string()];
+
+        assert_eq!(
+            expand_env_vars("Build ${PDF_COMPOSER_TEST_COMMIT}.", &allow_list),
+            "Build 3f2c1a9."
+        );
+    }
+
+    #[test]
+    fn leaves_other_references_as_they_are() {
+        env::set_var("PDF_COMPOSER_TEST_SECRET", "hunter2");
+        env::remove_var("PDF_COMPOSER_TEST_UNSET");
+        let allow_list = vec!["PDF_COMPOSER_TEST_UNSET".to_string()];
+
+        assert_eq!(
+            expand_env_vars("${PDF_COMPOSER_TEST_SECRET}", &allow_list),
+            "${PDF_COMPOSER_TEST_SECRET}"
+        );
+        assert_eq!(
+            expand_env_vars("${PDF_COMPOSER_TEST_UNSET} $HOME", &allow_list),
+            "${PDF_COMPOSER_TEST_UNSET} $HOME"
+        );
+        assert_eq!(
+            expand_env_vars("${PDF_COMPOSER_TEST_SECRET}", &[]),
+            "${PDF_COMPOSER_TEST_SECRET}"
+        );
+    }
+}
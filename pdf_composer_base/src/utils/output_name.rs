@@ -0,0 +1,138 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::path::Path;
+
+use pdf_composer_definitions::output_name::OutputNamer;
+
+use crate::utils::source_extension;
+
+/// This function names the documents generated from a source file.
+///
+/// # Arguments
+///
+/// * `source_file` - The path of the source document.
+/// * `source_extensions` - The source extensions (see `set_source_extensions`).
+/// * `output_namer` - The function set with `set_output_namer`, if any.
+///
+/// # Returns
+///
+/// The file name of the generated documents, without the extension of the output format. That is
+/// the name returned by `output_namer`, or, when there is no namer or it returns an empty name,
+/// the source file name without its source extension (see `source_extension`), or without its
+/// last extension (`Path::file_stem`) when it has none of the source extensions.
+///
+/// # Examples
+///
+/// ```ignore
+/// use std::path::Path;
+///
+/// let extensions = vec![".md".to_string(), ".en.md".to_string()];
+/// assert_eq!(output_name(Path::new("docs/report.md"), &extensions, None), "report");
+/// assert_eq!(output_name(Path::new("guide.en.md"), &extensions, None), "guide");
+/// assert_eq!(output_name(Path::new("notes.txt"), &extensions, None), "notes");
+/// ```
+pub fn output_name(
+    source_file: &Path,
+    source_extensions: &[String],
+    output_namer: Option<&OutputNamer>,
+) -> String {
+    let name = output_namer
+        .map(|output_namer| output_namer(source_file))
+        .unwrap_or_default();
+    if !name.is_empty() {
+        return name;
+    }
+    let file_name = source_file
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    match source_extension(&file_name, source_extensions) {
+        Some(extension) => file_name[..file_name.len() - extension.len()].to_string(),
+        None => source_file
+            .file_stem()
+            .map(|file_stem| file_stem.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Returns the source extensions from their `&str`s.
+    fn extensions(extensions: &[&str]) -> Vec<String> {
+        extensions
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn removes_the_source_extension() {
+        let extensions = extensions(&[".md", ".markdown"]);
+        assert_eq!(
+            output_name(Path::new("docs/report.markdown"), &extensions, None),
+            "report"
+        );
+        assert_eq!(
+            output_name(Path::new("README.MD"), &extensions, None),
+            "README"
+        );
+        assert_eq!(
+            output_name(Path::new("guide.en.md"), &extensions, None),
+            "guide.en"
+        );
+    }
+
+    #[test]
+    fn removes_a_source_extension_with_several_dots() {
+        let extensions = extensions(&[".md", ".en.md"]);
+        assert_eq!(
+            output_name(Path::new("docs/guide.en.md"), &extensions, None),
+            "guide"
+        );
+        assert_eq!(
+            output_name(Path::new("docs/guide.fr.md"), &extensions, None),
+            "guide.fr"
+        );
+    }
+
+    #[test]
+    fn removes_the_last_extension_of_other_files() {
+        let extensions = extensions(&[".md"]);
+        assert_eq!(
+            output_name(Path::new("notes.txt"), &extensions, None),
+            "notes"
+        );
+        assert_eq!(
+            output_name(Path::new("LICENSE"), &extensions, None),
+            "LICENSE"
+        );
+    }
+
+    #[test]
+    fn uses_the_name_of_the_output_namer() {
+        let extensions = extensions(&[".md"]);
+        let output_namer: OutputNamer = Arc::new(|source_file: &Path| {
+            if source_file.starts_with("drafts") {
+                String::new()
+            } else {
+                "final".to_string()
+            }
+        });
+        assert_eq!(
+            output_name(Path::new("report.md"), &extensions, Some(&output_namer)),
+            "final"
+        );
+        assert_eq!(
+            output_name(
+                Path::new("drafts/report.md"),
+                &extensions,
+                Some(&output_namer)
+            ),
+            "report"
+        );
+    }
+}
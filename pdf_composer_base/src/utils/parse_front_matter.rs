@@ -0,0 +1,165 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fmt;
+
+/// Enum representing why a source document has no YAML Front Matter to parse.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontMatterError {
+    /// The document does not start with a `---` line.
+    Missing,
+    /// The `---` line opening the front matter (on the line number given, starting at `1`) has no
+    /// closing `---` line.
+    Unterminated(usize),
+}
+
+impl fmt::Display for FrontMatterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrontMatterError::Missing => write!(f, "no YAML Front Matter found"),
+            FrontMatterError::Unterminated(line_number) => write!(
+                f,
+                "the YAML Front Matter opened on line {} is not closed with a `---` line",
+                line_number
+            ),
+        }
+    }
+}
+
+/// This function splits a YAML Front Matter document into its YAML and Markdown sections.
+///
+/// # Arguments
+///
+/// * `source` - The text of the source document.
+///
+/// # Returns
+///
+/// * `Ok((String, String))` with the YAML Front Matter content (between the `---` lines) and the
+///   Markdown content (after the closing `---` line), each line ending with `\n`.
+/// * `Err(FrontMatterError::Missing)` if the document does not start with a `---` line.
+/// * `Err(FrontMatterError::Unterminated(line_number))` if there is no closing `---` line.
+///
+/// # Remarks
+///
+/// Windows (`\r\n`) line endings, a UTF-8 byte order mark and blank lines before the opening
+/// `---` line are accepted. Only the first two `---` lines are treated as delimiters. Any further
+/// `---` lines (horizontal rules, for example) are kept as part of the Markdown content.
+///
+/// # Examples
+///
+/// ```ignore
+/// let source = "\u{FEFF}---\r\ntitle: \"Hello\"\r\n---\r\n# Heading\r\n";
+///
+/// let (yaml, markdown) = parse_front_matter(source).unwrap();
+///
+/// assert_eq!(yaml, "title: \"Hello\"\n");
+/// assert_eq!(markdown, "# Heading\n");
+/// assert_eq!(
+///     parse_front_matter("---\ntitle: \"Hello\"\n# Heading\n"),
+///     Err(FrontMatterError::Unterminated(1))
+/// );
+/// ```
+pub fn parse_front_matter(source: &str) -> Result<(String, String), FrontMatterError> {
+    let source = source.strip_prefix('\u{FEFF}').unwrap_or(source);
+    // `lines` takes a `\r` off the end of each line along with the `\n`
+    let mut lines = source.lines().enumerate();
+
+    let is_delimiter = |line: &str| line.trim_end() == "---";
+    let opening_line_number = loop {
+        match lines.next() {
+            Some((_, line)) if line.trim().is_empty() => continue,
+            Some((index, line)) if is_delimiter(line) => break index + 1,
+            _ => return Err(FrontMatterError::Missing),
+        }
+    };
+
+    let mut yaml_content = String::new();
+    loop {
+        match lines.next() {
+            Some((_, line)) if is_delimiter(line) => break,
+            Some((_, line)) => {
+                yaml_content.push_str(line);
+                yaml_content.push('\n');
+            }
+            None => return Err(FrontMatterError::Unterminated(opening_line_number)),
+        }
+    }
+
+    let mut markdown_content = String::new();
+    for (_, line) in lines {
+        markdown_content.push_str(line);
+        markdown_content.push('\n');
+    }
+
+    Ok((yaml_content, markdown_content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_the_front_matter_from_the_markdown() {
+        let source = "---\ntitle: \"Hello\"\n---\n# Heading\n\n---\n\nText\n";
+
+        assert_eq!(
+            parse_front_matter(source),
+            Ok((
+                "title: \"Hello\"\n".to_string(),
+                "# Heading\n\n---\n\nText\n".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn accepts_crlf_delimiters() {
+        let source = "---\r\ntitle: \"Hello\"\r\n--- \r\n# Heading\r\n";
+
+        assert_eq!(
+            parse_front_matter(source),
+            Ok(("title: \"Hello\"\n".to_string(), "# Heading\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn accepts_a_leading_byte_order_mark() {
+        let source = "\u{FEFF}---\ntitle: \"Hello\"\n---\n# Heading\n";
+
+        assert_eq!(
+            parse_front_matter(source),
+            Ok(("title: \"Hello\"\n".to_string(), "# Heading\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn accepts_blank_lines_before_the_opening_delimiter() {
+        let source = "\u{FEFF}\n  \r\n---\ntitle: a\n---\nText\n";
+
+        assert_eq!(
+            parse_front_matter(source),
+            Ok(("title: a\n".to_string(), "Text\n".to_string()))
+        );
+    }
+
+    #[test]
+    fn numbers_the_line_of_an_unterminated_front_matter() {
+        assert_eq!(
+            parse_front_matter("---\ntitle: \"Hello\"\n# Heading\n"),
+            Err(FrontMatterError::Unterminated(1))
+        );
+        assert_eq!(
+            parse_front_matter("\r\n\r\n---\r\ntitle: \"Hello\"\r\n"),
+            Err(FrontMatterError::Unterminated(3))
+        );
+    }
+
+    #[test]
+    fn reports_a_missing_front_matter() {
+        assert_eq!(
+            parse_front_matter("# Heading\n---\n"),
+            Err(FrontMatterError::Missing)
+        );
+        assert_eq!(parse_front_matter(""), Err(FrontMatterError::Missing));
+        assert_eq!(parse_front_matter("\n\n"), Err(FrontMatterError::Missing));
+    }
+}
@@ -0,0 +1,130 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::Regex;
+
+/// This function converts a date from the YAML Front Matter to a PDF date, as the `CreationDate`
+/// and `ModDate` document information entries are written.
+///
+/// # Arguments
+///
+/// * `value` - A string slice (`&str`) with the date: an ISO 8601 date (`2024-05-01`), date and
+///   time (`2024-05-01T09:30:00+01:00` or `2024-05-01 09:30`), or a PDF date (`D:20240501093000Z`).
+///
+/// # Returns
+///
+/// * `Some(String)` with the PDF date (`D:20240501093000+01'00'`).
+/// * `None` if the value is not a date (or the month, day or time is out of range).
+///
+/// # Remarks
+///
+/// A date and time without a time zone is written without one, which PDF readers treat as an
+/// unknown time zone. Fractions of a second are dropped.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(pdf_date_string("2024-05-01"), Some("D:20240501".to_string()));
+/// assert_eq!(
+///     pdf_date_string("2024-05-01T09:30:00+01:00"),
+///     Some("D:20240501093000+01'00'".to_string())
+/// );
+/// assert_eq!(pdf_date_string("1st May"), None);
+/// ```
+pub fn pdf_date_string(value: &str) -> Option<String> {
+    let value = value.trim();
+
+    // PDF dates are kept as they are
+    let pdf_date_regex =
+        Regex::new(r"^D:\d{4}(?:\d{2}){0,5}(?:[Zz]|[+-]\d{2}'?(?:\d{2}'?)?)?$").unwrap();
+    if pdf_date_regex.is_match(value) {
+        return Some(value.to_string());
+    }
+
+    let iso_date_regex = Regex::new(
+        r"^(\d{4})-(\d{2})-(\d{2})(?:[Tt ](\d{2}):(\d{2})(?::(\d{2})(?:\.\d+)?)?\s*(?:([Zz])|([+-])(\d{2}):?(\d{2})?)?)?$",
+    )
+    .unwrap();
+    let captures = iso_date_regex.captures(value)?;
+    let number = |index: usize| {
+        captures
+            .get(index)
+            .map_or(0, |part| part.as_str().parse::<u32>().unwrap_or(0))
+    };
+    if !(1..=12).contains(&number(2))
+        || !(1..=31).contains(&number(3))
+        || number(4) > 23
+        || number(5) > 59
+        || number(6) > 59
+        || number(9) > 23
+        || number(10) > 59
+    {
+        return None;
+    }
+
+    let mut pdf_date = format!("D:{}{}{}", &captures[1], &captures[2], &captures[3]);
+    if captures.get(4).is_some() {
+        pdf_date.push_str(&format!("{:02}{:02}{:02}", number(4), number(5), number(6)));
+        if captures.get(7).is_some() {
+            pdf_date.push('Z');
+        } else if let Some(sign) = captures.get(8) {
+            pdf_date.push_str(&format!(
+                "{}{:02}'{:02}'",
+                sign.as_str(),
+                number(9),
+                number(10)
+            ));
+        }
+    }
+    Some(pdf_date)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_iso_dates_and_times() {
+        assert_eq!(
+            pdf_date_string("2024-05-01"),
+            Some("D:20240501".to_string())
+        );
+        assert_eq!(
+            pdf_date_string("2024-05-01 09:30"),
+            Some("D:20240501093000".to_string())
+        );
+        assert_eq!(
+            pdf_date_string("2024-05-01T09:30:15.250Z"),
+            Some("D:20240501093015Z".to_string())
+        );
+        assert_eq!(
+            pdf_date_string("2024-05-01T09:30:00+01:00"),
+            Some("D:20240501093000+01'00'".to_string())
+        );
+        assert_eq!(
+            pdf_date_string("2024-05-01T09:30:00-0530"),
+            Some("D:20240501093000-05'30'".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_pdf_dates() {
+        assert_eq!(
+            pdf_date_string(" D:20240501093000Z "),
+            Some("D:20240501093000Z".to_string())
+        );
+        assert_eq!(
+            pdf_date_string("D:20240501093000+01'00'"),
+            Some("D:20240501093000+01'00'".to_string())
+        );
+    }
+
+    #[test]
+    fn refuses_other_values() {
+        assert_eq!(pdf_date_string("1st May"), None);
+        assert_eq!(pdf_date_string("2024-13-01"), None);
+        assert_eq!(pdf_date_string("2024-05-32"), None);
+        assert_eq!(pdf_date_string("2024-05-01T24:00"), None);
+        assert_eq!(pdf_date_string("D:2024-05-01"), None);
+    }
+}
@@ -0,0 +1,69 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use serde_yml::Value;
+use std::collections::BTreeMap;
+
+use super::{merge_markdown_yaml, unfilled_placeholders};
+
+/// This function removes the `{{key}}` placeholders of a text that have no YAML Front Matter
+/// value, leaving the other placeholders to be filled in.
+///
+/// # Arguments
+///
+/// * `yaml_btreemap` - A reference to a `BTreeMap<String, Value>` containing the YAML data.
+/// * `text` - A string slice (`&str`) containing the text with the placeholders.
+///
+/// # Returns
+///
+/// A `String` with the placeholders found by `unfilled_placeholders` removed. Escaped
+/// placeholders (see `unescape_placeholders`) are left as they are.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut yaml_data = BTreeMap::new();
+/// yaml_data.insert("title".to_string(), Value::String("Invoice".to_string()));
+///
+/// let text = remove_unfilled_placeholders(&yaml_data, "{{title}} for {{customer_name}}");
+/// assert_eq!(text, "{{title}} for ");
+/// ```
+pub fn remove_unfilled_placeholders(yaml_btreemap: &BTreeMap<String, Value>, text: &str) -> String {
+    let unfilled = unfilled_placeholders(yaml_btreemap, text);
+    if unfilled.is_empty() {
+        return text.to_string();
+    }
+
+    let empty_values: BTreeMap<String, Value> = unfilled
+        .into_iter()
+        .map(|key| (key, Value::String(String::new())))
+        .collect();
+    merge_markdown_yaml(empty_values, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_placeholders_without_a_value() {
+        let yaml_btreemap =
+            BTreeMap::from([("name".to_string(), Value::String("World".to_string()))]);
+
+        assert_eq!(
+            remove_unfilled_placeholders(&yaml_btreemap, "Hello {{name}}{{suffix}}!"),
+            "Hello {{name}}!"
+        );
+    }
+
+    #[test]
+    fn leaves_filled_text_alone() {
+        let yaml_btreemap =
+            BTreeMap::from([("name".to_string(), Value::String("World".to_string()))]);
+
+        assert_eq!(
+            remove_unfilled_placeholders(&yaml_btreemap, "Hello {{name}}"),
+            "Hello {{name}}"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// This function returns the source extension (see `set_source_extensions`) a file name ends with.
+///
+/// # Arguments
+///
+/// * `file_name` - The file name (or path) of the source document.
+/// * `extensions` - The source extensions, such as `.md` and `.markdown`.
+///
+/// # Returns
+///
+/// The extension the file name ends with (compared without regard to ASCII case), or `None`.
+/// When several of the extensions match, the longest is returned: `guide.en.md` has the extension
+/// `.en.md` when both `.md` and `.en.md` are set, and `.md` when only `.md` is.
+pub fn source_extension<'a>(file_name: &str, extensions: &'a [String]) -> Option<&'a str> {
+    extensions
+        .iter()
+        .filter(|extension| {
+            file_name.len() > extension.len()
+                && file_name.is_char_boundary(file_name.len() - extension.len())
+                && file_name[file_name.len() - extension.len()..].eq_ignore_ascii_case(extension)
+        })
+        .max_by_key(|extension| extension.len())
+        .map(String::as_str)
+}
+
+/// This function finds the source files in a directory and the directories below it.
+///
+/// # Arguments
+///
+/// * `directory` - The directory to look in.
+/// * `extensions` - The source extensions, such as `.md` and `.markdown`.
+///
+/// # Returns
+///
+/// The paths of the files with one of the extensions, sorted by path. Hidden files and directories
+/// (`.git` etc) are skipped.
+pub fn find_source_files(directory: &Path, extensions: &[String]) -> Vec<PathBuf> {
+    let mut source_files = Vec::new();
+    find_in_directory(directory, extensions, &mut source_files);
+    source_files.sort();
+    source_files
+}
+
+/// Adds the source files of a directory (and those below it) to `source_files`.
+fn find_in_directory(directory: &Path, extensions: &[String], source_files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.map_while(Result::ok) {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        // Skip hidden files and directories (.git etc)
+        if file_name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            find_in_directory(&path, extensions, source_files);
+        } else if source_extension(&file_name, extensions).is_some() {
+            source_files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    /// Returns the source extensions from their `&str`s.
+    fn extensions(extensions: &[&str]) -> Vec<String> {
+        extensions
+            .iter()
+            .map(|extension| extension.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn returns_the_longest_matching_extension() {
+        let extensions = extensions(&[".md", ".en.md", ".markdown"]);
+        assert_eq!(source_extension("guide.en.md", &extensions), Some(".en.md"));
+        assert_eq!(source_extension("guide.fr.md", &extensions), Some(".md"));
+        assert_eq!(
+            source_extension("NOTES.MarkDown", &extensions),
+            Some(".markdown")
+        );
+    }
+
+    #[test]
+    fn returns_none_for_other_file_names() {
+        let extensions = extensions(&[".md"]);
+        assert_eq!(source_extension("notes.txt", &extensions), None);
+        assert_eq!(source_extension(".md", &extensions), None);
+        assert_eq!(source_extension("résumé", &extensions), None);
+    }
+
+    #[test]
+    fn finds_source_files_below_a_directory() {
+        let directory =
+            env::temp_dir().join(format!("pdf-composer-source-extension-{}", process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("guides")).unwrap();
+        fs::create_dir_all(directory.join(".git")).unwrap();
+        for file in [
+            "b.md",
+            "a.MDX",
+            "guides/intro.md",
+            ".git/notes.md",
+            "image.png",
+        ] {
+            fs::write(directory.join(file), "").unwrap();
+        }
+
+        let source_files = find_source_files(&directory, &extensions(&[".md", ".mdx"]));
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(
+            source_files,
+            vec![
+                directory.join("a.MDX"),
+                directory.join("b.md"),
+                directory.join("guides/intro.md")
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+
+/// The regular expression of an escaped placeholder: `\{{` (a backslash before the braces) or a
+/// quoted literal such as `{{"{{"}}`. Placeholders and shortcodes match it first, and leave what
+/// it matches alone.
+pub const ESCAPED_PLACEHOLDER_PATTERN: &str = r#"\\\{\{|\{\{"[^"\n]*"\}\}"#;
+
+/// This function turns the escaped placeholders of a text into the literal text they stand for,
+/// once every placeholder and shortcode has been filled in.
+///
+/// # Arguments
+///
+/// * `text` - A string slice (`&str`) containing the text, with its placeholders filled in.
+///
+/// # Returns
+///
+/// A `String` with each `\{{` replaced by `{{`, and each quoted literal `{{"text"}}` replaced by
+/// `text`.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(unescape_placeholders(r"\{{title}}"), "{{title}}");
+/// assert_eq!(unescape_placeholders(r#"{{"{{"}}title}}"#), "{{title}}");
+/// ```
+pub fn unescape_placeholders(text: &str) -> String {
+    if !text.contains("{{") {
+        return text.to_string();
+    }
+
+    let regex = Regex::new(ESCAPED_PLACEHOLDER_PATTERN).unwrap();
+    regex
+        .replace_all(text, |captures: &Captures<'_>| {
+            let escaped = &captures[0];
+            match escaped
+                .strip_prefix("{{\"")
+                .and_then(|literal| literal.strip_suffix("\"}}"))
+            {
+                Some(literal) => literal.to_string(),
+                None => "{{".to_string(),
+            }
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescapes_escaped_placeholders() {
+        assert_eq!(
+            unescape_placeholders(r"Use \{{name}} here"),
+            "Use {{name}} here"
+        );
+        assert_eq!(
+            unescape_placeholders(r#"Use {{"{{name}}"}} here"#),
+            "Use {{name}} here"
+        );
+    }
+
+    #[test]
+    fn leaves_other_text_alone() {
+        assert_eq!(unescape_placeholders("Hello {{name}}"), "Hello {{name}}");
+        assert_eq!(unescape_placeholders(r"C:\docs {x}"), r"C:\docs {x}");
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::Regex;
+use serde_yml::Value;
+use std::collections::BTreeMap;
+
+use super::ESCAPED_PLACEHOLDER_PATTERN;
+
+/// This function finds the `{{key}}` placeholders of a text that have no YAML Front Matter value
+/// to be filled with.
+///
+/// # Arguments
+///
+/// * `yaml_btreemap` - A reference to a `BTreeMap<String, Value>` containing the YAML data.
+/// * `text` - A string slice (`&str`) containing the text with the placeholders.
+///
+/// # Returns
+///
+/// A `Vec<String>` with the keys of the placeholders that have no string value in `yaml_btreemap`
+/// (only string values are substituted, see `merge_markdown_yaml`), in the order they first appear.
+/// Escaped placeholders (see `unescape_placeholders`) are not included.
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut yaml_data = BTreeMap::new();
+/// yaml_data.insert("title".to_string(), Value::String("Invoice".to_string()));
+///
+/// let unfilled = unfilled_placeholders(&yaml_data, "{{title}} for {{customer_name}} \\{{total}}");
+/// assert_eq!(unfilled, vec!["customer_name".to_string()]);
+/// ```
+pub fn unfilled_placeholders(yaml_btreemap: &BTreeMap<String, Value>, text: &str) -> Vec<String> {
+    let regex = Regex::new(&format!(
+        r"{}|\{{\{{(\w+)\}}\}}",
+        ESCAPED_PLACEHOLDER_PATTERN
+    ))
+    .unwrap();
+
+    let mut unfilled: Vec<String> = Vec::new();
+    for key in regex
+        .captures_iter(text)
+        .filter_map(|captures| captures.get(1))
+        .map(|key| key.as_str())
+    {
+        let is_filled = yaml_btreemap.get(key).is_some_and(Value::is_string);
+        if !is_filled && !unfilled.iter().any(|unfilled_key| unfilled_key == key) {
+            unfilled.push(key.to_string());
+        }
+    }
+    unfilled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn returns_each_placeholder_without_a_string_value_once() {
+        let yaml_btreemap = BTreeMap::from([
+            ("title".to_string(), Value::String("Hello".to_string())),
+            ("pages".to_string(), Value::Number(3.into())),
+        ]);
+        let text = "{{title}} {{author}} {{pages}} {{author}} {{date}}";
+
+        assert_eq!(
+            unfilled_placeholders(&yaml_btreemap, text),
+            vec![
+                "author".to_string(),
+                "pages".to_string(),
+                "date".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_escaped_placeholders() {
+        let text = r#"\{{author}} {{"{{date}}"}}"#;
+
+        assert!(unfilled_placeholders(&BTreeMap::new(), text).is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::time::SystemTime;
+
+/// This function formats a time as a UTC timestamp (`2024-05-01T09:30:00Z`), as EPUB and XMP
+/// metadata use.
+///
+/// # Arguments
+///
+/// * `time` - The time to format (times before 1970 are formatted as 1970-01-01).
+///
+/// # Returns
+///
+/// A `String` with the ISO 8601 timestamp.
+pub fn utc_timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (days, seconds_of_day) = ((seconds / 86_400) as i64, seconds % 86_400);
+    let (year, month, day) = civil_date(days);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+/// This function converts a number of days since 1970-01-01 to a date of the (proleptic) Gregorian calendar.
+///
+/// # Arguments
+///
+/// * `days` - The number of days since 1970-01-01 (negative for earlier dates).
+///
+/// # Returns
+///
+/// A `(year, month, day)` tuple, with the month from 1 to 12 and the day from 1 to 31.
+pub fn civil_date(days: i64) -> (i64, i64, i64) {
+    // Civil date from the days since 1970-01-01 (Howard Hinnant's algorithm)
+    let shifted_days = days + 719_468;
+    let era = shifted_days.div_euclid(146_097);
+    let day_of_era = shifted_days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn formats_times_as_utc_timestamps() {
+        assert_eq!(
+            utc_timestamp(SystemTime::UNIX_EPOCH),
+            "1970-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            utc_timestamp(SystemTime::UNIX_EPOCH + Duration::from_secs(1_714_555_800)),
+            "2024-05-01T09:30:00Z"
+        );
+    }
+
+    #[test]
+    fn converts_days_to_civil_dates() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(-1), (1969, 12, 31));
+        // 2000 and 2024 are leap years, 2100 is not
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(19_782), (2024, 2, 29));
+        assert_eq!(civil_date(47_540), (2100, 2, 28));
+        assert_eq!(civil_date(47_541), (2100, 3, 1));
+    }
+}
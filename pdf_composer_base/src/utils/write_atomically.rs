@@ -0,0 +1,173 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Tells apart the temporary files of concurrent writes from the same process.
+static TEMPORARY_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// This function writes a file atomically: the content goes to a temporary file next to `path`,
+/// which is then renamed into place.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to write. Missing parent directories are created.
+/// * `write` - Writes the content of the file to the (buffered) temporary file.
+///
+/// # Returns
+///
+/// * `Ok(())` if the file was written and renamed into place.
+/// * `Err(e)` if the file could not be written or renamed. The temporary file is removed, and any
+///   file previously at `path` is left as it was.
+///
+/// # Remarks
+///
+/// A crash part way through, or a reader opening the file at the same time, never sees a truncated
+/// file: `path` holds either the previous content or the new content. The temporary file is kept
+/// in the same directory so that the rename never crosses file systems.
+///
+/// When `path` is open in an application that locks it (a PDF viewer on Windows, for example), the
+/// rename fails with `io::ErrorKind::PermissionDenied`.
+pub fn write_atomically<F>(path: &Path, write: F) -> io::Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    write_atomically_with(path, write, |temporary_file| {
+        fs::rename(temporary_file, path).map(|()| path.to_path_buf())
+    })
+    .map(|_| ())
+}
+
+/// This function writes a file atomically like `write_atomically`, moving the temporary file into
+/// place with `rename`.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file to write. Missing parent directories are created.
+/// * `write` - Writes the content of the file to the (buffered) temporary file.
+/// * `rename` - Moves the written temporary file into place, returning where it was moved to
+///   (retrying, or choosing another name, when `path` is locked).
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` with the path returned by `rename`.
+/// * `Err(e)` if the file could not be written or renamed. The temporary file is removed.
+pub fn write_atomically_with<F, R>(path: &Path, write: F, rename: R) -> io::Result<PathBuf>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+    R: FnOnce(&Path) -> io::Result<PathBuf>,
+{
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(directory)?;
+
+    let temporary_file = temporary_file_path(directory, path);
+    let written = File::create(&temporary_file).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        write(&mut writer)?;
+        let file = writer.into_inner().map_err(|error| error.into_error())?;
+        file.sync_all()
+    });
+
+    match written.and_then(|()| rename(&temporary_file)) {
+        Ok(written_path) => Ok(written_path),
+        Err(error) => {
+            let _ = fs::remove_file(&temporary_file);
+            Err(error)
+        }
+    }
+}
+
+/// Returns the path of a hidden temporary file in `directory` for the file at `path`.
+fn temporary_file_path(directory: &Path, path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(format!(
+        ".{}.{}.tmp",
+        process::id(),
+        TEMPORARY_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    directory.join(file_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::io::Write;
+
+    /// Returns an empty directory of its own in the temporary directory.
+    fn test_directory(name: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!(
+            "pdf-composer-write-atomically-{}-{}",
+            name,
+            process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn replaces_the_file_once_written() {
+        let directory = test_directory("replace");
+        let path = directory.join("report.pdf");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&path, "old").unwrap();
+
+        let written = write_atomically(&path, |writer| writer.write_all(b"new"));
+        let contents = fs::read_to_string(&path).unwrap();
+        let file_count = fs::read_dir(&directory).unwrap().count();
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(written.is_ok());
+        assert_eq!(contents, "new");
+        assert_eq!(file_count, 1);
+    }
+
+    #[test]
+    fn keeps_the_file_when_writing_fails() {
+        let directory = test_directory("failure");
+        let path = directory.join("report.pdf");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(&path, "old").unwrap();
+
+        let written = write_atomically(&path, |writer| {
+            writer.write_all(b"partial")?;
+            Err(io::Error::other("rendering failed"))
+        });
+        let contents = fs::read_to_string(&path).unwrap();
+        let file_count = fs::read_dir(&directory).unwrap().count();
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(written.is_err());
+        assert_eq!(contents, "old");
+        assert_eq!(file_count, 1);
+    }
+
+    #[test]
+    fn creates_the_directory_and_returns_the_renamed_path() {
+        let directory = test_directory("rename");
+        let path = directory.join("pdfs/report.pdf");
+        let renamed_path = directory.join("pdfs/report (1).pdf");
+
+        let written = write_atomically_with(
+            &path,
+            |writer| writer.write_all(b"new"),
+            |temporary_file| {
+                fs::rename(temporary_file, &renamed_path).map(|()| renamed_path.clone())
+            },
+        );
+        let contents = fs::read_to_string(&renamed_path).ok();
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(written.ok(), Some(renamed_path));
+        assert_eq!(contents.as_deref(), Some("new"));
+    }
+}
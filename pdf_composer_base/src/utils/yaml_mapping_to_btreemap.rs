@@ -29,7 +29,7 @@ use std::collections::BTreeMap;
 ///
 /// # Examples
 ///
-/// ```
+/// ```ignore
 /// use serde_yml::Value;
 /// use std::collections::BTreeMap;
 ///
@@ -0,0 +1,308 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use markdown::mdast::Node;
+use markdown::ParseOptions;
+use regex::Regex;
+use serde_yml::Value;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::asset_base::{asset_directory, is_markdown_link};
+use crate::directory_config::apply_directory_config;
+use crate::generate::{apply_front_matter_defaults, expand_document_variables, DocumentOverrides};
+use crate::utils::{
+    missing_front_matter_keys, parse_front_matter, unfilled_placeholders, yaml_mapping_to_btreemap,
+    FrontMatterError,
+};
+use pdf_composer_definitions::diagnostics::{DiagnosticKind, SourceDiagnostics};
+use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
+
+/// This function validates a single source document without generating a PDF.
+///
+/// # Arguments
+///
+/// * `source_file` - A reference to the `PathBuf` of the source document to validate.
+/// * `composer` - The `PDFComposerStruct` whose settings (document information entries,
+///   required front matter keys, front matter defaults, asset base) the document is checked
+///   against, with the `_pdf_composer.yaml` settings of its directories on top, as it is generated.
+///
+/// # Returns
+///
+/// A `SourceDiagnostics` struct listing every problem found. An empty list means the document is clean.
+///
+/// # Remarks
+///
+/// The following checks are performed:
+///
+/// 1. The source file exists and can be read.
+/// 2. The `_pdf_composer.yaml` files of its directories can be read and have valid settings.
+/// 3. The source file has a YAML Front Matter section that parses as a mapping.
+/// 4. Every key set with `require_front_matter_keys` is present, after the front matter defaults.
+/// 5. Every `{{placeholder}}` in the markdown has a matching (string) front matter value.
+/// 6. Every local image and link resolves to an existing file, relative to the asset base (the
+///    source file's directory without one, and for links to other markdown documents), and every
+///    image has alt text. Nothing is checked against an asset base URL.
+/// 7. Every document information entry refers to a key that exists in the front matter.
+pub fn validate_source(source_file: &PathBuf, composer: &PDFComposerStruct) -> SourceDiagnostics {
+    let mut diagnostics: Vec<DiagnosticKind> = Vec::new();
+
+    let source = match fs::read_to_string(source_file) {
+        Ok(source) => source,
+        Err(_) => {
+            diagnostics.push(DiagnosticKind::FileNotFound);
+            return SourceDiagnostics {
+                source_file: source_file.to_path_buf(),
+                diagnostics,
+            };
+        }
+    };
+
+    // The `_pdf_composer.yaml` settings of the directories of the document
+    let (composer, overrides) = match apply_directory_config(composer, source_file) {
+        Ok(settings) => settings,
+        Err(error) => {
+            diagnostics.push(DiagnosticKind::InvalidDirectoryConfig(error));
+            (Cow::Borrowed(composer), DocumentOverrides::default())
+        }
+    };
+    let composer = composer.as_ref();
+
+    let (yaml_content, markdown_content) = match parse_front_matter(&source) {
+        Ok(sections) => sections,
+        Err(FrontMatterError::Missing) => {
+            diagnostics.push(DiagnosticKind::MissingFrontMatter);
+            (String::new(), String::new())
+        }
+        Err(FrontMatterError::Unterminated(line_number)) => {
+            diagnostics.push(DiagnosticKind::UnterminatedFrontMatter(line_number));
+            (String::new(), String::new())
+        }
+    };
+
+    let yaml: Value = match serde_yml::from_str(&yaml_content) {
+        Ok(yaml) => yaml,
+        Err(error) => {
+            diagnostics.push(DiagnosticKind::InvalidFrontMatter(error.to_string()));
+            Value::Null
+        }
+    };
+
+    let yaml_btreemap: BTreeMap<String, Value> = if yaml == Value::Null {
+        if diagnostics.is_empty() {
+            diagnostics.push(DiagnosticKind::MissingFrontMatter);
+        }
+        BTreeMap::new()
+    } else {
+        let mut yaml_btreemap = yaml_mapping_to_btreemap(&yaml).unwrap_or_default();
+        apply_front_matter_defaults(composer, &mut yaml_btreemap);
+        yaml_btreemap
+    };
+    // Environment variables and dates are filled in as they are when the document is generated
+    let (yaml_btreemap, markdown_content) =
+        expand_document_variables(composer, source_file, yaml_btreemap, &markdown_content);
+
+    if !yaml_btreemap.is_empty() {
+        for key in missing_front_matter_keys(&yaml_btreemap, &composer.required_front_matter_keys) {
+            diagnostics.push(DiagnosticKind::MissingRequiredKey(key));
+        }
+    }
+
+    for key in unfilled_placeholders(&yaml_btreemap, &markdown_content) {
+        diagnostics.push(DiagnosticKind::UnknownPlaceholder(key));
+    }
+
+    // Only string values are substituted into placeholders (see `merge_markdown_yaml`)
+    let string_keys: BTreeSet<&String> = yaml_btreemap
+        .iter()
+        .filter(|(_key, value)| value.is_string())
+        .map(|(key, _value)| key)
+        .collect();
+
+    let directories = ReferenceDirectories {
+        source_directory: source_file.parent().unwrap_or(Path::new("")),
+        asset_directory: asset_directory(source_file, composer.asset_base.as_ref()),
+    };
+    if let Ok(tree) = markdown::to_mdast(&markdown_content, &ParseOptions::default()) {
+        check_local_references(&tree, &directories, &mut diagnostics);
+    }
+
+    let mut dictionary_entries = composer.pdf_document_entries.clone().unwrap_or_default();
+    dictionary_entries.extend(overrides.doc_info_entries);
+    for (doc_info_entry, yaml_entry) in &dictionary_entries {
+        if !yaml_btreemap.is_empty() && !string_keys.contains(yaml_entry) {
+            diagnostics.push(DiagnosticKind::MissingDocInfoKey {
+                doc_info_entry: doc_info_entry.to_string(),
+                yaml_entry: yaml_entry.to_string(),
+            });
+        }
+    }
+
+    SourceDiagnostics {
+        source_file: source_file.to_path_buf(),
+        diagnostics,
+    }
+}
+
+/// The directories the local links and images of a document are resolved against, as they are
+/// when it is rendered (see `resolve_assets` and `rewrite_document_links`).
+struct ReferenceDirectories<'a> {
+    /// The directory of the source document, for links to other markdown documents
+    source_directory: &'a Path,
+    /// The asset base directory for images and other links (`None` for an asset base URL)
+    asset_directory: Option<&'a Path>,
+}
+
+/// Recursively walks the markdown tree, recording links and images whose local target does not exist,
+/// and images without alt text.
+fn check_local_references(
+    node: &Node,
+    directories: &ReferenceDirectories,
+    diagnostics: &mut Vec<DiagnosticKind>,
+) {
+    match node {
+        Node::Image(image) => {
+            if !local_target_exists(&image.url, directories) {
+                diagnostics.push(DiagnosticKind::UnresolvedImage(image.url.to_string()));
+            }
+            if image.alt.trim().is_empty() {
+                diagnostics.push(DiagnosticKind::MissingAltText(image.url.to_string()));
+            }
+        }
+        Node::Link(link) if !local_target_exists(&link.url, directories) => {
+            diagnostics.push(DiagnosticKind::BrokenLinkPath(link.url.to_string()));
+        }
+        Node::Definition(definition) if !local_target_exists(&definition.url, directories) => {
+            diagnostics.push(DiagnosticKind::BrokenLinkPath(definition.url.to_string()));
+        }
+        _ => {}
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            check_local_references(child, directories, diagnostics);
+        }
+    }
+}
+
+/// Returns `false` only when `url` is a local path that cannot be found.
+/// Remote URLs (anything with a scheme), `data:` URIs and in-page `#anchors` are always treated as
+/// resolvable, and so is everything resolved against an asset base URL.
+fn local_target_exists(url: &str, directories: &ReferenceDirectories) -> bool {
+    let scheme_regex = Regex::new(r"^[A-Za-z][A-Za-z0-9+.-]*:").unwrap();
+    if url.is_empty() || url.starts_with('#') || scheme_regex.is_match(url) {
+        return true;
+    }
+
+    // Drop any fragment or query string before looking on disk
+    let path_part = url.split(['#', '?']).next().unwrap_or_default();
+    let decoded_path = url_escape::decode(path_part).to_string();
+
+    let directory = if is_markdown_link(path_part) {
+        directories.source_directory
+    } else {
+        match directories.asset_directory {
+            Some(asset_directory) => asset_directory,
+            None => return true,
+        }
+    };
+    directory.join(decoded_path).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PDFComposer;
+    use std::env;
+    use std::process;
+
+    /// Returns an empty directory of its own in the temporary directory.
+    fn test_directory(name: &str) -> PathBuf {
+        let directory =
+            env::temp_dir().join(format!("pdf-composer-validate-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn applies_the_front_matter_defaults_of_the_composer_and_directories() {
+        let directory = test_directory("defaults");
+        fs::write(
+            directory.join("_pdf_composer.yaml"),
+            "doc_info:\n  Subject: department\ndefaults:\n  department: Legal\n",
+        )
+        .unwrap();
+        let source_file = directory.join("terms.md");
+        fs::write(
+            &source_file,
+            "---\ntitle: Terms\n---\n# {{title}} for {{company}} ({{department}})\n",
+        )
+        .unwrap();
+
+        let mut composer: PDFComposerStruct = PDFComposer::new();
+        composer.set_front_matter_defaults([("company", "Orange Kiwi")]);
+        composer.require_front_matter_keys(&["company", "department"]);
+        composer.set_doc_info_entries([("Title", "title"), ("Author", "company")]);
+
+        let diagnostics = validate_source(&source_file, &composer);
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(diagnostics.diagnostics, []);
+    }
+
+    #[test]
+    fn reports_an_invalid_directory_configuration() {
+        let directory = test_directory("invalid-config");
+        fs::write(
+            directory.join("_pdf_composer.yaml"),
+            "sources: [other.md]\n",
+        )
+        .unwrap();
+        let source_file = directory.join("terms.md");
+        fs::write(&source_file, "---\ntitle: Terms\n---\n# Terms\n").unwrap();
+
+        let composer: PDFComposerStruct = PDFComposer::new();
+        let diagnostics = validate_source(&source_file, &composer);
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(matches!(
+            diagnostics.diagnostics.as_slice(),
+            [DiagnosticKind::InvalidDirectoryConfig(_)]
+        ));
+        assert!(diagnostics.has_errors());
+    }
+
+    #[test]
+    fn resolves_images_and_links_against_the_asset_base() {
+        let directory = test_directory("asset-base");
+        let assets = directory.join("assets");
+        fs::create_dir_all(assets.join("images")).unwrap();
+        fs::write(assets.join("images/logo.png"), b"PNG").unwrap();
+        fs::write(assets.join("terms.pdf"), b"%PDF").unwrap();
+        fs::write(directory.join("other.md"), "---\ntitle: Other\n---\n").unwrap();
+        let source_file = directory.join("terms.md");
+        fs::write(
+            &source_file,
+            "---\ntitle: Terms\n---\n![Logo](images/logo.png)\n\n[Terms](terms.pdf) and \
+             [other](other.md) but ![Missing](images/missing.png)\n",
+        )
+        .unwrap();
+
+        let mut composer: PDFComposerStruct = PDFComposer::new();
+        composer.set_asset_base(assets.as_path());
+        let diagnostics = validate_source(&source_file, &composer);
+        assert_eq!(
+            diagnostics.diagnostics,
+            [DiagnosticKind::UnresolvedImage(
+                "images/missing.png".to_string()
+            )]
+        );
+
+        // Nothing is checked against an asset base URL
+        composer.set_asset_base("https://cdn.example.com/docs/");
+        let diagnostics = validate_source(&source_file, &composer);
+        fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(diagnostics.diagnostics, []);
+    }
+}
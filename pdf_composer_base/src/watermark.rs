@@ -0,0 +1,137 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object as LopdfObject, ObjectId};
+
+use crate::page_tree::page_with_inherited_attributes;
+use crate::stamp::{
+    add_page_font, add_standard_font, append_page_content, approximate_text_width, page_media_box,
+    stamp_font_name, STAMP_FONT_RESOURCE,
+};
+use crate::utils::encode_win_ansi;
+use pdf_composer_definitions::fonts::FontsStandard;
+
+/// Resource name the see-through graphics state of the watermark is added to each page under.
+const WATERMARK_GRAPHICS_STATE_RESOURCE: &str = "PDFComposerGS1";
+
+/// How opaque the watermark is, from 0 (invisible) to 1, so the text under it can still be read.
+const WATERMARK_OPACITY: f32 = 0.15;
+
+/// The largest font size of the watermark, in points, for short texts on large pages.
+const MAX_WATERMARK_FONT_SIZE: f32 = 144.0;
+
+/// This function draws a watermark (such as `DRAFT`) diagonally across the middle of every page.
+///
+/// # Arguments
+///
+/// * `doc` - A mutable reference to the lopdf `Document`.
+/// * `text` - The text of the watermark. Characters the standard fonts cannot draw are shown as `?`.
+/// * `font` - The font of the document (the watermark is drawn in the matching standard 14 font).
+///
+/// # Returns
+///
+/// * `Ok(())` if every page was watermarked.
+/// * `Err(e)` if a page could not be updated.
+///
+/// # Remarks
+///
+/// The watermark is drawn in see-through grey on top of the page content, from the bottom left
+/// towards the top right corner, and is sized to span about two thirds of the diagonal.
+pub fn stamp_watermark(
+    doc: &mut Document,
+    text: &str,
+    font: FontsStandard,
+) -> Result<(), lopdf::Error> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(());
+    }
+    let base_font = stamp_font_name(font);
+    let font_id = add_standard_font(doc, base_font);
+    let graphics_state_id = doc.add_object(dictionary! {
+        "Type" => "ExtGState",
+        "ca" => WATERMARK_OPACITY,
+        "CA" => WATERMARK_OPACITY,
+    });
+
+    for (_, page_id) in doc.get_pages() {
+        let Some([left, bottom, right, top]) = page_media_box(doc, page_id) else {
+            continue;
+        };
+        let (width, height) = (right - left, top - bottom);
+        let diagonal = width.hypot(height);
+        let font_size = (diagonal * 0.66 / approximate_text_width(text, base_font, 1.0))
+            .min(MAX_WATERMARK_FONT_SIZE);
+        let text_width = approximate_text_width(text, base_font, font_size);
+        let (sin, cos) = (height / diagonal, width / diagonal);
+
+        add_page_font(doc, page_id, STAMP_FONT_RESOURCE, font_id)?;
+        add_page_graphics_state(doc, page_id, graphics_state_id)?;
+        let operations = vec![
+            Operation::new(
+                "gs",
+                vec![LopdfObject::Name(
+                    WATERMARK_GRAPHICS_STATE_RESOURCE.as_bytes().to_vec(),
+                )],
+            ),
+            Operation::new("g", vec![0.5.into()]),
+            // Rotate about the centre of the page
+            Operation::new(
+                "cm",
+                vec![
+                    cos.into(),
+                    sin.into(),
+                    (-sin).into(),
+                    cos.into(),
+                    (left + width / 2.0).into(),
+                    (bottom + height / 2.0).into(),
+                ],
+            ),
+            Operation::new("BT", vec![]),
+            Operation::new(
+                "Tf",
+                vec![
+                    LopdfObject::Name(STAMP_FONT_RESOURCE.as_bytes().to_vec()),
+                    font_size.into(),
+                ],
+            ),
+            // Centred on the diagonal, the capital letters about 0.7 of the font size high
+            Operation::new(
+                "Td",
+                vec![(-text_width / 2.0).into(), (-font_size * 0.35).into()],
+            ),
+            Operation::new(
+                "Tj",
+                vec![LopdfObject::string_literal(encode_win_ansi(text))],
+            ),
+            Operation::new("ET", vec![]),
+        ];
+        append_page_content(doc, page_id, Content { operations }.encode()?)?;
+    }
+
+    Ok(())
+}
+
+/// Makes the see-through graphics state of the watermark available to the content of a page (see
+/// `add_page_font`, which does the same for fonts).
+fn add_page_graphics_state(
+    doc: &mut Document,
+    page_id: ObjectId,
+    graphics_state_id: ObjectId,
+) -> Result<(), lopdf::Error> {
+    let page = page_with_inherited_attributes(doc, page_id)?;
+    let mut resources = match page.get(b"Resources") {
+        Ok(resources) => doc.dereference(resources)?.1.as_dict()?.clone(),
+        Err(_) => Dictionary::new(),
+    };
+    let mut graphics_states = match resources.get(b"ExtGState") {
+        Ok(graphics_states) => doc.dereference(graphics_states)?.1.as_dict()?.clone(),
+        Err(_) => Dictionary::new(),
+    };
+    graphics_states.set(WATERMARK_GRAPHICS_STATE_RESOURCE, graphics_state_id);
+    resources.set("ExtGState", graphics_states);
+
+    doc.get_dictionary_mut(page_id)?.set("Resources", resources);
+    Ok(())
+}
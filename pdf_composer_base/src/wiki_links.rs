@@ -0,0 +1,77 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use regex::{Captures, Regex};
+
+use crate::utils::{escape_html, map_outside_code};
+use pdf_composer_definitions::wiki_links::WikiLinkResolver;
+
+/// This function turns `[[wiki links]]` in the generated HTML into links.
+///
+/// # Arguments
+///
+/// * `html` - The HTML generated from the markdown.
+/// * `resolver` - The function resolving page names to paths or URLs (`None` to link to the document
+///   generated for the page, `Page Name.pdf`).
+/// * `extension` - The file extension of the generated documents (`pdf` or `html`).
+///
+/// # Returns
+///
+/// A `String` with the wiki links replaced by `<a class="wiki-link">` links.
+///
+/// # Remarks
+///
+/// `[[Page]]`, `[[Page|link text]]` and `[[Page#Section]]` are supported. The link text defaults to the
+/// page name (with the section, if any). Wiki links inside code are left alone.
+pub fn resolve_wiki_links(
+    html: &str,
+    resolver: Option<&WikiLinkResolver>,
+    extension: &str,
+) -> String {
+    if !html.contains("[[") {
+        return html.to_string();
+    }
+
+    let wiki_link_regex =
+        Regex::new(r"\[\[([^\[\]|#<>]+)(?:#([^\[\]|<>]+))?(?:\|([^\[\]<>]+))?\]\]").unwrap();
+
+    map_outside_code(html, |text| {
+        wiki_link_regex
+            .replace_all(text, |captures: &Captures| {
+                let page = unescape_html(captures[1].trim());
+                let mut target = match resolver {
+                    Some(resolver) => resolver(&page),
+                    None => format!("{}.{}", url_escape::encode_path(&page), extension),
+                };
+                if let Some(section) = captures.get(2) {
+                    target.push('#');
+                    target.push_str(&url_escape::encode_fragment(&unescape_html(
+                        section.as_str().trim(),
+                    )));
+                }
+
+                let link_text = match (captures.get(3), captures.get(2)) {
+                    (Some(alias), _) => alias.as_str().trim().to_string(),
+                    (None, Some(section)) => {
+                        format!("{} &gt; {}", captures[1].trim(), section.as_str().trim())
+                    }
+                    (None, None) => captures[1].trim().to_string(),
+                };
+
+                format!(
+                    "<a class=\"wiki-link\" href=\"{}\">{}</a>",
+                    escape_html(&target),
+                    link_text
+                )
+            })
+            .to_string()
+    })
+}
+
+/// Reverses the HTML escaping of the markdown conversion, giving back the page name as written.
+fn unescape_html(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
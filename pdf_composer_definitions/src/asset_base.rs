@@ -0,0 +1,49 @@
+use std::path::{Path, PathBuf};
+
+/// Enum representing the root relative links, images and stylesheets are resolved against: a local directory or a URL
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathOrUrl {
+    /// A local directory (images are embedded from it, links point to its files)
+    Path(PathBuf),
+    /// A URL, such as `https://cdn.example.com/docs/` (images are loaded from it, links point to it)
+    Url(String),
+}
+
+/// Implement `From<&str>` for PathOrUrl: strings with a scheme (`https://` etc) are URLs, anything else a path.
+impl From<&str> for PathOrUrl {
+    fn from(value: &str) -> Self {
+        let has_scheme = value.split_once("://").is_some_and(|(scheme, _)| {
+            scheme.len() > 1
+                && scheme.starts_with(|character: char| character.is_ascii_alphabetic())
+                && scheme.chars().all(|character| {
+                    character.is_ascii_alphanumeric() || matches!(character, '+' | '-' | '.')
+                })
+        });
+        if has_scheme {
+            PathOrUrl::Url(value.to_string())
+        } else {
+            PathOrUrl::Path(PathBuf::from(value))
+        }
+    }
+}
+
+/// Implement `From<String>` for PathOrUrl (see `From<&str>`).
+impl From<String> for PathOrUrl {
+    fn from(value: String) -> Self {
+        PathOrUrl::from(value.as_str())
+    }
+}
+
+/// Implement `From<&Path>` for PathOrUrl.
+impl From<&Path> for PathOrUrl {
+    fn from(value: &Path) -> Self {
+        PathOrUrl::Path(value.to_path_buf())
+    }
+}
+
+/// Implement `From<PathBuf>` for PathOrUrl.
+impl From<PathBuf> for PathOrUrl {
+    fn from(value: PathBuf) -> Self {
+        PathOrUrl::Path(value)
+    }
+}
@@ -0,0 +1,13 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::path::PathBuf;
+
+/// Struct representing a file embedded in the generated PDFs as an attachment (such as a data CSV, a signature or the terms)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileAttachment {
+    /// The path of the file. The file name is the name of the attachment
+    pub path: PathBuf,
+    /// The description shown next to the attachment in PDF readers (`None` for no description)
+    pub description: Option<String>,
+}
@@ -0,0 +1,52 @@
+use std::{fmt, time::Duration};
+
+/// Struct representing the time one stage of generation took, over the iterations of a benchmark
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StageTimings {
+    /// Name of the stage (for example, `render`, or `build (pdf)` for building the PDFs)
+    pub stage: String,
+    /// The time the stage took in the fastest iteration
+    pub min: Duration,
+    /// The median time the stage took
+    pub median: Duration,
+    /// The time the stage took in the slowest iteration
+    pub max: Duration,
+}
+
+/// Struct holding the results of a `bench` run
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BenchReport {
+    /// The number of times the source files were rendered
+    pub iterations: usize,
+    /// The number of source files rendered in each iteration
+    pub documents: usize,
+    /// The timings of each stage (over all the source files of an iteration), in the order the stages run, followed by the `total`
+    pub stages: Vec<StageTimings>,
+}
+
+/// Implements fmt::Display for BenchReport, as a table with one row per stage.
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} iteration(s) of {} document(s)",
+            self.iterations, self.documents
+        )?;
+        writeln!(
+            f,
+            "{:<16} {:>12} {:>12} {:>12}",
+            "stage", "min", "median", "max"
+        )?;
+        for timings in &self.stages {
+            writeln!(
+                f,
+                "{:<16} {:>12} {:>12} {:>12}",
+                timings.stage,
+                format!("{:.2?}", timings.min),
+                format!("{:.2?}", timings.median),
+                format!("{:.2?}", timings.max)
+            )?;
+        }
+        Ok(())
+    }
+}
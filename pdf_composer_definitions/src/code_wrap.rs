@@ -0,0 +1,14 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// Enum representing what happens to lines of code blocks that are too long for the page
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CodeWrap {
+    /// Wrap long lines onto the next line (line numbers stay with the line they started on)
+    #[default]
+    Wrap,
+    /// Make the text of a code block small enough for its longest line to fit
+    ShrinkToFit,
+    /// Cut long lines off at the edge of the page, ending them with a `…` marker
+    Truncate,
+}
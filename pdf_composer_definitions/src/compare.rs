@@ -0,0 +1,100 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Struct representing the options used when comparing two PDF documents
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompareOptions {
+    /// Compare the pages as images too, rendered with `pdftoppm` (Poppler), not only their structure
+    pub raster: bool,
+    /// The resolution the pages are rendered at, in dots per inch
+    pub dpi: u32,
+    /// How far apart (`0` to `255`) the samples of a pixel may be before the pixel counts as different (for anti-aliasing noise)
+    pub pixel_tolerance: u8,
+    /// The fraction (`0.0` to `1.0`) of the pixels of a page that may differ before the page counts as different
+    pub max_different_pixels: f64,
+    /// The directory the diff image (PNG) of each page that differs is written to. `None` writes no images
+    pub diff_directory: Option<PathBuf>,
+    /// The document information entries left out of the comparison, as they change every time a PDF is generated
+    pub ignored_info_entries: Vec<String>,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            raster: true,
+            dpi: 72,
+            pixel_tolerance: 16,
+            max_different_pixels: 0.0,
+            diff_directory: None,
+            ignored_info_entries: vec!["CreationDate".to_string(), "ModDate".to_string()],
+        }
+    }
+}
+
+/// Struct representing a difference in the structure of two PDF documents (their metadata, page sizes, text, fonts etc)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralDifference {
+    /// The page the difference is on (starting at `1`), or `None` for the document as a whole
+    pub page_number: Option<u32>,
+    /// What differs (for example, `page count`, `info Title`, `text` or `fonts`)
+    pub subject: String,
+    /// The value in the first document (empty if it has none)
+    pub a: String,
+    /// The value in the second document (empty if it has none)
+    pub b: String,
+}
+
+impl fmt::Display for StructuralDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(page_number) = self.page_number {
+            write!(f, "page {} ", page_number)?;
+        }
+        write!(f, "{}: {:?} ≠ {:?}", self.subject, self.a, self.b)
+    }
+}
+
+/// Struct representing the raster (image) comparison of a page of two PDF documents
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageComparison {
+    /// The page number (starting at `1`)
+    pub page_number: u32,
+    /// The number of pixels that differ by more than the pixel tolerance (all of them when the page sizes differ)
+    pub different_pixels: usize,
+    /// The number of pixels compared
+    pub total_pixels: usize,
+    /// Whether more pixels differ than the options allow
+    pub differs: bool,
+    /// The diff image written for the page, if it differs and a diff directory was given
+    pub diff_image: Option<PathBuf>,
+}
+
+impl PageComparison {
+    /// Returns the fraction (`0.0` to `1.0`) of the pixels of the page that differ
+    pub fn difference_ratio(&self) -> f64 {
+        match self.total_pixels {
+            0 => 0.0,
+            total_pixels => self.different_pixels as f64 / total_pixels as f64,
+        }
+    }
+}
+
+/// Struct representing the result of comparing two PDF documents
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PdfComparison {
+    /// The differences in the structure of the documents
+    pub structural_differences: Vec<StructuralDifference>,
+    /// The raster comparison of each page both documents have (empty when `CompareOptions::raster` is off)
+    pub pages: Vec<PageComparison>,
+}
+
+impl PdfComparison {
+    /// Returns `true` if the documents have no structural differences and no page differs
+    pub fn is_match(&self) -> bool {
+        self.structural_differences.is_empty() && self.different_pages().next().is_none()
+    }
+
+    /// Returns the raster comparisons of the pages that differ
+    pub fn different_pages(&self) -> impl Iterator<Item = &PageComparison> {
+        self.pages.iter().filter(|page| page.differs)
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::diagnostics::DiagnosticSeverity;
+
+/// The name of the configuration file the command line reads from the current directory
+pub const CONFIG_FILE_NAME: &str = "pdf_composer.toml";
+
+/// The name of the configuration files in source directories, whose settings apply to the documents
+/// beneath them (on top of those of the directories above and of the configuration file)
+pub const DIRECTORY_CONFIG_FILE_NAME: &str = "_pdf_composer.yaml";
+
+/// The URL of the JSON Schema of the configuration file, for editors to complete and validate it
+pub const CONFIG_SCHEMA_URL: &str =
+    "https://raw.githubusercontent.com/orangekiwi-io/pdf_composer/main/pdf_composer.schema.json";
+
+/// Struct representing a configuration file (`pdf_composer.toml`, or `.yaml`), with the settings a
+/// composer is set up with. Every setting is optional, and paths are relative to the file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+#[schemars(
+    title = "PDF Composer configuration",
+    description = "The settings of a PDF Composer configuration file (pdf_composer.toml). Paths are relative to the file"
+)]
+pub struct PDFComposerConfig {
+    /// The source documents to generate (a directory adds the source documents in it)
+    pub sources: Vec<PathBuf>,
+    /// The extensions of the source documents found in source directories, as `set_source_extensions` takes them (for example, `.md` and `.mdx`)
+    pub source_extensions: Vec<String>,
+    /// The directory the generated files are written to
+    pub output_directory: Option<PathBuf>,
+    /// A paper size name, for example `A4`, `Letter` or `JIS B5`
+    pub paper_size: Option<String>,
+    /// `portrait` or `landscape`
+    pub orientation: Option<String>,
+    /// The page margins in millimetres, as `set_margins` takes them (for example, `20` or `25 20 25 20`)
+    #[schemars(regex(pattern = r"^\s*\d+(\s+\d+){0,3}\s*$"))]
+    pub margins: Option<String>,
+    /// The language of the documents (a BCP 47 tag such as `en-GB`)
+    pub language: Option<String>,
+    /// The locale the dates and numbers of the placeholders are written in (for example, `de-DE`)
+    pub locale: Option<String>,
+    /// Document information entries mapped to the front matter keys they are read from (replacing the default entries)
+    pub doc_info: BTreeMap<String, String>,
+    /// Front matter values the documents get when they do not set them (for example, `company`)
+    pub defaults: BTreeMap<String, String>,
+    /// A CSS file whose styles are added after the built-in styles
+    pub stylesheet: Option<PathBuf>,
+    /// Text drawn diagonally across every page, such as `DRAFT` (an empty text for no watermark)
+    pub watermark: Option<String>,
+    /// The highest resolution, in dots per inch, the embedded images are downsampled to (for example, `150`)
+    #[schemars(range(min = 1))]
+    pub image_max_dpi: Option<u32>,
+    /// The quality, from 1 (smallest) to 100 (best), large JPEG images are recompressed at
+    #[schemars(range(min = 1, max = 100))]
+    pub jpeg_quality: Option<u8>,
+    /// Flags `{{#if name}}` conditional blocks of the documents are kept for (for example, `internal`)
+    pub flags: Vec<String>,
+    /// Named sets of settings applied on top of the others when selected (`set_profile` or `--profile`), such as `draft` and `final`
+    pub profile: BTreeMap<String, PDFComposerConfig>,
+}
+
+/// Enum representing the problems that can be found when validating a configuration file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigDiagnostic {
+    /// The file could not be read or parsed, or has a setting that is not recognised (the parser message is included)
+    InvalidFile(String),
+    /// A setting has a value that is not valid
+    InvalidValue {
+        /// The name of the setting (for example, `paper_size`)
+        setting: String,
+        /// What is wrong with the value
+        message: String,
+    },
+    /// A source document does not exist
+    SourceNotFound(PathBuf),
+    /// The stylesheet does not exist (the documents are generated without it)
+    StylesheetNotFound(PathBuf),
+}
+
+impl ConfigDiagnostic {
+    /// Returns the severity for this kind of diagnostic
+    pub fn severity(&self) -> DiagnosticSeverity {
+        match self {
+            ConfigDiagnostic::StylesheetNotFound(_) => DiagnosticSeverity::Warning,
+            _ => DiagnosticSeverity::Error,
+        }
+    }
+}
+
+/// Implements fmt::Display for ConfigDiagnostic, giving a human readable description of the problem.
+impl fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigDiagnostic::InvalidFile(message) => write!(f, "{}", message),
+            ConfigDiagnostic::InvalidValue { setting, message } => {
+                write!(f, "{}: {}", setting, message)
+            }
+            ConfigDiagnostic::SourceNotFound(path) => {
+                write!(f, "source document {} not found", path.display())
+            }
+            ConfigDiagnostic::StylesheetNotFound(path) => {
+                write!(f, "stylesheet {} not found", path.display())
+            }
+        }
+    }
+}
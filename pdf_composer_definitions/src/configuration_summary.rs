@@ -0,0 +1,135 @@
+use std::{collections::BTreeMap, fmt, path::PathBuf};
+
+use crate::conformance::Conformance;
+use crate::consts::MM_TO_INCH;
+use crate::fonts::FontsStandard;
+use crate::output_format::OutputFormat;
+use crate::page_properties::{PageMargins, PaperOrientation, PaperSize};
+use crate::pdf_version::PDFVersion;
+use crate::text_direction::TextDirection;
+
+/// Struct holding a snapshot of the settings of a `PDFComposerStruct`, for displaying or validating them
+#[derive(Clone, Debug)]
+pub struct ConfigurationSummary {
+    /// The source files, in the order they were added
+    pub source_files: Vec<PathBuf>,
+    /// The directory the documents are saved to
+    pub output_directory: PathBuf,
+    /// The formats of the generated documents
+    pub output_formats: Vec<OutputFormat>,
+    /// The PDF version (`Auto` picks the lowest one the features need)
+    pub pdf_version: PDFVersion,
+    /// The paper size
+    pub paper_size: PaperSize,
+    /// The paper orientation
+    pub orientation: PaperOrientation,
+    /// The page margins (top, right, bottom, left) in inches
+    pub margins: PageMargins,
+    /// Whether the left and right margins alternate on odd and even pages
+    pub mirrored_margins: bool,
+    /// The standard font
+    pub font: FontsStandard,
+    /// The direction text is written in
+    pub text_direction: TextDirection,
+    /// The language tag (`None` if not set)
+    pub language: Option<String>,
+    /// The document information entries, mapped to the YAML Front Matter keys they are filled from
+    pub doc_info_entries: BTreeMap<String, String>,
+    /// The YAML Front Matter keys every source document must have
+    pub required_front_matter_keys: Vec<String>,
+    /// The file name of the merged PDF in merge mode (`None` for one PDF per source file)
+    pub merged_file_name: Option<String>,
+    /// Whether tagged (accessible) PDFs are generated
+    pub tagged_pdf: bool,
+    /// The standard the PDFs conform to (`None` for no particular standard)
+    pub conformance: Option<Conformance>,
+    /// Whether identical inputs give identical PDFs
+    pub deterministic: bool,
+    /// The directory the rendered HTML is cached in (`None` for no cache)
+    pub html_cache: Option<PathBuf>,
+    /// Whether a warm headless Chromium is kept alive between calls
+    pub persistent_browser: bool,
+}
+
+/// Implements fmt::Display for ConfigurationSummary, with one setting per line (margins in millimetres).
+impl fmt::Display for ConfigurationSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<22} {}", "source files", self.source_files.len())?;
+        for source_file in &self.source_files {
+            writeln!(f, "{:<22} {}", "", source_file.display())?;
+        }
+        writeln!(
+            f,
+            "{:<22} {}",
+            "output directory",
+            self.output_directory.display()
+        )?;
+        let output_formats: Vec<String> = self
+            .output_formats
+            .iter()
+            .map(|output_format| output_format.to_string())
+            .collect();
+        writeln!(f, "{:<22} {}", "output formats", output_formats.join(", "))?;
+        writeln!(f, "{:<22} {}", "PDF version", self.pdf_version)?;
+        writeln!(
+            f,
+            "{:<22} {:?} {:?}",
+            "paper", self.paper_size, self.orientation
+        )?;
+        let margins: Vec<String> = self
+            .margins
+            .iter()
+            .map(|margin| format!("{}", (margin * MM_TO_INCH * 100.0).round() / 100.0))
+            .collect();
+        writeln!(
+            f,
+            "{:<22} {} mm{}",
+            "margins",
+            margins.join(" "),
+            if self.mirrored_margins {
+                " (mirrored)"
+            } else {
+                ""
+            }
+        )?;
+        writeln!(f, "{:<22} {:?}", "font", self.font)?;
+        writeln!(f, "{:<22} {}", "text direction", self.text_direction)?;
+        writeln!(
+            f,
+            "{:<22} {}",
+            "language",
+            self.language.as_deref().unwrap_or("-")
+        )?;
+        for (doc_info_entry, yaml_entry) in &self.doc_info_entries {
+            writeln!(
+                f,
+                "{:<22} {} ← {}",
+                "doc info entry", doc_info_entry, yaml_entry
+            )?;
+        }
+        if !self.required_front_matter_keys.is_empty() {
+            writeln!(
+                f,
+                "{:<22} {}",
+                "required keys",
+                self.required_front_matter_keys.join(", ")
+            )?;
+        }
+        if let Some(merged_file_name) = &self.merged_file_name {
+            writeln!(f, "{:<22} {}", "merged into", merged_file_name)?;
+        }
+        writeln!(f, "{:<22} {}", "tagged PDF", self.tagged_pdf)?;
+        if let Some(conformance) = &self.conformance {
+            writeln!(f, "{:<22} {}", "conformance", conformance)?;
+        }
+        writeln!(f, "{:<22} {}", "deterministic", self.deterministic)?;
+        if let Some(html_cache) = &self.html_cache {
+            writeln!(f, "{:<22} {}", "HTML cache", html_cache.display())?;
+        }
+        writeln!(
+            f,
+            "{:<22} {}",
+            "persistent browser", self.persistent_browser
+        )
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::pdf_version::PDFFeature;
+
+/// Enum representing the standards (profiles) the generated PDFs can be made to conform to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conformance {
+    /// PDF/X-4 (ISO 15930-7) for print production: embedded fonts, an output intent, a TrimBox and
+    /// BleedBox on every page, transparency groups with a colour space, and PDF/X metadata
+    PdfX4,
+}
+
+impl Conformance {
+    /// Returns the feature (with the PDF versions it allows) the conformance needs
+    pub fn pdf_feature(&self) -> PDFFeature {
+        match self {
+            Conformance::PdfX4 => PDFFeature::PdfX4,
+        }
+    }
+}
+
+/// Implements fmt::Display for Conformance, showing the name of the standard.
+impl fmt::Display for Conformance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Conformance::PdfX4 => write!(f, "PDF/X-4"),
+        }
+    }
+}
+
+impl FromStr for Conformance {
+    type Err = String;
+
+    /// Parses the name of a standard (`PDF/X-4`, `pdfx4`), ignoring case, spaces, hyphens,
+    /// underscores and slashes
+    fn from_str(conformance: &str) -> Result<Self, Self::Err> {
+        let name: String = conformance
+            .chars()
+            .filter(|character| !matches!(character, ' ' | '-' | '_' | '/'))
+            .collect::<String>()
+            .to_lowercase();
+        match name.as_str() {
+            "pdfx4" => Ok(Conformance::PdfX4),
+            _ => Err(format!("unknown conformance {}", conformance)),
+        }
+    }
+}
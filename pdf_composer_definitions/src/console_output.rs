@@ -0,0 +1,26 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// Enum representing how much is printed to the console while generating
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConsoleOutput {
+    /// Only warnings and errors (on stderr), leaving stdout to the application
+    Quiet,
+    /// The progress messages: the files processed, the documents generated and the metadata of each PDF
+    #[default]
+    Normal,
+    /// The progress messages, the settings and the time each document took to generate
+    Verbose,
+}
+
+impl ConsoleOutput {
+    /// Returns `true` if the progress messages are left out
+    pub fn is_quiet(self) -> bool {
+        self == ConsoleOutput::Quiet
+    }
+
+    /// Returns `true` if the settings and timings are printed too
+    pub fn is_verbose(self) -> bool {
+        self == ConsoleOutput::Verbose
+    }
+}
@@ -10,3 +10,17 @@ pub const DEFAULT_MARGIN: f64 = 10.0;
 pub const MM_TO_INCH: f64 = 25.4;
 /// CONST for default output directory if no output directory specified
 pub const DEFAULT_OUTPUT_DIRECTORY: &str = "pdf_composer_pdfs";
+/// The extensions of the source files found in source directories, if none are set
+pub const DEFAULT_SOURCE_EXTENSIONS: [&str; 2] = [".md", ".markdown"];
+/// The reserved entry names of the PDF document information dictionary (case sensitive)
+pub const RESERVED_DOC_INFO_ENTRIES: [&str; 9] = [
+    "Title",
+    "Author",
+    "Subject",
+    "Keywords",
+    "Creator",
+    "Producer",
+    "CreationDate",
+    "ModDate",
+    "Trapped",
+];
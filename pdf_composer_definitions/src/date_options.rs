@@ -0,0 +1,54 @@
+use std::str::FromStr;
+
+/// Struct representing how the `{{today}}`, `{{now}}` and `{{build_date "format"}}` placeholders are written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DateOptions {
+    /// The offset of the time zone from UTC in minutes, such as `60` for CET or `-300` for EST (`0`, UTC, by default)
+    pub utc_offset_minutes: i32,
+    /// The language the month and day names (`%B`, `%b`, `%A` and `%a`) are written in
+    pub locale: DateLocale,
+}
+
+/// Enum representing the languages month and day names can be written in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DateLocale {
+    /// English (`January`, `Monday`)
+    #[default]
+    En,
+    /// German (`Januar`, `Montag`)
+    De,
+    /// Spanish (`enero`, `lunes`)
+    Es,
+    /// French (`janvier`, `lundi`)
+    Fr,
+    /// Italian (`gennaio`, `lunedì`)
+    It,
+    /// Dutch (`januari`, `maandag`)
+    Nl,
+    /// Portuguese (`janeiro`, `segunda-feira`)
+    Pt,
+}
+
+impl FromStr for DateLocale {
+    type Err = String;
+
+    /// Parses a language tag (such as `fr` or `fr-CA`, in any case) by its language
+    fn from_str(language: &str) -> Result<Self, Self::Err> {
+        let primary_language = language
+            .trim()
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        match primary_language.as_str() {
+            "en" => Ok(DateLocale::En),
+            "de" => Ok(DateLocale::De),
+            "es" => Ok(DateLocale::Es),
+            "fr" => Ok(DateLocale::Fr),
+            "it" => Ok(DateLocale::It),
+            "nl" => Ok(DateLocale::Nl),
+            "pt" => Ok(DateLocale::Pt),
+            _ => Err(format!("unsupported date locale {}", language)),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+/// Struct representing a named destination: a name that links (`document.pdf#name`) and
+/// bookmarks can point to, and the place in the document it stands for
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedDestination {
+    /// The name of the destination
+    pub name: String,
+    /// The page the destination is on (starting at `1`)
+    pub page_number: u32,
+    /// The distance from the bottom of the page to show at the top of the window, in points
+    /// (`None` for the top of the page)
+    pub top: Option<f32>,
+}
+
+impl NamedDestination {
+    /// Creates a destination for the top of page `page_number`
+    pub fn new(name: &str, page_number: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            page_number,
+            top: None,
+        }
+    }
+}
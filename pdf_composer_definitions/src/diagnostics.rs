@@ -0,0 +1,120 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Enum representing how serious a source document diagnostic is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnosticSeverity {
+    /// The document can still be generated, but the output may not be what was intended
+    Warning,
+    /// The document cannot be generated
+    Error,
+}
+
+/// Enum representing the problems that can be found when validating a source document
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// The source file does not exist
+    FileNotFound,
+    /// The source file has no YAML Front Matter section
+    MissingFrontMatter,
+    /// The YAML Front Matter opened on a line (starting at `1`) is not closed with a `---` line
+    UnterminatedFrontMatter(usize),
+    /// The YAML Front Matter could not be parsed (the parser message is included)
+    InvalidFrontMatter(String),
+    /// A `_pdf_composer.yaml` file in the directories of the document could not be read or has
+    /// invalid settings (the message is included)
+    InvalidDirectoryConfig(String),
+    /// A key required by `require_front_matter_keys` is missing from the front matter
+    MissingRequiredKey(String),
+    /// A `{{placeholder}}` in the markdown has no matching front matter value
+    UnknownPlaceholder(String),
+    /// A link to a local file points at a path that does not exist
+    BrokenLinkPath(String),
+    /// An image points at a local path that does not exist
+    UnresolvedImage(String),
+    /// An image has no alt text, so screen readers cannot describe it
+    MissingAltText(String),
+    /// A document information entry refers to a YAML key missing from the front matter
+    MissingDocInfoKey {
+        /// The PDF document information entry name (for example, `Author`)
+        doc_info_entry: String,
+        /// The YAML key the entry is populated from (for example, `author`)
+        yaml_entry: String,
+    },
+}
+
+impl DiagnosticKind {
+    /// Returns the severity for this kind of diagnostic
+    pub fn severity(&self) -> DiagnosticSeverity {
+        match self {
+            DiagnosticKind::FileNotFound
+            | DiagnosticKind::MissingFrontMatter
+            | DiagnosticKind::UnterminatedFrontMatter(_)
+            | DiagnosticKind::InvalidFrontMatter(_)
+            | DiagnosticKind::InvalidDirectoryConfig(_)
+            | DiagnosticKind::MissingRequiredKey(_) => DiagnosticSeverity::Error,
+            _ => DiagnosticSeverity::Warning,
+        }
+    }
+}
+
+/// Implements fmt::Display for DiagnosticKind, giving a human readable description of the problem.
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticKind::FileNotFound => write!(f, "file not found"),
+            DiagnosticKind::MissingFrontMatter => write!(f, "no YAML Front Matter found"),
+            DiagnosticKind::UnterminatedFrontMatter(line_number) => write!(
+                f,
+                "the YAML Front Matter opened on line {} is not closed with a `---` line",
+                line_number
+            ),
+            DiagnosticKind::InvalidFrontMatter(message) => {
+                write!(f, "invalid YAML Front Matter: {}", message)
+            }
+            DiagnosticKind::InvalidDirectoryConfig(message) => {
+                write!(f, "invalid directory configuration: {}", message)
+            }
+            DiagnosticKind::MissingRequiredKey(key) => {
+                write!(f, "required front matter key {} is missing", key)
+            }
+            DiagnosticKind::UnknownPlaceholder(key) => {
+                write!(f, "placeholder {{{{{}}}}} has no front matter value", key)
+            }
+            DiagnosticKind::BrokenLinkPath(path) => write!(f, "linked file {} not found", path),
+            DiagnosticKind::UnresolvedImage(path) => write!(f, "image {} not found", path),
+            DiagnosticKind::MissingAltText(path) => write!(f, "image {} has no alt text", path),
+            DiagnosticKind::MissingDocInfoKey {
+                doc_info_entry,
+                yaml_entry,
+            } => write!(
+                f,
+                "document info entry {} refers to missing front matter key {}",
+                doc_info_entry, yaml_entry
+            ),
+        }
+    }
+}
+
+/// Struct holding all the diagnostics found for a single source document
+#[derive(Clone, Debug)]
+pub struct SourceDiagnostics {
+    /// Path to the source document that was validated
+    pub source_file: PathBuf,
+    /// The problems found in the source document (empty if none were found)
+    pub diagnostics: Vec<DiagnosticKind>,
+}
+
+impl SourceDiagnostics {
+    /// Returns `true` if any of the diagnostics would prevent the document from being generated
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.severity() == DiagnosticSeverity::Error)
+    }
+
+    /// Returns `true` if no problems were found
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
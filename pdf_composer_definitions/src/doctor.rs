@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// Enum representing the outcome of a single environment check
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The check passed
+    Pass,
+    /// The check found something worth knowing about, but PDFs can still be generated
+    Warn,
+    /// The check failed and PDF generation will not work
+    Fail,
+}
+
+/// Implements fmt::Display for CheckStatus, converting enum variants to short labels.
+impl fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "ok"),
+            CheckStatus::Warn => write!(f, "warn"),
+            CheckStatus::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// Struct representing the result of a single environment check
+#[derive(Clone, Debug)]
+pub struct DoctorCheck {
+    /// Short name of what was checked (for example, `Chromium`)
+    pub name: String,
+    /// The outcome of the check
+    pub status: CheckStatus,
+    /// Details about the outcome, such as the path found or the error encountered
+    pub detail: String,
+}
+
+/// Struct holding the results of all the environment checks run by `doctor`
+#[derive(Clone, Debug, Default)]
+pub struct DoctorReport {
+    /// The individual checks, in the order they were run
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    /// Returns `true` if none of the checks failed
+    pub fn is_ok(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.status != CheckStatus::Fail)
+    }
+}
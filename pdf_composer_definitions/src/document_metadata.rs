@@ -0,0 +1,16 @@
+use std::collections::BTreeMap;
+
+/// Struct representing the metadata read back from an existing PDF document
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentMetadata {
+    /// The PDF specification version in the file header (for example, `1.7`)
+    pub version: String,
+    /// The number of pages in the document
+    pub page_count: usize,
+    /// The document information dictionary entries (for example, `Title` → `My document`), decoded to text
+    pub info: BTreeMap<String, String>,
+    /// The natural language of the document from the `/Lang` catalog entry, if set
+    pub language: Option<String>,
+    /// The named destinations (link targets) of the document, with the page each is on (starting at `1`)
+    pub named_destinations: BTreeMap<String, u32>,
+}
@@ -84,3 +84,33 @@ impl GetCssName for FontsStandard {
         )
     }
 }
+
+/// Trait to return the PDF base font name of the standard font passed in
+pub trait GetPdfName {
+    /// Method to get (return) the PostScript name used for the font in PDF font dictionaries
+    /// (for example, `Times-Roman`) from a font name in the FontsStandard enum
+    fn get_pdf_name(&self) -> &'static str;
+}
+
+impl GetPdfName for FontsStandard {
+    /// Implements the `get_pdf_name` trait for `FontsStandard`,
+    /// converting enum variants to the names of the standard 14 fonts
+    fn get_pdf_name(&self) -> &'static str {
+        match self {
+            FontsStandard::Courier => "Courier",
+            FontsStandard::CourierBold => "Courier-Bold",
+            FontsStandard::CourierBoldOblique => "Courier-BoldOblique",
+            FontsStandard::CourierOblique => "Courier-Oblique",
+            FontsStandard::Helvetica => "Helvetica",
+            FontsStandard::HelveticaBold => "Helvetica-Bold",
+            FontsStandard::HelveticaBoldOblique => "Helvetica-BoldOblique",
+            FontsStandard::HelveticaOblique => "Helvetica-Oblique",
+            FontsStandard::Symbol => "Symbol",
+            FontsStandard::TimesBold => "Times-Bold",
+            FontsStandard::TimesBoldItalic => "Times-BoldItalic",
+            FontsStandard::TimesItalic => "Times-Italic",
+            FontsStandard::TimesRoman => "Times-Roman",
+            FontsStandard::ZapfDingbats => "ZapfDingbats",
+        }
+    }
+}
@@ -0,0 +1,21 @@
+/// Enum representing what happens to a document whose YAML Front Matter is missing a required key
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MissingKeysPolicy {
+    /// Report the missing keys and still generate the PDF
+    Report,
+    /// Report the missing keys and skip generating the PDF
+    Skip,
+}
+
+/// Enum representing what happens to a `{{key}}` placeholder that has no YAML Front Matter value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaceholderPolicy {
+    /// Leave the placeholder in the generated document as it is written
+    LeaveAsIs,
+    /// Remove the placeholder from the generated document
+    Remove,
+    /// Report the placeholder and leave it in the generated document
+    Warn,
+    /// Report the placeholder and skip generating the document
+    Error,
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+
+use crate::upload::Url;
+
+/// Enum representing the outcome of a single source file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DocumentStatus {
+    /// Generated in every output format
+    #[default]
+    Generated,
+    /// Not generated on purpose: missing required front matter keys or placeholder values, a skipped manifest entry, or an earlier failure with fail fast (`set_fail_fast`)
+    Skipped,
+    /// Not generated because of an error: file not found, invalid front matter, a build (browser) or post-process error
+    Failed,
+}
+
+/// Struct representing what happened to a single source file during generation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedDocument {
+    /// Path to the source document
+    pub source_file: PathBuf,
+    /// Path to the generated PDF (or HTML document), or `None` if nothing was generated (file not found, invalid front matter etc)
+    pub output_file: Option<PathBuf>,
+    /// Paths to the documents generated for the other output formats (`add_output`), in the order the formats were added
+    pub additional_output_files: Vec<PathBuf>,
+    /// The number of pages of the generated PDF, or `None` if no PDF was generated
+    pub page_count: Option<u32>,
+    /// Whether the document was generated, skipped or failed
+    pub status: DocumentStatus,
+    /// Why the document (or the document of one of its output formats) was not generated, or `None` if every document was generated
+    pub error: Option<String>,
+}
+
+/// Struct representing where a source file (chapter) ended up in a merged PDF
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergedChapter {
+    /// Path to the source document
+    pub source_file: PathBuf,
+    /// The physical page of the merged PDF the chapter starts on (starting at `1`)
+    pub first_page: u32,
+    /// The number of pages in the chapter
+    pub page_count: u32,
+    /// The page number (label) shown on the first page of the chapter
+    pub first_page_label: String,
+}
+
+/// Struct representing a generated file published by the uploader (`set_uploader`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadedFile {
+    /// Path to the generated file
+    pub file: PathBuf,
+    /// The URL the file was published at
+    pub url: Url,
+}
+
+/// Struct representing the outcome of a `generate_pdfs` run
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GenerationReport {
+    /// Every source file, in source order (`set_source_ordering`)
+    pub documents: Vec<GeneratedDocument>,
+    /// Path to the merged PDF (or HTML document), when generating in merge mode
+    pub merged_file: Option<PathBuf>,
+    /// Paths to the merged documents of the other output formats (`add_output`), in the order the formats were added
+    pub additional_merged_files: Vec<PathBuf>,
+    /// The page mapping of each chapter in the merged PDF (empty when not in merge mode, or for HTML output)
+    pub chapters: Vec<MergedChapter>,
+    /// The generated files published by the uploader, in the order they were generated (empty without an uploader)
+    pub uploads: Vec<UploadedFile>,
+    /// Paths to the generated files the uploader failed to publish
+    pub failed_uploads: Vec<PathBuf>,
+}
+
+impl GenerationReport {
+    /// Returns the number of source files a PDF was generated for
+    pub fn generated_count(&self) -> usize {
+        self.documents
+            .iter()
+            .filter(|document| document.output_file.is_some())
+            .count()
+    }
+
+    /// Returns the number of source files generated in every output format
+    pub fn succeeded_count(&self) -> usize {
+        self.count(DocumentStatus::Generated)
+    }
+
+    /// Returns the number of source files skipped
+    pub fn skipped_count(&self) -> usize {
+        self.count(DocumentStatus::Skipped)
+    }
+
+    /// Returns the number of source files that failed
+    pub fn failed_count(&self) -> usize {
+        self.count(DocumentStatus::Failed)
+    }
+
+    /// Returns the documents that failed, in source order
+    pub fn failed_documents(&self) -> Vec<&GeneratedDocument> {
+        self.documents
+            .iter()
+            .filter(|document| document.status == DocumentStatus::Failed)
+            .collect()
+    }
+
+    /// Returns the documents that were skipped, in source order
+    pub fn skipped_documents(&self) -> Vec<&GeneratedDocument> {
+        self.documents
+            .iter()
+            .filter(|document| document.status == DocumentStatus::Skipped)
+            .collect()
+    }
+
+    /// Returns the number of source files with the given status
+    fn count(&self, status: DocumentStatus) -> usize {
+        self.documents
+            .iter()
+            .filter(|document| document.status == status)
+            .count()
+    }
+}
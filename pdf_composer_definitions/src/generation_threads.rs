@@ -0,0 +1,24 @@
+use rayon::ThreadPool;
+use std::sync::Arc;
+
+/// Enum representing the threads the documents are generated on, in parallel
+#[derive(Clone, Debug, Default)]
+pub enum GenerationThreads {
+    /// The global rayon thread pool (one thread per CPU, shared with the rest of the application)
+    #[default]
+    Global,
+    /// A thread pool of its own with this many threads, built for each generation (`0` for one thread per CPU)
+    Count(usize),
+    /// A rayon thread pool supplied by the application, such as the pool a server already uses for background work
+    Pool(Arc<ThreadPool>),
+}
+
+/// Enum representing whether the documents are generated in parallel or one after another
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProcessingMode {
+    /// Generate the documents in parallel, on the `GenerationThreads`
+    #[default]
+    Parallel,
+    /// Generate the documents one after another, in order, on the calling thread (one Chromium instance at a time, and console output in order)
+    Sequential,
+}
@@ -0,0 +1,8 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Arc;
+
+/// A function rewriting the HTML body of each document after the markdown is converted to HTML
+/// (for example, wrapping tables in containers or adding classes) and returning the new HTML
+pub type HtmlTransform = Arc<dyn Fn(String) -> String + Send + Sync>;
@@ -0,0 +1,11 @@
+use std::path::PathBuf;
+
+/// Enum representing the ICC colour profile of an output intent: the colour space the PDF is meant
+/// to be printed (or shown) in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IccProfile {
+    /// The sRGB colour space (IEC 61966-2.1), the colour space Chromium draws in
+    SRGB,
+    /// An ICC profile file, such as the CMYK profile of the printing press (a FOGRA or GRACoL profile from the print shop)
+    FromFile(PathBuf),
+}
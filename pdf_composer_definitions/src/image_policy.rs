@@ -0,0 +1,24 @@
+/// Struct representing how the raster images embedded in the PDFs are treated after printing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImagePolicy {
+    /// The highest resolution (dots per inch at the size an image is shown on the page) images are kept at
+    pub max_dpi: u32,
+    /// Resample images above `max_dpi` down to `max_dpi` (`false` leaves their resolution as it is)
+    pub downsample: bool,
+    /// Recompress JPEG images larger than 32 KB at this quality, from 1 (smallest) to 100 (best) (`None` keeps them as they are; downsampled JPEG images are written at quality 85)
+    pub jpeg_quality: Option<u8>,
+    /// Also convert losslessly compressed images (such as PNG images) larger than 32 KB to JPEG at `jpeg_quality`, which suits photos but not screenshots or diagrams
+    pub convert_lossless: bool,
+}
+
+/// Implements Default for ImagePolicy: images are downsampled to 300 DPI, the usual resolution for print, and not recompressed.
+impl Default for ImagePolicy {
+    fn default() -> Self {
+        ImagePolicy {
+            max_dpi: 300,
+            downsample: true,
+            jpeg_quality: None,
+            convert_lossless: false,
+        }
+    }
+}
@@ -7,15 +7,96 @@
 //! It includes modules for handling constants, fonts, output directories, page properties,
 //! PDF composition, document entries, and valid PDF versions.
 
+/// Module defining the files embedded in the generated PDFs as attachments
+pub mod attachments;
+
+/// Module defining the root (a directory or URL) relative links and images are resolved against
+pub mod asset_base;
+
+/// Module defining the benchmark (`bench`) report
+pub mod bench;
+
+/// Module defining what happens to lines of code blocks that are too long for the page
+pub mod code_wrap;
+
+/// Module defining the options and result of comparing two PDF documents
+pub mod compare;
+
+/// Module defining the configuration file (`pdf_composer.toml`) settings
+pub mod config;
+
+/// Module defining the summary of the settings of a composer
+pub mod configuration_summary;
+
+/// Module defining the standards (such as PDF/X-4) the generated PDFs can conform to
+pub mod conformance;
+
+/// Module defining how much is printed to the console (quiet, normal or verbose)
+pub mod console_output;
+
 /// Module containing constant values used throughout PDF Composer
 pub mod consts;
 
+/// Module defining how the date placeholders are written (time zone and language)
+pub mod date_options;
+
+/// Module defining the named destinations added to PDF documents
+pub mod destinations;
+
+/// Module defining the diagnostics reported when validating source documents
+pub mod diagnostics;
+
+/// Module defining the metadata read back from existing PDF documents
+pub mod document_metadata;
+
+/// Module defining the environment preflight (doctor) report
+pub mod doctor;
+
+/// Module defining the report returned after generating PDFs
+pub mod generation_report;
+
+/// Module defining the threads the documents are generated on (and whether in parallel at all)
+pub mod generation_threads;
+
 /// Module handling font-related functionality
 pub mod fonts;
 
+/// Module defining the YAML Front Matter requirements for source documents
+pub mod front_matter;
+
+/// Module defining the function rewriting the HTML of each document
+pub mod html_transform;
+
+/// Module defining the ICC colour profiles of output intents
+pub mod icc_profile;
+
+/// Module defining how the raster images embedded in PDFs are treated (downsampling)
+pub mod image_policy;
+
+/// Module defining how dates and numbers are written in a language and region (locale)
+pub mod locale;
+
+/// Module defining what happens when a PDF cannot be saved because the file is locked
+pub mod locked_file;
+
+/// Module defining the options for merging PDF documents (and single-PDF merge mode)
+pub mod merge;
+
+/// Module defining where the documents open (a page or destination, and the zoom)
+pub mod open_action;
+
 /// Module handling the output directory (as a str or path)
 pub mod output_directory;
 
+/// Module defining the format of the generated documents (PDF, HTML, EPUB or text)
+pub mod output_format;
+
+/// Module defining the function naming the generated documents
+pub mod output_name;
+
+/// Module defining the page numbering styles and sections
+pub mod page_numbering;
+
 /// Module defining and handling page properties (such as size and orientation)
 pub mod page_properties;
 
@@ -25,5 +106,38 @@ pub mod pdf_composer;
 /// Module defining the structure for PDF document entries (key/value pairs)
 pub mod pdf_doc_entry;
 
-/// Module to re-export the PDF version enum
+/// Module defining the warm headless Chromium kept alive between `generate_pdfs` calls
+pub mod persistent_browser;
+
+/// Module defining the progress events of a generation run (written as NDJSON)
+pub mod progress;
+
+/// Module defining the running headers (current section title) options
+pub mod running_headers;
+
+/// Module to re-export the PDF version enum (and the features that need a minimum version)
 pub mod pdf_version;
+
+/// Module defining the shortcodes registered with `register_shortcode`
+pub mod shortcodes;
+
+/// Module defining the unsigned signature fields added to the generated PDFs
+pub mod signature_fields;
+
+/// Module defining the order the source files are generated in
+pub mod source_ordering;
+
+/// Module defining the text direction (left-to-right or right-to-left)
+pub mod text_direction;
+
+/// Module defining the text justification and hyphenation options
+pub mod text_justification;
+
+/// Module defining the uploader the generated files are published with
+pub mod upload;
+
+/// Module defining how PDF viewers show the documents when they are opened (page layout, panels)
+pub mod viewer_preferences;
+
+/// Module defining the resolver for `[[wiki link]]` targets
+pub mod wiki_links;
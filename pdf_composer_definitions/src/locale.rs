@@ -0,0 +1,60 @@
+use std::str::FromStr;
+
+use crate::date_options::DateLocale;
+
+/// Struct representing how dates and numbers are written in a language and region (see `set_locale`)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Locale {
+    /// The language tag the locale was set with, such as `de-DE`
+    pub tag: String,
+    /// The language the month and day names are written in
+    pub date_locale: DateLocale,
+    /// The format of dates, such as `%d.%m.%Y` (`{{today}}` and `{{date key}}`)
+    pub date_format: &'static str,
+    /// The format of dates with a time, such as `%d.%m.%Y %H:%M` (`{{now}}`)
+    pub date_time_format: &'static str,
+    /// The character between the whole and fractional parts of a number, such as `,`
+    pub decimal_separator: char,
+    /// The character between groups of thousands, such as `.`
+    pub group_separator: char,
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// Parses a language tag (such as `de`, `de-DE` or `en_US`, in any case) by its language and region
+    fn from_str(tag: &str) -> Result<Self, Self::Err> {
+        let date_locale = tag
+            .parse::<DateLocale>()
+            .map_err(|_| format!("unsupported locale {}", tag))?;
+        let region = tag
+            .trim()
+            .split(['-', '_'])
+            .nth(1)
+            .unwrap_or_default()
+            .to_uppercase();
+        let (date_format, date_time_format, decimal_separator, group_separator) =
+            match (date_locale, region.as_str()) {
+                // American English by default, as in CLDR
+                (DateLocale::En, "" | "US") => ("%m/%d/%Y", "%m/%d/%Y %I:%M %p", '.', ','),
+                (DateLocale::En, _) => ("%d/%m/%Y", "%d/%m/%Y %H:%M", '.', ','),
+                (DateLocale::De, "CH") => ("%d.%m.%Y", "%d.%m.%Y %H:%M", '.', '’'),
+                (DateLocale::De, _) => ("%d.%m.%Y", "%d.%m.%Y %H:%M", ',', '.'),
+                // A narrow no-break space between groups of thousands
+                (DateLocale::Fr, _) => ("%d/%m/%Y", "%d/%m/%Y %H:%M", ',', '\u{202F}'),
+                (DateLocale::Es | DateLocale::It, _) => ("%d/%m/%Y", "%d/%m/%Y %H:%M", ',', '.'),
+                (DateLocale::Nl, _) => ("%d-%m-%Y", "%d-%m-%Y %H:%M", ',', '.'),
+                // A no-break space between groups of thousands in Portugal
+                (DateLocale::Pt, "PT") => ("%d/%m/%Y", "%d/%m/%Y %H:%M", ',', '\u{A0}'),
+                (DateLocale::Pt, _) => ("%d/%m/%Y", "%d/%m/%Y %H:%M", ',', '.'),
+            };
+        Ok(Locale {
+            tag: tag.trim().to_string(),
+            date_locale,
+            date_format,
+            date_time_format,
+            decimal_separator,
+            group_separator,
+        })
+    }
+}
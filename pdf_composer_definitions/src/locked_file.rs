@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+/// Enum representing what happens when a PDF cannot be saved because the file is locked (open in a PDF viewer on Windows, for example)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LockedFilePolicy {
+    /// Fail to save the PDF, reporting that the file is open by another process
+    #[default]
+    Fail,
+    /// Try again a number of times, waiting in between, before failing (time to close the viewer)
+    Retry {
+        /// How many more times saving is tried
+        attempts: u32,
+        /// How long to wait before each attempt
+        delay: Duration,
+    },
+    /// Save the PDF under the first free alternate name instead, such as `report (1).pdf`
+    AlternateName,
+}
+
+impl LockedFilePolicy {
+    /// Returns a policy of retrying `attempts` times, `delay` apart.
+    pub fn retry(attempts: u32, delay: Duration) -> Self {
+        LockedFilePolicy::Retry { attempts, delay }
+    }
+}
@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::output_format::OutputFormat;
+use crate::pdf_version::PDFVersion;
+
+/// Enum representing which input document a merged PDF takes its document information from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MergeMetadataSource {
+    /// Use the document information of the first input document
+    #[default]
+    First,
+    /// Use the document information of the last input document
+    Last,
+    /// Start with empty document information (only `doc_info_overrides` are written)
+    Empty,
+}
+
+/// Struct representing the options used when merging PDF documents
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// Which input document the merged document information is taken from
+    pub metadata_source: MergeMetadataSource,
+    /// Document information entries (for example, `Title` → `Complete guide`) written on top of the selected metadata (`Trapped` as a name and `CreationDate` and `ModDate` as dates)
+    pub doc_info_overrides: BTreeMap<String, String>,
+    /// Add a bookmark for the first page of each input document, titled with its `Title` entry (or file name)
+    pub bookmarks: bool,
+    /// The PDF version of the merged document. `None` uses the highest version of the input documents
+    pub pdf_version: Option<PDFVersion>,
+}
+
+/// Struct representing single-PDF (merge) mode: every source file is generated as a chapter of one merged PDF
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MergeMode {
+    /// The file name of the merged PDF, written to the output directory (for example, `book.pdf`)
+    pub file_name: String,
+    /// Restart the page numbers at 1 at the start of each source file (chapter)
+    pub restart_page_numbers: bool,
+    /// Keep the PDF generated for each source file as well as the merged PDF
+    pub keep_chapter_pdfs: bool,
+    /// The options used to merge the chapters (metadata, bookmarks and PDF version)
+    pub options: MergeOptions,
+}
+
+impl MergeMode {
+    /// Creates a merge mode writing to `file_name`, with continuous page numbers and the chapter PDFs removed
+    pub fn new(file_name: &str) -> Self {
+        Self {
+            file_name: file_name.to_string(),
+            restart_page_numbers: false,
+            keep_chapter_pdfs: false,
+            options: MergeOptions::default(),
+        }
+    }
+
+    /// Returns the file name of the merged document in an output format (`book.pdf` is written as `book.html` for HTML output)
+    pub fn output_file_name(&self, output_format: OutputFormat) -> String {
+        match output_format {
+            OutputFormat::Pdf => self.file_name.to_string(),
+            output_format => Path::new(&self.file_name)
+                .with_extension(output_format.extension())
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+}
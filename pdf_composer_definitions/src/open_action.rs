@@ -0,0 +1,26 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// Enum representing the zoom a page is shown at when the document is opened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Zoom {
+    /// The whole page fits in the window
+    Fit,
+    /// The width of the page fits the window
+    FitWidth,
+    /// The height of the page fits the window
+    FitHeight,
+    /// A zoom percentage (such as `100` for actual size)
+    Percent(u32),
+    /// The zoom the viewer was already at
+    Inherit,
+}
+
+/// Enum representing where a document opens, instead of the first page at the viewer's default zoom
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OpenAction {
+    /// Open at a page (starting at `1`), at the top of the page
+    Page(u32, Zoom),
+    /// Open at a named destination, such as a heading id (`contents` for `# Contents {#contents}`)
+    Destination(String, Zoom),
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Enum representing the format of the documents generated from the source files
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// PDF documents, printed by Chromium
+    #[default]
+    Pdf,
+    /// Self-contained HTML documents (styles and local images inlined), without Chromium
+    Html,
+    /// EPUB 3 e-books, without Chromium (`epub` feature)
+    #[cfg(feature = "epub")]
+    Epub,
+    /// The content with the placeholders filled in, as plain text or normalized markdown, without Chromium
+    Text(TextFormat),
+}
+
+/// Enum representing the kind of text written for `OutputFormat::Text`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextFormat {
+    /// Plain text (`.txt`), without any markup
+    #[default]
+    Plain,
+    /// Normalized markdown (`.md`)
+    Markdown,
+}
+
+impl OutputFormat {
+    /// Returns the file extension (without the `.`) of the generated documents
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Html => "html",
+            #[cfg(feature = "epub")]
+            OutputFormat::Epub => "epub",
+            OutputFormat::Text(TextFormat::Plain) => "txt",
+            OutputFormat::Text(TextFormat::Markdown) => "md",
+        }
+    }
+}
+
+/// Implements fmt::Display for OutputFormat, showing the name of the format.
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Pdf => write!(f, "PDF"),
+            OutputFormat::Html => write!(f, "HTML"),
+            #[cfg(feature = "epub")]
+            OutputFormat::Epub => write!(f, "EPUB"),
+            OutputFormat::Text(TextFormat::Plain) => write!(f, "text"),
+            OutputFormat::Text(TextFormat::Markdown) => write!(f, "Markdown"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    /// Parses `pdf`, `html`, `epub`, `text` (or `txt`) or `markdown` (or `md`), in any case
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "pdf" => Ok(OutputFormat::Pdf),
+            "html" => Ok(OutputFormat::Html),
+            #[cfg(feature = "epub")]
+            "epub" => Ok(OutputFormat::Epub),
+            "text" | "txt" => Ok(OutputFormat::Text(TextFormat::Plain)),
+            "markdown" | "md" => Ok(OutputFormat::Text(TextFormat::Markdown)),
+            _ => Err(format!("unknown output format {}", format)),
+        }
+    }
+}
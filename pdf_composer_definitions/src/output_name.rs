@@ -0,0 +1,8 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{path::Path, sync::Arc};
+
+/// A function naming the documents generated from a source file: given the path of the source
+/// file, it returns the file name to use, without the extension of the output format
+pub type OutputNamer = Arc<dyn Fn(&Path) -> String + Send + Sync>;
@@ -0,0 +1,220 @@
+/// Enum representing how page numbers are written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PageNumberStyle {
+    /// Decimal numbers (1, 2, 3)
+    #[default]
+    Decimal,
+    /// Lowercase Roman numerals (i, ii, iii)
+    LowerRoman,
+    /// Uppercase Roman numerals (I, II, III)
+    UpperRoman,
+    /// Lowercase letters (a, b, c ... z, aa, bb)
+    LowerAlpha,
+    /// Uppercase letters (A, B, C ... Z, AA, BB)
+    UpperAlpha,
+}
+
+impl PageNumberStyle {
+    /// Returns the page number written in this style (for example, `4` is `iv` in `LowerRoman`)
+    pub fn format(&self, number: u32) -> String {
+        match self {
+            PageNumberStyle::Decimal => number.to_string(),
+            PageNumberStyle::LowerRoman => to_roman(number).to_lowercase(),
+            PageNumberStyle::UpperRoman => to_roman(number),
+            PageNumberStyle::LowerAlpha => to_alpha(number).to_lowercase(),
+            PageNumberStyle::UpperAlpha => to_alpha(number),
+        }
+    }
+
+    /// Returns the `/S` (numbering style) name used for this style in PDF page labels
+    pub fn pdf_name(&self) -> &'static str {
+        match self {
+            PageNumberStyle::Decimal => "D",
+            PageNumberStyle::LowerRoman => "r",
+            PageNumberStyle::UpperRoman => "R",
+            PageNumberStyle::LowerAlpha => "a",
+            PageNumberStyle::UpperAlpha => "A",
+        }
+    }
+}
+
+/// Writes a number as uppercase Roman numerals (`0` is written as an empty string)
+fn to_roman(mut number: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            roman.push_str(numeral);
+            number -= value;
+        }
+    }
+    roman
+}
+
+/// Writes a number as uppercase letters the way PDF page labels do: A to Z, then AA to ZZ, AAA to ZZZ etc
+fn to_alpha(number: u32) -> String {
+    if number == 0 {
+        return String::new();
+    }
+    let letter = char::from(b'A' + ((number - 1) % 26) as u8);
+    letter.to_string().repeat(((number - 1) / 26 + 1) as usize)
+}
+
+/// Struct representing a run of pages numbered in the same style
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageNumberSection {
+    /// The physical page the section starts on (starting at `1`)
+    pub start_page: u32,
+    /// How the page numbers in the section are written
+    pub style: PageNumberStyle,
+    /// The number of the first page in the section
+    pub first_number: u32,
+    /// Text put in front of each page number (for example, `A-` for `A-1`, `A-2`)
+    pub prefix: String,
+}
+
+impl PageNumberSection {
+    /// Creates a section starting on physical page `start_page`, numbered from `1` in `style`
+    pub fn new(start_page: u32, style: PageNumberStyle) -> Self {
+        Self {
+            start_page,
+            style,
+            first_number: 1,
+            prefix: String::new(),
+        }
+    }
+}
+
+/// Struct representing the page label of a run of pages: what PDF viewers show as the page number
+/// (such as `Cover`, `i` to `iv` or `A-1`), whatever the physical page number
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageLabelRange {
+    /// The physical page the range starts on (starting at `1`)
+    pub start_page: u32,
+    /// How the page numbers in the range are written (`None` for the prefix alone, such as `Cover`)
+    pub style: Option<PageNumberStyle>,
+    /// The number of the first page in the range
+    pub first_number: u32,
+    /// Text put in front of each page number, or the whole label if there is no style
+    pub prefix: String,
+}
+
+impl PageLabelRange {
+    /// Creates a range starting on physical page `start_page`, numbered from `1` in `style`
+    pub fn numbered(start_page: u32, style: PageNumberStyle) -> Self {
+        Self {
+            start_page,
+            style: Some(style),
+            first_number: 1,
+            prefix: String::new(),
+        }
+    }
+
+    /// Creates a range starting on physical page `start_page` labelled with `text` alone (such as
+    /// `Cover`), without a page number
+    pub fn text(start_page: u32, text: &str) -> Self {
+        Self {
+            start_page,
+            style: None,
+            first_number: 1,
+            prefix: text.to_string(),
+        }
+    }
+
+    /// Returns the label of a physical page (starting at `1`) from the ranges, ordered by the page
+    /// they start on. Pages before the first range are labelled with their physical page number
+    pub fn label(ranges: &[PageLabelRange], page_number: u32) -> String {
+        match ranges
+            .iter()
+            .rev()
+            .find(|range| range.start_page <= page_number)
+        {
+            Some(range) => match range.style {
+                Some(style) => format!(
+                    "{}{}",
+                    range.prefix,
+                    style.format(range.first_number + page_number - range.start_page)
+                ),
+                None => range.prefix.clone(),
+            },
+            None => page_number.to_string(),
+        }
+    }
+}
+
+impl From<&PageNumberSection> for PageLabelRange {
+    /// A page numbering section is labelled the way its page numbers are drawn
+    fn from(section: &PageNumberSection) -> Self {
+        Self {
+            start_page: section.start_page,
+            style: Some(section.style),
+            first_number: section.first_number,
+            prefix: section.prefix.clone(),
+        }
+    }
+}
+
+/// Struct representing how the pages of a document are numbered
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageNumbering {
+    /// The numbering sections, ordered by the page they start on
+    pub sections: Vec<PageNumberSection>,
+    /// The text drawn at the bottom of each page, with `{page}` replaced by the page number and `{pages}` by the number of pages.
+    /// `None` only sets the page numbers shown by PDF viewers
+    pub footer_template: Option<String>,
+}
+
+impl PageNumbering {
+    /// Creates page numbering from the given sections, with the page number drawn at the bottom of each page
+    pub fn sections<I: IntoIterator<Item = PageNumberSection>>(sections: I) -> Self {
+        let mut sections: Vec<PageNumberSection> = sections.into_iter().collect();
+        sections.sort_by_key(|section| section.start_page);
+        Self {
+            sections,
+            footer_template: Some("{page}".to_string()),
+        }
+    }
+
+    /// Creates page numbering with the first `front_matter_pages` pages (cover, contents, preface)
+    /// numbered i, ii, iii and the pages after them numbered from 1
+    pub fn front_matter(front_matter_pages: u32) -> Self {
+        Self::sections([
+            PageNumberSection::new(1, PageNumberStyle::LowerRoman),
+            PageNumberSection::new(front_matter_pages + 1, PageNumberStyle::Decimal),
+        ])
+    }
+
+    /// Returns the page number (label) of a physical page (starting at `1`).
+    /// Pages before the first section are numbered with their physical page number
+    pub fn label(&self, page_number: u32) -> String {
+        match self
+            .sections
+            .iter()
+            .rev()
+            .find(|section| section.start_page <= page_number)
+        {
+            Some(section) => format!(
+                "{}{}",
+                section.prefix,
+                section
+                    .style
+                    .format(section.first_number + page_number - section.start_page)
+            ),
+            None => page_number.to_string(),
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 /// Enum representing paper orientation
 #[derive(Clone, Copy, Debug)]
 pub enum PaperOrientation {
@@ -7,12 +9,26 @@ pub enum PaperOrientation {
     Portrait,
 }
 
+impl FromStr for PaperOrientation {
+    type Err = String;
+
+    /// Parses `landscape` or `portrait` (in any case)
+    fn from_str(orientation: &str) -> Result<Self, Self::Err> {
+        match orientation.trim().to_lowercase().as_str() {
+            "landscape" => Ok(PaperOrientation::Landscape),
+            "portrait" => Ok(PaperOrientation::Portrait),
+            _ => Err(format!("unknown paper orientation {}", orientation)),
+        }
+    }
+}
+
 /// Enum representing different paper sizes
 /// See the following for more information:
 /// * A Series Paper Sizes (ISO 216): <https://www.papersizes.org/a-paper-sizes.htm>
 /// * B Series Paper Sizes (ISO 216): <https://www.papersizes.org/b-paper-sizes.htm>
 /// * US Paper Sizes: <https://www.papersizes.org/us-paper-sizes.htm>
 /// * Japanese Paper Sizes: <https://www.papersizes.org/japanese-sizes.htm>
+///
 /// Paper sizes from ISO As and Bs, US Letter and Japanese sizes
 #[derive(Clone, Copy, Debug)]
 pub enum PaperSize {
@@ -96,6 +112,62 @@ pub enum PaperSize {
     JISB10,
 }
 
+impl FromStr for PaperSize {
+    type Err = String;
+
+    /// Parses a paper size name as written in the enum (`A4`, `Letter`, `JISB5`), ignoring case,
+    /// spaces, hyphens and underscores (so `half-letter` and `JIS B5` work too)
+    fn from_str(paper_size: &str) -> Result<Self, Self::Err> {
+        let name: String = paper_size
+            .chars()
+            .filter(|character| !matches!(character, ' ' | '-' | '_'))
+            .collect::<String>()
+            .to_uppercase();
+        match name.as_str() {
+            "A0" => Ok(PaperSize::A0),
+            "A1" => Ok(PaperSize::A1),
+            "A2" => Ok(PaperSize::A2),
+            "A3" => Ok(PaperSize::A3),
+            "A4" => Ok(PaperSize::A4),
+            "A5" => Ok(PaperSize::A5),
+            "A6" => Ok(PaperSize::A6),
+            "A7" => Ok(PaperSize::A7),
+            "A8" => Ok(PaperSize::A8),
+            "A9" => Ok(PaperSize::A9),
+            "A10" => Ok(PaperSize::A10),
+            "B0" => Ok(PaperSize::B0),
+            "B1" => Ok(PaperSize::B1),
+            "B2" => Ok(PaperSize::B2),
+            "B3" => Ok(PaperSize::B3),
+            "B4" => Ok(PaperSize::B4),
+            "B5" => Ok(PaperSize::B5),
+            "B6" => Ok(PaperSize::B6),
+            "B7" => Ok(PaperSize::B7),
+            "B8" => Ok(PaperSize::B8),
+            "B9" => Ok(PaperSize::B9),
+            "B10" => Ok(PaperSize::B10),
+            "HALFLETTER" => Ok(PaperSize::HalfLetter),
+            "LETTER" => Ok(PaperSize::Letter),
+            "LEGAL" => Ok(PaperSize::Legal),
+            "JUNIORLEGAL" => Ok(PaperSize::JuniorLegal),
+            "LEDGER" => Ok(PaperSize::Ledger),
+            "TABLOID" => Ok(PaperSize::Tabloid),
+            "JISB0" => Ok(PaperSize::JISB0),
+            "JISB1" => Ok(PaperSize::JISB1),
+            "JISB2" => Ok(PaperSize::JISB2),
+            "JISB3" => Ok(PaperSize::JISB3),
+            "JISB4" => Ok(PaperSize::JISB4),
+            "JISB5" => Ok(PaperSize::JISB5),
+            "JISB6" => Ok(PaperSize::JISB6),
+            "JISB7" => Ok(PaperSize::JISB7),
+            "JISB8" => Ok(PaperSize::JISB8),
+            "JISB9" => Ok(PaperSize::JISB9),
+            "JISB10" => Ok(PaperSize::JISB10),
+            _ => Err(format!("unknown paper size {}", paper_size)),
+        }
+    }
+}
+
 /// Type for the unit Headless Chrome prefers (inches) for setting paper size.
 /// The type is set here in case the units change from `f64`
 type PageUnit = f64;
@@ -1,11 +1,41 @@
 // Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use std::{collections::BTreeMap, fmt, path::PathBuf};
+use std::{collections::BTreeMap, fmt, path::PathBuf, sync::Arc};
 
+use crate::asset_base::PathOrUrl;
+use crate::attachments::FileAttachment;
+use crate::code_wrap::CodeWrap;
+use crate::conformance::Conformance;
+use crate::console_output::ConsoleOutput;
+use crate::date_options::DateOptions;
 use crate::fonts::FontsStandard;
+use crate::front_matter::{MissingKeysPolicy, PlaceholderPolicy};
+use crate::generation_threads::{GenerationThreads, ProcessingMode};
+use crate::html_transform::HtmlTransform;
+use crate::icc_profile::IccProfile;
+use crate::image_policy::ImagePolicy;
+use crate::locale::Locale;
+use crate::locked_file::LockedFilePolicy;
+use crate::merge::MergeMode;
+use crate::open_action::OpenAction;
+use crate::output_format::OutputFormat;
+use crate::output_name::OutputNamer;
+use crate::page_numbering::{PageLabelRange, PageNumbering};
 use crate::page_properties::{PageMargins, PaperOrientation, PaperSize};
+use crate::pdf_doc_entry::DocInfoBranding;
 use crate::pdf_version::PDFVersion;
+use crate::persistent_browser::PersistentBrowserHandle;
+use crate::progress::ProgressWriter;
+use crate::running_headers::RunningHeaders;
+use crate::shortcodes::ShortcodeHandler;
+use crate::signature_fields::SignatureField;
+use crate::source_ordering::SourceOrdering;
+use crate::text_direction::TextDirection;
+use crate::text_justification::JustifyOptions;
+use crate::upload::Uploader;
+use crate::viewer_preferences::ViewerPreferences;
+use crate::wiki_links::WikiLinkResolver;
 
 // pub use definitions::consts::PACKAGE_NAME;
 // pub use definitions::fonts::FontsStandard;
@@ -13,39 +43,245 @@ use crate::pdf_version::PDFVersion;
 // pub use definitions::pdf_version::PDFVersion;
 
 /// PDFComposer struct represents a tool for composing PDF documents from multiple source files.
+///
+/// A configured composer is `Send + Sync`, so it can be kept in shared application state, and
+/// cheap to clone for each request. Clones share the thread pool, wiki link resolver and
+/// persistent browser of the original.
+#[derive(Clone)]
 pub struct PDFComposerStruct {
     /// Vector containing paths to the source files used for composing the PDF document.
     pub fmy_source_files: Vec<PathBuf>,
+    /// The extensions (such as `.md`) of the source files found in the directories added as source files (and of the links rewritten to the generated documents).
+    pub source_extensions: Vec<String>,
+    /// Names the generated documents after their source files (`None` for the source file name without its extension).
+    pub output_namer: Option<OutputNamer>,
     /// Path to the directory where the composed PDF document will be saved.
     pub output_directory: PathBuf,
+    /// The formats of the generated documents (PDF, self-contained HTML, EPUB or text), in order. Each document is rendered once for all of them.
+    pub output_formats: Vec<OutputFormat>,
     /// Specifies the version of the PDF format to be used.
     pub pdf_version: PDFVersion,
     /// Optional mapping of document entries, where the key represents the entry name and the value represents the content.
     pub pdf_document_entries: Option<BTreeMap<String, String>>,
+    /// The `Creator` written into the document information, instead of or after `PDF Composer` (`None` for `PDF Composer`). A `generator` front matter value overrides it per document.
+    pub creator: Option<DocInfoBranding>,
+    /// The `Producer` written into the document information, instead of or after `PDF Composer` (`None` for `PDF Composer`).
+    pub producer: Option<DocInfoBranding>,
     /// Specifies the paper size for the PDF document.
     pub paper_size: PaperSize,
     /// Specifies the orientation of the page.
     pub orientation: PaperOrientation,
     /// Set the margins for the pages
     pub margins: PageMargins,
+    /// Alternate the left and right margins on odd and even pages (margins are then top, outer, bottom, inner)
+    pub mirrored_margins: bool,
     /// Set the for the PDF document
     pub font: FontsStandard,
+    /// YAML Front Matter keys every source document must have.
+    pub required_front_matter_keys: Vec<String>,
+    /// Front matter values the documents get when they do not set them.
+    pub front_matter_defaults: BTreeMap<String, String>,
+    /// What to do with a document that is missing any of the required front matter keys.
+    pub missing_keys_policy: MissingKeysPolicy,
+    /// What to do with a `{{key}}` placeholder that has no front matter value.
+    pub placeholder_policy: PlaceholderPolicy,
+    /// The direction text is written in. Can be overridden per document with the `dir` front matter key.
+    pub text_direction: TextDirection,
+    /// Optional language tag (such as `en-GB` or `ar`). Can be overridden per document with the `lang` or `language` front matter key.
+    pub language: Option<String>,
+    /// Optional justification (and hyphenation) of the body text. Text is left aligned if `None`.
+    pub text_justification: Option<JustifyOptions>,
+    /// Generate tagged (accessible) PDFs with a structure tree, for screen readers and PDF/UA.
+    pub tagged_pdf: bool,
+    /// Put the current section title at the top of each page (`None` for no running headers).
+    pub running_headers: Option<RunningHeaders>,
+    /// How the pages are numbered (`None` for no page numbers).
+    pub page_numbering: Option<PageNumbering>,
+    /// The page labels PDF viewers show, such as `Cover` then `i` to `iv` (`None` for the labels of the page numbering, if any).
+    pub page_labels: Option<Vec<PageLabelRange>>,
+    /// Merge the generated PDFs into a single PDF (`None` for one PDF per source file).
+    pub merge_mode: Option<MergeMode>,
+    /// Collect footnotes into a notes section with this title at the end of each document (`None` to leave footnotes unprocessed).
+    pub endnotes_title: Option<String>,
+    /// Number the lines of every fenced code block.
+    pub code_line_numbers: bool,
+    /// What happens to lines of code blocks that are too long for the page (wrapped by default).
+    pub code_wrap: CodeWrap,
+    /// Resolves `[[wiki link]]` page names to paths or URLs (`None` links to the PDF generated for the page).
+    pub wiki_link_resolver: Option<WikiLinkResolver>,
+    /// The shortcodes registered by name, rendered for `{{< name >}}` (in addition to the built-in shortcodes).
+    pub shortcodes: BTreeMap<String, ShortcodeHandler>,
+    /// Rewrites the HTML body of each document before it is printed (`None` to leave it as rendered).
+    pub html_transform: Option<HtmlTransform>,
+    /// How PDF viewers show the documents when they are opened, such as the page layout and the panel shown.
+    pub viewer_preferences: ViewerPreferences,
+    /// Where the documents open, such as the contents at the width of the window (`None` for the first page at the viewer's default zoom).
+    pub open_action: Option<OpenAction>,
+    /// Put a QR code of this text (with front matter placeholders) in the footer of every page (`None` for no QR code).
+    pub qr_code_footer: Option<String>,
+    /// Draw this text (with front matter placeholders), such as `DRAFT`, diagonally across every page (`None` for no watermark).
+    pub watermark: Option<String>,
+    /// Embed the source markdown file in each PDF as an attachment.
+    pub attach_source: bool,
+    /// Embed the front matter of each document, with the placeholders resolved, in its PDF as an attachment.
+    pub attach_front_matter: bool,
+    /// Files embedded in every PDF as attachments (in addition to those in the `attachments` front matter entry of each document).
+    pub attachments: Vec<FileAttachment>,
+    /// Unsigned signature fields added to every PDF (to the merged PDF in merge mode), for signing services to sign in.
+    pub signature_fields: Vec<SignatureField>,
+    /// The URL of the time stamping authority (TSA) the PDFs get an RFC 3161 document timestamp from (`None` for no timestamp).
+    #[cfg(feature = "timestamp")]
+    pub timestamp_authority: Option<String>,
+    /// Make the PDFs conform to a standard, such as PDF/X-4 for print production (`None` for no particular standard).
+    pub conformance: Option<Conformance>,
+    /// The colour profile the PDFs are meant to be printed with, written into the catalog as an output intent (`None` for no output intent, or sRGB with a conformance that needs one).
+    pub output_intent: Option<IccProfile>,
+    /// How the raster images embedded in the PDFs are treated, such as downsampling phone photos (`None` leaves images as Chromium embeds them).
+    pub image_policy: Option<ImagePolicy>,
+    /// Make identical inputs produce identical PDFs (fixed dates and a document ID digested from the finished PDF).
+    pub deterministic: bool,
+    /// Render very large documents this many H1 sections at a time, stitching the pages together (`None` renders each document in one go).
+    pub chunked_rendering: Option<usize>,
+    /// The threads the documents are generated on (the global rayon thread pool by default).
+    pub generation_threads: GenerationThreads,
+    /// Whether the documents are generated in parallel (the default) or one after another.
+    pub processing_mode: ProcessingMode,
+    /// Stop generating documents once one fails (by default the others are still generated, and the failures summarised at the end).
+    pub fail_fast: bool,
+    /// The order the source files are generated (and merged) in (the order they were added by default).
+    pub source_ordering: SourceOrdering,
+    /// Directory the rendered HTML of each document is cached in between runs (`None` for no cache).
+    pub html_cache: Option<PathBuf>,
+    /// The warm headless Chromium the PDFs are printed with (`None` launches Chromium for each PDF).
+    pub persistent_browser: Option<PersistentBrowserHandle>,
+    /// The root relative links and images are resolved against when printing (`None` for the directory of each source file).
+    pub asset_base: Option<PathOrUrl>,
+    /// A CSS file whose styles are added after the built-in styles of every PDF, HTML document and preview (`None` for the built-in styles only).
+    pub stylesheet: Option<PathBuf>,
+    /// What happens when a PDF cannot be saved because the file is locked (failing by default).
+    pub locked_file_policy: LockedFilePolicy,
+    /// A command (such as an optimizer or validator) run on every generated PDF, with placeholders for its path (`None` runs no command).
+    pub post_process_command: Option<String>,
+    /// Publishes every generated file (the merged files in merge mode) after generation (`None` uploads nothing).
+    pub uploader: Option<Arc<dyn Uploader>>,
+    /// Environment variables that `${NAME}` references in the front matter values and markdown expand to (empty for no expansion).
+    pub env_var_allow_list: Vec<String>,
+    /// The time zone and language of the `{{today}}`, `{{now}}` and `{{build_date "format"}}` placeholders.
+    pub date_options: DateOptions,
+    /// How the `{{today}}`, `{{now}}`, `{{date key}}` and `{{number key}}` placeholders are written (`None` for ISO 8601 dates and plain numbers).
+    pub locale: Option<Locale>,
+    /// How much is printed to the console: only warnings and errors (to stderr), the progress messages (the files processed and generated), or the settings and timings too.
+    pub console_output: ConsoleOutput,
+    /// Receives a progress event (start, finish, skip or error of each file, and a summary) as a line of JSON (`None` writes no events).
+    pub progress_writer: Option<ProgressWriter>,
+    /// The profile of the configuration file (such as `draft`) applied on top of its other settings (`None` for none).
+    pub profile: Option<String>,
+    /// The names `{{#if name}}` conditional blocks are kept for, in addition to the profile (such as `internal`).
+    pub flags: Vec<String>,
 }
 
 impl fmt::Debug for PDFComposerStruct {
     /// Implements the Debug trait for the PDFComposer struct, allowing it to be formatted for debugging purposes.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("PDFComposer")
+        let mut debug = f.debug_struct("PDFComposer");
+        debug
             .field("fmy_source_files", &self.fmy_source_files)
+            .field("source_extensions", &self.source_extensions)
+            .field(
+                "output_namer",
+                &self.output_namer.as_ref().map(|_| "Fn(&Path) -> String"),
+            )
             .field("output_directory", &self.output_directory)
+            .field("output_formats", &self.output_formats)
             .field("pdf_version", &self.pdf_version)
             .field("pdf_document_entries", &self.pdf_document_entries)
+            .field("creator", &self.creator)
+            .field("producer", &self.producer)
             .field("paper_size", &self.paper_size)
             .field("orientation", &self.orientation)
             .field("margins", &&self.margins)
+            .field("mirrored_margins", &self.mirrored_margins)
             .field("font", &&self.font)
-            .finish()
+            .field(
+                "required_front_matter_keys",
+                &self.required_front_matter_keys,
+            )
+            .field("front_matter_defaults", &self.front_matter_defaults)
+            .field("missing_keys_policy", &self.missing_keys_policy)
+            .field("placeholder_policy", &self.placeholder_policy)
+            .field("text_direction", &self.text_direction)
+            .field("language", &self.language)
+            .field("text_justification", &self.text_justification)
+            .field("tagged_pdf", &self.tagged_pdf)
+            .field("running_headers", &self.running_headers)
+            .field("page_numbering", &self.page_numbering)
+            .field("page_labels", &self.page_labels)
+            .field("merge_mode", &self.merge_mode)
+            .field("endnotes_title", &self.endnotes_title)
+            .field("code_line_numbers", &self.code_line_numbers)
+            .field("code_wrap", &self.code_wrap)
+            .field(
+                "wiki_link_resolver",
+                &self
+                    .wiki_link_resolver
+                    .as_ref()
+                    .map(|_| "Fn(&str) -> String"),
+            )
+            .field("shortcodes", &self.shortcodes.keys().collect::<Vec<_>>())
+            .field(
+                "html_transform",
+                &self.html_transform.as_ref().map(|_| "Fn(String) -> String"),
+            )
+            .field("viewer_preferences", &self.viewer_preferences)
+            .field("open_action", &self.open_action)
+            .field("qr_code_footer", &self.qr_code_footer)
+            .field("watermark", &self.watermark)
+            .field("attach_source", &self.attach_source)
+            .field("attach_front_matter", &self.attach_front_matter)
+            .field("attachments", &self.attachments)
+            .field("signature_fields", &self.signature_fields)
+            .field("conformance", &self.conformance)
+            .field("output_intent", &self.output_intent)
+            .field("image_policy", &self.image_policy)
+            .field("deterministic", &self.deterministic)
+            .field("chunked_rendering", &self.chunked_rendering)
+            .field("generation_threads", &self.generation_threads)
+            .field("processing_mode", &self.processing_mode)
+            .field("fail_fast", &self.fail_fast)
+            .field("source_ordering", &self.source_ordering)
+            .field("html_cache", &self.html_cache)
+            .field(
+                "persistent_browser",
+                &self
+                    .persistent_browser
+                    .as_ref()
+                    .map(|_| "PersistentBrowser"),
+            )
+            .field("asset_base", &self.asset_base)
+            .field("stylesheet", &self.stylesheet)
+            .field("locked_file_policy", &self.locked_file_policy)
+            .field("post_process_command", &self.post_process_command)
+            .field("uploader", &self.uploader.as_ref().map(|_| "Uploader"))
+            .field("env_var_allow_list", &self.env_var_allow_list)
+            .field("date_options", &self.date_options)
+            .field("locale", &self.locale)
+            .field("console_output", &self.console_output)
+            .field(
+                "progress_writer",
+                &self.progress_writer.as_ref().map(|_| "ProgressWriter"),
+            )
+            .field("profile", &self.profile)
+            .field("flags", &self.flags);
+        #[cfg(feature = "timestamp")]
+        debug.field("timestamp_authority", &self.timestamp_authority);
+        debug.finish()
     }
 }
 
+// A composer is shared between threads (web server state, rayon), so it must stay Send + Sync
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<PDFComposerStruct>();
+};
+
 // trait for PDFComposer is defined in the base workspace because of cross-crate traits and type rules
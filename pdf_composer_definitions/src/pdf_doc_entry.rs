@@ -18,3 +18,24 @@ impl<'a> fmt::Debug for PDFDocInfoEntry<'a> {
             .finish()
     }
 }
+
+/// Enum representing how a `Creator` or `Producer` name set on the composer is written into the
+/// document information (and the generator of HTML and EPUB documents)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DocInfoBranding {
+    /// Write the name instead of `PDF Composer`
+    Replace(String),
+    /// Write the name after `PDF Composer`, such as `PDF Composer; Acme Reports`
+    Append(String),
+}
+
+impl DocInfoBranding {
+    /// Returns the name written into the document information, replacing or following
+    /// `default_name`.
+    pub fn apply(&self, default_name: &str) -> String {
+        match self {
+            DocInfoBranding::Replace(name) => name.clone(),
+            DocInfoBranding::Append(name) => format!("{}; {}", default_name, name),
+        }
+    }
+}
@@ -1,21 +1,236 @@
+use std::cmp::Ordering;
 use std::fmt;
+use std::str::FromStr;
 
 /// Enum to represent different versions of the PDF specification.
 /// See <https://pdfa.org/resource/pdf-specification-archive/> for more information on the PDF specifications
-#[derive(Clone, Copy, Debug)]
+///
+/// The versions compare in the order they were published. `PDFVersion::Auto` is not a version of its
+/// own, so it compares with none of them (only with itself): resolve it first (see `resolve`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PDFVersion {
+    /// Represents PDF version 1.4 (Acrobat 5), still demanded by some archival and government systems.
+    V1_4,
+    /// Represents PDF version 1.5 (Acrobat 6), the first with compressed cross-reference streams.
+    V1_5,
+    /// Represents PDF version 1.6 (Acrobat 7).
+    V1_6,
     /// Represents PDF version 1.7.
     V1_7,
     /// Represents PDF version 2.0.
     V2_0,
+    /// The lowest version that can express all the features the document uses (see `PDFFeature`).
+    Auto,
+}
+
+/// Enum representing the features of a generated PDF that need a minimum PDF version
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PDFFeature {
+    /// A tagged (accessible) PDF, marked as such in the catalog (`/MarkInfo`)
+    TaggedPdf,
+    /// The natural language of the document in the catalog (`/Lang`)
+    DocumentLanguage,
+    /// Page numbers as page labels (`/PageLabels`)
+    PageLabels,
+    /// Right-to-left paging in the viewer (`/Direction` viewer preference)
+    RightToLeftPaging,
+    /// PDF/X-4 conformance (`Conformance::PdfX4`), which is based on PDF 1.6
+    PdfX4,
+    /// Compressed cross-reference streams, written instead of a cross-reference table when the
+    /// version allows them
+    CrossReferenceStreams,
+    /// The associated files of the catalog and file specifications (`/AF`, `/AFRelationship`),
+    /// written for attachments when the version allows them
+    AssociatedFiles,
+    /// A trusted document timestamp (`/DocTimeStamp` signature, `ETSI.RFC3161`), from the time
+    /// stamping authority of the composer
+    DocumentTimestamp,
+}
+
+impl PDFFeature {
+    /// Returns the first PDF version that can express the feature
+    pub fn minimum_version(&self) -> PDFVersion {
+        match self {
+            PDFFeature::TaggedPdf | PDFFeature::DocumentLanguage => PDFVersion::V1_4,
+            // Page labels and viewer preferences came with PDF 1.3, older than any version written
+            PDFFeature::PageLabels | PDFFeature::RightToLeftPaging => PDFVersion::V1_4,
+            PDFFeature::PdfX4 => PDFVersion::V1_6,
+            PDFFeature::CrossReferenceStreams => PDFVersion::V1_5,
+            PDFFeature::AssociatedFiles => PDFVersion::V2_0,
+            PDFFeature::DocumentTimestamp => PDFVersion::V2_0,
+        }
+    }
+
+    /// Returns the last PDF version the feature allows (`None` if later versions are fine)
+    pub fn maximum_version(&self) -> Option<PDFVersion> {
+        match self {
+            PDFFeature::PdfX4 => Some(PDFVersion::V1_6),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the feature can be expressed in `pdf_version` (never for `PDFVersion::Auto`)
+    pub fn allows(&self, pdf_version: PDFVersion) -> bool {
+        pdf_version >= self.minimum_version()
+            && self
+                .maximum_version()
+                .is_none_or(|maximum_version| pdf_version <= maximum_version)
+    }
+
+    /// Describes the PDF versions the feature allows (`PDF 1.6`, `PDF 1.4 or later`)
+    fn allowed_versions(&self) -> String {
+        match self.maximum_version() {
+            Some(maximum_version) if maximum_version == self.minimum_version() => {
+                format!("PDF {}", maximum_version)
+            }
+            Some(maximum_version) => {
+                format!("PDF {} to {}", self.minimum_version(), maximum_version)
+            }
+            None => format!("PDF {} or later", self.minimum_version()),
+        }
+    }
+}
+
+/// Implements fmt::Display for PDFFeature, describing the feature.
+impl fmt::Display for PDFFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PDFFeature::TaggedPdf => write!(f, "tagged PDF"),
+            PDFFeature::DocumentLanguage => write!(f, "document language"),
+            PDFFeature::PageLabels => write!(f, "page labels"),
+            PDFFeature::RightToLeftPaging => write!(f, "right-to-left paging"),
+            PDFFeature::PdfX4 => write!(f, "PDF/X-4"),
+            PDFFeature::CrossReferenceStreams => write!(f, "cross-reference streams"),
+            PDFFeature::AssociatedFiles => write!(f, "associated files"),
+            PDFFeature::DocumentTimestamp => write!(f, "document timestamp"),
+        }
+    }
+}
+
+/// Implements PartialOrd for PDFVersion, ordering the versions by when they were published and
+/// leaving `PDFVersion::Auto` unordered.
+impl PartialOrd for PDFVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self.published_order(), other.published_order()) {
+            (Some(order), Some(other_order)) => Some(order.cmp(&other_order)),
+            _ if self == other => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+impl PDFVersion {
+    /// Returns the position of the version in the order the versions were published (`None` for
+    /// `PDFVersion::Auto`)
+    fn published_order(&self) -> Option<u8> {
+        match self {
+            PDFVersion::V1_4 => Some(0),
+            PDFVersion::V1_5 => Some(1),
+            PDFVersion::V1_6 => Some(2),
+            PDFVersion::V1_7 => Some(3),
+            PDFVersion::V2_0 => Some(4),
+            PDFVersion::Auto => None,
+        }
+    }
+
+    /// Returns whether the version allows cross-reference streams (PDF 1.5 and later, and never
+    /// `PDFVersion::Auto`, which is resolved first)
+    pub fn supports_cross_reference_streams(&self) -> bool {
+        PDFFeature::CrossReferenceStreams.allows(*self)
+    }
+
+    /// Returns whether the version allows associated files (`/AF`, PDF 2.0)
+    pub fn supports_associated_files(&self) -> bool {
+        PDFFeature::AssociatedFiles.allows(*self)
+    }
+
+    /// Returns the version to write a document using `features` with: the lowest version that can
+    /// express them all for `PDFVersion::Auto`, otherwise the version itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the feature (and the versions it allows) if an explicit version cannot
+    /// express one of the features, or if no version can express them all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use pdf_composer_definitions::pdf_version::{PDFFeature, PDFVersion};
+    ///
+    /// assert_eq!(PDFVersion::Auto.resolve(&[]), Ok(PDFVersion::V1_4));
+    /// assert_eq!(
+    ///     PDFVersion::Auto.resolve(&[PDFFeature::TaggedPdf, PDFFeature::PdfX4]),
+    ///     Ok(PDFVersion::V1_6)
+    /// );
+    /// assert!(PDFVersion::V1_7.resolve(&[PDFFeature::PdfX4]).is_err());
+    ///
+    /// // Auto is not a version, so it is neither before nor after any of them
+    /// assert!(!(PDFVersion::Auto > PDFVersion::V2_0));
+    /// assert!(!PDFVersion::Auto.supports_cross_reference_streams());
+    /// ```
+    pub fn resolve(&self, features: &[PDFFeature]) -> Result<PDFVersion, String> {
+        let pdf_version = match self {
+            PDFVersion::Auto => features.iter().map(PDFFeature::minimum_version).fold(
+                PDFVersion::V1_4,
+                |highest, minimum_version| {
+                    if minimum_version > highest {
+                        minimum_version
+                    } else {
+                        highest
+                    }
+                },
+            ),
+            pdf_version => *pdf_version,
+        };
+        match features.iter().find(|feature| !feature.allows(pdf_version)) {
+            Some(feature) if *self == PDFVersion::Auto => Err(format!(
+                "no PDF version can express all of {} ({} needs {})",
+                features
+                    .iter()
+                    .map(PDFFeature::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                feature,
+                feature.allowed_versions()
+            )),
+            Some(feature) => Err(format!(
+                "PDF {} cannot express {} ({} is needed)",
+                self,
+                feature,
+                feature.allowed_versions()
+            )),
+            None => Ok(pdf_version),
+        }
+    }
 }
 
 /// Implements fmt:Display trait (warning in rust 1.78.0) for PDFVersion, converting enum variants to their corresponding String representations.
 impl fmt::Display for PDFVersion {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         match self {
+            PDFVersion::V1_4 => write!(f, "1.4"),
+            PDFVersion::V1_5 => write!(f, "1.5"),
+            PDFVersion::V1_6 => write!(f, "1.6"),
             PDFVersion::V1_7 => write!(f, "1.7"),
             PDFVersion::V2_0 => write!(f, "2.0"),
+            PDFVersion::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl FromStr for PDFVersion {
+    type Err = String;
+
+    /// Parses a version number such as `1.4` or `2.0` (`2` is read as `2.0`), or `auto`
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        match version.trim().to_lowercase().as_str() {
+            "1.4" => Ok(PDFVersion::V1_4),
+            "1.5" => Ok(PDFVersion::V1_5),
+            "1.6" => Ok(PDFVersion::V1_6),
+            "1.7" => Ok(PDFVersion::V1_7),
+            "2.0" | "2" => Ok(PDFVersion::V2_0),
+            "auto" => Ok(PDFVersion::Auto),
+            _ => Err(format!("unknown PDF version {}", version)),
         }
     }
 }
@@ -0,0 +1,11 @@
+use std::any::Any;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a persistent browser is kept alive without being used, unless set otherwise
+pub const DEFAULT_BROWSER_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A warm headless Chromium, shared by the `generate_pdfs` calls of one composer instead of launching Chromium for every PDF.
+/// The browser and its lifecycle (launching it and shutting it down when idle) belong to `pdf_composer_base`, so the composer
+/// only holds this opaque handle to it (created by `PDFComposer::with_persistent_browser`)
+pub type PersistentBrowserHandle = Arc<dyn Any + Send + Sync>;
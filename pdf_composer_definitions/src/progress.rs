@@ -0,0 +1,51 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// The writer progress events are written to, one JSON object per line (see `set_progress_writer`)
+pub type ProgressWriter = Arc<Mutex<dyn Write + Send>>;
+
+/// Enum representing a progress event of a generation run, written as a line of JSON with its
+/// kind in `event` (for example, `{"event":"start","file":"docs/intro.md"}`)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// A source file started generating
+    Start {
+        /// Path to the source document
+        file: String,
+    },
+    /// A source file was generated in every output format
+    Finish {
+        /// Path to the source document
+        file: String,
+        /// Paths to the generated documents, one for each output format
+        outputs: Vec<String>,
+    },
+    /// A source file was skipped (see `DocumentStatus::Skipped`)
+    Skip {
+        /// Path to the source document
+        file: String,
+        /// Why the document was skipped
+        message: String,
+    },
+    /// A source file failed (see `DocumentStatus::Failed`)
+    Error {
+        /// Path to the source document
+        file: String,
+        /// Why the document was not generated
+        message: String,
+    },
+    /// Every source file has been attempted
+    Summary {
+        /// The number of source files generated
+        generated: usize,
+        /// The number of source files skipped
+        skipped: usize,
+        /// The number of source files that failed
+        failed: usize,
+    },
+}
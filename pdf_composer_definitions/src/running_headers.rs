@@ -0,0 +1,18 @@
+/// Enum representing the deepest heading level shown in running headers
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RunningHeaderLevel {
+    /// Show the current top level (H1) heading only
+    H1,
+    /// Show the current H1 or H2 heading, whichever started most recently
+    #[default]
+    H2,
+}
+
+/// Struct representing the options for running headers (the current section title at the top of each page)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RunningHeaders {
+    /// The deepest heading level shown in the header
+    pub level: RunningHeaderLevel,
+    /// Leave the header off pages where a top level (H1) heading starts, such as chapter opening pages
+    pub skip_chapter_pages: bool,
+}
@@ -0,0 +1,24 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Struct representing a use of a registered shortcode: `{{< name "positional" key=value >}}`,
+/// or `{{< name >}}inner{{< /name >}}` with a closing tag
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShortcodeInvocation {
+    /// The name of the shortcode
+    pub name: String,
+    /// The arguments without a name, in order (quotes removed)
+    pub positional: Vec<String>,
+    /// The `key=value` arguments (quotes removed)
+    pub named: BTreeMap<String, String>,
+    /// The markdown between the opening and closing tags, as written (`None` without a closing tag)
+    pub inner: Option<String>,
+}
+
+/// A function rendering a registered shortcode to HTML, or returning an error message
+/// (the shortcode is then left as written, with a warning)
+pub type ShortcodeHandler =
+    Arc<dyn Fn(&ShortcodeInvocation) -> Result<String, String> + Send + Sync>;
@@ -0,0 +1,15 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// Struct representing an unsigned signature field added to the generated PDFs, for a signing
+/// service (or signer of your own) to sign in
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureField {
+    /// The name of the field, which signing services refer to it by (without periods)
+    pub name: String,
+    /// The page the field is on (starting at `1`)
+    pub page_number: u32,
+    /// The rectangle of the field on the page, in points from the bottom left corner of the
+    /// page: left, bottom, right, top
+    pub rect: [f32; 4],
+}
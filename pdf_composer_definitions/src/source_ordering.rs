@@ -0,0 +1,11 @@
+/// Enum representing the order the source files are generated in, which is also the order of the chapters in merge mode
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SourceOrdering {
+    /// The order the source files were added in
+    #[default]
+    AsAdded,
+    /// Alphabetical order of the source file paths, ignoring case
+    Alphabetical,
+    /// The order of a YAML Front Matter value (such as `weight: 10`): numbers first, lowest first, then text alphabetically. Documents without the key come last, in the order they were added
+    ByFrontMatterKey(String),
+}
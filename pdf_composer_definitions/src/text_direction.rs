@@ -0,0 +1,25 @@
+use std::fmt;
+
+/// Enum representing the direction text is written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextDirection {
+    /// Left-to-right text (for example, English)
+    Ltr,
+    /// Right-to-left text (for example, Arabic or Hebrew)
+    Rtl,
+}
+
+/// Implements fmt::Display for TextDirection, converting enum variants to their HTML `dir` attribute values.
+impl fmt::Display for TextDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextDirection::Ltr => write!(f, "ltr"),
+            TextDirection::Rtl => write!(f, "rtl"),
+        }
+    }
+}
+
+/// CSS font families appended after the chosen font for right-to-left documents,
+/// as none of the standard 14 PDF fonts have Arabic or Hebrew glyphs
+pub const RTL_FALLBACK_FONTS: &str =
+    "'Noto Naskh Arabic', 'Noto Sans Hebrew', 'Arial Unicode MS', Tahoma, sans-serif";
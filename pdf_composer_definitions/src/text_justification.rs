@@ -0,0 +1,8 @@
+/// Struct representing the options for justified body text
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct JustifyOptions {
+    /// Hyphenate words at line breaks (CSS `hyphens: auto`). Needs a language to be known.
+    pub hyphenate: bool,
+    /// Language used for hyphenation (such as `en-GB`), if no language has been set for the document
+    pub lang: Option<String>,
+}
@@ -0,0 +1,43 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+pub use url::Url;
+
+/// Struct representing what is known about a generated file when it is uploaded
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UploadMetadata {
+    /// Path to the source document the file was generated from (`None` for a merged document)
+    pub source_file: Option<PathBuf>,
+    /// The media type of the file (for example, `application/pdf`)
+    pub content_type: String,
+    /// The document information entries of a generated PDF (for example, `Title` → `My document`), empty for other formats
+    pub info: BTreeMap<String, String>,
+}
+
+/// Trait for publishing the generated files (to object storage, a CMS etc) once they are generated
+pub trait Uploader: Send + Sync {
+    /// Uploads a generated file, returning the URL it was published at
+    fn upload(
+        &self,
+        path: &Path,
+        metadata: &UploadMetadata,
+    ) -> Result<Url, Box<dyn Error + Send + Sync>>;
+}
+
+/// Implement Uploader for functions (and closures) with the signature of `upload`
+impl<F> Uploader for F
+where
+    F: Fn(&Path, &UploadMetadata) -> Result<Url, Box<dyn Error + Send + Sync>> + Send + Sync,
+{
+    fn upload(
+        &self,
+        path: &Path,
+        metadata: &UploadMetadata,
+    ) -> Result<Url, Box<dyn Error + Send + Sync>> {
+        self(path, metadata)
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/// Enum representing how pages are laid out when the document is opened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageLayout {
+    /// One page at a time
+    SinglePage,
+    /// The pages in one continuous column
+    OneColumn,
+    /// The pages in two continuous columns, odd pages on the left
+    TwoColumnLeft,
+    /// The pages in two continuous columns, odd pages on the right (a booklet with a cover)
+    TwoColumnRight,
+    /// Two pages at a time, odd pages on the left
+    TwoPageLeft,
+    /// Two pages at a time, odd pages on the right (a booklet with a cover)
+    TwoPageRight,
+}
+
+impl PageLayout {
+    /// Returns the `/PageLayout` name of the layout in the document catalog
+    pub fn pdf_name(&self) -> &'static str {
+        match self {
+            PageLayout::SinglePage => "SinglePage",
+            PageLayout::OneColumn => "OneColumn",
+            PageLayout::TwoColumnLeft => "TwoColumnLeft",
+            PageLayout::TwoColumnRight => "TwoColumnRight",
+            PageLayout::TwoPageLeft => "TwoPageLeft",
+            PageLayout::TwoPageRight => "TwoPageRight",
+        }
+    }
+}
+
+/// Enum representing the reading direction of the document, which is also the side it is bound on:
+/// two-page layouts put the first page of a spread on the left (`LeftToRight`) or on the right
+/// (`RightToLeft`, for Arabic and Hebrew booklets)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadingDirection {
+    /// Read left to right, bound on the left
+    LeftToRight,
+    /// Read right to left, bound on the right
+    RightToLeft,
+}
+
+impl ReadingDirection {
+    /// Returns the `/Direction` name of the direction in the viewer preferences
+    pub fn pdf_name(&self) -> &'static str {
+        match self {
+            ReadingDirection::LeftToRight => "L2R",
+            ReadingDirection::RightToLeft => "R2L",
+        }
+    }
+}
+
+/// Enum representing which panel (if any) is shown next to the pages when the document is opened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PageMode {
+    /// No panel
+    UseNone,
+    /// The bookmarks (document outline) panel
+    UseOutlines,
+    /// The page thumbnails panel
+    UseThumbs,
+    /// Full screen, without menus or panels
+    FullScreen,
+    /// The attachments panel
+    UseAttachments,
+}
+
+impl PageMode {
+    /// Returns the `/PageMode` name of the mode in the document catalog
+    pub fn pdf_name(&self) -> &'static str {
+        match self {
+            PageMode::UseNone => "UseNone",
+            PageMode::UseOutlines => "UseOutlines",
+            PageMode::UseThumbs => "UseThumbs",
+            PageMode::FullScreen => "FullScreen",
+            PageMode::UseAttachments => "UseAttachments",
+        }
+    }
+}
+
+/// Struct representing how PDF viewers show the document when it is opened. Options left at their
+/// defaults (`None` or `false`) are left to the viewer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ViewerPreferences {
+    /// How the pages are laid out (`None` for the viewer's own layout)
+    pub page_layout: Option<PageLayout>,
+    /// Which panel is shown next to the pages (`None` for the viewer's own choice)
+    pub page_mode: Option<PageMode>,
+    /// The reading and binding direction (`None` to follow the text direction, see `set_text_direction`)
+    pub direction: Option<ReadingDirection>,
+    /// Resize the viewer window to fit the first page
+    pub fit: bool,
+    /// Put the viewer window in the centre of the screen
+    pub center_window: bool,
+    /// Hide the toolbar of the viewer
+    pub hide_toolbar: bool,
+    /// Hide the menu bar of the viewer
+    pub hide_menubar: bool,
+    /// Hide the scroll bars, navigation controls and other window controls, leaving only the pages
+    pub hide_window_ui: bool,
+    /// Show the `Title` document information entry in the window title bar, rather than the file name
+    pub display_doc_title: bool,
+}
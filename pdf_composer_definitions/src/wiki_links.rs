@@ -0,0 +1,8 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::sync::Arc;
+
+/// A function resolving the page name of a `[[wiki link]]` (for example, `Other Page`)
+/// to the path or URL the link points to
+pub type WikiLinkResolver = Arc<dyn Fn(&str) -> String + Send + Sync>;
@@ -0,0 +1,103 @@
+/// The `bench` module contains the `bench` subcommand (per-stage timings).
+mod bench;
+/// Re-exports the `run_bench` function for use by `main`.
+pub use bench::run_bench;
+
+/// The `build` module contains the `build` subcommand (PDF generation, from files or stdin).
+mod build;
+/// Re-exports the `run_build` function for use by `main`.
+pub use build::run_build;
+
+/// The `check` module contains the `check` subcommand (source validation).
+mod check;
+/// Re-exports the `run_check` function for use by `main`.
+pub use check::run_check;
+
+/// The `doctor` module contains the `doctor` subcommand (environment preflight).
+mod doctor;
+/// Re-exports the `run_doctor` function for use by `main`.
+pub use doctor::run_doctor;
+
+/// The `init` module contains the `init` subcommand (starter project files).
+mod init;
+/// Re-exports the `run_init` function for use by `main`.
+pub use init::run_init;
+
+/// The `preview` module contains the `preview` subcommand (preview server with live reload).
+#[cfg(feature = "preview")]
+mod preview;
+/// Re-exports the `run_preview` function for use by `main`.
+#[cfg(feature = "preview")]
+pub use preview::run_preview;
+
+/// The `schema` module contains the `schema` subcommand (JSON Schema of the configuration file).
+mod schema;
+/// Re-exports the `run_schema` function for use by `main`.
+pub use schema::run_schema;
+
+/// Exit code used when the command line arguments could not be understood
+pub const EXIT_USAGE: u8 = 2;
+
+/// Exit code used when the configuration file could not be read or has invalid settings
+pub const EXIT_CONFIG: u8 = 3;
+
+/// Prints the command line usage to stdout
+pub fn print_usage() {
+    println!(
+        "PDF Composer {}
+
+Usage: pdf-composer <COMMAND> [OPTIONS] [FILES]...
+
+Commands:
+  bench    Render source documents repeatedly, printing min/median/max timings per stage
+  build    Generate the PDF of each source document (- reads a document from stdin)
+  check    Validate source documents without generating PDFs
+  doctor   Check Chromium, the output directory and the source files are usable
+  init     Create a starter pdf_composer.toml, example document, stylesheet and template
+  preview  Serve a source document as HTML, reloading on changes (`preview` feature)
+  schema   Print the JSON Schema of pdf_composer.toml, for editors to complete and validate it
+  help     Print this message
+
+Options for bench:
+  --iterations <COUNT>      Number of times to render the source documents (default: 5)
+  --output <DIR>            Output directory to write to (default: pdf_composer_pdfs)
+
+Options for build:
+  --config <PATH>           Configuration file to read (default: pdf_composer.toml, if there is one).
+                            Source files given on the command line replace its sources
+  --profile <NAME>          Apply the settings of a profile of the configuration file on top of
+                            its other settings (for example, draft or final)
+  --flag <NAME>             Keep the {{#if NAME}} conditional blocks of the documents (repeatable)
+  --fail-fast               Stop generating at the first source document that fails
+  -q, --quiet               Print only warnings and errors
+  -v, --verbose             Print the settings and the time each source document took as well
+  --progress <FORMAT>       text (the default) prints the progress messages, ndjson writes a JSON
+                            progress event per line to stdout (start, finish, skip or error of
+                            each source document, then a summary)
+  --output <PATH>           Output directory to write to (default: pdf_composer_pdfs), or the PDF
+                            file to write for a single source document (ending in .pdf, or -
+                            for stdout)
+
+Options for check:
+  --doc-info <ENTRY=KEY>    Check that the front matter has KEY for the document info ENTRY (repeatable)
+  --require <KEY>           Report an error if the front matter has no KEY (repeatable)
+
+Options for doctor:
+  --output <DIR>            Output directory to check (default: pdf_composer_pdfs)
+
+Options for init:
+  --force                   Overwrite existing files
+  [DIR]                     Directory to create the files in (default: the current directory)
+
+Options for preview:
+  --address <HOST:PORT>     Address to serve the preview on (default: 127.0.0.1:4000)
+
+Exit status:
+  0  Success (a skipped document is not a failure)
+  1  A document failed (build), or a check failed
+  2  The command line arguments could not be understood
+  3  The configuration file could not be read or has invalid settings (build)
+",
+        env!("CARGO_PKG_VERSION")
+    );
+}
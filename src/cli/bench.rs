@@ -0,0 +1,58 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer::{PDFComposer, PDFComposerStruct};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use super::EXIT_USAGE;
+
+/// The number of iterations when `--iterations` is not given
+const DEFAULT_ITERATIONS: usize = 5;
+
+/// Runs the `bench` subcommand, rendering the source files repeatedly and printing the
+/// min/median/max time of each stage.
+///
+/// # Returns
+///
+/// * `ExitCode::SUCCESS` once the benchmark has run.
+/// * `EXIT_USAGE` if the arguments could not be understood or no source files were given.
+pub fn run_bench(args: Vec<String>) -> ExitCode {
+    let mut composer: PDFComposerStruct = PDFComposer::new();
+    let mut iterations = DEFAULT_ITERATIONS;
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--iterations" => match args.next().and_then(|count| count.parse().ok()) {
+                Some(count) if count > 0 => iterations = count,
+                _ => {
+                    eprintln!("--iterations expects a number greater than 0");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            "--output" => match args.next() {
+                Some(directory) => composer.set_output_directory(Path::new(&directory)),
+                None => {
+                    eprintln!("--output expects a directory");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option for bench: {}", arg);
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    if paths.is_empty() {
+        eprintln!("bench expects one or more source files");
+        return ExitCode::from(EXIT_USAGE);
+    }
+    composer.add_source_files(paths);
+
+    // The report is printed by `bench`
+    composer.bench(iterations);
+    ExitCode::SUCCESS
+}
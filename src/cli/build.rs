@@ -0,0 +1,287 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer::{
+    read_config, ConsoleOutput, GenerationReport, OutputFormat, PDFComposer, PDFComposerStruct,
+    CONFIG_FILE_NAME,
+};
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, ExitCode};
+
+use super::{EXIT_CONFIG, EXIT_USAGE};
+
+/// The source file argument that reads the document from stdin (and the `--output` that writes the PDF to stdout)
+const STDIN_SOURCE: &str = "-";
+
+/// The name of a document read from stdin when `--output` does not name the PDF
+const STDIN_DOCUMENT_NAME: &str = "stdin";
+
+/// The `--progress` format writing the progress events to stdout, one JSON object per line
+const PROGRESS_NDJSON: &str = "ndjson";
+
+/// The `--progress` format printing the progress messages (the default)
+const PROGRESS_TEXT: &str = "text";
+
+/// Runs the `build` subcommand, generating the PDF of every source file (or of the document
+/// piped to stdin, with `-`). With `--output -`, the PDF of a single source is written to stdout
+/// instead of a file (the other output formats are not generated), and nothing else is printed to
+/// stdout.
+///
+/// The settings and sources of `pdf_composer.toml` (or of the `--config` file) are used, with
+/// source files given on the command line replacing its sources, and the settings of its
+/// `--profile` table on top. A source that fails does not
+/// stop the others (unless `--fail-fast`), and the failures are summarised at the end. With
+/// `--progress ndjson`, a progress event of each source is written to stdout as a line of JSON
+/// instead of the progress messages.
+///
+/// # Returns
+///
+/// * `ExitCode::SUCCESS` if no source failed (sources may have been skipped, such as for missing
+///   required front matter keys).
+/// * `ExitCode::FAILURE` if any source failed (file not found, invalid front matter, a build error).
+/// * `EXIT_USAGE` if the arguments could not be understood, no source files were given or, with
+///   `--output -`, the output formats do not include PDF.
+/// * `EXIT_CONFIG` if the configuration file could not be read, has invalid settings or has no
+///   profile of the `--profile` name.
+pub fn run_build(args: Vec<String>) -> ExitCode {
+    let mut composer: PDFComposerStruct = PDFComposer::new();
+    let mut output: Option<PathBuf> = None;
+    let mut config_file: Option<PathBuf> = None;
+    let mut profile: Option<String> = None;
+    let mut progress_ndjson = false;
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => match args.next() {
+                Some(path) => output = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("--output expects a directory or PDF file");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            "--config" => match args.next() {
+                Some(path) => config_file = Some(PathBuf::from(path)),
+                None => {
+                    eprintln!("--config expects a configuration file");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            "--flag" => match args.next() {
+                Some(flag) => composer.add_flag(&flag),
+                None => {
+                    eprintln!("--flag expects the name of a flag");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            "--profile" => match args.next() {
+                Some(name) => profile = Some(name),
+                None => {
+                    eprintln!("--profile expects the name of a profile of the configuration file");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            "--fail-fast" => composer.set_fail_fast(true),
+            "--quiet" | "-q" => composer.set_console_output(ConsoleOutput::Quiet),
+            "--verbose" | "-v" => composer.set_console_output(ConsoleOutput::Verbose),
+            "--progress" => match args.next().as_deref() {
+                Some(PROGRESS_NDJSON) => progress_ndjson = true,
+                Some(PROGRESS_TEXT) => progress_ndjson = false,
+                _ => {
+                    eprintln!("--progress expects text or ndjson");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option for build: {}", arg);
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    // The configuration file of the current directory is read unless another one is given
+    let config_file =
+        config_file.or_else(|| Some(PathBuf::from(CONFIG_FILE_NAME)).filter(|path| path.is_file()));
+    if let Some(profile) = &profile {
+        if config_file.is_none() {
+            eprintln!(
+                "Profile {} not applied: there is no configuration file ({} or --config)",
+                profile, CONFIG_FILE_NAME
+            );
+            return ExitCode::from(EXIT_CONFIG);
+        }
+        composer.set_profile(profile);
+    }
+    if let Some(config_file) = &config_file {
+        let applied = read_config(config_file).and_then(|mut config| {
+            if !paths.is_empty() {
+                config.sources.clear();
+            }
+            paths.extend(
+                config
+                    .sources
+                    .drain(..)
+                    .map(|source| config_file.parent().unwrap_or(Path::new("")).join(source)),
+            );
+            composer.apply_config(&config, config_file.parent().unwrap_or(Path::new("")))
+        });
+        if let Err(error) = applied {
+            eprintln!(
+                "Configuration {} not read: {}",
+                config_file.display(),
+                error
+            );
+            return ExitCode::from(EXIT_CONFIG);
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("build expects one or more source files, or - to read from stdin");
+        return ExitCode::from(EXIT_USAGE);
+    }
+    let reads_stdin = paths.iter().any(|path| path == Path::new(STDIN_SOURCE));
+    if reads_stdin && paths.len() > 1 {
+        eprintln!("build reads - (stdin) on its own, without other source files");
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    let writes_stdout = output
+        .as_ref()
+        .is_some_and(|path| path == Path::new(STDIN_SOURCE));
+    if writes_stdout && progress_ndjson {
+        eprintln!(
+            "--output - writes the PDF to stdout, so it cannot be used with --progress ndjson"
+        );
+        return ExitCode::from(EXIT_USAGE);
+    }
+    if progress_ndjson {
+        // Only the progress events reach stdout
+        composer.set_quiet(true);
+        composer.set_progress_writer(io::stdout());
+    }
+    if writes_stdout {
+        if paths.len() > 1 {
+            eprintln!(
+                "--output - writes a single PDF to stdout, so build expects a single source file"
+            );
+            return ExitCode::from(EXIT_USAGE);
+        }
+        if !composer.output_formats().contains(&OutputFormat::Pdf) {
+            eprintln!(
+                "--output - writes the PDF to stdout, but the output formats do not include PDF"
+            );
+            return ExitCode::from(EXIT_USAGE);
+        }
+        // Only the PDF is generated, in a directory of its own, and only its bytes reach stdout
+        composer.set_output_format(OutputFormat::Pdf);
+        composer.set_quiet(true);
+        output = Some(env::temp_dir().join(format!("pdf-composer-{}", process::id())));
+    }
+
+    // `--output report.pdf` names the PDF of a single source, anything else is a directory
+    let pdf_file = output.as_ref().filter(|path| {
+        path.extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+    });
+    if pdf_file.is_some() && paths.len() > 1 {
+        eprintln!("--output names a PDF file, so build expects a single source file");
+        return ExitCode::from(EXIT_USAGE);
+    }
+    match (pdf_file, &output) {
+        (Some(pdf_file), _) => composer.set_output_directory(
+            pdf_file
+                .parent()
+                .filter(|directory| !directory.as_os_str().is_empty())
+                .unwrap_or(Path::new(".")),
+        ),
+        (None, Some(directory)) => composer.set_output_directory(directory.as_path()),
+        (None, None) => {}
+    }
+
+    let report = if reads_stdin {
+        let mut source = String::new();
+        if let Err(error) = io::stdin().read_to_string(&mut source) {
+            eprintln!("Could not read the document from stdin: {}", error);
+            return ExitCode::FAILURE;
+        }
+        // The document is named after the PDF asked for, so it needs no renaming
+        let name = pdf_file
+            .and_then(|pdf_file| pdf_file.file_stem())
+            .map_or(STDIN_DOCUMENT_NAME.to_string(), |file_stem| {
+                file_stem.to_string_lossy().to_string()
+            });
+        composer.generate_from_markdown(&source, &name)
+    } else {
+        composer.add_source_files(paths);
+        composer.generate_pdfs()
+    };
+
+    if writes_stdout {
+        return write_to_stdout(&report, output.as_deref().unwrap_or(Path::new("")));
+    }
+
+    if let (Some(pdf_file), Some(generated_file)) = (pdf_file, generated_pdf(&report)) {
+        if generated_file.file_name() != pdf_file.file_name() {
+            if let Err(error) = fs::rename(generated_file, pdf_file) {
+                eprintln!(
+                    "Could not move {} to {}: {}",
+                    generated_file.display(),
+                    pdf_file.display(),
+                    error
+                );
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if report.failed_count() == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Writes the PDF generated for the first document of a report to stdout, removing the
+/// directory it was generated in (a front matter `output_dir` is always inside it).
+fn write_to_stdout(report: &GenerationReport, directory: &Path) -> ExitCode {
+    let pdf = generated_pdf(report).map(fs::read);
+    let _ = fs::remove_dir_all(directory);
+
+    let pdf = match pdf {
+        Some(Ok(pdf)) => pdf,
+        Some(Err(error)) => {
+            eprintln!("Could not read the generated PDF: {}", error);
+            return ExitCode::FAILURE;
+        }
+        None => {
+            eprintln!("No PDF was generated to write to stdout");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut stdout = io::stdout().lock();
+    if let Err(error) = stdout.write_all(&pdf).and_then(|()| stdout.flush()) {
+        eprintln!("Could not write the PDF to stdout: {}", error);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Returns the PDF generated for the first document of a report, if any (the first output format
+/// may not be PDF).
+fn generated_pdf(report: &GenerationReport) -> Option<&PathBuf> {
+    let document = report.documents.first()?;
+    document
+        .output_file
+        .iter()
+        .chain(&document.additional_output_files)
+        .find(|output_file| {
+            output_file.extension().is_some_and(|extension| {
+                extension.eq_ignore_ascii_case(OutputFormat::Pdf.extension())
+            })
+        })
+}
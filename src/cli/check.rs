@@ -0,0 +1,105 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer::{DiagnosticSeverity, PDFComposer, PDFComposerStruct, PDFDocInfoEntry};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::EXIT_USAGE;
+
+/// Runs the `check` subcommand, printing the diagnostics for every source file.
+///
+/// # Returns
+///
+/// * `ExitCode::SUCCESS` if no errors were found (warnings are allowed).
+/// * `ExitCode::FAILURE` if any source file has an error.
+/// * `EXIT_USAGE` if the arguments could not be understood.
+pub fn run_check(args: Vec<String>) -> ExitCode {
+    let mut composer: PDFComposerStruct = PDFComposer::new();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let mut doc_info_pairs: Vec<(String, String)> = Vec::new();
+    let mut required_keys: Vec<String> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--doc-info" => match args.next().as_deref().and_then(|pair| pair.split_once('=')) {
+                Some((entry, key)) => doc_info_pairs.push((entry.to_string(), key.to_string())),
+                None => {
+                    eprintln!("--doc-info expects a value in the form ENTRY=KEY");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            "--require" => match args.next() {
+                Some(key) => required_keys.push(key),
+                None => {
+                    eprintln!("--require expects a front matter key");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option for check: {}", arg);
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("No source files given to check");
+        return ExitCode::from(EXIT_USAGE);
+    }
+
+    composer.add_source_files(paths);
+    for (entry, key) in &doc_info_pairs {
+        composer.set_doc_info_entry(PDFDocInfoEntry {
+            doc_info_entry: entry,
+            yaml_entry: key,
+        });
+    }
+
+    let required_keys: Vec<&str> = required_keys.iter().map(String::as_str).collect();
+    composer.require_front_matter_keys(&required_keys);
+
+    let reports = composer.validate_sources();
+    let mut error_count = 0;
+    let mut warning_count = 0;
+
+    for report in &reports {
+        if report.is_clean() {
+            println!("ok       {}", report.source_file.display());
+            continue;
+        }
+        for diagnostic in &report.diagnostics {
+            let label = match diagnostic.severity() {
+                DiagnosticSeverity::Error => {
+                    error_count += 1;
+                    "error"
+                }
+                DiagnosticSeverity::Warning => {
+                    warning_count += 1;
+                    "warning"
+                }
+            };
+            println!(
+                "{:<8} {}: {}",
+                label,
+                report.source_file.display(),
+                diagnostic
+            );
+        }
+    }
+
+    println!(
+        "\n{} file(s) checked: {} error(s), {} warning(s)",
+        reports.len(),
+        error_count,
+        warning_count
+    );
+
+    if error_count > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
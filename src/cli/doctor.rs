@@ -0,0 +1,55 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer::{PDFComposer, PDFComposerStruct};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use super::EXIT_USAGE;
+
+/// Runs the `doctor` subcommand, printing the result of each environment check.
+///
+/// # Returns
+///
+/// * `ExitCode::SUCCESS` if no check failed (warnings are allowed).
+/// * `ExitCode::FAILURE` if any check failed.
+/// * `EXIT_USAGE` if the arguments could not be understood.
+pub fn run_doctor(args: Vec<String>) -> ExitCode {
+    let mut composer: PDFComposerStruct = PDFComposer::new();
+    let mut paths: Vec<PathBuf> = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => match args.next() {
+                Some(directory) => composer.set_output_directory(Path::new(&directory)),
+                None => {
+                    eprintln!("--output expects a directory");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option for doctor: {}", arg);
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    composer.add_source_files(paths);
+
+    let report = composer.doctor();
+    for check in &report.checks {
+        println!(
+            "[{:<4}] {}: {}",
+            check.status.to_string(),
+            check.name,
+            check.detail
+        );
+    }
+
+    if report.is_ok() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer::CONFIG_FILE_NAME;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use super::EXIT_USAGE;
+
+/// The files of a new project (path relative to the project directory, content)
+const STARTER_FILES: [(&str, &str); 4] = [
+    (CONFIG_FILE_NAME, include_str!("init/pdf_composer.toml")),
+    ("docs/example.md", include_str!("init/docs/example.md")),
+    ("styles/print.css", include_str!("init/styles/print.css")),
+    (
+        "templates/letter.md",
+        include_str!("init/templates/letter.md"),
+    ),
+];
+
+/// Runs the `init` subcommand, writing a starter configuration file, example document, stylesheet
+/// and mail merge template into a directory (the current directory by default).
+///
+/// # Returns
+///
+/// * `ExitCode::SUCCESS` if every file was written.
+/// * `ExitCode::FAILURE` if a file already exists (without `--force`) or could not be written.
+/// * `EXIT_USAGE` if the arguments could not be understood.
+pub fn run_init(args: Vec<String>) -> ExitCode {
+    let mut directory: Option<PathBuf> = None;
+    let mut force = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "--force" => force = true,
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option for init: {}", arg);
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ if directory.is_some() => {
+                eprintln!("init expects a single directory");
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ => directory = Some(PathBuf::from(arg)),
+        }
+    }
+    let directory = directory.unwrap_or(PathBuf::from("."));
+
+    // Nothing is written if any of the files would be overwritten
+    if !force {
+        let existing_files: Vec<PathBuf> = STARTER_FILES
+            .iter()
+            .map(|(path, _)| directory.join(path))
+            .filter(|path| path.exists())
+            .collect();
+        if !existing_files.is_empty() {
+            for existing_file in &existing_files {
+                eprintln!("{} already exists", existing_file.display());
+            }
+            eprintln!("No files were written (run init with --force to overwrite them)");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    for (path, content) in STARTER_FILES {
+        let file = directory.join(path);
+        let written = file
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&file, content));
+        if let Err(error) = written {
+            eprintln!("Could not write {}: {}", file.display(), error);
+            return ExitCode::FAILURE;
+        }
+        println!("Created {}", file.display());
+    }
+
+    let build_command = if directory == Path::new(".") {
+        "pdf-composer build".to_string()
+    } else {
+        format!("cd {} && pdf-composer build", directory.display())
+    };
+    println!("\nRun `{}` to generate the example PDF", build_command);
+    ExitCode::SUCCESS
+}
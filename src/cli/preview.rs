@@ -0,0 +1,60 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer::{PDFComposer, PDFComposerStruct};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use super::EXIT_USAGE;
+
+/// The address the preview server listens on when `--address` is not given.
+const DEFAULT_ADDRESS: &str = "127.0.0.1:4000";
+
+/// Runs the `preview` subcommand, serving a source document until the process is stopped.
+///
+/// # Returns
+///
+/// * `ExitCode::FAILURE` if the server could not be started.
+/// * `EXIT_USAGE` if the arguments could not be understood.
+pub fn run_preview(args: Vec<String>) -> ExitCode {
+    let mut address = DEFAULT_ADDRESS.to_string();
+    let mut source: Option<PathBuf> = None;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--address" => match args.next() {
+                Some(value) => address = value,
+                None => {
+                    eprintln!("--address expects HOST:PORT");
+                    return ExitCode::from(EXIT_USAGE);
+                }
+            },
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option for preview: {}", arg);
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ if source.is_some() => {
+                eprintln!("preview expects a single source document");
+                return ExitCode::from(EXIT_USAGE);
+            }
+            _ => source = Some(PathBuf::from(arg)),
+        }
+    }
+    let Some(source) = source else {
+        eprintln!("preview expects a source document");
+        return ExitCode::from(EXIT_USAGE);
+    };
+
+    let composer: PDFComposerStruct = PDFComposer::new();
+    match composer.preview(&source, &address) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!(
+                "Could not start the preview server on {}: {}",
+                address, error
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
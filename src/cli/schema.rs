@@ -0,0 +1,23 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use pdf_composer::config_schema;
+use std::process::ExitCode;
+
+use super::EXIT_USAGE;
+
+/// Runs the `schema` subcommand, printing the JSON Schema of the configuration file (the
+/// `pdf_composer.schema.json` shipped with the crate is written with it).
+///
+/// # Returns
+///
+/// * `ExitCode::SUCCESS` once the schema is printed.
+/// * `EXIT_USAGE` if any arguments were given.
+pub fn run_schema(args: Vec<String>) -> ExitCode {
+    if let Some(arg) = args.first() {
+        eprintln!("Unknown option for schema: {}", arg);
+        return ExitCode::from(EXIT_USAGE);
+    }
+    println!("{}", config_schema());
+    ExitCode::SUCCESS
+}
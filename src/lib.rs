@@ -9,10 +9,64 @@
 #![crate_name = "pdf_composer"]
 #![crate_type = "lib"]
 
-pub use pdf_composer_base::PDFComposer;
+#[cfg(feature = "s3")]
+pub use pdf_composer_base::S3Uploader;
+pub use pdf_composer_base::{
+    add_named_destinations, compare_pdfs, config_schema, extract_pages, merge_pdfs, read_config,
+    read_pdf_metadata, validate_config, PDFComposer,
+};
+pub use pdf_composer_definitions::asset_base::PathOrUrl;
+pub use pdf_composer_definitions::attachments::FileAttachment;
+pub use pdf_composer_definitions::bench::{BenchReport, StageTimings};
+pub use pdf_composer_definitions::code_wrap::CodeWrap;
+pub use pdf_composer_definitions::compare::{
+    CompareOptions, PageComparison, PdfComparison, StructuralDifference,
+};
+pub use pdf_composer_definitions::config::{
+    ConfigDiagnostic, PDFComposerConfig, CONFIG_FILE_NAME, CONFIG_SCHEMA_URL,
+    DIRECTORY_CONFIG_FILE_NAME,
+};
+pub use pdf_composer_definitions::configuration_summary::ConfigurationSummary;
+pub use pdf_composer_definitions::conformance::Conformance;
+pub use pdf_composer_definitions::console_output::ConsoleOutput;
 pub use pdf_composer_definitions::consts::PACKAGE_NAME;
+pub use pdf_composer_definitions::date_options::{DateLocale, DateOptions};
+pub use pdf_composer_definitions::destinations::NamedDestination;
+pub use pdf_composer_definitions::diagnostics::{
+    DiagnosticKind, DiagnosticSeverity, SourceDiagnostics,
+};
+pub use pdf_composer_definitions::doctor::{CheckStatus, DoctorCheck, DoctorReport};
+pub use pdf_composer_definitions::document_metadata::DocumentMetadata;
 pub use pdf_composer_definitions::fonts::FontsStandard;
+pub use pdf_composer_definitions::front_matter::{MissingKeysPolicy, PlaceholderPolicy};
+pub use pdf_composer_definitions::generation_report::{
+    DocumentStatus, GeneratedDocument, GenerationReport, MergedChapter, UploadedFile,
+};
+pub use pdf_composer_definitions::generation_threads::{GenerationThreads, ProcessingMode};
+pub use pdf_composer_definitions::html_transform::HtmlTransform;
+pub use pdf_composer_definitions::icc_profile::IccProfile;
+pub use pdf_composer_definitions::image_policy::ImagePolicy;
+pub use pdf_composer_definitions::locale::Locale;
+pub use pdf_composer_definitions::locked_file::LockedFilePolicy;
+pub use pdf_composer_definitions::merge::{MergeMetadataSource, MergeMode, MergeOptions};
+pub use pdf_composer_definitions::open_action::{OpenAction, Zoom};
+pub use pdf_composer_definitions::output_format::{OutputFormat, TextFormat};
+pub use pdf_composer_definitions::page_numbering::{
+    PageLabelRange, PageNumberSection, PageNumberStyle, PageNumbering,
+};
 pub use pdf_composer_definitions::page_properties::{PageMargins, PaperOrientation, PaperSize};
 pub use pdf_composer_definitions::pdf_composer::PDFComposerStruct;
-pub use pdf_composer_definitions::pdf_doc_entry::PDFDocInfoEntry;
-pub use pdf_composer_definitions::pdf_version::PDFVersion;
+pub use pdf_composer_definitions::pdf_doc_entry::{DocInfoBranding, PDFDocInfoEntry};
+pub use pdf_composer_definitions::pdf_version::{PDFFeature, PDFVersion};
+pub use pdf_composer_definitions::progress::{ProgressEvent, ProgressWriter};
+pub use pdf_composer_definitions::running_headers::{RunningHeaderLevel, RunningHeaders};
+pub use pdf_composer_definitions::shortcodes::{ShortcodeHandler, ShortcodeInvocation};
+pub use pdf_composer_definitions::signature_fields::SignatureField;
+pub use pdf_composer_definitions::source_ordering::SourceOrdering;
+pub use pdf_composer_definitions::text_direction::TextDirection;
+pub use pdf_composer_definitions::text_justification::JustifyOptions;
+pub use pdf_composer_definitions::upload::{UploadMetadata, Uploader, Url};
+pub use pdf_composer_definitions::viewer_preferences::{
+    PageLayout, PageMode, ReadingDirection, ViewerPreferences,
+};
+pub use pdf_composer_definitions::wiki_links::WikiLinkResolver;
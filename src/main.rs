@@ -0,0 +1,52 @@
+// Copyright © 2024 PDF Composer (pdf_composer). All rights reserved.
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! # PDF Composer command line interface
+//!
+//! `pdf-composer <COMMAND> [OPTIONS] [FILES]...`
+//!
+//! Run `pdf-composer help` for the list of commands.
+
+use std::env;
+use std::process::ExitCode;
+
+/// 'cli' module for the command line subcommands
+mod cli;
+#[cfg(feature = "preview")]
+use cli::run_preview;
+use cli::{
+    print_usage, run_bench, run_build, run_check, run_doctor, run_init, run_schema, EXIT_USAGE,
+};
+
+fn main() -> ExitCode {
+    let mut args = env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("bench") => run_bench(args.collect()),
+        Some("build") => run_build(args.collect()),
+        Some("check") => run_check(args.collect()),
+        Some("doctor") => run_doctor(args.collect()),
+        Some("init") => run_init(args.collect()),
+        #[cfg(feature = "preview")]
+        Some("preview") => run_preview(args.collect()),
+        #[cfg(not(feature = "preview"))]
+        Some("preview") => {
+            eprintln!("The preview command requires the `preview` feature");
+            ExitCode::from(EXIT_USAGE)
+        }
+        Some("schema") => run_schema(args.collect()),
+        Some("help") | Some("--help") | Some("-h") => {
+            print_usage();
+            ExitCode::SUCCESS
+        }
+        Some(command) => {
+            eprintln!("Unknown command: {}\n", command);
+            print_usage();
+            ExitCode::from(EXIT_USAGE)
+        }
+        None => {
+            print_usage();
+            ExitCode::from(EXIT_USAGE)
+        }
+    }
+}